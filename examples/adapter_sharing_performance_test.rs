@@ -0,0 +1,91 @@
+use rat_engine::server::{HyperAdapter, Router};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// 适配器共享性能测试
+///
+/// 模拟高连接周转下接受循环为每个连接准备适配器的开销，对比两种方式：
+/// - 逐连接构建：每个连接 `Arc::new(HyperAdapter::new(router.clone()))`（旧实现）
+/// - 共享适配器：接受循环外构建一次，每个连接只克隆 `Arc`（当前实现）
+///
+/// 通过计数分配器统计每个连接的堆分配次数和字节数。
+
+/// 统计堆分配次数和字节数的分配器
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// 模拟的连接数
+const CONNECTIONS: usize = 100_000;
+
+/// 执行 `prepare` 模拟 `CONNECTIONS` 个连接，返回（每连接分配次数，每连接分配字节数，每连接耗时纳秒）
+fn measure<F: FnMut() -> Arc<HyperAdapter>>(mut prepare: F) -> (f64, f64, f64) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let started = Instant::now();
+    for _ in 0..CONNECTIONS {
+        // 连接处理结束后适配器引用随之释放
+        std::hint::black_box(prepare());
+    }
+    let elapsed = started.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+    (
+        allocations as f64 / CONNECTIONS as f64,
+        bytes as f64 / CONNECTIONS as f64,
+        elapsed.as_nanos() as f64 / CONNECTIONS as f64,
+    )
+}
+
+fn main() {
+    println!("🚀 启动适配器共享性能测试（{} 个连接）", CONNECTIONS);
+
+    let router = Arc::new(Router::new());
+
+    // 旧实现：每个连接构建一个新的适配器
+    let per_connection = measure(|| {
+        let router = router.clone();
+        let cert_manager = router.get_cert_manager();
+        std::hint::black_box(cert_manager);
+        Arc::new(HyperAdapter::new(router.clone()))
+    });
+
+    // 当前实现：适配器和证书管理器只构建一次，每个连接只克隆 Arc
+    let adapter = Arc::new(HyperAdapter::new(router.clone()));
+    let cert_manager = router.get_cert_manager();
+    let shared = measure(|| {
+        std::hint::black_box(router.clone());
+        std::hint::black_box(cert_manager.clone());
+        adapter.clone()
+    });
+
+    println!("📊 逐连接构建: {:.2} 次分配/连接, {:.1} 字节/连接, {:.1} ns/连接", per_connection.0, per_connection.1, per_connection.2);
+    println!("📊 共享适配器: {:.2} 次分配/连接, {:.1} 字节/连接, {:.1} ns/连接", shared.0, shared.1, shared.2);
+
+    // 共享适配器后接受循环不再为适配器分配内存
+    assert_eq!(shared.0, 0.0, "共享适配器时每个连接不应产生堆分配");
+    assert!(per_connection.0 >= 1.0, "逐连接构建时每个连接至少有一次堆分配");
+    println!(
+        "✅ 每个连接减少 {:.2} 次堆分配（{:.1} 字节）",
+        per_connection.0 - shared.0,
+        per_connection.1 - shared.1
+    );
+}
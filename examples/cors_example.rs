@@ -68,7 +68,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
             router.add_route(Method::POST, "/api/echo", |req| {
                 Box::pin(async move {
-                    let body = req.body_as_string().unwrap_or_default();
+                    let body = req.body_text().unwrap_or_default();
                     let message = Message {
                         text: format!("Echo: {}", body),
                         timestamp: chrono::Utc::now().timestamp(),
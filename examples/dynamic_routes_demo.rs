@@ -49,7 +49,7 @@ async fn handle_user_info(req: HttpRequest) -> Result<Response<Full<Bytes>>, rat
 /// 用户资料更新处理器
 async fn handle_user_profile_update(req: HttpRequest) -> Result<Response<Full<Bytes>>, rat_engine::Error> {
     let user_id = req.param_as_i64("id").unwrap_or(0);
-    let body_str = req.body_as_string().unwrap_or_default();
+    let body_str = req.body_text().unwrap_or_default();
 
     let response_data = json!({
         "user_id": user_id,
@@ -8,7 +8,7 @@ use rat_engine::server::{
     global_sse_manager::get_global_sse_manager,
 };
 use rat_engine::RatEngine;
-use rat_engine::{Request, Method, StatusCode, Response};
+use rat_engine::{Request, Method, StatusCode, Response, Json, RatError};
 use rat_engine::{Incoming, Frame, Bytes, Full};
 use std::sync::Arc;
 use std::collections::HashMap;
@@ -165,107 +165,71 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     );
 
     // 登录API端点
-    router.add_route(
+    router.add_fallible_route(
         Method::POST,
         "/api/connect",
-        |req: HttpRequest| {
-            Box::pin(async move {
-                // 解析请求体
-                let body_result = req.body_as_json();
-                if body_result.is_err() {
-                    let error_response = json!({
-                        "status": "error",
-                        "message": "无效的JSON格式"
-                    });
-                    return Ok(Response::builder()
-                        .status(StatusCode::BAD_REQUEST)
-                        .header("Content-Type", "application/json")
-                        .body(Full::new(Bytes::from(error_response.to_string())))
-                        .unwrap());
-                }
-
-                let body = body_result.unwrap();
-
-                // 提取字段
-                let username = body.get("username").and_then(|v| v.as_str()).unwrap_or("").trim();
-                let nickname = body.get("nickname").and_then(|v| v.as_str()).unwrap_or(username).trim();
-                let room_id = body.get("room_id").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
-
-                // 验证输入
-                if username.is_empty() || username.len() < 2 || username.len() > 20 {
-                    let error_response = json!({
-                        "status": "error",
-                        "message": "用户名长度必须在2-20个字符之间"
-                    });
-                    return Ok(Response::builder()
-                        .status(StatusCode::BAD_REQUEST)
-                        .header("Content-Type", "application/json")
-                        .body(Full::new(Bytes::from(error_response.to_string())))
-                        .unwrap());
-                }
+        |req: HttpRequest| async move {
+            let error = |message: &str| (StatusCode::BAD_REQUEST, Json(json!({
+                "status": "error",
+                "message": message
+            })));
+
+            // 解析请求体
+            let Ok(body) = req.body_json() else {
+                return Ok(error("无效的JSON格式"));
+            };
+
+            // 提取字段
+            let username = body.get("username").and_then(|v| v.as_str()).unwrap_or("").trim();
+            let nickname = body.get("nickname").and_then(|v| v.as_str()).unwrap_or(username).trim();
+            let room_id = body.get("room_id").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+            // 验证输入
+            if username.is_empty() || username.len() < 2 || username.len() > 20 {
+                return Ok(error("用户名长度必须在2-20个字符之间"));
+            }
 
-                // 验证房间ID
-                let room_manager = get_room_manager();
-                if !room_manager.is_valid_room(room_id) {
-                    let error_response = json!({
-                        "status": "error",
-                        "message": "无效的房间ID，请选择1-3的房间"
-                    });
-                    return Ok(Response::builder()
-                        .status(StatusCode::BAD_REQUEST)
-                        .header("Content-Type", "application/json")
-                        .body(Full::new(Bytes::from(error_response.to_string())))
-                        .unwrap());
+            // 验证房间ID
+            let room_manager = get_room_manager();
+            if !room_manager.is_valid_room(room_id) {
+                return Ok(error("无效的房间ID，请选择1-3的房间"));
+            }
+            let room = room_manager.get_room(room_id).unwrap();
+
+            // 生成连接UUID
+            let connection_uuid = Uuid::new_v4().to_string();
+
+            // 将用户添加到房间
+            room.add_user(connection_uuid.clone(), UserInfo {
+                username: username.to_string(),
+                nickname: nickname.to_string(),
+                room_id,
+                connection_uuid: connection_uuid.clone(),
+            });
+
+            // 向房间内其他用户广播新用户加入消息
+            let join_message = json!({
+                "type": "user_join",
+                "user": {
+                    "username": username,
+                    "nickname": nickname,
+                    "connection_uuid": connection_uuid
+                },
+                "member_count": room.get_member_count()
+            });
+            broadcast_to_room(room_id, "system", &join_message);
+
+            Ok::<_, RatError>((StatusCode::OK, Json(json!({
+                "status": "success",
+                "message": "登录成功",
+                "data": {
+                    "connection_uuid": connection_uuid,
+                    "username": username,
+                    "nickname": nickname,
+                    "room_id": room_id,
+                    "room_name": room.name
                 }
-
-                // 生成连接UUID
-                let connection_uuid = Uuid::new_v4().to_string();
-
-                // 获取房间信息
-                let room = room_manager.get_room(room_id).unwrap();
-
-                // 创建用户信息
-                let user_info = UserInfo {
-                    username: username.to_string(),
-                    nickname: nickname.to_string(),
-                    room_id,
-                    connection_uuid: connection_uuid.clone(),
-                };
-
-                // 将用户添加到房间
-                room.add_user(connection_uuid.clone(), user_info.clone());
-
-                // 构建成功响应
-                let success_response = json!({
-                    "status": "success",
-                    "message": "登录成功",
-                    "data": {
-                        "connection_uuid": connection_uuid,
-                        "username": username,
-                        "nickname": nickname,
-                        "room_id": room_id,
-                        "room_name": room.name
-                    }
-                });
-
-                // 向房间内其他用户广播新用户加入消息
-                let join_message = json!({
-                    "type": "user_join",
-                    "user": {
-                        "username": username,
-                        "nickname": nickname,
-                        "connection_uuid": connection_uuid
-                    },
-                    "member_count": room.get_member_count()
-                });
-                broadcast_to_room(room_id, "system", &join_message);
-
-                Ok(Response::builder()
-                    .status(StatusCode::OK)
-                    .header("Content-Type", "application/json")
-                    .body(Full::new(Bytes::from(success_response.to_string())))
-                    .unwrap())
-            })
+            }))))
         }
     );
 
@@ -388,7 +352,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 }
 
                 // 解析请求体
-                let body_result = req.body_as_json();
+                let body_result = req.body_json();
                 if body_result.is_err() {
                     let error_response = json!({
                         "status": "error",
@@ -191,7 +191,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         |req: HttpRequest| {
             Box::pin(async move {
                 // 解析请求体
-                let body_result = req.body_as_json();
+                let body_result = req.body_json();
                 if body_result.is_err() {
                     let error_response = json!({
                         "status": "error",
@@ -407,7 +407,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 }
 
                 // 解析请求体
-                let body_result = req.body_as_json();
+                let body_result = req.body_json();
                 if body_result.is_err() {
                     let error_response = json!({
                         "status": "error",
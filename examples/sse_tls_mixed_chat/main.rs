@@ -115,13 +115,9 @@ fn broadcast_to_room(room_id: u32, event_type: &str, data: &Value) {
             "data": data
         });
 
-        // 向房间内所有用户发送消息
-        for (connection_uuid, _) in room.get_members() {
-            if let Err(e) = sse_manager.send_data(&connection_uuid, &message.to_string()) {
-                println!("❌ 向用户 {} 发送消息失败: {}", connection_uuid, e);
-                // 可以考虑移除失效连接
-            }
-        }
+        // 向房间分组内所有已建立 SSE 连接的用户发送消息，失效连接由管理器清理
+        let sent = sse_manager.broadcast_to_group(&room_id.to_string(), &message.to_string());
+        println!("📡 房间 {} 广播 {} 消息，送达 {} 个连接", room_id, event_type, sent);
     }
 }
 
@@ -146,6 +142,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let cert_manager_config = CertManagerConfig::shared(cert_config);
     let cert_manager = CertificateManager::from_config(cert_manager_config)?;
 
+    // 聊天室空闲时定时发送心跳，避免连接被反向代理的空闲超时断开
+    get_global_sse_manager().set_heartbeat_interval(Some(std::time::Duration::from_secs(15)));
+
     // 创建路由器（混合模式，自动检测协议类型）
     let mut router = Router::new();
     // 注意：不调用 enable_http_only()，使用混合模式
@@ -191,7 +190,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         |req: HttpRequest| {
             Box::pin(async move {
                 // 解析请求体
-                let body_result = req.body_as_json();
+                let body_result = req.body_json();
                 if body_result.is_err() {
                     let error_response = json!({
                         "status": "error",
@@ -354,6 +353,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 // 注册SSE连接
                 let sse_manager = get_global_sse_manager();
                 let response = sse_manager.register_connection(connection_uuid.clone())?;
+                sse_manager.join_group(&connection_uuid, &room.id.to_string());
 
                 // 发送欢迎消息
                 let welcome_message = json!({
@@ -407,7 +407,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 }
 
                 // 解析请求体
-                let body_result = req.body_as_json();
+                let body_result = req.body_json();
                 if body_result.is_err() {
                     let error_response = json!({
                         "status": "error",
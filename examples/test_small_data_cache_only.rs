@@ -164,6 +164,9 @@ async fn main() -> Result<()> {
         excluded_content_types: std::collections::HashSet::new(),
         excluded_extensions: std::collections::HashSet::new(),
         enable_smart_compression: true, // 启用智能压缩决策
+        adaptive_threshold: None,
+        brotli_static: None,
+        brotli_dynamic: None,
     };
     router.enable_compression(compression_config);
 
@@ -593,8 +593,13 @@ class RatApp:
         
         return decorator
     
-    def chunk(self, rule: str, methods: Optional[List[str]] = None, **options):
-        """分块传输响应装饰器 - 返回值将被视为分块数据"""
+    def chunk(self, rule: str, methods: Optional[List[str]] = None, content_type: Optional[str] = None, **options):
+        """分块传输响应装饰器 - 返回值将被视为分块数据
+
+        Args:
+            content_type: 生成器响应的 Content-Type；未指定时按第一块推断，
+                str 为 text/plain; charset=utf-8，bytes 为 application/octet-stream
+        """
         if methods is None:
             methods = ['GET']
         
@@ -602,16 +607,17 @@ class RatApp:
             @functools.wraps(func)
             def chunk_wrapper(*args, **kwargs):
                 result = func(*args, **kwargs)
-                # 处理 generator 对象
-                if hasattr(result, '__iter__') and not isinstance(result, (str, bytes)):
-                    # 将 generator 转换为字符串
-                    chunk_data = ''.join(str(chunk) for chunk in result)
-                    return TypedResponse(chunk_data, ResponseType.CHUNK)
-                # 返回TypedResponse对象
-                elif isinstance(result, str):
-                    return TypedResponse(result, ResponseType.CHUNK)
+                # 生成器/迭代器直接返回，由 Rust 端逐块拉取并发送，不在 Python 侧缓冲
+                if hasattr(result, '__next__'):
+                    return result
+                # 列表等可迭代对象按块发送
+                elif hasattr(result, '__iter__') and not isinstance(result, (str, bytes)):
+                    return [str(chunk) for chunk in result]
                 return result
             
+            # 标记为分块路由，用于自动注册为流式路由
+            chunk_wrapper._is_chunk_route = True
+            chunk_wrapper._chunk_content_type = content_type
             self._add_route(rule, chunk_wrapper, methods, _from_decorator=True)
             return chunk_wrapper
         
@@ -1571,6 +1577,10 @@ class RatApp:
                             self._router.add_sse_route(method, route.pattern, route.handler)
                             if debug:
                                 print(f"   📡 SSE Registered route: {method} {route.pattern}")
+                        elif getattr(route.handler, '_is_chunk_route', False):
+                            self._router.add_chunked_route(method, route.pattern, route.handler)
+                            if debug:
+                                print(f"   📦 Chunked Registered route: {method} {route.pattern}")
                         else:
                             # 🔍 [DEBUG] 打印路由注册信息
                             if debug:
@@ -0,0 +1,105 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+"""
+生成器分块响应测试
+使用 RatApp 创建服务器并测试 @app.chunk 生成器响应的 Content-Type 和响应内容：
+- str 生成器推断为 text/plain; charset=utf-8
+- bytes 生成器推断为 application/octet-stream
+- 显式 content_type 优先于推断结果
+"""
+
+import time
+import threading
+import requests
+import sys
+from rat_engine import RatApp
+
+SERVER_HOST = "127.0.0.1"
+SERVER_PORT = 8086
+SERVER_URL = f"http://{SERVER_HOST}:{SERVER_PORT}"
+
+def create_chunked_server():
+    """创建带有生成器分块路由的服务器"""
+    print("🚀 创建生成器分块测试 RatApp...")
+    app = RatApp(name="chunked_generator_test")
+
+    @app.chunk("/text")
+    def handle_text(request_data):
+        for i in range(3):
+            yield f"第 {i} 块\n"
+
+    @app.chunk("/bytes")
+    def handle_bytes(request_data):
+        for i in range(3):
+            yield bytes([i]) * 4
+
+    @app.chunk("/csv", content_type="text/csv; charset=utf-8")
+    def handle_csv(request_data):
+        yield "id,name\n"
+        yield "1,rat\n"
+
+    return app
+
+def check_case(name: str, url: str, expected_content_type: str, expected_body: bytes) -> bool:
+    """测试单个用例"""
+    print(f"\n🧪 测试: {name}")
+    try:
+        response = requests.get(url, timeout=5)
+    except Exception as e:
+        print(f"   ❌ 请求失败: {e}")
+        return False
+
+    content_type = response.headers.get('Content-Type', '')
+    if content_type != expected_content_type:
+        print(f"   ❌ Content-Type 不匹配: 期望 {expected_content_type}, 实际 {content_type}")
+        return False
+    print(f"   ✅ Content-Type 正确: {content_type}")
+
+    if response.content != expected_body:
+        print(f"   ❌ 响应内容不匹配: 期望 {expected_body!r}, 实际 {response.content!r}")
+        return False
+    print(f"   ✅ 响应内容正确: {len(response.content)} 字节")
+    return True
+
+def test_chunked_generator() -> bool:
+    """测试生成器分块响应"""
+    app = create_chunked_server()
+
+    print(f"📡 启动服务器在端口 {SERVER_PORT}...")
+    server_thread = threading.Thread(
+        target=lambda: app.run(host=SERVER_HOST, port=SERVER_PORT),
+        daemon=True,
+    )
+    server_thread.start()
+
+    print("⏳ 等待服务器启动...")
+    time.sleep(3)
+
+    cases = [
+        ("str 生成器", f"{SERVER_URL}/text", "text/plain; charset=utf-8",
+         "".join(f"第 {i} 块\n" for i in range(3)).encode("utf-8")),
+        ("bytes 生成器", f"{SERVER_URL}/bytes", "application/octet-stream",
+         b"".join(bytes([i]) * 4 for i in range(3))),
+        ("显式 content_type", f"{SERVER_URL}/csv", "text/csv; charset=utf-8",
+         b"id,name\n1,rat\n"),
+    ]
+
+    success_count = sum(1 for case in cases if check_case(*case))
+
+    print(f"\n{'='*60}")
+    print(f"🎯 测试完成: {success_count}/{len(cases)} 通过")
+    return success_count == len(cases)
+
+def main():
+    """主函数"""
+    print("🚀 RAT Engine 生成器分块响应测试")
+    print("=" * 50)
+    success = test_chunked_generator()
+    if success:
+        print("\n✅ 所有测试通过！")
+    else:
+        print("\n❌ 部分测试失败，请检查服务器状态")
+    return 0 if success else 1
+
+if __name__ == "__main__":
+    sys.exit(main())
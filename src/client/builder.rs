@@ -0,0 +1,237 @@
+//! RAT Engine HTTP 客户端构建器
+//!
+//! 与 gRPC 客户端构建器不同，HTTP 客户端的各项配置都有合理的默认值，
+//! 便于在服务端处理器中快速创建出站客户端
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+use rustls::{ClientConfig, RootCertStore};
+use rustls::crypto::ring::default_provider;
+use rustls::pki_types::CertificateDer;
+use rustls_platform_verifier::BuilderVerifierExt;
+
+use crate::error::{RatError, RatResult};
+use crate::client::grpc_builder::MtlsClientConfig;
+use crate::client::http_client::RatHttpClient;
+
+/// RAT Engine HTTP 客户端构建器
+#[derive(Debug)]
+pub struct RatHttpClientBuilder {
+    /// 连接超时时间
+    connect_timeout: Duration,
+    /// 请求超时时间（包含读取完整响应体）
+    request_timeout: Duration,
+    /// 每个目标主机保留的最大空闲连接数
+    max_idle_per_host: usize,
+    /// 响应体最大字节数（压缩响应按解压后的大小计算）
+    max_response_size: usize,
+    /// 用户代理字符串
+    user_agent: String,
+    /// 默认请求头
+    default_headers: HeaderMap,
+    /// 是否协商压缩并自动解压响应体
+    compression: bool,
+    /// mTLS 配置（与 gRPC 客户端共用同一结构）
+    mtls_config: Option<MtlsClientConfig>,
+    /// 额外信任的 CA 证书（DER 格式）
+    ca_certs: Vec<Vec<u8>>,
+}
+
+impl Default for RatHttpClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RatHttpClientBuilder {
+    /// 创建新的构建器实例
+    pub fn new() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(30),
+            max_idle_per_host: 8,
+            max_response_size: 32 * 1024 * 1024,
+            user_agent: format!("rat_engine/{}", env!("CARGO_PKG_VERSION")),
+            default_headers: HeaderMap::new(),
+            compression: false,
+            mtls_config: None,
+            ca_certs: Vec::new(),
+        }
+    }
+
+    /// 设置连接超时时间
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// 设置请求超时时间
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// 设置每个目标主机保留的最大空闲连接数（0 表示不复用连接）
+    pub fn max_idle_per_host(mut self, count: usize) -> Self {
+        self.max_idle_per_host = count;
+        self
+    }
+
+    /// 设置响应体最大字节数（默认 32 MiB）
+    ///
+    /// 同时限制读取的原始响应体和解压后的响应体，超过时请求返回错误
+    pub fn max_response_size(mut self, size: usize) -> Self {
+        self.max_response_size = size;
+        self
+    }
+
+    /// 设置用户代理字符串
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// 添加默认请求头，每个请求都会携带
+    pub fn default_header(mut self, name: &str, value: &str) -> RatResult<Self> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| RatError::RequestError(format!("无效的请求头名称: {}", e)))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| RatError::RequestError(format!("无效的请求头值: {}", e)))?;
+        self.default_headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// 启用压缩协商
+    ///
+    /// 发送 `Accept-Encoding` 并按 `Content-Encoding` 自动解压响应体，
+    /// 支持的算法取决于启用的 compression 特性。
+    /// 已有客户端可通过 [`RatHttpClient::with_compression`] 派生共享连接池的压缩客户端
+    pub fn enable_compression(mut self) -> Self {
+        self.compression = true;
+        self
+    }
+
+    /// 禁用压缩协商
+    pub fn disable_compression(mut self) -> Self {
+        self.compression = false;
+        self
+    }
+
+    /// 添加信任的 CA 证书（PEM 文件），用于访问使用私有 CA 的服务
+    pub fn add_root_certificate<S: Into<String>>(mut self, ca_cert_path: S) -> RatResult<Self> {
+        let ca_pem = std::fs::read_to_string(ca_cert_path.into())
+            .map_err(|e| RatError::RequestError(format!("读取 CA 证书失败: {}", e)))?;
+        let certs = rustls_pemfile::certs(&mut ca_pem.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| RatError::RequestError(format!("解析 CA 证书失败: {}", e)))?;
+        self.ca_certs.extend(certs.iter().map(|c| c.to_vec()));
+        Ok(self)
+    }
+
+    /// 配置 mTLS 客户端认证（PEM 文件）
+    ///
+    /// 可选的 CA 证书用于验证服务器证书，未提供时使用系统证书
+    pub fn with_client_certs<S: Into<String>>(
+        mut self,
+        client_cert_path: S,
+        client_key_path: S,
+        ca_cert_path: Option<String>,
+    ) -> RatResult<Self> {
+        let cert_path = client_cert_path.into();
+        let key_path = client_key_path.into();
+
+        let cert_pem = std::fs::read_to_string(&cert_path)
+            .map_err(|e| RatError::RequestError(format!("读取客户端证书失败: {}", e)))?;
+        let cert_chain = rustls_pemfile::certs(&mut cert_pem.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| RatError::RequestError(format!("解析客户端证书失败: {}", e)))?;
+        if cert_chain.is_empty() {
+            return Err(RatError::RequestError("客户端证书链为空".to_string()));
+        }
+
+        let key_pem = std::fs::read_to_string(&key_path)
+            .map_err(|e| RatError::RequestError(format!("读取客户端私钥失败: {}", e)))?;
+
+        if let Some(ca_path) = ca_cert_path.as_ref().filter(|p| !p.is_empty()) {
+            self = self.add_root_certificate(ca_path.clone())?;
+        }
+
+        self.mtls_config = Some(MtlsClientConfig {
+            client_cert_chain: cert_chain.iter().map(|c| c.to_vec()).collect(),
+            client_private_key: key_pem.into_bytes(),
+            ca_certs: None,
+            server_name: None,
+            client_cert_path: Some(cert_path),
+            client_key_path: Some(key_path),
+            ca_cert_path,
+        });
+        Ok(self)
+    }
+
+    /// 配置 mTLS 客户端认证（直接传入与 gRPC 客户端相同的配置结构）
+    pub fn with_mtls(mut self, mtls_config: MtlsClientConfig) -> Self {
+        if let Some(ca_certs) = &mtls_config.ca_certs {
+            self.ca_certs.extend(ca_certs.iter().cloned());
+        }
+        self.mtls_config = Some(mtls_config);
+        self
+    }
+
+    /// 构建 HTTP 客户端
+    pub fn build(self) -> RatResult<RatHttpClient> {
+        let tls_config = self.create_tls_config()?;
+
+        Ok(RatHttpClient::new(
+            self.connect_timeout,
+            self.request_timeout,
+            self.max_idle_per_host,
+            self.max_response_size,
+            self.user_agent,
+            self.default_headers,
+            self.compression,
+            tls_config,
+        ))
+    }
+
+    /// 创建 TLS 配置
+    ///
+    /// - 配置了 CA 证书时只信任这些 CA，否则使用系统证书
+    /// - ALPN 固定为 http/1.1
+    fn create_tls_config(&self) -> RatResult<Arc<ClientConfig>> {
+        crate::utils::crypto_provider::ensure_crypto_provider_installed();
+
+        let builder = ClientConfig::builder_with_provider(Arc::new(default_provider()))
+            .with_safe_default_protocol_versions()?;
+
+        let builder = if self.ca_certs.is_empty() {
+            builder.with_platform_verifier()
+        } else {
+            let mut roots = RootCertStore::empty();
+            for cert in &self.ca_certs {
+                roots.add(CertificateDer::from(cert.clone()))
+                    .map_err(|e| RatError::TlsError(format!("添加 CA 证书失败: {}", e)))?;
+            }
+            builder.with_root_certificates(roots)
+        };
+
+        let mut config = match &self.mtls_config {
+            Some(mtls) => {
+                let cert_chain = mtls.client_cert_chain
+                    .iter()
+                    .map(|c| CertificateDer::from(c.clone()))
+                    .collect();
+                let private_key = rustls_pemfile::private_key(&mut mtls.client_private_key.as_slice())
+                    .map_err(|e| RatError::RequestError(format!("解析客户端私钥失败: {}", e)))?
+                    .ok_or_else(|| RatError::RequestError("客户端私钥为空".to_string()))?;
+                builder.with_client_auth_cert(cert_chain, private_key)
+                    .map_err(|e| RatError::RequestError(format!("配置客户端证书失败: {}", e)))?
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(Arc::new(config))
+    }
+}
@@ -4,9 +4,10 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use dashmap::DashMap;
+use futures_util::FutureExt;
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::interval;
 use hyper::Uri;
@@ -37,6 +38,8 @@ pub struct ClientConnection {
     pub usage_count: AtomicU64,
     /// 连接任务句柄
     pub connection_handle: Option<tokio::task::JoinHandle<()>>,
+    /// 底层 H2 连接已结束（收到 GOAWAY 或连接断开）
+    pub closed: Arc<AtomicBool>,
 }
 
 impl ClientConnection {
@@ -57,6 +60,7 @@ impl ClientConnection {
             is_active: true,
             usage_count: AtomicU64::new(0),
             connection_handle,
+            closed: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -76,8 +80,13 @@ impl ClientConnection {
     }
 
     /// 检查连接是否可用
+    ///
+    /// 收到 GOAWAY 或连接已断开时不可用：连接任务结束会设置关闭标记，
+    /// 另外通过一次非阻塞的就绪探测尽早发现已不接受新流的连接
     pub fn is_ready(&self) -> bool {
         self.is_active
+            && !self.closed.load(Ordering::Acquire)
+            && !matches!(self.send_request.clone().ready().now_or_never(), Some(Err(_)))
     }
 }
 
@@ -226,6 +235,7 @@ impl ClientConnectionPool {
                         is_active: connection.is_active,
                         usage_count: AtomicU64::new(connection.get_usage_count()),
                         connection_handle: None,
+                        closed: connection.closed.clone(),
                     }));
                 }
             }
@@ -239,16 +249,28 @@ impl ClientConnectionPool {
     }
 
     fn find_available_connection(&self, target_key: &str) -> Option<String> {
+        let mut available = None;
+        let mut stale = Vec::new();
+
         if let Some(connection_ids) = self.target_connections.get(target_key) {
             for connection_id in connection_ids.iter() {
                 if let Some(connection) = self.connections.get(connection_id) {
                     if connection.is_ready() {
-                        return Some(connection_id.clone());
+                        available = Some(connection_id.clone());
+                        break;
                     }
+                    stale.push(connection_id.clone());
                 }
             }
         }
-        None
+
+        // 收到 GOAWAY 或已断开的连接不再复用，移出连接池以便为后续调用建立新连接
+        for connection_id in stale {
+            debug!("[客户端] ♻️ 连接 {} 已不可用（GOAWAY 或连接断开），移出连接池", connection_id);
+            self.remove_connection(&connection_id);
+        }
+
+        available
     }
 
     fn can_create_new_connection(&self, target_key: &str) -> bool {
@@ -292,6 +314,8 @@ impl ClientConnectionPool {
 
         let send_request;
         let connection_handle;
+        let closed = Arc::new(AtomicBool::new(false));
+        let closed_flag = closed.clone();
 
         if is_https {
             debug!("[客户端] 🔐 建立 TLS 连接到 {}:{} (h2c模式: {})", host, port, self.config.h2c_mode);
@@ -322,9 +346,12 @@ impl ClientConnectionPool {
             send_request = send_req;
 
             connection_handle = tokio::spawn(async move {
-                if let Err(e) = h2_conn.await {
-                    error!("[客户端] H2 TLS 连接错误: {}", e);
+                match h2_conn.await {
+                    Err(e) if e.is_go_away() => info!("[客户端] 📴 服务器发送 GOAWAY，H2 TLS 连接关闭: {}", e),
+                    Err(e) => error!("[客户端] H2 TLS 连接错误: {}", e),
+                    Ok(()) => {}
                 }
+                closed_flag.store(true, Ordering::Release);
             });
         } else {
             debug!("[客户端] 🌐 建立 HTTP/2 Cleartext 连接到 {}:{}", host, port);
@@ -338,18 +365,22 @@ impl ClientConnectionPool {
             send_request = send_req;
 
             connection_handle = tokio::spawn(async move {
-                if let Err(e) = h2_conn.await {
-                    error!("[客户端] H2 连接错误: {}", e);
+                match h2_conn.await {
+                    Err(e) if e.is_go_away() => info!("[客户端] 📴 服务器发送 GOAWAY，H2 连接关闭: {}", e),
+                    Err(e) => error!("[客户端] H2 连接错误: {}", e),
+                    Ok(()) => {}
                 }
+                closed_flag.store(true, Ordering::Release);
             });
         }
 
-        let client_connection = ClientConnection::new(
+        let mut client_connection = ClientConnection::new(
             connection_id.clone(),
             target_uri,
             send_request,
             Some(connection_handle),
         );
+        client_connection.closed = closed;
 
         self.connections.insert(connection_id.clone(), client_connection);
 
@@ -370,6 +401,7 @@ impl ClientConnectionPool {
                 is_active: connection.is_active,
                 usage_count: AtomicU64::new(connection.get_usage_count()),
                 connection_handle: None,
+                closed: connection.closed.clone(),
             }))
         } else {
             Err(RatError::NetworkError("连接创建后立即丢失".to_string()))
@@ -502,3 +534,65 @@ impl Drop for ClientConnectionPool {
         crate::utils::logger::debug!("✅ 客户端连接池已完成清理");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// 启动一个处理单个请求后发送 GOAWAY 的 H2 服务器，可接受多个连接
+    async fn spawn_goaway_server() -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut connection = h2::server::handshake(stream).await.unwrap();
+                    if let Some(Ok((_request, mut respond))) = connection.accept().await {
+                        let response = hyper::Response::builder().status(200).body(()).unwrap();
+                        let mut send = respond.send_response(response, false).unwrap();
+                        send.send_data(Bytes::from_static(b"ok"), true).unwrap();
+                    }
+                    // 模拟服务器优雅重启
+                    connection.graceful_shutdown();
+                    while connection.accept().await.is_some() {}
+                });
+            }
+        });
+        addr
+    }
+
+    async fn call(connection: &ClientConnection) -> RatResult<()> {
+        let mut send_request = connection.send_request.clone().ready().await
+            .map_err(|e| RatError::NetworkError(e.to_string()))?;
+        let request = hyper::Request::get("/").body(()).unwrap();
+        let (response, _) = send_request.send_request(request, true)
+            .map_err(|e| RatError::NetworkError(e.to_string()))?;
+        let response = response.await.map_err(|e| RatError::NetworkError(e.to_string()))?;
+        assert_eq!(response.status(), 200);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_after_goaway() {
+        let addr = spawn_goaway_server().await;
+        let uri: Uri = format!("http://{}", addr).parse().unwrap();
+        let pool = ClientConnectionPool::new(ConnectionPoolConfig::default());
+
+        let first = pool.get_connection(&uri).await.unwrap();
+        call(&first).await.unwrap();
+
+        // 等待客户端感知 GOAWAY
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while first.is_ready() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("客户端未感知 GOAWAY");
+
+        // 下一次调用透明地建立新连接
+        let second = pool.get_connection(&uri).await.unwrap();
+        assert_ne!(first.connection_id, second.connection_id);
+        call(&second).await.unwrap();
+        assert_eq!(pool.get_stats().0, 1);
+    }
+}
@@ -17,6 +17,7 @@ use hyper::header::{HeaderMap, HeaderValue, CONTENT_TYPE, USER_AGENT};
 use http_body_util::Full;
 use hyper::body::Bytes;
 use tokio::sync::mpsc;
+use super::grpc_cancellation::{CancellationToken, cancelled_error, cancelled_if_set};
 
 impl RatGrpcClient {
     /// 创建委托模式的双向流连接
@@ -77,6 +78,45 @@ impl RatGrpcClient {
         handler: Arc<H>,
         metadata: Option<HashMap<String, String>>,
     ) -> RatResult<u64>
+    where
+        H: ClientBidirectionalHandler + 'static,
+        <H as ClientBidirectionalHandler>::ReceiveData: bincode::Decode<()>,
+    {
+        self.open_bidirectional_stream_delegated(uri, service, method, handler, metadata, None).await
+    }
+
+    /// 使用指定 URI 创建委托模式双向流，并可通过取消令牌中途终止
+    ///
+    /// `cancel` 被触发后，客户端向服务端发送 `RST_STREAM(CANCEL)`，
+    /// 处理器的 `on_disconnected` 收到取消原因；连接本身保持可用并留在连接池中
+    pub async fn create_bidirectional_stream_delegated_with_cancel<H>(
+        &self,
+        uri: &str,
+        service: &str,
+        method: &str,
+        handler: Arc<H>,
+        metadata: Option<HashMap<String, String>>,
+        cancel: CancellationToken,
+    ) -> RatResult<u64>
+    where
+        H: ClientBidirectionalHandler + 'static,
+        <H as ClientBidirectionalHandler>::ReceiveData: bincode::Decode<()>,
+    {
+        if cancel.is_cancelled() {
+            return Err(cancelled_error());
+        }
+        self.open_bidirectional_stream_delegated(uri, service, method, handler, metadata, Some(cancel)).await
+    }
+
+    async fn open_bidirectional_stream_delegated<H>(
+        &self,
+        uri: &str,
+        service: &str,
+        method: &str,
+        handler: Arc<H>,
+        metadata: Option<HashMap<String, String>>,
+        cancel: Option<CancellationToken>,
+    ) -> RatResult<u64>
     where
         H: ClientBidirectionalHandler + 'static,
         <H as ClientBidirectionalHandler>::ReceiveData: bincode::Decode<()>,
@@ -110,9 +150,16 @@ impl RatGrpcClient {
         let (response, send_stream) = send_request.send_request(request, false)
             .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("send_bidirectional_stream_request_failed", &[("msg", &e.to_string())])))?;
 
-        // 等待响应头
-        let response = response.await
-            .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("receive_bidirectional_stream_response_failed", &[("msg", &e.to_string())])))?;
+        // 等待响应头（期间同样可以取消，丢弃响应 future 和发送端即重置该流）
+        let response = tokio::select! {
+            response = response => response
+                .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("receive_bidirectional_stream_response_failed", &[("msg", &e.to_string())])))?,
+            _ = cancelled_if_set(cancel.as_ref()) => {
+                self.connection_pool.release_connection(&connection.connection_id);
+                info!("🛑 双向流调用在收到响应前被取消: {}/{}", service, method);
+                return Err(cancelled_error());
+            }
+        };
 
         let receive_stream = response.into_body();
 
@@ -145,13 +192,26 @@ impl RatGrpcClient {
         });
 
         // 启动发送任务
+        let send_cancel = cancel.clone();
         let send_task = {
             let mut send_stream = send_stream;
             tokio::spawn(async move {
                 let mut send_rx = send_rx;
                 let mut message_sent = false;
                 
-                while let Some(data) = send_rx.recv().await {
+                loop {
+                    let data = tokio::select! {
+                        biased;
+                        _ = cancelled_if_set(send_cancel.as_ref()) => {
+                            send_stream.send_reset(h2::Reason::CANCEL);
+                            info!("🛑 [委托模式] 双向流 {} 已取消，已重置 H2 流", stream_id);
+                            break;
+                        }
+                        data = send_rx.recv() => match data {
+                            Some(data) => data,
+                            None => break,
+                        },
+                    };
                     message_sent = true;
                     
                     // 尝试检查是否为已序列化的 GrpcStreamMessage（关闭指令）
@@ -206,7 +266,19 @@ impl RatGrpcClient {
                 let mut buffer = Vec::new();
                 
                 info!("🔄 [委托模式] 开始接收响应流数据...");
-                while let Some(chunk_result) = receive_stream.data().await {
+                let mut disconnect_reason = None;
+                loop {
+                    let chunk_result = tokio::select! {
+                        biased;
+                        _ = cancelled_if_set(cancel.as_ref()) => {
+                            disconnect_reason = Some(cancelled_error().to_string());
+                            break;
+                        }
+                        chunk = receive_stream.data() => match chunk {
+                            Some(chunk) => chunk,
+                            None => break,
+                        },
+                    };
                     info!("📡 [委托模式-网络层] ===== 网络数据接收事件 =====");
                     info!("📡 [委托模式-网络层] 数据块结果状态: {:?}", chunk_result.is_ok());
                     match chunk_result {
@@ -300,8 +372,8 @@ impl RatGrpcClient {
                     }
                 }
                 
-                // 通知处理器连接断开
-                handler_clone.on_disconnected(&context_clone, None).await;
+                // 通知处理器连接断开（取消时附带原因）
+                handler_clone.on_disconnected(&context_clone, disconnect_reason).await;
                 info!("消息接收完成");
             })
         };
@@ -22,6 +22,7 @@ use crate::compression::{CompressionType, CompressionConfig};
 #[cfg(not(feature = "compression"))]
 use crate::client::grpc_builder::CompressionConfig;
 use crate::server::grpc_types::{GrpcRequest, GrpcResponse};
+use crate::server::grpc_metadata::GrpcResponseMetadata;
 use crate::server::grpc_codec::GrpcCodec;
 use crate::client::connection_pool::{ClientConnectionPool, ConnectionPoolConfig};
 // use crate::client::grpc_builder::MtlsClientConfig; // 暂时注释
@@ -191,6 +192,7 @@ impl RatGrpcClient {
         headers.insert(USER_AGENT, HeaderValue::from_str(&self.user_agent)
             .map_err(|e| RatError::RequestError(rat_embed_lang::tf("invalid_user_agent_msg", &[("msg", &e.to_string())])))?);
         headers.insert(ACCEPT_ENCODING, HeaderValue::from_static(self.compression_mode.accept_encoding()));
+        headers.insert("grpc-accept-encoding", HeaderValue::from_static(super::message_utils::grpc_accept_encoding()));
         
         if let Some(encoding) = content_encoding {
             headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
@@ -243,6 +245,17 @@ impl RatGrpcClient {
 
     /// 使用指定 URI 进行强类型 gRPC 调用
     pub async fn call_typed_with_uri<T, R>(&self, uri: &str, service: &str, method: &str, request_data: T, metadata: Option<HashMap<String, String>>) -> RatResult<GrpcResponse<R>>
+    where
+        T: Serialize + bincode::Encode + Send + Sync,
+        R: for<'de> Deserialize<'de> + Send + Sync + bincode::Decode<()>,
+    {
+        self.call_typed_with_response_metadata(uri, service, method, request_data, metadata)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// 使用指定 URI 进行强类型 gRPC 调用，同时返回服务端设置的头部和尾部元数据
+    pub async fn call_typed_with_response_metadata<T, R>(&self, uri: &str, service: &str, method: &str, request_data: T, metadata: Option<HashMap<String, String>>) -> RatResult<(GrpcResponse<R>, GrpcResponseMetadata)>
     where
         T: Serialize + bincode::Encode + Send + Sync,
         R: for<'de> Deserialize<'de> + Send + Sync + bincode::Decode<()>,
@@ -284,6 +297,7 @@ impl RatGrpcClient {
         headers.insert(USER_AGENT, HeaderValue::from_str(&self.user_agent)
             .map_err(|e| RatError::RequestError(rat_embed_lang::tf("invalid_user_agent_msg", &[("msg", &e.to_string())])))?);
         headers.insert(ACCEPT_ENCODING, HeaderValue::from_static(self.compression_mode.accept_encoding()));
+        headers.insert("grpc-accept-encoding", HeaderValue::from_static(super::message_utils::grpc_accept_encoding()));
         
         if let Some(encoding) = content_encoding {
             headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
@@ -307,10 +321,12 @@ impl RatGrpcClient {
         let request = Request::from_parts(parts, body);
 
         // 发送请求
-        let (status, headers, body) = self.send_request(request).await?;
+        let (status, headers, trailers, body) = self.send_request_with_trailers(request).await?;
 
         // 解析响应
-        self.parse_grpc_response(status, headers, body)
+        let response_metadata = GrpcResponseMetadata { headers: headers.clone(), trailers };
+        let response = self.parse_grpc_response(status, headers, body)?;
+        Ok((response, response_metadata))
     }
     /// 获取压缩模式
     pub fn compression_mode(&self) -> GrpcCompressionMode {
@@ -0,0 +1,284 @@
+//! gRPC 客户端调用取消
+//!
+//! [`CancellationToken`] 可以克隆后交给其他任务，触发后正在进行的流式调用
+//! （服务端流、客户端流、委托模式双向流）立即结束：
+//! 客户端释放 H2 流，h2 会向服务端发送 `RST_STREAM(CANCEL)`，
+//! 底层连接不受影响，继续留在连接池中供后续调用复用。
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::error::RatError;
+
+/// 调用取消令牌
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    state: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    /// 创建未取消的令牌
+    pub fn new() -> Self {
+        let (state, _) = watch::channel(false);
+        Self { state: Arc::new(state) }
+    }
+
+    /// 触发取消，所有克隆共享同一状态，重复调用无副作用
+    pub fn cancel(&self) {
+        self.state.send_replace(true);
+    }
+
+    /// 是否已取消
+    pub fn is_cancelled(&self) -> bool {
+        *self.state.borrow()
+    }
+
+    /// 等待令牌被取消
+    pub async fn cancelled(&self) {
+        let mut state = self.state.subscribe();
+        // 发送端由令牌自身持有，不会提前关闭
+        let _ = state.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+/// 等待可选的取消令牌；未提供令牌时永不完成
+pub(crate) async fn cancelled_if_set(cancel: Option<&CancellationToken>) {
+    match cancel {
+        Some(token) => token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// 调用被取消时返回的错误
+pub(crate) fn cancelled_error() -> RatError {
+    RatError::RequestError(rat_embed_lang::t("grpc_call_cancelled"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Poll;
+    use std::time::Duration;
+    use futures_util::StreamExt;
+    use hyper::body::Bytes;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use crate::client::grpc_builder::RatGrpcClientBuilder;
+    use crate::server::grpc_codec::GrpcCodec;
+    use crate::server::grpc_types::GrpcStreamResponse;
+
+    fn stream_frame(sequence: u64, end_of_stream: bool) -> Bytes {
+        let message = GrpcStreamResponse {
+            id: sequence,
+            stream_id: 1,
+            sequence,
+            data: sequence.to_be_bytes().to_vec(),
+            end_of_stream,
+            metadata: Default::default(),
+        };
+        Bytes::from(GrpcCodec::encode_frame(&message).unwrap())
+    }
+
+    /// 启动 h2c 服务器：`/test.Svc/Endless` 持续推送消息直到被客户端重置，
+    /// 并上报收到的 RST_STREAM 原因；`/test.Svc/Once` 推送一条消息后正常结束
+    async fn spawn_stream_server(resets: mpsc::UnboundedSender<Option<h2::Reason>>) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let resets = resets.clone();
+                tokio::spawn(async move {
+                    let mut connection = h2::server::handshake(stream).await.unwrap();
+                    while let Some(Ok((request, mut respond))) = connection.accept().await {
+                        let resets = resets.clone();
+                        tokio::spawn(async move {
+                            let response = hyper::Response::builder()
+                                .status(200)
+                                .header("content-type", "application/grpc")
+                                .body(())
+                                .unwrap();
+                            let mut send = respond.send_response(response, false).unwrap();
+                            if request.uri().path().ends_with("/Once") {
+                                send.send_data(stream_frame(1, true), false).unwrap();
+                                let mut trailers = hyper::HeaderMap::new();
+                                trailers.insert("grpc-status", "0".parse().unwrap());
+                                send.send_trailers(trailers).unwrap();
+                                return;
+                            }
+
+                            let mut sequence = 0;
+                            loop {
+                                tokio::time::sleep(Duration::from_millis(10)).await;
+                                let reset = futures_util::poll!(futures_util::future::poll_fn(|cx| send.poll_reset(cx)));
+                                if let Poll::Ready(reason) = reset {
+                                    let _ = resets.send(reason.ok());
+                                    return;
+                                }
+                                sequence += 1;
+                                if send.send_data(stream_frame(sequence, false), false).is_err() {
+                                    let _ = resets.send(None);
+                                    return;
+                                }
+                            }
+                        });
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_cancel_server_stream_keeps_connection_healthy() {
+        let (reset_tx, mut reset_rx) = mpsc::unbounded_channel();
+        let addr = spawn_stream_server(reset_tx).await;
+        let uri = format!("http://{}", addr);
+        let client = RatGrpcClientBuilder::new()
+            .connect_timeout(Duration::from_secs(5)).unwrap()
+            .request_timeout(Duration::from_secs(5)).unwrap()
+            .max_idle_connections(4).unwrap()
+            .http2_only()
+            .user_agent("cancel-test").unwrap()
+            .disable_compression()
+            .h2c_mode()
+            .build()
+            .unwrap();
+
+        let token = CancellationToken::new();
+        let mut response = client
+            .call_server_stream_with_cancel::<Vec<u8>, Vec<u8>>(&uri, "test.Svc", "Endless", Vec::new(), None, token.clone())
+            .await
+            .unwrap();
+
+        // 收到几条消息后中途取消
+        for _ in 0..3 {
+            assert!(response.stream.next().await.unwrap().is_ok());
+        }
+        token.cancel();
+        assert!(response.stream.next().await.unwrap().is_err());
+        assert!(response.stream.next().await.is_none());
+
+        // 服务端收到 RST_STREAM(CANCEL)
+        let reason = tokio::time::timeout(Duration::from_secs(5), reset_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(reason, Some(h2::Reason::CANCEL));
+
+        // 连接仍留在连接池中，后续调用复用同一连接
+        let mut response = client
+            .call_server_stream_with_cancel::<Vec<u8>, Vec<u8>>(&uri, "test.Svc", "Once", Vec::new(), None, CancellationToken::new())
+            .await
+            .unwrap();
+        let message = response.stream.next().await.unwrap().unwrap();
+        assert!(message.end_of_stream);
+        assert_eq!(client.connection_pool.get_stats().0, 1);
+    }
+
+    fn test_client() -> crate::client::grpc_client::RatGrpcClient {
+        RatGrpcClientBuilder::new()
+            .connect_timeout(Duration::from_secs(5)).unwrap()
+            .request_timeout(Duration::from_secs(5)).unwrap()
+            .max_idle_connections(4).unwrap()
+            .http2_only()
+            .user_agent("cancel-test").unwrap()
+            .disable_compression()
+            .h2c_mode()
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cancel_client_stream() {
+        let (reset_tx, mut reset_rx) = mpsc::unbounded_channel();
+        let addr = spawn_stream_server(reset_tx).await;
+        let client = test_client();
+
+        let token = CancellationToken::new();
+        let (mut sender, response) = client
+            .call_client_stream_with_cancel::<Vec<u8>, Vec<u8>>(&format!("http://{}", addr), "test.Svc", "Endless", None, token.clone())
+            .await
+            .unwrap();
+        sender.send(vec![1, 2, 3]).await.unwrap();
+
+        token.cancel();
+        let result = tokio::time::timeout(Duration::from_secs(5), response).await.unwrap().unwrap();
+        assert!(result.is_err());
+
+        // 服务端收到 RST_STREAM(CANCEL)，之后的发送失败
+        let reason = tokio::time::timeout(Duration::from_secs(5), reset_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(reason, Some(h2::Reason::CANCEL));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(sender.send(vec![4]).await.is_err());
+        assert_eq!(client.connection_pool.get_stats().0, 1);
+    }
+
+    struct RecordingHandler {
+        disconnected: mpsc::UnboundedSender<Option<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::client::grpc_client_delegated::ClientBidirectionalHandler for RecordingHandler {
+        type SendData = Vec<u8>;
+        type ReceiveData = Vec<u8>;
+
+        async fn on_connected(&self, _context: &crate::client::grpc_client_delegated::ClientStreamContext) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn on_message_received(&self, _message: Vec<u8>, _context: &crate::client::grpc_client_delegated::ClientStreamContext) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn on_send_task(&self, _context: &crate::client::grpc_client_delegated::ClientStreamContext) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn on_disconnected(&self, _context: &crate::client::grpc_client_delegated::ClientStreamContext, reason: Option<String>) {
+            let _ = self.disconnected.send(reason);
+        }
+
+        async fn on_error(&self, _context: &crate::client::grpc_client_delegated::ClientStreamContext, _error: String) {}
+    }
+
+    #[tokio::test]
+    async fn test_cancel_bidirectional_stream() {
+        let (reset_tx, mut reset_rx) = mpsc::unbounded_channel();
+        let addr = spawn_stream_server(reset_tx).await;
+        let client = test_client();
+
+        let (disconnected_tx, mut disconnected_rx) = mpsc::unbounded_channel();
+        let handler = std::sync::Arc::new(RecordingHandler { disconnected: disconnected_tx });
+        let token = CancellationToken::new();
+        client
+            .create_bidirectional_stream_delegated_with_cancel(&format!("http://{}", addr), "test.Svc", "Endless", handler, None, token.clone())
+            .await
+            .unwrap();
+
+        token.cancel();
+
+        // 处理器收到带原因的断开通知，服务端收到 RST_STREAM(CANCEL)
+        let reason = tokio::time::timeout(Duration::from_secs(5), disconnected_rx.recv()).await.unwrap().unwrap();
+        assert!(reason.is_some());
+        let reset = tokio::time::timeout(Duration::from_secs(5), reset_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(reset, Some(h2::Reason::CANCEL));
+        assert_eq!(client.connection_pool.get_stats().0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_token_shared_between_clones() {
+        let token = CancellationToken::new();
+        let waiter = {
+            let token = token.clone();
+            tokio::spawn(async move { token.cancelled().await })
+        };
+        assert!(!token.is_cancelled());
+        token.clone().cancel();
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert!(token.is_cancelled());
+    }
+}
@@ -19,6 +19,7 @@ use hyper::body::Bytes;
 use tokio::sync::mpsc;
 use super::GrpcStreamResponse;
 use super::GrpcStreamSender;
+use super::grpc_cancellation::{CancellationToken, cancelled_error, cancelled_if_set};
 
 impl RatGrpcClient {
     /// 创建客户端流连接（统一化版本，用于分块上传等场景）
@@ -41,6 +42,50 @@ impl RatGrpcClient {
         method: &str, 
         metadata: Option<HashMap<String, String>>
     ) -> RatResult<(GrpcStreamSender<S>, tokio::sync::oneshot::Receiver<RatResult<R>>)>
+    where
+        S: Serialize + Send + Sync + 'static + bincode::Encode,
+        R: for<'de> Deserialize<'de> + Send + Sync + 'static + bincode::Decode<()>,
+    {
+        self.open_client_stream(uri, service, method, metadata, None).await
+    }
+
+    /// 创建客户端流连接，并可通过取消令牌中途终止
+    ///
+    /// `cancel` 被触发后，发送端向服务端发送 `RST_STREAM(CANCEL)`，之后的 `send` 返回错误，
+    /// 响应接收器得到取消错误；连接本身保持可用并留在连接池中
+    ///
+    /// # 参数
+    /// * `uri` - 服务器 URI
+    /// * `service` - 服务名称
+    /// * `method` - 方法名称
+    /// * `metadata` - 可选的元数据
+    /// * `cancel` - 取消令牌
+    pub async fn call_client_stream_with_cancel<S, R>(
+        &self,
+        uri: &str,
+        service: &str,
+        method: &str,
+        metadata: Option<HashMap<String, String>>,
+        cancel: CancellationToken,
+    ) -> RatResult<(GrpcStreamSender<S>, tokio::sync::oneshot::Receiver<RatResult<R>>)>
+    where
+        S: Serialize + Send + Sync + 'static + bincode::Encode,
+        R: for<'de> Deserialize<'de> + Send + Sync + 'static + bincode::Decode<()>,
+    {
+        if cancel.is_cancelled() {
+            return Err(cancelled_error());
+        }
+        self.open_client_stream(uri, service, method, metadata, Some(cancel)).await
+    }
+
+    async fn open_client_stream<S, R>(
+        &self,
+        uri: &str,
+        service: &str,
+        method: &str,
+        metadata: Option<HashMap<String, String>>,
+        cancel: Option<CancellationToken>,
+    ) -> RatResult<(GrpcStreamSender<S>, tokio::sync::oneshot::Receiver<RatResult<R>>)>
     where
         S: Serialize + Send + Sync + 'static + bincode::Encode,
         R: for<'de> Deserialize<'de> + Send + Sync + 'static + bincode::Decode<()>,
@@ -70,9 +115,16 @@ impl RatGrpcClient {
         let (response, send_stream) = send_request.send_request(request, false)
             .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("send_client_stream_request_failed", &[("msg", &e.to_string())])))?;
 
-        // 等待响应头
-        let response = response.await
-            .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("receive_client_stream_response_failed", &[("msg", &e.to_string())])))?;
+        // 等待响应头（期间同样可以取消，丢弃响应 future 和发送端即重置该流）
+        let response = tokio::select! {
+            response = response => response
+                .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("receive_client_stream_response_failed", &[("msg", &e.to_string())])))?,
+            _ = cancelled_if_set(cancel.as_ref()) => {
+                self.connection_pool.release_connection(&connection.connection_id);
+                info!("🛑 客户端流调用在收到响应前被取消: {}/{}", service, method);
+                return Err(cancelled_error());
+            }
+        };
 
         let receive_stream = response.into_body();
 
@@ -83,13 +135,28 @@ impl RatGrpcClient {
         // 启动发送任务（复用双向流的发送逻辑）
         let connection_id = connection.connection_id.clone();
         let connection_pool = self.connection_pool.clone();
+        let send_cancel = cancel.clone();
         let send_task = {
             let mut send_stream = send_stream;
             tokio::spawn(async move {
                 let mut send_rx = send_rx;
                 let mut message_sent = false;
                 
-                while let Some(data) = send_rx.recv().await {
+                loop {
+                    let data = tokio::select! {
+                        biased;
+                        _ = cancelled_if_set(send_cancel.as_ref()) => {
+                            // 重置流后不再发送结束信号
+                            send_stream.send_reset(h2::Reason::CANCEL);
+                            info!("🛑 [客户端流] 调用已取消，已重置 H2 流");
+                            message_sent = false;
+                            break;
+                        }
+                        data = send_rx.recv() => match data {
+                            Some(data) => data,
+                            None => break,
+                        },
+                    };
                     message_sent = true;
                     
                     // 构建 gRPC 消息帧
@@ -128,7 +195,18 @@ impl RatGrpcClient {
                 let mut buffer = Vec::new();
                 
                 // 接收响应数据
-                while let Some(chunk_result) = receive_stream.data().await {
+                loop {
+                    let chunk_result = tokio::select! {
+                        biased;
+                        _ = cancelled_if_set(cancel.as_ref()) => {
+                            let _ = response_tx.send(Err(cancelled_error()));
+                            return;
+                        }
+                        chunk = receive_stream.data() => match chunk {
+                            Some(chunk) => chunk,
+                            None => break,
+                        },
+                    };
                     match chunk_result {
                         Ok(chunk) => buffer.extend_from_slice(&chunk),
                         Err(e) => {
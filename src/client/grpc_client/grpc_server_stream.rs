@@ -18,6 +18,7 @@ use hyper::body::Bytes;
 use h2::RecvStream;
 use super::GrpcStreamResponse;
 use super::GrpcStreamSender;
+use super::grpc_cancellation::{CancellationToken, cancelled_error};
 
 impl RatGrpcClient {
     ///
@@ -82,6 +83,7 @@ impl RatGrpcClient {
         headers.insert(USER_AGENT, HeaderValue::from_str(&self.user_agent)
             .map_err(|e| RatError::RequestError(rat_embed_lang::tf("invalid_user_agent_msg", &[("msg", &e.to_string())])))?);
         headers.insert(ACCEPT_ENCODING, HeaderValue::from_static(self.compression_mode.accept_encoding()));
+        headers.insert("grpc-accept-encoding", HeaderValue::from_static(super::message_utils::grpc_accept_encoding()));
         headers.insert("grpc-stream-type", HeaderValue::from_static("server-stream"));
         
         if let Some(encoding) = content_encoding {
@@ -101,8 +103,12 @@ impl RatGrpcClient {
 
         // 发送 H2 流请求并获取流响应
         let h2_response = self.send_h2_request_stream(request).await?;
+        let encoding = h2_response.headers()
+            .get("grpc-encoding")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
         let recv_stream = h2_response.into_body();
-        let stream = self.create_server_stream(recv_stream);
+        let stream = self.create_server_stream(recv_stream, encoding);
 
         Ok(GrpcStreamResponse {
             stream_id,
@@ -164,13 +170,135 @@ impl RatGrpcClient {
         R: for<'de> Deserialize<'de> + Send + Sync + 'static + bincode::Decode<()>,
     {
         let stream_id = self.stream_id_counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let (request, body) = self.build_server_stream_request(uri, service, method, request_data, metadata)?;
+        let request = request.map(|_| Full::new(body));
+
+        // 发送 H2 流请求并获取流响应
+        let h2_response = self.send_h2_request_stream(request).await?;
+        let encoding = h2_response.headers()
+            .get("grpc-encoding")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let recv_stream = h2_response.into_body();
+        let stream = self.create_server_stream(recv_stream, encoding);
+
+        Ok(GrpcStreamResponse {
+            stream_id,
+            stream,
+        })
+    }
+
+    /// 调用服务端流 gRPC 方法，并可通过取消令牌中途终止
+    ///
+    /// 请求通过连接池中的 H2 连接发送。`cancel` 被触发后，响应流产出一个取消错误后结束，
+    /// 同时释放 H2 流，h2 向服务端发送 `RST_STREAM(CANCEL)`；连接本身保持可用并留在连接池中
+    ///
+    /// # 参数
+    /// * `uri` - 服务器 URI (例如: "https://localhost:8080")
+    /// * `service` - 服务名称
+    /// * `method` - 方法名称
+    /// * `request_data` - 请求数据
+    /// * `metadata` - 可选的元数据
+    /// * `cancel` - 取消令牌
+    pub async fn call_server_stream_with_cancel<T, R>(
+        &self,
+        uri: &str,
+        service: &str,
+        method: &str,
+        request_data: T,
+        metadata: Option<HashMap<String, String>>,
+        cancel: CancellationToken,
+    ) -> RatResult<GrpcStreamResponse<R>>
+    where
+        T: Serialize + Send + Sync + bincode::Encode,
+        R: for<'de> Deserialize<'de> + Send + Sync + 'static + bincode::Decode<()>,
+    {
+        if cancel.is_cancelled() {
+            return Err(cancelled_error());
+        }
+
+        let stream_id = self.stream_id_counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let (request, body) = self.build_server_stream_request(uri, service, method, request_data, metadata)?;
+
+        // 从连接池获取连接
+        let connection = self.connection_pool.get_connection(request.uri()).await
+            .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("get_connection_failed", &[("msg", &e.to_string())])))?;
+        let connection_id = connection.connection_id.clone();
+        let mut send_request = connection.send_request.clone().ready().await
+            .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("h2_send_request_failed", &[("msg", &e.to_string())])))?;
+
+        let (response, mut send_stream) = send_request.send_request(request, false)
+            .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("h2_send_request_failed", &[("msg", &e.to_string())])))?;
+        send_stream.send_data(body, true)
+            .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("h2_send_data_failed", &[("msg", &e.to_string())])))?;
+
+        // 等待响应头期间同样可以取消，丢弃响应 future 即重置该流
+        let h2_response = tokio::select! {
+            response = tokio::time::timeout(self.request_timeout, response) => response
+                .map_err(|_| RatError::TimeoutError(rat_embed_lang::tf("h2_response_timeout", &[("msg", &format!("{}/{}", service, method))])))?
+                .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("h2_receive_response_failed", &[("msg", &e.to_string())])))?,
+            _ = cancel.cancelled() => {
+                self.connection_pool.release_connection(&connection_id);
+                info!("🛑 服务端流调用在收到响应前被取消: {}/{}", service, method);
+                return Err(cancelled_error());
+            }
+        };
+
+        let encoding = h2_response.headers()
+            .get("grpc-encoding")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let mut messages = self.create_server_stream::<R>(h2_response.into_body(), encoding);
+
+        let connection_pool = self.connection_pool.clone();
+        let call = format!("{}/{}", service, method);
+        let stream = stream! {
+            loop {
+                let next = tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => None,
+                    item = messages.next() => Some(item),
+                };
+                match next {
+                    Some(Some(item)) => yield item,
+                    Some(None) => break,
+                    None => {
+                        info!("🛑 服务端流调用已取消: {}", call);
+                        yield Err(cancelled_error());
+                        break;
+                    }
+                }
+            }
+            // 释放 RecvStream：流尚未结束时 h2 会发送 RST_STREAM(CANCEL)
+            drop(messages);
+            connection_pool.release_connection(&connection_id);
+        };
+
+        Ok(GrpcStreamResponse {
+            stream_id,
+            stream: Box::pin(stream),
+        })
+    }
+
+    /// 构建服务端流请求，返回请求头和已编码的请求体
+    fn build_server_stream_request<T>(
+        &self,
+        uri: &str,
+        service: &str,
+        method: &str,
+        request_data: T,
+        metadata: Option<HashMap<String, String>>,
+    ) -> RatResult<(Request<()>, Bytes)>
+    where
+        T: Serialize + Send + Sync + bincode::Encode,
+    {
         let request_id = self.request_id_counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-        
+
         // 统一化处理：先序列化强类型数据为 Vec<u8>，然后包装到 GrpcRequest 中
         // 这样服务端就能接收到 GrpcRequest<Vec<u8>> 格式的数据，与 call_typed 保持一致
         let serialized_data = GrpcCodec::encode(&request_data)
             .map_err(|e| RatError::SerializationError(rat_embed_lang::tf("serialize_request_failed", &[("msg", &e.to_string())])))?;
-        
+
         // 构建 gRPC 请求（使用序列化后的数据）
         let grpc_request = GrpcRequest {
             id: request_id,
@@ -183,10 +311,6 @@ impl RatGrpcClient {
         let grpc_message = GrpcCodec::encode_frame(&grpc_request)
             .map_err(|e| RatError::SerializationError(rat_embed_lang::tf("encode_grpc_request_failed", &[("msg", &e.to_string())])))?;
 
-        // 服务端流直接使用 gRPC 消息格式，不进行额外的 HTTP 压缩
-        let compressed_data = Bytes::from(grpc_message);
-        let content_encoding: Option<&'static str> = None;
-
         // 构建 HTTP 请求
         let base_uri_str = uri.trim_end_matches('/').to_string();
         let path = format!("/{}/{}", service, method);
@@ -200,36 +324,24 @@ impl RatGrpcClient {
         headers.insert(USER_AGENT, HeaderValue::from_str(&self.user_agent)
             .map_err(|e| RatError::RequestError(rat_embed_lang::tf("invalid_user_agent_msg", &[("msg", &e.to_string())])))?);
         headers.insert(ACCEPT_ENCODING, HeaderValue::from_static(self.compression_mode.accept_encoding()));
+        headers.insert("grpc-accept-encoding", HeaderValue::from_static(super::message_utils::grpc_accept_encoding()));
         headers.insert("grpc-stream-type", HeaderValue::from_static("server-stream"));
-        
-        if let Some(encoding) = content_encoding {
-            headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
-        }
 
+        // 服务端流直接使用 gRPC 消息格式，不进行额外的 HTTP 压缩
         let request = Request::builder()
             .method(Method::POST)
             .uri(request_uri)
-            .body(Full::new(compressed_data))
+            .body(())
             .map_err(|e| RatError::RequestError(rat_embed_lang::tf("build_request_failed", &[("msg", &e.to_string())])))?;
 
         // 添加头部
         let (mut parts, body) = request.into_parts();
         parts.headers = headers;
-        let request = Request::from_parts(parts, body);
-
-        // 发送 H2 流请求并获取流响应
-        let h2_response = self.send_h2_request_stream(request).await?;
-        let recv_stream = h2_response.into_body();
-        let stream = self.create_server_stream(recv_stream);
-
-        Ok(GrpcStreamResponse {
-            stream_id,
-            stream,
-        })
+        Ok((Request::from_parts(parts, body), Bytes::from(grpc_message)))
     }
 
     /// 创建服务端流 - 直接使用 H2 RecvStream
-    fn create_server_stream<R>(&self, mut recv_stream: RecvStream) -> Pin<Box<dyn Stream<Item = Result<GrpcStreamMessage<R>, RatError>> + Send>>
+    fn create_server_stream<R>(&self, mut recv_stream: RecvStream, encoding: Option<String>) -> Pin<Box<dyn Stream<Item = Result<GrpcStreamMessage<R>, RatError>> + Send>>
     where
         R: for<'de> Deserialize<'de> + Send + Sync + 'static + bincode::Decode<()>,
     {
@@ -253,15 +365,20 @@ impl RatGrpcClient {
                             
                                      
                             if buffer.len() >= 5 + message_length {
-                                let message_data = &buffer[5..5 + message_length];
-                                
-                                               
-                                // 检查压缩标志
-                                if compression_flag != 0 {
-                                    yield Err(RatError::DeserializationError("不支持压缩的 gRPC 消息".to_string()));
-                                    stream_ended = true;
-                                    break;
-                                }
+                                // 按压缩标志和 grpc-encoding 解压消息
+                                let payload = match super::message_utils::decompress_grpc_payload(
+                                    compression_flag != 0,
+                                    &buffer[5..5 + message_length],
+                                    encoding.as_deref(),
+                                ) {
+                                    Ok(payload) => payload,
+                                    Err(e) => {
+                                        yield Err(e);
+                                        stream_ended = true;
+                                        break;
+                                    }
+                                };
+                                let message_data: &[u8] = &payload;
                                 
                                 // 优化反序列化策略：先尝试直接反序列化为目标类型 R
                                 // 如果失败，再尝试反序列化为 GrpcStreamMessage<Vec<u8>>
@@ -349,6 +466,7 @@ impl RatGrpcClient {
                                  }
                                 
                                 // 移除已处理的数据
+                                drop(payload);
                                 buffer.drain(0..5 + message_length);
                             } else {
                                 // 数据不完整，等待更多数据
@@ -377,7 +495,8 @@ impl RatGrpcClient {
                                 if status_code != 0 {
                                     let grpc_message = trailers.get("grpc-message")
                                         .and_then(|v| v.to_str().ok())
-                                        .unwrap_or("Unknown error");
+                                        .map(GrpcCodec::decode_status_message)
+                                        .unwrap_or_else(|| "Unknown error".to_string());
                                     yield Err(RatError::Other(rat_embed_lang::tf("grpc_error_with_status", &[("status", &status_code.to_string()), ("message", &grpc_message)])));
                                 }
                             }
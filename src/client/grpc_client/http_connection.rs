@@ -29,6 +29,10 @@ use rustls::pki_types::ServerName;
 use tokio_rustls::TlsConnector;
 use crate::client::grpc_client::RatGrpcClient;
 
+/// 一元响应的 trailers，随 [`RatGrpcClient::send_h2_request`] 的响应扩展返回
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ResponseTrailers(pub HeaderMap);
+
 impl RatGrpcClient {
     async fn establish_h2_connection(&self, uri: &Uri) -> RatResult<h2::client::SendRequest<bytes::Bytes>> {
         let is_https = uri.scheme_str() == Some("https");
@@ -141,6 +145,11 @@ impl RatGrpcClient {
             let _ = body_stream.flow_control().release_capacity(chunk.len());
         }
 
+        // 读取 trailers（gRPC 状态和尾部元数据）
+        let trailers = body_stream.trailers().await
+            .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("h2_read_response_body_failed", &[("msg", &e.to_string())])))?
+            .unwrap_or_default();
+
         // 构建 Hyper 兼容的响应
         let mut response_builder = Response::builder().status(status);
 
@@ -154,6 +163,7 @@ impl RatGrpcClient {
 
         // 构建最终响应
         let response = response_builder
+            .extension(ResponseTrailers(trailers))
             .body(body)
             .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("build_response_failed", &[("msg", &e.to_string())])))?;
 
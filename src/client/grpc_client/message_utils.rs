@@ -1,5 +1,6 @@
     //! gRPC 消息处理工具模块
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 
@@ -22,6 +23,73 @@ use http_body_util::{Full, BodyExt};
 use super::GrpcCompressionMode;
 use crate::client::grpc_client::RatGrpcClient;
 
+/// 客户端能够解压的 gRPC 响应编码（通过 `grpc-accept-encoding` 告知服务器）
+pub(crate) fn grpc_accept_encoding() -> &'static str {
+    if cfg!(feature = "compression") {
+        "gzip, deflate, lz4, identity"
+    } else {
+        "identity"
+    }
+}
+
+/// 解压后 gRPC 消息的最大字节数（与帧长度限制一致）
+pub(crate) const MAX_DECOMPRESSED_MESSAGE_SIZE: usize = 100 * 1024 * 1024;
+
+/// 按帧的压缩标志和响应的 `grpc-encoding` 头部还原 gRPC 消息负载
+///
+/// 未设置压缩标志时直接返回原始数据；设置了压缩标志但服务器使用的编码
+/// 不在 [`grpc_accept_encoding`] 声明的范围内时返回错误
+pub(crate) fn decompress_grpc_payload<'a>(compressed: bool, payload: &'a [u8], encoding: Option<&str>) -> RatResult<Cow<'a, [u8]>> {
+    decompress_grpc_payload_with_limit(compressed, payload, encoding, MAX_DECOMPRESSED_MESSAGE_SIZE)
+}
+
+/// 同 [`decompress_grpc_payload`]，解压结果超过 `limit` 字节时返回错误（防止解压炸弹）
+fn decompress_grpc_payload_with_limit<'a>(compressed: bool, payload: &'a [u8], encoding: Option<&str>, limit: usize) -> RatResult<Cow<'a, [u8]>> {
+    #[cfg(not(feature = "compression"))]
+    let _ = limit;
+    if !compressed {
+        return Ok(Cow::Borrowed(payload));
+    }
+
+    let encoding = encoding.map(|e| e.trim().to_ascii_lowercase()).unwrap_or_default();
+    match encoding.as_str() {
+        "" | "identity" => Err(RatError::DecodingError(
+            "gRPC 消息设置了压缩标志，但响应未声明 grpc-encoding".to_string()
+        )),
+        #[cfg(feature = "compression")]
+        "gzip" => read_limited(flate2::read::GzDecoder::new(payload), limit, "gzip").map(Cow::Owned),
+        #[cfg(feature = "compression")]
+        "deflate" => read_limited(flate2::read::ZlibDecoder::new(payload), limit, "deflate").map(Cow::Owned),
+        #[cfg(feature = "compression")]
+        "lz4" => {
+            let decompressed = lz4_flex::block::decompress(payload, payload.len().saturating_mul(4).min(limit))
+                .map_err(|e| RatError::DecodingError(rat_embed_lang::tf("lz4_decompress_failed", &[("msg", &e.to_string())])))?;
+            Ok(Cow::Owned(decompressed))
+        }
+        other => Err(RatError::DecodingError(format!(
+            "服务器使用了客户端未声明支持的 gRPC 编码: {}（客户端支持: {}）",
+            other, grpc_accept_encoding()
+        ))),
+    }
+}
+
+/// 最多读取 `limit + 1` 字节，读满说明解压结果超过限制
+#[cfg(feature = "compression")]
+fn read_limited<R: std::io::Read>(reader: R, limit: usize, encoding: &str) -> RatResult<Vec<u8>> {
+    use std::io::Read;
+
+    let mut decompressed = Vec::new();
+    reader.take(limit as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| RatError::DecodingError(format!("gRPC 消息 {} 解压失败: {}", encoding, e)))?;
+    if decompressed.len() > limit {
+        return Err(RatError::DecodingError(format!(
+            "gRPC 消息解压后超过大小限制: {} 字节", limit
+        )));
+    }
+    Ok(decompressed)
+}
+
 impl RatGrpcClient {
     /// 构建标准 gRPC 消息格式
     ///
@@ -133,16 +201,26 @@ impl RatGrpcClient {
     /// gRPC 本身就不支持 HTTP/1.1，所以统一使用 h2 crate 处理 HTTP/2 和 H2C
     /// 直接返回响应数据，不再考虑 Hyper 兼容性
     pub async fn send_request(&self, request: Request<Full<Bytes>>) -> RatResult<(StatusCode, HeaderMap, Bytes)> {
+        let (status, headers, _trailers, body) = self.send_request_with_trailers(request).await?;
+        Ok((status, headers, body))
+    }
+
+    /// 发送请求，同时返回响应 trailers
+    pub(crate) async fn send_request_with_trailers(&self, request: Request<Full<Bytes>>) -> RatResult<(StatusCode, HeaderMap, HeaderMap, Bytes)> {
         // gRPC 统一使用 h2 依赖，根据 URI scheme 决定是否使用 TLS
         let response = self.send_h2_request(request).await?;
         
         // 直接提取响应数据
-        let (parts, body) = response.into_parts();
+        let (mut parts, body) = response.into_parts();
         let body_bytes = body.collect().await
             .map_err(|e| RatError::NetworkError(rat_embed_lang::tf("read_response_failed", &[("msg", &e.to_string())])))?
             .to_bytes();
+        let trailers = parts.extensions
+            .remove::<super::http_connection::ResponseTrailers>()
+            .map(|trailers| trailers.0)
+            .unwrap_or_default();
         
-        Ok((parts.status, parts.headers, body_bytes))
+        Ok((parts.status, parts.headers, trailers, body_bytes))
     }
     /// 解析 gRPC 响应
     pub fn parse_grpc_response<R>(&self, status: StatusCode, headers: HeaderMap, body_bytes: Bytes) -> RatResult<GrpcResponse<R>>
@@ -171,8 +249,8 @@ impl RatGrpcClient {
         let grpc_message = headers
             .get("grpc-message")
             .and_then(|v| v.to_str().ok())
-            .unwrap_or("")
-            .to_string();
+            .map(GrpcCodec::decode_status_message)
+            .unwrap_or_default();
 
         // 提取元数据（所有非标准 gRPC 头部）
         let mut metadata = std::collections::HashMap::new();
@@ -192,12 +270,15 @@ impl RatGrpcClient {
             }
         }
 
-        // 使用统一的编解码器解析帧并反序列化
+        // 使用统一的编解码器解析帧，按压缩标志和 grpc-encoding 解压后再反序列化
         let message_data = GrpcCodec::parse_frame(&body_bytes)
             .map_err(|e| RatError::DecodingError(rat_embed_lang::tf("parse_grpc_frame_failed", &[("msg", &e.to_string())])))?;
+        let compressed = body_bytes.first().is_some_and(|flag| *flag != 0);
+        let encoding = headers.get("grpc-encoding").and_then(|v| v.to_str().ok());
+        let message_data = decompress_grpc_payload(compressed, message_data, encoding)?;
 
           // 直接反序列化为最终的 R 类型，因为服务端现在发送完整的 GrpcResponse 结构
-        let response_data: R = GrpcCodec::decode(message_data)
+        let response_data: R = GrpcCodec::decode(&message_data)
             .map_err(|e| {
                 error!("❌ [客户端] GrpcCodec 反序列化最终数据类型失败: {}", e);
                 RatError::DeserializationError(rat_embed_lang::tf("deserialize_data_type_failed", &[("msg", &e.to_string())]))
@@ -208,7 +289,7 @@ impl RatGrpcClient {
             status: 0, // OK
             message: "Success".to_string(),
             data: response_data,
-            metadata: std::collections::HashMap::new(),
+            metadata,
         };
 
         Ok(grpc_response)
@@ -234,3 +315,60 @@ impl RatGrpcClient {
         self.stream_id_counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uncompressed_payload_is_borrowed() {
+        let payload = b"hello";
+        let result = decompress_grpc_payload(false, payload, Some("gzip")).unwrap();
+        assert!(matches!(result, Cow::Borrowed(_)));
+        assert_eq!(&*result, payload);
+    }
+
+    #[test]
+    fn test_compressed_flag_without_encoding() {
+        assert!(decompress_grpc_payload(true, b"data", None).is_err());
+        assert!(decompress_grpc_payload(true, b"data", Some("identity")).is_err());
+    }
+
+    #[test]
+    fn test_unadvertised_encoding() {
+        let err = decompress_grpc_payload(true, b"data", Some("snappy")).unwrap_err();
+        assert!(err.to_string().contains("snappy"));
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_gzip_round_trip() {
+        use std::io::Write;
+
+        let frame = GrpcCodec::encode_frame(&"compressed response".to_string()).unwrap();
+        let message = GrpcCodec::parse_frame(&frame).unwrap();
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(message).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let decompressed = decompress_grpc_payload(true, &compressed, Some("gzip")).unwrap();
+        let decoded: String = GrpcCodec::decode(&decompressed).unwrap();
+        assert_eq!(decoded, "compressed response");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_gzip_decompression_limit() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&vec![0u8; 64 * 1024]).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let err = decompress_grpc_payload_with_limit(true, &compressed, Some("gzip"), 1024).unwrap_err();
+        assert!(err.to_string().contains("1024"));
+        let exact = decompress_grpc_payload_with_limit(true, &compressed, Some("gzip"), 64 * 1024).unwrap();
+        assert_eq!(exact.len(), 64 * 1024);
+    }
+}
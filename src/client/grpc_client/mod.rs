@@ -11,11 +11,13 @@ pub use grpc_calls::*;
 pub use grpc_bidirectional_stream::*;
 pub use grpc_client_stream::*;
 pub use grpc_server_stream::*;
+pub use grpc_cancellation::CancellationToken;
 pub use message_utils::*;
 pub use http_connection::*;
 
 // 重新导出 gRPC 类型以保持向后兼容性
 pub use crate::server::grpc_types::{GrpcRequest, GrpcResponse, GrpcStreamMessage};
+pub use crate::server::grpc_metadata::GrpcResponseMetadata;
 
 // Python集成模块只在启用python特性时导出
 #[cfg(feature = "python")]
@@ -30,6 +32,7 @@ mod grpc_calls;
 mod grpc_bidirectional_stream;
 mod grpc_client_stream;
 mod grpc_server_stream;
+mod grpc_cancellation;
 mod message_utils;
 mod http_connection;
 
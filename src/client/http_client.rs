@@ -0,0 +1,379 @@
+//! RAT Engine HTTP 客户端
+//!
+//! 基于 hyper HTTP/1.1 的出站客户端，供服务端处理器调用外部 HTTP 服务：
+//! - 按 `scheme://authority` 复用空闲连接，[`RatHttpClient::with_compression`] 派生的客户端共享同一个连接池
+//! - TLS 使用 rustls，默认系统证书验证，支持 mTLS（与 gRPC 客户端一致）
+//! - 可选压缩协商并自动解压响应体，响应体（含解压后）大小受 `max_response_size` 限制
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use hyper::{Request, Uri};
+use hyper::body::Bytes;
+use hyper::client::conn::http1::SendRequest;
+use hyper::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT_ENCODING, CONNECTION, CONTENT_ENCODING, CONTENT_LENGTH, HOST, USER_AGENT};
+use hyper_util::rt::TokioIo;
+use http_body_util::{BodyExt, Full, Limited};
+use rustls::ClientConfig;
+use rustls::pki_types::ServerName;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+
+use crate::error::{RatError, RatResult};
+use crate::client::builder::RatHttpClientBuilder;
+use crate::client::types::{HttpHeaders, HttpMethod, HttpRequestBuilder, HttpStatusCode};
+use crate::utils::logger::debug;
+
+type PooledSender = SendRequest<Full<Bytes>>;
+
+/// RAT Engine HTTP 客户端
+///
+/// 克隆开销很小，多个处理器可共享同一个实例及其连接池
+#[derive(Clone)]
+pub struct RatHttpClient {
+    inner: Arc<ClientInner>,
+}
+
+#[derive(Clone)]
+struct ClientInner {
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    max_idle_per_host: usize,
+    max_response_size: usize,
+    user_agent: String,
+    default_headers: HeaderMap,
+    compression: bool,
+    tls_config: Arc<ClientConfig>,
+    /// 空闲连接池（key: scheme://authority），派生的客户端共享
+    idle: Arc<DashMap<String, Vec<PooledSender>>>,
+}
+
+/// HTTP 响应
+#[derive(Debug, Clone)]
+pub struct RatHttpResponse {
+    /// 状态码
+    pub status: HttpStatusCode,
+    /// 响应头（名称均为小写）
+    pub headers: HttpHeaders,
+    /// 响应体（已按需解压）
+    pub body: Bytes,
+}
+
+impl RatHttpResponse {
+    /// 是否为成功状态码（2xx）
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    /// 获取响应头
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(|v| v.as_str())
+    }
+
+    /// 以 UTF-8 文本读取响应体
+    pub fn text(&self) -> RatResult<String> {
+        String::from_utf8(self.body.to_vec())
+            .map_err(|e| RatError::DecodingError(format!("响应体不是有效的 UTF-8: {}", e)))
+    }
+
+    /// 将响应体反序列化为 JSON
+    pub fn json<T: DeserializeOwned>(&self) -> RatResult<T> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| RatError::DeserializationError(format!("解析 JSON 响应失败: {}", e)))
+    }
+}
+
+impl std::fmt::Debug for RatHttpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RatHttpClient")
+            .field("connect_timeout", &self.inner.connect_timeout)
+            .field("request_timeout", &self.inner.request_timeout)
+            .field("max_idle_per_host", &self.inner.max_idle_per_host)
+            .field("max_response_size", &self.inner.max_response_size)
+            .field("user_agent", &self.inner.user_agent)
+            .field("compression", &self.inner.compression)
+            .finish()
+    }
+}
+
+impl RatHttpClient {
+    /// 由构建器调用
+    pub(crate) fn new(
+        connect_timeout: Duration,
+        request_timeout: Duration,
+        max_idle_per_host: usize,
+        max_response_size: usize,
+        user_agent: String,
+        default_headers: HeaderMap,
+        compression: bool,
+        tls_config: Arc<ClientConfig>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(ClientInner {
+                connect_timeout,
+                request_timeout,
+                max_idle_per_host,
+                max_response_size,
+                user_agent,
+                default_headers,
+                compression,
+                tls_config,
+                idle: Arc::new(DashMap::new()),
+            }),
+        }
+    }
+
+    /// 派生一个启用或禁用压缩协商的客户端
+    ///
+    /// 其余配置保持不变，并与当前客户端共享连接池，无需为压缩单独建立连接
+    pub fn with_compression(&self, enabled: bool) -> Self {
+        Self {
+            inner: Arc::new(ClientInner {
+                compression: enabled,
+                ..(*self.inner).clone()
+            }),
+        }
+    }
+
+    /// 创建构建器
+    pub fn builder() -> RatHttpClientBuilder {
+        RatHttpClientBuilder::new()
+    }
+
+    /// 发送 GET 请求
+    pub async fn get(&self, url: &str) -> RatResult<RatHttpResponse> {
+        self.request(HttpRequestBuilder::new(HttpMethod::Get, url)).await
+    }
+
+    /// 发送 POST 请求
+    pub async fn post(&self, url: &str, body: impl Into<Vec<u8>>) -> RatResult<RatHttpResponse> {
+        self.request(HttpRequestBuilder::new(HttpMethod::Post, url).body(body)).await
+    }
+
+    /// 发送 POST JSON 请求
+    pub async fn post_json<T: Serialize>(&self, url: &str, data: &T) -> RatResult<RatHttpResponse> {
+        let request = HttpRequestBuilder::new(HttpMethod::Post, url)
+            .json(data)
+            .map_err(|e| RatError::SerializationError(format!("序列化请求体失败: {}", e)))?;
+        self.request(request).await
+    }
+
+    /// 发送 PUT 请求
+    pub async fn put(&self, url: &str, body: impl Into<Vec<u8>>) -> RatResult<RatHttpResponse> {
+        self.request(HttpRequestBuilder::new(HttpMethod::Put, url).body(body)).await
+    }
+
+    /// 发送 DELETE 请求
+    pub async fn delete(&self, url: &str) -> RatResult<RatHttpResponse> {
+        self.request(HttpRequestBuilder::new(HttpMethod::Delete, url)).await
+    }
+
+    /// 发送任意请求
+    ///
+    /// 整个请求（建连、发送、读取响应体）受 `request_timeout` 限制
+    pub async fn request(&self, request: HttpRequestBuilder) -> RatResult<RatHttpResponse> {
+        let uri: Uri = request.url.parse()
+            .map_err(|e| RatError::RequestError(format!("无效的 URL '{}': {}", request.url, e)))?;
+        let scheme = uri.scheme_str().unwrap_or("http");
+        if scheme != "http" && scheme != "https" {
+            return Err(RatError::RequestError(format!("不支持的协议: {}", scheme)));
+        }
+        let authority = uri.authority()
+            .ok_or_else(|| RatError::RequestError("URL 必须包含主机".to_string()))?
+            .clone();
+        let pool_key = format!("{}://{}", scheme, authority);
+        let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+
+        let body = Bytes::from(request.body.unwrap_or_default());
+        let body_len = body.len();
+        let mut hyper_request = Request::builder()
+            .method(hyper::Method::from(request.method))
+            .uri(path)
+            .body(Full::new(body))
+            .map_err(|e| RatError::RequestError(format!("构建请求失败: {}", e)))?;
+
+        let headers = hyper_request.headers_mut();
+        headers.extend(self.inner.default_headers.clone());
+        headers.insert(HOST, HeaderValue::from_str(authority.as_str())
+            .map_err(|e| RatError::RequestError(format!("无效的主机: {}", e)))?);
+        if let Ok(value) = HeaderValue::from_str(&self.inner.user_agent) {
+            headers.insert(USER_AGENT, value);
+        }
+        if self.inner.compression {
+            if let Some(value) = accept_encoding() {
+                headers.insert(ACCEPT_ENCODING, HeaderValue::from_static(value));
+            }
+        }
+        for (name, value) in &request.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| RatError::RequestError(format!("无效的请求头名称 '{}': {}", name, e)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| RatError::RequestError(format!("无效的请求头值: {}", e)))?;
+            headers.insert(name, value);
+        }
+        if body_len > 0 {
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(body_len));
+        }
+
+        tokio::time::timeout(self.inner.request_timeout, self.execute(&pool_key, &uri, hyper_request))
+            .await
+            .map_err(|_| RatError::TimeoutError(format!("请求超时: {}", request.url)))?
+    }
+
+    /// 发送请求并读取完整响应
+    async fn execute(&self, pool_key: &str, uri: &Uri, request: Request<Full<Bytes>>) -> RatResult<RatHttpResponse> {
+        let mut sender = match self.take_idle(pool_key).await {
+            Some(sender) => sender,
+            None => self.connect(uri).await?,
+        };
+
+        let response = sender.send_request(request).await
+            .map_err(|e| RatError::NetworkError(format!("发送 HTTP 请求失败: {}", e)))?;
+        let (parts, body) = response.into_parts();
+        let max_response_size = self.inner.max_response_size;
+        let body = Limited::new(body, max_response_size).collect().await
+            .map_err(|e| match e.downcast::<http_body_util::LengthLimitError>() {
+                Ok(_) => RatError::NetworkError(format!("响应体超过大小限制 {} 字节", max_response_size)),
+                Err(e) => RatError::NetworkError(format!("读取响应体失败: {}", e)),
+            })?
+            .to_bytes();
+
+        let keep_alive = parts.headers.get(CONNECTION)
+            .and_then(|v| v.to_str().ok())
+            .is_none_or(|v| !v.eq_ignore_ascii_case("close"));
+        if keep_alive {
+            self.return_idle(pool_key, sender);
+        }
+
+        let content_encoding = parts.headers.get(CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_ascii_lowercase());
+        let (body, decoded) = match content_encoding {
+            Some(encoding) if self.inner.compression && encoding != "identity" => (decompress(&encoding, &body, max_response_size)?, true),
+            _ => (body, false),
+        };
+
+        let mut headers = HashMap::new();
+        for (name, value) in &parts.headers {
+            if decoded && (name == CONTENT_ENCODING || name == CONTENT_LENGTH) {
+                continue;
+            }
+            if let Ok(value) = value.to_str() {
+                headers.entry(name.as_str().to_string())
+                    .and_modify(|existing: &mut String| {
+                        existing.push_str(", ");
+                        existing.push_str(value);
+                    })
+                    .or_insert_with(|| value.to_string());
+            }
+        }
+
+        debug!("📥 [HTTP客户端] {} {} - {} 字节", parts.status, uri, body.len());
+
+        Ok(RatHttpResponse {
+            status: parts.status.into(),
+            headers,
+            body,
+        })
+    }
+
+    /// 取出一个可用的空闲连接，已关闭的连接直接丢弃
+    async fn take_idle(&self, pool_key: &str) -> Option<PooledSender> {
+        loop {
+            let mut sender = self.inner.idle.get_mut(pool_key)?.pop()?;
+            if !sender.is_closed() && sender.ready().await.is_ok() {
+                return Some(sender);
+            }
+        }
+    }
+
+    /// 归还连接到空闲池
+    fn return_idle(&self, pool_key: &str, sender: PooledSender) {
+        if self.inner.max_idle_per_host == 0 || sender.is_closed() {
+            return;
+        }
+        let mut idle = self.inner.idle.entry(pool_key.to_string()).or_default();
+        if idle.len() < self.inner.max_idle_per_host {
+            idle.push(sender);
+        }
+    }
+
+    /// 建立新连接（HTTPS 先完成 TLS 握手）
+    async fn connect(&self, uri: &Uri) -> RatResult<PooledSender> {
+        let is_https = uri.scheme_str() == Some("https");
+        let host = uri.host().ok_or_else(|| RatError::RequestError("URL 缺少主机".to_string()))?;
+        let port = uri.port_u16().unwrap_or(if is_https { 443 } else { 80 });
+        let addr = format!("{}:{}", host, port);
+
+        let tcp_stream = tokio::time::timeout(self.inner.connect_timeout, TcpStream::connect(&addr))
+            .await
+            .map_err(|_| RatError::TimeoutError(format!("连接超时: {}", addr)))?
+            .map_err(|e| RatError::NetworkError(format!("连接 {} 失败: {}", addr, e)))?;
+        let _ = tcp_stream.set_nodelay(true);
+
+        debug!("🔗 [HTTP客户端] 建立新连接: {} ({})", addr, if is_https { "HTTPS" } else { "HTTP" });
+
+        if is_https {
+            let server_name = ServerName::try_from(host.trim_start_matches('[').trim_end_matches(']').to_string())
+                .map_err(|e| RatError::TlsError(format!("无效的服务器名称: {}", e)))?;
+            let tls_stream = TlsConnector::from(self.inner.tls_config.clone())
+                .connect(server_name, tcp_stream)
+                .await
+                .map_err(|e| RatError::NetworkError(format!("TLS 握手失败: {}", e)))?;
+            Self::handshake(tls_stream).await
+        } else {
+            Self::handshake(tcp_stream).await
+        }
+    }
+
+    async fn handshake<S>(stream: S) -> RatResult<PooledSender>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| RatError::NetworkError(format!("HTTP/1.1 握手失败: {}", e)))?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                debug!("[HTTP客户端] 连接结束: {}", e);
+            }
+        });
+        Ok(sender)
+    }
+}
+
+/// 根据启用的压缩特性生成 Accept-Encoding
+fn accept_encoding() -> Option<&'static str> {
+    if !cfg!(feature = "compression") {
+        return None;
+    }
+    Some(match (cfg!(feature = "compression-br"), cfg!(feature = "compression-zstd")) {
+        (true, true) => "br, zstd, gzip, deflate",
+        (true, false) => "br, gzip, deflate",
+        (false, true) => "zstd, gzip, deflate",
+        (false, false) => "gzip, deflate",
+    })
+}
+
+/// 按 Content-Encoding 解压响应体，解压后超过 `limit` 字节时返回错误
+fn decompress(encoding: &str, body: &Bytes, limit: usize) -> RatResult<Bytes> {
+    #[cfg(feature = "compression")]
+    {
+        let algorithm = crate::compression::CompressionType::from_str(encoding)
+            .ok_or_else(|| RatError::DecodingError(format!("不支持的响应编码: {}", encoding)))?;
+        crate::compression::Compressor::default()
+            .decompress_with_limit(body, algorithm, limit)
+            .map(Bytes::from)
+            .map_err(|e| RatError::DecodingError(format!("解压响应体失败: {}", e)))
+    }
+    #[cfg(not(feature = "compression"))]
+    {
+        let _ = (body, limit);
+        Err(RatError::DecodingError(format!("未启用 compression 特性，无法解压响应编码: {}", encoding)))
+    }
+}
@@ -51,14 +51,18 @@ pub mod grpc_client_delegated;
 pub mod download_metadata;
 pub mod types;
 pub mod connection_pool;
+#[cfg(any(feature = "client", feature = "http-client"))]
+pub mod builder;
+#[cfg(any(feature = "client", feature = "http-client"))]
+pub mod http_client;
 
 #[cfg(feature = "reqwest")]
 pub mod independent_http_client;
 
-// #[cfg(any(feature = "client", feature = "http-client"))]
-// pub use builder::RatHttpClientBuilder;  // 已移除HTTP客户端，只保留gRPC客户端
-// #[cfg(any(feature = "client", feature = "http-client"))]
-// pub use http_client::{RatHttpClient, RatHttpResponse};  // 已移除HTTP客户端，只保留gRPC客户端
+#[cfg(any(feature = "client", feature = "http-client"))]
+pub use builder::RatHttpClientBuilder;
+#[cfg(any(feature = "client", feature = "http-client"))]
+pub use http_client::{RatHttpClient, RatHttpResponse};
 // #[cfg(any(feature = "client", feature = "http-client"))]
 // pub use http_client_delegated::{HttpRequestHandler, HttpRequestManager};  // 已移除HTTP客户端，只保留gRPC客户端
 
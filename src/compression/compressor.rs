@@ -43,21 +43,31 @@ impl Compressor {
         }
     }
 
+    /// 获取创建压缩器时使用的配置
+    pub fn config(&self) -> &CompressionConfig {
+        &self.config
+    }
+
     /// 压缩数据
     pub fn compress(&self, data: &[u8], algorithm: CompressionType) -> Result<Vec<u8>, String> {
+        self.compress_with_level(data, algorithm, self.level)
+    }
+
+    /// 使用指定级别压缩数据
+    pub fn compress_with_level(&self, data: &[u8], algorithm: CompressionType, level: u32) -> Result<Vec<u8>, String> {
         match algorithm {
             CompressionType::None => Ok(data.to_vec()),
-            CompressionType::Gzip => self.compress_gzip(data),
-            CompressionType::Deflate => self.compress_deflate(data),
+            CompressionType::Gzip => self.compress_gzip(data, level),
+            CompressionType::Deflate => self.compress_deflate(data, level),
             CompressionType::Brotli => {
                 #[cfg(feature = "compression-br")]
-                { self.compress_brotli(data) }
+                { self.compress_brotli(data, level) }
                 #[cfg(not(feature = "compression-br"))]
                 { Err("Brotli compression not enabled".to_string()) }
             },
             CompressionType::Zstd => {
                 #[cfg(feature = "compression-zstd")]
-                { self.compress_zstd(data) }
+                { self.compress_zstd(data, level) }
                 #[cfg(not(feature = "compression-zstd"))]
                 { Err("Zstd compression not enabled".to_string()) }
             },
@@ -97,15 +107,72 @@ impl Compressor {
         }
     }
 
+    /// 解压数据，解压后超过 `limit` 字节时返回错误
+    ///
+    /// 用于解压不可信的数据（例如出站请求的响应体），避免解压炸弹耗尽内存
+    pub fn decompress_with_limit(&self, data: &[u8], algorithm: CompressionType, limit: usize) -> Result<Vec<u8>, String> {
+        match algorithm {
+            CompressionType::None => {
+                if data.len() > limit {
+                    return Err(format!("Decompressed size exceeds limit of {} bytes", limit));
+                }
+                Ok(data.to_vec())
+            },
+            CompressionType::Gzip => {
+                #[cfg(feature = "compression")]
+                { read_limited(flate2::read::GzDecoder::new(data), limit, "Gzip") }
+                #[cfg(not(feature = "compression"))]
+                { Err("Gzip decompression not enabled".to_string()) }
+            },
+            CompressionType::Deflate => {
+                #[cfg(feature = "compression")]
+                { read_limited(flate2::read::DeflateDecoder::new(data), limit, "Deflate") }
+                #[cfg(not(feature = "compression"))]
+                { Err("Deflate decompression not enabled".to_string()) }
+            },
+            CompressionType::Brotli => {
+                #[cfg(feature = "compression-br")]
+                { read_limited(brotli::Decompressor::new(data, 4096), limit, "Brotli") }
+                #[cfg(not(feature = "compression-br"))]
+                { Err("Brotli decompression not enabled".to_string()) }
+            },
+            CompressionType::Zstd => {
+                #[cfg(feature = "compression-zstd")]
+                {
+                    let decoder = zstd::stream::read::Decoder::new(data)
+                        .map_err(|e| format!("Zstd decompression error: {}", e))?;
+                    read_limited(decoder, limit, "Zstd")
+                }
+                #[cfg(not(feature = "compression-zstd"))]
+                { Err("Zstd decompression not enabled".to_string()) }
+            },
+            CompressionType::Lz4 => {
+                #[cfg(feature = "compression")]
+                {
+                    // 前置的长度字段决定了分配大小，先校验再解压
+                    let size = data.get(..4)
+                        .map(|prefix| u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize)
+                        .ok_or_else(|| "LZ4 decompression error: missing size prefix".to_string())?;
+                    if size > limit {
+                        return Err(format!("Decompressed size exceeds limit of {} bytes", limit));
+                    }
+                    self.decompress_lz4(data)
+                }
+                #[cfg(not(feature = "compression"))]
+                { Err("LZ4 decompression not enabled".to_string()) }
+            },
+        }
+    }
+
     // Gzip 压缩
-    fn compress_gzip(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+    fn compress_gzip(&self, data: &[u8], level: u32) -> Result<Vec<u8>, String> {
         #[cfg(feature = "compression")]
         {
             use std::io::Write;
             use flate2::write::GzEncoder;
             use flate2::Compression;
 
-            let mut encoder = GzEncoder::new(Vec::new(), Compression::new(self.level));
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level));
             encoder.write_all(data).map_err(|e| format!("Gzip compression error: {}", e))?;
             encoder.finish().map_err(|e| format!("Gzip finish error: {}", e))
         }
@@ -134,14 +201,14 @@ impl Compressor {
     }
 
     // Deflate 压缩
-    fn compress_deflate(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+    fn compress_deflate(&self, data: &[u8], level: u32) -> Result<Vec<u8>, String> {
         #[cfg(feature = "compression")]
         {
             use std::io::Write;
             use flate2::write::DeflateEncoder;
             use flate2::Compression;
 
-            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::new(self.level));
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::new(level));
             encoder.write_all(data).map_err(|e| format!("Deflate compression error: {}", e))?;
             encoder.finish().map_err(|e| format!("Deflate finish error: {}", e))
         }
@@ -171,12 +238,18 @@ impl Compressor {
 
     // Brotli 压缩
     #[cfg(feature = "compression-br")]
-    fn compress_brotli(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+    fn compress_brotli(&self, data: &[u8], level: u32) -> Result<Vec<u8>, String> {
+        self.compress_brotli_with(data, super::config::BrotliSettings::new(level, super::config::DEFAULT_BROTLI_WINDOW))
+    }
+
+    /// 使用指定的质量和窗口进行 Brotli 压缩
+    #[cfg(feature = "compression-br")]
+    pub fn compress_brotli_with(&self, data: &[u8], settings: super::config::BrotliSettings) -> Result<Vec<u8>, String> {
         use brotli::enc::BrotliEncoderParams;
 
         let mut params = BrotliEncoderParams::default();
-        params.quality = self.level as i32;
-        params.lgwin = 22; // 窗口大小，推荐 20-22
+        params.quality = settings.quality as i32;
+        params.lgwin = settings.window as i32;
 
         let mut output = Vec::new();
         brotli::BrotliCompress(&mut &data[..], &mut output, &params)
@@ -196,8 +269,8 @@ impl Compressor {
 
     // Zstd 压缩
     #[cfg(feature = "compression-zstd")]
-    fn compress_zstd(&self, data: &[u8]) -> Result<Vec<u8>, String> {
-        zstd::encode_all(data, self.level as i32)
+    fn compress_zstd(&self, data: &[u8], level: u32) -> Result<Vec<u8>, String> {
+        zstd::encode_all(data, level as i32)
             .map_err(|e| format!("Zstd compression error: {}", e))
     }
 
@@ -223,6 +296,21 @@ impl Compressor {
     }
 }
 
+/// 最多读取 `limit + 1` 字节，多出的一个字节说明解压结果超过限制
+#[cfg(feature = "compression")]
+fn read_limited<R: std::io::Read>(reader: R, limit: usize, name: &str) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut decompressed = Vec::new();
+    reader.take(limit as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| format!("{} decompression error: {}", name, e))?;
+    if decompressed.len() > limit {
+        return Err(format!("Decompressed size exceeds limit of {} bytes", limit));
+    }
+    Ok(decompressed)
+}
+
 impl Default for Compressor {
     fn default() -> Self {
         Self::new(CompressionConfig::default())
@@ -235,6 +323,20 @@ impl Compressor {
         response: Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>,
         accept_encoding: &str,
         file_ext: &str,
+    ) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+        self.compress_response_under_load(response, accept_encoding, file_ext, 0).await
+    }
+
+    /// 按当前负载压缩 HTTP 响应
+    ///
+    /// `load` 为正在处理的请求数；启用自适应压缩时据此降低压缩级别或跳过压缩，
+    /// 未启用时与 [`Compressor::compress_response`] 行为一致
+    pub async fn compress_response_under_load(
+        &self,
+        response: Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>,
+        accept_encoding: &str,
+        file_ext: &str,
+        load: usize,
     ) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
         use bytes::BytesMut;
         use http_body_util::BodyExt;
@@ -250,6 +352,13 @@ impl Compressor {
             return Ok(Response::from_parts(parts, body));
         }
 
+        // 自适应压缩：负载过高时直接跳过，避免压缩占用 CPU
+        let Some(level) = self.config.level_for_load(load) else {
+            #[cfg(feature = "compression")]
+            crate::utils::logger::debug!("🔥 [Compression] 当前负载 {} 过高，跳过压缩", load);
+            return Ok(Response::from_parts(parts, body));
+        };
+
         // 获取内容类型
         let content_type = parts.headers.get("content-type")
             .and_then(|v| v.to_str().ok());
@@ -297,8 +406,16 @@ impl Compressor {
             return Ok(Response::from_parts(parts, boxed_body));
         }
 
-        // 压缩数据
-        match self.compress(&data, algorithm) {
+        // 压缩数据：Brotli 按响应是否可缓存选择质量和窗口
+        let compressed = match algorithm {
+            #[cfg(feature = "compression-br")]
+            CompressionType::Brotli => {
+                let cacheable = CompressionConfig::is_cacheable_response(&parts.headers);
+                self.compress_brotli_with(&data, self.config.brotli_settings(cacheable, level))
+            }
+            _ => self.compress_with_level(&data, algorithm, level),
+        };
+        match compressed {
             Ok(compressed) => {
                 // 获取压缩前后的大小，用于日志记录
                 let original_size = data.len();
@@ -4,6 +4,28 @@ use std::collections::HashSet;
 use hyper::header::HeaderMap;
 use super::types::CompressionType;
 
+/// 未单独配置时使用的 Brotli 窗口大小（lgwin）
+pub const DEFAULT_BROTLI_WINDOW: u32 = 22;
+
+/// Brotli 编码参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrotliSettings {
+    /// 质量 (0-11，越大压缩率越高但速度越慢)
+    pub quality: u32,
+    /// 窗口大小的以 2 为底的对数 (10-24)
+    pub window: u32,
+}
+
+impl BrotliSettings {
+    /// 创建 Brotli 参数，超出范围的取值会被截断到合法区间
+    pub fn new(quality: u32, window: u32) -> Self {
+        Self {
+            quality: quality.min(11),
+            window: window.clamp(10, 24),
+        }
+    }
+}
+
 /// 压缩配置
 #[derive(Debug, Clone)]
 pub struct CompressionConfig {
@@ -19,6 +41,16 @@ pub struct CompressionConfig {
     pub excluded_extensions: HashSet<String>,
     /// 是否启用智能压缩决策
     pub enable_smart_compression: bool,
+    /// 自适应压缩的负载阈值（正在处理的请求数，None 表示不启用）
+    ///
+    /// 负载达到阈值时降为最快的压缩级别，达到阈值两倍时跳过压缩，以带宽换取 CPU 余量。
+    /// 负载来自路由器的性能指标收集器（通过 `RatEngine` 构建时自动设置），
+    /// 路由器没有指标收集器时自适应不生效，始终按 `level` 压缩
+    pub adaptive_threshold: Option<usize>,
+    /// 可缓存响应（静态资源）的 Brotli 参数，None 时与动态响应一样按 `level` 压缩
+    pub brotli_static: Option<BrotliSettings>,
+    /// 动态响应的 Brotli 参数，None 时按 `level` 压缩
+    pub brotli_dynamic: Option<BrotliSettings>,
 }
 
 impl Default for CompressionConfig {
@@ -58,6 +90,9 @@ impl Default for CompressionConfig {
             enable_smart_compression: true, // 启用压缩特性时才启用智能压缩
             #[cfg(not(feature = "compression"))]
             enable_smart_compression: false, // 没有压缩特性时禁用智能压缩
+            adaptive_threshold: None,
+            brotli_static: None,
+            brotli_dynamic: None,
         }
     }
 }
@@ -189,6 +224,85 @@ impl CompressionConfig {
         self
     }
 
+    /// 启用基于负载的自适应压缩
+    ///
+    /// `threshold` 为正在处理的请求数阈值：达到阈值时使用级别 1 压缩，
+    /// 达到两倍阈值时不再压缩。
+    ///
+    /// 需要路由器设置了性能指标收集器（见 `Router::set_metrics`，`RatEngine` 构建时自动设置），
+    /// 否则无法获得负载，退回按静态的 `level` 压缩
+    pub fn adaptive(mut self, threshold: usize) -> Self {
+        self.adaptive_threshold = Some(threshold.max(1));
+        self
+    }
+
+    /// 设置可缓存响应（静态资源）的 Brotli 质量和窗口
+    ///
+    /// 可缓存的响应只压缩一次就会被重复使用，适合用质量 11 换取更高的压缩率。
+    /// 是否可缓存由响应的 `Cache-Control` 判断，见 [`CompressionConfig::is_cacheable_response`]
+    pub fn brotli_static(mut self, quality: u32, window: u32) -> Self {
+        self.brotli_static = Some(BrotliSettings::new(quality, window));
+        self
+    }
+
+    /// 设置动态响应的 Brotli 质量和窗口
+    pub fn brotli_dynamic(mut self, quality: u32, window: u32) -> Self {
+        self.brotli_dynamic = Some(BrotliSettings::new(quality, window));
+        self
+    }
+
+    /// 确定本次压缩使用的 Brotli 参数
+    ///
+    /// `level` 为 [`CompressionConfig::level_for_load`] 给出的级别；
+    /// 自适应压缩降级时质量同样降到该级别，只保留配置的窗口大小
+    pub fn brotli_settings(&self, cacheable: bool, level: u32) -> BrotliSettings {
+        let configured = if cacheable { self.brotli_static } else { self.brotli_dynamic };
+        match configured {
+            Some(settings) if level >= self.level => settings,
+            Some(settings) => BrotliSettings::new(level, settings.window),
+            None => BrotliSettings::new(level, DEFAULT_BROTLI_WINDOW),
+        }
+    }
+
+    /// 响应是否可被缓存复用（按静态资源对待）
+    ///
+    /// `Cache-Control` 含 `public`、`immutable` 或正数的 `max-age`/`s-maxage`，
+    /// 且不含 `no-store`、`no-cache`、`private` 时视为可缓存
+    pub fn is_cacheable_response(headers: &HeaderMap) -> bool {
+        let Some(cache_control) = headers.get("cache-control").and_then(|v| v.to_str().ok()) else {
+            return false;
+        };
+
+        let mut cacheable = false;
+        for directive in cache_control.split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim().to_string(), Some(value.trim().trim_matches('"').to_string())),
+                None => (directive.clone(), None),
+            };
+            match name.as_str() {
+                "no-store" | "no-cache" | "private" => return false,
+                "public" | "immutable" => cacheable = true,
+                "max-age" | "s-maxage" => {
+                    if value.and_then(|v| v.parse::<u64>().ok()).unwrap_or(0) > 0 {
+                        cacheable = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        cacheable
+    }
+
+    /// 根据当前负载确定压缩级别，返回 `None` 表示应跳过压缩
+    pub fn level_for_load(&self, load: usize) -> Option<u32> {
+        match self.adaptive_threshold {
+            Some(threshold) if load >= threshold.saturating_mul(2) => None,
+            Some(threshold) if load >= threshold => Some(1),
+            _ => Some(self.level),
+        }
+    }
+
     /// 智能压缩决策 - 检查数据是否值得压缩
     /// 使用字节频率分析来估算数据是否值得压缩
     #[cfg(feature = "compression")]
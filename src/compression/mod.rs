@@ -11,5 +11,5 @@ pub mod utils;
 
 // 重新导出主要的公共类型
 pub use types::CompressionType;
-pub use config::CompressionConfig;
+pub use config::{CompressionConfig, BrotliSettings};
 pub use compressor::Compressor;
\ No newline at end of file
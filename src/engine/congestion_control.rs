@@ -479,10 +479,16 @@ impl CongestionControlManager {
     }
     
     /// 获取拥塞控制统计信息
+    ///
+    /// 拥塞控制未启用（配置关闭或初始化失败）时返回空表：
+    /// 此时窗口和速率只是占位的默认值，RTT、带宽等指标也不会被更新，不应当作真实数据上报
     pub fn get_stats(&self) -> std::collections::HashMap<String, f64> {
         let mut stats = std::collections::HashMap::new();
+        if self.controller.is_none() {
+            return stats;
+        }
         
-        stats.insert("enabled".to_string(), if self.controller.is_some() { 1.0 } else { 0.0 });
+        stats.insert("enabled".to_string(), 1.0);
         stats.insert("window_size".to_string(), self.window_size() as f64);
         stats.insert("pacing_rate".to_string(), self.pacing_rate() as f64);
         stats.insert("rtt_us".to_string(), self.network_metrics.current_rtt_us.load(Ordering::Relaxed) as f64);
@@ -496,4 +502,25 @@ impl CongestionControlManager {
     pub fn is_enabled(&self) -> bool {
         self.controller.is_some()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_stats_are_empty() {
+        let config = CongestionControlConfig {
+            enabled: false,
+            algorithm: "auto".to_string(),
+            auto_switching: true,
+            platform_optimized: true,
+            metrics_window_size: 32,
+            switch_cooldown_ms: 1000,
+        };
+        let manager = CongestionControlManager::new(config, Arc::new(AtomicMetrics::new()));
+        assert!(!manager.is_enabled());
+        assert!(manager.get_stats().is_empty());
+        assert_eq!(manager.current_algorithm(), "disabled");
+    }
+}
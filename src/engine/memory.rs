@@ -188,6 +188,27 @@ impl MemoryPool {
         self.stats.xlarge_pool_size.store(self.config.initial_capacity / 10, Ordering::Relaxed);
     }
     
+    /// 预热池中的缓冲区
+    ///
+    /// 把每个空闲缓冲区完整写一遍后归还，提前触发缺页，返回写入的字节数
+    pub fn warmup(&self) -> usize {
+        let mut touched = 0;
+        for pool in [&self.small_buffers, &self.medium_buffers, &self.large_buffers, &self.xlarge_buffers] {
+            let mut buffers = Vec::with_capacity(pool.len());
+            while let Some(mut buffer) = pool.pop() {
+                let capacity = buffer.capacity();
+                buffer.resize(capacity, 0);
+                buffer.clear();
+                touched += capacity;
+                buffers.push(buffer);
+            }
+            for buffer in buffers {
+                pool.push(buffer);
+            }
+        }
+        touched
+    }
+    
     /// 获取缓冲区
     pub fn get_buffer(&self) -> BytesMut {
         self.get_buffer_with_size(self.config.medium_size)
@@ -438,6 +459,22 @@ mod tests {
         pool.return_buffer(large);
     }
     
+    #[test]
+    fn test_warmup_keeps_pool_sizes() {
+        let pool = MemoryPool::with_config(MemoryPoolConfig {
+            initial_capacity: 4,
+            ..Default::default()
+        });
+        let before = pool.get_stats();
+
+        let touched = pool.warmup();
+        assert!(touched >= 4 * (1024 + 8192 + 65536));
+
+        let after = pool.get_stats();
+        assert_eq!(after.medium_pool_size, before.medium_pool_size);
+        assert_eq!(pool.get_buffer().capacity(), 8192);
+    }
+    
     #[test]
     fn test_pooled_buffer() {
         let pool = Arc::new(MemoryPool::new(1024));
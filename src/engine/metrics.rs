@@ -4,11 +4,13 @@
 //! - 原子计数器
 //! - 延迟统计
 //! - 吞吐量监控
+//! - 连接复用（keep-alive）统计
 //! - 实时性能报告
 
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// 原子性能监控器
 /// 
@@ -24,6 +26,24 @@ pub struct AtomicMetrics {
     connection_count: AtomicU64,
     /// 活跃连接数
     active_connections: AtomicUsize,
+    /// 正在处理的请求数
+    active_requests: AtomicUsize,
+    /// 因队列饱和被拒绝的请求数
+    rejected_requests: AtomicU64,
+    /// 客户端在响应产生前断开的请求数
+    client_disconnects: AtomicU64,
+    /// TLS 握手超时数
+    tls_handshake_timeouts: AtomicU64,
+    /// TLS 握手失败数（不含超时）
+    tls_handshake_failures: AtomicU64,
+    /// 因 RST_STREAM 洪泛被关闭的 HTTP/2 连接数
+    h2_flood_closures: AtomicU64,
+    /// 缓存命中数
+    cache_hits: AtomicU64,
+    /// 命中过期缓存（stale-while-revalidate）的次数
+    cache_stale_hits: AtomicU64,
+    /// 缓存未命中数
+    cache_misses: AtomicU64,
     
     /// 延迟统计
     latency_stats: LatencyStats,
@@ -31,6 +51,12 @@ pub struct AtomicMetrics {
     /// 吞吐量统计
     throughput_stats: ThroughputStats,
     
+    /// 新建连接速率统计
+    connection_rate_stats: ThroughputStats,
+    
+    /// 连接复用统计
+    reuse_stats: ReuseStats,
+    
     /// 错误类型计数
     error_types: ErrorTypeCounters,
     
@@ -38,6 +64,60 @@ pub struct AtomicMetrics {
     start_time: Instant,
 }
 
+/// 活跃请求守卫
+///
+/// 请求处理结束（包括提前返回或 panic 展开）时自动减少活跃请求数
+pub struct ActiveRequestGuard {
+    metrics: Arc<AtomicMetrics>,
+}
+
+impl Drop for ActiveRequestGuard {
+    fn drop(&mut self) {
+        self.metrics.decrement_active_requests();
+    }
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+impl ActiveRequestGuard {
+    /// 将守卫移入响应体
+    ///
+    /// 流式响应（SSE、分块等）在处理器返回后仍在发送，
+    /// 计数要保持到响应体发送完毕或被连接丢弃为止
+    pub fn hold_until_body_done(
+        self,
+        response: hyper::Response<http_body_util::combinators::BoxBody<bytes::Bytes, BoxError>>,
+    ) -> hyper::Response<http_body_util::combinators::BoxBody<bytes::Bytes, BoxError>> {
+        response.map(|body| http_body_util::combinators::BoxBody::new(GuardedBody { inner: body, _guard: self }))
+    }
+}
+
+/// 持有活跃请求守卫的响应体
+struct GuardedBody {
+    inner: http_body_util::combinators::BoxBody<bytes::Bytes, BoxError>,
+    _guard: ActiveRequestGuard,
+}
+
+impl hyper::body::Body for GuardedBody {
+    type Data = bytes::Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<hyper::body::Frame<bytes::Bytes>, BoxError>>> {
+        std::pin::Pin::new(&mut self.get_mut().inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> hyper::body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
 /// 延迟统计
 struct LatencyStats {
     /// 总延迟时间（微秒）
@@ -56,12 +136,25 @@ struct LatencyStats {
 
 /// 吞吐量统计
 struct ThroughputStats {
-    /// 每秒请求数（RPS）
-    requests_per_second: AtomicU64,
+    /// 每秒数量（请求数或新建连接数）
+    per_second: AtomicU64,
     /// 上次更新时间
     last_update: AtomicU64,
-    /// 上次请求计数
-    last_request_count: AtomicU64,
+    /// 上次计数
+    last_count: AtomicU64,
+}
+
+/// 单连接请求数直方图的桶上界（最后一个桶收集超过 100 的连接）
+pub const CONNECTION_REQUEST_BUCKETS: [u64; 7] = [1, 2, 5, 10, 50, 100, u64::MAX];
+
+/// 连接复用统计（只统计已关闭的连接）
+struct ReuseStats {
+    /// 已关闭的连接数
+    closed_connections: AtomicU64,
+    /// 已关闭连接上处理的请求总数
+    requests: AtomicU64,
+    /// 单连接请求数直方图，与 `CONNECTION_REQUEST_BUCKETS` 一一对应
+    buckets: [AtomicU64; CONNECTION_REQUEST_BUCKETS.len()],
 }
 
 /// 错误类型计数器
@@ -87,8 +180,19 @@ impl AtomicMetrics {
             error_count: AtomicU64::new(0),
             connection_count: AtomicU64::new(0),
             active_connections: AtomicUsize::new(0),
+            active_requests: AtomicUsize::new(0),
+            rejected_requests: AtomicU64::new(0),
+            client_disconnects: AtomicU64::new(0),
+            tls_handshake_timeouts: AtomicU64::new(0),
+            tls_handshake_failures: AtomicU64::new(0),
+            h2_flood_closures: AtomicU64::new(0),
+            cache_hits: AtomicU64::new(0),
+            cache_stale_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
             latency_stats: LatencyStats::new(),
             throughput_stats: ThroughputStats::new(),
+            connection_rate_stats: ThroughputStats::new(),
+            reuse_stats: ReuseStats::new(),
             error_types: ErrorTypeCounters::new(),
             start_time: Instant::now(),
         }
@@ -159,8 +263,45 @@ impl AtomicMetrics {
     
     /// 增加连接计数
     pub fn increment_connections(&self) {
-        self.connection_count.fetch_add(1, Ordering::Relaxed);
+        let total = self.connection_count.fetch_add(1, Ordering::Relaxed) + 1;
         self.active_connections.fetch_add(1, Ordering::Relaxed);
+        self.connection_rate_stats.update(self.start_time, total);
+    }
+    
+    /// 记录一个连接关闭，`requests` 为该连接在生命周期内处理的请求数
+    ///
+    /// 没有处理任何请求的连接（例如握手后直接断开）不计入复用统计
+    pub fn record_connection_closed(&self, requests: u64) {
+        if requests == 0 {
+            return;
+        }
+        self.reuse_stats.closed_connections.fetch_add(1, Ordering::Relaxed);
+        self.reuse_stats.requests.fetch_add(requests, Ordering::Relaxed);
+        let bucket = CONNECTION_REQUEST_BUCKETS.iter()
+            .position(|&upper| requests <= upper)
+            .unwrap_or(CONNECTION_REQUEST_BUCKETS.len() - 1);
+        self.reuse_stats.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// 获取连接复用统计
+    pub fn connection_reuse_stats(&self) -> ConnectionReuseStats {
+        let closed_connections = self.reuse_stats.closed_connections.load(Ordering::Relaxed);
+        let requests = self.reuse_stats.requests.load(Ordering::Relaxed);
+        ConnectionReuseStats {
+            closed_connections,
+            requests,
+            // 除每个连接的第一个请求外，其余请求都复用了已有连接
+            reuse_ratio: if requests > 0 {
+                requests.saturating_sub(closed_connections) as f64 / requests as f64
+            } else {
+                0.0
+            },
+            new_connections_per_second: self.connection_rate_stats.per_second.load(Ordering::Relaxed),
+            requests_per_connection: CONNECTION_REQUEST_BUCKETS.iter()
+                .zip(self.reuse_stats.buckets.iter())
+                .map(|(&upper, count)| (upper, count.load(Ordering::Relaxed)))
+                .collect(),
+        }
     }
     
     /// 减少活跃连接数
@@ -168,6 +309,102 @@ impl AtomicMetrics {
         self.active_connections.fetch_sub(1, Ordering::Relaxed);
     }
     
+    /// 进入一个请求（活跃请求数 +1）
+    pub fn increment_active_requests(&self) {
+        self.active_requests.fetch_add(1, Ordering::AcqRel);
+    }
+    
+    /// 完成一个请求（活跃请求数 -1）
+    pub fn decrement_active_requests(&self) {
+        self.active_requests.fetch_sub(1, Ordering::AcqRel);
+    }
+    
+    /// 获取正在处理的请求数
+    ///
+    /// 滚动发布时可等待该值归零后再终止进程
+    pub fn active_requests(&self) -> usize {
+        self.active_requests.load(Ordering::Acquire)
+    }
+    
+    /// 开始跟踪一个活跃请求，返回的守卫在 drop 时自动减少计数
+    pub fn track_active_request(self: &Arc<Self>) -> ActiveRequestGuard {
+        self.increment_active_requests();
+        ActiveRequestGuard { metrics: self.clone() }
+    }
+    
+    /// 记录一次因队列饱和而被拒绝的请求
+    pub fn record_rejected_request(&self) {
+        self.rejected_requests.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// 获取因队列饱和被拒绝的请求数
+    pub fn rejected_requests(&self) -> u64 {
+        self.rejected_requests.load(Ordering::Relaxed)
+    }
+    
+    /// 记录一次客户端在响应产生前断开的请求
+    pub fn record_client_disconnect(&self) {
+        self.client_disconnects.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// 获取客户端在响应产生前断开的请求数
+    pub fn client_disconnects(&self) -> u64 {
+        self.client_disconnects.load(Ordering::Relaxed)
+    }
+    
+    /// 记录一次 TLS 握手超时
+    pub fn record_tls_handshake_timeout(&self) {
+        self.tls_handshake_timeouts.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// 获取 TLS 握手超时数
+    pub fn tls_handshake_timeouts(&self) -> u64 {
+        self.tls_handshake_timeouts.load(Ordering::Relaxed)
+    }
+    
+    /// 记录一次 TLS 握手失败（超时单独统计）
+    pub fn record_tls_handshake_failure(&self) {
+        self.tls_handshake_failures.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// 获取 TLS 握手失败数（不含超时）
+    pub fn tls_handshake_failures(&self) -> u64 {
+        self.tls_handshake_failures.load(Ordering::Relaxed)
+    }
+    
+    /// 记录一次因洪泛被关闭的 HTTP/2 连接
+    pub fn record_h2_flood_closure(&self) {
+        self.h2_flood_closures.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// 获取因洪泛被关闭的 HTTP/2 连接数
+    pub fn h2_flood_closures(&self) -> u64 {
+        self.h2_flood_closures.load(Ordering::Relaxed)
+    }
+    
+    /// 记录一次缓存命中，`stale` 表示命中的是等待后台刷新的过期缓存
+    pub fn record_cache_hit(&self, stale: bool) {
+        if stale {
+            self.cache_stale_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    
+    /// 记录一次缓存未命中
+    pub fn record_cache_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// 获取缓存统计（命中数, 过期命中数, 未命中数）
+    pub fn cache_stats(&self) -> (u64, u64, u64) {
+        (
+            self.cache_hits.load(Ordering::Relaxed),
+            self.cache_stale_hits.load(Ordering::Relaxed),
+            self.cache_misses.load(Ordering::Relaxed),
+        )
+    }
+    
     /// 增加错误计数
     pub fn increment_errors(&self) {
         self.error_count.fetch_add(1, Ordering::Relaxed);
@@ -175,21 +412,7 @@ impl AtomicMetrics {
     
     /// 更新吞吐量统计
     fn update_throughput(&self) {
-        let now = Instant::now().duration_since(self.start_time).as_secs();
-        let last_update = self.throughput_stats.last_update.load(Ordering::Relaxed);
-        
-        // 每秒更新一次吞吐量
-        if now > last_update {
-            let current_requests = self.request_count.load(Ordering::Relaxed);
-            let last_requests = self.throughput_stats.last_request_count.load(Ordering::Relaxed);
-            
-            if now > last_update {
-                let rps = (current_requests - last_requests) / (now - last_update).max(1);
-                self.throughput_stats.requests_per_second.store(rps, Ordering::Relaxed);
-                self.throughput_stats.last_update.store(now, Ordering::Relaxed);
-                self.throughput_stats.last_request_count.store(current_requests, Ordering::Relaxed);
-            }
-        }
+        self.throughput_stats.update(self.start_time, self.request_count.load(Ordering::Relaxed));
     }
     
     /// 更新百分位数估计（简化的指数移动平均）
@@ -236,6 +459,18 @@ impl AtomicMetrics {
         metrics.insert("requests_error".to_string(), self.error_count.load(Ordering::Relaxed));
         metrics.insert("connections_total".to_string(), self.connection_count.load(Ordering::Relaxed));
         metrics.insert("connections_active".to_string(), self.active_connections.load(Ordering::Relaxed) as u64);
+        metrics.insert("requests_active".to_string(), self.active_requests() as u64);
+        metrics.insert("requests_rejected".to_string(), self.rejected_requests());
+        metrics.insert("requests_client_disconnected".to_string(), self.client_disconnects());
+        metrics.insert("tls_handshake_timeouts".to_string(), self.tls_handshake_timeouts());
+        metrics.insert("tls_handshake_failures".to_string(), self.tls_handshake_failures());
+        metrics.insert("h2_flood_closures".to_string(), self.h2_flood_closures());
+        
+        // 缓存
+        let (cache_hits, cache_stale_hits, cache_misses) = self.cache_stats();
+        metrics.insert("cache_hits".to_string(), cache_hits);
+        metrics.insert("cache_stale_hits".to_string(), cache_stale_hits);
+        metrics.insert("cache_misses".to_string(), cache_misses);
         
         // 延迟指标
         let request_count = self.request_count.load(Ordering::Relaxed);
@@ -250,7 +485,16 @@ impl AtomicMetrics {
         metrics.insert("latency_p99_us".to_string(), self.latency_stats.p99_latency_us.load(Ordering::Relaxed));
         
         // 吞吐量
-        metrics.insert("requests_per_second".to_string(), self.throughput_stats.requests_per_second.load(Ordering::Relaxed));
+        metrics.insert("requests_per_second".to_string(), self.throughput_stats.per_second.load(Ordering::Relaxed));
+        
+        // 连接复用
+        let reuse = self.connection_reuse_stats();
+        metrics.insert("connections_per_second".to_string(), reuse.new_connections_per_second);
+        metrics.insert("connections_closed".to_string(), reuse.closed_connections);
+        metrics.insert("connection_reuse_ratio_permille".to_string(), (reuse.reuse_ratio * 1000.0).round() as u64);
+        for (upper, count) in &reuse.requests_per_connection {
+            metrics.insert(format!("connection_requests_le_{}", ConnectionReuseStats::bucket_label(*upper)), *count);
+        }
         
         // 错误类型
         metrics.insert("errors_timeout".to_string(), self.error_types.timeout_errors.load(Ordering::Relaxed));
@@ -286,7 +530,7 @@ impl AtomicMetrics {
                 0.0
             },
             connections_active: self.active_connections.load(Ordering::Relaxed),
-            requests_per_second: self.throughput_stats.requests_per_second.load(Ordering::Relaxed),
+            requests_per_second: self.throughput_stats.per_second.load(Ordering::Relaxed),
             latency_avg_us: if request_count > 0 {
                 self.latency_stats.total_latency_us.load(Ordering::Relaxed) / request_count
             } else {
@@ -305,6 +549,14 @@ impl AtomicMetrics {
         self.error_count.store(0, Ordering::Relaxed);
         self.connection_count.store(0, Ordering::Relaxed);
         self.active_connections.store(0, Ordering::Relaxed);
+        self.rejected_requests.store(0, Ordering::Relaxed);
+        self.client_disconnects.store(0, Ordering::Relaxed);
+        self.tls_handshake_timeouts.store(0, Ordering::Relaxed);
+        self.tls_handshake_failures.store(0, Ordering::Relaxed);
+        self.h2_flood_closures.store(0, Ordering::Relaxed);
+        self.cache_hits.store(0, Ordering::Relaxed);
+        self.cache_stale_hits.store(0, Ordering::Relaxed);
+        self.cache_misses.store(0, Ordering::Relaxed);
         
         self.latency_stats.total_latency_us.store(0, Ordering::Relaxed);
         self.latency_stats.min_latency_us.store(0, Ordering::Relaxed);
@@ -313,9 +565,14 @@ impl AtomicMetrics {
         self.latency_stats.p95_latency_us.store(0, Ordering::Relaxed);
         self.latency_stats.p99_latency_us.store(0, Ordering::Relaxed);
         
-        self.throughput_stats.requests_per_second.store(0, Ordering::Relaxed);
-        self.throughput_stats.last_update.store(0, Ordering::Relaxed);
-        self.throughput_stats.last_request_count.store(0, Ordering::Relaxed);
+        self.throughput_stats.reset();
+        self.connection_rate_stats.reset();
+        
+        self.reuse_stats.closed_connections.store(0, Ordering::Relaxed);
+        self.reuse_stats.requests.store(0, Ordering::Relaxed);
+        for bucket in &self.reuse_stats.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
         
         self.error_types.timeout_errors.store(0, Ordering::Relaxed);
         self.error_types.python_errors.store(0, Ordering::Relaxed);
@@ -341,9 +598,39 @@ impl LatencyStats {
 impl ThroughputStats {
     fn new() -> Self {
         Self {
-            requests_per_second: AtomicU64::new(0),
+            per_second: AtomicU64::new(0),
             last_update: AtomicU64::new(0),
-            last_request_count: AtomicU64::new(0),
+            last_count: AtomicU64::new(0),
+        }
+    }
+    
+    /// 每秒更新一次速率，`current` 为当前的累计计数
+    fn update(&self, start_time: Instant, current: u64) {
+        let now = Instant::now().duration_since(start_time).as_secs();
+        let last_update = self.last_update.load(Ordering::Relaxed);
+        
+        if now > last_update {
+            let last_count = self.last_count.load(Ordering::Relaxed);
+            let rate = current.saturating_sub(last_count) / (now - last_update).max(1);
+            self.per_second.store(rate, Ordering::Relaxed);
+            self.last_update.store(now, Ordering::Relaxed);
+            self.last_count.store(current, Ordering::Relaxed);
+        }
+    }
+    
+    fn reset(&self) {
+        self.per_second.store(0, Ordering::Relaxed);
+        self.last_update.store(0, Ordering::Relaxed);
+        self.last_count.store(0, Ordering::Relaxed);
+    }
+}
+
+impl ReuseStats {
+    fn new() -> Self {
+        Self {
+            closed_connections: AtomicU64::new(0),
+            requests: AtomicU64::new(0),
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
         }
     }
 }
@@ -370,6 +657,37 @@ pub enum ErrorType {
     Other,
 }
 
+/// 连接复用统计快照
+#[derive(Debug, Clone)]
+pub struct ConnectionReuseStats {
+    /// 已关闭且至少处理过一个请求的连接数
+    pub closed_connections: u64,
+    /// 这些连接上处理的请求总数
+    pub requests: u64,
+    /// keep-alive 复用率：复用已有连接的请求占比
+    pub reuse_ratio: f64,
+    /// 每秒新建连接数
+    pub new_connections_per_second: u64,
+    /// 单连接请求数直方图：(桶上界, 连接数)，上界为 `u64::MAX` 的桶收集其余连接
+    pub requests_per_connection: Vec<(u64, u64)>,
+}
+
+impl ConnectionReuseStats {
+    /// 直方图桶的标签，最后一个桶为 `inf`
+    pub fn bucket_label(upper: u64) -> String {
+        if upper == u64::MAX { "inf".to_string() } else { upper.to_string() }
+    }
+    
+    /// 平均每个连接处理的请求数
+    pub fn requests_per_connection_avg(&self) -> f64 {
+        if self.closed_connections > 0 {
+            self.requests as f64 / self.closed_connections as f64
+        } else {
+            0.0
+        }
+    }
+}
+
 /// 性能指标摘要
 #[derive(Debug, Clone)]
 pub struct MetricsSummary {
@@ -483,4 +801,66 @@ mod tests {
         assert_eq!(summary.requests_success, 1000);
         assert_eq!(summary.success_rate, 1.0);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_connection_reuse_stats() {
+        let metrics = AtomicMetrics::new();
+        metrics.record_connection_closed(1);
+        metrics.record_connection_closed(4);
+        metrics.record_connection_closed(500);
+        metrics.record_connection_closed(0);
+        
+        let stats = metrics.connection_reuse_stats();
+        assert_eq!(stats.closed_connections, 3);
+        assert_eq!(stats.requests, 505);
+        assert!((stats.reuse_ratio - 502.0 / 505.0).abs() < f64::EPSILON);
+        assert_eq!(stats.requests_per_connection[0], (1, 1));
+        assert_eq!(stats.requests_per_connection[2], (5, 1));
+        assert_eq!(stats.requests_per_connection[6], (u64::MAX, 1));
+        
+        let all = metrics.get_all();
+        assert_eq!(all["connection_requests_le_inf"], 1);
+        assert_eq!(all["connection_reuse_ratio_permille"], 994);
+        
+        metrics.reset();
+        assert_eq!(metrics.connection_reuse_stats().closed_connections, 0);
+    }
+    
+    #[test]
+    fn test_active_request_guard() {
+        let metrics = Arc::new(AtomicMetrics::new());
+        assert_eq!(metrics.active_requests(), 0);
+        
+        let first = metrics.track_active_request();
+        let second = metrics.track_active_request();
+        assert_eq!(metrics.active_requests(), 2);
+        
+        drop(first);
+        assert_eq!(metrics.active_requests(), 1);
+        drop(second);
+        assert_eq!(metrics.active_requests(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_active_request_held_by_body() {
+        use http_body_util::BodyExt;
+        
+        let metrics = Arc::new(AtomicMetrics::new());
+        let (frames, stream) = tokio::sync::mpsc::unbounded_channel::<Result<hyper::body::Frame<bytes::Bytes>, BoxError>>();
+        let body = http_body_util::combinators::BoxBody::new(http_body_util::StreamBody::new(
+            tokio_stream::wrappers::UnboundedReceiverStream::new(stream)
+        ));
+        let response = metrics.track_active_request().hold_until_body_done(hyper::Response::new(body));
+        
+        // 处理器已返回，流式响应体仍在发送
+        let mut body = response.into_body();
+        frames.send(Ok(hyper::body::Frame::data(bytes::Bytes::from_static(b"chunk")))).unwrap();
+        assert!(body.frame().await.is_some());
+        assert_eq!(metrics.active_requests(), 1);
+        
+        drop(frames);
+        assert!(body.frame().await.is_none());
+        drop(body);
+        assert_eq!(metrics.active_requests(), 0);
+    }
+}
@@ -5,6 +5,21 @@
 //! - 零拷贝网络 I/O
 //! - 内存池管理
 //! - 原子性能监控
+//!
+//! # 请求处理路径
+//!
+//! 单端口模式下连接有两种处理方式，由 [`RatEngineBuilder::http_path`] 选择：
+//!
+//! - [`HttpPath::Hyper`]（默认）：每个连接先做协议检测，再交给 hyper 的 `serve_connection`
+//!   处理 HTTP/1.1、HTTP/2、TLS 和 gRPC。流式响应、Early Hints、trailers 等特性都走这条路径。
+//! - [`HttpPath::WorkStealing`]：明文 HTTP/1.1 连接由独立的读取任务用零拷贝解析器读取完整请求
+//!   （等待时间受 `connection_idle_timeout` 约束，只发送了一部分的请求收到 408），读到后才进入工作窃取队列，
+//!   由工作线程执行处理器，收集完整响应体后按 HTTP/1.1 手动序列化写回；keep-alive 连接随后回到读取状态。
+//!   请求体上限、读取请求体之前的检查和状态码钩子与 hyper 路径一致。
+//!   不支持 TLS、HTTP/2 和 gRPC，请求（含请求体）不能超过 1MB，流式响应会被缓冲到结束后一次发送，
+//!   适合短小请求的纯 HTTP/1.1 场景。
+//!
+//! 分端口模式始终使用 hyper 路径。
 
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -24,6 +39,8 @@ pub struct HttpRequest {
     pub body: Vec<u8>,
     pub remote_addr: String,
     pub real_ip: String,
+    /// 请求行中的协议版本（HTTP/1.0 或 HTTP/1.1）
+    pub version: hyper::Version,
 }
 
 /// HTTP 响应结构体
@@ -50,6 +67,8 @@ use memory::MemoryPool;
 use metrics::AtomicMetrics;
 use smart_transfer::SmartTransferManager;
 use congestion_control::CongestionControlManager;
+use crate::error::BuilderError;
+use crate::server::load_shed::LoadShedReason;
 
 /// 高性能 RAT 引擎核心（空实现 - 所有功能通过 RatEngineBuilder 访问）
 pub struct RatEngine {
@@ -112,19 +131,25 @@ impl RatEngine {
         panic!("RatEngine is an empty implementation. Use RatEngineBuilder to create and configure engines.");
     }
     
-    /// 获取拥塞控制统计信息（通过 builder 访问）
+    /// 获取拥塞控制统计信息
+    ///
+    /// 空实现没有拥塞控制，返回空表
     pub async fn get_congestion_stats(&self) -> HashMap<String, f64> {
-        panic!("RatEngine is an empty implementation. Use RatEngineBuilder to create and configure engines.");
+        HashMap::new()
     }
     
-    /// 获取拥塞控制窗口大小（通过 builder 访问）
-    pub async fn get_congestion_window(&self) -> u32 {
-        panic!("RatEngine is an empty implementation. Use RatEngineBuilder to create and configure engines.");
+    /// 获取拥塞控制窗口大小
+    ///
+    /// 空实现没有拥塞控制，返回 `None`
+    pub async fn get_congestion_window(&self) -> Option<u32> {
+        None
     }
     
-    /// 获取拥塞控制发送速率（通过 builder 访问）
-    pub async fn get_congestion_send_rate(&self) -> f64 {
-        panic!("RatEngine is an empty implementation. Use RatEngineBuilder to create and configure engines.");
+    /// 获取拥塞控制发送速率
+    ///
+    /// 空实现没有拥塞控制，返回 `None`
+    pub async fn get_congestion_send_rate(&self) -> Option<f64> {
+        None
     }
     
     /// 处理数据包发送事件（通过 builder 访问）
@@ -148,16 +173,55 @@ impl RatEngine {
     }
 }
 
+/// 单端口模式下 HTTP 连接的处理路径，见模块文档
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpPath {
+    /// 协议检测后交给 hyper 处理（支持全部协议）
+    #[default]
+    Hyper,
+    /// 明文 HTTP/1.1 连接进入工作窃取队列，由工作线程处理
+    WorkStealing,
+}
+
 /// 引擎配置
 #[derive(Debug, Clone)]
 pub struct EngineConfig {
     pub worker_threads: usize,
     pub max_connections: usize,
     pub buffer_size: usize,
-    pub timeout: Duration,
+    /// 单个请求的处理期限（处理器在该时间内未返回响应时返回 504）
+    ///
+    /// 为 `None` 时保留路由器上通过 `Router::set_request_timeout` 设置的值
+    pub request_timeout: Option<Duration>,
+    /// 连接空闲超时（HTTP/1.1 keep-alive 连接等待下一个请求头的最长时间）
+    pub connection_idle_timeout: Duration,
+    /// 读取请求体的空闲超时：连续该时长没有收到新数据时返回 408，每收到一块数据重新计时
+    pub request_body_timeout: Duration,
+    /// TLS 握手超时
+    pub tls_handshake_timeout: Duration,
     pub enable_keepalive: bool,
     pub tcp_nodelay: bool,
     pub congestion_control: crate::engine::congestion_control::CongestionControlConfig,
+    /// 工作队列最大深度，超过后新请求直接返回过载拒绝响应（None 表示不限制）
+    pub max_queue_depth: Option<usize>,
+    /// 同时进行中的握手数量上限（None 表示不限制）
+    pub max_concurrent_handshakes: Option<usize>,
+    /// 优雅关闭时等待进行中连接结束的最长时间
+    pub shutdown_timeout: Duration,
+    /// 启动时预热内存池，降低首批请求的延迟
+    pub prewarm: bool,
+    /// 在响应中添加 X-Detected-Protocol 调试头部
+    pub expose_detection_debug: bool,
+    /// 连接数上限、队列深度、SSE 连接数等限制触发时统一返回的响应
+    pub load_shed_response: crate::server::load_shed::LoadShedResponse,
+    /// 启动时只输出路由数量摘要，完整列表降为 debug 级别
+    pub quiet_startup: bool,
+    /// 单端口模式下 HTTP 连接的处理路径
+    pub http_path: HttpPath,
+    /// 每个 HTTP 响应的默认写出速率上限（字节/秒），None 表示不限制
+    pub response_rate_limit: Option<u64>,
+    /// HTTP/2 连接参数与洪泛防护阈值
+    pub h2_config: crate::server::h2_config::H2Config,
 }
 
 impl Default for EngineConfig {
@@ -166,7 +230,10 @@ impl Default for EngineConfig {
             worker_threads: num_cpus::get(),
             max_connections: 10000,
             buffer_size: 8192,
-            timeout: Duration::from_secs(30),
+            request_timeout: None,
+            connection_idle_timeout: Duration::from_secs(60),
+            request_body_timeout: Duration::from_secs(60),
+            tls_handshake_timeout: Duration::from_secs(10),
             enable_keepalive: true,
             tcp_nodelay: true,
             congestion_control: crate::engine::congestion_control::CongestionControlConfig {
@@ -177,6 +244,16 @@ impl Default for EngineConfig {
                 metrics_window_size: 32,
                 switch_cooldown_ms: 1000,
             },
+            max_queue_depth: None,
+            max_concurrent_handshakes: None,
+            shutdown_timeout: Duration::from_secs(30),
+            prewarm: false,
+            expose_detection_debug: false,
+            load_shed_response: crate::server::load_shed::LoadShedResponse::default(),
+            quiet_startup: false,
+            http_path: HttpPath::default(),
+            response_rate_limit: None,
+            h2_config: Default::default(),
         }
     }
 }
@@ -220,7 +297,15 @@ pub struct RatEngineBuilder {
     server_config: crate::server::config::ServerConfig,
     router: Option<crate::server::Router>,
     cert_manager: Option<Arc<std::sync::RwLock<crate::server::cert_manager::CertificateManager>>>,
+    cert_renewal: Option<(crate::server::cert_manager::CertRenewalConfig, Option<crate::server::cert_manager::CertRenewalHook>)>,
+    alpn_protocols: Option<Vec<String>>,
+    redacted_headers: Option<Vec<String>>,
+    protocol_policy: Option<crate::server::protocol_policy::ProtocolPolicy>,
+    protocol_block_response: Option<crate::server::protocol_policy::ProtocolBlockResponse>,
+    /// 显式配置过的过载拒绝响应，构建时同步到全局 SSE 管理器
+    sse_load_shed_response: Option<crate::server::load_shed::LoadShedResponse>,
     auto_init_logger: bool,
+    fallback_log_config: Option<crate::utils::logger::LogConfig>,
     built: bool,
 }
 
@@ -234,6 +319,8 @@ pub trait Middleware: Send + Sync {
 pub struct ActualRatEngine {
     /// 工作窃取队列
     work_queue: Arc<WorkStealingQueue<HttpTask>>,
+    /// 有新请求进入工作队列时唤醒空闲的工作线程
+    work_available: Arc<tokio::sync::Notify>,
     /// 连接池管理
     connection_pool: Arc<ConnectionPool>,
     /// 内存池
@@ -246,6 +333,10 @@ pub struct ActualRatEngine {
     router: Option<Arc<crate::server::Router>>,
     /// 证书管理器
     cert_manager: Option<Arc<std::sync::RwLock<crate::server::cert_manager::CertificateManager>>>,
+    /// 证书续期配置
+    cert_renewal: Option<(crate::server::cert_manager::CertRenewalConfig, Option<crate::server::cert_manager::CertRenewalHook>)>,
+    /// 证书续期任务句柄
+    cert_renewal_task: Arc<tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
     /// 性能监控
     metrics: Arc<AtomicMetrics>,
     /// 配置
@@ -254,6 +345,12 @@ pub struct ActualRatEngine {
     server_config: crate::server::config::ServerConfig,
     /// 工作线程句柄
     worker_handles: Arc<tokio::sync::Mutex<Vec<tokio::task::JoinHandle<()>>>>,
+    /// 实际绑定的监听地址（启动后设置）
+    bound_addr: Arc<std::sync::RwLock<Option<std::net::SocketAddr>>>,
+    /// 关闭信号（true 表示已请求关闭）
+    shutdown_signal: Arc<tokio::sync::watch::Sender<bool>>,
+    /// 接受循环是否仍在运行（包含关闭后的连接排空阶段）
+    serving: Arc<tokio::sync::watch::Sender<bool>>,
 }
 
 impl RatEngineBuilder {
@@ -264,7 +361,14 @@ impl RatEngineBuilder {
             server_config: crate::server::config::ServerConfig::default(8080),
             router: None,
             cert_manager: None,
+            cert_renewal: None,
+            alpn_protocols: None,
+            redacted_headers: None,
+            protocol_policy: None,
+            protocol_block_response: None,
+            sse_load_shed_response: None,
             auto_init_logger: false,
+            fallback_log_config: Some(crate::utils::logger::LogConfig::minimal()),
             built: false,
         }
     }
@@ -282,18 +386,157 @@ impl RatEngineBuilder {
         self
     }
     
+    /// 设置工作队列最大深度
+    ///
+    /// 队列积压超过该深度时，新请求立即返回过载拒绝响应（默认 `503 Service Unavailable`），
+    /// 而不是排队等待；被拒绝的请求计入 `requests_rejected` 指标
+    pub fn max_queue_depth(mut self, depth: usize) -> Self {
+        self.engine_config.max_queue_depth = Some(depth.max(1));
+        self
+    }
+    
+    /// 设置同时进行中的 TCP/TLS 握手数量上限
+    ///
+    /// 协议检测和 TLS 握手完成后才释放名额，握手洪泛时接受循环暂停，
+    /// 避免大量半开连接派生无限多的任务
+    pub fn max_concurrent_handshakes(mut self, count: usize) -> Self {
+        self.engine_config.max_concurrent_handshakes = Some(count.max(1));
+        self
+    }
+    
+    /// 设置过载拒绝响应
+    ///
+    /// 连接数上限、工作队列深度和 SSE 连接数上限共用该响应，默认返回 503 和 `Retry-After: 1`
+    /// （未配置时 SSE 连接数上限保持 `Retry-After: 5`）
+    pub fn load_shed_response(mut self, response: crate::server::load_shed::LoadShedResponse) -> Self {
+        self.sse_load_shed_response = Some(response.clone());
+        self.engine_config.load_shed_response = response;
+        self
+    }
+    
     /// 设置缓冲区大小
     pub fn buffer_size(mut self, size: usize) -> Self {
         self.engine_config.buffer_size = size.max(1024);
         self
     }
     
-    /// 设置超时时间
+    /// 设置超时时间（等同于 `request_timeout`，保留用于向后兼容）
     pub fn timeout(mut self, timeout: Duration) -> Self {
-        self.engine_config.timeout = timeout;
+        self.engine_config.request_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置单个请求的处理期限
+    ///
+    /// 处理器在该时间内未返回响应时返回 504；流式响应只限制到响应头返回为止
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.engine_config.request_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置连接空闲超时
+    ///
+    /// HTTP/1.1 keep-alive 连接在该时间内没有收到下一个请求头时关闭
+    pub fn connection_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.engine_config.connection_idle_timeout = timeout;
+        self
+    }
+
+    /// 设置读取请求体的空闲超时
+    ///
+    /// 每收到一块数据重新计时，客户端连续该时长没有发送新数据（例如声明的 Content-Length
+    /// 大于实际发送的字节数又不关闭连接）时返回 `408 Request Timeout` 并关闭连接；
+    /// 持续上传的大请求体不受总时长限制
+    pub fn request_body_timeout(mut self, timeout: Duration) -> Self {
+        self.engine_config.request_body_timeout = timeout;
+        self
+    }
+
+    /// 设置 TLS 握手超时
+    pub fn tls_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.engine_config.tls_handshake_timeout = timeout;
+        self
+    }
+    
+    /// 设置优雅关闭的等待时间
+    ///
+    /// `shutdown()` 停止接受新连接后，最多等待该时长让进行中的连接结束
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.engine_config.shutdown_timeout = timeout;
+        self
+    }
+    
+    /// 选择单端口模式下 HTTP 连接的处理路径（默认 [`HttpPath::Hyper`]）
+    ///
+    /// [`HttpPath::WorkStealing`] 只处理明文 HTTP/1.1，与证书、gRPC 和分端口模式同时配置时构建失败
+    pub fn http_path(mut self, path: HttpPath) -> Self {
+        self.engine_config.http_path = path;
+        self
+    }
+    
+    /// 设置 HTTP/2 连接参数与洪泛防护阈值
+    ///
+    /// 客户端在服务端接受前重置的流过多（Rapid Reset 攻击）时关闭连接，并计入 `h2_flood_closures` 指标
+    pub fn h2_config(mut self, config: crate::server::h2_config::H2Config) -> Self {
+        self.engine_config.h2_config = config;
+        self
+    }
+    
+    /// 启用/禁用安静启动
+    ///
+    /// 启用后启动日志只输出 "已注册 142 个 HTTP 路由，12 个 gRPC 方法" 这样的摘要，
+    /// 逐条的路由列表降为 debug 级别，适合注册了大量路由的服务
+    pub fn quiet_startup(mut self, enabled: bool) -> Self {
+        self.engine_config.quiet_startup = enabled;
+        self
+    }
+    
+    /// 设置 HTTP 响应的默认写出速率上限（字节/秒）
+    ///
+    /// 速率按单个响应计算：HTTP/1.1 下即每个连接的速率，HTTP/2 下为每个流的速率。
+    /// 通过 `Router::add_route_with_rate` 单独设置的路由以路由的速率为准
+    pub fn response_rate_limit(mut self, bytes_per_sec: Option<u64>) -> Self {
+        self.engine_config.response_rate_limit = bytes_per_sec;
+        self
+    }
+    
+    /// 启用/禁用启动预热
+    ///
+    /// 启用后在开始接受连接前调用 [`ActualRatEngine::warmup`]，
+    /// 提前触发内存池缓冲区的缺页，避免首批请求承担这部分开销
+    pub fn prewarm(mut self, enabled: bool) -> Self {
+        self.engine_config.prewarm = enabled;
         self
     }
     
+    /// 启用/禁用协议检测调试头部
+    ///
+    /// 启用后经过协议检测的 HTTP 响应带有 `X-Detected-Protocol: HTTP1_1; confidence=0.97`，
+    /// 其中协议是连接实际分派到的处理器类型，置信度是对预读数据的判定结果。
+    /// 用于在预发环境排查客户端被拦截或误路由的原因，生产环境不建议开启
+    pub fn expose_detection_debug(mut self, enabled: bool) -> Self {
+        self.engine_config.expose_detection_debug = enabled;
+        self
+    }
+
+    /// 设置协议拦截策略
+    ///
+    /// 策略在协议检测后、分派到协议处理器之前调用，参数为检测到的协议和置信度。
+    /// 默认策略见 [`default_protocol_policy`](crate::server::protocol_policy::default_protocol_policy)
+    pub fn protocol_policy(mut self, policy: crate::server::protocol_policy::ProtocolPolicy) -> Self {
+        self.protocol_policy = Some(policy);
+        self
+    }
+
+    /// 设置协议策略判定为拦截（`BlockDecision::Block`）时写回的 HTTP/1.1 响应
+    ///
+    /// 默认 `None`：不写任何数据直接关闭连接。TLS 连接和 HTTP/2 前言无法解析该响应，仍然静默关闭；
+    /// 策略自己返回的 `BlockWith` 响应不受此设置影响
+    pub fn protocol_block_response(mut self, response: Option<crate::server::protocol_policy::ProtocolBlockResponse>) -> Self {
+        self.protocol_block_response = response;
+        self
+    }
+
     /// 启用/禁用 Keep-Alive
     pub fn keepalive(mut self, enabled: bool) -> Self {
         self.engine_config.enable_keepalive = enabled;
@@ -319,12 +562,63 @@ impl RatEngineBuilder {
         self
     }
 
+    /// 自定义 TLS 握手时声明的 ALPN 协议列表（按优先级排列）
+    ///
+    /// 覆盖默认的 `h2`、`http/1.1`。只能包含服务器能处理的协议，
+    /// 例如只声明 `h2` 可以让仅支持 HTTP/1.1 的客户端在握手阶段失败，而不是收到 426。
+    /// 列表在 `build()` 时校验并应用到证书管理器，证书重新加载后仍然生效
+    pub fn alpn_protocols(mut self, protocols: Vec<String>) -> Self {
+        self.alpn_protocols = Some(protocols);
+        self
+    }
+
+    /// 启用证书后台续期（仅从磁盘重新加载，适用于由外部工具续期证书的场景）
+    pub fn certificate_renewal(mut self, config: crate::server::cert_manager::CertRenewalConfig) -> Self {
+        self.cert_renewal = Some((config, None));
+        self
+    }
+
+    /// 启用证书后台续期，并在进入续期窗口时调用钩子签发新证书
+    ///
+    /// 签发逻辑完全由钩子提供，可以接入任意外部签发流程
+    pub fn certificate_renewal_with_hook(
+        mut self,
+        config: crate::server::cert_manager::CertRenewalConfig,
+        hook: crate::server::cert_manager::CertRenewalHook,
+    ) -> Self {
+        self.cert_renewal = Some((config, Some(hook)));
+        self
+    }
+
     /// 配置服务器（包括分端口模式）
     pub fn server_config(mut self, config: crate::server::config::ServerConfig) -> Self {
         self.server_config = config;
         self
     }
 
+    /// 设置端口配置（同端口或分端口模式），保留服务器配置中的其他选项
+    ///
+    /// 分端口模式下 HTTP 和 gRPC 各自监听一个端口，两者的证书可以通过
+    /// [`with_separated_certificates`](Self::with_separated_certificates) 分别配置
+    pub fn port_config(mut self, config: crate::server::port_config::PortConfig) -> Self {
+        self.server_config.port_config = config;
+        self
+    }
+
+    /// 为 gRPC 设置独立端口，切换到分端口模式
+    ///
+    /// HTTP 沿用当前配置的端口和绑定地址；之后调用 `start(host, port)` 时，
+    /// 传入的地址作为 HTTP 监听地址，gRPC 在同一主机的该端口上监听
+    pub fn grpc_port(mut self, port: u16) -> Self {
+        use crate::server::port_config::PortMode;
+        let (http_port, bind_addr) = match self.server_config.port_config.mode {
+            PortMode::Unified { port, bind_addr } => (port, bind_addr),
+            PortMode::Separated { http_port, bind_addr, .. } => (http_port, bind_addr),
+        };
+        self.server_config.port_config.mode = PortMode::Separated { http_port, grpc_port: port, bind_addr };
+        self
+    }
+
     /// 获取证书管理器的引用（用于测试和高级配置）
     pub fn get_cert_manager(&self) -> Option<&Arc<std::sync::RwLock<crate::server::cert_manager::CertificateManager>>> {
         self.cert_manager.as_ref()
@@ -429,14 +723,42 @@ impl RatEngineBuilder {
     }
     
     /// 启用自动日志初始化
+    ///
+    /// 使用 [`with_log_config`](Self::with_log_config) 设置的配置，未设置时使用默认的终端日志
     pub fn enable_logger(mut self) -> Self {
         self.auto_init_logger = true;
         self
     }
     
     /// 禁用自动日志初始化
+    ///
+    /// 同时关闭兜底日志：调用方没有自行初始化日志时，引擎的日志全部静默跳过
     pub fn disable_logger(mut self) -> Self {
         self.auto_init_logger = false;
+        self.fallback_log_config = None;
+        self
+    }
+    
+    /// 设置兜底日志配置
+    ///
+    /// 构建时既没有启用自动日志初始化、调用方也没有初始化日志的情况下，
+    /// 引擎使用此配置初始化日志，避免丢失全部诊断信息。
+    /// 默认为 [`LogConfig::minimal`](crate::utils::logger::LogConfig::minimal)，为 `None` 时不做兜底
+    ///
+    /// 注意：兜底日志会占用 rat_logger 的全局日志器，之后调用方再初始化日志不会生效。
+    /// 需要自定义日志时，请在构建引擎之前初始化，或传入 `None`
+    pub fn fallback_logger(mut self, log_config: Option<crate::utils::logger::LogConfig>) -> Self {
+        self.fallback_log_config = log_config;
+        self
+    }
+    
+    /// 设置日志中需要脱敏的头部（替换默认的 Authorization、Cookie、Set-Cookie、Proxy-Authorization）
+    pub fn redact_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redacted_headers = Some(headers.into_iter().map(Into::into).collect());
         self
     }
     
@@ -449,9 +771,9 @@ impl RatEngineBuilder {
     
     /// ⚠️ ACME 证书管理暂时不可用
     ///
-    /// 新的 rustls 实现暂不支持 ACME 自动证书。
-    /// 请使用 with_certificate_files() 配置静态证书文件。
-    #[deprecated(note = "ACME 暂不支持，请使用 with_certificate_files 配置静态证书")]
+    /// 新的 rustls 实现暂不内置 ACME 自动证书，调用会 panic。
+    /// 请使用 with_certificate_files() 配置静态证书文件，
+    /// 需要自动续期时通过 certificate_renewal_with_hook() 接入自己的签发流程。
     pub async fn cert_manager_acme(
         self,
         _domain: String,
@@ -465,41 +787,108 @@ impl RatEngineBuilder {
     }
     
     /// 构建引擎
-    pub fn build(mut self) -> Result<ActualRatEngine, Box<dyn std::error::Error + Send + Sync>> {
+    pub fn build(mut self) -> Result<ActualRatEngine, BuilderError> {
         if self.built {
-            return Err("Builder has already been used".into());
+            return Err(BuilderError::AlreadyBuilt);
         }
         
         // 必须提供路由器
         if self.router.is_none() {
-            return Err("Router must be provided. Use .router() method to set a router.".into());
+            return Err(BuilderError::MissingRouter);
         }
         
+        // 应用自定义 ALPN 协议列表，拒绝服务器无法处理的协议
+        if let Some(protocols) = self.alpn_protocols.take() {
+            crate::server::cert_manager::validate_alpn_protocols(&protocols)
+                .map_err(BuilderError::InvalidAlpn)?;
+            if let Some(cert_manager) = &self.cert_manager {
+                cert_manager.write()
+                    .map_err(|_| BuilderError::CertManagerPoisoned)?
+                    .set_alpn_protocols(protocols)
+                    .map_err(BuilderError::InvalidAlpn)?;
+            } else {
+                crate::utils::logger::warn!("⚠️ 未配置证书管理器，自定义 ALPN 协议列表不会生效");
+            }
+        }
+
+        // 绑定端口前校验证书配置，配置错误时立即失败
+        if let Some(cert_manager) = &self.cert_manager {
+            let cert_manager = cert_manager.read()
+                .map_err(|_| BuilderError::CertManagerPoisoned)?;
+            cert_manager.validate()?;
+        }
+
+        // 工作窃取路径只能处理明文 HTTP/1.1
+        if self.engine_config.http_path == HttpPath::WorkStealing {
+            let conflict = if self.cert_manager.is_some() {
+                Some("TLS 证书")
+            } else if self.router.as_ref().is_some_and(|router| !router.list_grpc_methods().is_empty()) {
+                Some("gRPC 服务")
+            } else if self.server_config.is_separated_mode() {
+                Some("分端口模式")
+            } else {
+                None
+            };
+            if let Some(conflict) = conflict {
+                return Err(BuilderError::UnsupportedHttpPath(format!("工作窃取路径不支持{}", conflict)));
+            }
+        }
+
+        // 分端口模式：端口不能冲突，gRPC 端口必须有证书
+        if let crate::server::port_config::PortMode::Separated { http_port, grpc_port, .. } = self.server_config.port_config.mode {
+            use crate::server::port_config::PortConfigError;
+            if http_port == grpc_port {
+                return Err(BuilderError::InvalidPortConfig(PortConfigError::PortConflict(grpc_port)));
+            }
+            let has_grpc_cert = match &self.cert_manager {
+                Some(cert_manager) => cert_manager.read()
+                    .map_err(|_| BuilderError::CertManagerPoisoned)?
+                    .has_grpc_cert(),
+                None => false,
+            };
+            if !has_grpc_cert {
+                return Err(BuilderError::InvalidPortConfig(PortConfigError::IncompatibleConfig(
+                    "分端口模式下 gRPC 端口必须配置 TLS 证书".to_string()
+                )));
+            }
+        }
+
         self.built = true;
         
-        // 如果启用，自动初始化日志系统（避免重复初始化）
-        if self.auto_init_logger {
-            // 检查日志系统是否已经初始化
-            if let Some(log_config) = &self.server_config.log_config {
-                match crate::utils::logger::Logger::init(log_config.clone()) {
-                    Ok(_) => {},
-                    Err(e) if e.to_string().contains("already initialized") => {
-                        // 日志系统已经初始化，忽略错误
-                    },
-                    Err(e) => {
-                        return Err(format!("日志系统初始化失败: {}", e).into());
-                    }
+        // 如果启用，自动初始化日志系统（避免重复初始化）；
+        // 否则在调用方没有初始化日志时使用兜底配置
+        let log_config = if self.auto_init_logger {
+            Some(self.server_config.log_config.clone().unwrap_or_default())
+        } else if !crate::utils::logger::mark_logger_ready() {
+            self.fallback_log_config.clone()
+        } else {
+            // 调用方已自行初始化日志
+            None
+        };
+        if let Some(log_config) = log_config {
+            match crate::utils::logger::Logger::init(log_config) {
+                Ok(_) => {},
+                Err(e) if e.to_string().contains("already initialized") => {
+                    // 日志系统已经初始化，忽略错误
+                },
+                Err(e) => {
+                    return Err(BuilderError::LoggerInitFailed(e.to_string()));
                 }
             }
         }
         
+        // 应用日志头部脱敏配置
+        if let Some(headers) = &self.redacted_headers {
+            crate::utils::logger::set_redacted_headers(headers);
+        }
+        
         let work_queue = Arc::new(WorkStealingQueue::new(self.engine_config.worker_threads));
         let connection_pool = Arc::new(ConnectionPool::new(self.engine_config.max_connections));
         let memory_pool = Arc::new(MemoryPool::new(self.engine_config.buffer_size));
         
         // 创建智能传输管理器
         let smart_transfer = Arc::new(SmartTransferManager::new()
-            .map_err(|e| format!("智能传输管理器初始化失败: {}", e))?);
+            .map_err(|e| BuilderError::TransferInitFailed(e.to_string()))?);
         
         let metrics = Arc::new(AtomicMetrics::new());
         
@@ -511,50 +900,103 @@ impl RatEngineBuilder {
             )
         ));
 
+        // 显式配置时 SSE 连接数上限与其他限制器共用同一份拒绝响应
+        if let Some(response) = self.sse_load_shed_response.take() {
+            crate::server::global_sse_manager::get_global_sse_manager().set_load_shed_response(response);
+        }
+
+        let shutdown_signal = Arc::new(tokio::sync::watch::channel(false).0);
+
         // 将证书管理器设置到 router（如果有的话）
         // 这样可以自动启用 HTTP/2 支持
-        let router = if let Some(cert_mgr) = &self.cert_manager {
-            if let Some(router) = self.router {
-                let mut r = router.clone();
+        // 同时注入性能指标，用于统计所有协议路径的活跃请求数
+        let router = self.router.map(|mut r| {
+            if let Some(cert_mgr) = &self.cert_manager {
                 r.set_cert_manager(cert_mgr.clone());
-                Some(Arc::new(r))
-            } else {
-                None
             }
-        } else {
-            self.router.map(Arc::new)
-        };
+            r.set_metrics(metrics.clone());
+            // 关闭时通知每个连接优雅关闭
+            r.set_shutdown_signal(shutdown_signal.subscribe());
+            // 只在显式配置时覆盖，避免冲掉路由器上已有的请求期限
+            if let Some(timeout) = self.engine_config.request_timeout {
+                r.set_request_timeout(Some(timeout));
+            }
+            r.set_connection_idle_timeout(Some(self.engine_config.connection_idle_timeout));
+            r.set_request_body_timeout(Some(self.engine_config.request_body_timeout));
+            r.set_tls_handshake_timeout(Some(self.engine_config.tls_handshake_timeout));
+            r.set_h2_config(self.engine_config.h2_config.clone());
+            r.set_expose_detection_debug(self.engine_config.expose_detection_debug);
+            r.set_quiet_startup(self.engine_config.quiet_startup);
+            if self.engine_config.response_rate_limit.is_some() {
+                r.set_response_rate_limit(self.engine_config.response_rate_limit);
+            }
+            if let Some(policy) = &self.protocol_policy {
+                r.set_protocol_policy(policy.clone());
+            }
+            if let Some(response) = &self.protocol_block_response {
+                r.set_protocol_block_response(Some(response.clone()));
+            }
+            if let Some(max_handshakes) = self.engine_config.max_concurrent_handshakes {
+                r.set_max_concurrent_handshakes(max_handshakes);
+            }
+            Arc::new(r)
+        });
 
         Ok(ActualRatEngine {
             work_queue,
+            work_available: Arc::new(tokio::sync::Notify::new()),
             connection_pool,
             memory_pool,
             smart_transfer,
             congestion_control,
             router,
             cert_manager: self.cert_manager,
+            cert_renewal: self.cert_renewal,
+            cert_renewal_task: Arc::new(tokio::sync::Mutex::new(None)),
             metrics,
             config: self.engine_config,
             server_config: self.server_config,
             worker_handles: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            bound_addr: Arc::new(std::sync::RwLock::new(None)),
+            shutdown_signal,
+            serving: Arc::new(tokio::sync::watch::channel(false).0),
         })
     }
     
     /// 构建并启动服务器
-    pub async fn build_and_start(self, host: String, port: u16) -> Result<ActualRatEngine, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn build_and_start(self, host: String, port: u16) -> Result<ActualRatEngine, BuilderError> {
         let engine = self.build()?;
-        engine.start(host, port).await?;
+        engine.start(host, port).await.map_err(BuilderError::StartFailed)?;
         Ok(engine)
     }
 }
 
 impl ActualRatEngine {
-    /// 启动服务器（单端口模式）
+    /// 启动服务器
     ///
-    /// 如果配置了分端口模式，请使用 `start_separated()` 方法
+    /// 单端口模式下 HTTP 和 gRPC 共用 `host:port`；
+    /// 通过 `grpc_port()` 或 `port_config()` 配置了分端口模式时，`host:port` 作为 HTTP 监听地址，
+    /// gRPC 在同一主机的配置端口上监听
     pub async fn start(&self, host: String, port: u16) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // 重定向到更明确的方法名
-        self.start_single_port_multi_protocol(host, port).await
+        let Some(grpc_addr) = self.server_config.grpc_addr() else {
+            // 重定向到更明确的方法名
+            return self.start_single_port_multi_protocol(host, port).await;
+        };
+
+        let http_addr = tokio::net::lookup_host((host.as_str(), port)).await?
+            .next()
+            .ok_or_else(|| format!("无法解析监听地址: {}:{}", host, port))?;
+        if http_addr.port() == grpc_addr.port() {
+            return Err(crate::server::port_config::PortConfigError::PortConflict(port).into());
+        }
+
+        let mut config = self.server_config.clone();
+        config.port_config.mode = crate::server::port_config::PortMode::Separated {
+            http_port: http_addr.port(),
+            grpc_port: grpc_addr.port(),
+            bind_addr: http_addr.ip(),
+        };
+        self.serve_separated(config).await
     }
 
     /// 启动服务器（单端口多协议模式）
@@ -609,6 +1051,16 @@ impl ActualRatEngine {
         let addr = format!("{}:{}", host, port);
         let listener = TcpListener::bind(&addr).await?;
 
+        // 记录实际绑定地址（端口为 0 时由系统分配）
+        let local_addr = listener.local_addr()?;
+        if let Ok(mut bound_addr) = self.bound_addr.write() {
+            *bound_addr = Some(local_addr);
+        }
+        // 绑定成功即视为运行中，此后调用 shutdown() 会等待接受循环退出；
+        // 守卫在返回（包括出错和 panic）时复位，避免 shutdown() 一直等待
+        let _serving = ServingGuard::new(self.serving.clone());
+        let addr = local_addr.to_string();
+
         // ============ 证书校验 ============
         // gRPC 强制要求 TLS 证书
         if let Some(router) = &self.router {
@@ -651,22 +1103,7 @@ impl ActualRatEngine {
         
         // 打印已注册的路由
         if let Some(router) = &self.router {
-            let routes = router.list_routes();
-            let grpc_methods = router.list_grpc_methods();
-            
-            if !routes.is_empty() {
-                crate::utils::logger::info!("📋 已注册的 HTTP 路由:");
-                for (method, path) in routes {
-                    crate::utils::logger::info!("   {} {}", method, path);
-                }
-            }
-            
-            if !grpc_methods.is_empty() {
-                crate::utils::logger::info!("📝 已注册的 gRPC 方法:");
-                for method in grpc_methods {
-                    crate::utils::logger::info!("   {}", method);
-                }
-            }
+            router.log_registered_routes();
         }
         
         crate::utils::logger::info!("🌐 服务器支持 HTTP 请求");
@@ -674,15 +1111,47 @@ impl ActualRatEngine {
         // 注意：rustls 的 ALPN 在创建 ServerConfig 时已经设置（只支持 h2）
         // 不需要在这里配置 ALPN
 
-        // 启动工作线程
-        self.start_workers().await;
+        // 启动预热（如果启用）
+        if self.config.prewarm {
+            self.warmup();
+        }
+
+        // 工作线程只服务工作窃取路径，hyper 路径下不启动，避免空轮询
+        if self.config.http_path == HttpPath::WorkStealing {
+            self.start_workers().await;
+        }
+
+        // 启动证书续期任务（如果配置了）
+        self.start_cert_renewal().await;
+
+        // 适配器和证书管理器在接受循环外只构建一次，所有连接共享
+        // 每个连接只做 Arc 引用计数递增，相比逐连接构建适配器，
+        // 每个连接少一次 Arc<HyperAdapter> 堆分配（64 位平台 24 字节）和一次证书管理器查询；
+        // 可用 examples/adapter_sharing_performance_test.rs 测量（共享后接受循环中为 0 次分配/连接）
+        let shared = self.router.as_ref().map(|router| {
+            let adapter = Arc::new(crate::server::hyper_adapter::HyperAdapter::new(router.clone()));
+            // 优先使用router中的证书管理器，否则使用engine的证书管理器
+            let cert_manager = router.get_cert_manager().or_else(|| self.cert_manager.clone());
+            (router.clone(), adapter, cert_manager)
+        });
         
+        // 每个连接任务持有一个接收端，全部释放即表示连接已排空
+        let (drain_tx, drain_rx) = tokio::sync::watch::channel(());
+        let mut shutdown_rx = self.shutdown_signal.subscribe();
+
         // 主接受循环
         loop {
-            match listener.accept().await {
+            let accepted = tokio::select! {
+                _ = shutdown_rx.wait_for(|stop| *stop) => break,
+                accepted = listener.accept() => accepted,
+            };
+
+            match accepted {
                 Ok((stream, addr)) => {
                     if !self.connection_pool.try_acquire() {
                         crate::utils::logger::warn!("Connection limit reached, dropping connection from {}", addr);
+                        // 只对明文 HTTP/1 请求写回拒绝响应，其他协议直接关闭
+                        crate::server::load_shed::reject_connection(stream, &self.config.load_shed_response);
                         continue;
                     }
                     
@@ -693,19 +1162,31 @@ impl ActualRatEngine {
                         let _ = stream.set_nodelay(true);
                     }
                     
+                    if self.config.http_path == HttpPath::WorkStealing {
+                        // 连接结束时释放连接名额；连接存活期间参与关闭时的排空
+                        let task = HttpTask::new(stream, addr, self.memory_pool.clone()).with_drain(drain_rx.clone());
+                        self.submit_task(task).await;
+                        continue;
+                    }
+                    
                     // 使用协议检测处理连接
-                    if let Some(router) = &self.router {
+                    if let Some((router, adapter, cert_manager)) = &shared {
+                        let permit = match router.handshake_limiter() {
+                            Some(limiter) => Some(limiter.acquire().await),
+                            None => None,
+                        };
                         let router = router.clone();
-                        let adapter = Arc::new(crate::server::hyper_adapter::HyperAdapter::new(router.clone()));
-                        // 优先使用router中的证书管理器，否则使用engine的证书管理器
-                        let cert_manager = router.get_cert_manager().or_else(|| self.cert_manager.clone());
+                        let adapter = adapter.clone();
+                        let cert_manager = cert_manager.clone();
+                        let drain = drain_rx.clone();
 
                         // 异步处理连接（使用协议检测）
-                        tokio::spawn(async move {
+                        tokio::spawn(crate::server::handshake_limiter::scope(permit, async move {
+                            let _drain = drain;
                             if let Err(e) = crate::server::detect_and_handle_protocol_with_tls(stream, addr, router, adapter, cert_manager).await {
                                 crate::utils::logger::error!("连接处理失败: {}: {}", addr, e);
                             }
-                        });
+                        }));
                     } else {
                         crate::utils::logger::error!("路由器未配置，无法处理连接");
                         drop(stream);
@@ -716,6 +1197,24 @@ impl ActualRatEngine {
                 }
             }
         }
+
+        // 停止接受新连接，等待进行中的连接结束
+        drop(listener);
+        drop(drain_rx);
+        let in_flight = drain_tx.receiver_count();
+        if in_flight > 0 {
+            crate::utils::logger::info!("🛑 已停止接受新连接，等待 {} 个进行中的连接结束...", in_flight);
+            if tokio::time::timeout(self.config.shutdown_timeout, drain_tx.closed()).await.is_err() {
+                crate::utils::logger::warn!(
+                    "⚠️ 等待连接结束超时（{:?}），仍有 {} 个连接未结束，不再等待",
+                    self.config.shutdown_timeout,
+                    drain_tx.receiver_count()
+                );
+            }
+        }
+
+        crate::utils::logger::info!("✅ 服务器已停止: {}", addr);
+        Ok(())
     }
 
     /// 启动服务器（分端口模式）
@@ -728,98 +1227,144 @@ impl ActualRatEngine {
             return Err("单端口模式请使用 start(host, port) 方法，而不是 start_separated()".into());
         }
 
+        self.serve_separated(self.server_config.clone()).await
+    }
+
+    /// 按给定的分端口配置启动 HTTP 和 gRPC 监听器
+    ///
+    /// 与单端口模式一样响应 [`Self::shutdown`]：停止接受新连接，等待进行中的连接结束（最长 `shutdown_timeout`）
+    async fn serve_separated(&self, config: crate::server::config::ServerConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let router = self.router.clone().ok_or("分端口模式必须配置路由器")?;
+
+        // 启动证书续期任务（如果配置了）
+        self.start_cert_renewal().await;
+
+        let _serving = ServingGuard::new(self.serving.clone());
+        let (drain_tx, drain_rx) = tokio::sync::watch::channel(());
+        let mut shutdown_rx = self.shutdown_signal.subscribe();
+        let shutdown = async move {
+            let _ = shutdown_rx.wait_for(|stop| *stop).await;
+        };
+
         // 调用分端口服务器，传递证书管理器
-        crate::server::run_separated_server(
-            self.server_config.clone(),
-            self.router.clone().ok_or("分端口模式必须配置路由器")?,
-            self.cert_manager.clone()
-        ).await.map_err(|e| e.into())
+        let result = crate::server::serve_separated_until(
+            config,
+            router,
+            self.cert_manager.clone(),
+            shutdown,
+            Some(drain_rx),
+        ).await;
+
+        let in_flight = drain_tx.receiver_count();
+        if in_flight > 0 {
+            crate::utils::logger::info!("🛑 已停止接受新连接，等待 {} 个进行中的连接结束...", in_flight);
+            if tokio::time::timeout(self.config.shutdown_timeout, drain_tx.closed()).await.is_err() {
+                crate::utils::logger::warn!(
+                    "⚠️ 等待连接结束超时（{:?}），仍有 {} 个连接未结束，不再等待",
+                    self.config.shutdown_timeout,
+                    drain_tx.receiver_count()
+                );
+            }
+        }
+
+        result.map_err(|e| e.into())
+    }
+
+    /// 启动证书续期后台任务
+    ///
+    /// 任务已在运行时不会重复启动；任务随关闭信号结束，句柄保留以便关闭时中止
+    async fn start_cert_renewal(&self) {
+        if let (Some((config, hook)), Some(cert_manager)) = (&self.cert_renewal, &self.cert_manager) {
+            let mut task = self.cert_renewal_task.lock().await;
+            if task.as_ref().is_some_and(|handle| !handle.is_finished()) {
+                return;
+            }
+            *task = Some(crate::server::cert_manager::spawn_renewal_task(
+                cert_manager.clone(),
+                config.clone(),
+                hook.clone(),
+                self.shutdown_signal.subscribe(),
+            ));
+        }
+    }
+
+    /// 提交 HTTP 连接到工作窃取路径
+    ///
+    /// 连接先由独立的读取任务等待完整请求（受 `connection_idle_timeout` 约束，不占用工作线程），
+    /// 读到完整请求后才进入工作队列。调用方需要先占用一个连接名额，连接结束时由引擎释放。
+    ///
+    /// 工作队列深度受 `max_queue_depth` 限制（未配置时为 `max_connections`），
+    /// 队列已满时直接向客户端返回过载拒绝响应（默认 503）。
+    /// 只有 [`HttpPath::WorkStealing`] 模式会启动工作线程，其他模式下提交的连接同样以过载拒绝响应关闭
+    pub async fn submit_task(&self, mut task: HttpTask) {
+        if self.config.http_path != HttpPath::WorkStealing {
+            crate::utils::logger::warn!("⚠️ 当前 HTTP 处理路径为 {:?}，工作线程未启动，拒绝连接 {}", self.config.http_path, task.remote_addr());
+            let _ = task.send_response(self.config.load_shed_response.to_http1_bytes()).await;
+            task.close().await;
+            self.connection_pool.release();
+            return;
+        }
+        self.work_stealing_context().await_request(task);
+    }
+    
+    /// 工作窃取路径的共享状态
+    fn work_stealing_context(&self) -> WorkStealingContext {
+        WorkStealingContext {
+            work_queue: self.work_queue.clone(),
+            work_available: self.work_available.clone(),
+            connection_pool: self.connection_pool.clone(),
+            metrics: self.metrics.clone(),
+            router: self.router.clone(),
+            shutdown: self.shutdown_signal.subscribe(),
+            idle_timeout: self.config.connection_idle_timeout,
+            max_queue_depth: self.config.max_queue_depth.unwrap_or(self.config.max_connections),
+            load_shed_response: self.config.load_shed_response.clone(),
+        }
+    }
+    
+    /// 获取因队列饱和被拒绝的请求数
+    pub fn rejected_requests(&self) -> u64 {
+        self.metrics.rejected_requests()
+    }
+    
+    /// 预热内存池
+    ///
+    /// 写入一遍预分配的缓冲区，让操作系统提前完成物理页分配，返回耗时
+    pub fn warmup(&self) -> Duration {
+        let started = Instant::now();
+        let touched = self.memory_pool.warmup();
+        let elapsed = started.elapsed();
+        crate::utils::logger::info!(
+            "🔥 预热完成: 内存池 {} KB，耗时 {:?}",
+            touched / 1024,
+            elapsed
+        );
+        elapsed
     }
 
     /// 启动工作线程
     async fn start_workers(&self) {
         let mut handles = self.worker_handles.lock().await;
         for worker_id in 0..self.config.worker_threads {
-            let work_queue = self.work_queue.clone();
-            let connection_pool = self.connection_pool.clone();
-            let router = self.router.clone();
-            let metrics = self.metrics.clone();
-            let timeout = self.config.timeout;
-            
-            let handle = tokio::spawn(async move {
-                Self::worker_loop(worker_id, work_queue, connection_pool, router, metrics, timeout).await;
-            });
-            
-            handles.push(handle);
+            let context = self.work_stealing_context();
+            handles.push(tokio::spawn(context.worker_loop(worker_id)));
         }
         
         crate::utils::logger::info!("✅ Started {} worker threads", self.config.worker_threads);
     }
     
-    /// 工作线程主循环
-    async fn worker_loop(
-        worker_id: usize,
-        work_queue: Arc<WorkStealingQueue<HttpTask>>,
-        connection_pool: Arc<ConnectionPool>,
-        router: Option<Arc<crate::server::Router>>,
-        metrics: Arc<AtomicMetrics>,
-        timeout: Duration,
-    ) {
-        crate::utils::logger::debug!("Worker {} started", worker_id);
-        
-        let mut empty_count = 0;
-        
-        loop {
-            // 从队列中获取任务
-            if let Some(mut task) = work_queue.pop(worker_id) {
-                empty_count = 0; // 重置空计数
-                
-                let start_time = Instant::now();
-                
-                // 处理 HTTP 请求
-                let result = Self::process_http_task(&mut task, &router, timeout).await;
-                
-                // 记录性能指标
-                let duration = start_time.elapsed();
-                metrics.record_request_duration(duration);
-                
-                if result.is_err() {
-                    metrics.increment_errors();
-                }
-                
-                // 释放连接
-                connection_pool.release();
-            } else {
-                empty_count += 1;
-                
-                // 没有任务时使用指数退避策略，避免忙等待
-                let sleep_duration = if empty_count < 10 {
-                    // 前10次空轮询：5ms
-                    Duration::from_millis(5)
-                } else if empty_count < 50 {
-                    // 10-50次：20ms
-                    Duration::from_millis(20)
-                } else {
-                    // 50次后：100ms
-                    Duration::from_millis(100)
-                };
-                
-                tokio::time::sleep(sleep_duration).await;
-            }
-        }
-    }
-    
-    /// 处理单个 HTTP 任务
-    async fn process_http_task(
+    /// 处理一个已读取的请求并写出响应
+    ///
+    /// 返回连接是否可以继续使用：`close_connection` 为 true 或返回了 500 错误响应时为 false，
+    /// 此时响应中都声明了 `Connection: close`
+    async fn respond_to_request(
         task: &mut HttpTask,
+        request: HttpRequest,
         router: &Option<Arc<crate::server::Router>>,
-        timeout: Duration,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        close_connection: bool,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         let start_time = Instant::now();
         
-        // 读取 HTTP 请求
-        let request = task.read_request().await?;
-        
         // 记录请求日志
         crate::utils::logger::debug!("🔍 [引擎] 处理 HTTP 请求: {} {}", request.method, request.path);
         
@@ -827,7 +1372,7 @@ impl ActualRatEngine {
         let server_request = crate::server::http_request::HttpRequest {
             method: hyper::Method::from_bytes(request.method.as_bytes())?,
             uri: format!("{}{}", request.path, request.query_string).parse()?,
-            version: hyper::Version::HTTP_11,
+            version: request.version,
             headers: request.headers.iter().filter_map(|(k, v)| {
                 Some((hyper::header::HeaderName::from_bytes(k.as_bytes()).ok()?, hyper::header::HeaderValue::from_str(v).ok()?))
             }).collect(),
@@ -836,31 +1381,92 @@ impl ActualRatEngine {
             source: crate::server::http_request::RequestSource::Http1,
             path_params: std::collections::HashMap::new(),
             python_handler_name: None,
+            tls_info: None,
+            state: Default::default(),
+            real_ip: None,
+            client_disconnect: Default::default(),
+            trailers: None,
         };
         
+        // 与 hyper 路径相同的请求体大小上限和读取请求体之前的检查
+        if let Some(router) = router {
+            let mut builder = hyper::Request::builder()
+                .method(server_request.method.clone())
+                .uri(server_request.uri.clone())
+                .version(server_request.version);
+            if let Some(headers) = builder.headers_mut() {
+                *headers = server_request.headers.clone();
+            }
+            let (parts, ()) = builder.body(())?.into_parts();
+            // 声明的 Content-Length 之外，再按解码后的请求体（包括分块传输编码）检查上限
+            let rejection = router.reject_buffered_request(&parts).or_else(|| {
+                let max = router.max_request_body_size()?;
+                if server_request.body.len() <= max {
+                    return None;
+                }
+                crate::utils::logger::warn!("🚫 [引擎] 请求体 {} 字节超过限制 {} 字节，返回 413", server_request.body.len(), max);
+                Some(router.connection_error_response(hyper::StatusCode::PAYLOAD_TOO_LARGE, "Payload Too Large"))
+            });
+            if let Some(mut response) = rejection {
+                crate::utils::logger::debug!("🚫 [引擎] 请求被拒绝: {} {} -> {}", request.method, request.path, response.status());
+                Self::declare_connection(&mut response, request.version, close_connection);
+                let response_data = Self::convert_response_to_bytes(response, request.version).await?;
+                task.send_response(response_data).await?;
+                return Ok(!close_connection);
+            }
+        }
+        
         // 使用路由器处理请求
         if let Some(router) = router {
-            // 使用路由器处理请求
-            let result = router.handle_http(server_request).await;
+            let result = if request.version < hyper::Version::HTTP_11 {
+                // HTTP/1.0 客户端不支持 1xx 中间响应，Early Hints 由路由器合并到最终响应的 Link 头部
+                router.handle_http(server_request).await
+            } else {
+                // 使用路由器处理请求，处理期间提交的 Early Hints 立即以 103 中间响应写出
+                let (hints_tx, mut hints_rx) = tokio::sync::mpsc::unbounded_channel();
+                let handling = crate::server::early_hints::scope_interim(hints_tx, router.handle_http(server_request));
+                tokio::pin!(handling);
+                let result = loop {
+                    tokio::select! {
+                        result = &mut handling => break result,
+                        Some(hints) = hints_rx.recv() => {
+                            task.send_response(crate::server::early_hints::encode_http1(&hints)).await?;
+                        }
+                    }
+                };
+                while let Ok(hints) = hints_rx.try_recv() {
+                    task.send_response(crate::server::early_hints::encode_http1(&hints)).await?;
+                }
+                result
+            };
             let total_duration = start_time.elapsed();
             
             match result {
-                Ok(response) => {
+                Ok(mut response) => {
                     let status_code = response.status().as_u16();
                     
-                    // 统计信息日志（info 级别，生产环境可见）
-                    crate::utils::logger::info!(
-                        "📊 {} {} {} {} {}ms", 
-                        request.real_ip, 
-                        request.method, 
-                        request.path, 
-                        status_code, 
-                        total_duration.as_millis()
+                    // 处理完该请求后关闭连接时在响应中声明，剩余数据不再处理
+                    Self::declare_connection(&mut response, request.version, close_connection);
+                    
+                    // 统计信息日志（默认 info 级别，可按路由调整或关闭）
+                    crate::utils::logger::log_at(
+                        router.access_log_level(&request.path),
+                        format_args!(
+                            "📊 {} {} {} {} {}ms",
+                            request.real_ip,
+                            request.method,
+                            request.path,
+                            status_code,
+                            total_duration.as_millis()
+                        ),
                     );
                     
-                    // 转换响应为字节数据
-                    let response_data = Self::convert_response_to_bytes(response).await?;
-                    task.send_response(response_data).await?;
+                    // 处理器指定的 TCP 写出策略
+                    let write_mode = response.extensions().get::<crate::server::tcp_write_mode::TcpWriteMode>().copied();
+
+                    // 转换响应为字节数据，状态行使用请求的协议版本
+                    let response_data = Self::convert_response_to_bytes(response, request.version).await?;
+                    task.send_response_with_mode(response_data, write_mode).await?;
                 }
                 Err(e) => {
                     // 错误访问日志 - error级别
@@ -874,8 +1480,8 @@ impl ActualRatEngine {
                     );
                     
                     crate::utils::logger::error!("❌ [引擎] 路由器处理请求失败: {}", e);
-                    let error_response = b"HTTP/1.1 500 Internal Server Error\r\nContent-Type: text/plain\r\n\r\nInternal Server Error";
-                    task.send_response(error_response.to_vec()).await?;
+                    task.send_response(Self::internal_error_bytes(request.version, "Internal Server Error")).await?;
+                    return Ok(false);
                 }
             }
         } else {
@@ -892,45 +1498,88 @@ impl ActualRatEngine {
                 total_duration.as_millis()
             );
             
-            let response_data = b"HTTP/1.1 500 Internal Server Error\r\n\r\nNo router configured";
-            task.send_response(response_data.to_vec()).await?;
+            task.send_response(Self::internal_error_bytes(request.version, "No router configured")).await?;
+            return Ok(false);
         }
         
-        Ok(())
+        Ok(!close_connection)
+    }
+    
+    /// 处理器失败时的 500 响应，带 `Content-Length` 并声明关闭连接
+    fn internal_error_bytes(version: hyper::Version, message: &str) -> Vec<u8> {
+        let version_str = match version {
+            hyper::Version::HTTP_10 => "HTTP/1.0",
+            _ => "HTTP/1.1",
+        };
+        format!(
+            "{} 500 Internal Server Error\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            version_str,
+            message.len(),
+            message
+        ).into_bytes()
     }
     
+    /// 在响应中声明连接是否保持
+    ///
+    /// 关闭时写入 `Connection: close`；HTTP/1.0 默认关闭，保持连接时需要显式写入 `Connection: keep-alive`
+    fn declare_connection<B>(response: &mut hyper::Response<B>, version: hyper::Version, close_connection: bool) {
+        if close_connection {
+            response.headers_mut().insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("close"));
+        } else if version == hyper::Version::HTTP_10 {
+            response.headers_mut().insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("keep-alive"));
+        }
+    }
+
     /// 将 hyper::Response 转换为 HTTP 响应字节数据
+    ///
+    /// - 状态行版本使用连接实际协商的版本（HTTP/1.0 或 HTTP/1.1），而不是处理器设置的版本
+    /// - 响应体已完整收集，统一使用 `Content-Length` 分帧，移除处理器设置的
+    ///   `Content-Length` / `Transfer-Encoding`，避免重复或冲突的分帧头
     async fn convert_response_to_bytes(
         response: hyper::Response<http_body_util::combinators::BoxBody<hyper::body::Bytes, Box<dyn std::error::Error + Send + Sync>>>,
+        connection_version: hyper::Version,
     ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
         use http_body_util::BodyExt;
         
         let (parts, body) = response.into_parts();
         let body_bytes = body.collect().await?.to_bytes();
         
-        // 构建 HTTP 响应字符串
-        let version_str = match parts.version {
-            hyper::Version::HTTP_09 => "HTTP/0.9",
+        // 工作窃取路径只处理 HTTP/1.x 连接
+        let version_str = match connection_version {
             hyper::Version::HTTP_10 => "HTTP/1.0",
-            hyper::Version::HTTP_11 => "HTTP/1.1",
-            hyper::Version::HTTP_2 => "HTTP/2.0",
-            hyper::Version::HTTP_3 => "HTTP/3.0",
             _ => "HTTP/1.1",
         };
-        let mut response_str = format!("{} {} {}\r\n", version_str, parts.status.as_u16(), parts.status.canonical_reason().unwrap_or("OK"));
+
+        let mut response_bytes = Vec::with_capacity(256 + body_bytes.len());
+        response_bytes.extend_from_slice(format!(
+            "{} {} {}\r\n",
+            version_str,
+            parts.status.as_u16(),
+            parts.status.canonical_reason().unwrap_or("Unknown")
+        ).as_bytes());
         
-        // 添加头部
+        // 添加头部（跳过分帧相关头部，由下面统一设置）
         for (name, value) in parts.headers.iter() {
-            if let Ok(value_str) = value.to_str() {
-                response_str.push_str(&format!("{}: {}\r\n", name, value_str));
+            if name == hyper::header::CONTENT_LENGTH || name == hyper::header::TRANSFER_ENCODING {
+                continue;
             }
+            response_bytes.extend_from_slice(name.as_str().as_bytes());
+            response_bytes.extend_from_slice(b": ");
+            response_bytes.extend_from_slice(value.as_bytes());
+            response_bytes.extend_from_slice(b"\r\n");
+        }
+
+        // 1xx / 204 / 304 响应不允许携带响应体
+        let status = parts.status.as_u16();
+        let bodyless = parts.status.is_informational() || status == 204 || status == 304;
+        if !bodyless {
+            response_bytes.extend_from_slice(format!("content-length: {}\r\n", body_bytes.len()).as_bytes());
         }
         
-        response_str.push_str("\r\n");
-        
-        // 转换为字节数组
-        let mut response_bytes = response_str.into_bytes();
-        response_bytes.extend_from_slice(&body_bytes);
+        response_bytes.extend_from_slice(b"\r\n");
+        if !bodyless {
+            response_bytes.extend_from_slice(&body_bytes);
+        }
         
         Ok(response_bytes)
     }
@@ -945,6 +1594,13 @@ impl ActualRatEngine {
         self.metrics.reset();
     }
     
+    /// 获取正在处理的请求数（HTTP 与 gRPC 全部协议路径）
+    ///
+    /// 配合优雅关闭使用：停止接收新连接后，等待该值归零再终止进程
+    pub fn active_requests(&self) -> usize {
+        self.metrics.active_requests()
+    }
+    
     /// 获取工作线程数
     pub fn get_workers(&self) -> usize {
         self.config.worker_threads
@@ -955,14 +1611,38 @@ impl ActualRatEngine {
         self.config.max_connections
     }
     
+    /// 获取引擎配置
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+    
+    /// 获取服务器配置
+    pub fn server_config(&self) -> &crate::server::config::ServerConfig {
+        &self.server_config
+    }
+    
+    /// 获取实际绑定的监听地址（服务器启动前返回 None）
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        self.bound_addr.read().ok().and_then(|addr| *addr)
+    }
+    
     /// 获取主机地址
-    pub fn get_host(&self) -> &str {
-        "127.0.0.1" // 默认值，可以从配置中获取
+    ///
+    /// 启动后返回实际绑定的地址，启动前返回服务器配置中的地址
+    pub fn get_host(&self) -> String {
+        self.local_addr()
+            .unwrap_or_else(|| self.server_config.addr())
+            .ip()
+            .to_string()
     }
     
     /// 获取端口
+    ///
+    /// 启动后返回实际绑定的端口，启动前返回服务器配置中的端口
     pub fn get_port(&self) -> u16 {
-        8000 // 默认值，可以从配置中获取
+        self.local_addr()
+            .unwrap_or_else(|| self.server_config.addr())
+            .port()
     }
     
     /// 获取智能传输管理器
@@ -1016,21 +1696,23 @@ impl ActualRatEngine {
     }
     
     /// 获取拥塞控制统计信息
+    ///
+    /// 拥塞控制未启用时返回空表，而不是一组默认值
     pub async fn get_congestion_stats(&self) -> HashMap<String, f64> {
         let manager = self.congestion_control.lock().await;
         manager.get_stats()
     }
     
-    /// 获取拥塞控制窗口大小
-    pub async fn get_congestion_window(&self) -> u32 {
+    /// 获取拥塞控制窗口大小，拥塞控制未启用时返回 `None`
+    pub async fn get_congestion_window(&self) -> Option<u32> {
         let manager = self.congestion_control.lock().await;
-        manager.window_size() as u32
+        manager.is_enabled().then(|| manager.window_size() as u32)
     }
     
-    /// 获取拥塞控制发送速率
-    pub async fn get_congestion_send_rate(&self) -> f64 {
+    /// 获取拥塞控制发送速率，拥塞控制未启用时返回 `None`
+    pub async fn get_congestion_send_rate(&self) -> Option<f64> {
         let manager = self.congestion_control.lock().await;
-        manager.pacing_rate() as f64
+        manager.is_enabled().then(|| manager.pacing_rate() as f64)
     }
     
     /// 处理数据包发送事件（用于拥塞控制）
@@ -1058,9 +1740,22 @@ impl ActualRatEngine {
     }
     
     /// 优雅关闭
+    ///
+    /// 通知接受循环停止接受新连接，等待进行中的连接结束（最长 `shutdown_timeout`），
+    /// 随后停止工作线程。可在其他任务或线程中调用，正在运行的 `start()` 随之返回；
+    /// 关闭后引擎不能再次启动
     pub async fn shutdown(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         crate::utils::logger::info!("🛑 Shutting down RAT Engine...");
         
+        self.shutdown_signal.send_replace(true);
+        let mut serving = self.serving.subscribe();
+        let _ = serving.wait_for(|running| !*running).await;
+
+        // 续期任务收到关闭信号后自行结束，这里中止以防钩子仍在执行
+        if let Some(handle) = self.cert_renewal_task.lock().await.take() {
+            handle.abort();
+        }
+        
         // 等待所有工作线程完成
         let mut handles = self.worker_handles.lock().await;
         for handle in handles.drain(..) {
@@ -1070,4 +1765,184 @@ impl ActualRatEngine {
         crate::utils::logger::info!("✅ RAT Engine shutdown complete");
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// 运行状态守卫
+///
+/// 创建时把 `serving` 置为运行中，离开作用域时复位，等待关闭的调用方不会因提前返回或 panic 而一直挂起
+struct ServingGuard(Arc<tokio::sync::watch::Sender<bool>>);
+
+impl ServingGuard {
+    fn new(serving: Arc<tokio::sync::watch::Sender<bool>>) -> Self {
+        serving.send_replace(true);
+        Self(serving)
+    }
+}
+
+impl Drop for ServingGuard {
+    fn drop(&mut self) {
+        self.0.send_replace(false);
+    }
+}
+
+/// 工作窃取路径的共享状态
+///
+/// 连接在两种状态之间切换：等待请求时由独立的读取任务持有，受空闲超时约束，不占用工作线程；
+/// 读到完整请求后进入工作队列，由工作线程执行处理器并写出响应，保持连接时随后回到等待状态
+#[derive(Clone)]
+struct WorkStealingContext {
+    work_queue: Arc<WorkStealingQueue<HttpTask>>,
+    work_available: Arc<tokio::sync::Notify>,
+    connection_pool: Arc<ConnectionPool>,
+    metrics: Arc<AtomicMetrics>,
+    router: Option<Arc<crate::server::Router>>,
+    shutdown: tokio::sync::watch::Receiver<bool>,
+    idle_timeout: Duration,
+    max_queue_depth: usize,
+    load_shed_response: crate::server::load_shed::LoadShedResponse,
+}
+
+impl WorkStealingContext {
+    /// 为连接启动读取任务，读到完整请求后放入工作队列
+    fn await_request(&self, task: HttpTask) {
+        tokio::spawn(self.clone().read_request(task));
+    }
+
+    /// 等待下一个完整请求
+    ///
+    /// - 空闲超过 `idle_timeout` 时关闭连接；请求只发送了一部分时先返回 408
+    /// - 请求超过缓冲区上限时返回 413，请求行无法解析时返回 400
+    /// - 引擎关闭时不再等待新请求
+    async fn read_request(mut self, mut task: HttpTask) {
+        let read = tokio::select! {
+            read = tokio::time::timeout(self.idle_timeout, task.read_request()) => Some(read),
+            _ = self.shutdown.wait_for(|stop| *stop) => None,
+        };
+
+        match read {
+            Some(Ok(Ok(request))) => {
+                task.set_pending_request(request);
+                self.enqueue(task).await;
+            }
+            Some(Err(_)) if task.has_buffered_data() => {
+                crate::utils::logger::warn!("⏱️ [引擎] 读取请求超时（{:?}），返回 408: {}", self.idle_timeout, task.remote_addr());
+                self.reject(&mut task, hyper::StatusCode::REQUEST_TIMEOUT, "Request Timeout").await;
+                self.finish(task);
+            }
+            Some(Ok(Err(network::HttpError::RequestTooLarge))) => {
+                crate::utils::logger::warn!("🚫 [引擎] 请求超过缓冲区上限，返回 413: {}", task.remote_addr());
+                self.reject(&mut task, hyper::StatusCode::PAYLOAD_TOO_LARGE, "Payload Too Large").await;
+                self.finish(task);
+            }
+            Some(Ok(Err(network::HttpError::InvalidRequest(reason)))) => {
+                crate::utils::logger::debug!("[引擎] 无法解析的请求 {}: {}", task.remote_addr(), reason);
+                self.reject(&mut task, hyper::StatusCode::BAD_REQUEST, "Bad Request").await;
+                self.finish(task);
+            }
+            // 空闲超时、客户端关闭、读取失败或引擎关闭
+            _ => self.finish(task),
+        }
+    }
+
+    /// 把已读取请求的连接放入工作队列，队列已满时返回过载拒绝响应
+    async fn enqueue(&self, task: HttpTask) {
+        match self.work_queue.try_push(task, None, self.max_queue_depth) {
+            Ok(()) => self.work_available.notify_one(),
+            Err(mut task) => {
+                self.metrics.record_rejected_request();
+                crate::utils::logger::warn!("⚠️ {}（深度上限 {}），拒绝请求", LoadShedReason::QueueFull.as_str(), self.max_queue_depth);
+                if let Err(e) = task.send_response(self.load_shed_response.to_http1_bytes()).await {
+                    crate::utils::logger::debug!("发送过载拒绝响应失败: {:?}", e);
+                }
+                self.finish(task);
+            }
+        }
+    }
+
+    /// 写出连接层错误响应，并声明关闭连接
+    async fn reject(&self, task: &mut HttpTask, status: hyper::StatusCode, message: &str) {
+        let Some(router) = &self.router else {
+            return;
+        };
+        let mut response = router.connection_error_response(status, message);
+        response.headers_mut().insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("close"));
+        match ActualRatEngine::convert_response_to_bytes(response, hyper::Version::HTTP_11).await {
+            Ok(bytes) => {
+                let _ = task.send_response(bytes).await;
+            }
+            Err(e) => crate::utils::logger::debug!("构建错误响应失败: {}", e),
+        }
+    }
+
+    /// 在后台关闭连接并释放连接名额，不占用工作线程等待客户端
+    fn finish(&self, mut task: HttpTask) {
+        let connection_pool = self.connection_pool.clone();
+        tokio::spawn(async move {
+            task.close().await;
+            connection_pool.release();
+        });
+    }
+
+    /// 工作线程主循环
+    ///
+    /// 队列为空时等待新请求的通知；引擎关闭后处理完队列中剩余的请求再退出
+    async fn worker_loop(mut self, worker_id: usize) {
+        crate::utils::logger::debug!("Worker {} started", worker_id);
+
+        loop {
+            if let Some(task) = self.work_queue.pop(worker_id) {
+                self.process(task).await;
+                continue;
+            }
+            if *self.shutdown.borrow() {
+                break;
+            }
+            tokio::select! {
+                _ = self.work_available.notified() => {}
+                _ = self.shutdown.wait_for(|stop| *stop) => {}
+            }
+        }
+
+        crate::utils::logger::debug!("Worker {} stopped", worker_id);
+    }
+
+    /// 处理一个请求
+    ///
+    /// 客户端要求关闭、引擎正在关闭或处理器失败时，响应声明 `Connection: close` 后关闭连接；
+    /// 否则连接回到等待下一个请求的状态，流水线中已缓冲的请求会被立即读出
+    async fn process(&self, mut task: HttpTask) {
+        let Some(request) = task.take_pending_request() else {
+            self.finish(task);
+            return;
+        };
+
+        let keep_alive = !requests_close(&request) && !*self.shutdown.borrow();
+        let start_time = Instant::now();
+        let result = ActualRatEngine::respond_to_request(&mut task, request, &self.router, !keep_alive).await;
+        self.metrics.record_request_duration(start_time.elapsed());
+
+        match result {
+            Ok(true) => self.await_request(task),
+            Ok(false) => self.finish(task),
+            Err(e) => {
+                self.metrics.increment_errors();
+                crate::utils::logger::debug!("[引擎] 处理请求失败 {}: {}", task.remote_addr(), e);
+                self.finish(task);
+            }
+        }
+    }
+}
+
+/// 请求是否要求处理完后关闭连接
+///
+/// HTTP/1.1 默认保持连接，除非带有 `Connection: close`；
+/// HTTP/1.0 默认关闭，除非带有 `Connection: keep-alive`
+fn requests_close(request: &HttpRequest) -> bool {
+    let has_token = |expected: &str| request.headers.get("connection")
+        .is_some_and(|value| value.split(',').any(|token| token.trim().eq_ignore_ascii_case(expected)));
+    if request.version == hyper::Version::HTTP_10 {
+        !has_token("keep-alive")
+    } else {
+        has_token("close")
+    }
+}
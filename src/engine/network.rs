@@ -16,6 +16,9 @@ use std::time::Instant;
 use crate::engine::memory::MemoryPool;
 use crate::utils::ip_extractor::IpExtractor;
 
+/// 关闭带有剩余数据的连接时，等待客户端结束发送的最长时间
+const LINGER_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
 /// HTTP 任务
 /// 
 /// 封装了一个完整的 HTTP 请求处理任务，包括网络连接、
@@ -31,6 +34,10 @@ pub struct HttpTask {
     created_at: Instant,
     /// 内存池引用
     memory_pool: Arc<MemoryPool>,
+    /// 已读取、等待工作线程处理的请求
+    pending_request: Option<crate::engine::HttpRequest>,
+    /// 连接排空守卫，只需随连接一起持有和释放
+    _drain: Option<tokio::sync::watch::Receiver<()>>,
 }
 
 impl HttpTask {
@@ -44,10 +51,57 @@ impl HttpTask {
             buffer,
             created_at: Instant::now(),
             memory_pool,
+            pending_request: None,
+            _drain: None,
         }
     }
     
+    /// 让连接参与引擎关闭时的排空：任务（连接）结束前引擎会等待它
+    pub fn with_drain(mut self, drain: tokio::sync::watch::Receiver<()>) -> Self {
+        self._drain = Some(drain);
+        self
+    }
+    
+    /// 保存已读取的请求，交给工作线程处理
+    pub(crate) fn set_pending_request(&mut self, request: crate::engine::HttpRequest) {
+        self.pending_request = Some(request);
+    }
+    
+    /// 取出等待处理的请求
+    pub(crate) fn take_pending_request(&mut self) -> Option<crate::engine::HttpRequest> {
+        self.pending_request.take()
+    }
+    
+    /// 缓冲区中是否还有上一个请求之后未处理的数据
+    pub fn has_buffered_data(&self) -> bool {
+        self.buffer.has_buffered_data()
+    }
+    
+    /// 丢弃剩余数据并关闭连接
+    ///
+    /// 先发送 FIN；如果客户端在请求之后还发送了数据，再短暂读取并丢弃，
+    /// 避免直接关闭带未读数据的 socket 触发 RST，导致客户端丢失尚未读取的响应
+    pub async fn close(&mut self) {
+        let had_trailing_data = self.buffer.has_buffered_data();
+        self.buffer.reset();
+        if self.stream.shutdown().await.is_err() || !had_trailing_data {
+            return;
+        }
+        
+        let mut discard = [0u8; 4096];
+        let _ = tokio::time::timeout(LINGER_TIMEOUT, async {
+            while let Ok(n) = self.stream.read(&mut discard).await {
+                if n == 0 {
+                    break;
+                }
+            }
+        }).await;
+    }
+    
     /// 读取 HTTP 请求
+    ///
+    /// 每次只消费一个完整请求（请求头 + `Content-Length` 指定的请求体），
+    /// 之后的数据留在缓冲区中，作为同一连接上下一个请求的开头
     pub async fn read_request(&mut self) -> Result<crate::engine::HttpRequest, HttpError> {
         // 读取请求数据到缓冲区
         self.buffer.read_http_request(&mut self.stream).await?;
@@ -70,6 +124,33 @@ impl HttpTask {
         Ok(())
     }
     
+    /// 按指定的 TCP 写出策略发送 HTTP 响应（原始字节）
+    ///
+    /// 写出结束后恢复连接原来的 TCP_NODELAY 设置；设置 socket 选项失败时按默认方式发送
+    pub async fn send_response_with_mode(
+        &mut self,
+        response_bytes: Vec<u8>,
+        mode: Option<crate::server::tcp_write_mode::TcpWriteMode>,
+    ) -> Result<(), HttpError> {
+        let Some(mode) = mode else {
+            return self.send_response(response_bytes).await;
+        };
+
+        let previous_nodelay = match crate::server::tcp_write_mode::begin(&self.stream, mode) {
+            Ok(previous) => previous,
+            Err(e) => {
+                crate::utils::logger::debug!("设置 TCP 写出策略 {:?} 失败: {}", mode, e);
+                return self.send_response(response_bytes).await;
+            }
+        };
+
+        let result = self.send_response(response_bytes).await;
+        if let Err(e) = crate::server::tcp_write_mode::end(&self.stream, mode, previous_nodelay) {
+            crate::utils::logger::debug!("恢复 TCP 写出策略失败: {}", e);
+        }
+        result
+    }
+    
     /// 发送结构化 HTTP 响应
     pub async fn send_structured_response(&mut self, response: crate::engine::HttpResponse) -> Result<(), HttpError> {
         // 构建 HTTP 响应
@@ -165,8 +246,8 @@ impl ZeroCopyBuffer {
             self.buffer.reserve(4096);
         }
         
-        // 读取数据直到找到完整的 HTTP 请求
-        loop {
+        // 读取数据直到找到完整的 HTTP 请求（上一个请求之后的剩余数据可能已经足够）
+        while !self.has_complete_request() {
             let n = stream.read_buf(&mut self.buffer).await
                 .map_err(HttpError::IoError)?;
             
@@ -176,11 +257,6 @@ impl ZeroCopyBuffer {
             
             self.write_pos += n;
             
-            // 检查是否有完整的 HTTP 请求
-            if self.has_complete_request() {
-                break;
-            }
-            
             // 防止缓冲区过大
             if self.buffer.len() > 1024 * 1024 { // 1MB 限制
                 return Err(HttpError::RequestTooLarge);
@@ -193,20 +269,24 @@ impl ZeroCopyBuffer {
     /// 检查是否有完整的 HTTP 请求
     fn has_complete_request(&self) -> bool {
         let data = &self.buffer[self.read_pos..self.write_pos];
-        
+        self.complete_request_len(data).is_some()
+    }
+    
+    /// 第一个完整请求的长度（请求头 + 请求体），数据不完整时返回 None
+    fn complete_request_len(&self, data: &[u8]) -> Option<usize> {
         // 查找请求头结束标记
-        if let Some(headers_end) = self.find_headers_end(data) {
-            // 检查是否需要读取请求体
-            if let Some(content_length) = self.extract_content_length(data) {
-                let body_start = headers_end + 4; // "\r\n\r\n" 的长度
-                let available_body = data.len().saturating_sub(body_start);
-                available_body >= content_length
-            } else {
-                true // 没有请求体，只需要头部完整
-            }
-        } else {
-            false
-        }
+        let headers_end = self.find_headers_end(data)?;
+        let body_start = headers_end + 4; // "\r\n\r\n" 的长度
+        
+        // 只在请求头中查找 Content-Length，没有时视为无请求体
+        let content_length = self.extract_content_length(&data[..headers_end]).unwrap_or(0);
+        let request_len = body_start + content_length;
+        (data.len() >= request_len).then_some(request_len)
+    }
+    
+    /// 是否还有未处理的数据
+    pub fn has_buffered_data(&self) -> bool {
+        self.read_pos < self.write_pos
     }
     
     /// 查找请求头结束位置
@@ -236,26 +316,30 @@ impl ZeroCopyBuffer {
         // 查找请求头结束位置
         let headers_end = self.find_headers_end(data)
             .ok_or(HttpError::InvalidRequest("Headers not complete".to_string()))?;
+        let request_len = self.complete_request_len(data)
+            .ok_or(HttpError::InvalidRequest("Body not complete".to_string()))?;
         
         let headers_data = &data[..headers_end];
         let body_start = headers_end + 4;
         
         // 解析请求行
-        let (method, path, query_string) = self.parse_request_line(headers_data)?;
+        let (method, path, query_string, version) = self.parse_request_line(headers_data)?;
         
         // 解析请求头
         let headers = self.parse_headers(headers_data)?;
         
-        // 提取请求体
-        let body = if body_start < data.len() {
-            data[body_start..].to_vec()
-        } else {
-            Vec::new()
-        };
+        // 提取请求体（只取 Content-Length 指定的长度，之后的数据属于下一个请求）
+        let body = data[body_start..request_len].to_vec();
         
         // 提取真实 IP
         let real_ip = IpExtractor::extract_real_ip(&headers, &remote_addr.to_string());
         
+        // 消费当前请求，全部处理完时重置缓冲区
+        self.read_pos += request_len;
+        if self.read_pos == self.write_pos {
+            self.reset();
+        }
+        
         Ok(crate::engine::HttpRequest {
             method,
             path,
@@ -264,11 +348,14 @@ impl ZeroCopyBuffer {
             body,
             remote_addr: remote_addr.to_string(),
             real_ip,
+            version,
         })
     }
     
     /// 解析请求行
-    fn parse_request_line(&self, headers_data: &[u8]) -> Result<(String, String, String), HttpError> {
+    ///
+    /// 返回方法、路径、查询字符串和协议版本；只接受 HTTP/1.x
+    fn parse_request_line(&self, headers_data: &[u8]) -> Result<(String, String, String, hyper::Version), HttpError> {
         let headers_str = String::from_utf8_lossy(headers_data);
         let first_line = headers_str.lines().next()
             .ok_or_else(|| HttpError::InvalidRequest("No request line".to_string()))?;
@@ -280,6 +367,12 @@ impl ZeroCopyBuffer {
         
         let method = parts[0].to_string();
         let url = parts[1];
+        let version = match parts[2].trim() {
+            "HTTP/1.0" => hyper::Version::HTTP_10,
+            // 更高的 1.x 次版本按 HTTP/1.1 处理
+            v if v.starts_with("HTTP/1.") => hyper::Version::HTTP_11,
+            _ => return Err(HttpError::InvalidRequest("Unsupported HTTP version".to_string())),
+        };
         
         // 分离路径和查询字符串
         let (path, query_string) = if let Some(pos) = url.find('?') {
@@ -288,7 +381,7 @@ impl ZeroCopyBuffer {
             (url.to_string(), String::new())
         };
         
-        Ok((method, path, query_string))
+        Ok((method, path, query_string, version))
     }
     
     /// 解析请求头
@@ -375,11 +468,16 @@ mod tests {
         let buffer = ZeroCopyBuffer::new(1024);
         let headers_data = b"GET /api/test?param=value HTTP/1.1\r\n";
         
-        let (method, path, query) = buffer.parse_request_line(headers_data).expect("解析请求行失败");
+        let (method, path, query, version) = buffer.parse_request_line(headers_data).expect("解析请求行失败");
         
         assert_eq!(method, "GET");
         assert_eq!(path, "/api/test");
         assert_eq!(query, "param=value");
+        assert_eq!(version, hyper::Version::HTTP_11);
+
+        let (_, _, _, version) = buffer.parse_request_line(b"GET / HTTP/1.0\r\n").unwrap();
+        assert_eq!(version, hyper::Version::HTTP_10);
+        assert!(buffer.parse_request_line(b"GET / SPDY/3\r\n").is_err());
     }
     
     #[test]
@@ -393,6 +491,86 @@ mod tests {
         assert_eq!(headers.get("user-agent"), Some(&"test".to_string()));
     }
     
+    fn buffer_with(data: &[u8]) -> ZeroCopyBuffer {
+        let mut buffer = ZeroCopyBuffer::new(1024);
+        buffer.buffer.extend_from_slice(data);
+        buffer.write_pos = data.len();
+        buffer
+    }
+    
+    #[test]
+    fn test_trailing_data_is_not_part_of_body() {
+        let remote_addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+        let mut buffer = buffer_with(b"POST /a HTTP/1.1\r\nContent-Length: 3\r\n\r\nabcGET /b HTTP/1.1\r\nContent-Length: 99\r\n\r\n");
+        
+        let first = buffer.parse_http_request(remote_addr).unwrap();
+        assert_eq!(first.path, "/a");
+        assert_eq!(first.body, b"abc");
+        assert!(buffer.has_buffered_data());
+        
+        let second = buffer.parse_http_request(remote_addr).unwrap_err();
+        assert!(matches!(second, HttpError::InvalidRequest(_)));
+        
+        let mut buffer = buffer_with(b"GET /c HTTP/1.1\r\n\r\n");
+        assert_eq!(buffer.parse_http_request(remote_addr).unwrap().body, b"");
+        assert!(!buffer.has_buffered_data());
+    }
+    
+    #[tokio::test]
+    async fn test_trailing_data_after_request() {
+        use http_body_util::Full;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        
+        let mut router = crate::server::Router::new();
+        router.add_route(hyper::Method::GET, "/<name>", |req| {
+            let name = req.param("name").unwrap_or_default().to_string();
+            Box::pin(async move { Ok(hyper::Response::new(Full::new(Bytes::from(name)))) })
+        });
+        let engine = Arc::new(crate::RatEngine::builder()
+            .worker_threads(1)
+            .http_path(crate::engine::HttpPath::WorkStealing)
+            .router(router)
+            .build()
+            .unwrap());
+        let server = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.start("127.0.0.1".to_string(), 0).await }
+        });
+        let addr = loop {
+            if let Some(addr) = engine.local_addr() {
+                break addr;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        };
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        
+        // keep-alive 请求之后的数据是下一个请求；Connection: close 请求之后的数据被忽略
+        client.write_all(b"GET /first HTTP/1.1\r\nHost: x\r\n\r\nGET /second HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\nGET /ignored HTTP/1.1\r\n\r\n").await.unwrap();
+        
+        let mut output = String::new();
+        tokio::time::timeout(std::time::Duration::from_secs(5), client.read_to_string(&mut output)).await.unwrap().unwrap();
+        assert_eq!(output.matches("HTTP/1.1 200").count(), 2);
+        assert!(output.contains("first"));
+        assert!(output.contains("second"));
+        assert!(!output.contains("ignored"));
+        assert!(output.to_lowercase().contains("connection: close"));
+        
+        engine.shutdown().await.unwrap();
+        server.await.unwrap().unwrap();
+    }
+    
+    #[test]
+    fn test_internal_error_response_closes_connection() {
+        let response = String::from_utf8(crate::engine::ActualRatEngine::internal_error_bytes(hyper::Version::HTTP_11, "boom")).unwrap();
+        assert!(response.starts_with("HTTP/1.1 500"), "{}", response);
+        assert!(response.contains("Content-Length: 4\r\n"), "{}", response);
+        assert!(response.contains("Connection: close\r\n"), "{}", response);
+        assert!(response.ends_with("\r\n\r\nboom"), "{}", response);
+        
+        let http10 = String::from_utf8(crate::engine::ActualRatEngine::internal_error_bytes(hyper::Version::HTTP_10, "boom")).unwrap();
+        assert!(http10.starts_with("HTTP/1.0 500"), "{}", http10);
+    }
+    
     #[test]
     fn test_ip_extractor_integration() {
         let mut headers = HashMap::new();
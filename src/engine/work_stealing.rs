@@ -122,6 +122,19 @@ impl<T> WorkStealingQueue<T> {
         self.local_queues[idx].push(item);
     }
     
+    /// 在队列深度未超限时推送任务
+    ///
+    /// # 返回
+    /// 队列深度（估计值）已达到 `max_depth` 时不入队，原样返回任务，
+    /// 由调用方立即拒绝，避免请求在饱和队列中无限等待
+    pub fn try_push(&self, item: T, worker_id: Option<usize>, max_depth: usize) -> Result<(), T> {
+        if self.len_estimate() >= max_depth {
+            return Err(item);
+        }
+        self.push(item, worker_id);
+        Ok(())
+    }
+    
     /// 推送任务到全局队列
     /// 
     /// 用于需要全局负载均衡的场景
@@ -250,6 +263,18 @@ mod tests {
     use std::thread;
     use std::time::Duration;
     
+    #[test]
+    fn test_try_push_respects_max_depth() {
+        let queue = WorkStealingQueue::new(2);
+        
+        assert!(queue.try_push(1, None, 2).is_ok());
+        assert!(queue.try_push(2, None, 2).is_ok());
+        assert_eq!(queue.try_push(3, None, 2), Err(3));
+        
+        queue.pop(0);
+        assert!(queue.try_push(3, None, 2).is_ok());
+    }
+    
     #[test]
     fn test_basic_push_pop() {
         let queue = WorkStealingQueue::new(2);
@@ -153,6 +153,28 @@ impl fmt::Display for RatError {
     }
 }
 
+impl RatError {
+    /// 作为处理器错误返回给客户端时使用的 HTTP 状态码
+    ///
+    /// 调用方输入有误的错误映射为 4xx，其余映射为 5xx
+    pub fn status_code(&self) -> hyper::StatusCode {
+        use hyper::StatusCode;
+        match self {
+            RatError::RequestError(_)
+            | RatError::DecodingError(_)
+            | RatError::ParseError(_)
+            | RatError::ValidationError(_)
+            | RatError::DeserializationError(_)
+            | RatError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+            RatError::SecurityError(_) => StatusCode::FORBIDDEN,
+            RatError::RouteError(_) => StatusCode::NOT_FOUND,
+            RatError::TimeoutError(_) => StatusCode::GATEWAY_TIMEOUT,
+            RatError::NetworkError(_) => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
 impl std::error::Error for RatError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -222,6 +244,25 @@ impl From<flate2::DecompressError> for RatError {
 // 注意：zstd 0.12.4 版本使用 std::io::Error 而不是自定义的 Error 类型
 // 因此这里不需要重复实现 From<std::io::Error>
 
+// JSON 解析错误：通过 i18n 输出本地化信息，并附带 serde 给出的行列位置
+impl From<serde_json::Error> for RatError {
+    fn from(err: serde_json::Error) -> Self {
+        if err.line() == 0 {
+            return RatError::DeserializationError(rat_embed_lang::tf("json_parse_failed", &[("msg", &err.to_string())]));
+        }
+
+        // serde_json 的错误信息末尾固定附带英文位置，本地化时去掉后单独填充
+        let full = err.to_string();
+        let suffix = format!(" at line {} column {}", err.line(), err.column());
+        let msg = full.strip_suffix(&suffix).unwrap_or(&full);
+        RatError::DeserializationError(rat_embed_lang::tf("json_parse_failed_at", &[
+            ("msg", msg),
+            ("line", &err.line().to_string()),
+            ("column", &err.column().to_string()),
+        ]))
+    }
+}
+
 impl From<std::string::FromUtf8Error> for RatError {
     fn from(err: std::string::FromUtf8Error) -> Self {
         RatError::ValidationError(format!("Invalid UTF-8: {}", err))
@@ -319,6 +360,74 @@ impl std::error::Error for CacheError {
     }
 }
 
+/// 引擎构建错误
+///
+/// 由 `RatEngineBuilder::build` 和 `build_and_start` 返回，调用方可以按失败原因分别处理
+#[derive(Debug)]
+pub enum BuilderError {
+    /// 构建器已经使用过
+    AlreadyBuilt,
+    /// 未设置路由器
+    MissingRouter,
+    /// 证书配置校验失败
+    CertError(crate::server::cert_manager::CertError),
+    /// 证书管理器锁已损坏
+    CertManagerPoisoned,
+    /// ALPN 协议列表无效（为空、重复或包含服务器无法处理的协议）
+    InvalidAlpn(String),
+    /// 端口配置无效（分端口模式下端口冲突或缺少 gRPC 证书）
+    InvalidPortConfig(crate::server::port_config::PortConfigError),
+    /// 请求处理路径与其他配置冲突（工作窃取路径只支持明文 HTTP/1.1）
+    UnsupportedHttpPath(String),
+    /// 日志系统初始化失败
+    LoggerInitFailed(String),
+    /// 智能传输管理器初始化失败
+    TransferInitFailed(String),
+    /// 构建成功但启动服务器失败（仅 `build_and_start`）
+    StartFailed(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let localized = match self {
+            BuilderError::AlreadyBuilt => rat_embed_lang::t("builder_already_built"),
+            BuilderError::MissingRouter => rat_embed_lang::t("builder_missing_router"),
+            BuilderError::CertError(err) => rat_embed_lang::tf("builder_cert_error", &[("msg", &err.to_string())]),
+            BuilderError::CertManagerPoisoned => rat_embed_lang::t("builder_cert_manager_poisoned"),
+            BuilderError::InvalidAlpn(msg) => rat_embed_lang::tf("builder_invalid_alpn", &[("msg", msg)]),
+            BuilderError::InvalidPortConfig(err) => rat_embed_lang::tf("builder_invalid_port_config", &[("msg", &err.to_string())]),
+            BuilderError::UnsupportedHttpPath(msg) => rat_embed_lang::tf("builder_unsupported_http_path", &[("msg", msg)]),
+            BuilderError::LoggerInitFailed(msg) => rat_embed_lang::tf("builder_logger_init_failed", &[("msg", msg)]),
+            BuilderError::TransferInitFailed(msg) => rat_embed_lang::tf("builder_transfer_init_failed", &[("msg", msg)]),
+            BuilderError::StartFailed(err) => rat_embed_lang::tf("builder_start_failed", &[("msg", &err.to_string())]),
+        };
+        write!(f, "{}", localized)
+    }
+}
+
+impl std::error::Error for BuilderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuilderError::CertError(err) => Some(err),
+            BuilderError::InvalidPortConfig(err) => Some(err),
+            BuilderError::StartFailed(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<crate::server::cert_manager::CertError> for BuilderError {
+    fn from(err: crate::server::cert_manager::CertError) -> Self {
+        BuilderError::CertError(err)
+    }
+}
+
+impl From<BuilderError> for RatError {
+    fn from(err: BuilderError) -> Self {
+        RatError::ConfigError(err.to_string())
+    }
+}
+
 /// 错误处理工具函数
 pub mod utils {
     use super::*;
@@ -760,6 +760,13 @@ pub fn init_error_translations() {
     h2_send_data_failed.insert("ja-JP".to_string(), "H2データ送信失敗: {msg}".to_string());
     translations.insert("h2_send_data_failed".to_string(), h2_send_data_failed);
 
+    // grpc_call_cancelled - gRPC 调用已取消
+    let mut grpc_call_cancelled = HashMap::new();
+    grpc_call_cancelled.insert("zh-CN".to_string(), "gRPC 调用已取消".to_string());
+    grpc_call_cancelled.insert("en-US".to_string(), "gRPC call cancelled".to_string());
+    grpc_call_cancelled.insert("ja-JP".to_string(), "gRPC 呼び出しがキャンセルされました".to_string());
+    translations.insert("grpc_call_cancelled".to_string(), grpc_call_cancelled);
+
     // h2_send_empty_data_failed - H2 发送空数据失败
     let mut h2_send_empty_data_failed = HashMap::new();
     h2_send_empty_data_failed.insert("zh-CN".to_string(), "H2 发送空数据失败: {msg}".to_string());
@@ -865,6 +872,90 @@ pub fn init_error_translations() {
     json_parse_failed.insert("ja-JP".to_string(), "JSON解析失敗: {msg}".to_string());
     translations.insert("json_parse_failed".to_string(), json_parse_failed);
 
+    // json_parse_failed_at - JSON解析失败（带位置）
+    let mut json_parse_failed_at = HashMap::new();
+    json_parse_failed_at.insert("zh-CN".to_string(), "JSON解析失败（第 {line} 行第 {column} 列）: {msg}".to_string());
+    json_parse_failed_at.insert("en-US".to_string(), "JSON parse failed at line {line} column {column}: {msg}".to_string());
+    json_parse_failed_at.insert("ja-JP".to_string(), "JSON解析失敗（{line} 行 {column} 列）: {msg}".to_string());
+    translations.insert("json_parse_failed_at".to_string(), json_parse_failed_at);
+
+    // request_body_not_utf8 - 请求体不是有效的UTF-8
+    let mut request_body_not_utf8 = HashMap::new();
+    request_body_not_utf8.insert("zh-CN".to_string(), "请求体不是有效的UTF-8: {msg}".to_string());
+    request_body_not_utf8.insert("en-US".to_string(), "Request body is not valid UTF-8: {msg}".to_string());
+    request_body_not_utf8.insert("ja-JP".to_string(), "リクエストボディは有効なUTF-8ではありません: {msg}".to_string());
+    translations.insert("request_body_not_utf8".to_string(), request_body_not_utf8);
+
+    // builder_already_built - 构建器已经使用过
+    let mut builder_already_built = HashMap::new();
+    builder_already_built.insert("zh-CN".to_string(), "构建器已经使用过".to_string());
+    builder_already_built.insert("en-US".to_string(), "Builder has already been used".to_string());
+    builder_already_built.insert("ja-JP".to_string(), "ビルダーは既に使用されています".to_string());
+    translations.insert("builder_already_built".to_string(), builder_already_built);
+
+    // builder_missing_router - 未设置路由器
+    let mut builder_missing_router = HashMap::new();
+    builder_missing_router.insert("zh-CN".to_string(), "必须提供路由器，请使用 .router() 方法设置".to_string());
+    builder_missing_router.insert("en-US".to_string(), "Router must be provided. Use .router() method to set a router.".to_string());
+    builder_missing_router.insert("ja-JP".to_string(), "ルーターが必要です。.router() メソッドで設定してください".to_string());
+    translations.insert("builder_missing_router".to_string(), builder_missing_router);
+
+    // builder_cert_error - 证书配置无效
+    let mut builder_cert_error = HashMap::new();
+    builder_cert_error.insert("zh-CN".to_string(), "证书配置无效: {msg}".to_string());
+    builder_cert_error.insert("en-US".to_string(), "Invalid certificate configuration: {msg}".to_string());
+    builder_cert_error.insert("ja-JP".to_string(), "証明書設定が無効です: {msg}".to_string());
+    translations.insert("builder_cert_error".to_string(), builder_cert_error);
+
+    // builder_cert_manager_poisoned - 证书管理器锁已损坏
+    let mut builder_cert_manager_poisoned = HashMap::new();
+    builder_cert_manager_poisoned.insert("zh-CN".to_string(), "证书管理器锁已损坏".to_string());
+    builder_cert_manager_poisoned.insert("en-US".to_string(), "Certificate manager lock is poisoned".to_string());
+    builder_cert_manager_poisoned.insert("ja-JP".to_string(), "証明書マネージャーのロックが破損しています".to_string());
+    translations.insert("builder_cert_manager_poisoned".to_string(), builder_cert_manager_poisoned);
+
+    // builder_logger_init_failed - 日志系统初始化失败
+    let mut builder_logger_init_failed = HashMap::new();
+    builder_logger_init_failed.insert("zh-CN".to_string(), "日志系统初始化失败: {msg}".to_string());
+    builder_logger_init_failed.insert("en-US".to_string(), "Logger initialization failed: {msg}".to_string());
+    builder_logger_init_failed.insert("ja-JP".to_string(), "ログシステムの初期化に失敗しました: {msg}".to_string());
+    translations.insert("builder_logger_init_failed".to_string(), builder_logger_init_failed);
+
+    // builder_invalid_alpn - ALPN 协议列表无效
+    let mut builder_invalid_alpn = HashMap::new();
+    builder_invalid_alpn.insert("zh-CN".to_string(), "ALPN 协议列表无效: {msg}".to_string());
+    builder_invalid_alpn.insert("en-US".to_string(), "Invalid ALPN protocol list: {msg}".to_string());
+    builder_invalid_alpn.insert("ja-JP".to_string(), "ALPN プロトコルリストが無効です: {msg}".to_string());
+    translations.insert("builder_invalid_alpn".to_string(), builder_invalid_alpn);
+
+    // builder_invalid_port_config - 端口配置无效
+    let mut builder_invalid_port_config = HashMap::new();
+    builder_invalid_port_config.insert("zh-CN".to_string(), "端口配置无效: {msg}".to_string());
+    builder_invalid_port_config.insert("en-US".to_string(), "Invalid port configuration: {msg}".to_string());
+    builder_invalid_port_config.insert("ja-JP".to_string(), "ポート設定が無効です: {msg}".to_string());
+    translations.insert("builder_invalid_port_config".to_string(), builder_invalid_port_config);
+
+    // builder_unsupported_http_path - 请求处理路径与配置冲突
+    let mut builder_unsupported_http_path = HashMap::new();
+    builder_unsupported_http_path.insert("zh-CN".to_string(), "请求处理路径配置冲突: {msg}".to_string());
+    builder_unsupported_http_path.insert("en-US".to_string(), "Unsupported request handling path: {msg}".to_string());
+    builder_unsupported_http_path.insert("ja-JP".to_string(), "リクエスト処理パスの設定が競合しています: {msg}".to_string());
+    translations.insert("builder_unsupported_http_path".to_string(), builder_unsupported_http_path);
+
+    // builder_transfer_init_failed - 智能传输管理器初始化失败
+    let mut builder_transfer_init_failed = HashMap::new();
+    builder_transfer_init_failed.insert("zh-CN".to_string(), "智能传输管理器初始化失败: {msg}".to_string());
+    builder_transfer_init_failed.insert("en-US".to_string(), "Smart transfer manager initialization failed: {msg}".to_string());
+    builder_transfer_init_failed.insert("ja-JP".to_string(), "スマート転送マネージャーの初期化に失敗しました: {msg}".to_string());
+    translations.insert("builder_transfer_init_failed".to_string(), builder_transfer_init_failed);
+
+    // builder_start_failed - 启动服务器失败
+    let mut builder_start_failed = HashMap::new();
+    builder_start_failed.insert("zh-CN".to_string(), "启动服务器失败: {msg}".to_string());
+    builder_start_failed.insert("en-US".to_string(), "Failed to start server: {msg}".to_string());
+    builder_start_failed.insert("ja-JP".to_string(), "サーバーの起動に失敗しました: {msg}".to_string());
+    translations.insert("builder_start_failed".to_string(), builder_start_failed);
+
     register_translations(translations);
 
     // 设置语言 - 优先使用系统语言，fallback到中文
@@ -26,6 +26,7 @@ pub mod cache;
 
 // 公共模块
 pub mod common;
+pub mod response;
 
 // 在库加载时确保 CryptoProvider 只安装一次
 lazy_static! {
@@ -41,7 +42,7 @@ lazy_static! {
 pub mod python_api;
 
 // 导出核心类型
-pub use server::{ServerConfig, Router, WorkerPool};
+pub use server::{ServerConfig, Router, WorkerPool, IntoResponse, Json, CacheControl};
 pub use engine::RatEngine;
 
 // 重新导出 hyper 常用类型，让用户无需直接引入 hyper
@@ -53,11 +54,11 @@ pub use http_body_util::{Full, Empty, BodyExt};
 // 导出客户端相关类型
 #[cfg(feature = "client")]
 pub use client::{
-    // RatHttpClient, RatHttpClientBuilder,  // 已移除HTTP客户端，只保留gRPC客户端
+    RatHttpClient, RatHttpClientBuilder,
     RatGrpcClient, RatGrpcClientBuilder,
     GrpcRequest, GrpcResponse, GrpcCompressionMode,
     GrpcStreamResponse,
-    // RatHttpResponse, HttpMethod, HttpStatusCode, HttpHeaders, HttpRequestBuilder,  // 已移除HTTP客户端，只保留gRPC客户端
+    RatHttpResponse, HttpMethod, HttpStatusCode, HttpHeaders, HttpRequestBuilder,
     download_metadata::{DownloadMetadataManager, DownloadMetadata, ChunkInfo, DownloadStatus},
     connection_pool::ClientConnectionPool,
 };
@@ -73,7 +74,7 @@ pub use server::grpc_types::GrpcStreamMessage;
 pub use utils::sys_info::SystemInfo;
 pub use utils::logger::{Logger, LogLevel, LogConfig};
 pub use utils::logger::{error, warn, info, debug, trace};
-pub use error::{RatError, RatResult, CacheError};
+pub use error::{RatError, RatResult, CacheError, BuilderError};
 
 // 导出性能优化函数
 pub use server::performance::optimize_for_throughput;
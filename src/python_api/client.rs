@@ -662,7 +662,7 @@ impl ClientManager {
         let http_delegated_handlers = Arc::new(RwLock::new(HashMap::new()));
         let grpc_unary_handlers = Arc::new(RwLock::new(HashMap::new()));
 
-        rat_logger::info!("🚀 [CLIENT_MANAGER] 启动工作线程...");
+        crate::utils::logger::info!("🚀 [CLIENT_MANAGER] 启动工作线程...");
         // 启动工作线程
         let worker_handle = Self::start_worker(
             grpc_client.clone(),
@@ -700,13 +700,13 @@ impl ClientManager {
         grpc_unary_handlers: Arc<RwLock<HashMap<String, PythonGrpcUnaryHandler>>>,
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
-            rat_logger::info!("🚀 [WORKER_THREAD] PyO3客户端工作线程启动");
-            rat_logger::info!("📋 [WORKER_THREAD] gRPC客户端: {}", if grpc_client.is_some() { "✅ 已启用" } else { "❌ 已禁用" });
-            rat_logger::info!("📋 [WORKER_THREAD] HTTP客户端: {}", if http_client.is_some() { "✅ 已启用" } else { "❌ 已禁用" });
+            crate::utils::logger::info!("🚀 [WORKER_THREAD] PyO3客户端工作线程启动");
+            crate::utils::logger::info!("📋 [WORKER_THREAD] gRPC客户端: {}", if grpc_client.is_some() { "✅ 已启用" } else { "❌ 已禁用" });
+            crate::utils::logger::info!("📋 [WORKER_THREAD] HTTP客户端: {}", if http_client.is_some() { "✅ 已启用" } else { "❌ 已禁用" });
             
             while !shutdown_signal.load(std::sync::atomic::Ordering::Relaxed) {
                 if let Some(request) = request_queue.pop() {
-                    rat_logger::debug!("🔄 [WORKER_THREAD] 收到请求类型: {:?}", std::mem::discriminant(&request));
+                    crate::utils::logger::debug!("🔄 [WORKER_THREAD] 收到请求类型: {:?}", std::mem::discriminant(&request));
                     Self::handle_request(
                         request,
                         &grpc_client,
@@ -736,7 +736,7 @@ impl ClientManager {
         http_delegated_handlers: &Arc<RwLock<HashMap<String, PythonHttpDelegatedHandler>>>,
         grpc_unary_handlers: &Arc<RwLock<HashMap<String, PythonGrpcUnaryHandler>>>,
     ) {
-        rat_logger::debug!("🔧 [HANDLE_REQUEST] 开始处理请求类型: {:?}", std::mem::discriminant(&request));
+        crate::utils::logger::debug!("🔧 [HANDLE_REQUEST] 开始处理请求类型: {:?}", std::mem::discriminant(&request));
         let start_time = std::time::Instant::now();
         
         match request {
@@ -765,12 +765,12 @@ impl ClientManager {
                 let _ = response_tx.send(result);
             },
             ClientRequest::HttpGet { url, headers, response_tx } => {
-                rat_logger::info!("🌐 [HANDLE_REQUEST] 处理HTTP GET请求: {}", url);
+                crate::utils::logger::info!("🌐 [HANDLE_REQUEST] 处理HTTP GET请求: {}", url);
                 let result = Self::handle_http_get_request(
                     http_client, &url, headers
                 ).await;
                 let elapsed = start_time.elapsed();
-                rat_logger::info!("⏱️ [HANDLE_REQUEST] HTTP GET请求处理完成，耗时: {:?}", elapsed);
+                crate::utils::logger::info!("⏱️ [HANDLE_REQUEST] HTTP GET请求处理完成，耗时: {:?}", elapsed);
                 let _ = response_tx.send(result);
             },
             ClientRequest::HttpPost { url, body, headers, response_tx } => {
@@ -834,12 +834,12 @@ impl ClientManager {
                 let _ = response_tx.send(result);
             },
             ClientRequest::Shutdown => {
-                rat_logger::info!("📥 [HANDLE_REQUEST] 收到关闭指令");
+                crate::utils::logger::info!("📥 [HANDLE_REQUEST] 收到关闭指令");
             },
         }
         
         let total_elapsed = start_time.elapsed();
-        rat_logger::info!("✅ [HANDLE_REQUEST] 请求处理完成，总耗时: {:?}", total_elapsed);
+        crate::utils::logger::info!("✅ [HANDLE_REQUEST] 请求处理完成，总耗时: {:?}", total_elapsed);
     }
 
     /// 处理 gRPC 一元请求
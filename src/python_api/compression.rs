@@ -61,6 +61,9 @@ impl PyCompressionConfig {
             excluded_content_types: excluded_content_types.unwrap_or_default().into_iter().collect(),
             excluded_extensions: excluded_extensions.unwrap_or_default().into_iter().collect(),
             enable_smart_compression,
+            adaptive_threshold: None,
+            brotli_static: None,
+            brotli_dynamic: None,
         };
 
         Self { config }
@@ -65,6 +65,41 @@ impl PyRatEngineBuilder {
         Ok(())
     }
     
+    /// 设置单个请求的处理期限（秒）
+    fn request_timeout(&mut self, timeout_secs: u64) -> PyResult<()> {
+        let builder = std::mem::replace(&mut self.builder, RatEngine::builder());
+        self.builder = builder.request_timeout(std::time::Duration::from_secs(timeout_secs));
+        Ok(())
+    }
+    
+    /// 设置 keep-alive 连接空闲超时（秒）
+    fn connection_idle_timeout(&mut self, timeout_secs: u64) -> PyResult<()> {
+        let builder = std::mem::replace(&mut self.builder, RatEngine::builder());
+        self.builder = builder.connection_idle_timeout(std::time::Duration::from_secs(timeout_secs));
+        Ok(())
+    }
+    
+    /// 设置 TLS 握手超时（秒）
+    fn tls_handshake_timeout(&mut self, timeout_secs: u64) -> PyResult<()> {
+        let builder = std::mem::replace(&mut self.builder, RatEngine::builder());
+        self.builder = builder.tls_handshake_timeout(std::time::Duration::from_secs(timeout_secs));
+        Ok(())
+    }
+    
+    /// 设置优雅关闭时等待进行中连接结束的最长时间（秒）
+    fn shutdown_timeout(&mut self, timeout_secs: u64) -> PyResult<()> {
+        let builder = std::mem::replace(&mut self.builder, RatEngine::builder());
+        self.builder = builder.shutdown_timeout(std::time::Duration::from_secs(timeout_secs));
+        Ok(())
+    }
+    
+    /// 启用/禁用启动预热
+    fn prewarm(&mut self, enabled: bool) -> PyResult<()> {
+        let builder = std::mem::replace(&mut self.builder, RatEngine::builder());
+        self.builder = builder.prewarm(enabled);
+        Ok(())
+    }
+    
     /// 启用 Keep-Alive
     fn keepalive(&mut self, enabled: bool) -> PyResult<()> {
         let builder = std::mem::replace(&mut self.builder, RatEngine::builder());
@@ -206,10 +241,7 @@ impl PyRatEngineBuilder {
         });
         
         match result {
-            Ok(engine) => Ok(PyRatEngine {
-                engine: Arc::new(engine),
-                runtime,
-            }),
+            Ok(engine) => Ok(PyRatEngine::new(engine, runtime)),
             Err(e) => Err(pyo3::exceptions::PyRuntimeError::new_err(format!("启动服务器失败: {}", e)))
         }
     }
@@ -218,36 +250,49 @@ impl PyRatEngineBuilder {
     fn build(&mut self) -> PyResult<PyRatEngine> {
         let builder = std::mem::replace(&mut self.builder, RatEngine::builder());
         match builder.build() {
-            Ok(engine) => Ok(PyRatEngine {
-                engine: Arc::new(engine),
-                runtime: self.runtime.clone(),
-            }),
+            Ok(engine) => Ok(PyRatEngine::new(engine, self.runtime.clone())),
             Err(e) => Err(pyo3::exceptions::PyRuntimeError::new_err(format!("构建引擎失败: {}", e)))
         }
     }
 }
 
+/// 后台运行的服务器任务
+type ServerTask = tokio::task::JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>;
+
 /// Python RAT Engine 实例
 #[pyclass(name = "RatEngine")]
 pub struct PyRatEngine {
     engine: Arc<ActualRatEngine>,
     runtime: Arc<Runtime>,
+    server_task: std::sync::Mutex<Option<ServerTask>>,
+}
+
+impl PyRatEngine {
+    fn new(engine: ActualRatEngine, runtime: Arc<Runtime>) -> Self {
+        Self {
+            engine: Arc::new(engine),
+            runtime,
+            server_task: std::sync::Mutex::new(None),
+        }
+    }
 }
 
 #[pymethods]
 impl PyRatEngine {
-    /// 启动服务器
+    /// 启动服务器（阻塞直到服务器停止）
+    /// 
+    /// 运行期间释放 GIL，其他 Python 线程可以调用 `stop()` 结束服务器
     /// 
     /// # 参数
     /// - host: 监听主机地址
     /// - port: 监听端口
-    fn start(&self, host: String, port: u16) -> PyResult<()> {
+    fn start(&self, py: Python, host: String, port: u16) -> PyResult<()> {
         let engine = self.engine.clone();
         let runtime = self.runtime.clone();
         
-        let result = runtime.block_on(async move {
+        let result = py.allow_threads(|| runtime.block_on(async move {
             engine.start(host, port).await
-        });
+        }));
         
         match result {
             Ok(_) => Ok(()),
@@ -255,6 +300,90 @@ impl PyRatEngine {
         }
     }
     
+    /// 在后台启动服务器，监听端口绑定后立即返回
+    /// 
+    /// 适用于测试和需要自行管理生命周期的场景，配合 `stop()` 或 `with` 语句使用
+    /// 
+    /// # 参数
+    /// - host: 监听主机地址
+    /// - port: 监听端口（0 表示由系统分配，可通过 `port` 属性获取实际端口）
+    fn start_background(&self, py: Python, host: String, port: u16) -> PyResult<()> {
+        let mut server_task = self.server_task.lock()
+            .map_err(|_| pyo3::exceptions::PyRuntimeError::new_err("服务器任务状态异常"))?;
+        if server_task.is_some() {
+            return Err(pyo3::exceptions::PyRuntimeError::new_err("服务器已在后台运行"));
+        }
+        
+        let engine = self.engine.clone();
+        let handle = self.runtime.spawn(async move {
+            engine.start(host, port).await
+        });
+        
+        // 等待端口绑定；启动失败时返回错误
+        let engine = self.engine.clone();
+        let runtime = self.runtime.clone();
+        let started = py.allow_threads(|| runtime.block_on(async {
+            loop {
+                if engine.local_addr().is_some() {
+                    return Ok(Some(handle));
+                }
+                if handle.is_finished() {
+                    return match handle.await {
+                        Ok(Ok(())) => Ok(None),
+                        Ok(Err(e)) => Err(format!("启动服务器失败: {}", e)),
+                        Err(e) => Err(format!("服务器任务异常退出: {}", e)),
+                    };
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        }));
+        
+        *server_task = started.map_err(pyo3::exceptions::PyRuntimeError::new_err)?;
+        Ok(())
+    }
+    
+    /// 优雅停止服务器
+    /// 
+    /// 停止接受新连接，等待进行中的连接结束后返回；
+    /// 服务器由 `start_background()` 启动时会等待后台任务退出
+    fn stop(&self, py: Python) -> PyResult<()> {
+        let server_task = self.server_task.lock()
+            .map_err(|_| pyo3::exceptions::PyRuntimeError::new_err("服务器任务状态异常"))?
+            .take();
+        let engine = self.engine.clone();
+        let runtime = self.runtime.clone();
+        
+        let result = py.allow_threads(|| runtime.block_on(async move {
+            engine.shutdown().await.map_err(|e| e.to_string())?;
+            if let Some(handle) = server_task {
+                match handle.await {
+                    Ok(result) => result.map_err(|e| e.to_string())?,
+                    Err(e) => return Err(format!("服务器任务异常退出: {}", e)),
+                }
+            }
+            Ok(())
+        }));
+        
+        result.map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("停止服务器失败: {}", e)))
+    }
+    
+    /// 上下文管理器入口
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    /// 上下文管理器出口，退出 `with` 块时优雅停止服务器
+    fn __exit__(
+        &self,
+        py: Python,
+        _exc_type: Option<&PyAny>,
+        _exc_value: Option<&PyAny>,
+        _traceback: Option<&PyAny>,
+    ) -> PyResult<bool> {
+        self.stop(py)?;
+        Ok(false)
+    }
+    
     /// 获取工作线程数
     #[getter]
     fn get_workers(&self) -> PyResult<usize> {
@@ -171,6 +171,18 @@ pub struct HttpRequest {
     /// Python处理器名字 (仅用于Python集成，避免Python层二次路由匹配)
     #[pyo3(get)]
     pub python_handler_name: Option<String>,
+
+    /// 是否通过 TLS 连接接收
+    #[pyo3(get)]
+    pub is_tls: bool,
+
+    /// TLS 协商的 ALPN 协议 (明文连接为 None)
+    #[pyo3(get)]
+    pub alpn: Option<String>,
+
+    /// 客户端证书主题 (仅 mTLS 连接)
+    #[pyo3(get)]
+    pub client_cert_subject: Option<String>,
 }
 
 #[pymethods]
@@ -186,7 +198,10 @@ impl HttpRequest {
         remote_addr: Option<String>,
         real_ip: Option<String>,
         path_params: Option<HashMap<String, String>>,
-        python_handler_name: Option<String>
+        python_handler_name: Option<String>,
+        is_tls: Option<bool>,
+        alpn: Option<String>,
+        client_cert_subject: Option<String>
     ) -> Self {
         Self {
             method: method.unwrap_or_else(|| "GET".to_string()),
@@ -198,8 +213,17 @@ impl HttpRequest {
             real_ip: real_ip.unwrap_or_else(|| "127.0.0.1".to_string()),
             path_params: path_params.unwrap_or_default(),
             python_handler_name,
+            is_tls: is_tls.unwrap_or(false),
+            alpn,
+            client_cert_subject,
         }
     }
+
+    /// 客户端 IP (考虑代理头，与 Rust 端 `client_ip()` 一致)
+    #[getter]
+    pub fn remote_ip(&self) -> String {
+        self.real_ip.clone()
+    }
     
     /// 获取查询参数字典
     /// 
@@ -329,6 +353,10 @@ impl HttpRequest {
     
     /// 检查是否为安全连接 (HTTPS)
     pub fn is_secure(&self) -> bool {
+        if self.is_tls {
+            return true;
+        }
+
         // 检查各种 HTTPS 指示头
         self.headers.get("x-forwarded-proto")
             .map(|v| v.to_lowercase() == "https")
@@ -38,6 +38,9 @@ pub async fn convert_hyper_to_http_request(req: Request<Incoming>) -> Result<Htt
         real_ip: "127.0.0.1".to_string(),
         path_params: std::collections::HashMap::new(),
         python_handler_name: None,
+        is_tls: false,
+        alpn: None,
+        client_cert_subject: None,
     })
 }
 
@@ -424,7 +424,10 @@ impl PyHttpHandler {
                             crate::utils::logger::debug!("🐍 [Rust DEBUG] path_params非空，克隆现有数据，路径: {}, 参数数量: {}", req_clone.uri.path(), req_clone.path_params.len());
                             req_clone.path_params.clone()
                         }),
-                            req_clone.python_handler_name.clone()
+                            req_clone.python_handler_name.clone(),
+                            Some(req_clone.is_tls()),
+                            req_clone.tls_info.as_ref().and_then(|tls| tls.alpn.clone()),
+                            req_clone.tls_info.as_ref().and_then(|tls| tls.client_cert_subject.clone())
                         );
 
                     // 🔍 调试py_request中的path_params
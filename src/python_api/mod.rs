@@ -7,7 +7,7 @@ use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use std::collections::HashMap;
 use crate::utils::logger::{LogConfig, LogLevel, LogOutput, Logger};
-use rat_logger::{debug, info, warn, error, trace, emergency, startup_log, flush_logs};
+use crate::utils::logger::{debug, info, warn, error, trace, emergency, startup_log, flush_logs};
 use std::path::PathBuf;
 
 // 子模块
@@ -200,6 +200,9 @@ impl PyRouter {
             excluded_content_types: excluded_content_types.unwrap_or_default().into_iter().collect(),
             excluded_extensions: excluded_extensions.unwrap_or_default().into_iter().collect(),
             enable_smart_compression: false,
+            adaptive_threshold: None,
+            brotli_static: None,
+            brotli_dynamic: None,
         };
           
         // 启用压缩
@@ -777,10 +780,14 @@ impl PyRouter {
             Box::pin(async move {
                 // 调用 Python 处理器，传递主库提供的路径参数
                 match PyRouter::execute_python_chunked_handler(value, req, handler, codec, path_params) {
-                    Ok(chunked_response) => {
+                    Ok(PyChunkedBody::Buffered(chunked_response)) => {
                         // 使用 ChunkedResponse 的 build 方法构建响应
                         chunked_response.build()
                     }
+                    Ok(PyChunkedBody::Iterator { iterator, content_type }) => {
+                        // 生成器由 Rust 端逐块驱动
+                        stream_python_iterator(iterator, content_type).await
+                    }
                     Err(_) => {
                         let error_response = ChunkedResponse::new().add_chunk("Internal Server Error".to_string());
                         error_response.build()
@@ -1282,8 +1289,8 @@ impl PyRouter {
         handler: PyObject,
         codec: PyQuickCodec,
         path_params: HashMap<String, String>
-    ) -> Result<ChunkedResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let result = Python::with_gil(|py| -> Result<ChunkedResponse, pyo3::PyErr> {
+    ) -> Result<PyChunkedBody, Box<dyn std::error::Error + Send + Sync>> {
+        let result = Python::with_gil(|py| -> Result<PyChunkedBody, pyo3::PyErr> {
             // 准备请求数据
             let request_data = prepare_request_data_from_http_request(py, &req, &codec, Some(&path_params))?;
             
@@ -1299,8 +1306,18 @@ impl PyRouter {
             let args = pyo3::types::PyTuple::new(py, &args_vec);
             let result = handler.call(py, args, None)?;
             
+            // 生成器/迭代器不在此处展开，交给 Rust 端按需拉取
+            if result.as_ref(py).hasattr("__next__")? {
+                // `@app.chunk(content_type=...)` 显式指定的响应类型
+                let content_type = handler.getattr(py, "_chunk_content_type")
+                    .ok()
+                    .and_then(|value| value.extract::<Option<String>>(py).ok())
+                    .flatten();
+                return Ok(PyChunkedBody::Iterator { iterator: result, content_type });
+            }
+
             // 处理 Python 函数返回的响应
-            handle_python_chunked_response(py, result, &codec)
+            handle_python_chunked_response(py, result, &codec).map(PyChunkedBody::Buffered)
         });
         
         result.map_err(|e| {
@@ -1772,6 +1789,116 @@ fn handle_python_sse_response(
     Ok(())
 }
 
+/// Python 分块处理器的返回结果
+enum PyChunkedBody {
+    /// 已完整生成的数据块（字符串或列表）
+    Buffered(ChunkedResponse),
+    /// 逐块产出数据的 Python 生成器/迭代器，以及显式指定的 Content-Type
+    Iterator {
+        iterator: PyObject,
+        content_type: Option<String>,
+    },
+}
+
+/// Python 生成器驱动的响应体通道容量（块数）
+const PY_ITERATOR_CHANNEL_CAPACITY: usize = 4;
+
+/// 由 Rust 端驱动 Python 生成器，逐块发送响应体
+///
+/// 在阻塞线程中逐次调用 `__next__`，每取一块后即释放 GIL；通道容量有限，
+/// 客户端消费变慢时发送会阻塞，生成器随之暂停，数据不会在 Python 侧堆积。
+/// 生成器结束、抛出异常或客户端断开后调用其 `close()`，执行 finally 中的清理逻辑。
+///
+/// 未显式指定 `content_type` 时按第一块的类型决定：`str` 为 `text/plain; charset=utf-8`，
+/// `bytes`/`bytearray` 为 `application/octet-stream`，因此响应头在第一块产出后才发送
+async fn stream_python_iterator(iterator: PyObject, content_type: Option<String>) -> Result<Response<StreamingBody>, hyper::Error> {
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<hyper::body::Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>(
+        PY_ITERATOR_CHANNEL_CAPACITY,
+    );
+    let (first_kind_tx, first_kind_rx) = tokio::sync::oneshot::channel::<&'static str>();
+
+    tokio::task::spawn_blocking(move || {
+        let mut first_kind_tx = Some(first_kind_tx);
+        loop {
+            let next = Python::with_gil(|py| -> PyResult<Option<(Bytes, bool)>> {
+                match iterator.call_method0(py, "__next__") {
+                    Ok(item) => python_chunk_to_bytes(item.as_ref(py)).map(Some),
+                    Err(e) if e.is_instance_of::<pyo3::exceptions::PyStopIteration>(py) => Ok(None),
+                    Err(e) => Err(e),
+                }
+            });
+
+            if let Some(first_kind_tx) = first_kind_tx.take() {
+                let is_text = !matches!(next, Ok(Some((_, false))));
+                let _ = first_kind_tx.send(chunk_content_type(is_text));
+            }
+
+            let frame = match next {
+                Ok(Some((chunk, _))) => Ok(hyper::body::Frame::data(chunk)),
+                Ok(None) => break,
+                Err(e) => {
+                    error!("❌ [Python流式] 生成器产出数据失败: {}", e);
+                    Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+                }
+            };
+
+            let failed = frame.is_err();
+            if tx.blocking_send(frame).is_err() {
+                debug!("🔌 [Python流式] 客户端已断开，停止驱动生成器");
+                break;
+            }
+            if failed {
+                break;
+            }
+        }
+
+        Python::with_gil(|py| {
+            if let Err(e) = iterator.call_method0(py, "close") {
+                if !e.is_instance_of::<pyo3::exceptions::PyAttributeError>(py) {
+                    warn!("⚠️ [Python流式] 关闭生成器失败: {}", e);
+                }
+            }
+        });
+    });
+
+    let content_type = match content_type {
+        Some(content_type) => content_type,
+        // 生成器没有产出任何数据就结束时按文本处理
+        None => first_kind_rx.await.unwrap_or_else(|_| chunk_content_type(true)).to_string(),
+    };
+
+    StreamingResponse::new()
+        .status(StatusCode::OK)
+        .with_header("Content-Type", &content_type)
+        .stream(tokio_stream::wrappers::ReceiverStream::new(rx))
+        .build()
+}
+
+/// 根据生成器产出的数据块类型推断 Content-Type
+fn chunk_content_type(is_text: bool) -> &'static str {
+    if is_text {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// 将生成器产出的单个数据块转换为字节（支持 bytes、bytearray 和 str），并返回是否为文本
+fn python_chunk_to_bytes(item: &PyAny) -> PyResult<(Bytes, bool)> {
+    if let Ok(bytes) = item.downcast::<PyBytes>() {
+        Ok((Bytes::copy_from_slice(bytes.as_bytes()), false))
+    } else if let Ok(text) = item.extract::<String>() {
+        Ok((Bytes::from(text), true))
+    } else if let Ok(data) = item.extract::<Vec<u8>>() {
+        Ok((Bytes::from(data), false))
+    } else {
+        Err(pyo3::exceptions::PyTypeError::new_err(format!(
+            "生成器只能产出 bytes 或 str，实际类型: {}",
+            item.get_type().name()?
+        )))
+    }
+}
+
 /// 处理 Python 函数返回的分块响应
 fn handle_python_chunked_response(
     py: Python,
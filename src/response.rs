@@ -0,0 +1,197 @@
+//! 响应构建辅助函数
+//!
+//! 提供重定向、JSON、文本等常用响应的便捷构建方法，保证状态码与响应头的正确搭配。
+//! 所有可能因用户数据失败的构建步骤都返回 `RatResult`，避免在处理器中使用 `.unwrap()`
+
+use hyper::{Response, StatusCode};
+use hyper::header::{HeaderName, HeaderValue, CONTENT_TYPE, LOCATION};
+use serde::Serialize;
+use hyper::body::Bytes;
+use http_body_util::Full;
+
+use crate::error::{RatError, RatResult};
+use crate::server::tcp_write_mode::TcpWriteMode;
+
+/// 构建重定向响应
+///
+/// 状态码必须为 3xx，`location` 会作为 `Location` 头部返回
+///
+/// # 示例
+///
+/// ```rust
+/// use rat_engine::StatusCode;
+/// use rat_engine::response::redirect;
+///
+/// let resp = redirect(StatusCode::FOUND, "/login").unwrap();
+/// assert_eq!(resp.status(), StatusCode::FOUND);
+/// ```
+pub fn redirect(status: StatusCode, location: &str) -> RatResult<Response<Full<Bytes>>> {
+    if !status.is_redirection() {
+        return Err(RatError::InvalidArgument(format!("重定向状态码必须为 3xx，实际为 {}", status)));
+    }
+
+    let location = HeaderValue::from_str(location)
+        .map_err(|e| RatError::InvalidArgument(format!("无效的重定向地址 {:?}: {}", location, e)))?;
+
+    let mut response = Response::new(Full::new(Bytes::new()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(LOCATION, location);
+    Ok(response)
+}
+
+/// 永久重定向（308 Permanent Redirect，保留请求方法和请求体）
+pub fn redirect_permanent(location: &str) -> RatResult<Response<Full<Bytes>>> {
+    redirect(StatusCode::PERMANENT_REDIRECT, location)
+}
+
+/// 临时重定向（307 Temporary Redirect，保留请求方法和请求体）
+pub fn redirect_temporary(location: &str) -> RatResult<Response<Full<Bytes>>> {
+    redirect(StatusCode::TEMPORARY_REDIRECT, location)
+}
+
+/// 303 See Other，客户端使用 GET 请求新地址（常用于表单提交后的跳转）
+pub fn see_other(location: &str) -> RatResult<Response<Full<Bytes>>> {
+    redirect(StatusCode::SEE_OTHER, location)
+}
+
+/// 构建 JSON 响应
+///
+/// 序列化失败时返回 `RatError::SerializationError`，不会 panic
+///
+/// # 示例
+///
+/// ```rust
+/// use rat_engine::StatusCode;
+/// use rat_engine::response::json;
+///
+/// let resp = json(StatusCode::OK, &serde_json::json!({"ok": true})).unwrap();
+/// assert_eq!(resp.headers()["content-type"], "application/json");
+/// ```
+pub fn json<T: Serialize + ?Sized>(status: StatusCode, value: &T) -> RatResult<Response<Full<Bytes>>> {
+    let body = serde_json::to_vec(value)
+        .map_err(|e| RatError::SerializationError(format!("JSON 序列化失败: {}", e)))?;
+
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = status;
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    Ok(response)
+}
+
+/// 构建纯文本响应（`text/plain; charset=utf-8`）
+pub fn text(status: StatusCode, body: impl Into<String>) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body.into())));
+    *response.status_mut() = status;
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+    response
+}
+
+/// 为响应添加头部，名称或值非法时返回错误而不是 panic
+///
+/// 适用于头部内容来自用户数据的场景，与 [`set_header`] 相同（替换已有值）
+pub fn with_header<B>(response: Response<B>, name: &str, value: &str) -> RatResult<Response<B>> {
+    set_header(response, name, value)
+}
+
+/// 设置响应头，替换该名称已有的所有值
+pub fn set_header<B>(mut response: Response<B>, name: &str, value: &str) -> RatResult<Response<B>> {
+    let (header_name, header_value) = parse_header(name, value)?;
+    response.headers_mut().insert(header_name, header_value);
+    Ok(response)
+}
+
+/// 追加响应头，保留该名称已有的值（如多个 `Set-Cookie`、`Link`）
+pub fn append_header<B>(mut response: Response<B>, name: &str, value: &str) -> RatResult<Response<B>> {
+    let (header_name, header_value) = parse_header(name, value)?;
+    response.headers_mut().append(header_name, header_value);
+    Ok(response)
+}
+
+fn parse_header(name: &str, value: &str) -> RatResult<(HeaderName, HeaderValue)> {
+    let header_name = HeaderName::from_bytes(name.as_bytes())
+        .map_err(|e| RatError::InvalidArgument(format!("无效的响应头名称 {:?}: {}", name, e)))?;
+    let header_value = HeaderValue::from_str(value)
+        .map_err(|e| RatError::InvalidArgument(format!("无效的响应头值 {:?}: {}", value, e)))?;
+    Ok((header_name, header_value))
+}
+
+/// 为响应指定 TCP 写出策略
+///
+/// 大文件等吞吐优先的响应使用 [`TcpWriteMode::Throughput`] 合并写出，
+/// 小的 JSON 响应使用 [`TcpWriteMode::LowLatency`]。
+/// 目前只在引擎直接处理的明文 HTTP/1.1 连接上生效，其他路径忽略该标记
+pub fn with_tcp_write_mode<B>(mut response: Response<B>, mode: TcpWriteMode) -> Response<B> {
+    response.extensions_mut().insert(mode);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_sets_location() {
+        let resp = redirect(StatusCode::MOVED_PERMANENTLY, "https://example.com/new").unwrap();
+        assert_eq!(resp.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(resp.headers().get(LOCATION).unwrap(), "https://example.com/new");
+    }
+
+    #[test]
+    fn test_redirect_rejects_non_3xx() {
+        assert!(redirect(StatusCode::OK, "/").is_err());
+        assert!(redirect(StatusCode::NOT_FOUND, "/").is_err());
+    }
+
+    #[test]
+    fn test_redirect_rejects_invalid_location() {
+        assert!(redirect(StatusCode::FOUND, "/bad\nlocation").is_err());
+    }
+
+    #[test]
+    fn test_redirect_shortcuts() {
+        assert_eq!(redirect_permanent("/a").unwrap().status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(redirect_temporary("/a").unwrap().status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(see_other("/a").unwrap().status(), StatusCode::SEE_OTHER);
+    }
+
+    #[test]
+    fn test_json_and_text() {
+        let resp = json(StatusCode::CREATED, &vec![1, 2, 3]).unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.headers()[CONTENT_TYPE], "application/json");
+
+        // 非字符串键的映射无法序列化为 JSON，返回错误而不是 panic
+        let mut map = std::collections::HashMap::new();
+        map.insert((1, 2), "v");
+        assert!(matches!(json(StatusCode::OK, &map), Err(RatError::SerializationError(_))));
+
+        assert_eq!(text(StatusCode::OK, "hi").headers()[CONTENT_TYPE], "text/plain; charset=utf-8");
+    }
+
+    #[test]
+    fn test_with_header_rejects_invalid_value() {
+        let resp = text(StatusCode::OK, "hi");
+        assert!(with_header(resp, "x-user", "bad\nvalue").is_err());
+        let resp = with_header(text(StatusCode::OK, "hi"), "x-user", "alice").unwrap();
+        assert_eq!(resp.headers()["x-user"], "alice");
+    }
+
+    #[test]
+    fn test_set_header_replaces_and_append_header_keeps() {
+        let resp = append_header(text(StatusCode::OK, "hi"), "set-cookie", "a=1").unwrap();
+        let resp = append_header(resp, "set-cookie", "b=2").unwrap();
+        assert_eq!(resp.headers().get_all("set-cookie").iter().count(), 2);
+
+        let resp = set_header(resp, "set-cookie", "c=3").unwrap();
+        let cookies: Vec<_> = resp.headers().get_all("set-cookie").iter().collect();
+        assert_eq!(cookies, vec!["c=3"]);
+
+        let resp = set_header(resp, "content-type", "application/xml").unwrap();
+        assert_eq!(resp.headers()[CONTENT_TYPE], "application/xml");
+    }
+
+    #[test]
+    fn test_with_tcp_write_mode() {
+        let resp = with_tcp_write_mode(text(StatusCode::OK, "big"), TcpWriteMode::Throughput);
+        assert_eq!(resp.extensions().get::<TcpWriteMode>(), Some(&TcpWriteMode::Throughput));
+    }
+}
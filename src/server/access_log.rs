@@ -0,0 +1,236 @@
+//! 访问日志记录
+//!
+//! 每个连接在建立时创建一个 [`ConnectionContext`]，随请求扩展传递到适配器，
+//! 用于在访问日志中记录协商的协议、连接是否被复用（keep-alive / 多路复用）以及连接存活时间，
+//! 便于判断客户端是否真正使用了 HTTP/2，以及 keep-alive 的实际效果。
+//!
+//! gRPC 调用不经过适配器，由 `Router::handle_grpc_request` 以 [`AccessLogProtocol::Grpc`] 记录，
+//! 这类记录不带连接信息。
+
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use hyper::{Method, Version};
+
+/// 连接上下文
+///
+/// 同一连接上的所有请求共享同一个实例（克隆只增加引用计数）
+#[derive(Debug, Clone)]
+pub struct ConnectionContext {
+    established_at: Instant,
+    requests: Arc<RequestCounter>,
+    detected: Option<crate::server::protocol_detector::DetectedProtocol>,
+}
+
+/// 连接上的请求计数，最后一个引用释放（连接关闭）时汇总到连接复用指标
+struct RequestCounter {
+    count: AtomicU64,
+    metrics: Option<Arc<crate::engine::metrics::AtomicMetrics>>,
+}
+
+impl fmt::Debug for RequestCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestCounter")
+            .field("count", &self.count.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl Drop for RequestCounter {
+    fn drop(&mut self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_connection_closed(self.count.load(Ordering::Relaxed));
+        }
+    }
+}
+
+impl Default for ConnectionContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectionContext {
+    /// 在连接建立时创建
+    ///
+    /// 在协议检测作用域中创建时会记录该连接的协议判定结果
+    pub fn new() -> Self {
+        Self::with_metrics(None)
+    }
+
+    /// 创建连接上下文，连接关闭时把请求数汇总到 `metrics` 的连接复用统计
+    pub fn with_metrics(metrics: Option<Arc<crate::engine::metrics::AtomicMetrics>>) -> Self {
+        Self {
+            established_at: Instant::now(),
+            requests: Arc::new(RequestCounter { count: AtomicU64::new(0), metrics }),
+            detected: crate::server::protocol_detector::current_detected(),
+        }
+    }
+
+    /// 连接的协议判定结果（未经过协议检测时为 None）
+    pub fn detected_protocol(&self) -> Option<crate::server::protocol_detector::DetectedProtocol> {
+        self.detected
+    }
+
+    /// 记录一个新请求，返回该请求在连接上的序号（从 1 开始）
+    pub fn begin_request(&self) -> u64 {
+        self.requests.count.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// 连接上已处理的请求数
+    pub fn request_count(&self) -> u64 {
+        self.requests.count.load(Ordering::Relaxed)
+    }
+
+    /// 连接存活时间
+    pub fn age(&self) -> Duration {
+        self.established_at.elapsed()
+    }
+}
+
+/// 访问日志协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessLogProtocol {
+    Http10,
+    Http11,
+    Http2,
+    Grpc,
+    Other,
+}
+
+impl AccessLogProtocol {
+    /// 根据 HTTP 版本和 Content-Type 判断协议
+    pub fn detect(version: Version, content_type: Option<&str>) -> Self {
+        let is_grpc = content_type.is_some_and(|ct| ct.starts_with("application/grpc"));
+        match version {
+            Version::HTTP_2 if is_grpc => AccessLogProtocol::Grpc,
+            Version::HTTP_2 => AccessLogProtocol::Http2,
+            Version::HTTP_11 => AccessLogProtocol::Http11,
+            Version::HTTP_10 => AccessLogProtocol::Http10,
+            _ => AccessLogProtocol::Other,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessLogProtocol::Http10 => "HTTP/1.0",
+            AccessLogProtocol::Http11 => "HTTP/1.1",
+            AccessLogProtocol::Http2 => "HTTP/2",
+            AccessLogProtocol::Grpc => "gRPC",
+            AccessLogProtocol::Other => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for AccessLogProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 单条访问日志记录
+#[derive(Debug, Clone)]
+pub struct AccessLogRecord {
+    /// 客户端 IP（未知时为 None）
+    pub client_ip: Option<IpAddr>,
+    pub method: Method,
+    pub path: String,
+    pub status: u16,
+    /// 请求处理耗时
+    pub duration: Duration,
+    /// 协商的协议
+    pub protocol: AccessLogProtocol,
+    /// 请求在连接上的序号（从 1 开始，没有连接上下文时为 None）
+    pub connection_request_seq: Option<u64>,
+    /// 处理请求时连接已存活的时间
+    pub connection_age: Option<Duration>,
+}
+
+impl AccessLogRecord {
+    /// 是否复用了已有连接（HTTP/1.1 keep-alive 或 HTTP/2 多路复用）
+    pub fn reused_connection(&self) -> bool {
+        self.connection_request_seq.is_some_and(|seq| seq > 1)
+    }
+}
+
+impl fmt::Display for AccessLogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.client_ip {
+            Some(ip) => write!(f, "{}", ip)?,
+            None => f.write_str("unknown")?,
+        }
+        write!(
+            f,
+            " {} {} {} {} {}",
+            self.method,
+            self.path,
+            self.status,
+            crate::utils::logger::format_duration(self.duration),
+            self.protocol
+        )?;
+        if let (Some(seq), Some(age)) = (self.connection_request_seq, self.connection_age) {
+            write!(
+                f,
+                " conn#{} {} age={}",
+                seq,
+                if self.reused_connection() { "reused" } else { "new" },
+                crate::utils::logger::format_duration(age)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_protocol() {
+        assert_eq!(AccessLogProtocol::detect(Version::HTTP_11, None), AccessLogProtocol::Http11);
+        assert_eq!(AccessLogProtocol::detect(Version::HTTP_2, Some("text/html")), AccessLogProtocol::Http2);
+        assert_eq!(AccessLogProtocol::detect(Version::HTTP_2, Some("application/grpc+proto")), AccessLogProtocol::Grpc);
+    }
+
+    #[test]
+    fn test_connection_reuse() {
+        let conn = ConnectionContext::new();
+        let first = conn.begin_request();
+        let second = conn.clone().begin_request();
+        assert_eq!((first, second), (1, 2));
+        assert_eq!(conn.request_count(), 2);
+
+        let mut record = AccessLogRecord {
+            client_ip: None,
+            method: Method::GET,
+            path: "/".to_string(),
+            status: 200,
+            duration: Duration::from_millis(1),
+            protocol: AccessLogProtocol::Http11,
+            connection_request_seq: Some(first),
+            connection_age: Some(conn.age()),
+        };
+        assert!(!record.reused_connection());
+        record.connection_request_seq = Some(second);
+        assert!(record.reused_connection());
+        assert!(record.to_string().contains("conn#2 reused"));
+    }
+
+    #[test]
+    fn test_connection_close_records_reuse_metrics() {
+        let metrics = Arc::new(crate::engine::metrics::AtomicMetrics::new());
+        let conn = ConnectionContext::with_metrics(Some(metrics.clone()));
+        let per_request = conn.clone();
+        conn.begin_request();
+        per_request.begin_request();
+        drop(per_request);
+        assert_eq!(metrics.connection_reuse_stats().closed_connections, 0);
+
+        drop(conn);
+        let stats = metrics.connection_reuse_stats();
+        assert_eq!((stats.closed_connections, stats.requests), (1, 2));
+    }
+}
@@ -0,0 +1,111 @@
+//! 应用状态注册表
+//!
+//! 按类型存放共享状态（数据库连接池、配置等），处理器通过 `req.state::<T>()` 获取，
+//! 无需在每个闭包中捕获同一个 `Arc`。每种类型只保存一个实例，以 `TypeId` 为键。
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// 按类型索引的共享状态集合
+///
+/// 克隆只增加引用计数；插入时写时复制，不影响已分发给请求的副本
+#[derive(Clone, Default)]
+pub struct AppState {
+    states: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl AppState {
+    /// 创建空的状态集合
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 插入状态，同类型的旧值会被替换
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: Arc<T>) {
+        Arc::make_mut(&mut self.states).insert(TypeId::of::<T>(), value);
+    }
+
+    /// 获取指定类型的状态
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.states
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast::<T>().ok())
+    }
+
+    /// 是否包含指定类型的状态
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.states.contains_key(&TypeId::of::<T>())
+    }
+
+    /// 状态数量
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// 用 `other` 中的状态补全缺失的类型，已存在的类型保持不变
+    ///
+    /// 请求上预先注入的状态（例如测试中的模拟对象）优先于路由器注册的状态
+    pub fn fill_from(&mut self, other: &AppState) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            self.states = other.states.clone();
+            return;
+        }
+        let states = Arc::make_mut(&mut self.states);
+        for (type_id, value) in other.states.iter() {
+            states.entry(*type_id).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+impl fmt::Debug for AppState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppState").field("len", &self.states.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Config {
+        name: &'static str,
+    }
+
+    #[test]
+    fn test_typed_lookup() {
+        let mut state = AppState::new();
+        state.insert(Arc::new(Config { name: "prod" }));
+        state.insert(Arc::new(42u32));
+
+        assert_eq!(state.get::<Config>().unwrap().name, "prod");
+        assert_eq!(*state.get::<u32>().unwrap(), 42);
+        assert!(state.get::<String>().is_none());
+    }
+
+    #[test]
+    fn test_fill_from_keeps_existing() {
+        let mut router_state = AppState::new();
+        router_state.insert(Arc::new(Config { name: "prod" }));
+        router_state.insert(Arc::new(1u32));
+
+        let mut request_state = AppState::new();
+        request_state.insert(Arc::new(Config { name: "mock" }));
+        request_state.fill_from(&router_state);
+
+        assert_eq!(request_state.get::<Config>().unwrap().name, "mock");
+        assert_eq!(*request_state.get::<u32>().unwrap(), 1);
+        // 路由器自身的状态不受影响
+        assert_eq!(router_state.get::<Config>().unwrap().name, "prod");
+    }
+}
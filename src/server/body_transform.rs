@@ -0,0 +1,138 @@
+//! 响应体转换中间件
+//!
+//! 在处理器返回之后、缓存和压缩之前改写完整的响应体，
+//! 适用于添加 BOM、向 HTML 注入统计脚本、脱敏等场景。
+//!
+//! 只作用于普通（非流式）路由：流式路由的响应体是增量产生的，转换器需要完整内容，
+//! 因此会直接跳过。已带有 `Content-Encoding` 的响应同样跳过，避免改写压缩后的字节。
+
+use std::sync::Arc;
+
+use hyper::Response;
+use hyper::body::Bytes;
+use hyper::header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, HeaderValue};
+use http_body_util::{BodyExt, Full};
+
+/// 响应体转换器
+pub trait BodyTransform: Send + Sync {
+    /// 转换响应体，`content_type` 为响应的完整 Content-Type（可能带参数）
+    fn transform_body(&self, content_type: &str, body: &[u8]) -> Vec<u8>;
+}
+
+impl<F> BodyTransform for F
+where
+    F: Fn(&str, &[u8]) -> Vec<u8> + Send + Sync,
+{
+    fn transform_body(&self, content_type: &str, body: &[u8]) -> Vec<u8> {
+        self(content_type, body)
+    }
+}
+
+/// 带 Content-Type 过滤的转换器
+#[derive(Clone)]
+pub struct BodyTransformRule {
+    /// 匹配的媒体类型（小写，支持 `text/*` 形式的通配）
+    content_types: Vec<String>,
+    transform: Arc<dyn BodyTransform>,
+}
+
+impl BodyTransformRule {
+    /// 创建转换规则，`content_types` 为空时匹配所有响应
+    pub fn new<T: BodyTransform + 'static>(content_types: &[&str], transform: T) -> Self {
+        Self {
+            content_types: content_types.iter().map(|ct| ct.trim().to_ascii_lowercase()).collect(),
+            transform: Arc::new(transform),
+        }
+    }
+
+    /// 规则是否适用于该 Content-Type
+    pub fn matches(&self, content_type: &str) -> bool {
+        if self.content_types.is_empty() {
+            return true;
+        }
+
+        let media_type = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        self.content_types.iter().any(|pattern| match pattern.strip_suffix("/*") {
+            Some(prefix) => media_type.split('/').next() == Some(prefix),
+            None => *pattern == media_type,
+        })
+    }
+}
+
+/// 依次应用匹配的转换规则，并更新 Content-Length
+pub(crate) async fn apply(rules: &[BodyTransformRule], response: Response<Full<Bytes>>) -> Response<Full<Bytes>> {
+    if rules.is_empty() || response.headers().contains_key(CONTENT_ENCODING) {
+        return response;
+    }
+
+    let content_type = response.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    let matched: Vec<&BodyTransformRule> = rules.iter().filter(|rule| rule.matches(&content_type)).collect();
+    if matched.is_empty() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match body.collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(never) => match never {},
+    };
+
+    let mut transformed = body.to_vec();
+    for rule in matched {
+        transformed = rule.transform.transform_body(&content_type, &transformed);
+    }
+
+    if parts.headers.contains_key(CONTENT_LENGTH) {
+        parts.headers.insert(CONTENT_LENGTH, HeaderValue::from(transformed.len()));
+    }
+    Response::from_parts(parts, Full::new(Bytes::from(transformed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type_matching() {
+        let rule = BodyTransformRule::new(&["text/html", "application/*"], |_: &str, body: &[u8]| body.to_vec());
+        assert!(rule.matches("text/html; charset=utf-8"));
+        assert!(rule.matches("Application/JSON"));
+        assert!(!rule.matches("text/plain"));
+        assert!(!rule.matches(""));
+
+        let any = BodyTransformRule::new(&[], |_: &str, body: &[u8]| body.to_vec());
+        assert!(any.matches("image/png"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_updates_content_length_and_skips_encoded() {
+        let bom = BodyTransformRule::new(&["text/csv"], |_: &str, body: &[u8]| {
+            let mut out = b"\xEF\xBB\xBF".to_vec();
+            out.extend_from_slice(body);
+            out
+        });
+        let rules = vec![bom];
+
+        let response = Response::builder()
+            .header(CONTENT_TYPE, "text/csv")
+            .header(CONTENT_LENGTH, "3")
+            .body(Full::new(Bytes::from("a,b")))
+            .unwrap();
+        let response = apply(&rules, response).await;
+        assert_eq!(response.headers()[CONTENT_LENGTH], "6");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"\xEF\xBB\xBFa,b");
+
+        let encoded = Response::builder()
+            .header(CONTENT_TYPE, "text/csv")
+            .header(CONTENT_ENCODING, "gzip")
+            .body(Full::new(Bytes::from("a,b")))
+            .unwrap();
+        let body = apply(&rules, encoded).await.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"a,b");
+    }
+}
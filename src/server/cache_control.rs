@@ -0,0 +1,141 @@
+//! 客户端缓存控制
+//!
+//! 缓存中间件负责服务端缓存，[`CacheControl`] 负责告诉浏览器和代理如何缓存响应：
+//! 按配置生成 `Cache-Control` 和 `Expires` 头部，避免每个处理器手写字符串。
+//!
+//! ```ignore
+//! // 带指纹的静态资源内容永不变化，可以长期缓存
+//! router.add_route_with_cache_control(Method::GET, "/assets/<path>", CacheControl::immutable_asset(), serve_asset);
+//!
+//! // 包含个人数据的接口禁止缓存
+//! router.add_route_with_cache_control(Method::GET, "/me", CacheControl::no_store(), profile);
+//! ```
+//!
+//! 只有成功（2xx）的响应会被设置头部，处理器自行设置了 `Cache-Control` 时保持不变。
+
+use std::time::{Duration, SystemTime};
+
+use hyper::Response;
+use hyper::header::{HeaderMap, HeaderValue, CACHE_CONTROL, EXPIRES};
+
+/// 带指纹的静态资源的缓存时间（一年）
+const IMMUTABLE_MAX_AGE: u64 = 365 * 24 * 3600;
+
+/// 客户端缓存策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    /// 缓存有效期（秒）
+    pub max_age: u64,
+    /// 允许代理等共享缓存保存（`public`），否则只允许浏览器缓存（`private`）
+    pub public: bool,
+    /// 有效期内内容不会变化，浏览器刷新时也无需重新验证
+    pub immutable: bool,
+    /// 禁止任何缓存，设置后忽略其他字段
+    pub no_store: bool,
+}
+
+impl CacheControl {
+    /// 只允许浏览器缓存指定时间
+    pub fn private(max_age: Duration) -> Self {
+        Self { max_age: max_age.as_secs(), ..Self::default() }
+    }
+
+    /// 允许浏览器和共享缓存缓存指定时间
+    pub fn public(max_age: Duration) -> Self {
+        Self { max_age: max_age.as_secs(), public: true, ..Self::default() }
+    }
+
+    /// 带内容指纹的静态资源：公开缓存一年且不再验证
+    pub fn immutable_asset() -> Self {
+        Self { max_age: IMMUTABLE_MAX_AGE, public: true, immutable: true, no_store: false }
+    }
+
+    /// 禁止缓存
+    pub fn no_store() -> Self {
+        Self { no_store: true, ..Self::default() }
+    }
+
+    /// `Cache-Control` 头部的值
+    pub fn header_value(&self) -> String {
+        if self.no_store {
+            return "no-store".to_string();
+        }
+        let mut value = format!(
+            "{}, max-age={}",
+            if self.public { "public" } else { "private" },
+            self.max_age
+        );
+        if self.immutable {
+            value.push_str(", immutable");
+        }
+        value
+    }
+
+    /// 写入 `Cache-Control` 和 `Expires`，已有 `Cache-Control` 时不做修改
+    pub fn apply_to_headers(&self, headers: &mut HeaderMap) {
+        if headers.contains_key(CACHE_CONTROL) {
+            return;
+        }
+        if let Ok(value) = HeaderValue::from_str(&self.header_value()) {
+            headers.insert(CACHE_CONTROL, value);
+        }
+
+        // 兼容只认识 HTTP/1.0 Expires 的缓存
+        let expires = if self.no_store || self.max_age == 0 {
+            SystemTime::UNIX_EPOCH
+        } else {
+            SystemTime::now() + Duration::from_secs(self.max_age)
+        };
+        if let Ok(value) = HeaderValue::from_str(&httpdate::fmt_http_date(expires)) {
+            headers.insert(EXPIRES, value);
+        }
+    }
+
+    /// 为成功响应设置缓存头部
+    pub fn apply<B>(&self, response: &mut Response<B>) {
+        if response.status().is_success() {
+            self.apply_to_headers(response.headers_mut());
+        }
+    }
+}
+
+/// 把响应标记为带指纹的不可变资源
+pub fn mark_immutable<B>(response: &mut Response<B>) {
+    CacheControl::immutable_asset().apply(response);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::StatusCode;
+
+    #[test]
+    fn test_header_values() {
+        assert_eq!(CacheControl::private(Duration::from_secs(60)).header_value(), "private, max-age=60");
+        assert_eq!(CacheControl::public(Duration::from_secs(300)).header_value(), "public, max-age=300");
+        assert_eq!(CacheControl::immutable_asset().header_value(), "public, max-age=31536000, immutable");
+        let no_store = CacheControl { max_age: 600, public: true, no_store: true, ..CacheControl::default() };
+        assert_eq!(no_store.header_value(), "no-store");
+    }
+
+    #[test]
+    fn test_apply_respects_status_and_existing_header() {
+        let mut response = Response::new(());
+        mark_immutable(&mut response);
+        assert_eq!(response.headers()[CACHE_CONTROL], "public, max-age=31536000, immutable");
+        assert!(response.headers().contains_key(EXPIRES));
+
+        // 错误响应不缓存
+        let mut response = Response::new(());
+        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        mark_immutable(&mut response);
+        assert!(!response.headers().contains_key(CACHE_CONTROL));
+
+        // 处理器自己设置的策略优先
+        let mut response = Response::new(());
+        response.headers_mut().insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        CacheControl::no_store().apply(&mut response);
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-cache");
+        assert!(!response.headers().contains_key(EXPIRES));
+    }
+}
@@ -16,8 +16,20 @@ use http_body_util::{combinators::BoxBody, Full, BodyExt};
 use std::error::Error;
 use std::sync::Arc;
 use bytes::Bytes as BytesType;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use crate::cache::Cache;
+use dashmap::DashMap;
+
+/// 多版本缓存键可能带有的编码后缀
+const ENCODING_SUFFIXES: &[&str] = &["identity", "gzip", "deflate", "br", "lz4", "zstd"];
+
+/// 清理缓存键索引中过期条目的最小间隔
+const KEY_PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 底层缓存只支持秒级 TTL：向上取整，保证底层条目不早于索引中的到期时间过期
+fn ttl_seconds(ttl: Duration) -> u64 {
+    (ttl.as_millis().div_ceil(1000) as u64).max(1)
+}
 
 /// 简化的缓存中间件结构体
 pub struct CacheMiddleware {
@@ -25,6 +37,12 @@ pub struct CacheMiddleware {
     cache: Arc<dyn Cache>,
     /// 默认TTL（秒）
     default_ttl: Option<u64>,
+    /// 已写入的缓存键索引（用于按路径失效）：键 -> 到期时间（None 表示不过期）
+    ///
+    /// 到期时间保留毫秒精度，读取时以它为准；过期条目定期从索引中清理
+    keys: DashMap<String, Option<Instant>>,
+    /// 下一次允许清理索引的时间
+    next_key_prune: std::sync::Mutex<Instant>,
 }
 
 impl CacheMiddleware {
@@ -43,7 +61,39 @@ impl CacheMiddleware {
         Self {
             cache,
             default_ttl,
+            keys: DashMap::new(),
+            next_key_prune: std::sync::Mutex::new(Instant::now() + KEY_PRUNE_INTERVAL),
+        }
+    }
+
+    /// 记录写入的缓存键及其到期时间，并按间隔清理已过期的索引条目
+    fn record_key(&self, key: &str, ttl: Option<Duration>) {
+        let now = Instant::now();
+        self.keys.insert(key.to_string(), ttl.map(|ttl| now + ttl));
+        self.prune_expired_keys(now);
+    }
+
+    /// 清理索引中已过期的条目（两次清理至少间隔 [`KEY_PRUNE_INTERVAL`]）
+    ///
+    /// 底层缓存按向上取整的秒数过期，到期后再保留 1 秒，确保底层条目也已过期
+    fn prune_expired_keys(&self, now: Instant) {
+        {
+            let Ok(mut next) = self.next_key_prune.try_lock() else {
+                return;
+            };
+            if now < *next {
+                return;
+            }
+            *next = now + KEY_PRUNE_INTERVAL;
         }
+        self.keys.retain(|_, expires_at| expires_at.is_none_or(|at| now < at + Duration::from_secs(1)));
+    }
+
+    /// 索引中记录的到期时间是否已过（底层缓存可能仍保留该条目，最多 1 秒）
+    fn is_expired(&self, key: &str) -> bool {
+        self.keys.get(key)
+            .and_then(|expires_at| *expires_at)
+            .is_some_and(|at| Instant::now() >= at)
     }
 
     /// 检查响应是否可以缓存
@@ -93,6 +143,16 @@ impl CacheMiddleware {
         &self,
         req: &Request<B>,
         res: Response<BoxBody<Bytes, Box<dyn Error + Send + Sync>>>,
+    ) -> Result<Response<BoxBody<Bytes, Box<dyn Error + Send + Sync>>>, hyper::Error> {
+        self.process_with_ttl(req, res, None).await
+    }
+
+    /// 处理请求和响应（可覆盖默认TTL，用于按路由配置缓存时间，精确到毫秒）
+    pub async fn process_with_ttl<B>(
+        &self,
+        req: &Request<B>,
+        res: Response<BoxBody<Bytes, Box<dyn Error + Send + Sync>>>,
+        ttl_override: Option<Duration>,
     ) -> Result<Response<BoxBody<Bytes, Box<dyn Error + Send + Sync>>>, hyper::Error> {
         // 只缓存GET请求
         if req.method() != hyper::Method::GET {
@@ -105,7 +165,11 @@ impl CacheMiddleware {
 
         // 尝试从缓存获取
         let start_time = Instant::now();
-        let cached_data = self.cache.get(&cache_key).await;
+        let cached_data = if self.is_expired(&cache_key) {
+            Ok(None)
+        } else {
+            self.cache.get(&cache_key).await
+        };
 
         match cached_data {
             Ok(Some(data)) => {
@@ -166,13 +230,15 @@ impl CacheMiddleware {
             crate::utils::logger::info!("🎯 [CacheMiddleware] 响应可以缓存，开始存储...");
 
             // 直接使用 rat_memcache 存储原始数据
-            if let Some(ttl) = self.default_ttl {
+            if let Some(ttl) = ttl_override.or(self.default_ttl.map(Duration::from_secs)) {
+                self.record_key(&cache_key, Some(ttl));
                 let _ = self.cache.set_with_ttl(
                     cache_key.clone(),
                     bytes.clone(),
-                    ttl
+                    ttl_seconds(ttl)
                 ).await;
             } else {
+                self.record_key(&cache_key, None);
                 let _ = self.cache.set(
                     cache_key.clone(),
                     bytes.clone()
@@ -211,22 +277,163 @@ impl CacheMiddleware {
 
     /// 直接访问底层缓存的方法（供CacheVersionManager使用）
     pub async fn get_direct(&self, key: &str) -> Result<Option<bytes::Bytes>, Box<dyn std::error::Error + Send + Sync>> {
+        if self.is_expired(key) {
+            return Ok(None);
+        }
         self.cache.get(key).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
     }
 
     /// 直接设置底层缓存的方法（供CacheVersionManager使用）
     pub async fn set_direct(&self, key: &str, value: bytes::Bytes) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.record_key(key, None);
         self.cache.set(key.to_string(), value).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
     }
 
     /// 直接设置底层缓存带TTL的方法（供CacheVersionManager使用）
     pub async fn set_direct_with_ttl(&self, key: &str, value: bytes::Bytes, ttl: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.cache.set_with_ttl(key.to_string(), value, ttl).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+        self.set_direct_with_expiry(key, value, Duration::from_secs(ttl)).await
+    }
+
+    /// 直接设置底层缓存，TTL 精确到毫秒（底层按秒存储，读取时按索引中的到期时间判断）
+    pub async fn set_direct_with_expiry(&self, key: &str, value: bytes::Bytes, ttl: Duration) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.record_key(key, Some(ttl));
+        self.cache.set_with_ttl(key.to_string(), value, ttl_seconds(ttl)).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
     }
 
     /// 直接删除底层缓存的方法（供CacheVersionManager使用）
     pub async fn delete_direct(&self, key: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        self.keys.remove(key);
         self.cache.delete(key).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
     }
+
+    /// 获取已写入且未过期的缓存键
+    pub fn cached_keys(&self) -> Vec<String> {
+        let now = Instant::now();
+        self.keys.iter()
+            .filter(|entry| entry.value().is_none_or(|at| now < at))
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    /// 按路径或模式使失效
+    ///
+    /// - `/users/1`：精确匹配该路径（忽略查询参数和编码版本）
+    /// - `/users/*`：匹配该前缀下的所有路径
+    ///
+    /// 返回删除的缓存条目数量
+    pub async fn invalidate(&self, path_or_pattern: &str) -> usize {
+        let matched: Vec<String> = self.keys.iter()
+            .filter(|entry| Self::key_matches(entry.key(), path_or_pattern))
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        let mut removed = 0;
+        for key in matched {
+            match self.delete_direct(&key).await {
+                Ok(_) => removed += 1,
+                Err(e) => crate::utils::logger::warn!("⚠️ [CacheMiddleware] 删除缓存失败 {}: {}", key, e),
+            }
+        }
+
+        crate::utils::logger::info!("🧹 [CacheMiddleware] 缓存失效: {} (共 {} 条)", path_or_pattern, removed);
+        removed
+    }
+
+    /// 清空全部缓存
+    pub async fn invalidate_all(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.keys.clear();
+        self.cache.clear().await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        crate::utils::logger::info!("🧹 [CacheMiddleware] 已清空全部缓存");
+        Ok(())
+    }
+
+    /// 检查缓存键是否匹配路径模式
+    ///
+    /// 缓存键格式：`{METHOD}{path}[?query][:encoding]`
+    pub fn key_matches(key: &str, path_or_pattern: &str) -> bool {
+        // 去掉方法前缀
+        let rest = key.trim_start_matches(|c: char| c.is_ascii_uppercase());
+        // 去掉多版本缓存的编码后缀
+        let rest = match rest.rsplit_once(':') {
+            Some((base, encoding)) if ENCODING_SUFFIXES.contains(&encoding) => base,
+            _ => rest,
+        };
+        // 去掉查询参数
+        let path = rest.split('?').next().unwrap_or(rest);
+
+        match path_or_pattern.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == path_or_pattern,
+        }
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RatResult;
+    use std::collections::HashMap;
+
+    /// 忽略 TTL 的内存缓存，用于验证索引中的毫秒级到期时间
+    #[derive(Default)]
+    struct MemoryCache(std::sync::Mutex<HashMap<String, Bytes>>);
+
+    #[async_trait::async_trait]
+    impl Cache for MemoryCache {
+        async fn set(&self, key: String, value: Bytes) -> RatResult<()> {
+            self.0.lock().unwrap().insert(key, value);
+            Ok(())
+        }
+
+        async fn set_with_ttl(&self, key: String, value: Bytes, _ttl_seconds: u64) -> RatResult<()> {
+            self.set(key, value).await
+        }
+
+        async fn get(&self, key: &str) -> RatResult<Option<Bytes>> {
+            Ok(self.0.lock().unwrap().get(key).cloned())
+        }
+
+        async fn delete(&self, key: &str) -> RatResult<bool> {
+            Ok(self.0.lock().unwrap().remove(key).is_some())
+        }
+
+        async fn clear(&self) -> RatResult<()> {
+            self.0.lock().unwrap().clear();
+            Ok(())
+        }
+
+        async fn exists(&self, key: &str) -> RatResult<bool> {
+            Ok(self.0.lock().unwrap().contains_key(key))
+        }
+
+        async fn get_stats(&self) -> RatResult<String> {
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_ttl_seconds_rounds_up() {
+        assert_eq!(ttl_seconds(Duration::from_millis(1)), 1);
+        assert_eq!(ttl_seconds(Duration::from_millis(1500)), 2);
+        assert_eq!(ttl_seconds(Duration::from_secs(3)), 3);
+    }
+
+    #[tokio::test]
+    async fn test_millisecond_expiry_and_index_pruning() {
+        let middleware = CacheMiddleware::new(Arc::new(MemoryCache::default()), None);
+        middleware.set_direct_with_expiry("GET/short", Bytes::from("a"), Duration::from_millis(30)).await.unwrap();
+        middleware.set_direct("GET/forever", Bytes::from("b")).await.unwrap();
+        assert!(middleware.get_direct("GET/short").await.unwrap().is_some());
+
+        // 底层缓存仍保留条目，但索引中的到期时间已过
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(middleware.get_direct("GET/short").await.unwrap().is_none());
+        assert_eq!(middleware.cached_keys(), vec!["GET/forever".to_string()]);
+
+        // 底层条目也过期之后，索引条目被清理
+        middleware.prune_expired_keys(Instant::now() + Duration::from_secs(2));
+        assert!(!middleware.keys.contains_key("GET/short"));
+        assert!(middleware.keys.contains_key("GET/forever"));
+    }
+}
@@ -13,6 +13,64 @@ use std::error::Error;
 use std::sync::Arc;
 use bytes::Bytes as BytesType;
 
+/// 响应的缓存状态，对应 `x-cache` 头部
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// 命中缓存
+    Hit,
+    /// 命中过期缓存，后台正在重新验证（stale-while-revalidate）
+    StaleHit,
+    /// 未命中缓存
+    Miss,
+}
+
+impl CacheStatus {
+    /// `x-cache` 头部的值
+    pub fn header_value(&self) -> &'static str {
+        match self {
+            CacheStatus::Hit => "HIT",
+            CacheStatus::StaleHit => "HIT; stale",
+            CacheStatus::Miss => "MISS",
+        }
+    }
+
+    /// 从响应头部解析缓存状态
+    pub fn from_headers(headers: &hyper::HeaderMap) -> Option<Self> {
+        match headers.get("x-cache")?.to_str().ok()? {
+            "HIT" => Some(CacheStatus::Hit),
+            "HIT; stale" => Some(CacheStatus::StaleHit),
+            "MISS" => Some(CacheStatus::Miss),
+            _ => None,
+        }
+    }
+}
+
+/// 记录响应的缓存状态并按配置决定是否保留缓存相关头部
+///
+/// `expose_headers` 为 false 时移除 `x-cache`、`x-cache-type` 和 `x-cache-time`，
+/// 统计照常记录，便于生产环境隐藏缓存细节
+pub(crate) fn observe_cache_status<B>(
+    response: &mut Response<B>,
+    metrics: Option<&crate::engine::metrics::AtomicMetrics>,
+    expose_headers: bool,
+) -> Option<CacheStatus> {
+    let status = CacheStatus::from_headers(response.headers());
+    if let (Some(status), Some(metrics)) = (status, metrics) {
+        match status {
+            CacheStatus::Hit => metrics.record_cache_hit(false),
+            CacheStatus::StaleHit => metrics.record_cache_hit(true),
+            CacheStatus::Miss => metrics.record_cache_miss(),
+        }
+    }
+    if !expose_headers {
+        let headers = response.headers_mut();
+        headers.remove("x-cache");
+        headers.remove("x-cache-type");
+        headers.remove("x-cache-time");
+    }
+    status
+}
+
 /// 缓存中间件实现
 pub enum CacheMiddlewareImpl {
     /// 单版本缓存
@@ -45,11 +103,21 @@ impl CacheMiddlewareImpl {
         &self,
         req: &Request<B>,
         res: Response<BoxBody<Bytes, Box<dyn Error + Send + Sync>>>,
+    ) -> Result<Response<BoxBody<Bytes, Box<dyn Error + Send + Sync>>>, hyper::Error> {
+        self.process_with_ttl(req, res, None).await
+    }
+
+    /// 处理请求和响应（可覆盖默认TTL，用于按路由配置缓存时间，精确到毫秒）
+    pub async fn process_with_ttl<B>(
+        &self,
+        req: &Request<B>,
+        res: Response<BoxBody<Bytes, Box<dyn Error + Send + Sync>>>,
+        ttl_override: Option<std::time::Duration>,
     ) -> Result<Response<BoxBody<Bytes, Box<dyn Error + Send + Sync>>>, hyper::Error> {
         match self {
             CacheMiddlewareImpl::SingleVersion(middleware) => {
                 // 单版本缓存处理
-                middleware.process(req, res).await
+                middleware.process_with_ttl(req, res, ttl_override).await
             },
             #[cfg(feature = "cache")]
 CacheMiddlewareImpl::MultiVersion(version_manager) => {
@@ -122,12 +190,12 @@ CacheMiddlewareImpl::MultiVersion(version_manager) => {
                         .unwrap_or("application/octet-stream");
 
                     // 使用CacheVersionManager存储数据
-                    if let Err(e) = manager.handle_cache_storage(
+                    if let Err(e) = manager.handle_cache_storage_with_ttl(
                         &base_cache_key,
                         content_type,
                         bytes.clone(),
                         "identity",
-                        None
+                        ttl_override
                     ).await {
                         crate::utils::logger::error!("多版本缓存存储失败: {}", e);
                     }
@@ -152,6 +220,27 @@ CacheMiddlewareImpl::MultiVersion(version_manager) => {
         }
     }
 
+    /// 获取底层单版本缓存中间件
+    fn base_middleware(&self) -> &Arc<CacheMiddleware> {
+        match self {
+            CacheMiddlewareImpl::SingleVersion(middleware) => middleware,
+            #[cfg(feature = "cache")]
+            CacheMiddlewareImpl::MultiVersion(version_manager) => version_manager.cache_middleware(),
+        }
+    }
+
+    /// 按路径或模式使缓存失效（`/users/1` 精确匹配，`/users/*` 前缀匹配）
+    ///
+    /// 多版本缓存会同时清理所有编码版本，返回删除的缓存条目数量
+    pub async fn invalidate(&self, path_or_pattern: &str) -> usize {
+        self.base_middleware().invalidate(path_or_pattern).await
+    }
+
+    /// 清空全部缓存
+    pub async fn invalidate_all(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.base_middleware().invalidate_all().await
+    }
+
     /// 生成缓存键
     ///
     /// # 参数
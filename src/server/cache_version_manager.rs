@@ -365,10 +365,22 @@ impl CacheVersionManager {
     pub async fn handle_cache_storage(
         &self,
         base_key: &str,
-        _content_type: &str,
+        content_type: &str,
         data: Bytes,
         encoding: &str,
         ttl: Option<u64>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.handle_cache_storage_with_ttl(base_key, content_type, data, encoding, ttl.map(std::time::Duration::from_secs)).await
+    }
+
+    /// 处理缓存存储，TTL 精确到毫秒
+    pub async fn handle_cache_storage_with_ttl(
+        &self,
+        base_key: &str,
+        _content_type: &str,
+        data: Bytes,
+        encoding: &str,
+        ttl: Option<std::time::Duration>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // 存储原始版本（或指定编码版本）
         let original_key = format!("{}:{}", base_key, encoding);
@@ -396,7 +408,7 @@ impl CacheVersionManager {
         }
 
         if let Some(ttl) = ttl {
-            self.cache_middleware.set_direct_with_ttl(&original_key, data.clone(), ttl).await?;
+            self.cache_middleware.set_direct_with_expiry(&original_key, data.clone(), ttl).await?;
         } else {
             self.cache_middleware.set_direct(&original_key, data.clone()).await?;
         }
@@ -458,7 +470,7 @@ impl CacheVersionManager {
         &self,
         base_key: &str,
         data: &Bytes,
-        ttl: Option<u64>,
+        ttl: Option<std::time::Duration>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // 创建压缩器配置，启用所有支持的编码和智能压缩
         let mut compression_config = CompressionConfig::new().enable_smart_compression(self.config.enable_smart_precompression);
@@ -545,7 +557,7 @@ impl CacheVersionManager {
 
                         // 存储压缩版本
                         if let Some(ttl) = ttl {
-                            self.cache_middleware.set_direct_with_ttl(&compressed_key, compressed_bytes.clone(), ttl).await?;
+                            self.cache_middleware.set_direct_with_expiry(&compressed_key, compressed_bytes.clone(), ttl).await?;
                         } else {
                             self.cache_middleware.set_direct(&compressed_key, compressed_bytes.clone()).await?;
                         }
@@ -609,6 +621,11 @@ impl CacheVersionManager {
         Ok(removed_count)
     }
 
+    /// 获取底层单版本缓存中间件
+    pub fn cache_middleware(&self) -> &Arc<CacheMiddleware> {
+        &self.cache_middleware
+    }
+
     /// 获取缓存统计信息（如果启用）
     pub fn get_stats(&self) -> Option<&EncodingStats> {
         self.stats.as_ref().map(|v| &**v)
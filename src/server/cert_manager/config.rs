@@ -17,6 +17,8 @@ pub struct CertConfig {
     pub ca_path: Option<PathBuf>,
     /// 支持的域名列表（用于 SNI）
     pub domains: Vec<String>,
+    /// 备用证书（证书路径, 私钥路径），通常与主证书组成 RSA + ECDSA 双证书
+    pub alternate_cert: Option<(PathBuf, PathBuf)>,
 }
 
 impl CertConfig {
@@ -27,6 +29,7 @@ impl CertConfig {
             key_path: key_path.into(),
             ca_path: None,
             domains: Vec::new(),
+            alternate_cert: None,
         }
     }
 
@@ -42,6 +45,15 @@ impl CertConfig {
         self
     }
 
+    /// 添加备用证书
+    ///
+    /// 握手时按客户端支持的签名算法选择：主证书可用时优先使用主证书，
+    /// 否则使用备用证书。常见用法是主证书用 ECDSA、备用证书用 RSA，兼容只支持 RSA 的旧客户端
+    pub fn with_alternate_cert(mut self, cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        self.alternate_cert = Some((cert_path.into(), key_path.into()));
+        self
+    }
+
     /// 验证证书文件是否存在
     pub fn validate(&self) -> Result<(), String> {
         if !self.cert_path.exists() {
@@ -55,10 +67,58 @@ impl CertConfig {
                 return Err(format!("CA 证书文件不存在: {}", ca_path.display()));
             }
         }
+        if let Some((cert_path, key_path)) = &self.alternate_cert {
+            if !cert_path.exists() {
+                return Err(format!("备用证书文件不存在: {}", cert_path.display()));
+            }
+            if !key_path.exists() {
+                return Err(format!("备用私钥文件不存在: {}", key_path.display()));
+            }
+        }
         Ok(())
     }
 }
 
+/// 服务器能够处理的 ALPN 协议
+pub const SUPPORTED_ALPN_PROTOCOLS: &[&str] = &["h2", "http/1.1", "http/1.0"];
+
+/// 未自定义时声明的 ALPN 协议
+const DEFAULT_ALPN_PROTOCOLS: &[&str] = &["h2", "http/1.1"];
+
+/// 默认的 ALPN 协议列表
+///
+/// 同时声明 HTTP/2 和 HTTP/1.1，让 TLS 握手能成功完成，再在应用层检查并拒绝非 HTTP/2 的 gRPC 请求；
+/// `http/1.0` 需要通过自定义列表显式声明
+pub fn default_alpn_protocols() -> Vec<Vec<u8>> {
+    DEFAULT_ALPN_PROTOCOLS.iter().map(|p| p.as_bytes().to_vec()).collect()
+}
+
+/// 校验自定义 ALPN 协议列表并转换为握手使用的字节形式
+///
+/// 列表不能为空、不能重复，且只能包含服务器实际能处理的协议
+pub fn validate_alpn_protocols(protocols: &[String]) -> Result<Vec<Vec<u8>>, String> {
+    if protocols.is_empty() {
+        return Err("ALPN 协议列表不能为空".to_string());
+    }
+
+    let mut validated: Vec<Vec<u8>> = Vec::with_capacity(protocols.len());
+    for protocol in protocols {
+        if !SUPPORTED_ALPN_PROTOCOLS.contains(&protocol.as_str()) {
+            return Err(format!(
+                "服务器不支持 ALPN 协议: {}（支持: {}）",
+                protocol,
+                SUPPORTED_ALPN_PROTOCOLS.join(", ")
+            ));
+        }
+        let bytes = protocol.as_bytes().to_vec();
+        if validated.contains(&bytes) {
+            return Err(format!("ALPN 协议重复: {}", protocol));
+        }
+        validated.push(bytes);
+    }
+    Ok(validated)
+}
+
 /// 证书管理器配置
 ///
 /// 支持两种模式：
@@ -74,6 +134,8 @@ pub struct CertManagerConfig {
     pub http_cert: Option<CertConfig>,
     /// 是否为分端口模式
     pub separated_mode: bool,
+    /// 自定义 ALPN 协议列表（按优先级排列），为 `None` 时使用默认的 `h2`、`http/1.1`
+    pub alpn_protocols: Option<Vec<String>>,
 }
 
 impl Default for CertManagerConfig {
@@ -83,6 +145,7 @@ impl Default for CertManagerConfig {
             grpc_cert: None,
             http_cert: None,
             separated_mode: false,
+            alpn_protocols: None,
         }
     }
 }
@@ -95,6 +158,7 @@ impl CertManagerConfig {
             grpc_cert: None,
             http_cert: None,
             separated_mode: false,
+            alpn_protocols: None,
         }
     }
 
@@ -105,9 +169,19 @@ impl CertManagerConfig {
             grpc_cert: Some(grpc_cert),
             http_cert,
             separated_mode: true,
+            alpn_protocols: None,
         }
     }
 
+    /// 设置自定义 ALPN 协议列表
+    ///
+    /// 列表中的协议必须是服务器能够处理的协议（见 [`SUPPORTED_ALPN_PROTOCOLS`]），
+    /// 在创建证书管理器时校验
+    pub fn with_alpn_protocols(mut self, protocols: Vec<String>) -> Self {
+        self.alpn_protocols = Some(protocols);
+        self
+    }
+
     /// 获取 gRPC 证书配置
     pub fn get_grpc_cert(&self) -> Option<&CertConfig> {
         if self.separated_mode {
@@ -0,0 +1,41 @@
+//! 证书配置校验错误
+
+use std::fmt;
+
+/// 证书配置校验错误
+///
+/// 由 [`CertificateManager::validate`](super::CertificateManager::validate) 返回，
+/// 用于在绑定端口前发现证书配置问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertError {
+    /// 证书、私钥或 CA 文件不存在
+    FileNotFound(String),
+    /// 证书或私钥无法解析
+    InvalidCertificate(String),
+    /// 私钥与证书不匹配
+    KeyMismatch(String),
+    /// 证书链不完整（签发者与上一级证书不对应）
+    BrokenChain(String),
+    /// 启用了 mTLS 但客户端 CA 不可用
+    MissingClientCa(String),
+}
+
+impl fmt::Display for CertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertError::FileNotFound(msg) => write!(f, "证书文件缺失: {}", msg),
+            CertError::InvalidCertificate(msg) => write!(f, "证书解析失败: {}", msg),
+            CertError::KeyMismatch(msg) => write!(f, "{}", rat_embed_lang::tf("key_cert_match_check_failed", &[("msg", msg)])),
+            CertError::BrokenChain(msg) => write!(f, "证书链不完整: {}", msg),
+            CertError::MissingClientCa(msg) => write!(f, "mTLS 客户端 CA 不可用: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CertError {}
+
+impl From<CertError> for crate::error::RatError {
+    fn from(err: CertError) -> Self {
+        crate::error::RatError::TlsError(err.to_string())
+    }
+}
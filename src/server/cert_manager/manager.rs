@@ -8,7 +8,8 @@ use std::collections::HashMap;
 
 use rustls::server::ServerConfig;
 
-use super::config::{CertManagerConfig, CertConfig};
+use super::config::{CertManagerConfig, CertConfig, validate_alpn_protocols};
+use super::error::CertError;
 use super::rustls_cert::RustlsCertManager;
 
 /// 证书管理器
@@ -30,16 +31,28 @@ pub struct CertificateManager {
 impl CertificateManager {
     /// 从配置创建证书管理器
     pub fn from_config(config: CertManagerConfig) -> Result<Self, String> {
+        let alpn_protocols = match &config.alpn_protocols {
+            Some(protocols) => Some(validate_alpn_protocols(protocols)?),
+            None => None,
+        };
+        let load = |cert_config: &CertConfig| -> Result<RustlsCertManager, String> {
+            let mut manager = RustlsCertManager::from_config(cert_config)?;
+            if let Some(alpn) = &alpn_protocols {
+                manager.recreate_server_config_with_alpn(alpn.clone());
+            }
+            Ok(manager)
+        };
+
         if config.separated_mode {
             // 分端口模式
             let grpc_manager = if let Some(grpc_cert) = &config.grpc_cert {
-                Some(RustlsCertManager::from_config(grpc_cert)?)
+                Some(load(grpc_cert)?)
             } else {
                 None
             };
 
             let http_manager = if let Some(http_cert) = &config.http_cert {
-                Some(RustlsCertManager::from_config(http_cert)?)
+                Some(load(http_cert)?)
             } else {
                 None
             };
@@ -53,7 +66,7 @@ impl CertificateManager {
         } else {
             // 同端口模式
             let shared_manager = if let Some(shared_cert) = &config.shared_cert {
-                Some(RustlsCertManager::from_config(shared_cert)?)
+                Some(load(shared_cert)?)
             } else {
                 None
             };
@@ -147,6 +160,197 @@ impl CertificateManager {
     pub fn is_separated_mode(&self) -> bool {
         self.config.separated_mode
     }
+
+    /// 替换 ALPN 协议列表
+    ///
+    /// 校验通过后用新列表重建所有 ServerConfig，并记录到配置中，之后的重新加载会沿用该列表。
+    /// 只影响之后开始的握手
+    pub fn set_alpn_protocols(&mut self, protocols: Vec<String>) -> Result<(), String> {
+        let alpn = validate_alpn_protocols(&protocols)?;
+        for manager in [&mut self.grpc_manager, &mut self.http_manager, &mut self.shared_manager] {
+            if let Some(manager) = manager {
+                manager.recreate_server_config_with_alpn(alpn.clone());
+            }
+        }
+        self.config.alpn_protocols = Some(protocols);
+        Ok(())
+    }
+
+    /// 从磁盘重新加载证书并替换 ServerConfig
+    ///
+    /// 新证书全部加载成功后才会替换，失败时保留原证书。
+    /// 管理器已被服务器共享时应使用 [`Self::reload_shared`]，避免持有写锁读取磁盘
+    pub fn reload(&mut self) -> Result<(), String> {
+        let fresh = Self::from_config(self.config.clone())?;
+        *self = fresh;
+        Ok(())
+    }
+
+    /// 重新加载共享证书管理器中的证书
+    ///
+    /// 新证书在锁外完整加载和校验，写锁只用于替换整个管理器，
+    /// 因此握手读取 ServerConfig 时要么拿到旧配置、要么拿到新配置，不会看到半更新状态，
+    /// 也不会因为读取磁盘而被阻塞。
+    ///
+    /// 已建立的连接和正在握手的连接持有旧 ServerConfig 的 `Arc`，会继续使用旧证书直到关闭；
+    /// 只有替换之后开始的握手才使用新证书
+    pub fn reload_shared(manager: &RwLock<Self>) -> Result<(), String> {
+        let config = manager.read()
+            .map_err(|_| "证书管理器锁已损坏".to_string())?
+            .config
+            .clone();
+        let fresh = Self::from_config(config)?;
+
+        let mut guard = manager.write().map_err(|_| "证书管理器锁已损坏".to_string())?;
+        *guard = fresh;
+        Ok(())
+    }
+
+    /// 校验证书配置
+    ///
+    /// 在绑定端口前完整检查每一组已配置的证书：
+    /// - 证书、私钥、CA 文件存在且可解析
+    /// - 私钥与叶子证书的公钥匹配
+    /// - 证书链中每一级的签发者与下一级证书的主题对应
+    /// - 启用 mTLS 时客户端 CA 至少包含一个可用证书
+    pub fn validate(&self) -> Result<(), CertError> {
+        let certs = if self.config.separated_mode {
+            vec![("gRPC", &self.config.grpc_cert), ("HTTP", &self.config.http_cert)]
+        } else {
+            vec![("共用", &self.config.shared_cert)]
+        };
+
+        for (label, cert_config) in certs {
+            if let Some(cert_config) = cert_config {
+                Self::validate_cert_config(cert_config)
+                    .map_err(|e| Self::label_error(label, e))?;
+
+                // 备用证书按同样的规则独立校验（不限制算法，RSA 与 ECDSA 都可以作为备用证书）
+                if let Some((alt_cert_path, alt_key_path)) = &cert_config.alternate_cert {
+                    Self::validate_cert_config(&CertConfig::from_paths(alt_cert_path, alt_key_path))
+                        .map_err(|e| Self::label_error(&format!("{}/备用", label), e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn label_error(label: &str, err: CertError) -> CertError {
+        match err {
+            CertError::FileNotFound(msg) => CertError::FileNotFound(format!("[{}] {}", label, msg)),
+            CertError::InvalidCertificate(msg) => CertError::InvalidCertificate(format!("[{}] {}", label, msg)),
+            CertError::KeyMismatch(msg) => CertError::KeyMismatch(format!("[{}] {}", label, msg)),
+            CertError::BrokenChain(msg) => CertError::BrokenChain(format!("[{}] {}", label, msg)),
+            CertError::MissingClientCa(msg) => CertError::MissingClientCa(format!("[{}] {}", label, msg)),
+        }
+    }
+
+    fn validate_cert_config(cert_config: &CertConfig) -> Result<(), CertError> {
+        use rustls::pki_types::CertificateDer;
+        use rustls::sign::CertifiedKey;
+        use rustls::{Error as TlsError, InconsistentKeys};
+
+        cert_config.validate().map_err(CertError::FileNotFound)?;
+
+        let cert_pem = std::fs::read(&cert_config.cert_path)
+            .map_err(|e| CertError::InvalidCertificate(format!("{}: {}", cert_config.cert_path.display(), e)))?;
+        let chain: Vec<CertificateDer<'static>> = rustls_pemfile::certs(&mut cert_pem.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CertError::InvalidCertificate(format!("{}: {}", cert_config.cert_path.display(), e)))?;
+        if chain.is_empty() {
+            return Err(CertError::InvalidCertificate(format!("{}: 证书为空", cert_config.cert_path.display())));
+        }
+
+        let key_pem = std::fs::read(&cert_config.key_path)
+            .map_err(|e| CertError::InvalidCertificate(format!("{}: {}", cert_config.key_path.display(), e)))?;
+        let key = rustls_pemfile::private_key(&mut key_pem.as_slice())
+            .map_err(|e| CertError::InvalidCertificate(format!("{}: {}", cert_config.key_path.display(), e)))?
+            .ok_or_else(|| CertError::InvalidCertificate(format!("{}: 私钥文件为空", cert_config.key_path.display())))?;
+
+        let parsed = chain.iter()
+            .map(|der| x509_parser::parse_x509_certificate(der.as_ref()).map(|(_, cert)| cert))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CertError::InvalidCertificate(format!("{}: {}", cert_config.cert_path.display(), e)))?;
+        let algorithm = key_algorithm_name(parsed[0].public_key());
+
+        // 私钥与证书匹配检查（无法提取公钥的密钥类型跳过，与 rustls 的处理一致）
+        let provider = rustls::crypto::ring::default_provider();
+        let signing_key = provider.key_provider.load_private_key(key)
+            .map_err(|e| CertError::InvalidCertificate(format!(
+                "{}: 无法加载 {} 私钥（支持 RSA、ECDSA P-256/P-384、Ed25519）: {}",
+                cert_config.key_path.display(), algorithm, e
+            )))?;
+        match CertifiedKey::new(chain.clone(), signing_key).keys_match() {
+            Ok(()) | Err(TlsError::InconsistentKeys(InconsistentKeys::Unknown)) => {}
+            Err(e) => {
+                return Err(CertError::KeyMismatch(format!(
+                    "{} / {}: {}", cert_config.cert_path.display(), cert_config.key_path.display(), e
+                )));
+            }
+        }
+
+        // 证书链检查：每一级的签发者必须是下一级证书的主题
+        for pair in parsed.windows(2) {
+            if pair[0].issuer() != pair[1].subject() {
+                return Err(CertError::BrokenChain(format!(
+                    "{}: \"{}\" 的签发者为 \"{}\"，但下一级证书为 \"{}\"",
+                    cert_config.cert_path.display(), pair[0].subject(), pair[0].issuer(), pair[1].subject()
+                )));
+            }
+        }
+
+        // mTLS 客户端 CA 检查
+        if let Some(ca_path) = &cert_config.ca_path {
+            let ca_pem = std::fs::read(ca_path)
+                .map_err(|e| CertError::MissingClientCa(format!("{}: {}", ca_path.display(), e)))?;
+            let ca_certs = rustls_pemfile::certs(&mut ca_pem.as_slice())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| CertError::MissingClientCa(format!("{}: {}", ca_path.display(), e)))?;
+            let mut roots = rustls::RootCertStore::empty();
+            let (added, _) = roots.add_parsable_certificates(ca_certs);
+            if added == 0 {
+                return Err(CertError::MissingClientCa(format!("{}: 未包含可用的 CA 证书", ca_path.display())));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 获取已配置证书中最早的到期时间（Unix 时间戳，秒）
+    pub fn earliest_expiry(&self) -> Option<i64> {
+        [&self.config.shared_cert, &self.config.grpc_cert, &self.config.http_cert]
+            .into_iter()
+            .flatten()
+            .filter_map(|c| super::renewal::cert_not_after(&c.cert_path).ok())
+            .min()
+    }
+}
+
+/// 根据证书公钥信息返回密钥算法名称
+///
+/// ECDSA 证书会进一步区分曲线，便于在日志和错误信息中准确报告
+pub fn key_algorithm_name(spki: &x509_parser::x509::SubjectPublicKeyInfo) -> &'static str {
+    use x509_parser::oid_registry::{
+        OID_EC_P256, OID_KEY_TYPE_EC_PUBLIC_KEY, OID_NIST_EC_P384, OID_NIST_EC_P521,
+        OID_PKCS1_RSAENCRYPTION, OID_SIG_ED25519,
+    };
+
+    let algorithm = &spki.algorithm.algorithm;
+    if *algorithm == OID_PKCS1_RSAENCRYPTION {
+        "RSA"
+    } else if *algorithm == OID_SIG_ED25519 {
+        "Ed25519"
+    } else if *algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY {
+        let curve = spki.algorithm.parameters.as_ref().and_then(|p| p.as_oid().ok());
+        match curve {
+            Some(oid) if oid == OID_EC_P256 => "ECDSA P-256",
+            Some(oid) if oid == OID_NIST_EC_P384 => "ECDSA P-384",
+            Some(oid) if oid == OID_NIST_EC_P521 => "ECDSA P-521",
+            _ => "ECDSA（未知曲线）",
+        }
+    } else {
+        "未知算法"
+    }
 }
 
 // ============ mTLS 相关接口 ============
@@ -226,6 +430,98 @@ mod tests {
         manager.get_grpc_server_config();
     }
 
+    fn write_pem(dir: &std::path::Path, name: &str, pem: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, pem).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_validate_cert_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert_a = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_b = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = write_pem(dir.path(), "a.pem", &cert_a.serialize_pem().unwrap());
+        let key_path = write_pem(dir.path(), "a-key.pem", &cert_a.serialize_private_key_pem());
+        let other_key_path = write_pem(dir.path(), "b-key.pem", &cert_b.serialize_private_key_pem());
+        let empty_ca_path = write_pem(dir.path(), "ca.pem", "");
+
+        let ok = CertificateManager {
+            config: CertManagerConfig::shared(CertConfig::from_paths(&cert_path, &key_path)),
+            grpc_manager: None,
+            http_manager: None,
+            shared_manager: None,
+        };
+        assert_eq!(ok.validate(), Ok(()));
+
+        let mismatch = CertificateManager {
+            config: CertManagerConfig::shared(CertConfig::from_paths(&cert_path, &other_key_path)),
+            ..ok
+        };
+        assert!(matches!(mismatch.validate(), Err(CertError::KeyMismatch(_))));
+
+        let no_ca = CertificateManager {
+            config: CertManagerConfig::shared(CertConfig::from_paths(&cert_path, &key_path).with_ca(&empty_ca_path)),
+            ..mismatch
+        };
+        assert!(matches!(no_ca.validate(), Err(CertError::MissingClientCa(_))));
+
+        let missing = CertificateManager {
+            config: CertManagerConfig::shared(create_test_cert_config()),
+            ..no_ca
+        };
+        assert!(matches!(missing.validate(), Err(CertError::FileNotFound(_))));
+    }
+
+    #[test]
+    fn test_validate_accepts_p256_and_ed25519() {
+        let dir = tempfile::tempdir().unwrap();
+        for (alg, expected) in [(&rcgen::PKCS_ECDSA_P256_SHA256, "ECDSA P-256"), (&rcgen::PKCS_ED25519, "Ed25519")] {
+            let mut params = rcgen::CertificateParams::new(vec!["localhost".to_string()]);
+            params.alg = alg;
+            let cert = rcgen::Certificate::from_params(params).unwrap();
+            let der = cert.serialize_der().unwrap();
+            let (_, parsed) = x509_parser::parse_x509_certificate(&der).unwrap();
+            assert_eq!(key_algorithm_name(parsed.public_key()), expected);
+
+            let cert_path = write_pem(dir.path(), &format!("{}.pem", expected), &cert.serialize_pem().unwrap());
+            let key_path = write_pem(dir.path(), &format!("{}-key.pem", expected), &cert.serialize_private_key_pem());
+            let manager = CertificateManager::from_config(
+                CertManagerConfig::shared(CertConfig::from_paths(&cert_path, &key_path))
+            ).unwrap();
+            assert_eq!(manager.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_validate_alternate_cert() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut params = rcgen::CertificateParams::new(vec!["localhost".to_string()]);
+        params.alg = &rcgen::PKCS_ED25519;
+        let primary = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let alternate = rcgen::Certificate::from_params(params).unwrap();
+        let cert_path = write_pem(dir.path(), "primary.pem", &primary.serialize_pem().unwrap());
+        let key_path = write_pem(dir.path(), "primary-key.pem", &primary.serialize_private_key_pem());
+        let alt_cert_path = write_pem(dir.path(), "alt.pem", &alternate.serialize_pem().unwrap());
+        let alt_key_path = write_pem(dir.path(), "alt-key.pem", &alternate.serialize_private_key_pem());
+
+        let cert_config = CertConfig::from_paths(&cert_path, &key_path)
+            .with_domains(vec!["localhost".to_string()])
+            .with_alternate_cert(&alt_cert_path, &alt_key_path);
+        let manager = CertificateManager::from_config(CertManagerConfig::shared(cert_config)).unwrap();
+        assert_eq!(manager.validate(), Ok(()));
+
+        let mismatched = CertificateManager {
+            config: CertManagerConfig::shared(
+                CertConfig::from_paths(&cert_path, &key_path).with_alternate_cert(&alt_cert_path, &key_path)
+            ),
+            grpc_manager: None,
+            http_manager: None,
+            shared_manager: None,
+        };
+        assert!(matches!(mismatched.validate(), Err(CertError::KeyMismatch(_))));
+    }
+
     #[test]
     fn test_shared_mode() {
         let config = CertManagerConfig::shared(create_test_cert_config());
@@ -243,4 +539,111 @@ mod tests {
         assert!(config.grpc_cert.is_some());
         assert!(config.http_cert.is_some());
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_reload_during_handshake_flood() {
+        use rustls::pki_types::{CertificateDer, ServerName};
+
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let dir = tempfile::tempdir().unwrap();
+        let certs = [
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap(),
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap(),
+        ];
+        let cert_path = write_pem(dir.path(), "cert.pem", &certs[0].serialize_pem().unwrap());
+        let key_path = write_pem(dir.path(), "key.pem", &certs[0].serialize_private_key_pem());
+
+        let manager = Arc::new(RwLock::new(CertificateManager::from_config(
+            CertManagerConfig::shared(CertConfig::from_paths(&cert_path, &key_path))
+        ).unwrap()));
+
+        // 客户端同时信任新旧证书，握手失败只可能来自替换过程本身
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in &certs {
+            roots.add(CertificateDer::from(cert.serialize_der().unwrap())).unwrap();
+        }
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(
+            rustls::ClientConfig::builder().with_root_certificates(roots).with_no_client_auth()
+        ));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server_manager = manager.clone();
+        let server = tokio::spawn(async move {
+            let mut handshakes = Vec::new();
+            for _ in 0..200 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let config = server_manager.read().unwrap().get_http_server_config().unwrap();
+                handshakes.push(tokio::spawn(async move {
+                    tokio_rustls::TlsAcceptor::from(config).accept(stream).await.map(|_| ())
+                }));
+            }
+            handshakes
+        });
+
+        let rotation_manager = manager.clone();
+        let rotation = tokio::spawn(async move {
+            for i in 1..=50 {
+                let cert = &certs[i % 2];
+                std::fs::write(&cert_path, cert.serialize_pem().unwrap()).unwrap();
+                std::fs::write(&key_path, cert.serialize_private_key_pem()).unwrap();
+                CertificateManager::reload_shared(&rotation_manager).unwrap();
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let clients: Vec<_> = (0..200).map(|_| {
+            let connector = connector.clone();
+            tokio::spawn(async move {
+                let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+                connector.connect(ServerName::try_from("localhost").unwrap(), stream).await.map(|_| ())
+            })
+        }).collect();
+
+        for client in clients {
+            client.await.unwrap().expect("证书替换期间客户端握手失败");
+        }
+        for handshake in server.await.unwrap() {
+            handshake.await.unwrap().expect("证书替换期间服务端握手失败");
+        }
+        rotation.await.unwrap();
+    }
+
+    #[test]
+    fn test_custom_alpn_protocols() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = write_pem(dir.path(), "cert.pem", &cert.serialize_pem().unwrap());
+        let key_path = write_pem(dir.path(), "key.pem", &cert.serialize_private_key_pem());
+        let cert_config = CertConfig::from_paths(&cert_path, &key_path).with_domains(vec!["localhost".to_string()]);
+
+        let default = CertificateManager::from_config(CertManagerConfig::shared(cert_config.clone())).unwrap();
+        assert_eq!(default.get_grpc_server_config().alpn_protocols, vec![b"h2".to_vec(), b"http/1.1".to_vec()]);
+
+        let config = CertManagerConfig::shared(cert_config.clone()).with_alpn_protocols(vec!["h2".to_string()]);
+        let mut manager = CertificateManager::from_config(config).unwrap();
+        assert_eq!(manager.get_grpc_server_config().alpn_protocols, vec![b"h2".to_vec()]);
+
+        // 替换后的列表在重新加载后仍然生效
+        manager.set_alpn_protocols(vec!["http/1.1".to_string(), "h2".to_string()]).unwrap();
+        manager.reload().unwrap();
+        assert_eq!(manager.get_grpc_server_config().alpn_protocols, vec![b"http/1.1".to_vec(), b"h2".to_vec()]);
+
+        // 服务器无法处理的协议、空列表和重复项都会被拒绝，原配置保持不变
+        assert!(manager.set_alpn_protocols(vec!["h3".to_string()]).is_err());
+        assert!(manager.set_alpn_protocols(Vec::new()).is_err());
+        assert!(manager.set_alpn_protocols(vec!["h2".to_string(), "h2".to_string()]).is_err());
+        assert_eq!(manager.get_grpc_server_config().alpn_protocols, vec![b"http/1.1".to_vec(), b"h2".to_vec()]);
+
+        // HTTP/1.0 可以显式声明
+        manager.set_alpn_protocols(vec!["h2".to_string(), "http/1.1".to_string(), "http/1.0".to_string()]).unwrap();
+        assert_eq!(
+            manager.get_grpc_server_config().alpn_protocols,
+            vec![b"h2".to_vec(), b"http/1.1".to_vec(), b"http/1.0".to_vec()]
+        );
+        assert!(CertificateManager::from_config(
+            CertManagerConfig::shared(cert_config).with_alpn_protocols(vec!["spdy/3".to_string()])
+        ).is_err());
+    }
 }
@@ -4,9 +4,13 @@
 //! 强制使用 ring 作为加密后端
 
 pub mod config;
+pub mod error;
 pub mod rustls_cert;
 pub mod manager;
+pub mod renewal;
 
-pub use config::{CertManagerConfig, CertConfig};
+pub use config::{CertManagerConfig, CertConfig, SUPPORTED_ALPN_PROTOCOLS, validate_alpn_protocols};
+pub use error::CertError;
 pub use rustls_cert::RustlsCertManager;
-pub use manager::CertificateManager;
+pub use manager::{CertificateManager, key_algorithm_name};
+pub use renewal::{CertRenewalConfig, CertRenewalHook, spawn_renewal_task};
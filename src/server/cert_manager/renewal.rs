@@ -0,0 +1,298 @@
+//! 证书后台续期
+//!
+//! 定期检查证书到期时间，进入续期窗口后调用调用方提供的续期钩子
+//! 签发新证书，失败时按指数退避重试；成功后重新加载证书并热替换
+//! ServerConfig，新的 TLS 握手立即使用新证书，无需重启服务。
+
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use super::config::CertManagerConfig;
+use super::manager::CertificateManager;
+use crate::server::graceful_shutdown::ShutdownSignal;
+use crate::utils::logger::{info, warn, error};
+
+/// 续期钩子
+///
+/// 接收当前证书配置，负责把新证书写入配置中的 `cert_path` / `key_path`。
+/// 签发方式由调用方决定，引擎本身不内置 ACME 客户端。
+/// 返回 `Err` 视为临时失败，会按退避策略重试。
+pub type CertRenewalHook = Arc<
+    dyn Fn(CertManagerConfig) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// 证书续期配置
+#[derive(Debug, Clone)]
+pub struct CertRenewalConfig {
+    /// 检查间隔
+    pub check_interval: Duration,
+    /// 距离到期多久开始续期
+    pub renew_before: Duration,
+    /// 单次续期尝试的超时时间
+    pub attempt_timeout: Duration,
+    /// 最大重试次数（不含首次尝试）
+    pub max_retries: u32,
+    /// 首次重试前的等待时间
+    pub initial_backoff: Duration,
+    /// 退避等待时间上限
+    pub max_backoff: Duration,
+}
+
+impl Default for CertRenewalConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(12 * 60 * 60),
+            renew_before: Duration::from_secs(30 * 24 * 60 * 60),
+            attempt_timeout: Duration::from_secs(300),
+            max_retries: 5,
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+impl CertRenewalConfig {
+    /// 设置检查间隔
+    pub fn with_check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    /// 设置续期提前量（按天）
+    pub fn with_renewal_days(mut self, days: u64) -> Self {
+        self.renew_before = Duration::from_secs(days * 24 * 60 * 60);
+        self
+    }
+
+    /// 设置单次尝试超时
+    pub fn with_attempt_timeout(mut self, timeout: Duration) -> Self {
+        self.attempt_timeout = timeout;
+        self
+    }
+
+    /// 设置重试策略
+    pub fn with_retry(mut self, max_retries: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff.max(initial_backoff);
+        self
+    }
+
+    /// 计算第 `attempt` 次重试前的等待时间（从 0 开始，指数退避并封顶）
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt.min(31)).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+/// 读取证书文件中第一张证书的到期时间（Unix 时间戳，秒）
+pub fn cert_not_after(cert_path: &Path) -> Result<i64, String> {
+    let pem_data = std::fs::read(cert_path)
+        .map_err(|e| format!("读取证书文件失败: {}", e))?;
+    let (_, pem) = x509_parser::pem::parse_x509_pem(&pem_data)
+        .map_err(|e| format!("解析证书 PEM 失败: {:?}", e))?;
+    let cert = pem.parse_x509()
+        .map_err(|e| format!("解析 X.509 证书失败: {:?}", e))?;
+    Ok(cert.validity().not_after.timestamp())
+}
+
+/// 启动证书续期后台任务
+///
+/// - `hook` 为 `None` 时只从磁盘重新加载证书（适用于由外部工具续期的场景）
+/// - 重新加载后最早到期时间延后才算续期成功，否则按退避策略重试
+/// - 证书管理器通过写锁原地替换，已建立的连接不受影响
+/// - `shutdown` 变为 `true` 时任务结束，等待检查周期、退避和续期钩子时同样会立即退出
+pub fn spawn_renewal_task(
+    cert_manager: Arc<RwLock<CertificateManager>>,
+    config: CertRenewalConfig,
+    hook: Option<CertRenewalHook>,
+    shutdown: watch::Receiver<bool>,
+) -> tokio::task::JoinHandle<()> {
+    let stopped = ShutdownSignal::new(shutdown).requested();
+    tokio::spawn(async move {
+        info!("🔄 证书续期任务已启动，检查间隔: {:?}，提前续期: {:?}",
+            config.check_interval, config.renew_before);
+        tokio::pin!(stopped);
+
+        loop {
+            let expiry = match cert_manager.read() {
+                Ok(guard) => guard.earliest_expiry(),
+                Err(_) => {
+                    error!("❌ 证书管理器锁已损坏，续期任务退出");
+                    return;
+                }
+            };
+
+            let now = chrono::Utc::now().timestamp();
+            let due = match expiry {
+                Some(not_after) => not_after - now <= config.renew_before.as_secs() as i64,
+                None => {
+                    warn!("⚠️ 无法读取证书到期时间，跳过本轮续期检查");
+                    false
+                }
+            };
+
+            if due {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = renew_with_retry(&cert_manager, &config, hook.as_ref()) => {}
+                }
+            }
+
+            tokio::select! {
+                _ = &mut stopped => break,
+                _ = tokio::time::sleep(config.check_interval) => {}
+            }
+        }
+
+        info!("🛑 证书续期任务已停止");
+    })
+}
+
+/// 执行一次续期（带超时与指数退避重试）
+async fn renew_with_retry(
+    cert_manager: &Arc<RwLock<CertificateManager>>,
+    config: &CertRenewalConfig,
+    hook: Option<&CertRenewalHook>,
+) -> bool {
+    let (cert_config, previous_expiry) = match cert_manager.read() {
+        Ok(guard) => (guard.get_config().clone(), guard.earliest_expiry()),
+        Err(_) => return false,
+    };
+
+    for attempt in 0..=config.max_retries {
+        if attempt > 0 {
+            let backoff = config.backoff_for(attempt - 1);
+            warn!("⏳ 证书续期第 {} 次重试，等待 {:?}", attempt, backoff);
+            tokio::time::sleep(backoff).await;
+        }
+
+        let issued = match hook {
+            Some(hook) => match tokio::time::timeout(config.attempt_timeout, hook(cert_config.clone())).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("续期超时（{:?}）", config.attempt_timeout)),
+            },
+            None => Ok(()),
+        };
+
+        if let Err(e) = issued {
+            warn!("⚠️ 证书签发失败: {}", e);
+            continue;
+        }
+
+        if let Err(e) = CertificateManager::reload_shared(cert_manager) {
+            warn!("⚠️ 重新加载证书失败: {}", e);
+            continue;
+        }
+
+        // 磁盘上的证书没有更新时，重新加载只是换回同一张证书，不能算续期成功
+        let renewed_expiry = cert_manager.read().ok().and_then(|guard| guard.earliest_expiry());
+        match (previous_expiry, renewed_expiry) {
+            (_, None) => warn!("⚠️ 无法读取续期后的证书到期时间"),
+            (Some(previous), Some(renewed)) if renewed <= previous => {
+                warn!("⚠️ 证书到期时间没有延后（{}），续期未生效", renewed);
+            }
+            (_, Some(_)) => {
+                info!("✅ 证书续期成功，新握手将使用新证书");
+                return true;
+            }
+        }
+    }
+
+    error!("❌ 证书续期失败，已重试 {} 次，将在下一个检查周期继续尝试", config.max_retries);
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let config = CertRenewalConfig::default()
+            .with_retry(10, Duration::from_secs(1), Duration::from_secs(10));
+        assert_eq!(config.backoff_for(0), Duration::from_secs(1));
+        assert_eq!(config.backoff_for(1), Duration::from_secs(2));
+        assert_eq!(config.backoff_for(3), Duration::from_secs(8));
+        assert_eq!(config.backoff_for(4), Duration::from_secs(10));
+        assert_eq!(config.backoff_for(40), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_renewal_retries_then_swaps_server_config() {
+        use chrono::Datelike;
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use super::super::config::CertConfig;
+
+        tokio::time::pause();
+
+        let dir = tempfile::tempdir().unwrap();
+        let soon = chrono::Utc::now() + chrono::Duration::days(10);
+        let mut params = rcgen::CertificateParams::new(vec!["localhost".to_string()]);
+        params.not_after = rcgen::date_time_ymd(soon.year(), soon.month() as u8, soon.day() as u8);
+        let expiring = rcgen::Certificate::from_params(params).unwrap();
+        let renewed = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+        std::fs::write(&cert_path, expiring.serialize_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, expiring.serialize_private_key_pem()).unwrap();
+
+        let manager = Arc::new(RwLock::new(CertificateManager::from_config(
+            CertManagerConfig::shared(CertConfig::from_paths(&cert_path, &key_path))
+        ).unwrap()));
+        let old_expiry = manager.read().unwrap().earliest_expiry().unwrap();
+        let old_server_config = manager.read().unwrap().get_http_server_config().unwrap();
+
+        // 第一次签发失败，第二次写入新证书
+        let attempts = Arc::new(AtomicU32::new(0));
+        let hook_attempts = attempts.clone();
+        let renewed_pem = (renewed.serialize_pem().unwrap(), renewed.serialize_private_key_pem());
+        let hook: CertRenewalHook = Arc::new(
+            move |config: CertManagerConfig| -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> {
+                let attempt = hook_attempts.fetch_add(1, Ordering::SeqCst);
+                let (cert_pem, key_pem) = renewed_pem.clone();
+                Box::pin(async move {
+                    if attempt == 0 {
+                        return Err("DNS 暂时不可用".to_string());
+                    }
+                    let cert = config.shared_cert.ok_or("缺少证书配置")?;
+                    std::fs::write(&cert.cert_path, cert_pem).map_err(|e| e.to_string())?;
+                    std::fs::write(&cert.key_path, key_pem).map_err(|e| e.to_string())?;
+                    Ok(())
+                })
+            },
+        );
+
+        let config = CertRenewalConfig::default()
+            .with_check_interval(Duration::from_secs(60 * 60))
+            .with_retry(3, Duration::from_secs(30), Duration::from_secs(60));
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let task = spawn_renewal_task(manager.clone(), config, Some(hook), shutdown_rx);
+
+        // 证书 10 天后到期，首次检查即进入 30 天的续期窗口；失败后退避 30 秒重试
+        tokio::time::sleep(Duration::from_secs(31)).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        let new_expiry = manager.read().unwrap().earliest_expiry().unwrap();
+        assert!(new_expiry > old_expiry);
+        let new_server_config = manager.read().unwrap().get_http_server_config().unwrap();
+        assert!(!Arc::ptr_eq(&old_server_config, &new_server_config));
+
+        // 新证书不在续期窗口内，之后的检查不再调用钩子
+        tokio::time::sleep(Duration::from_secs(3 * 60 * 60)).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        shutdown_tx.send_replace(true);
+        task.await.unwrap();
+    }
+}
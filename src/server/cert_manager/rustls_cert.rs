@@ -9,20 +9,21 @@ use std::io::BufReader;
 use std::path::Path;
 use std::sync::Arc;
 
-use rustls::server::{ServerConfig, ResolvesServerCertUsingSni, WebPkiClientVerifier};
+use rustls::server::{ServerConfig, ClientHello, ResolvesServerCert, ResolvesServerCertUsingSni, WebPkiClientVerifier};
 use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, PrivatePkcs8KeyDer};
+use rustls::SignatureScheme;
 use rustls::sign::CertifiedKey;
 use rustls::crypto::CryptoProvider;
 use rustls_pemfile::{certs, private_key};
 use rustls::RootCertStore;
 
-use crate::utils::logger::{info, error};
+use crate::utils::logger::{info, debug, error};
 
 /// Rustls 证书管理器
 #[derive(Clone)]
 pub struct RustlsCertManager {
     /// SNI 解析器
-    sni_resolver: Arc<ResolvesServerCertUsingSni>,
+    sni_resolver: Arc<dyn ResolvesServerCert>,
     /// ServerConfig
     server_config: Arc<ServerConfig>,
     /// 支持的域名列表
@@ -35,39 +36,10 @@ impl RustlsCertManager {
         // 获取 provider（直接使用，不关心是否重复安装）
         let provider = rustls::crypto::ring::default_provider();
 
-        println!("加载证书: {}", cert_config.cert_path.display());
-
-        // 创建 SNI 解析器
-        let mut sni_resolver = ResolvesServerCertUsingSni::new();
+        info!("📜 加载证书: {}", cert_config.cert_path.display());
 
         // 读取证书和私钥
-        let cert_file = File::open(&cert_config.cert_path)
-            .map_err(|e| format!("打开证书文件失败: {}", e))?;
-        let mut cert_reader = BufReader::new(cert_file);
-        let certs: Vec<CertificateDer<'static>> = certs(&mut cert_reader)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("解析证书失败: {}", e))?
-            .into_iter()
-            .map(|cert| CertificateDer::from(cert.into_owned()))
-            .collect();
-
-        let key_file = File::open(&cert_config.key_path)
-            .map_err(|e| format!("打开私钥文件失败: {}", e))?;
-        let mut key_reader = BufReader::new(key_file);
-        let key = private_key(&mut key_reader)
-            .map_err(|e| format!("解析私钥失败: {}", e))?
-            .ok_or("私钥文件为空")?;
-
-        let static_key = PrivateKeyDer::from(key);
-
-        // 验证证书
-        if certs.is_empty() {
-            return Err("证书为空".to_string());
-        }
-
-        // 创建 CertifiedKey
-        let certified_key = CertifiedKey::from_der(certs, static_key, &provider)
-            .map_err(|e| format!("创建 CertifiedKey 失败: {:?}", e))?;
+        let certified_key = Self::load_certified_key(&cert_config.cert_path, &cert_config.key_path, &provider)?;
 
         // 确定域名（优先使用配置中的域名，否则从证书提取）
         let domains = if cert_config.domains.is_empty() {
@@ -77,15 +49,26 @@ impl RustlsCertManager {
             cert_config.domains.clone()
         };
 
-        // 为每个域名添加证书
-        for domain in &domains {
-            sni_resolver.add(domain, certified_key.clone())
-                .map_err(|e| format!("添加证书到 SNI 解析器失败: {:?}", e))?;
-            println!("  ✓ 域名: {}", domain);
-        }
+        // 为每个域名添加证书；配置了备用证书时使用双证书解析器
+        let sni_resolver_arc: Arc<dyn ResolvesServerCert> = if let Some((alt_cert_path, alt_key_path)) = &cert_config.alternate_cert {
+            info!("📜 加载备用证书: {}", alt_cert_path.display());
+            let alternate_key = Self::load_certified_key(alt_cert_path, alt_key_path, &provider)?;
 
-        // 创建 ServerConfig，ALPN 只支持 h2
-        let sni_resolver_arc = Arc::new(sni_resolver);
+            let mut dual_resolver = DualCertResolver::default();
+            for domain in &domains {
+                dual_resolver.add(domain, vec![Arc::new(certified_key.clone()), Arc::new(alternate_key.clone())]);
+                debug!("  ✓ 域名: {}（双证书）", domain);
+            }
+            Arc::new(dual_resolver)
+        } else {
+            let mut sni_resolver = ResolvesServerCertUsingSni::new();
+            for domain in &domains {
+                sni_resolver.add(domain, certified_key.clone())
+                    .map_err(|e| format!("添加证书到 SNI 解析器失败: {:?}", e))?;
+                debug!("  ✓ 域名: {}", domain);
+            }
+            Arc::new(sni_resolver)
+        };
 
         // 根据是否配置了 CA 证书决定是否启用 mTLS
         let server_config = if let Some(ca_path) = &cert_config.ca_path {
@@ -139,7 +122,7 @@ impl RustlsCertManager {
         // 设置 ALPN 协议，同时支持 HTTP/2 和 HTTP/1.1
         // 这样 TLS 握手能成功完成，然后在应用层检查并拒绝非 HTTP/2
         let mut server_config = server_config;
-        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        server_config.alpn_protocols = super::config::default_alpn_protocols();
 
         let server_config = Arc::new(server_config);
 
@@ -152,6 +135,36 @@ impl RustlsCertManager {
         })
     }
 
+    /// 读取证书链和私钥并创建 CertifiedKey
+    fn load_certified_key(cert_path: &Path, key_path: &Path, provider: &CryptoProvider) -> Result<CertifiedKey, String> {
+        let cert_file = File::open(cert_path)
+            .map_err(|e| format!("打开证书文件失败: {}", e))?;
+        let mut cert_reader = BufReader::new(cert_file);
+        let certs: Vec<CertificateDer<'static>> = certs(&mut cert_reader)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("解析证书失败: {}", e))?
+            .into_iter()
+            .map(|cert| CertificateDer::from(cert.into_owned()))
+            .collect();
+
+        let key_file = File::open(key_path)
+            .map_err(|e| format!("打开私钥文件失败: {}", e))?;
+        let mut key_reader = BufReader::new(key_file);
+        let key = private_key(&mut key_reader)
+            .map_err(|e| format!("解析私钥失败: {}", e))?
+            .ok_or("私钥文件为空")?;
+
+        let static_key = PrivateKeyDer::from(key);
+
+        // 验证证书
+        if certs.is_empty() {
+            return Err("证书为空".to_string());
+        }
+
+        CertifiedKey::from_der(certs, static_key, provider)
+            .map_err(|e| format!("创建 CertifiedKey 失败: {:?}", e))
+    }
+
     /// 从证书目录加载所有证书（格式：domain.pem, domain-key.pem）
     pub fn from_dir(cert_dir: &str) -> Result<Self, String> {
         let path = Path::new(cert_dir);
@@ -259,7 +272,7 @@ impl RustlsCertManager {
 
         // 设置 ALPN 协议，同时支持 HTTP/2 和 HTTP/1.1
         // 这样 TLS 握手能成功完成，然后在应用层检查并拒绝非 HTTP/2
-        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        server_config.alpn_protocols = super::config::default_alpn_protocols();
 
         let server_config = Arc::new(server_config);
 
@@ -270,6 +283,15 @@ impl RustlsCertManager {
         })
     }
 
+    /// 使用新的 ALPN 协议列表重建 ServerConfig
+    ///
+    /// 证书解析器保持不变，已持有旧 ServerConfig 的连接不受影响
+    pub fn recreate_server_config_with_alpn(&mut self, alpn_protocols: Vec<Vec<u8>>) {
+        let mut server_config = (*self.server_config).clone();
+        server_config.alpn_protocols = alpn_protocols;
+        self.server_config = Arc::new(server_config);
+    }
+
     /// 获取 ServerConfig
     pub fn get_server_config(&self) -> Arc<ServerConfig> {
         self.server_config.clone()
@@ -286,6 +308,37 @@ impl RustlsCertManager {
     }
 }
 
+/// 双证书解析器
+///
+/// 每个域名对应按优先级排列的多张证书（如 ECDSA + RSA），
+/// 握手时选择第一张与客户端签名算法兼容的证书；都不兼容时返回第一张，由握手报告错误
+#[derive(Debug, Default)]
+struct DualCertResolver {
+    by_name: HashMap<String, Vec<Arc<CertifiedKey>>>,
+}
+
+impl DualCertResolver {
+    fn add(&mut self, name: &str, keys: Vec<Arc<CertifiedKey>>) {
+        self.by_name.insert(name.to_ascii_lowercase(), keys);
+    }
+}
+
+impl ResolvesServerCert for DualCertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let name = client_hello.server_name()?.to_ascii_lowercase();
+        let keys = self.by_name.get(&name)?;
+        select_certified_key(keys, client_hello.signature_schemes())
+    }
+}
+
+/// 从候选证书中选择第一张支持客户端签名算法的证书
+fn select_certified_key(keys: &[Arc<CertifiedKey>], offered: &[SignatureScheme]) -> Option<Arc<CertifiedKey>> {
+    keys.iter()
+        .find(|key| key.key.choose_scheme(offered).is_some())
+        .or_else(|| keys.first())
+        .cloned()
+}
+
 /// ALPN 协议检查工具
 pub struct AlpnProtocol;
 
@@ -295,9 +348,9 @@ impl AlpnProtocol {
         matches!(protocol, Some(p) if p == b"h2")
     }
 
-    /// 检查是否为 HTTP/1.1 连接
+    /// 检查是否为 HTTP/1.x 连接
     pub fn is_http11(protocol: &Option<Vec<u8>>) -> bool {
-        matches!(protocol, Some(p) if p == b"http/1.1")
+        matches!(protocol, Some(p) if p == b"http/1.1" || p == b"http/1.0")
             || protocol.is_none()
     }
 }
@@ -306,6 +359,28 @@ impl AlpnProtocol {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_select_certified_key_by_signature_scheme() {
+        let provider = rustls::crypto::ring::default_provider();
+        let make_key = |alg: &'static rcgen::SignatureAlgorithm| {
+            let mut params = rcgen::CertificateParams::new(vec!["localhost".to_string()]);
+            params.alg = alg;
+            let cert = rcgen::Certificate::from_params(params).unwrap();
+            let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.serialize_private_key_der()));
+            Arc::new(CertifiedKey::from_der(vec![CertificateDer::from(cert.serialize_der().unwrap())], key, &provider).unwrap())
+        };
+        let ecdsa = make_key(&rcgen::PKCS_ECDSA_P256_SHA256);
+        let ed25519 = make_key(&rcgen::PKCS_ED25519);
+        let keys = vec![ecdsa.clone(), ed25519.clone()];
+
+        let chosen = select_certified_key(&keys, &[SignatureScheme::ECDSA_NISTP256_SHA256]).unwrap();
+        assert!(Arc::ptr_eq(&chosen, &ecdsa));
+        let chosen = select_certified_key(&keys, &[SignatureScheme::ED25519]).unwrap();
+        assert!(Arc::ptr_eq(&chosen, &ed25519));
+        let chosen = select_certified_key(&keys, &[SignatureScheme::RSA_PSS_SHA256]).unwrap();
+        assert!(Arc::ptr_eq(&chosen, &ecdsa));
+    }
+
     #[test]
     fn test_alpn_check() {
         assert!(AlpnProtocol::is_http2(&Some(b"h2".to_vec())));
@@ -0,0 +1,113 @@
+//! 客户端断开信号
+//!
+//! 路由器在开始处理请求时为其创建 [`ClientDisconnect`]，处理器可以等待该信号，
+//! 在客户端提前离开（HTTP/2 流被重置、连接关闭导致处理被丢弃）时停止耗时的工作：
+//!
+//! ```ignore
+//! router.add_route(Method::GET, "/report", |req| {
+//!     Box::pin(async move {
+//!         tokio::select! {
+//!             report = build_report() => Ok(report),
+//!             _ = req.client_disconnect.disconnected() => Ok(Response::new(Full::new(Bytes::new()))),
+//!         }
+//!     })
+//! });
+//! ```
+//!
+//! 请求在响应产生前被放弃时还会计入 `client_disconnects` 指标，与正常完成的请求区分开。
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::engine::metrics::AtomicMetrics;
+
+/// 客户端断开信号，克隆后共享同一状态
+#[derive(Debug, Clone, Default)]
+pub struct ClientDisconnect {
+    state: Option<watch::Receiver<bool>>,
+}
+
+impl ClientDisconnect {
+    /// 客户端是否已在响应产生前断开
+    pub fn is_disconnected(&self) -> bool {
+        self.state.as_ref().map(|state| *state.borrow()).unwrap_or(false)
+    }
+
+    /// 等待客户端断开
+    ///
+    /// 请求正常完成或请求未经过路由器时永远不会返回
+    pub async fn disconnected(&self) {
+        if let Some(state) = &self.state {
+            let mut state = state.clone();
+            if state.wait_for(|disconnected| *disconnected).await.is_ok() {
+                return;
+            }
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+/// 断开守卫
+///
+/// 随请求处理的 future 一起持有；处理完成前被丢弃即视为客户端已断开
+pub(crate) struct DisconnectGuard {
+    state: watch::Sender<bool>,
+    metrics: Option<Arc<AtomicMetrics>>,
+    completed: bool,
+}
+
+impl DisconnectGuard {
+    /// 创建守卫和交给处理器的信号
+    pub(crate) fn new(metrics: Option<Arc<AtomicMetrics>>) -> (Self, ClientDisconnect) {
+        let (state, receiver) = watch::channel(false);
+        let guard = Self { state, metrics, completed: false };
+        (guard, ClientDisconnect { state: Some(receiver) })
+    }
+
+    /// 处理已经产生响应
+    pub(crate) fn complete(mut self) {
+        self.completed = true;
+    }
+}
+
+impl Drop for DisconnectGuard {
+    fn drop(&mut self) {
+        // panic 展开不是客户端断开
+        if self.completed || std::thread::panicking() {
+            return;
+        }
+        self.state.send_replace(true);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_client_disconnect();
+        }
+        crate::utils::logger::debug!("🔌 [Router] 客户端在响应产生前断开，已通知处理器");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_signal_fires_only_when_abandoned() {
+        let metrics = Arc::new(AtomicMetrics::new());
+
+        let (guard, signal) = DisconnectGuard::new(Some(metrics.clone()));
+        guard.complete();
+        assert!(!signal.is_disconnected());
+        assert!(tokio::time::timeout(Duration::from_millis(20), signal.disconnected()).await.is_err());
+        assert_eq!(metrics.client_disconnects(), 0);
+
+        let (guard, signal) = DisconnectGuard::new(Some(metrics.clone()));
+        let waiter = tokio::spawn({
+            let signal = signal.clone();
+            async move { signal.disconnected().await }
+        });
+        drop(guard);
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert!(signal.is_disconnected());
+        assert_eq!(metrics.client_disconnects(), 1);
+    }
+}
@@ -0,0 +1,128 @@
+//! 103 Early Hints 支持
+//!
+//! 处理器在生成最终响应前调用 [`send`]（或 `HttpRequest::early_hints`）提交提示头部，
+//! 通常是 `Link: </style.css>; rel=preload; as=style`，让浏览器提前获取关键资源。
+//!
+//! 提示通过任务本地存储传递给当前连接：
+//! - 能够写出中间响应的连接（引擎的 HTTP/1.1 原始连接路径）立即发送 `103 Early Hints`
+//! - 其他路径（hyper 和 h2 目前都不支持服务端发送任意 1xx 响应）以及 HTTP/1.0 请求
+//!   将 `Link` 头部合并到最终响应，浏览器同样会据此预加载
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use hyper::header::{HeaderMap, LINK};
+use tokio::sync::mpsc::UnboundedSender;
+
+tokio::task_local! {
+    static EARLY_HINTS: EarlyHintsSink;
+}
+
+/// 提示的去向
+enum EarlyHintsSink {
+    /// 连接可以写出 103 中间响应
+    Interim(UnboundedSender<HeaderMap>),
+    /// 收集后合并到最终响应
+    Deferred(Arc<Mutex<HeaderMap>>),
+}
+
+/// 提交 Early Hints
+///
+/// 返回 `false` 表示当前不在请求处理作用域内，提示被丢弃
+pub fn send(headers: HeaderMap) -> bool {
+    EARLY_HINTS.try_with(|sink| match sink {
+        EarlyHintsSink::Interim(tx) => tx.send(headers).is_ok(),
+        EarlyHintsSink::Deferred(collected) => {
+            if let Ok(mut collected) = collected.lock() {
+                for (name, value) in headers.iter() {
+                    collected.append(name.clone(), value.clone());
+                }
+            }
+            true
+        }
+    }).unwrap_or(false)
+}
+
+/// 在可以写出 103 中间响应的作用域中运行请求处理
+pub async fn scope_interim<F: Future>(tx: UnboundedSender<HeaderMap>, future: F) -> F::Output {
+    EARLY_HINTS.scope(EarlyHintsSink::Interim(tx), future).await
+}
+
+/// 在收集提示的作用域中运行请求处理，返回处理结果和收集到的提示
+///
+/// 已处于其他作用域中时直接运行，不重复收集
+pub async fn scope_deferred<F: Future>(future: F) -> (F::Output, HeaderMap) {
+    if EARLY_HINTS.try_with(|_| ()).is_ok() {
+        return (future.await, HeaderMap::new());
+    }
+
+    let collected = Arc::new(Mutex::new(HeaderMap::new()));
+    let output = EARLY_HINTS.scope(EarlyHintsSink::Deferred(collected.clone()), future).await;
+    let hints = collected.lock().map(|mut h| std::mem::take(&mut *h)).unwrap_or_default();
+    (output, hints)
+}
+
+/// 将收集到的 `Link` 提示合并到最终响应头（已存在的相同值不重复添加）
+pub fn merge_links(headers: &mut HeaderMap, hints: &HeaderMap) {
+    for value in hints.get_all(LINK) {
+        if !headers.get_all(LINK).iter().any(|existing| existing == value) {
+            headers.append(LINK, value.clone());
+        }
+    }
+}
+
+/// 编码 HTTP/1.1 的 103 中间响应
+pub fn encode_http1(headers: &HeaderMap) -> Vec<u8> {
+    let mut buf = b"HTTP/1.1 103 Early Hints\r\n".to_vec();
+    for (name, value) in headers.iter() {
+        buf.extend_from_slice(name.as_str().as_bytes());
+        buf.extend_from_slice(b": ");
+        buf.extend_from_slice(value.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+    }
+    buf.extend_from_slice(b"\r\n");
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn preload(path: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, HeaderValue::from_static(path));
+        headers
+    }
+
+    #[test]
+    fn test_send_outside_scope() {
+        assert!(!send(preload("</a.css>; rel=preload; as=style")));
+    }
+
+    #[tokio::test]
+    async fn test_interim_scope() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        scope_interim(tx, async {
+            assert!(send(preload("</a.css>; rel=preload; as=style")));
+        }).await;
+
+        let hints = rx.recv().await.unwrap();
+        assert_eq!(
+            encode_http1(&hints),
+            b"HTTP/1.1 103 Early Hints\r\nlink: </a.css>; rel=preload; as=style\r\n\r\n".to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deferred_scope_merges_links() {
+        let (_, hints) = scope_deferred(async {
+            send(preload("</a.css>; rel=preload; as=style"));
+            send(preload("</b.js>; rel=preload; as=script"));
+        }).await;
+
+        let mut headers = preload("</a.css>; rel=preload; as=style");
+        merge_links(&mut headers, &hints);
+        assert_eq!(headers.get_all(LINK).iter().count(), 2);
+    }
+}
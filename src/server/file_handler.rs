@@ -9,6 +9,8 @@
 //! 4. MIME 类型自动检测
 //! 5. 缓存控制和 ETag 支持
 //! 6. 范围请求支持 (HTTP Range)
+//! 7. 预压缩文件 - 直接返回 `.br`/`.zst`/`.gz` 同名文件
+//! 8. 内容嗅探 - 扩展名未知时按文件头识别常见类型（需显式启用）
 
 use hyper::{Request, Response, StatusCode, HeaderMap};
 use hyper::body::{Incoming, Bytes};
@@ -70,6 +72,16 @@ static MIME_TYPES: &[(&str, &str)] = &[
     ("gz", "application/gzip"),
 ];
 
+/// 预压缩文件的编码和扩展名，按优先级排列
+static PRECOMPRESSED_VARIANTS: &[(&str, &str)] = &[
+    ("br", "br"),
+    ("zstd", "zst"),
+    ("gzip", "gz"),
+];
+
+/// 内容嗅探读取的文件头字节数
+const SNIFF_LEN: usize = 512;
+
 /// 文件处理器配置
 #[derive(Debug, Clone)]
 pub struct FileHandlerConfig {
@@ -85,6 +97,10 @@ pub struct FileHandlerConfig {
     pub default_cache_time: u32,
     /// 分块大小 (用于大文件流式传输)
     pub chunk_size: usize,
+    /// 优先返回预压缩的同名文件（`app.js.br`、`app.js.zst`、`app.js.gz`）
+    pub precompressed: bool,
+    /// 扩展名未知时根据文件头嗅探 MIME 类型
+    pub sniff_content_type: bool,
 }
 
 impl Default for FileHandlerConfig {
@@ -96,10 +112,32 @@ impl Default for FileHandlerConfig {
             enable_cache_control: true,
             default_cache_time: 3600, // 1小时
             chunk_size: 64 * 1024, // 64KB
+            precompressed: false,
+            sniff_content_type: false,
         }
     }
 }
 
+impl FileHandlerConfig {
+    /// 启用或禁用预压缩文件
+    ///
+    /// 启用后，如果客户端的 `Accept-Encoding` 接受 br/zstd/gzip 且存在对应的同名文件，
+    /// 直接返回该文件并设置 `Content-Encoding`；否则返回原文件，由压缩中间件按需压缩
+    pub fn precompressed(mut self, enabled: bool) -> Self {
+        self.precompressed = enabled;
+        self
+    }
+    
+    /// 启用或禁用内容嗅探
+    ///
+    /// 扩展名仍是首选依据；只有扩展名缺失或不在映射表中时，才读取文件开头的字节识别
+    /// png/jpeg/gif/pdf/json/html，识别失败则不设置 `Content-Type`
+    pub fn sniff_content_type(mut self, enabled: bool) -> Self {
+        self.sniff_content_type = enabled;
+        self
+    }
+}
+
 /// 文件处理器
 pub struct FileHandler {
     config: FileHandlerConfig,
@@ -156,29 +194,49 @@ impl FileHandler {
                 .unwrap());
         }
         
+        // 范围请求始终针对原文件，不使用预压缩版本
+        let range_header = req.headers().get("range");
+        let variant = if self.config.precompressed && range_header.is_none() {
+            self.find_precompressed(&full_path, req).await
+        } else {
+            None
+        };
+        let (serve_path, metadata, encoding) = match variant {
+            Some((path, metadata, encoding)) => (path, metadata, Some(encoding)),
+            None => (full_path.clone(), metadata, None),
+        };
+        
         // 处理条件请求 (ETag, If-Modified-Since)
-        if let Some(response) = self.handle_conditional_request(&full_path, &metadata, req).await? {
+        if let Some(response) = self.handle_conditional_request(&metadata, encoding, req).await? {
             return Ok(response);
         }
         
         // 处理范围请求
-        if let Some(range_header) = req.headers().get("range") {
+        if let Some(range_header) = range_header {
             return self.handle_range_request(&full_path, &metadata, range_header).await;
         }
         
         // 读取完整文件
-        let content = async_fs::read(&full_path).await?;
+        let content = async_fs::read(&serve_path).await?;
         
         // 构建响应
         let mut response = Response::builder()
             .status(StatusCode::OK)
             .header("Content-Length", content.len().to_string());
         
-        // 设置 MIME 类型
-        if let Some(mime_type) = self.get_mime_type(&full_path) {
+        // 设置 MIME 类型（按原文件判断，预压缩文件的内容无法嗅探）
+        if let Some(mime_type) = self.resolve_mime_type(&full_path).await {
             response = response.header("Content-Type", mime_type);
         }
         
+        // 预压缩文件的编码，响应随 Accept-Encoding 变化
+        if let Some(encoding) = encoding {
+            response = response.header("Content-Encoding", encoding);
+        }
+        if self.config.precompressed {
+            response = response.header("Vary", "Accept-Encoding");
+        }
+        
         // 设置缓存控制
         if self.config.enable_cache_control {
             response = response.header(
@@ -189,8 +247,8 @@ impl FileHandler {
         
         // 设置 ETag
         if self.config.enable_etag {
-            let etag = self.generate_etag(&metadata).await?;
-            response = response.header("ETag", format!("\"{}\"", etag));
+            let etag = self.entity_tag(&metadata, encoding).await?;
+            response = response.header("ETag", etag);
         }
         
         // 设置最后修改时间
@@ -303,6 +361,34 @@ impl FileHandler {
         Ok(path)
     }
     
+    /// 查找客户端可接受的预压缩文件，返回文件路径、元数据和编码
+    async fn find_precompressed<B>(
+        &self,
+        full_path: &Path,
+        req: &Request<B>,
+    ) -> Option<(PathBuf, Metadata, &'static str)> {
+        let accept_encoding = req.headers().get("accept-encoding")?.to_str().ok()?;
+        
+        for (encoding, extension) in PRECOMPRESSED_VARIANTS {
+            if !accepts_encoding(accept_encoding, encoding) {
+                continue;
+            }
+            
+            let mut variant = full_path.as_os_str().to_owned();
+            variant.push(".");
+            variant.push(extension);
+            let variant = PathBuf::from(variant);
+            
+            if let Ok(metadata) = async_fs::metadata(&variant).await {
+                if metadata.is_file() {
+                    return Some((variant, metadata, *encoding));
+                }
+            }
+        }
+        
+        None
+    }
+    
     /// 获取文件的 MIME 类型
     fn get_mime_type(&self, path: &Path) -> Option<String> {
         path.extension()
@@ -311,6 +397,21 @@ impl FileHandler {
             .cloned()
     }
     
+    /// 获取文件的 MIME 类型，扩展名无法识别且启用了内容嗅探时读取文件头判断
+    async fn resolve_mime_type(&self, path: &Path) -> Option<String> {
+        if let Some(mime_type) = self.get_mime_type(path) {
+            return Some(mime_type);
+        }
+        if !self.config.sniff_content_type {
+            return None;
+        }
+        
+        let mut file = async_fs::File::open(path).await.ok()?;
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+        (&mut file).take(SNIFF_LEN as u64).read_to_end(&mut head).await.ok()?;
+        sniff_mime_type(&head).map(|mime_type| mime_type.to_string())
+    }
+    
     /// 生成 ETag
     async fn generate_etag(&self, metadata: &Metadata) -> Result<String, RatError> {
         let mut hasher = Sha256::new();
@@ -328,24 +429,33 @@ impl FileHandler {
         Ok(format!("{:x}", result)[..16].to_string()) // 取前16个字符
     }
     
+    /// 生成带引号的 ETag，预压缩文件附加编码后缀，避免与原文件混用
+    async fn entity_tag(&self, metadata: &Metadata, encoding: Option<&str>) -> Result<String, RatError> {
+        let etag = self.generate_etag(metadata).await?;
+        Ok(match encoding {
+            Some(encoding) => format!("\"{}-{}\"", etag, encoding),
+            None => format!("\"{}\"", etag),
+        })
+    }
+    
     /// 处理条件请求
     async fn handle_conditional_request<B>(
         &self,
-        path: &Path,
         metadata: &Metadata,
+        encoding: Option<&str>,
         req: &Request<B>,
     ) -> Result<Option<Response<Full<Bytes>>>, RatError> {
         // 检查 If-None-Match (ETag)
         if self.config.enable_etag {
             if let Some(if_none_match) = req.headers().get("if-none-match") {
-                let etag = self.generate_etag(metadata).await?;
+                let etag = self.entity_tag(metadata, encoding).await?;
                 let request_etag = if_none_match.to_str().unwrap_or("");
                 
-                if request_etag == format!("\"{}\"", etag) || request_etag == "*" {
+                if request_etag == etag || request_etag == "*" {
                     return Ok(Some(
                         Response::builder()
                             .status(StatusCode::NOT_MODIFIED)
-                            .header("ETag", format!("\"{}\"", etag))
+                            .header("ETag", etag)
                             .body(Full::new(Bytes::new()))
                             .unwrap()
                     ));
@@ -440,7 +550,7 @@ impl FileHandler {
             .header("Accept-Ranges", "bytes");
         
         // 设置 MIME 类型
-        if let Some(mime_type) = self.get_mime_type(path) {
+        if let Some(mime_type) = self.resolve_mime_type(path).await {
             response = response.header("Content-Type", mime_type);
         }
         
@@ -450,6 +560,69 @@ impl FileHandler {
     }
 }
 
+/// Accept-Encoding 是否接受指定编码（q=0 视为拒绝，`*` 匹配未显式列出的编码）
+fn accepts_encoding(accept_encoding: &str, encoding: &str) -> bool {
+    let mut wildcard = None;
+    for part in accept_encoding.split(',') {
+        let mut params = part.split(';');
+        let name = params.next().unwrap_or("").trim();
+        let quality = params
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .next()
+            .and_then(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        
+        if name.eq_ignore_ascii_case(encoding) {
+            return quality > 0.0;
+        }
+        if name == "*" {
+            wildcard = Some(quality > 0.0);
+        }
+    }
+    wildcard.unwrap_or(false)
+}
+
+/// 根据文件开头的字节识别常见的 MIME 类型
+fn sniff_mime_type(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some("image/png");
+    }
+    if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some("image/jpeg");
+    }
+    if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        return Some("image/gif");
+    }
+    if head.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+    
+    // 文本类型：必须是合法的 UTF-8（允许在截断处拆开多字节字符）
+    if let Err(e) = std::str::from_utf8(head) {
+        if e.error_len().is_some() {
+            return None;
+        }
+    }
+    let text = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+    let text = &text[text.iter().position(|b| !b.is_ascii_whitespace())?..];
+    
+    const HTML_PREFIXES: &[&[u8]] = &[b"<!doctype html", b"<html", b"<head", b"<body"];
+    if HTML_PREFIXES.iter().any(|prefix| {
+        text.len() >= prefix.len() && text[..prefix.len()].eq_ignore_ascii_case(prefix)
+    }) {
+        return Some("text/html; charset=utf-8");
+    }
+    
+    // JSON：对象的第一个成员必须是字符串键，数组的第一个元素必须是合法的 JSON 值开头
+    let next = text[1..].iter().find(|b| !b.is_ascii_whitespace());
+    let is_json = match (text[0], next) {
+        (b'{', Some(b'"' | b'}')) => true,
+        (b'[', Some(b'{' | b'[' | b'"' | b']' | b'-' | b'0'..=b'9')) => true,
+        _ => false,
+    };
+    is_json.then_some("application/json; charset=utf-8")
+}
+
 /// GridFS 文件处理器 (示例接口)
 pub trait GridFSHandler: Send + Sync {
     /// 从 GridFS 读取文件
@@ -507,4 +680,92 @@ mod tests {
         let response = result.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
+    
+    #[tokio::test]
+    async fn test_precompressed_variants() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("app.js"), b"console.log(1);").unwrap();
+        fs::write(dir.path().join("app.js.br"), b"brotli-bytes").unwrap();
+        fs::write(dir.path().join("app.js.gz"), b"gzip-bytes").unwrap();
+        
+        let handler = FileHandler::new(FileHandlerConfig {
+            static_root: dir.path().to_path_buf(),
+            ..FileHandlerConfig::default()
+        }.precompressed(true));
+        
+        let request = |accept: Option<&str>| {
+            let mut builder = Request::builder().uri("/app.js");
+            if let Some(accept) = accept {
+                builder = builder.header("accept-encoding", accept);
+            }
+            builder.body(()).unwrap()
+        };
+        
+        let response = handler.serve_static_file("app.js", &request(Some("gzip, br"))).await.unwrap();
+        assert_eq!(response.headers()["content-encoding"], "br");
+        assert_eq!(response.headers()["content-type"], "application/javascript; charset=utf-8");
+        assert_eq!(response.headers()["vary"], "Accept-Encoding");
+        let body = http_body_util::BodyExt::collect(response.into_body()).await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"brotli-bytes");
+        
+        // br 被 q=0 拒绝，退回 gzip 版本
+        let response = handler.serve_static_file("app.js", &request(Some("gzip, br;q=0"))).await.unwrap();
+        assert_eq!(response.headers()["content-encoding"], "gzip");
+        
+        // 不接受压缩时返回原文件
+        let response = handler.serve_static_file("app.js", &request(None)).await.unwrap();
+        assert!(!response.headers().contains_key("content-encoding"));
+        assert_eq!(response.headers()["content-length"], "15");
+    }
+    
+    #[test]
+    fn test_sniff_mime_type() {
+        assert_eq!(sniff_mime_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+        assert_eq!(sniff_mime_type(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff_mime_type(b"GIF89a\x01\0"), Some("image/gif"));
+        assert_eq!(sniff_mime_type(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(sniff_mime_type(b"\xEF\xBB\xBF  <!DOCTYPE HTML><html>"), Some("text/html; charset=utf-8"));
+        assert_eq!(sniff_mime_type(b"\n{ \"ok\": true }"), Some("application/json; charset=utf-8"));
+        assert_eq!(sniff_mime_type(b"[1, 2, 3]"), Some("application/json; charset=utf-8"));
+        
+        // INI 段落、纯文本和二进制数据不做猜测
+        assert_eq!(sniff_mime_type(b"[section]\nkey=value"), None);
+        assert_eq!(sniff_mime_type(b"hello world"), None);
+        assert_eq!(sniff_mime_type(&[0x00, 0xC3, 0x28, 0x01]), None);
+        assert_eq!(sniff_mime_type(b""), None);
+    }
+    
+    #[tokio::test]
+    async fn test_sniff_only_when_extension_unknown() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("upload"), b"\x89PNG\r\n\x1a\nrest-of-image").unwrap();
+        fs::write(dir.path().join("data.txt"), b"{\"a\":1}").unwrap();
+        let config = FileHandlerConfig {
+            static_root: dir.path().to_path_buf(),
+            ..FileHandlerConfig::default()
+        };
+        let request = Request::builder().uri("/").body(()).unwrap();
+        
+        // 默认不嗅探
+        let handler = FileHandler::new(config.clone());
+        let response = handler.serve_static_file("upload", &request).await.unwrap();
+        assert!(!response.headers().contains_key("content-type"));
+        
+        let handler = FileHandler::new(config.sniff_content_type(true));
+        let response = handler.serve_static_file("upload", &request).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "image/png");
+        
+        // 扩展名已知时以扩展名为准
+        let response = handler.serve_static_file("data.txt", &request).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/plain; charset=utf-8");
+    }
+    
+    #[test]
+    fn test_accepts_encoding() {
+        assert!(accepts_encoding("gzip, deflate, br", "br"));
+        assert!(!accepts_encoding("gzip;q=0", "gzip"));
+        assert!(accepts_encoding("*", "zstd"));
+        assert!(!accepts_encoding("*, br;q=0", "br"));
+        assert!(!accepts_encoding("identity", "gzip"));
+    }
 }
\ No newline at end of file
@@ -1,14 +1,42 @@
 //! 全局 SSE 管理器
 //!
 //! 独立的 SSE 连接管理，不依赖 SseResponse 的存储逻辑
+//!
+//! # 并发写入
+//!
+//! 同一连接可以被多个任务或线程同时调用 `send_event`、`send_data`、`send_heartbeat` 和 `broadcast`。
+//! 每条消息先在调用方完整格式化（包括结尾的空行），再作为单个数据帧放入该连接的通道；
+//! 通道只有写出连接一个消费者，因此消息之间不会交错，客户端总能收到完整的事件边界。
+//! 并发发送的消息按进入通道的顺序写出，同一调用方发出的消息保持先后顺序。
+//!
+//! # 心跳保活
+//!
+//! 反向代理通常会断开长时间没有数据的连接。通过 [`GlobalSseManager::with_heartbeat`]
+//! 或 [`GlobalSseManager::set_heartbeat_interval`] 设置心跳间隔后，每个新注册的连接都会定时收到
+//! `: heartbeat` 注释帧，与空闲回收任务的探测心跳（[`GlobalSseManager::send_heartbeat`]）是同一种帧。
+//! 心跳帧与普通消息一样整帧进入通道，不会与 `send_data` 的内容交错；
+//! 连接被断开或接收端被丢弃后，心跳任务在下一个周期自动退出。
+//!
+//! # 分组广播
+//!
+//! 聊天室等场景可以把连接加入分组，再通过 [`GlobalSseManager::broadcast_to_group`] 一次性推送：
+//! 消息只格式化一次，推送期间持有该分组的锁，与同一分组的加入、离开操作互斥。
+//! 连接断开时自动退出所有分组，发送失败的成员会被清理。
 
 use dashmap::DashMap;
+use std::collections::HashSet;
+use std::net::IpAddr;
 use std::sync::Arc;
-use hyper::{Response, StatusCode};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use futures_util::StreamExt;
+use hyper::{Response, StatusCode, HeaderMap};
+use hyper::header::{HeaderName, HeaderValue};
 use bytes::Bytes;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use crate::server::streaming::{StreamingResponse, StreamingBody};
+use crate::server::streaming::{StreamingResponse, StreamingBody, SseLimits};
+use crate::server::load_shed::{LoadShedResponse, LoadShedReason};
 use crate::utils::logger::{info, debug, warn};
 
 /// 全局 SSE 管理器
@@ -17,6 +45,215 @@ use crate::utils::logger::{info, debug, warn};
 pub struct GlobalSseManager {
     /// 连接映射表：connection_id -> Arc<sender>
     connections: Arc<DashMap<String, Arc<mpsc::UnboundedSender<Result<hyper::body::Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>>>>,
+    /// 消息大小限制
+    limits: std::sync::RwLock<SseLimits>,
+    /// 最近活跃时间：connection_id -> 距 epoch 的毫秒数
+    ///
+    /// 在连接轮询响应流、取走帧准备写出时刷新，缓冲在通道里的消息不算活跃
+    last_activity: Arc<DashMap<String, Arc<AtomicU64>>>,
+    /// 活跃时间的计时起点
+    epoch: Instant,
+    /// 连接数限制
+    connection_limits: std::sync::RwLock<SseConnectionLimits>,
+    /// 每个客户端 IP 的连接数
+    ip_connections: Arc<DashMap<IpAddr, usize>>,
+    /// 连接所属的客户端 IP：connection_id -> IP
+    connection_ips: Arc<DashMap<String, IpAddr>>,
+    /// 超过连接数限制时返回的响应
+    load_shed_response: std::sync::RwLock<LoadShedResponse>,
+    /// 心跳间隔，None 表示不发送
+    heartbeat_interval: std::sync::RwLock<Option<Duration>>,
+    /// 分组成员：group_id -> connection_id 集合
+    groups: Arc<DashMap<String, HashSet<String>>>,
+    /// 分组反向索引：connection_id -> group_id 集合，断开连接时不必遍历所有分组
+    connection_groups: Arc<DashMap<String, HashSet<String>>>,
+    /// 串行化注册：名额检查与写入连接表在同一临界区内完成，避免并发注册越过上限
+    registration: std::sync::Mutex<()>,
+}
+
+/// SSE 连接数限制
+///
+/// 防止客户端大量打开 EventSource 耗尽内存和文件描述符；默认不限制
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SseConnectionLimits {
+    /// 全局最大连接数
+    pub max_connections: Option<usize>,
+    /// 单个客户端 IP 的最大连接数（仅对注册时提供了客户端 IP 的连接生效）
+    pub max_connections_per_ip: Option<usize>,
+}
+
+impl SseConnectionLimits {
+    /// 设置全局最大连接数
+    pub fn with_max_connections(mut self, max: usize) -> Self {
+        self.max_connections = Some(max);
+        self
+    }
+
+    /// 设置单个客户端 IP 的最大连接数
+    pub fn with_max_connections_per_ip(mut self, max: usize) -> Self {
+        self.max_connections_per_ip = Some(max);
+        self
+    }
+}
+
+/// SSE 连接注册错误
+#[derive(Debug)]
+pub enum SseRegisterError {
+    /// 超过全局最大连接数
+    TooManyConnections { limit: usize },
+    /// 超过单个客户端 IP 的最大连接数
+    TooManyConnectionsFromIp { ip: IpAddr, limit: usize },
+    /// 构建响应失败
+    Response(hyper::Error),
+}
+
+impl std::fmt::Display for SseRegisterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyConnections { limit } => write!(f, "SSE 连接数已达上限 {}", limit),
+            Self::TooManyConnectionsFromIp { ip, limit } => write!(f, "客户端 {} 的 SSE 连接数已达上限 {}", ip, limit),
+            Self::Response(e) => write!(f, "构建 SSE 响应失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SseRegisterError {}
+
+impl SseRegisterError {
+    /// 转换为 HTTP 响应：超过连接数限制时返回 503 并带 `Retry-After: 5`
+    pub fn into_response(self) -> Result<Response<StreamingBody>, hyper::Error> {
+        self.into_response_with(&default_sse_load_shed_response())
+    }
+
+    /// 使用指定的过载拒绝响应转换为 HTTP 响应
+    pub fn into_response_with(self, shed: &LoadShedResponse) -> Result<Response<StreamingBody>, hyper::Error> {
+        match self {
+            Self::Response(e) => Err(e),
+            _ => {
+                let mut response = StreamingResponse::new()
+                    .status(shed.status())
+                    .with_header("Content-Type", shed.content_type())
+                    .with_header("Cache-Control", "no-cache");
+                if let Some(retry_after) = shed.retry_after_header() {
+                    response = response.with_header("Retry-After", retry_after);
+                }
+                let body = shed.body().clone();
+                response
+                    .stream(futures_util::stream::once(async move { Ok(hyper::body::Frame::data(body)) }))
+                    .build()
+            }
+        }
+    }
+}
+
+/// 心跳注释帧，客户端的 EventSource 会忽略注释行
+const HEARTBEAT_FRAME: &[u8] = b": heartbeat\n\n";
+
+/// SSE 连接数超限时建议客户端重连的间隔
+const SSE_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// SSE 默认的过载拒绝响应：503 并带 `Retry-After: 5`
+fn default_sse_load_shed_response() -> LoadShedResponse {
+    LoadShedResponse::default().with_retry_after(Some(SSE_RETRY_AFTER))
+}
+
+/// SSE 空闲连接回收配置
+#[derive(Debug, Clone, Copy)]
+pub struct SseReaperConfig {
+    /// 空闲超过该时长的连接会被断开
+    pub idle_timeout: Duration,
+    /// 检查间隔
+    pub check_interval: Duration,
+    /// 每次检查时是否先发送心跳探测
+    ///
+    /// 活跃时间在连接轮询响应流时刷新，而不是在客户端确认收到时刷新：
+    /// 帧被取走后先进入 hyper 的写缓冲和内核发送缓冲区（HTTP/2 还受流控窗口限制）。
+    /// 已失联的客户端不再读取数据，缓冲区写满后连接停止轮询，心跳不再刷新活跃时间，
+    /// 最终因超时被回收；因此检测延迟取决于缓冲区大小，而不只是 `idle_timeout`
+    pub probe_with_heartbeat: bool,
+}
+
+impl Default for SseReaperConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(120),
+            check_interval: Duration::from_secs(30),
+            probe_with_heartbeat: true,
+        }
+    }
+}
+
+impl SseReaperConfig {
+    /// 设置空闲超时
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// 设置检查间隔
+    pub fn with_check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    /// 设置是否发送心跳探测
+    pub fn with_heartbeat_probe(mut self, enabled: bool) -> Self {
+        self.probe_with_heartbeat = enabled;
+        self
+    }
+}
+
+/// SSE 连接注册选项
+///
+/// 用于自定义 SSE 响应的状态码和额外响应头，额外响应头会覆盖同名的默认头部
+#[derive(Debug, Clone)]
+pub struct SseRegisterOptions {
+    /// 响应状态码（默认 200）
+    pub status: StatusCode,
+    /// 额外响应头
+    pub headers: HeaderMap,
+    /// 客户端 IP，用于单 IP 连接数限制
+    pub client_ip: Option<IpAddr>,
+}
+
+impl Default for SseRegisterOptions {
+    fn default() -> Self {
+        Self {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            client_ip: None,
+        }
+    }
+}
+
+impl SseRegisterOptions {
+    /// 设置响应状态码
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// 添加额外响应头（名称或值无效时忽略）
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            (Ok(name), Ok(value)) => {
+                self.headers.insert(name, value);
+            }
+            _ => warn!("⚠️ [全局SSE管理器] 忽略无效的响应头: {}: {}", name, value),
+        }
+        self
+    }
+
+    /// 设置客户端 IP（启用单 IP 连接数限制时需要）
+    pub fn with_client_ip(mut self, ip: IpAddr) -> Self {
+        self.client_ip = Some(ip);
+        self
+    }
+
+    /// 禁用反向代理缓冲（nginx 的 `X-Accel-Buffering: no`）
+    pub fn disable_proxy_buffering(self) -> Self {
+        self.with_header("X-Accel-Buffering", "no")
+    }
 }
 
 impl GlobalSseManager {
@@ -24,12 +261,180 @@ impl GlobalSseManager {
     pub fn new() -> Self {
         Self {
             connections: Arc::new(DashMap::new()),
+            limits: std::sync::RwLock::new(SseLimits::default()),
+            last_activity: Arc::new(DashMap::new()),
+            epoch: Instant::now(),
+            connection_limits: std::sync::RwLock::new(SseConnectionLimits::default()),
+            ip_connections: Arc::new(DashMap::new()),
+            connection_ips: Arc::new(DashMap::new()),
+            load_shed_response: std::sync::RwLock::new(default_sse_load_shed_response()),
+            heartbeat_interval: std::sync::RwLock::new(None),
+            groups: Arc::new(DashMap::new()),
+            connection_groups: Arc::new(DashMap::new()),
+            registration: std::sync::Mutex::new(()),
+        }
+    }
+
+    /// 为之后注册的连接启用心跳
+    pub fn with_heartbeat(self, interval: Duration) -> Self {
+        self.set_heartbeat_interval(Some(interval));
+        self
+    }
+
+    /// 设置心跳间隔（只影响之后注册的连接，None 表示不发送）
+    ///
+    /// 全局管理器以 `Arc` 共享，无法使用 [`Self::with_heartbeat`]，通过该方法设置
+    pub fn set_heartbeat_interval(&self, interval: Option<Duration>) {
+        if let Ok(mut guard) = self.heartbeat_interval.write() {
+            *guard = interval.filter(|interval| !interval.is_zero());
+        }
+    }
+
+    /// 获取当前心跳间隔
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        self.heartbeat_interval.read().map(|guard| *guard).unwrap_or_default()
+    }
+
+    /// 向连接写入一个心跳注释帧，定时心跳和空闲回收的探测共用
+    fn push_heartbeat(
+        sender: &mpsc::UnboundedSender<Result<hyper::body::Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>,
+    ) -> Result<(), mpsc::error::SendError<Result<hyper::body::Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>> {
+        sender.send(Ok(hyper::body::Frame::data(Bytes::from_static(HEARTBEAT_FRAME))))
+    }
+
+    /// 为连接启动心跳任务
+    ///
+    /// 任务只持有 sender 的弱引用：连接从映射表中移除（断开或被同名连接替换）后 sender 被释放，
+    /// 任务随之退出；接收端被丢弃时发送失败，任务同样退出
+    fn spawn_heartbeat(connection_id: &str, sender: &Arc<mpsc::UnboundedSender<Result<hyper::body::Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>>, interval: Duration) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("⚠️ [全局SSE管理器] 当前不在 Tokio 运行时中，连接 {} 不发送心跳", connection_id);
+            return;
+        };
+        let sender = Arc::downgrade(sender);
+        let connection_id = connection_id.to_string();
+        runtime.spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(sender) = sender.upgrade() else {
+                    break;
+                };
+                if Self::push_heartbeat(&sender).is_err() {
+                    break;
+                }
+            }
+            debug!("[全局SSE管理器] 连接 {} 的心跳任务已退出", connection_id);
+        });
+    }
+
+    /// 设置超过连接数限制时返回的响应
+    ///
+    /// 通过 `RatEngineBuilder::load_shed_response` 配置时会自动同步到全局管理器
+    pub fn set_load_shed_response(&self, response: LoadShedResponse) {
+        if let Ok(mut current) = self.load_shed_response.write() {
+            *current = response;
+        }
+    }
+
+    /// 获取超过连接数限制时返回的响应
+    pub fn load_shed_response(&self) -> LoadShedResponse {
+        self.load_shed_response.read().map(|r| r.clone()).unwrap_or_else(|_| default_sse_load_shed_response())
+    }
+
+    fn elapsed_millis(epoch: Instant) -> u64 {
+        epoch.elapsed().as_millis() as u64
+    }
+
+    /// 设置消息大小限制（对之后发送的所有消息生效）
+    pub fn set_limits(&self, limits: SseLimits) {
+        if let Ok(mut guard) = self.limits.write() {
+            *guard = limits;
+        }
+    }
+
+    /// 获取当前消息大小限制
+    pub fn get_limits(&self) -> SseLimits {
+        self.limits.read().map(|guard| *guard).unwrap_or_default()
+    }
+
+    /// 设置连接数限制（只影响之后的注册，已建立的连接不会被断开）
+    pub fn set_connection_limits(&self, limits: SseConnectionLimits) {
+        if let Ok(mut guard) = self.connection_limits.write() {
+            *guard = limits;
+        }
+    }
+
+    /// 获取当前连接数限制
+    pub fn get_connection_limits(&self) -> SseConnectionLimits {
+        self.connection_limits.read().map(|guard| *guard).unwrap_or_default()
+    }
+
+    /// 获取指定客户端 IP 的连接数
+    pub fn connection_count_for_ip(&self, ip: &IpAddr) -> usize {
+        self.ip_connections.get(ip).map(|count| *count).unwrap_or(0)
+    }
+
+    /// 检查连接数限制并占用名额
+    ///
+    /// 使用已存在的 `connection_id` 重新注册时视为替换旧连接，不额外占用全局名额。
+    /// 调用方需持有 `registration` 锁，直到连接写入连接表
+    fn reserve_slot(&self, connection_id: &str, client_ip: Option<IpAddr>) -> Result<(), SseRegisterError> {
+        let limits = self.get_connection_limits();
+
+        // 名额已满时先回收接收端已被丢弃的连接，避免已关闭的页面继续占用名额
+        let saturated = limits.max_connections.is_some_and(|limit| self.connections.len() >= limit)
+            || client_ip
+                .zip(limits.max_connections_per_ip)
+                .is_some_and(|(ip, limit)| self.connection_count_for_ip(&ip) >= limit);
+        if saturated {
+            self.prune_closed_connections();
+        }
+
+        let replacing = self.connections.contains_key(connection_id);
+        if let Some(limit) = limits.max_connections {
+            if !replacing && self.connections.len() >= limit {
+                return Err(SseRegisterError::TooManyConnections { limit });
+            }
+        }
+
+        let previous_ip = self.connection_ips.get(connection_id).map(|ip| *ip);
+        if client_ip == previous_ip {
+            return Ok(());
+        }
+
+        if let Some(ip) = client_ip {
+            let mut count = self.ip_connections.entry(ip).or_insert(0);
+            if let Some(limit) = limits.max_connections_per_ip {
+                if *count >= limit {
+                    return Err(SseRegisterError::TooManyConnectionsFromIp { ip, limit });
+                }
+            }
+            *count += 1;
+        }
+
+        self.release_slot(connection_id);
+        if let Some(ip) = client_ip {
+            self.connection_ips.insert(connection_id.to_string(), ip);
+        }
+        Ok(())
+    }
+
+    /// 释放连接占用的单 IP 名额
+    fn release_slot(&self, connection_id: &str) {
+        if let Some((_, ip)) = self.connection_ips.remove(connection_id) {
+            if let Some(mut count) = self.ip_connections.get_mut(&ip) {
+                *count = count.saturating_sub(1);
+            }
+            self.ip_connections.remove_if(&ip, |_, count| *count == 0);
         }
     }
 
     /// 注册 SSE 连接
     ///
-    /// 在管理器内部创建通道，构建响应并存储 sender
+    /// 在管理器内部创建通道，构建响应并存储 sender。
+    /// 超过连接数限制时不会注册，直接返回过载拒绝响应（默认 503）
     ///
     /// # 参数
     /// * `connection_id` - 连接ID，由调用者自定义
@@ -37,23 +442,68 @@ impl GlobalSseManager {
     /// # 返回值
     /// 构建好的 SSE 响应
     pub fn register_connection(&self, connection_id: String) -> Result<Response<StreamingBody>, hyper::Error> {
+        self.register_connection_with_options(connection_id, SseRegisterOptions::default())
+    }
+
+    /// 注册 SSE 连接，并自定义响应状态码和响应头
+    ///
+    /// 适用于部署在 nginx 等反向代理之后、需要关闭代理缓冲或调整缓存指令的场景
+    ///
+    /// # 示例
+    /// ```ignore
+    /// let options = SseRegisterOptions::default()
+    ///     .disable_proxy_buffering()
+    ///     .with_header("Cache-Control", "no-cache, no-transform");
+    /// manager.register_connection_with_options(id, options)
+    /// ```
+    pub fn register_connection_with_options(&self, connection_id: String, options: SseRegisterOptions) -> Result<Response<StreamingBody>, hyper::Error> {
+        match self.try_register_connection_with_options(connection_id, options) {
+            Ok(response) => Ok(response),
+            Err(e) => e.into_response_with(&self.load_shed_response()),
+        }
+    }
+
+    /// 注册 SSE 连接，超过连接数限制时返回错误
+    ///
+    /// 错误可通过 [`SseRegisterError::into_response_with`] 转换为过载拒绝响应
+    pub fn try_register_connection_with_options(&self, connection_id: String, options: SseRegisterOptions) -> Result<Response<StreamingBody>, SseRegisterError> {
+        // 持有到连接写入连接表为止
+        let _registration = self.registration.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = self.reserve_slot(&connection_id, options.client_ip) {
+            warn!("🚫 [全局SSE管理器] {}，拒绝注册连接 {}: {}", LoadShedReason::SseConnectionLimit.as_str(), connection_id, e);
+            return Err(e);
+        }
+
         // 创建通道
         let (sender, receiver) = mpsc::unbounded_channel();
 
         // 存储sender
-        self.connections.insert(connection_id.clone(), Arc::new(sender));
+        let sender = Arc::new(sender);
+        if let Some(interval) = self.heartbeat_interval() {
+            Self::spawn_heartbeat(&connection_id, &sender, interval);
+        }
+        self.connections.insert(connection_id.clone(), sender);
+
+        // 记录活跃时间，帧被连接取走时刷新
+        let activity = Arc::new(AtomicU64::new(Self::elapsed_millis(self.epoch)));
+        self.last_activity.insert(connection_id.clone(), activity.clone());
+        let epoch = self.epoch;
 
         // 构建响应流
-        let stream = UnboundedReceiverStream::new(receiver);
+        let stream = UnboundedReceiverStream::new(receiver).inspect(move |_| {
+            activity.store(Self::elapsed_millis(epoch), Ordering::Relaxed);
+        });
 
-        let response = StreamingResponse::new()
-            .status(StatusCode::OK)
+        let mut response = StreamingResponse::new()
+            .status(options.status)
             .with_header("Content-Type", "text/event-stream")
             .with_header("Cache-Control", "no-cache")
             .with_header("Connection", "keep-alive")
-            .with_header("Access-Control-Allow-Origin", "*")
-            .stream(stream)
-            .build();
+            .with_header("Access-Control-Allow-Origin", "*");
+        for (name, value) in options.headers.iter() {
+            response = response.with_header(name, value.clone());
+        }
+        let response = response.stream(stream).build().map_err(SseRegisterError::Response);
 
         info!("🔗 [全局SSE管理器] 创建并注册连接: {}", connection_id);
         response
@@ -71,7 +521,7 @@ impl GlobalSseManager {
     /// * `Err(String)` - 发送失败
     pub fn send_event(&self, connection_id: &str, event: &str, data: &str) -> Result<(), String> {
         if let Some(sender) = self.connections.get(connection_id) {
-            let formatted = format!("event: {}\ndata: {}\n\n", event, data);
+            let formatted = format!("{}\n", self.get_limits().format_message(Some(event), data)?);
             sender
                 .send(Ok(hyper::body::Frame::data(Bytes::from(formatted))))
                 .map_err(|e| format!("发送SSE事件失败: {:?}", e))
@@ -91,7 +541,7 @@ impl GlobalSseManager {
     /// * `Err(String)` - 发送失败
     pub fn send_data(&self, connection_id: &str, data: &str) -> Result<(), String> {
         if let Some(sender) = self.connections.get(connection_id) {
-            let formatted = format!("data: {}\n\n", data);
+            let formatted = format!("{}\n", self.get_limits().format_message(None, data)?);
             sender
                 .send(Ok(hyper::body::Frame::data(Bytes::from(formatted))))
                 .map_err(|e| format!("发送SSE数据失败: {:?}", e))
@@ -110,9 +560,7 @@ impl GlobalSseManager {
     /// * `Err(String)` - 发送失败
     pub fn send_heartbeat(&self, connection_id: &str) -> Result<(), String> {
         if let Some(sender) = self.connections.get(connection_id) {
-            sender
-                .send(Ok(hyper::body::Frame::data(Bytes::from(": heartbeat\n\n"))))
-                .map_err(|e| format!("发送心跳失败: {:?}", e))
+            Self::push_heartbeat(&sender).map_err(|e| format!("发送心跳失败: {:?}", e))
         } else {
             Err("连接不存在".to_string())
         }
@@ -131,10 +579,14 @@ impl GlobalSseManager {
     /// * `true` - 连接存在并已断开
     /// * `false` - 连接不存在
     pub fn disconnect_connection(&self, connection_id: &str) -> bool {
-        if let Some((_, sender)) = self.connections.remove(connection_id) {
-            // 发送断开事件（不管成功失败）
-            let _ = sender.send(Ok(hyper::body::Frame::data(Bytes::from("event: disconnect\ndata: 服务器断开连接\n\n"))));
-            let _ = sender.send(Ok(hyper::body::Frame::data(Bytes::from("DISCONNECT_EVENT"))));
+        // 先从连接表移除，之后并发的 join_group 不会再把它加入分组
+        let removed = self.connections.remove(connection_id);
+        self.last_activity.remove(connection_id);
+        self.release_slot(connection_id);
+        self.leave_all_groups(connection_id);
+        if let Some((_, sender)) = removed {
+            // 发送断开事件（不管成功失败），断开事件和结束标记放在同一帧中，不会被其他消息隔开
+            let _ = sender.send(Ok(hyper::body::Frame::data(Bytes::from("event: disconnect\ndata: 服务器断开连接\n\nDISCONNECT_EVENT"))));
 
             // 关闭发送器
             drop(sender);
@@ -161,6 +613,9 @@ impl GlobalSseManager {
     /// * `false` - 连接不存在
     pub(crate) fn remove_connection(&self, connection_id: &str) -> bool {
         let removed = self.connections.remove(connection_id).is_some();
+        self.last_activity.remove(connection_id);
+        self.release_slot(connection_id);
+        self.leave_all_groups(connection_id);
         if removed {
             info!("🗑️ [全局SSE管理器] 移除连接: {}", connection_id);
         }
@@ -209,6 +664,100 @@ impl GlobalSseManager {
         success_count
     }
 
+    /// 把连接加入分组
+    ///
+    /// # 返回值
+    /// * `true` - 已加入（或已在分组中）
+    /// * `false` - 连接不存在
+    pub fn join_group(&self, connection_id: &str, group_id: &str) -> bool {
+        // 加入期间持有连接表的读锁：移除连接要等加入完成，随后的清理能看到新的成员关系
+        let Some(_connection) = self.connections.get(connection_id) else {
+            warn!("🔍 [全局SSE管理器] 连接 {} 不存在，无法加入分组 {}", connection_id, group_id);
+            return false;
+        };
+        self.groups.entry(group_id.to_string()).or_default().insert(connection_id.to_string());
+        self.connection_groups.entry(connection_id.to_string()).or_default().insert(group_id.to_string());
+        debug!("👥 [全局SSE管理器] 连接 {} 加入分组 {}", connection_id, group_id);
+        true
+    }
+
+    /// 把连接移出分组，分组为空时一并删除
+    ///
+    /// # 返回值
+    /// 连接原本在分组中时返回 `true`
+    pub fn leave_group(&self, connection_id: &str, group_id: &str) -> bool {
+        let removed = self.groups
+            .get_mut(group_id)
+            .map(|mut members| members.remove(connection_id))
+            .unwrap_or(false);
+        self.groups.remove_if(group_id, |_, members| members.is_empty());
+        if let Some(mut groups) = self.connection_groups.get_mut(connection_id) {
+            groups.remove(group_id);
+        }
+        self.connection_groups.remove_if(connection_id, |_, groups| groups.is_empty());
+        removed
+    }
+
+    /// 分组中的连接 ID
+    pub fn group_members(&self, group_id: &str) -> Vec<String> {
+        self.groups
+            .get(group_id)
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 把连接移出所有分组
+    fn leave_all_groups(&self, connection_id: &str) {
+        let Some((_, group_ids)) = self.connection_groups.remove(connection_id) else {
+            return;
+        };
+        for group_id in &group_ids {
+            if let Some(mut members) = self.groups.get_mut(group_id) {
+                members.remove(connection_id);
+            }
+            self.groups.remove_if(group_id, |_, members| members.is_empty());
+        }
+    }
+
+    /// 向分组内的所有连接发送数据
+    ///
+    /// 消息只格式化一次；发送失败（连接已不存在或接收端已丢弃）的成员会被移出分组并清理连接
+    ///
+    /// # 返回值
+    /// 返回成功发送的连接数量
+    pub fn broadcast_to_group(&self, group_id: &str, data: &str) -> usize {
+        let formatted = match self.get_limits().format_message(None, data) {
+            Ok(message) => Bytes::from(format!("{}\n", message)),
+            Err(e) => {
+                warn!("❌ [全局SSE管理器] 分组 {} 的广播消息被拒绝: {}", group_id, e);
+                return 0;
+            }
+        };
+
+        // 复制成员列表后释放分组锁，发送和清理过程不持有分组锁
+        let members = self.group_members(group_id);
+        let mut success_count = 0;
+        for connection_id in members {
+            let sent = self.connections
+                .get(&connection_id)
+                .map(|sender| sender.send(Ok(hyper::body::Frame::data(formatted.clone()))).is_ok());
+            match sent {
+                Some(true) => success_count += 1,
+                // 接收端已丢弃：只有连接仍是这个已关闭的连接时才移除，同 ID 重新注册的连接不受影响
+                Some(false) => {
+                    self.remove_closed_connection(&connection_id);
+                }
+                // 连接已被移除，清理残留的成员关系
+                None => {
+                    self.leave_group(&connection_id, group_id);
+                }
+            }
+        }
+
+        debug!("📡 [全局SSE管理器] 分组广播: group='{}', 成功连接数={}", group_id, success_count);
+        success_count
+    }
+
     /// 获取连接统计
     ///
     /// # 返回值
@@ -229,10 +778,130 @@ impl GlobalSseManager {
         self.connections.contains_key(connection_id)
     }
 
+    /// 当前仍然存活的连接数量
+    ///
+    /// 与 [`Self::get_connection_count`] 不同，统计前会先移除接收端已被丢弃的连接
+    /// （例如浏览器标签页直接关闭），结果可用于核对应用自己维护的成员表
+    pub fn connection_count(&self) -> usize {
+        self.prune_closed_connections();
+        self.connections.len()
+    }
+
+    /// 连接是否仍然存活
+    ///
+    /// 连接已注册但接收端已被丢弃时返回 `false`，并顺便移除该连接
+    pub fn is_connected(&self, connection_id: &str) -> bool {
+        !self.remove_closed_connection(connection_id) && self.connections.contains_key(connection_id)
+    }
+
+    /// 所有仍然存活的连接 ID
+    pub fn active_connection_ids(&self) -> Vec<String> {
+        self.prune_closed_connections();
+        self.connections.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    /// 移除接收端已被丢弃的连接，返回移除的数量
+    fn prune_closed_connections(&self) -> usize {
+        let closed: Vec<String> = self.connections
+            .iter()
+            .filter(|entry| entry.value().is_closed())
+            .map(|entry| entry.key().clone())
+            .collect();
+        closed.iter().filter(|connection_id| self.remove_closed_connection(connection_id)).count()
+    }
+
+    /// 连接的接收端已被丢弃时移除连接
+    ///
+    /// 判断与移除在同一把分片锁内完成，期间用同一 ID 重新注册的存活连接不会被误删；
+    /// 只有确实移除时才释放名额和分组
+    fn remove_closed_connection(&self, connection_id: &str) -> bool {
+        if self.connections.remove_if(connection_id, |_, sender| sender.is_closed()).is_none() {
+            return false;
+        }
+        self.last_activity.remove(connection_id);
+        self.release_slot(connection_id);
+        self.leave_all_groups(connection_id);
+        info!("🗑️ [全局SSE管理器] 移除已关闭的连接: {}", connection_id);
+        true
+    }
+
+    /// 获取连接的空闲时长（距最近一次帧被取走的时间）
+    ///
+    /// 帧被取走只表示已交给连接写出，不代表客户端已经收到
+    ///
+    /// # 返回值
+    /// 连接不存在时返回 `None`
+    pub fn idle_duration(&self, connection_id: &str) -> Option<Duration> {
+        let last = self.last_activity.get(connection_id)?.load(Ordering::Relaxed);
+        let now = Self::elapsed_millis(self.epoch);
+        Some(Duration::from_millis(now.saturating_sub(last)))
+    }
+
+    /// 断开所有空闲超过 `idle_timeout` 的连接
+    ///
+    /// 同时清理接收端已被丢弃的连接
+    ///
+    /// # 返回值
+    /// 返回回收的连接数量
+    pub fn reap_idle_connections(&self, idle_timeout: Duration) -> usize {
+        let stale: Vec<String> = self.connections
+            .iter()
+            .filter(|entry| {
+                entry.value().is_closed()
+                    || self.idle_duration(entry.key()).is_none_or(|idle| idle > idle_timeout)
+            })
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        for connection_id in &stale {
+            self.disconnect_connection(connection_id);
+        }
+
+        if !stale.is_empty() {
+            info!("🧹 [全局SSE管理器] 回收空闲连接 {} 个（空闲阈值: {:?}）", stale.len(), idle_timeout);
+        }
+        stale.len()
+    }
+
+    /// 启动空闲连接回收任务
+    ///
+    /// 每个检查周期先按需向所有连接发送心跳，发送失败的连接立即移除，
+    /// 随后断开空闲超过阈值的连接
+    pub fn start_idle_reaper(self: &Arc<Self>, config: SseReaperConfig) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(config.check_interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+
+                if config.probe_with_heartbeat {
+                    let ids: Vec<String> = manager.connections.iter().map(|e| e.key().clone()).collect();
+                    for connection_id in ids {
+                        if manager.send_heartbeat(&connection_id).is_err() {
+                            manager.remove_connection(&connection_id);
+                        }
+                    }
+                }
+
+                manager.reap_idle_connections(config.idle_timeout);
+            }
+            debug!("[全局SSE管理器] 空闲连接回收任务已退出");
+        })
+    }
+
     /// 清空所有连接
     pub fn clear(&self) {
         let count = self.connections.len();
         self.connections.clear();
+        self.last_activity.clear();
+        self.groups.clear();
+        self.connection_groups.clear();
+        self.connection_ips.clear();
+        self.ip_connections.clear();
         info!("🧹 [全局SSE管理器] 清空所有连接，清理了 {} 个连接", count);
     }
 }
@@ -289,14 +958,200 @@ pub fn register_sse_connection(connection_id: String) -> Result<Response<Streami
     manager.register_connection(connection_id)
 }
 
+/// 便捷函数：注册 SSE 连接（自定义状态码和响应头）
+pub fn register_sse_connection_with_options(connection_id: String, options: SseRegisterOptions) -> Result<Response<StreamingBody>, hyper::Error> {
+    let manager = get_global_sse_manager();
+    manager.register_connection_with_options(connection_id, options)
+}
+
+/// 便捷函数：设置全局 SSE 管理器的连接数限制
+pub fn set_sse_connection_limits(limits: SseConnectionLimits) {
+    let manager = get_global_sse_manager();
+    manager.set_connection_limits(limits)
+}
+
+/// 便捷函数：设置全局 SSE 管理器的心跳间隔
+pub fn set_sse_heartbeat_interval(interval: Option<Duration>) {
+    let manager = get_global_sse_manager();
+    manager.set_heartbeat_interval(interval)
+}
+
+/// 便捷函数：向全局 SSE 管理器中的分组广播数据
+pub fn broadcast_sse_to_group(group_id: &str, data: &str) -> usize {
+    let manager = get_global_sse_manager();
+    manager.broadcast_to_group(group_id, data)
+}
+
 /// 便捷函数：主动断开 SSE 连接
 pub fn disconnect_sse_connection(connection_id: &str) -> bool {
     let manager = get_global_sse_manager();
     manager.disconnect_connection(connection_id)
 }
 
+/// 便捷函数：为全局 SSE 管理器启动空闲连接回收任务
+pub fn start_sse_idle_reaper(config: SseReaperConfig) -> tokio::task::JoinHandle<()> {
+    let manager = get_global_sse_manager();
+    manager.start_idle_reaper(config)
+}
+
 /// 便捷函数：获取连接数量
 pub fn get_sse_connection_count() -> usize {
     let manager = get_global_sse_manager();
     manager.get_connection_count()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn test_concurrent_sends_keep_event_boundaries() {
+        const SENDERS: usize = 8;
+        const EVENTS: usize = 200;
+
+        let manager = Arc::new(GlobalSseManager::new());
+        let response = manager.register_connection("conn".to_string()).unwrap();
+
+        let workers: Vec<_> = (0..SENDERS).map(|sender| {
+            let manager = manager.clone();
+            std::thread::spawn(move || {
+                for i in 0..EVENTS {
+                    // 多行数据会被格式化为多个 data 行，必须整体出现
+                    let data = format!("{}-{}-a\n{}-{}-b", sender, i, sender, i);
+                    if i % 2 == 0 {
+                        manager.send_event("conn", &format!("t{}", sender), &data).unwrap();
+                    } else {
+                        manager.send_data("conn", &data).unwrap();
+                    }
+                }
+            })
+        }).collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        manager.remove_connection("conn");
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let events: Vec<&str> = body.split("\n\n").filter(|block| !block.is_empty()).collect();
+        assert_eq!(events.len(), SENDERS * EVENTS);
+
+        let mut next = vec![0usize; SENDERS];
+        for event in events {
+            let lines: Vec<&str> = event.lines().collect();
+            let data_lines = if lines[0].starts_with("event: ") { &lines[1..] } else { &lines[..] };
+            assert_eq!(data_lines.len(), 2, "事件被拆散: {:?}", event);
+            let first = data_lines[0].strip_prefix("data: ").unwrap();
+            let mut fields = first.split('-');
+            let sender: usize = fields.next().unwrap().parse().unwrap();
+            let i: usize = fields.next().unwrap().parse().unwrap();
+            assert_eq!(data_lines[1], format!("data: {}-{}-b", sender, i));
+            if i % 2 == 0 {
+                assert_eq!(lines[0], format!("event: t{}", sender));
+            }
+            // 同一发送方的消息保持顺序
+            assert_eq!(next[sender], i);
+            next[sender] += 1;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_until_disconnect() {
+        let manager = GlobalSseManager::new().with_heartbeat(Duration::from_millis(20));
+        assert_eq!(manager.heartbeat_interval(), Some(Duration::from_millis(20)));
+        let mut body = manager.register_connection("conn".to_string()).unwrap().into_body();
+
+        let frame = tokio::time::timeout(Duration::from_secs(1), body.frame()).await.unwrap().unwrap().unwrap();
+        assert_eq!(frame.into_data().unwrap(), ": heartbeat\n\n");
+
+        // 普通消息与心跳各自成帧
+        manager.send_data("conn", "hello").unwrap();
+        let mut saw_data = false;
+        while !saw_data {
+            let frame = tokio::time::timeout(Duration::from_secs(1), body.frame()).await.unwrap().unwrap().unwrap();
+            let data = frame.into_data().unwrap();
+            assert!(data == ": heartbeat\n\n" || data == "data: hello\n\n", "意外的帧: {:?}", data);
+            saw_data = data == "data: hello\n\n";
+        }
+
+        // 断开后心跳任务退出，响应流随之结束
+        manager.disconnect_connection("conn");
+        let rest = tokio::time::timeout(Duration::from_secs(1), body.collect()).await.unwrap().unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&rest).ends_with("DISCONNECT_EVENT"));
+    }
+
+    #[test]
+    fn test_concurrent_registrations_respect_limit() {
+        const LIMIT: usize = 4;
+
+        let manager = Arc::new(GlobalSseManager::new());
+        manager.set_connection_limits(SseConnectionLimits::default().with_max_connections(LIMIT));
+
+        let workers: Vec<_> = (0..8).map(|worker| {
+            let manager = manager.clone();
+            std::thread::spawn(move || {
+                (0..50)
+                    .filter_map(|i| manager.try_register_connection_with_options(format!("{}-{}", worker, i), SseRegisterOptions::default()).ok())
+                    .collect::<Vec<_>>()
+            })
+        }).collect();
+        // 持有所有响应，保证接收端存活
+        let accepted: Vec<_> = workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect();
+
+        assert_eq!(accepted.len(), LIMIT);
+        assert_eq!(manager.connection_count(), LIMIT);
+    }
+
+    #[test]
+    fn test_liveness_ignores_dropped_receivers() {
+        let manager = GlobalSseManager::new();
+        let _alive = manager.register_connection("alive".to_string()).unwrap();
+        let closed = manager.register_connection("closed".to_string()).unwrap();
+        assert_eq!(manager.connection_count(), 2);
+
+        // 浏览器直接关闭：接收端被丢弃，但没有调用 disconnect_connection
+        drop(closed);
+        assert!(manager.has_connection("closed"));
+        assert!(!manager.is_connected("closed"));
+        assert!(!manager.has_connection("closed"));
+
+        drop(manager.register_connection("closed".to_string()).unwrap());
+        assert_eq!(manager.connection_count(), 1);
+        assert_eq!(manager.active_connection_ids(), vec!["alive".to_string()]);
+        assert!(manager.is_connected("alive"));
+        assert!(!manager.is_connected("unknown"));
+    }
+
+    #[tokio::test]
+    async fn test_group_broadcast() {
+        let manager = GlobalSseManager::new();
+        let first = manager.register_connection("a".to_string()).unwrap();
+        let second = manager.register_connection("b".to_string()).unwrap();
+        let _outsider = manager.register_connection("c".to_string()).unwrap();
+
+        assert!(manager.join_group("a", "room-1"));
+        assert!(manager.join_group("b", "room-1"));
+        assert!(!manager.join_group("missing", "room-1"));
+        assert_eq!(manager.broadcast_to_group("room-1", "hi"), 2);
+        assert_eq!(manager.broadcast_to_group("room-2", "hi"), 0);
+
+        // 接收端被丢弃的成员在广播时被清理
+        drop(second);
+        assert_eq!(manager.broadcast_to_group("room-1", "again"), 1);
+        assert_eq!(manager.group_members("room-1"), vec!["a".to_string()]);
+        assert!(!manager.has_connection("b"));
+
+        // 断开连接后自动退出分组，空分组被删除
+        assert!(manager.leave_group("a", "room-1") && !manager.leave_group("a", "room-1"));
+        manager.join_group("a", "room-1");
+        manager.disconnect_connection("a");
+        assert!(manager.group_members("room-1").is_empty());
+        assert!(!manager.groups.contains_key("room-1"));
+        assert!(manager.connection_groups.is_empty());
+
+        let body = first.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with("data: hi\n\ndata: again\n\n"));
+    }
+}
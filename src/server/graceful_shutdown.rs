@@ -0,0 +1,139 @@
+//! 连接级优雅关闭
+//!
+//! 引擎关闭时，路由器持有的关闭信号会通知每个连接：
+//! - hyper 连接调用 `graceful_shutdown`：HTTP/1.1 在当前响应写完后关闭 keep-alive 连接，HTTP/2 发送 GOAWAY
+//! - h2 连接同样发送 GOAWAY，已接受的流继续完成
+//! - SSE 响应体在收到信号后立即结束，长连接不会拖住关闭流程
+//!
+//! 路由器未关联引擎（例如直接调用 `Router::handle_http`）时信号永远不会触发。
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+use http_body_util::combinators::BoxBody;
+use hyper::Response;
+use hyper::body::{Body, Bytes, Frame, SizeHint};
+use tokio::sync::watch;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 引擎关闭信号
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ShutdownSignal {
+    receiver: Option<watch::Receiver<bool>>,
+}
+
+impl ShutdownSignal {
+    /// 关联引擎的关闭信号（`true` 表示开始关闭）
+    pub(crate) fn new(receiver: watch::Receiver<bool>) -> Self {
+        Self { receiver: Some(receiver) }
+    }
+
+    /// 是否关联了引擎
+    pub(crate) fn is_attached(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// 等待关闭信号；未关联引擎或引擎已被释放时永不完成
+    pub(crate) fn requested(&self) -> impl Future<Output = ()> + Send + 'static {
+        let receiver = self.receiver.clone();
+        async move {
+            if let Some(mut receiver) = receiver {
+                if receiver.wait_for(|stop| *stop).await.is_ok() {
+                    return;
+                }
+            }
+            std::future::pending::<()>().await
+        }
+    }
+}
+
+/// 关闭时结束 SSE 响应体，其他响应原样返回
+pub(crate) fn close_event_stream_on_shutdown(
+    response: Response<BoxBody<Bytes, BoxError>>,
+    signal: &ShutdownSignal,
+) -> Response<BoxBody<Bytes, BoxError>> {
+    let is_event_stream = response.headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.starts_with("text/event-stream"))
+        .unwrap_or(false);
+    if !is_event_stream || !signal.is_attached() {
+        return response;
+    }
+
+    let shutdown: Pin<Box<dyn Future<Output = ()> + Send>> = Box::pin(signal.requested());
+    response.map(|body| BoxBody::new(UntilShutdown {
+        inner: body,
+        shutdown: Mutex::new(shutdown),
+    }))
+}
+
+/// 收到关闭信号后结束的响应体
+struct UntilShutdown {
+    inner: BoxBody<Bytes, BoxError>,
+    /// 只在 `poll_frame` 中通过 `get_mut` 访问，Mutex 仅用于满足 `BoxBody` 的 `Sync` 要求
+    shutdown: Mutex<Pin<Box<dyn Future<Output = ()> + Send>>>,
+}
+
+impl Body for UntilShutdown {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, BoxError>>> {
+        let this = self.get_mut();
+        let shutdown = this.shutdown.get_mut().unwrap_or_else(|e| e.into_inner());
+        if shutdown.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+        Pin::new(&mut this.inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        // 可能提前结束，不给出长度
+        SizeHint::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::{BodyExt, StreamBody};
+
+    #[tokio::test]
+    async fn test_event_stream_ends_on_shutdown() {
+        let (tx, rx) = watch::channel(false);
+        let signal = ShutdownSignal::new(rx);
+
+        let (frames, stream) = tokio::sync::mpsc::unbounded_channel::<Result<Frame<Bytes>, BoxError>>();
+        let body = BoxBody::new(StreamBody::new(tokio_stream::wrappers::UnboundedReceiverStream::new(stream)));
+        let response = Response::builder()
+            .header(hyper::header::CONTENT_TYPE, "text/event-stream")
+            .body(body)
+            .unwrap();
+        let mut body = close_event_stream_on_shutdown(response, &signal).into_body();
+
+        frames.send(Ok(Frame::data(Bytes::from_static(b"data: 1\n\n")))).unwrap();
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!(frame.into_data().unwrap(), Bytes::from_static(b"data: 1\n\n"));
+
+        // 发送端仍然存活，关闭信号让响应体结束
+        tx.send_replace(true);
+        assert!(body.frame().await.is_none());
+        drop(frames);
+    }
+
+    #[tokio::test]
+    async fn test_detached_signal_never_fires() {
+        let signal = ShutdownSignal::default();
+        assert!(!signal.is_attached());
+        let fired = tokio::time::timeout(std::time::Duration::from_millis(20), signal.requested()).await;
+        assert!(fired.is_err());
+    }
+}
@@ -149,6 +149,44 @@ impl GrpcCodec {
         let consumed = 5 + length;
         Some((data, consumed))
     }
+
+    /// 按 gRPC 规范对 `grpc-message` 进行百分号编码
+    ///
+    /// 0x20-0x7E 之间的可打印 ASCII（`%` 除外）原样保留，其余字节（包括 UTF-8 多字节字符）编码为 `%XX`
+    pub fn encode_status_message(message: &str) -> String {
+        let mut encoded = String::with_capacity(message.len());
+        for &byte in message.as_bytes() {
+            if (0x20..=0x7E).contains(&byte) && byte != b'%' {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        encoded
+    }
+
+    /// 解码百分号编码的 `grpc-message`
+    ///
+    /// 无效的转义序列原样保留，解码结果不是合法 UTF-8 时按有损方式转换
+    pub fn decode_status_message(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' && i + 2 < bytes.len() {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok()
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                if let Some(byte) = hex {
+                    decoded.push(byte);
+                    i += 3;
+                    continue;
+                }
+            }
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
+    }
 }
 
 #[cfg(test)]
@@ -194,6 +232,18 @@ mod tests {
         assert_eq!(decoded, message);
     }
 
+    #[test]
+    fn test_status_message_percent_encoding() {
+        let encoded = GrpcCodec::encode_status_message("未知服务: a.B 100%");
+        assert!(encoded.is_ascii());
+        assert!(encoded.starts_with("%E6%9C%AA"));
+        assert!(encoded.ends_with(": a.B 100%25"));
+        assert_eq!(GrpcCodec::decode_status_message(&encoded), "未知服务: a.B 100%");
+
+        // 无效转义原样保留
+        assert_eq!(GrpcCodec::decode_status_message("50%zz%"), "50%zz%");
+    }
+
     #[test]
     fn test_create_parse_frame() {
         let data = b"hello world";
@@ -31,7 +31,7 @@ pub async fn handle_grpc_tls_connection(
 
     // 使用 tokio-rustls 进行 TLS 握手
     let acceptor = tokio_rustls::TlsAcceptor::from(server_config);
-    let tls_stream = acceptor.accept(stream).await
+    let tls_stream = crate::server::tls_handshake::accept(&acceptor, stream, router.tls_handshake_timeout(), router.metrics().map(|m| m.as_ref())).await
         .map_err(|e| {
             error!("❌ [gRPC] TLS 握手失败: {}", e);
             format!("TLS 握手失败: {}", e)
@@ -193,7 +193,7 @@ impl UnaryHandler for DelegatedUnaryHandler {
             let request_data = RequestData {
                 method: Some("POST".to_string()),
                 path: format!("/{}/{}", context.method.service, context.method.method),
-                headers: context.headers.clone(),
+                headers: context.metadata_map(),
                 body: request.data,
                 service: Some(context.method.service.clone()),
                 grpc_method: Some(context.method.method.clone()),
@@ -264,7 +264,7 @@ impl ServerStreamHandler for DelegatedServerStreamHandler {
             let request_data = RequestData {
                 method: Some("POST".to_string()),
                 path: format!("/{}/{}", context.method.service, context.method.method),
-                headers: context.headers.clone(),
+                headers: context.metadata_map(),
                 body: request.data,
                 service: Some(context.method.service.clone()),
                 grpc_method: Some(context.method.method.clone()),
@@ -335,7 +335,7 @@ impl ClientStreamHandler for DelegatedClientStreamHandler {
             let request_data = RequestData {
                 method: Some("POST".to_string()),
                 path: format!("/{}/{}", context.method.service, context.method.method),
-                headers: context.headers.clone(),
+                headers: context.metadata_map(),
                 body: Vec::new(), // 客户端流的初始请求体为空
                 service: Some(context.method.service.clone()),
                 grpc_method: Some(context.method.method.clone()),
@@ -440,7 +440,7 @@ impl BidirectionalHandler for DelegatedBidirectionalHandler {
             let request_data = RequestData {
                 method: Some("POST".to_string()),
                 path: format!("/{}/{}", context.method.service, context.method.method),
-                headers: context.headers.clone(),
+                headers: context.metadata_map(),
                 body: Vec::new(), // 双向流的初始请求体为空
                 service: Some(context.method.service.clone()),
                 grpc_method: Some(context.method.method.clone()),
@@ -42,7 +42,7 @@ where
 
     // 使用 tokio-rustls 进行 TLS 握手
     let acceptor = tokio_rustls::TlsAcceptor::from(server_config);
-    let tls_stream = acceptor.accept(stream).await
+    let tls_stream = crate::server::tls_handshake::accept(&acceptor, stream, router.tls_handshake_timeout(), router.metrics().map(|m| m.as_ref())).await
         .map_err(|e| {
             error!("❌ [gRPC h2c-over-TLS] TLS 握手失败: {}", e);
             format!("TLS 握手失败: {}", e)
@@ -70,8 +70,7 @@ where
 {
     debug!("🔧 [gRPC h2c-over-TLS] 开始处理 h2c over TLS: {}", remote_addr);
 
-    let mut h2_builder = h2::server::Builder::default();
-    h2_builder.max_frame_size(1024 * 1024);
+    let h2_builder = router.h2_config().server_builder();
 
     // h2c handshake：客户端发送的是 h2c 格式（PRI * HTTP/2.0...）
     let mut connection = h2_builder.handshake(tls_stream).await
@@ -82,10 +81,16 @@ where
 
     debug!("✅ [gRPC h2c-over-TLS] h2c 握手成功: {}", remote_addr);
 
+    // 连接级并发流限制
+    let stream_limit = router.new_grpc_stream_limit();
+
     // 处理连接上的流
     while let Some(result) = connection.accept().await {
         match result {
-            Ok((request, mut respond)) => {
+            Ok((mut request, mut respond)) => {
+                if let Some(limit) = &stream_limit {
+                    request.extensions_mut().insert(limit.clone());
+                }
                 debug!("📨 [gRPC h2c-over-TLS] 收到请求: {} {}", request.method(), request.uri());
 
                 // 处理 gRPC 请求
@@ -104,6 +109,7 @@ where
                 });
             }
             Err(e) => {
+                crate::server::h2_config::record_connection_error(&e, remote_addr, router.metrics().map(|m| m.as_ref()));
                 error!("❌ [gRPC h2c-over-TLS] 接受流失败: {}", e);
                 break;
             }
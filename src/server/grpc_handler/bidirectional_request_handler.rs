@@ -1,12 +1,13 @@
     use std::pin::Pin;
 use h2::{server::SendResponse, RecvStream};
-use hyper::http::{Request, Response, StatusCode, HeaderMap, HeaderValue};
+use hyper::http::Request;
 use bytes;
 use futures_util::StreamExt;
 use crate::server::grpc_types::*;
 use crate::utils::logger::{debug, info};
 use super::handler_traits::BidirectionalHandler;
 use super::request_handler_core::GrpcRequestHandler;
+use super::request_utils::grpc_response_headers;
 
 impl GrpcRequestHandler {
     /// 处理双向流请求
@@ -26,16 +27,13 @@ impl GrpcRequestHandler {
         
         // 调用处理器
         debug!("🔍 [DEBUG] 准备调用双向流处理器");
+        let metadata = context.response_metadata.clone();
         match handler.handle(request_stream, context).await {
             Ok(mut response_stream) => {
                 debug!("🔍 [DEBUG] 双向流处理器调用成功，准备发送响应头");
                 
                 // 发送响应头
-                let response = Response::builder()
-                    .status(StatusCode::OK)
-                    .header("content-type", "application/grpc")
-                    .header("grpc-encoding", "identity")
-                    .body(())?;
+                let response = grpc_response_headers(&metadata)?;
                 
                 debug!("🔍 [DEBUG] 响应头构建完成，准备发送");
                 let mut send_stream = respond.send_response(response, false)?;
@@ -50,6 +48,7 @@ impl GrpcRequestHandler {
                         Ok(message) => {
                             debug!("🔍 [DEBUG] 编码响应消息");
                             let data = self.encode_grpc_message(&message)?;
+                            super::metrics::add_bytes_sent(data.len());
                             debug!("🔍 [DEBUG] 发送响应数据");
                             if let Err(e) = send_stream.send_data(data.into(), false) {
                                 let error_msg = e.to_string();
@@ -67,7 +66,7 @@ impl GrpcRequestHandler {
                         }
                         Err(error) => {
                             debug!("🔍 [DEBUG] 响应流出现错误: {:?}", error);
-                            self.send_grpc_error_to_stream(&mut send_stream, error).await?;
+                            self.send_grpc_error_to_stream(&mut send_stream, error, &metadata).await?;
                             break;
                         }
                     }
@@ -78,14 +77,14 @@ impl GrpcRequestHandler {
                 // 只有在流未关闭时才发送 gRPC 状态
                 if !stream_closed {
                     debug!("🔍 [DEBUG] 发送 gRPC 状态");
-                    self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "").await?;
+                    self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "", &metadata).await?;
                 }
                 
                 debug!("🔍 [DEBUG] handle_bidirectional_request 成功完成");
             }
             Err(error) => {
                 debug!("🔍 [DEBUG] 双向流处理器调用失败: {:?}", error);
-                self.send_grpc_error(respond, error).await?;
+                self.send_grpc_error_with_metadata(respond, error, &metadata).await?;
             }
         }
         
@@ -1,10 +1,11 @@
     use h2::{server::SendResponse, RecvStream};
-use hyper::http::{Request, Response, StatusCode};
+use hyper::http::Request;
 use bytes;
 use crate::server::grpc_types::*;
 use crate::server::grpc_codec::GrpcCodec;
 use super::handler_traits::ClientStreamHandler;
 use super::request_handler_core::GrpcRequestHandler;
+use super::request_utils::grpc_response_headers;
 
 impl GrpcRequestHandler {
     /// 处理客户端流请求
@@ -15,12 +16,10 @@ impl GrpcRequestHandler {
         handler: &dyn ClientStreamHandler,
         context: GrpcContext,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // 对于客户端流，需要先发送响应头让客户端知道连接已建立
-        let response = Response::builder()
-            .status(StatusCode::OK)
-            .header("content-type", "application/grpc")
-            .header("grpc-encoding", "identity")
-            .body(())?;
+        // 对于客户端流，需要先发送响应头让客户端知道连接已建立，
+        // 因此处理器只能设置尾部元数据
+        let metadata = context.response_metadata.clone();
+        let response = grpc_response_headers(&metadata)?;
         
         let mut send_stream = respond.send_response(response, false)?;
          // 创建请求流
@@ -32,12 +31,13 @@ impl GrpcRequestHandler {
                           // 直接发送 GrpcResponse 数据，不包装成 GrpcStreamMessage
                 let data = GrpcCodec::encode_frame(&response)
                     .map_err(|e| GrpcError::Internal(format!("编码 gRPC 响应失败: {}", e)))?;
+                super::metrics::add_bytes_sent(data.len());
                 send_stream.send_data(data.into(), false)?;
                 // 发送 gRPC 状态
-                self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "").await?;
+                self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "", &metadata).await?;
             }
             Err(error) => {
-                         self.send_grpc_error_to_stream(&mut send_stream, error).await?;
+                         self.send_grpc_error_to_stream(&mut send_stream, error, &metadata).await?;
             }
         }
         
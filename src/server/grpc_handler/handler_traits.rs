@@ -2,7 +2,7 @@ use std::pin::Pin;
 use futures_util::{Stream, StreamExt};
 use crate::server::grpc_types::*;
 use crate::server::grpc_codec::GrpcCodec;
-use serde::Serialize;
+use serde::{Serialize, Deserialize};
 
 pub trait UnaryHandler: Send + Sync {
     /// 处理一元请求
@@ -94,6 +94,63 @@ where
     }
 }
 
+/// 基于异步函数的强类型服务端流处理器
+///
+/// 自动解码请求并逐条编码响应流；处理器返回错误或流中出现错误时，
+/// 注册表以该错误状态结束调用，流正常结束时发送 OK 状态
+pub struct FnServerStreamHandler<Req, Resp, F> {
+    handler: std::sync::Arc<F>,
+    _phantom: std::marker::PhantomData<fn(Req) -> Resp>,
+}
+
+impl<Req, Resp, F> FnServerStreamHandler<Req, Resp, F> {
+    pub fn new(handler: F) -> Self {
+        Self {
+            handler: std::sync::Arc::new(handler),
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<Req, Resp, F, Fut, S> ServerStreamHandler for FnServerStreamHandler<Req, Resp, F>
+where
+    Req: for<'de> Deserialize<'de> + bincode::Decode<()> + Send + 'static,
+    Resp: Serialize + bincode::Encode + Send + 'static,
+    F: Fn(Req, GrpcContext) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<S, GrpcError>> + Send + 'static,
+    S: Stream<Item = Result<Resp, GrpcError>> + Send + 'static,
+{
+    fn handle(
+        &self,
+        request: GrpcRequest<Vec<u8>>,
+        context: GrpcContext,
+    ) -> Pin<Box<dyn Future<Output = Result<Pin<Box<dyn Stream<Item = Result<GrpcStreamMessage<Vec<u8>>, GrpcError>> + Send>>, GrpcError>> + Send>> {
+        let handler = self.handler.clone();
+        Box::pin(async move {
+            let typed_request = GrpcCodec::decode::<Req>(&request.data)
+                .map_err(|e| GrpcError::InvalidArgument(format!("解码请求失败: {}", e)))?;
+            let request_id = request.id;
+
+            let typed_stream = handler(typed_request, context).await?;
+
+            let serialized_stream = typed_stream.enumerate().map(move |(sequence, item)| {
+                let data = GrpcCodec::encode(&item?)
+                    .map_err(|e| GrpcError::Internal(format!("序列化数据失败: {}", e)))?;
+                Ok(GrpcStreamMessage {
+                    id: request_id,
+                    stream_id: request_id,
+                    sequence: sequence as u64,
+                    end_of_stream: false,
+                    data,
+                    metadata: std::collections::HashMap::new(),
+                })
+            });
+
+            Ok(Box::pin(serialized_stream) as Pin<Box<dyn Stream<Item = Result<GrpcStreamMessage<Vec<u8>>, GrpcError>> + Send>>)
+        })
+    }
+}
+
 /// 客户端流处理器特征
 pub trait ClientStreamHandler: Send + Sync {
     /// 处理客户端流请求
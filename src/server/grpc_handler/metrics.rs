@@ -0,0 +1,227 @@
+//! gRPC 按方法统计的调用指标
+//!
+//! 记录每个方法的调用次数、耗时、收发消息字节数和最终状态码。
+//! 一次调用内的字节数和状态码通过 task-local 收集，发送/读取路径无需显式传递上下文；
+//! 在调用作用域之外（例如无锁模式下委托到队列的任务）记录操作会被静默忽略。
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+use dashmap::DashMap;
+use serde::Serialize;
+use crate::server::grpc_types::GrpcStatusCode;
+
+/// 标准 gRPC 状态码数量（0..=16）
+const STATUS_CODE_COUNT: usize = 17;
+
+/// 未记录状态码时的占位值
+const STATUS_UNSET: u32 = u32::MAX;
+
+/// 单个方法的累计指标
+#[derive(Debug)]
+struct MethodStats {
+    calls: AtomicU64,
+    total_latency_us: AtomicU64,
+    max_latency_us: AtomicU64,
+    bytes_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    status_counts: [AtomicU64; STATUS_CODE_COUNT],
+}
+
+impl Default for MethodStats {
+    fn default() -> Self {
+        Self {
+            calls: AtomicU64::new(0),
+            total_latency_us: AtomicU64::new(0),
+            max_latency_us: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            status_counts: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+/// 单个方法的指标快照
+#[derive(Debug, Clone, Serialize)]
+pub struct GrpcMethodSnapshot {
+    /// 调用次数
+    pub calls: u64,
+    /// 平均耗时（微秒）
+    pub avg_latency_us: u64,
+    /// 最大耗时（微秒）
+    pub max_latency_us: u64,
+    /// 收到的请求消息字节数
+    pub bytes_received: u64,
+    /// 发送的响应消息字节数
+    pub bytes_sent: u64,
+    /// 各最终状态码的次数，键为状态码名称（如 `Ok`、`NotFound`）
+    pub status_codes: BTreeMap<String, u64>,
+}
+
+/// 一次调用内收集的数据
+#[derive(Debug)]
+struct CallRecord {
+    bytes_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    status: AtomicU32,
+}
+
+tokio::task_local! {
+    static CURRENT_CALL: Arc<CallRecord>;
+}
+
+/// gRPC 方法指标注册表
+#[derive(Debug, Default)]
+pub struct GrpcMethodMetrics {
+    methods: DashMap<String, Arc<MethodStats>>,
+}
+
+impl GrpcMethodMetrics {
+    /// 创建空的指标注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在调用作用域内执行 `fut`，结束后按方法记录耗时、字节数和状态码
+    ///
+    /// 如果调用过程中没有发送过状态码，则按返回值推断：`Ok` 记为 OK，`Err` 记为 Internal
+    pub(crate) async fn track<F, T, E>(&self, method: &str, fut: F) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        let record = Arc::new(CallRecord {
+            bytes_received: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            status: AtomicU32::new(STATUS_UNSET),
+        });
+        let started = std::time::Instant::now();
+        let result = CURRENT_CALL.scope(record.clone(), fut).await;
+
+        let status = match record.status.load(Ordering::Relaxed) {
+            STATUS_UNSET if result.is_ok() => GrpcStatusCode::Ok,
+            STATUS_UNSET => GrpcStatusCode::Internal,
+            code => GrpcStatusCode::from_u32(code).unwrap_or(GrpcStatusCode::Unknown),
+        };
+        self.record(
+            method,
+            started.elapsed(),
+            record.bytes_received.load(Ordering::Relaxed),
+            record.bytes_sent.load(Ordering::Relaxed),
+            status,
+        );
+        result
+    }
+
+    /// 记录一次完成的调用
+    pub fn record(&self, method: &str, latency: Duration, bytes_received: u64, bytes_sent: u64, status: GrpcStatusCode) {
+        let stats = match self.methods.get(method) {
+            Some(stats) => stats.clone(),
+            None => self.methods.entry(method.to_string()).or_default().clone(),
+        };
+        let latency_us = latency.as_micros().min(u64::MAX as u128) as u64;
+
+        stats.calls.fetch_add(1, Ordering::Relaxed);
+        stats.total_latency_us.fetch_add(latency_us, Ordering::Relaxed);
+        stats.max_latency_us.fetch_max(latency_us, Ordering::Relaxed);
+        stats.bytes_received.fetch_add(bytes_received, Ordering::Relaxed);
+        stats.bytes_sent.fetch_add(bytes_sent, Ordering::Relaxed);
+        let index = (status.as_u32() as usize).min(STATUS_CODE_COUNT - 1);
+        stats.status_counts[index].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 获取单个方法的指标快照
+    pub fn method(&self, method: &str) -> Option<GrpcMethodSnapshot> {
+        self.methods.get(method).map(|stats| stats.snapshot())
+    }
+
+    /// 获取所有方法的指标快照
+    pub fn snapshot(&self) -> BTreeMap<String, GrpcMethodSnapshot> {
+        self.methods
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().snapshot()))
+            .collect()
+    }
+
+    /// 清空所有指标
+    pub fn reset(&self) {
+        self.methods.clear();
+    }
+}
+
+impl MethodStats {
+    fn snapshot(&self) -> GrpcMethodSnapshot {
+        let calls = self.calls.load(Ordering::Relaxed);
+        let total_latency_us = self.total_latency_us.load(Ordering::Relaxed);
+        let status_codes = self
+            .status_counts
+            .iter()
+            .enumerate()
+            .filter_map(|(code, count)| {
+                let count = count.load(Ordering::Relaxed);
+                let status = GrpcStatusCode::from_u32(code as u32)?;
+                (count > 0).then(|| (format!("{:?}", status), count))
+            })
+            .collect();
+
+        GrpcMethodSnapshot {
+            calls,
+            avg_latency_us: if calls == 0 { 0 } else { total_latency_us / calls },
+            max_latency_us: self.max_latency_us.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            status_codes,
+        }
+    }
+}
+
+/// 记录当前调用收到的字节数
+pub(crate) fn add_bytes_received(len: usize) {
+    let _ = CURRENT_CALL.try_with(|call| call.bytes_received.fetch_add(len as u64, Ordering::Relaxed));
+}
+
+/// 记录当前调用发送的字节数
+pub(crate) fn add_bytes_sent(len: usize) {
+    let _ = CURRENT_CALL.try_with(|call| call.bytes_sent.fetch_add(len as u64, Ordering::Relaxed));
+}
+
+/// 记录当前调用的最终状态码
+pub(crate) fn set_status(status: GrpcStatusCode) {
+    let _ = CURRENT_CALL.try_with(|call| call.status.store(status.as_u32(), Ordering::Relaxed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_track_records_bytes_and_status() {
+        let metrics = GrpcMethodMetrics::new();
+
+        let result: Result<(), ()> = metrics.track("/svc.Echo/Say", async {
+            add_bytes_received(12);
+            add_bytes_sent(30);
+            set_status(GrpcStatusCode::NotFound);
+            Ok(())
+        }).await;
+        assert!(result.is_ok());
+
+        let _: Result<(), ()> = metrics.track("/svc.Echo/Say", async { Err(()) }).await;
+
+        let snapshot = metrics.method("/svc.Echo/Say").unwrap();
+        assert_eq!(snapshot.calls, 2);
+        assert_eq!(snapshot.bytes_received, 12);
+        assert_eq!(snapshot.bytes_sent, 30);
+        assert_eq!(snapshot.status_codes.get("NotFound"), Some(&1));
+        assert_eq!(snapshot.status_codes.get("Internal"), Some(&1));
+        assert!(metrics.method("/svc.Echo/Other").is_none());
+    }
+
+    #[test]
+    fn test_recording_outside_call_is_ignored() {
+        add_bytes_received(1);
+        add_bytes_sent(1);
+        set_status(GrpcStatusCode::Ok);
+    }
+}
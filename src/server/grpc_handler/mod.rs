@@ -36,12 +36,14 @@ pub mod bidirectional_request_handler;
 pub mod request_utils;
 pub mod request_stream;
 pub mod service_registry_default;
+pub mod metrics;
 
 // 重新导出所有公共API，保持与原模块的兼容性
 
 // 数据类型
 pub use types::{
     GrpcTask,
+    GrpcStreamLimit,
     GrpcConnectionType,
     GrpcConnection,
 };
@@ -62,6 +64,7 @@ pub use handler_traits::{
     ServerStreamHandler,
     TypedServerStreamHandler,
     TypedServerStreamAdapter,
+    FnServerStreamHandler,
     ClientStreamHandler,
     BidirectionalHandler,
 };
@@ -77,6 +80,12 @@ pub use request_utils::{
     // 现在可能需要调整为 pub(crate) 或提供公共接口
 };
 
+// 调用指标
+pub use metrics::{
+    GrpcMethodMetrics,
+    GrpcMethodSnapshot,
+};
+
 // 请求流
 pub use request_stream::{
     GrpcRequestStream,
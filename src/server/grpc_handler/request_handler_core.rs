@@ -5,29 +5,72 @@ use bytes;
 use crate::server::grpc_types::*;
 use crate::utils::logger::{info, warn, debug, error};
 use super::service_registry::GrpcServiceRegistry;
+use super::metrics::GrpcMethodMetrics;
 use super::types::*;
 
 pub struct GrpcRequestHandler {
     registry: Arc<RwLock<GrpcServiceRegistry>>,
+    metrics: Arc<GrpcMethodMetrics>,
 }
 
 impl GrpcRequestHandler {
     /// 创建新的请求处理器
     pub fn new(registry: Arc<RwLock<GrpcServiceRegistry>>) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            metrics: Arc::new(GrpcMethodMetrics::new()),
+        }
+    }
+
+    /// 获取按方法统计的调用指标
+    pub fn metrics(&self) -> &Arc<GrpcMethodMetrics> {
+        &self.metrics
     }
     
     /// 处理 gRPC 请求（集成无锁队列和向下委托）
     pub async fn handle_request(
         &self,
         request: Request<RecvStream>,
-        mut respond: SendResponse<bytes::Bytes>,
+        respond: SendResponse<bytes::Bytes>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let method = self.extract_grpc_method(&request)?;
+        let method = match self.extract_grpc_method(&request) {
+            Ok(method) => method,
+            Err(_) => {
+                // 路径不合法时按 gRPC 规范返回 UNIMPLEMENTED，而不是直接重置流
+                warn!("❌ 无效的 gRPC 方法路径: {}", request.uri().path());
+                let error = GrpcError::Unimplemented(format!("无效的方法路径: {}", request.uri().path()));
+                return self.send_grpc_error(respond, error).await;
+            }
+        };
+        // 连接级并发流限制：许可在整个 RPC 处理期间持有，无锁模式下随任务移交给工作线程，直到流结束
+        let stream_permit = match request.extensions().get::<GrpcStreamLimit>() {
+            Some(limit) => match limit.try_acquire() {
+                Some(permit) => Some(permit),
+                None => {
+                    warn!("🚫 连接上的并发 gRPC 流已达上限 {}，拒绝: {}", limit.limit(), method);
+                    let error = GrpcError::ResourceExhausted(format!("连接上的并发流已达上限 {}", limit.limit()));
+                    return self.send_grpc_error(respond, error).await;
+                }
+            },
+            None => None,
+        };
         let context = self.create_grpc_context(&request);
         
         debug!("🔄 处理 gRPC 请求: {}", method);
         
+        let method_name = method.clone();
+        self.metrics.track(&method_name, self.dispatch_request(request, respond, method, context, stream_permit)).await
+    }
+
+    /// 按注册表模式分发请求
+    async fn dispatch_request(
+        &self,
+        request: Request<RecvStream>,
+        respond: SendResponse<bytes::Bytes>,
+        method: String,
+        context: GrpcContext,
+        permit: Option<tokio::sync::OwnedSemaphorePermit>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // 检查是否启用无锁模式
         let lockfree_enabled = {
             let registry = self.registry.read().unwrap();
@@ -37,11 +80,13 @@ impl GrpcRequestHandler {
         if lockfree_enabled {
             // 无锁模式：向下委托任务
             debug!("🚀 使用无锁模式处理 gRPC 请求");
-            self.handle_request_lockfree(request, respond, method, context).await
+            self.handle_request_lockfree(request, respond, method, context, permit).await
         } else {
             // 传统模式：直接处理
             debug!("🔄 使用传统模式处理 gRPC 请求");
-            self.handle_request_traditional(request, respond, method, context).await
+            let result = self.handle_request_traditional(request, respond, method, context).await;
+            drop(permit);
+            result
         }
     }
     
@@ -52,6 +97,7 @@ impl GrpcRequestHandler {
         respond: SendResponse<bytes::Bytes>,
         method: String,
         context: GrpcContext,
+        permit: Option<tokio::sync::OwnedSemaphorePermit>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // 获取处理器类型，避免长时间持有锁
         let handler_type = {
@@ -78,6 +124,7 @@ impl GrpcRequestHandler {
                     request: grpc_request,
                     context,
                     respond: Some(respond),
+                    permit,
                 };
                 
                 let registry = self.registry.read().unwrap();
@@ -94,6 +141,7 @@ impl GrpcRequestHandler {
                     request: grpc_request,
                     context,
                     respond: Some(respond),
+                    permit,
                 };
                 
                 let registry = self.registry.read().unwrap();
@@ -110,6 +158,7 @@ impl GrpcRequestHandler {
                     request_stream: Some(request_stream),
                     context,
                     respond: Some(respond),
+                    permit,
                 };
                 
                 let registry = self.registry.read().unwrap();
@@ -119,7 +168,7 @@ impl GrpcRequestHandler {
             _ => {
                 // 方法未找到
                 warn!("❌ gRPC 方法未找到: {}", method);
-                self.send_grpc_error(respond, GrpcError::Unimplemented(format!("方法未实现: {}", method))).await?;
+                self.send_grpc_error(respond, self.unimplemented_error(&method)).await?;
             }
         }
         
@@ -191,8 +240,128 @@ impl GrpcRequestHandler {
             _ => {
                 // 方法未找到
                 warn!("❌ gRPC 方法未找到: {}", method);
-                self.send_grpc_error(respond, GrpcError::Unimplemented(format!("方法未实现: {}", method))).await
+                self.send_grpc_error(respond, self.unimplemented_error(&method)).await
             }
         }
     }
+
+    /// 为未注册的方法构造 UNIMPLEMENTED 错误，区分服务不存在和方法不存在
+    fn unimplemented_error(&self, method: &str) -> GrpcError {
+        let path = method.trim_start_matches('/');
+        let Some((service, name)) = path.rsplit_once('/') else {
+            return GrpcError::Unimplemented(format!("无效的方法路径: {}", method));
+        };
+
+        let service_prefix = format!("{}/", service);
+        let service_known = self.registry.read().unwrap()
+            .list_methods()
+            .iter()
+            .any(|registered| registered.trim_start_matches('/').starts_with(&service_prefix));
+
+        if service_known {
+            GrpcError::Unimplemented(format!("服务 {} 中不存在方法 {}", service, name))
+        } else {
+            GrpcError::Unimplemented(format!("未知服务: {}", service))
+        }
+    }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::pin::Pin;
+    use super::super::handler_traits::UnaryHandler;
+
+    struct NoopHandler;
+
+    impl UnaryHandler for NoopHandler {
+        fn handle(
+            &self,
+            _request: GrpcRequest<Vec<u8>>,
+            _context: GrpcContext,
+        ) -> Pin<Box<dyn Future<Output = Result<GrpcResponse<Vec<u8>>, GrpcError>> + Send>> {
+            Box::pin(async { Err(GrpcError::Internal("不应被调用".to_string())) })
+        }
+    }
+
+    /// 启动内存中的 h2 服务端，为每个流附加可选的连接级流限制
+    async fn serve(handler: Arc<GrpcRequestHandler>, limit: Option<GrpcStreamLimit>) -> h2::client::SendRequest<bytes::Bytes> {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+        tokio::spawn(async move {
+            let mut connection = h2::server::handshake(server_io).await.unwrap();
+            while let Some(Ok((mut request, respond))) = connection.accept().await {
+                if let Some(limit) = &limit {
+                    request.extensions_mut().insert(limit.clone());
+                }
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let _ = handler.handle_request(request, respond).await;
+                });
+            }
+        });
+
+        let (client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+        client
+    }
+
+    fn grpc_request(path: &str) -> Request<()> {
+        Request::builder()
+            .method("POST")
+            .uri(format!("http://localhost{}", path))
+            .header("content-type", "application/grpc")
+            .header("te", "trailers")
+            .body(())
+            .unwrap()
+    }
+
+    async fn call(handler: Arc<GrpcRequestHandler>, path: &str) -> hyper::http::HeaderMap {
+        let mut client = serve(handler, None).await;
+        let (response, _) = client.send_request(grpc_request(path), true).unwrap();
+        let response = response.await.unwrap();
+        assert_eq!(response.status(), hyper::StatusCode::OK);
+        assert!(response.body().is_end_stream());
+        response.headers().clone()
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method_returns_unimplemented() {
+        let mut registry = GrpcServiceRegistry::new();
+        registry.register_unary("/echo.Echo/Say", NoopHandler);
+        let handler = Arc::new(GrpcRequestHandler::new(Arc::new(RwLock::new(registry))));
+
+        let headers = call(handler.clone(), "/echo.Echo/Shout").await;
+        assert_eq!(headers["grpc-status"], "12");
+        let message = headers["grpc-message"].to_str().unwrap();
+        assert!(message.is_ascii());
+        assert_eq!(crate::server::grpc_codec::GrpcCodec::decode_status_message(message), "服务 echo.Echo 中不存在方法 Shout");
+
+        let headers = call(handler, "/missing.Service/Call").await;
+        assert_eq!(headers["grpc-status"], "12");
+        let message = headers["grpc-message"].to_str().unwrap();
+        assert!(message.is_ascii());
+        assert_eq!(crate::server::grpc_codec::GrpcCodec::decode_status_message(message), "未知服务: missing.Service");
+    }
+
+    #[tokio::test]
+    async fn test_stream_limit_rejects_with_resource_exhausted() {
+        let mut registry = GrpcServiceRegistry::new();
+        registry.register_unary("/echo.Echo/Say", NoopHandler);
+        let handler = Arc::new(GrpcRequestHandler::new(Arc::new(RwLock::new(registry))));
+        let mut client = serve(handler, Some(GrpcStreamLimit::new(1))).await;
+
+        // 第一个流不结束请求体，处理器一直等待读取，占住唯一的许可
+        let (first, _body) = client.send_request(grpc_request("/echo.Echo/Say"), false).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let (second, _) = client.send_request(grpc_request("/echo.Echo/Say"), true).unwrap();
+        let second = second.await.unwrap();
+        assert_eq!(second.headers()["grpc-status"], "8");
+
+        drop(first);
+    }
+}
\ No newline at end of file
@@ -98,6 +98,7 @@ impl Stream for GrpcRequestStream {
         // 读取更多数据
         match this.body.poll_data(cx) {
             Poll::Ready(Some(Ok(chunk))) => {
+                super::metrics::add_bytes_received(chunk.len());
                 // 释放流控制容量
                 if let Err(e) = this.body.flow_control().release_capacity(chunk.len()) {
                     println!("DEBUG: 释放流控制容量失败: {}", e);
@@ -1,4 +1,3 @@
-    use std::collections::HashMap;
 use std::pin::Pin;
 use futures_util::Stream;
 use std::sync::Arc;
@@ -8,9 +7,123 @@ use bytes;
 use pin_project_lite::pin_project;
 use crate::server::grpc_types::*;
 use crate::server::grpc_codec::GrpcCodec;
+use crate::server::grpc_metadata::ResponseMetadata;
 use crate::utils::logger::{debug, info, error};
 use super::request_handler_core::GrpcRequestHandler;
 use super::request_stream::GrpcRequestStream;
+use super::metrics;
+
+/// 判断发送失败是否由客户端断开引起
+pub(crate) fn is_stream_closed_error(error_msg: &str) -> bool {
+    error_msg.contains("inactive stream") ||
+        error_msg.contains("closed") ||
+        error_msg.contains("broken pipe") ||
+        error_msg.contains("connection reset")
+}
+
+/// 构建 trailers-only 响应
+///
+/// HTTP 200，grpc-status / grpc-message 直接放在唯一的 HEADERS 帧中，不发送 DATA；
+/// 处理器设置的头部和尾部元数据也一起放在这个 HEADERS 帧中
+pub(crate) fn trailers_only_response(
+    status: GrpcStatusCode,
+    message: &str,
+    metadata: &ResponseMetadata,
+) -> Result<Response<()>, hyper::http::Error> {
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "application/grpc")
+        .header("grpc-status", status.as_u32().to_string());
+    if !message.is_empty() {
+        builder = builder.header("grpc-message", GrpcCodec::encode_status_message(message));
+    }
+    let mut response = builder.body(())?;
+    metadata.apply_leading(response.headers_mut());
+    metadata.apply_trailing(response.headers_mut());
+    Ok(response)
+}
+
+/// 构建带头部元数据的正常响应头
+pub(crate) fn grpc_response_headers(metadata: &ResponseMetadata) -> Result<Response<()>, hyper::http::Error> {
+    let mut response = Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "application/grpc")
+        .header("grpc-encoding", "identity")
+        .body(())?;
+    metadata.apply_leading(response.headers_mut());
+    Ok(response)
+}
+
+/// 构建状态 trailers，并附加尾部元数据
+pub(crate) fn grpc_status_trailers(
+    status: GrpcStatusCode,
+    message: &str,
+    metadata: &ResponseMetadata,
+) -> Result<HeaderMap, hyper::header::InvalidHeaderValue> {
+    let mut trailers = HeaderMap::new();
+    metadata.apply_trailing(&mut trailers);
+    trailers.insert("grpc-status", HeaderValue::from_str(&status.as_u32().to_string())?);
+    if !message.is_empty() {
+        trailers.insert("grpc-message", HeaderValue::from_str(&GrpcCodec::encode_status_message(message))?);
+    }
+    Ok(trailers)
+}
+
+/// gRPC 流式响应发送器
+///
+/// 响应头可延迟到第一条消息时才发送；如果在任何消息之前就结束（处理器报错或流为空），
+/// 则改为发送 trailers-only 响应
+pub(crate) struct GrpcResponseSender {
+    respond: SendResponse<bytes::Bytes>,
+    send_stream: Option<h2::SendStream<bytes::Bytes>>,
+    metadata: ResponseMetadata,
+}
+
+impl GrpcResponseSender {
+    pub(crate) fn new(respond: SendResponse<bytes::Bytes>, metadata: ResponseMetadata) -> Self {
+        Self { respond, send_stream: None, metadata }
+    }
+
+    /// 发送响应头（已发送时不做任何事）
+    pub(crate) fn send_headers(&mut self) -> Result<&mut h2::SendStream<bytes::Bytes>, h2::Error> {
+        if self.send_stream.is_none() {
+            let response = grpc_response_headers(&self.metadata).expect("静态 gRPC 响应头");
+            self.send_stream = Some(self.respond.send_response(response, false)?);
+        }
+        Ok(self.send_stream.as_mut().expect("响应头已发送"))
+    }
+
+    /// 发送一条已编码的消息（首条消息前会先发送响应头）
+    pub(crate) fn send_data(&mut self, data: bytes::Bytes) -> Result<(), h2::Error> {
+        metrics::add_bytes_sent(data.len());
+        self.send_headers()?.send_data(data, false)
+    }
+
+    /// 结束响应：已发送过消息时发送 trailers，否则发送 trailers-only 响应
+    pub(crate) fn finish(mut self, status: GrpcStatusCode, message: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        metrics::set_status(status);
+        let result = match self.send_stream.as_mut() {
+            Some(send_stream) => send_stream.send_trailers(grpc_status_trailers(status, message, &self.metadata)?),
+            None => self.respond
+                .send_response(trailers_only_response(status, message, &self.metadata)?, true)
+                .map(|_| ()),
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) if is_stream_closed_error(&e.to_string()) => {
+                info!("ℹ️ [服务端] 客户端连接已关闭，gRPC 状态发送被忽略");
+                Ok(())
+            }
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// 以错误结束响应
+    pub(crate) fn finish_with_error(self, error: GrpcError) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.finish(error.status_code(), error.message())
+    }
+}
 
 impl GrpcRequestHandler {
     /// 提取 gRPC 方法名
@@ -28,25 +141,26 @@ impl GrpcRequestHandler {
     
     /// 创建 gRPC 上下文
     pub(crate) fn create_grpc_context(&self, request: &Request<RecvStream>) -> GrpcContext {
-        let mut metadata = HashMap::new();
-        
-        // 提取请求头作为元数据
-        for (name, value) in request.headers() {
-            if let Ok(value_str) = value.to_str() {
-                metadata.insert(name.to_string(), value_str.to_string());
-            }
-        }
-        
         // 从请求扩展中获取远程地址（如果可用）
         let remote_addr = request.extensions()
             .get::<std::net::SocketAddr>()
             .copied();
         
+        // 解析客户端截止时间
+        let timeout = request.headers()
+            .get("grpc-timeout")
+            .and_then(|v| v.to_str().ok())
+            .and_then(GrpcContext::parse_grpc_timeout);
+        let deadline = timeout.map(|t| std::time::Instant::now() + t);
+        
         GrpcContext {
             remote_addr,
-            headers: metadata,
             method: GrpcMethodDescriptor::from_path(request.uri().path(), GrpcMethodType::Unary)
                 .unwrap_or_else(|| GrpcMethodDescriptor::new("unknown", "unknown", GrpcMethodType::Unary)),
+            metadata: request.headers().clone(),
+            timeout,
+            deadline,
+            response_metadata: ResponseMetadata::default(),
         }
     }
     
@@ -72,6 +186,7 @@ impl GrpcRequestHandler {
                 }
             }
         }
+        metrics::add_bytes_received(data.len());
         
         self.decode_grpc_request(&data, &context)
     }
@@ -94,7 +209,7 @@ impl GrpcRequestHandler {
                     id: 0, // 默认 ID
                     method: context.method.method.clone(),
                     data: payload.to_vec(),
-                    metadata: context.headers.clone(),
+                    metadata: context.metadata_map(),
                 };
                 Ok(request)
             }
@@ -128,10 +243,12 @@ impl GrpcRequestHandler {
         &self,
         mut respond: SendResponse<bytes::Bytes>,
         response: GrpcResponse<Vec<u8>>,
+        metadata: &ResponseMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // 直接使用 response.data，不再序列化整个 GrpcResponse 结构体
         // 因为 response.data 已经包含了序列化后的实际响应数据
         let response_data = response.data;
+        metrics::set_status(GrpcStatusCode::from_u32(response.status).unwrap_or(GrpcStatusCode::Unknown));
         
         // 构建 gRPC 消息格式（5字节头部 + 数据）
         let mut data = Vec::new();
@@ -145,13 +262,10 @@ impl GrpcRequestHandler {
         
         // 消息数据
         data.extend_from_slice(&response_data);
+        metrics::add_bytes_sent(data.len());
         
-        let http_response = Response::builder()
-            .status(StatusCode::OK)
-            .header("content-type", "application/grpc")
-            .header("grpc-encoding", "identity")
-            .header("grpc-status", response.status.to_string())
-            .body(())?;
+        let mut http_response = grpc_response_headers(metadata)?;
+        http_response.headers_mut().insert("grpc-status", HeaderValue::from(response.status));
         
         let mut send_stream = respond.send_response(http_response, false)?;
         
@@ -167,9 +281,10 @@ impl GrpcRequestHandler {
         
         // 发送 gRPC 状态
         let mut trailers = HeaderMap::new();
+        metadata.apply_trailing(&mut trailers);
         trailers.insert("grpc-status", HeaderValue::from_str(&response.status.to_string())?);
         if !response.message.is_empty() {
-            trailers.insert("grpc-message", HeaderValue::from_str(&response.message)?);
+            trailers.insert("grpc-message", HeaderValue::from_str(&GrpcCodec::encode_status_message(&response.message))?);
         }
         
         // 容错处理：如果流已经关闭，不记录为错误
@@ -186,16 +301,22 @@ impl GrpcRequestHandler {
     
     /// 发送 gRPC 错误
     pub(crate) async fn send_grpc_error(
+        &self,
+        respond: SendResponse<bytes::Bytes>,
+        error: GrpcError,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.send_grpc_error_with_metadata(respond, error, &ResponseMetadata::default()).await
+    }
+    
+    /// 发送 gRPC 错误，并附带处理器设置的元数据
+    pub(crate) async fn send_grpc_error_with_metadata(
         &self,
         mut respond: SendResponse<bytes::Bytes>,
         error: GrpcError,
+        metadata: &ResponseMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let http_response = Response::builder()
-            .status(StatusCode::OK)
-            .header("content-type", "application/grpc")
-            .header("grpc-status", error.status_code().as_u32().to_string())
-            .header("grpc-message", error.message())
-            .body(())?;
+        metrics::set_status(error.status_code());
+        let http_response = trailers_only_response(error.status_code(), error.message(), metadata)?;
         
         if let Err(e) = respond.send_response(http_response, true) {
             let error_msg = e.to_string();
@@ -218,10 +339,10 @@ impl GrpcRequestHandler {
         &self,
         send_stream: &mut h2::SendStream<bytes::Bytes>,
         error: GrpcError,
+        metadata: &ResponseMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut trailers = HeaderMap::new();
-        trailers.insert("grpc-status", HeaderValue::from_str(&error.status_code().as_u32().to_string())?);
-        trailers.insert("grpc-message", HeaderValue::from_str(&error.message())?);
+        metrics::set_status(error.status_code());
+        let trailers = grpc_status_trailers(error.status_code(), error.message(), metadata)?;
         
         match send_stream.send_trailers(trailers) {
             Ok(_) => Ok(()),
@@ -246,12 +367,10 @@ impl GrpcRequestHandler {
         send_stream: &mut h2::SendStream<bytes::Bytes>,
         status: GrpcStatusCode,
         message: &str,
+        metadata: &ResponseMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut trailers = HeaderMap::new();
-        trailers.insert("grpc-status", HeaderValue::from_str(&status.as_u32().to_string())?);
-        if !message.is_empty() {
-            trailers.insert("grpc-message", HeaderValue::from_str(message)?);
-        }
+        metrics::set_status(status);
+        let trailers = grpc_status_trailers(status, message, metadata)?;
         
         if let Err(e) = send_stream.send_trailers(trailers) {
             let error_msg = e.to_string();
@@ -268,3 +387,98 @@ impl GrpcRequestHandler {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_error_before_first_message_is_trailers_only() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+        let server = tokio::spawn(async move {
+            let mut connection = h2::server::handshake(server_io).await.unwrap();
+            let (_request, respond) = connection.accept().await.unwrap().unwrap();
+            GrpcResponseSender::new(respond, ResponseMetadata::default())
+                .finish_with_error(GrpcError::Unauthenticated("invalid token".to_string()))
+                .unwrap();
+            // 继续驱动连接，把帧写出
+            while connection.accept().await.is_some() {}
+        });
+
+        let (mut client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("http://localhost/test.Service/Method")
+            .header("content-type", "application/grpc")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = response.await.unwrap();
+
+        // 唯一的 HEADERS 帧携带状态并结束流：没有 DATA，也没有单独的 trailers
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["grpc-status"],
+            GrpcStatusCode::Unauthenticated.as_u32().to_string().as_str()
+        );
+        assert_eq!(response.headers()["grpc-message"], "invalid token");
+        let mut body = response.into_body();
+        assert!(body.is_end_stream());
+        assert!(body.data().await.is_none());
+        assert!(body.trailers().await.unwrap().is_none());
+
+        drop(client);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_response_metadata_in_headers_and_trailers() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+        let server = tokio::spawn(async move {
+            let mut connection = h2::server::handshake(server_io).await.unwrap();
+            let (_request, respond) = connection.accept().await.unwrap().unwrap();
+            let metadata = ResponseMetadata::new();
+            metadata.set_header("x-request-id", "r-42").unwrap();
+            metadata.set_trailer_bin("x-checksum-bin", &[1, 2, 3]).unwrap();
+            let mut sender = GrpcResponseSender::new(respond, metadata.clone());
+            sender.send_data(bytes::Bytes::from_static(&[0, 0, 0, 0, 0])).unwrap();
+            // 响应头发送后不能再设置头部元数据
+            assert!(metadata.set_header("x-late", "1").is_err());
+            sender.finish(GrpcStatusCode::Ok, "").unwrap();
+            while connection.accept().await.is_some() {}
+        });
+
+        let (mut client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("http://localhost/test.Service/Method")
+            .header("content-type", "application/grpc")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = response.await.unwrap();
+        let headers = response.headers().clone();
+        let mut body = response.into_body();
+        while body.data().await.is_some() {}
+        let trailers = body.trailers().await.unwrap().unwrap();
+
+        let received = crate::server::grpc_metadata::GrpcResponseMetadata { headers, trailers };
+        assert_eq!(received.header("x-request-id"), Some("r-42"));
+        assert!(received.header("x-late").is_none());
+        assert_eq!(received.trailer("grpc-status"), Some("0"));
+        assert_eq!(received.trailer_bin("x-checksum-bin").unwrap(), vec![1, 2, 3]);
+
+        drop(client);
+        server.abort();
+    }
+}
@@ -1,19 +1,20 @@
     use std::pin::Pin;
 use h2::{server::SendResponse, RecvStream};
-use hyper::http::{Request, Response, StatusCode, HeaderMap, HeaderValue};
+use hyper::http::Request;
 use bytes;
-use futures_util::StreamExt;
+use futures_util::{FutureExt, StreamExt};
 use crate::server::grpc_types::*;
-use crate::utils::logger::{info, warn, error, debug};
+use crate::utils::logger::{info, error};
 use super::handler_traits::ServerStreamHandler;
 use super::request_handler_core::GrpcRequestHandler;
+use super::request_utils::{is_stream_closed_error, GrpcResponseSender};
 
 impl GrpcRequestHandler {
     /// 处理服务端流请求
     pub(crate) async fn handle_server_stream_request(
         &self,
         request: Request<RecvStream>,
-        mut respond: SendResponse<bytes::Bytes>,
+        respond: SendResponse<bytes::Bytes>,
         handler: &dyn ServerStreamHandler,
         context: GrpcContext,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -21,97 +22,66 @@ impl GrpcRequestHandler {
         let grpc_request = self.read_grpc_request(request).await?;
         
         // 调用处理器
+        let metadata = context.response_metadata.clone();
         match handler.handle(grpc_request, context).await {
             Ok(mut stream) => {
-                // 发送响应头
-                let response = Response::builder()
-                    .status(StatusCode::OK)
-                    .header("content-type", "application/grpc")
-                    .header("grpc-encoding", "identity")
-                    .body(())?;
+                let mut sender = GrpcResponseSender::new(respond, metadata);
                 
-                let mut send_stream = match respond.send_response(response, false) {
-                    Ok(stream) => stream,
-                    Err(e) => {
-                        // 如果发送响应头失败，可能是连接已关闭
-                        if e.to_string().contains("inactive stream") || e.to_string().contains("closed") {
+                // 首个结果已就绪且为错误（或流为空）时使用 trailers-only 响应；
+                // 否则立即发送响应头，避免客户端等待第一条消息
+                let mut pending = stream.next().now_or_never();
+                if pending.is_none() {
+                    if let Err(e) = sender.send_headers() {
+                        if is_stream_closed_error(&e.to_string()) {
                             info!("ℹ️ [服务端] 客户端连接已关闭，无法发送响应头");
                             return Ok(());
                         }
                         return Err(Box::new(e));
                     }
-                };
-                
-                let mut stream_closed = false;
-                let mut error_sent = false;
+                }
                 
                 // 发送流数据
-                while let Some(result) = stream.next().await {
+                loop {
+                    let next = match pending.take() {
+                        Some(next) => next,
+                        None => stream.next().await,
+                    };
+                    let Some(result) = next else {
+                        break;
+                    };
                     match result {
                         Ok(message) => {
                             let data = match self.encode_grpc_message(&message) {
                                 Ok(data) => data,
                                 Err(e) => {
                                     error!("❌ 编码 gRPC 消息失败: {}", e);
-                                    break;
+                                    return sender.finish_with_error(e);
                                 }
                             };
                             
                             // 发送数据时检查连接状态
-                            if let Err(e) = send_stream.send_data(data.into(), false) {
+                            if let Err(e) = sender.send_data(data.into()) {
                                 let error_msg = e.to_string();
-                                if error_msg.contains("inactive stream") || 
-                                   error_msg.contains("closed") || 
-                                   error_msg.contains("broken pipe") ||
-                                   error_msg.contains("connection reset") {
+                                if is_stream_closed_error(&error_msg) {
                                     info!("ℹ️ [服务端] 客户端连接已关闭，停止发送数据");
-                                    stream_closed = true;
-                                    break;
                                 } else {
                                     error!("❌ 发送数据失败: {}", error_msg);
-                                    break;
                                 }
+                                return Ok(());
                             }
                         }
                         Err(error) => {
                             // 尝试发送错误，但如果连接已关闭则忽略
-                            let _ = self.send_grpc_error_to_stream(&mut send_stream, error).await;
-                            error_sent = true;
-                            break;
+                            return sender.finish_with_error(error);
                         }
                     }
                 }
                 
-                // 只有在流未关闭且未发送错误时才发送正常的 gRPC 状态
-                if !stream_closed && !error_sent {
-                    let _ = self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "").await;
-                }
+                sender.finish(GrpcStatusCode::Ok, "")?;
             }
             Err(error) => {
-                // 对于服务端流，当处理器返回错误时，先发送正常的响应头，然后通过 trailers 发送错误
-                let response = Response::builder()
-                    .status(StatusCode::OK)
-                    .header("content-type", "application/grpc")
-                    .header("grpc-encoding", "identity")
-                    .body(())?;
-                
-                match respond.send_response(response, false) {
-                    Ok(mut send_stream) => {
-                        // 通过 trailers 发送错误状态
-                        let _ = self.send_grpc_error_to_stream(&mut send_stream, error).await;
-                    }
-                    Err(e) => {
-                        let error_msg = e.to_string();
-                        if error_msg.contains("inactive stream") || 
-                           error_msg.contains("closed") || 
-                           error_msg.contains("broken pipe") ||
-                           error_msg.contains("connection reset") {
-                            info!("ℹ️ [服务端] 客户端连接已关闭，错误响应发送被忽略");
-                        } else {
-                            error!("❌ 发送服务端流响应头失败: {}", error_msg);
-                        }
-                    }
-                }
+                // 处理器在产生任何消息前返回错误：trailers-only 响应
+                self.send_grpc_error_with_metadata(respond, error, &metadata).await?;
             }
         }
         
@@ -7,14 +7,16 @@ use h2::server::SendResponse;
 use tokio::sync::broadcast;
 use crossbeam_queue::SegQueue;
 use dashmap::DashMap;
-use hyper::http::{Request, Response, StatusCode, HeaderMap, HeaderValue};
+use hyper::http::{HeaderMap, HeaderValue};
 use crate::server::grpc_types::*;
 use crate::server::grpc_codec::GrpcCodec;
+use crate::server::grpc_metadata::ResponseMetadata;
 use crate::utils::logger::{info, warn, debug, error};
 use crate::engine::work_stealing::WorkStealingQueue;
 use super::types::*;
 use super::handler_traits::*;
 use super::connection_manager::GrpcConnectionManager;
+use super::request_utils::{grpc_response_headers, grpc_status_trailers, trailers_only_response};
 
 pub struct GrpcServiceRegistry {
     /// 一元请求处理器
@@ -39,6 +41,8 @@ pub struct GrpcServiceRegistry {
     connection_manager: Arc<GrpcConnectionManager>,
     /// 维护任务句柄
     maintenance_handle: Option<tokio::task::JoinHandle<()>>,
+    /// 每个连接允许的最大并发 gRPC 流（None 表示不限制）
+    max_streams_per_connection: Option<usize>,
 }
 
 impl GrpcServiceRegistry {
@@ -59,6 +63,7 @@ impl GrpcServiceRegistry {
             shutdown_tx: None,
             connection_manager,
             maintenance_handle: None,
+            max_streams_per_connection: None,
         }
     }
     
@@ -80,6 +85,7 @@ impl GrpcServiceRegistry {
             shutdown_tx: None,
             connection_manager,
             maintenance_handle: None,
+            max_streams_per_connection: None,
         }
     }
     
@@ -236,37 +242,39 @@ impl GrpcServiceRegistry {
     /// 处理从队列中获取的任务
     pub async fn process_task(&self, task: GrpcTask) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         match task {
-            GrpcTask::UnaryRequest { method, request, context, respond } => {
+            GrpcTask::UnaryRequest { method, request, context, respond, permit } => {
+                // 许可随任务作用域持有，处理（包括流式发送）结束后释放
+                let _permit = permit;
                 if let Some(mut respond) = respond {
                     if let Some(handler) = self.get_unary_handler(&method) {
                         debug!("🔄 处理无锁队列中的一元请求: {}", method);
+                        let metadata = context.response_metadata.clone();
                         match handler.handle(request, context).await {
                             Ok(response) => {
                                 // 直接发送响应，不创建临时处理器
-                                self.send_unary_response(respond, response).await?;
+                                self.send_unary_response(respond, response, &metadata).await?;
                             }
                             Err(error) => {
-                                self.send_unary_error(respond, error).await?;
+                                self.send_unary_error(respond, error, &metadata).await?;
                             }
                         }
                     } else {
                         warn!("❌ 无锁队列中的一元请求处理器未找到: {}", method);
-                        self.send_unary_error(respond, GrpcError::Unimplemented(format!("方法未实现: {}", method))).await?;
+                        self.send_unary_error(respond, GrpcError::Unimplemented(format!("方法未实现: {}", method)), &ResponseMetadata::default()).await?;
                     }
                 }
             }
-            GrpcTask::ServerStreamRequest { method, request, context, respond } => {
+            GrpcTask::ServerStreamRequest { method, request, context, respond, permit } => {
+                // 许可随任务作用域持有，处理（包括流式发送）结束后释放
+                let _permit = permit;
                 if let Some(mut respond) = respond {
                     if let Some(handler) = self.get_server_stream_handler(&method) {
                         debug!("🔄 处理无锁队列中的服务端流请求: {}", method);
+                        let metadata = context.response_metadata.clone();
                         match handler.handle(request, context).await {
                             Ok(mut stream) => {
                                 // 发送响应头
-                                let response = Response::builder()
-                                    .status(StatusCode::OK)
-                                    .header("content-type", "application/grpc")
-                                    .header("grpc-encoding", "identity")
-                                    .body(())?;
+                                let response = grpc_response_headers(&metadata)?;
                                 
                                 let mut send_stream = respond.send_response(response, false)?;
                                 
@@ -285,37 +293,36 @@ impl GrpcServiceRegistry {
                                             }
                                         }
                                         Err(error) => {
-                                            self.send_grpc_error_to_stream(&mut send_stream, error).await?;
+                                            self.send_grpc_error_to_stream(&mut send_stream, error, &metadata).await?;
                                             break;
                                         }
                                     }
                                 }
                                 
                                 // 发送 gRPC 状态
-                                self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "").await?;
+                                self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "", &metadata).await?;
                             }
                             Err(error) => {
-                                self.send_unary_error(respond, error).await?;
+                                self.send_unary_error(respond, error, &metadata).await?;
                             }
                         }
                     } else {
                         warn!("❌ 无锁队列中的服务端流请求处理器未找到: {}", method);
-                        self.send_unary_error(respond, GrpcError::Unimplemented(format!("方法未实现: {}", method))).await?;
+                        self.send_unary_error(respond, GrpcError::Unimplemented(format!("方法未实现: {}", method)), &ResponseMetadata::default()).await?;
                     }
                 }
             }
-            GrpcTask::BidirectionalData { method, request_stream, context, respond } => {
+            GrpcTask::BidirectionalData { method, request_stream, context, respond, permit } => {
+                // 许可随任务作用域持有，处理（包括流式发送）结束后释放
+                let _permit = permit;
                 if let (Some(request_stream), Some(mut respond)) = (request_stream, respond) {
                     if let Some(handler) = self.get_bidirectional_handler(&method) {
                         debug!("🔄 处理无锁队列中的双向流请求: {}", method);
+                        let metadata = context.response_metadata.clone();
                         match handler.handle(request_stream, context).await {
                             Ok(mut response_stream) => {
                                 // 发送响应头
-                                let response = Response::builder()
-                                    .status(StatusCode::OK)
-                                    .header("content-type", "application/grpc")
-                                    .header("grpc-encoding", "identity")
-                                    .body(())?;
+                                let response = grpc_response_headers(&metadata)?;
                                 
                                 let mut send_stream = respond.send_response(response, false)?;
                                 
@@ -334,22 +341,22 @@ impl GrpcServiceRegistry {
                                             }
                                         }
                                         Err(error) => {
-                                            self.send_grpc_error_to_stream(&mut send_stream, error).await?;
+                                            self.send_grpc_error_to_stream(&mut send_stream, error, &metadata).await?;
                                             break;
                                         }
                                     }
                                 }
                                 
                                 // 发送 gRPC 状态
-                                self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "").await?;
+                                self.send_grpc_status(&mut send_stream, GrpcStatusCode::Ok, "", &metadata).await?;
                             }
                             Err(error) => {
-                                self.send_unary_error(respond, error).await?;
+                                self.send_unary_error(respond, error, &metadata).await?;
                             }
                         }
                     } else {
                         warn!("❌ 无锁队列中的双向流请求处理器未找到: {}", method);
-                        self.send_unary_error(respond, GrpcError::Unimplemented(format!("方法未实现: {}", method))).await?;
+                        self.send_unary_error(respond, GrpcError::Unimplemented(format!("方法未实现: {}", method)), &ResponseMetadata::default()).await?;
                     }
                 }
             }
@@ -378,6 +385,20 @@ impl GrpcServiceRegistry {
         self.server_stream_handlers.insert(method, Arc::new(handler));
     }
     
+    /// 注册强类型服务端流处理器（异步函数形式）
+    ///
+    /// 处理器接收解码后的请求并返回响应流，注册表负责驱动流、逐条编码发送并写入尾部状态
+    pub fn add_server_stream_typed<Req, Resp, S, F, Fut>(&mut self, method: impl Into<String>, handler: F)
+    where
+        Req: for<'de> serde::Deserialize<'de> + bincode::Decode<()> + Send + 'static,
+        Resp: serde::Serialize + bincode::Encode + Send + 'static,
+        S: Stream<Item = Result<Resp, GrpcError>> + Send + 'static,
+        F: Fn(Req, GrpcContext) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<S, GrpcError>> + Send + 'static,
+    {
+        self.register_server_stream(method, FnServerStreamHandler::<Req, Resp, F>::new(handler));
+    }
+    
     /// 注册客户端流处理器
     pub fn register_client_stream<H>(&mut self, method: impl Into<String>, handler: H)
     where
@@ -418,6 +439,19 @@ impl GrpcServiceRegistry {
         self.bidirectional_handlers.get(method).cloned()
     }
     
+    /// 设置每个连接允许的最大并发 gRPC 流（None 表示不限制）
+    ///
+    /// 与 HTTP/2 的 max_concurrent_streams 相互独立：超出的流照常被接受，
+    /// 但会立即以 RESOURCE_EXHAUSTED 结束，不会进入处理器
+    pub fn set_max_streams_per_connection(&mut self, limit: Option<usize>) {
+        self.max_streams_per_connection = limit;
+    }
+
+    /// 获取每个连接允许的最大并发 gRPC 流
+    pub fn max_streams_per_connection(&self) -> Option<usize> {
+        self.max_streams_per_connection
+    }
+
     /// 列出所有注册的方法
     pub fn list_methods(&self) -> Vec<String> {
         let mut methods = Vec::new();
@@ -434,6 +468,7 @@ impl GrpcServiceRegistry {
         &self,
         mut respond: SendResponse<bytes::Bytes>,
         response: GrpcResponse<Vec<u8>>,
+        metadata: &ResponseMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // 直接使用 response.data，不再序列化整个 GrpcResponse 结构体
         // 因为 response.data 已经包含了序列化后的实际响应数据
@@ -446,12 +481,8 @@ impl GrpcServiceRegistry {
         data.extend_from_slice(&length.to_be_bytes());
         data.extend_from_slice(&response_data);
         
-        let http_response = Response::builder()
-            .status(StatusCode::OK)
-            .header("content-type", "application/grpc")
-            .header("grpc-encoding", "identity")
-            .header("grpc-status", response.status.to_string())
-            .body(())?;
+        let mut http_response = grpc_response_headers(metadata)?;
+        http_response.headers_mut().insert("grpc-status", HeaderValue::from(response.status));
         
         let mut send_stream = respond.send_response(http_response, false)?;
         
@@ -467,9 +498,10 @@ impl GrpcServiceRegistry {
         
         // 发送 gRPC 状态
         let mut trailers = HeaderMap::new();
+        metadata.apply_trailing(&mut trailers);
         trailers.insert("grpc-status", HeaderValue::from_str(&response.status.to_string())?);
         if !response.message.is_empty() {
-            trailers.insert("grpc-message", HeaderValue::from_str(&response.message)?);
+            trailers.insert("grpc-message", HeaderValue::from_str(&GrpcCodec::encode_status_message(&response.message))?);
         }
         
         // 容错处理：如果流已经关闭，不记录为错误
@@ -489,13 +521,9 @@ impl GrpcServiceRegistry {
         &self,
         mut respond: SendResponse<bytes::Bytes>,
         error: GrpcError,
+        metadata: &ResponseMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let http_response = Response::builder()
-            .status(StatusCode::OK)
-            .header("content-type", "application/grpc")
-            .header("grpc-status", error.status_code().as_u32().to_string())
-            .header("grpc-message", error.message())
-            .body(())?;
+        let http_response = trailers_only_response(error.status_code(), error.message(), metadata)?;
         
         respond.send_response(http_response, true)?;
         
@@ -542,10 +570,9 @@ impl GrpcServiceRegistry {
         &self,
         send_stream: &mut h2::SendStream<bytes::Bytes>,
         error: GrpcError,
+        metadata: &ResponseMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut trailers = HeaderMap::new();
-        trailers.insert("grpc-status", HeaderValue::from_str(&error.status_code().as_u32().to_string())?);
-        trailers.insert("grpc-message", HeaderValue::from_str(&error.message())?);
+        let trailers = grpc_status_trailers(error.status_code(), error.message(), metadata)?;
         
         // 容错处理：如果流已经关闭，不记录为错误
         if let Err(e) = send_stream.send_trailers(trailers) {
@@ -565,12 +592,9 @@ impl GrpcServiceRegistry {
         send_stream: &mut h2::SendStream<bytes::Bytes>,
         status: GrpcStatusCode,
         message: &str,
+        metadata: &ResponseMetadata,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut trailers = HeaderMap::new();
-        trailers.insert("grpc-status", HeaderValue::from_str(&status.as_u32().to_string())?);
-        if !message.is_empty() {
-            trailers.insert("grpc-message", HeaderValue::from_str(message)?);
-        }
+        let trailers = grpc_status_trailers(status, message, metadata)?;
         
         // 容错处理：如果流已经关闭，不记录为错误
         if let Err(e) = send_stream.send_trailers(trailers) {
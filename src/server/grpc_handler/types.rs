@@ -10,6 +10,36 @@ use crate::server::grpc_types::*;
 use crate::server::grpc_codec::GrpcCodec;
 use serde::Serialize;
 
+/// 单个连接上的 gRPC 并发流限制
+///
+/// H2 接受循环为每个连接创建一份，通过请求扩展交给 gRPC 处理器；
+/// 每个 RPC 在处理期间（包括无锁模式下委托后的流处理）占用一个许可，许可耗尽时新的流以 RESOURCE_EXHAUSTED 拒绝
+#[derive(Clone)]
+pub struct GrpcStreamLimit {
+    permits: std::sync::Arc<tokio::sync::Semaphore>,
+    limit: usize,
+}
+
+impl GrpcStreamLimit {
+    /// 创建允许 `limit` 个并发流的限制
+    pub fn new(limit: usize) -> Self {
+        Self {
+            permits: std::sync::Arc::new(tokio::sync::Semaphore::new(limit)),
+            limit,
+        }
+    }
+
+    /// 并发流上限
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// 尝试占用一个流许可，已达上限时返回 None
+    pub(crate) fn try_acquire(&self) -> Option<tokio::sync::OwnedSemaphorePermit> {
+        self.permits.clone().try_acquire_owned().ok()
+    }
+}
+
 pub enum GrpcTask {
     /// 一元请求任务
     UnaryRequest {
@@ -17,6 +47,8 @@ pub enum GrpcTask {
         request: GrpcRequest<Vec<u8>>,
         context: GrpcContext,
         respond: Option<SendResponse<bytes::Bytes>>,
+        /// 连接级并发流许可，任务处理结束（流结束）时释放
+        permit: Option<tokio::sync::OwnedSemaphorePermit>,
     },
     /// 服务端流请求任务
     ServerStreamRequest {
@@ -24,6 +56,8 @@ pub enum GrpcTask {
         request: GrpcRequest<Vec<u8>>,
         context: GrpcContext,
         respond: Option<SendResponse<bytes::Bytes>>,
+        /// 连接级并发流许可，任务处理结束（流结束）时释放
+        permit: Option<tokio::sync::OwnedSemaphorePermit>,
     },
     /// 双向流数据任务
     BidirectionalData {
@@ -31,6 +65,8 @@ pub enum GrpcTask {
         request_stream: Option<Pin<Box<dyn Stream<Item = Result<GrpcStreamMessage<Vec<u8>>, GrpcError>> + Send>>>,
         context: GrpcContext,
         respond: Option<SendResponse<bytes::Bytes>>,
+        /// 连接级并发流许可，任务处理结束（流结束）时释放
+        permit: Option<tokio::sync::OwnedSemaphorePermit>,
     },
 }
 
@@ -18,12 +18,13 @@ impl GrpcRequestHandler {
         let grpc_request = self.read_grpc_request(request).await?;
         
         // 调用处理器
+        let metadata = context.response_metadata.clone();
         match handler.handle(grpc_request, context).await {
             Ok(response) => {
-                self.send_grpc_response(respond, response).await?;
+                self.send_grpc_response(respond, response, &metadata).await?;
             }
             Err(error) => {
-                self.send_grpc_error(respond, error).await?;
+                self.send_grpc_error_with_metadata(respond, error, &metadata).await?;
             }
         }
         
@@ -0,0 +1,255 @@
+//! gRPC 响应元数据
+//!
+//! 处理器通过 [`GrpcContext`](crate::server::grpc_types::GrpcContext) 设置响应元数据：
+//! 头部元数据随响应 HEADERS 帧发送，尾部元数据与 `grpc-status` 一起放在 trailers 中。
+//!
+//! ```ignore
+//! registry.add_unary_typed("/demo.Orders/Get", |id: u64, ctx: GrpcContext| async move {
+//!     ctx.set_header("x-request-id", "r-42")?;
+//!     ctx.set_trailer_bin("x-checksum-bin", &checksum)?;
+//!     Ok(load_order(id).await)
+//! });
+//! ```
+//!
+//! 键必须是小写字母、数字和 `-`、`_`、`.` 组成，不能以 `grpc-` 开头或使用 HTTP/2 保留头部；
+//! 二进制值的键必须以 `-bin` 结尾并按 gRPC 规范进行 base64 编码，文本值的键则不能以 `-bin` 结尾。
+//! 客户端收到的元数据通过 [`GrpcResponseMetadata`] 读取。
+
+use std::sync::{Arc, Mutex};
+
+use base64::{Engine as _, engine::general_purpose};
+use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::server::grpc_types::GrpcError;
+
+/// 不允许处理器设置的头部
+const RESERVED_KEYS: &[&str] = &[
+    "content-type",
+    "content-length",
+    "te",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+    "user-agent",
+];
+
+/// 处理器设置的响应元数据，克隆后共享同一状态
+#[derive(Debug, Clone, Default)]
+pub struct ResponseMetadata {
+    inner: Arc<Mutex<MetadataState>>,
+}
+
+#[derive(Debug, Default)]
+struct MetadataState {
+    leading: HeaderMap,
+    trailing: HeaderMap,
+    headers_sent: bool,
+}
+
+impl ResponseMetadata {
+    /// 创建空的响应元数据
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置文本头部元数据
+    pub fn set_header(&self, key: &str, value: &str) -> Result<(), GrpcError> {
+        let (name, value) = ascii_entry(key, value)?;
+        self.insert(true, name, value)
+    }
+
+    /// 设置二进制头部元数据，键必须以 `-bin` 结尾
+    pub fn set_header_bin(&self, key: &str, value: &[u8]) -> Result<(), GrpcError> {
+        let (name, value) = binary_entry(key, value)?;
+        self.insert(true, name, value)
+    }
+
+    /// 设置文本尾部元数据
+    pub fn set_trailer(&self, key: &str, value: &str) -> Result<(), GrpcError> {
+        let (name, value) = ascii_entry(key, value)?;
+        self.insert(false, name, value)
+    }
+
+    /// 设置二进制尾部元数据，键必须以 `-bin` 结尾
+    pub fn set_trailer_bin(&self, key: &str, value: &[u8]) -> Result<(), GrpcError> {
+        let (name, value) = binary_entry(key, value)?;
+        self.insert(false, name, value)
+    }
+
+    /// 当前的头部元数据
+    pub fn leading(&self) -> HeaderMap {
+        self.lock().leading.clone()
+    }
+
+    /// 当前的尾部元数据
+    pub fn trailing(&self) -> HeaderMap {
+        self.lock().trailing.clone()
+    }
+
+    /// 把头部元数据写入响应头，之后再设置头部元数据会返回错误
+    pub(crate) fn apply_leading(&self, headers: &mut HeaderMap) {
+        let mut state = self.lock();
+        state.headers_sent = true;
+        for (name, value) in std::mem::take(&mut state.leading) {
+            if let Some(name) = name {
+                headers.append(name, value);
+            }
+        }
+    }
+
+    /// 把尾部元数据写入 trailers
+    pub(crate) fn apply_trailing(&self, trailers: &mut HeaderMap) {
+        let mut state = self.lock();
+        for (name, value) in std::mem::take(&mut state.trailing) {
+            if let Some(name) = name {
+                trailers.append(name, value);
+            }
+        }
+    }
+
+    fn insert(&self, leading: bool, name: HeaderName, value: HeaderValue) -> Result<(), GrpcError> {
+        let mut state = self.lock();
+        if leading {
+            if state.headers_sent {
+                return Err(GrpcError::Internal(format!("响应头已发送，无法设置头部元数据: {}", name)));
+            }
+            state.leading.insert(name, value);
+        } else {
+            state.trailing.insert(name, value);
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MetadataState> {
+        // 元数据只做简单的插入和取出，中途 panic 不会留下不一致的状态
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 客户端收到的响应元数据
+#[derive(Debug, Clone, Default)]
+pub struct GrpcResponseMetadata {
+    /// 响应头（trailers-only 响应中也包含状态）
+    pub headers: HeaderMap,
+    /// 响应 trailers
+    pub trailers: HeaderMap,
+}
+
+impl GrpcResponseMetadata {
+    /// 读取文本头部元数据
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    /// 读取并解码二进制头部元数据
+    pub fn header_bin(&self, key: &str) -> Option<Vec<u8>> {
+        self.headers.get(key).and_then(decode_binary)
+    }
+
+    /// 读取文本尾部元数据
+    pub fn trailer(&self, key: &str) -> Option<&str> {
+        self.trailers.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    /// 读取并解码二进制尾部元数据
+    pub fn trailer_bin(&self, key: &str) -> Option<Vec<u8>> {
+        self.trailers.get(key).and_then(decode_binary)
+    }
+}
+
+/// 校验元数据键
+fn metadata_key(key: &str, binary: bool) -> Result<HeaderName, GrpcError> {
+    let valid_chars = !key.is_empty()
+        && key.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'-' | b'_' | b'.'));
+    if !valid_chars {
+        return Err(GrpcError::Internal(format!("元数据键只能包含小写字母、数字和 -_. : {:?}", key)));
+    }
+    if key.starts_with("grpc-") || RESERVED_KEYS.contains(&key) {
+        return Err(GrpcError::Internal(format!("元数据键为保留头部: {}", key)));
+    }
+    if binary != key.ends_with("-bin") {
+        let reason = if binary { "二进制元数据的键必须以 -bin 结尾" } else { "以 -bin 结尾的键只能设置二进制值" };
+        return Err(GrpcError::Internal(format!("{}: {}", reason, key)));
+    }
+    HeaderName::from_bytes(key.as_bytes())
+        .map_err(|e| GrpcError::Internal(format!("无效的元数据键 {}: {}", key, e)))
+}
+
+fn ascii_entry(key: &str, value: &str) -> Result<(HeaderName, HeaderValue), GrpcError> {
+    let name = metadata_key(key, false)?;
+    if !value.bytes().all(|b| (0x20..=0x7e).contains(&b)) {
+        return Err(GrpcError::Internal(format!("元数据 {} 的值只能包含可打印 ASCII 字符，二进制内容请使用 -bin 键", key)));
+    }
+    let value = HeaderValue::from_str(value)
+        .map_err(|e| GrpcError::Internal(format!("无效的元数据值 {}: {}", key, e)))?;
+    Ok((name, value))
+}
+
+fn binary_entry(key: &str, value: &[u8]) -> Result<(HeaderName, HeaderValue), GrpcError> {
+    let name = metadata_key(key, true)?;
+    let encoded = general_purpose::STANDARD_NO_PAD.encode(value);
+    let value = HeaderValue::from_str(&encoded).expect("base64 输出总是合法的头部值");
+    Ok((name, value))
+}
+
+/// 解码二进制元数据，接受带或不带填充的 base64
+fn decode_binary(value: &HeaderValue) -> Option<Vec<u8>> {
+    let value = value.to_str().ok()?.trim_end_matches('=');
+    general_purpose::STANDARD_NO_PAD.decode(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_validation() {
+        let metadata = ResponseMetadata::new();
+        assert!(metadata.set_header("x-request-id", "r-1").is_ok());
+        assert!(metadata.set_trailer_bin("x-checksum-bin", &[0, 255, 7]).is_ok());
+
+        assert!(metadata.set_header("X-Request-Id", "r-1").is_err());
+        assert!(metadata.set_header("grpc-status", "0").is_err());
+        assert!(metadata.set_header("content-type", "text/plain").is_err());
+        assert!(metadata.set_header("x-data-bin", "abc").is_err());
+        assert!(metadata.set_header_bin("x-data", b"abc").is_err());
+        assert!(metadata.set_trailer("x-note", "换行\n").is_err());
+    }
+
+    #[test]
+    fn test_leading_rejected_after_headers_sent() {
+        let metadata = ResponseMetadata::new();
+        metadata.set_header("x-a", "1").unwrap();
+        metadata.set_trailer("x-b", "2").unwrap();
+
+        let mut headers = HeaderMap::new();
+        metadata.clone().apply_leading(&mut headers);
+        assert_eq!(headers["x-a"], "1");
+        assert!(metadata.set_header("x-c", "3").is_err());
+
+        // 尾部元数据不受影响
+        metadata.set_trailer("x-d", "4").unwrap();
+        let mut trailers = HeaderMap::new();
+        metadata.apply_trailing(&mut trailers);
+        assert_eq!(trailers.len(), 2);
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let metadata = ResponseMetadata::new();
+        metadata.set_trailer_bin("x-raw-bin", b"\x00\x01hello\xff").unwrap();
+        let mut trailers = HeaderMap::new();
+        metadata.apply_trailing(&mut trailers);
+
+        let received = GrpcResponseMetadata { headers: HeaderMap::new(), trailers };
+        assert_eq!(received.trailer_bin("x-raw-bin").unwrap(), b"\x00\x01hello\xff");
+
+        // 其他实现可能发送带填充的 base64
+        let mut headers = HeaderMap::new();
+        headers.insert("x-raw-bin", HeaderValue::from_static("aGk="));
+        let received = GrpcResponseMetadata { headers, trailers: HeaderMap::new() };
+        assert_eq!(received.header_bin("x-raw-bin").unwrap(), b"hi");
+    }
+}
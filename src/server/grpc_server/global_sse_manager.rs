@@ -133,8 +133,7 @@ impl GlobalSseManager {
     pub fn disconnect_connection(&self, connection_id: &str) -> bool {
         if let Some((_, sender)) = self.connections.remove(connection_id) {
             // 发送断开事件（不管成功失败）
-            let _ = sender.send(Ok(hyper::body::Frame::data(Bytes::from("event: disconnect\ndata: 服务器断开连接\n\n"))));
-            let _ = sender.send(Ok(hyper::body::Frame::data(Bytes::from("DISCONNECT_EVENT"))));
+            let _ = sender.send(Ok(hyper::body::Frame::data(Bytes::from("event: disconnect\ndata: 服务器断开连接\n\nDISCONNECT_EVENT"))));
 
             // 关闭发送器
             drop(sender);
@@ -37,7 +37,7 @@ where
     println!("🔍 [DEBUG] [gRPC] 开始 TLS 握手，remote_addr={}", remote_addr);
 
     let acceptor = tokio_rustls::TlsAcceptor::from(server_config);
-    let tls_stream = acceptor.accept(stream).await
+    let tls_stream = crate::server::tls_handshake::accept(&acceptor, stream, router.tls_handshake_timeout(), router.metrics().map(|m| m.as_ref())).await
         .map_err(|e| {
             println!("❌ [DEBUG] [gRPC] TLS 握手失败，错误类型: {:?}", std::error::Error::source(&e));
             println!("❌ [DEBUG] [gRPC] 完整错误: {:?}", e);
@@ -46,6 +46,7 @@ where
         })?;
 
     info!("✅ [gRPC] TLS 握手成功: {}", remote_addr);
+    crate::server::handshake_limiter::release_handshake_permit();
 
     // 获取 ALPN 协议
     let (_tcp_stream, conn) = tls_stream.get_ref();
@@ -81,8 +82,7 @@ where
 {
     debug!("🔧 [gRPC专用] 开始处理 gRPC over HTTP/2: {}", remote_addr);
 
-    let mut h2_builder = h2::server::Builder::default();
-    h2_builder.max_frame_size(1024 * 1024);
+    let h2_builder = router.h2_config().server_builder();
 
     let mut connection = h2_builder.handshake(tls_stream).await
         .map_err(|e| {
@@ -92,10 +92,32 @@ where
 
     info!("✅ [gRPC专用] HTTP/2 连接已建立: {}", remote_addr);
 
+    // 连接级并发流限制
+    let stream_limit = router.new_grpc_stream_limit();
+
     // 处理 gRPC 请求
-    while let Some(request_result) = connection.accept().await {
+    let shutdown = router.shutdown_signal().requested();
+    tokio::pin!(shutdown);
+    let mut draining = false;
+    loop {
+        let request_result = tokio::select! {
+            request_result = connection.accept() => match request_result {
+                Some(request_result) => request_result,
+                None => break,
+            },
+            _ = &mut shutdown, if !draining => {
+                // 引擎关闭：发送 GOAWAY，不再接受新流，已接受的流继续完成
+                debug!("🛑 [gRPC专用] 引擎关闭，开始优雅关闭连接: {}", remote_addr);
+                connection.graceful_shutdown();
+                draining = true;
+                continue;
+            }
+        };
         match request_result {
-            Ok((request, respond)) => {
+            Ok((mut request, respond)) => {
+                if let Some(limit) = &stream_limit {
+                    request.extensions_mut().insert(limit.clone());
+                }
                 debug!("📥 [gRPC专用] 接收到 gRPC 请求: {} {}",
                     request.method(), request.uri().path());
 
@@ -108,6 +130,7 @@ where
                 });
             }
             Err(e) => {
+                crate::server::h2_config::record_connection_error(&e, remote_addr, router.metrics().map(|m| m.as_ref()));
                 error!("❌ [gRPC专用] 接受请求失败: {}", e);
                 break;
             }
@@ -37,7 +37,7 @@ pub async fn handle_h2_request(
     debug!("📋 [gRPC专用] 请求头:");
     for (name, value) in request.headers() {
         if let Ok(value_str) = value.to_str() {
-            debug!("   {}: {}", name, value_str);
+            debug!("   {}: {}", name, crate::utils::logger::redact_header_value(name.as_str(), value_str));
         }
     }
 
@@ -54,7 +54,7 @@ pub async fn handle_h2_request(
     // 处理 gRPC 请求
     router.handle_grpc_request(request_with_addr, respond).await
         .map_err(|e| {
-            rat_logger::error!("❌ [gRPC专用] gRPC 请求处理失败: {}", e);
+            crate::utils::logger::error!("❌ [gRPC专用] gRPC 请求处理失败: {}", e);
             format!("gRPC 请求处理失败: {}", e)
         })?;
 
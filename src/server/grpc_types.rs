@@ -550,10 +550,107 @@ impl GrpcMethodDescriptor {
 pub struct GrpcContext {
     /// 远程地址
     pub remote_addr: Option<std::net::SocketAddr>,
-    /// 请求头
-    pub headers: HashMap<String, String>,
     /// 方法描述符
     pub method: GrpcMethodDescriptor,
+    /// 请求元数据（保留多值和二进制头）
+    pub metadata: hyper::HeaderMap,
+    /// 客户端通过 `grpc-timeout` 指定的超时时间
+    pub timeout: Option<std::time::Duration>,
+    /// 根据 `grpc-timeout` 计算出的截止时间
+    pub deadline: Option<std::time::Instant>,
+    /// 处理器设置的响应元数据
+    pub response_metadata: crate::server::grpc_metadata::ResponseMetadata,
+}
+
+impl GrpcContext {
+    /// 获取请求元数据
+    pub fn metadata(&self) -> &hyper::HeaderMap {
+        &self.metadata
+    }
+
+    /// 获取单个元数据值（非 ASCII 值返回 None）
+    pub fn metadata_value(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    /// 以字符串映射形式获取请求头（非 ASCII 值被忽略，多值头只保留最后一个）
+    #[deprecated(note = "请使用 metadata() 或 metadata_value() 替代")]
+    pub fn headers(&self) -> HashMap<String, String> {
+        self.metadata_map()
+    }
+
+    /// 元数据的字符串映射，用于填充 `GrpcRequest::metadata` 等旧接口
+    pub(crate) fn metadata_map(&self) -> HashMap<String, String> {
+        self.metadata.iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect()
+    }
+
+    /// 获取对端地址
+    pub fn peer_addr(&self) -> Option<std::net::SocketAddr> {
+        self.remote_addr
+    }
+
+    /// 获取客户端指定的超时时间
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        self.timeout
+    }
+
+    /// 获取请求截止时间
+    pub fn deadline(&self) -> Option<std::time::Instant> {
+        self.deadline
+    }
+
+    /// 设置文本头部元数据（随响应头发送）
+    pub fn set_header(&self, key: &str, value: &str) -> Result<(), GrpcError> {
+        self.response_metadata.set_header(key, value)
+    }
+
+    /// 设置二进制头部元数据，键必须以 `-bin` 结尾
+    pub fn set_header_bin(&self, key: &str, value: &[u8]) -> Result<(), GrpcError> {
+        self.response_metadata.set_header_bin(key, value)
+    }
+
+    /// 设置文本尾部元数据（随 `grpc-status` 发送）
+    pub fn set_trailer(&self, key: &str, value: &str) -> Result<(), GrpcError> {
+        self.response_metadata.set_trailer(key, value)
+    }
+
+    /// 设置二进制尾部元数据，键必须以 `-bin` 结尾
+    pub fn set_trailer_bin(&self, key: &str, value: &[u8]) -> Result<(), GrpcError> {
+        self.response_metadata.set_trailer_bin(key, value)
+    }
+
+    /// 距离截止时间的剩余时间（已超时返回 0，未设置截止时间返回 None）
+    pub fn remaining(&self) -> Option<std::time::Duration> {
+        self.deadline.map(|d| d.saturating_duration_since(std::time::Instant::now()))
+    }
+
+    /// 解析 `grpc-timeout` 头
+    ///
+    /// 格式为最多 8 位数字加单位：H（时）、M（分）、S（秒）、m（毫秒）、u（微秒）、n（纳秒）
+    pub fn parse_grpc_timeout(value: &str) -> Option<std::time::Duration> {
+        use std::time::Duration;
+
+        let value = value.trim();
+        if value.len() < 2 || value.len() > 9 {
+            return None;
+        }
+        let (digits, unit) = value.split_at(value.len() - 1);
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let amount: u64 = digits.parse().ok()?;
+        match unit {
+            "H" => Some(Duration::from_secs(amount * 3600)),
+            "M" => Some(Duration::from_secs(amount * 60)),
+            "S" => Some(Duration::from_secs(amount)),
+            "m" => Some(Duration::from_millis(amount)),
+            "u" => Some(Duration::from_micros(amount)),
+            "n" => Some(Duration::from_nanos(amount)),
+            _ => None,
+        }
+    }
 }
 
 pin_project! {
@@ -0,0 +1,161 @@
+//! HTTP/2 连接参数与洪泛防护
+//!
+//! 所有 HTTP/2 接入路径（HTTP/2 over TLS、gRPC、h2c 升级以及 hyper 自动协商）共用
+//! [`H2Config`]，通过 `RatEngineBuilder::h2_config` 或 `Router::set_h2_config` 设置。
+//!
+//! - **RST_STREAM 洪泛**（CVE-2023-44487 "Rapid Reset"）：客户端打开流后立即重置，
+//!   在服务端接受之前被重置的流超过 `max_pending_accept_reset_streams` 时，
+//!   h2 发送 `GOAWAY(ENHANCE_YOUR_CALM)` 并关闭连接；服务端因协议错误主动重置的流超过
+//!   `max_local_error_reset_streams` 时同样关闭连接。
+//! - **PING 洪泛**：h2 自动回复 PING，同一时间只保留一个待发送的 PONG，
+//!   回复速度受写方向背压约束，不会无限占用内存；h2 不向应用层暴露 PING 帧，也不提供 PING 速率限制。
+//!
+//! 因洪泛被关闭的连接计入 `h2_flood_closures` 指标，包括 hyper `serve_connection` 处理的连接。
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::engine::metrics::AtomicMetrics;
+
+/// HTTP/2 服务端连接参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct H2Config {
+    /// 最大帧大小
+    pub max_frame_size: u32,
+    /// 服务端接受前就被客户端重置的流数量上限，超过后关闭连接
+    pub max_pending_accept_reset_streams: usize,
+    /// 服务端因对方违反协议而重置的流数量上限，超过后关闭连接（None 表示不限制）
+    pub max_local_error_reset_streams: Option<usize>,
+    /// 保留已重置流状态的数量上限
+    pub max_concurrent_reset_streams: usize,
+    /// 已重置流状态的保留时间
+    pub reset_stream_duration: Duration,
+}
+
+impl Default for H2Config {
+    fn default() -> Self {
+        Self {
+            max_frame_size: 1024 * 1024,
+            max_pending_accept_reset_streams: 20,
+            max_local_error_reset_streams: Some(1024),
+            max_concurrent_reset_streams: 10,
+            reset_stream_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+impl H2Config {
+    /// 设置最大帧大小
+    pub fn max_frame_size(mut self, size: u32) -> Self {
+        self.max_frame_size = size;
+        self
+    }
+
+    /// 设置接受前被重置的流数量上限
+    pub fn max_pending_accept_reset_streams(mut self, max: usize) -> Self {
+        self.max_pending_accept_reset_streams = max;
+        self
+    }
+
+    /// 设置服务端因协议错误重置的流数量上限
+    pub fn max_local_error_reset_streams(mut self, max: Option<usize>) -> Self {
+        self.max_local_error_reset_streams = max;
+        self
+    }
+
+    /// 设置已重置流状态的保留数量和时长
+    pub fn reset_stream_retention(mut self, max: usize, duration: Duration) -> Self {
+        self.max_concurrent_reset_streams = max;
+        self.reset_stream_duration = duration;
+        self
+    }
+
+    /// 创建按配置设置好的 h2 服务端构建器
+    pub fn server_builder(&self) -> h2::server::Builder {
+        let mut builder = h2::server::Builder::default();
+        builder
+            .max_frame_size(self.max_frame_size)
+            .max_pending_accept_reset_streams(self.max_pending_accept_reset_streams)
+            .max_local_error_reset_streams(self.max_local_error_reset_streams)
+            .max_concurrent_reset_streams(self.max_concurrent_reset_streams)
+            .reset_stream_duration(self.reset_stream_duration);
+        builder
+    }
+}
+
+/// 是否为 h2 检测到洪泛后主动关闭连接产生的错误
+pub fn is_flood_error(error: &h2::Error) -> bool {
+    error.reason() == Some(h2::Reason::ENHANCE_YOUR_CALM) && !error.is_remote()
+}
+
+/// 记录 HTTP/2 连接错误，因洪泛关闭的连接计入指标
+pub(crate) fn record_connection_error(error: &h2::Error, remote_addr: SocketAddr, metrics: Option<&AtomicMetrics>) {
+    if !is_flood_error(error) {
+        return;
+    }
+    crate::utils::logger::warn!("🚨 HTTP/2 连接 {} 重置流过多，已发送 GOAWAY 并关闭连接", remote_addr);
+    if let Some(metrics) = metrics {
+        metrics.record_h2_flood_closure();
+    }
+}
+
+/// 记录 hyper `serve_connection` 返回的连接错误
+///
+/// hyper 自动协商路径的错误被包装在 `hyper::Error` 中，沿错误链查找底层的 h2 错误，
+/// 因洪泛关闭的连接同样计入指标
+pub(crate) fn record_serve_error(error: &(dyn std::error::Error + 'static), remote_addr: SocketAddr, metrics: Option<&AtomicMetrics>) {
+    let mut source = Some(error);
+    while let Some(current) = source {
+        if let Some(h2_error) = current.downcast_ref::<h2::Error>() {
+            record_connection_error(h2_error, remote_addr, metrics);
+            return;
+        }
+        source = current.source();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flood_error_classification() {
+        assert!(is_flood_error(&h2::Error::from(h2::Reason::ENHANCE_YOUR_CALM)));
+        assert!(!is_flood_error(&h2::Error::from(h2::Reason::CANCEL)));
+
+        let metrics = AtomicMetrics::new();
+        let addr: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        record_connection_error(&h2::Error::from(h2::Reason::PROTOCOL_ERROR), addr, Some(&metrics));
+        assert_eq!(metrics.h2_flood_closures(), 0);
+        record_connection_error(&h2::Error::from(h2::Reason::ENHANCE_YOUR_CALM), addr, Some(&metrics));
+        assert_eq!(metrics.h2_flood_closures(), 1);
+    }
+
+    #[derive(Debug)]
+    struct Wrapped(h2::Error);
+
+    impl std::fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "connection error")
+        }
+    }
+
+    impl std::error::Error for Wrapped {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_serve_error_finds_wrapped_flood() {
+        let metrics = AtomicMetrics::new();
+        let addr: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let error: Box<dyn std::error::Error + Send + Sync> = Box::new(Wrapped(h2::Error::from(h2::Reason::ENHANCE_YOUR_CALM)));
+        record_serve_error(error.as_ref(), addr, Some(&metrics));
+        assert_eq!(metrics.h2_flood_closures(), 1);
+
+        let error: Box<dyn std::error::Error + Send + Sync> = "connection reset by peer".into();
+        record_serve_error(error.as_ref(), addr, Some(&metrics));
+        assert_eq!(metrics.h2_flood_closures(), 1);
+    }
+}
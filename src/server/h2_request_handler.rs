@@ -37,7 +37,7 @@ pub async fn handle_h2_request(
     debug!("📋 [服务端] 请求头:");
     for (name, value) in request.headers() {
         if let Ok(value_str) = value.to_str() {
-            debug!("   {}: {}", name, value_str);
+            debug!("   {}: {}", name, crate::utils::logger::redact_header_value(name.as_str(), value_str));
         }
     }
     
@@ -99,7 +99,7 @@ pub async fn handle_h2_request(
         // 处理 gRPC 请求
         router.handle_grpc_request(request_with_addr, respond).await
             .map_err(|e| {
-                rat_logger::error!("❌ [服务端] gRPC 请求处理失败: {}", e);
+                crate::utils::logger::error!("❌ [服务端] gRPC 请求处理失败: {}", e);
                 format!("gRPC 请求处理失败: {}", e)
             })?;
     } else {
@@ -110,30 +110,39 @@ pub async fn handle_h2_request(
         
         // 读取 RecvStream 数据
         let (parts, mut recv_stream) = request.into_parts();
-        let mut body_data = Vec::new();
-        
-        while let Some(chunk) = recv_stream.data().await {
-            let chunk = chunk.map_err(|e| format!("读取 HTTP/2 请求体失败: {}", e))?;
-            body_data.extend_from_slice(&chunk);
-            recv_stream.flow_control().release_capacity(chunk.len())
-                .map_err(|e| format!("HTTP/2 流量控制失败: {}", e))?;
-        }
+        let Some(body_data) = router.read_h2_body(&parts, &mut recv_stream, &mut respond, remote_addr).await else {
+            return Ok(());
+        };
         
         // 使用通用的 HttpRequest 结构体
-        let http_request = crate::server::http_request::HttpRequest::from_h2_request(
+        let tls_info = parts.extensions.get::<crate::server::http_request::TlsInfo>().cloned();
+        let mut http_request = crate::server::http_request::HttpRequest::from_h2_request(
             parts.method,
             parts.uri,
             parts.headers,
             bytes::Bytes::from(body_data),
             Some(remote_addr),
         );
+        // TLS 连接处理器写入的连接信息（明文 h2c 连接没有）
+        http_request.tls_info = tls_info;
         
         debug!("🔄 [HTTP/2] 已转换为通用 HttpRequest，调用 Router::handle_http");
         
-        // 调用 Router 的通用 handle_http 方法
-        match router.handle_http(http_request).await {
+        // 调用 Router 的通用 handle_http 方法；客户端在响应前重置流时放弃处理，
+        // 路由器会通知处理器并计入客户端断开指标
+        let handling = router.handle_http(http_request);
+        tokio::pin!(handling);
+        let result = tokio::select! {
+            result = &mut handling => result,
+            reason = futures_util::future::poll_fn(|cx| respond.poll_reset(cx)) => {
+                debug!("🔌 [HTTP/2] 客户端在响应前重置了流: {:?}", reason);
+                return Ok(());
+            }
+        };
+        match result {
             Ok(response) => {
                 debug!("✅ [HTTP/2] Router 处理成功");
+                let response = crate::server::graceful_shutdown::close_event_stream_on_shutdown(response, router.shutdown_signal());
                 
                 // 将 BoxBody 响应转换为 H2 响应
                 let (parts, mut body) = response.into_parts();
@@ -225,3 +234,62 @@ pub async fn handle_h2_request(
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Method;
+
+    #[tokio::test]
+    async fn test_truncated_body_only_resets_its_stream() {
+        let mut router = Router::new();
+        router.add_route(Method::POST, "/echo", |req| {
+            Box::pin(async move { Ok(Response::new(Full::new(req.body.clone()))) })
+        });
+        let router = Arc::new(router);
+        let remote_addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+        tokio::spawn(async move {
+            let mut connection = server::handshake(server_io).await.unwrap();
+            while let Some(Ok((request, respond))) = connection.accept().await {
+                let router = router.clone();
+                tokio::spawn(async move {
+                    let _ = handle_h2_request(request, respond, remote_addr, router).await;
+                });
+            }
+        });
+
+        let (client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        // 流 A：请求体发送到一半被客户端重置
+        let mut client = client.ready().await.unwrap();
+        let truncated = hyper::Request::post("http://localhost/echo").body(()).unwrap();
+        let (response_a, mut send_a) = client.send_request(truncated, false).unwrap();
+        send_a.send_data(Bytes::from_static(b"partial"), false).unwrap();
+        send_a.send_reset(h2::Reason::CANCEL);
+
+        // 流 B：同一连接上的正常请求不受影响
+        let mut client = client.ready().await.unwrap();
+        let request = hyper::Request::post("http://localhost/echo").body(()).unwrap();
+        let (response_b, mut send_b) = client.send_request(request, false).unwrap();
+        send_b.send_data(Bytes::from_static(b"hello"), true).unwrap();
+
+        let response_b = response_b.await.unwrap();
+        assert_eq!(response_b.status(), hyper::StatusCode::OK);
+        let mut body = response_b.into_body();
+        let mut data = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.unwrap();
+            let _ = body.flow_control().release_capacity(chunk.len());
+            data.extend_from_slice(&chunk);
+        }
+        assert_eq!(data, b"hello");
+
+        // 被重置的流不会得到响应
+        assert!(response_a.await.is_err());
+    }
+}
@@ -98,8 +98,7 @@ where
 
     info!("🔍 [服务端] 开始处理升级后的 H2C 连接: {}", remote_addr);
 
-    let mut h2_builder = h2::server::Builder::default();
-    h2_builder.max_frame_size(1024 * 1024);
+    let h2_builder = router.h2_config().server_builder();
 
     let mut connection = h2_builder.handshake(upgraded_io).await
         .map_err(|e| format!("HTTP/2 握手失败: {}", e))?;
@@ -117,6 +116,7 @@ where
                 });
             }
             Err(e) => {
+                crate::server::h2_config::record_connection_error(&e, remote_addr, router.metrics().map(|m| m.as_ref()));
                 error!("❌ [服务端] 接受 HTTP/2 请求失败: {}", e);
                 break;
             }
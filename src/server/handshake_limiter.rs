@@ -0,0 +1,99 @@
+//! 握手并发限制
+//!
+//! 每个接受的连接都会派生独立任务进行协议检测和 TLS 握手，
+//! 握手洪泛时大量半开连接会在连接池计数之前就耗尽内存。
+//! 这里用信号量限制同时进行中的握手数量：接受循环在派生任务前获取许可，
+//! 许可保存在任务本地存储中，握手（或明文连接的协议检测）完成后立即释放，
+//! 连接异常结束时随任务一起释放。
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+tokio::task_local! {
+    static HANDSHAKE_PERMIT: RefCell<Option<OwnedSemaphorePermit>>;
+}
+
+/// 握手并发限制器
+#[derive(Debug)]
+pub struct HandshakeLimiter {
+    semaphore: Arc<Semaphore>,
+    max_concurrent: usize,
+}
+
+impl HandshakeLimiter {
+    /// 创建限制器，`max_concurrent` 为同时进行中的握手上限
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
+        }
+    }
+
+    /// 等待获取握手许可
+    ///
+    /// 在接受循环中调用，许可耗尽时暂停接受新连接，多余的连接留在内核队列中
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.semaphore.clone()
+            .acquire_owned()
+            .await
+            .expect("握手信号量不会被关闭")
+    }
+
+    /// 同时进行中的握手上限
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// 当前进行中的握手数量
+    pub fn in_progress(&self) -> usize {
+        self.max_concurrent - self.semaphore.available_permits()
+    }
+}
+
+/// 在持有握手许可的任务本地作用域中运行连接处理
+pub async fn scope<F>(permit: Option<OwnedSemaphorePermit>, future: F) -> F::Output
+where
+    F: Future,
+{
+    HANDSHAKE_PERMIT.scope(RefCell::new(permit), future).await
+}
+
+/// 释放当前任务持有的握手许可（未持有时为空操作）
+pub fn release_handshake_permit() {
+    let _ = HANDSHAKE_PERMIT.try_with(|permit| permit.borrow_mut().take());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_permit_released_after_handshake() {
+        let limiter = HandshakeLimiter::new(2);
+        let permit = limiter.acquire().await;
+        assert_eq!(limiter.in_progress(), 1);
+
+        scope(Some(permit), async {
+            assert_eq!(limiter.in_progress(), 1);
+            release_handshake_permit();
+            assert_eq!(limiter.in_progress(), 0);
+            // 重复释放不会出错
+            release_handshake_permit();
+        }).await;
+    }
+
+    #[tokio::test]
+    async fn test_permit_released_when_task_ends() {
+        let limiter = HandshakeLimiter::new(1);
+        let permit = limiter.acquire().await;
+        scope(Some(permit), async {}).await;
+        assert_eq!(limiter.in_progress(), 0);
+
+        // 作用域之外释放是空操作
+        release_handshake_permit();
+    }
+}
@@ -15,7 +15,6 @@ use hyper::body::Incoming;
 use hyper::body::Bytes;
 use http_body_util::{Full, combinators::BoxBody};
 use hyper_util::rt::TokioIo;
-use hyper_util::server::conn::auto::Builder as AutoBuilder;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use futures_util::StreamExt;
@@ -71,7 +70,7 @@ where
         // 将泛型 stream 转换为 TcpStream（这里需要一些技巧）
         // 简化处理：假设 S 是 TcpStream
         println!("🔍 [服务端] 调用 acceptor.accept()...");
-        let mut tls_stream = acceptor.accept(stream).await
+        let mut tls_stream = crate::server::tls_handshake::accept(&acceptor, stream, router.tls_handshake_timeout(), router.metrics().map(|m| m.as_ref())).await
             .map_err(|e| {
                 error!("❌ [服务端] TLS 握手失败: {}", e);
                 format!("TLS 握手失败: {}", e)
@@ -89,6 +88,7 @@ where
 
         // TLS 模式强制要求 HTTP/2
         let is_http2 = alpn_protocol.as_ref().map(|p| p == b"h2").unwrap_or(false);
+        let tls_info = crate::server::http_request::TlsInfo::from_connection(conn);
         println!("🔍 [服务端] is_http2 = {}", is_http2);
 
         if !is_http2 {
@@ -120,22 +120,37 @@ where
 
         // 使用 hyper auto builder 处理 HTTP/2，通过 HyperAdapter 使用服务端连接池
         println!("🔍 [服务端] 使用 hyper auto builder 处理 HTTP/2...");
-        use hyper_util::server::conn::auto::Builder as AutoBuilder;
 
         let io = TokioIo::new(tls_stream);
-        let service = hyper::service::service_fn(move |req| {
+        let mut connection_builder = router.http_connection_builder();
+        let connection = router.new_connection_context();
+        let shutdown = router.shutdown_signal().requested();
+        let metrics = router.metrics().cloned();
+        let service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
             let adapter = adapter.clone();
+            req.extensions_mut().insert(connection.clone());
+            // TLS 连接信息随请求扩展传递给处理器
+            req.extensions_mut().insert(tls_info.clone());
             async move {
                 adapter.handle_request(req, Some(remote_addr)).await
             }
         });
 
-        if let Err(e) = AutoBuilder::new(hyper_util::rt::TokioExecutor::new())
+        let serving = connection_builder
             .http2()
             .enable_connect_protocol()
-            .serve_connection_with_upgrades(io, service)
-            .await
-        {
+            .serve_connection_with_upgrades(io, service);
+        tokio::pin!(serving);
+        let result = tokio::select! {
+            result = serving.as_mut() => result,
+            _ = shutdown => {
+                // 引擎关闭：不再接受新请求，当前响应写完后关闭连接（HTTP/2 发送 GOAWAY）
+                serving.as_mut().graceful_shutdown();
+                serving.await
+            }
+        };
+        if let Err(e) = result {
+            crate::server::h2_config::record_serve_error(e.as_ref(), remote_addr, metrics.as_deref());
             // 区分正常的客户端断开连接和真正的服务器错误
             let error_msg = e.to_string();
             if error_msg.contains("connection closed") ||
@@ -178,19 +193,33 @@ pub async fn handle_http1_connection(
     adapter: Arc<HyperAdapter>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let io = TokioIo::new(stream);
-    let service = hyper::service::service_fn(move |req| {
+    let mut connection_builder = adapter.router().http_connection_builder();
+    let connection = adapter.router().new_connection_context();
+    let shutdown = adapter.router().shutdown_signal().requested();
+    let metrics = adapter.router().metrics().cloned();
+    let service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
         let adapter = adapter.clone();
+        req.extensions_mut().insert(connection.clone());
         async move {
             adapter.handle_request(req, Some(remote_addr)).await
         }
     });
 
-    if let Err(e) = AutoBuilder::new(hyper_util::rt::TokioExecutor::new())
+    let serving = connection_builder
         .http2()
         .enable_connect_protocol()
-        .serve_connection_with_upgrades(io, service)
-        .await
-    {
+        .serve_connection_with_upgrades(io, service);
+    tokio::pin!(serving);
+    let result = tokio::select! {
+        result = serving.as_mut() => result,
+        _ = shutdown => {
+            // 引擎关闭：不再接受新请求，当前响应写完后关闭连接（HTTP/2 发送 GOAWAY）
+            serving.as_mut().graceful_shutdown();
+            serving.await
+        }
+    };
+    if let Err(e) = result {
+        crate::server::h2_config::record_serve_error(e.as_ref(), remote_addr, metrics.as_deref());
         // 区分正常的客户端断开连接和真正的服务器错误
         let error_msg = e.to_string();
         if error_msg.contains("connection closed before message completed") ||
@@ -221,19 +250,33 @@ where
     S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
 {
     let io = TokioIo::new(stream);
-    let service = hyper::service::service_fn(move |req| {
+    let mut connection_builder = adapter.router().http_connection_builder();
+    let connection = adapter.router().new_connection_context();
+    let shutdown = adapter.router().shutdown_signal().requested();
+    let metrics = adapter.router().metrics().cloned();
+    let service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
         let adapter = adapter.clone();
+        req.extensions_mut().insert(connection.clone());
         async move {
             adapter.handle_request(req, Some(remote_addr)).await
         }
     });
 
-    if let Err(e) = AutoBuilder::new(hyper_util::rt::TokioExecutor::new())
+    let serving = connection_builder
         .http2()
         .enable_connect_protocol()
-        .serve_connection_with_upgrades(io, service)
-        .await
-    {
+        .serve_connection_with_upgrades(io, service);
+    tokio::pin!(serving);
+    let result = tokio::select! {
+        result = serving.as_mut() => result,
+        _ = shutdown => {
+            // 引擎关闭：不再接受新请求，当前响应写完后关闭连接（HTTP/2 发送 GOAWAY）
+            serving.as_mut().graceful_shutdown();
+            serving.await
+        }
+    };
+    if let Err(e) = result {
+        crate::server::h2_config::record_serve_error(e.as_ref(), remote_addr, metrics.as_deref());
         // 区分正常的客户端断开连接和真正的服务器错误
         let error_msg = e.to_string();
         if error_msg.contains("connection closed before message completed") ||
@@ -7,8 +7,121 @@ use hyper::body::Bytes;
 use http_body_util::BodyExt;
 use std::net::SocketAddr;
 use std::collections::HashMap;
+use std::sync::Arc;
 use serde_json::Value;
 
+/// 请求 trailers 的大小上限（所有字段名和值的字节数之和）
+///
+/// 该上限在请求体读取完成后检查：读取期间 hyper 会先缓冲整个 trailers 块，
+/// 由 hyper 自身的上限约束（默认 16 KiB，超出时请求体读取失败），
+/// 因此单个请求最多缓冲约 16 KiB 的 trailers，之后才按本上限返回错误
+pub const MAX_REQUEST_TRAILERS_SIZE: usize = 8 * 1024;
+
+/// 不允许出现在 trailers 中的字段：影响消息分帧、路由、认证或内容解释的头部只能在请求头中发送
+const FORBIDDEN_TRAILERS: &[&str] = &[
+    "authorization",
+    "cache-control",
+    "connection",
+    "content-encoding",
+    "content-length",
+    "content-range",
+    "content-type",
+    "expect",
+    "host",
+    "max-forwards",
+    "proxy-authorization",
+    "range",
+    "te",
+    "trailer",
+    "transfer-encoding",
+];
+
+/// 请求 trailers 超过 [`MAX_REQUEST_TRAILERS_SIZE`]
+#[derive(Debug)]
+pub struct TrailersTooLarge {
+    /// 收到的 trailers 大小
+    pub size: usize,
+}
+
+impl std::fmt::Display for TrailersTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "请求 trailers 过大: {} 字节，上限 {} 字节", self.size, MAX_REQUEST_TRAILERS_SIZE)
+    }
+}
+
+impl std::error::Error for TrailersTooLarge {}
+
+/// 读取请求体时超过 [`IdleTimeoutBody`] 的空闲时间没有收到新数据
+#[derive(Debug)]
+pub struct BodyIdleTimeout {
+    /// 允许的最长空闲时间
+    pub timeout: std::time::Duration,
+}
+
+impl std::fmt::Display for BodyIdleTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "请求体超过 {:?} 没有收到新数据", self.timeout)
+    }
+}
+
+impl std::error::Error for BodyIdleTimeout {}
+
+/// 带空闲超时的请求体
+///
+/// 每收到一帧数据就重新计时，连续 `timeout` 没有新数据时返回 [`BodyIdleTimeout`]；
+/// 持续上传的大请求体不受总时长限制。`timeout` 为 `None` 时原样透传
+pub(crate) struct IdleTimeoutBody<B> {
+    inner: B,
+    timeout: Option<std::time::Duration>,
+    sleep: Option<std::pin::Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl<B> IdleTimeoutBody<B> {
+    pub(crate) fn new(inner: B, timeout: Option<std::time::Duration>) -> Self {
+        Self { inner, timeout, sleep: None }
+    }
+}
+
+impl<B> hyper::body::Body for IdleTimeoutBody<B>
+where
+    B: hyper::body::Body<Data = Bytes> + Unpin,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_frame(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<hyper::body::Frame<Bytes>, Self::Error>>> {
+        use std::future::Future;
+        use std::task::Poll;
+
+        let this = self.get_mut();
+        if let Poll::Ready(frame) = std::pin::Pin::new(&mut this.inner).poll_frame(cx) {
+            // 收到数据即重新计时
+            this.sleep = None;
+            return Poll::Ready(frame.map(|frame| frame.map_err(Into::into)));
+        }
+
+        if let Some(timeout) = this.timeout {
+            let sleep = this.sleep.get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+            if sleep.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Some(Err(Box::new(BodyIdleTimeout { timeout }))));
+            }
+        }
+        Poll::Pending
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> hyper::body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
 /// HTTP 请求来源类型
 #[derive(Debug, Clone)]
 pub enum RequestSource {
@@ -20,6 +133,30 @@ pub enum RequestSource {
     H2c,
 }
 
+/// TLS 连接信息
+///
+/// 由 TLS 连接处理器写入请求扩展，明文连接上的请求不携带
+#[derive(Debug, Clone, Default)]
+pub struct TlsInfo {
+    /// 协商的 ALPN 协议
+    pub alpn: Option<String>,
+    /// 客户端证书主题（仅 mTLS 连接）
+    pub client_cert_subject: Option<String>,
+}
+
+impl TlsInfo {
+    /// 从握手完成的 TLS 连接状态中提取
+    pub fn from_connection(conn: &rustls::CommonState) -> Self {
+        let alpn = conn.alpn_protocol()
+            .map(|p| String::from_utf8_lossy(p).into_owned());
+        let client_cert_subject = conn.peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| x509_parser::parse_x509_certificate(cert.as_ref()).ok())
+            .map(|(_, cert)| cert.subject().to_string());
+        Self { alpn, client_cert_subject }
+    }
+}
+
 /// 统一的 HTTP 请求结构体
 ///
 /// 用于替换 hyper::Request<Incoming>，支持标准 HTTP 请求和 SSE
@@ -43,6 +180,19 @@ pub struct HttpRequest {
     pub path_params: HashMap<String, String>,
     /// Python处理器名字（仅用于Python集成，避免Python层二次路由匹配）
     pub python_handler_name: Option<String>,
+    /// TLS 连接信息（明文连接为 None）
+    pub tls_info: Option<TlsInfo>,
+    /// 应用共享状态（由路由器填充）
+    pub state: crate::server::app_state::AppState,
+    /// 按路由器的真实 IP 配置解析出的客户端地址（未配置时为 None）
+    pub real_ip: Option<std::net::IpAddr>,
+    /// 客户端断开信号（由路由器填充）
+    pub client_disconnect: crate::server::client_disconnect::ClientDisconnect,
+    /// 请求体之后的 trailers，只保留 `Trailer` 请求头中声明过的字段
+    ///
+    /// 只有经过 hyper 读取的请求（[`HttpRequest::from_hyper_request`]）会填充；
+    /// 工作窃取引擎路径不支持分块传输编码、HTTP/2 直连路径只缓冲 DATA 帧，两者始终为 `None`
+    pub trailers: Option<HeaderMap>,
 }
 
 impl HttpRequest {
@@ -51,17 +201,47 @@ impl HttpRequest {
         req: hyper::Request<Incoming>,
         remote_addr: Option<SocketAddr>,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::from_hyper_request_limited(req, remote_addr, None).await
+    }
+
+    /// 从 hyper::Request<Incoming> 创建 HttpRequest，并限制请求体大小
+    ///
+    /// 限制按解码后的字节计算（分块传输编码的帧开销不计入），
+    /// 超出时立即停止读取并返回 `http_body_util::LengthLimitError`
+    pub async fn from_hyper_request_limited<B>(
+        req: hyper::Request<B>,
+        remote_addr: Option<SocketAddr>,
+        max_body_size: Option<usize>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>>
+    where
+        B: hyper::body::Body<Data = Bytes>,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
         let (parts, body) = req.into_parts();
         
         // 收集请求体
-        let body_bytes = match body.collect().await {
-            Ok(collected) => collected.to_bytes(),
+        let collected = match max_body_size {
+            Some(max) => http_body_util::Limited::new(body, max).collect().await,
+            None => body.collect().await.map_err(Into::into),
+        };
+        let (body_bytes, trailers) = match collected {
+            Ok(collected) => {
+                let trailers = collected.trailers().cloned();
+                (collected.to_bytes(), trailers)
+            }
             Err(e) => {
-                crate::utils::logger::error!("收集请求体失败: {}", e);
-                return Err(Box::new(e));
+                if !e.is::<http_body_util::LengthLimitError>() {
+                    crate::utils::logger::error!("收集请求体失败: {}", e);
+                }
+                return Err(e);
             }
         };
 
+        let trailers = match trailers {
+            Some(trailers) => advertised_trailers(&parts.headers, trailers)?,
+            None => None,
+        };
+
         // 根据版本判断请求来源
         let source = match parts.version {
             Version::HTTP_11 => RequestSource::Http1,
@@ -86,6 +266,11 @@ impl HttpRequest {
             source,
             path_params: HashMap::new(),
             python_handler_name: None,
+            tls_info: parts.extensions.get::<TlsInfo>().cloned(),
+            state: Default::default(),
+            real_ip: None,
+            client_disconnect: Default::default(),
+            trailers,
         })
     }
 
@@ -107,9 +292,46 @@ impl HttpRequest {
             source: RequestSource::Http2,
             path_params: HashMap::new(),
             python_handler_name: None,
+            tls_info: None,
+            state: Default::default(),
+            real_ip: None,
+            client_disconnect: Default::default(),
+            trailers: None,
         }
     }
 
+    /// 请求体之后的 trailers（例如分块上传结束时附带的校验和）
+    ///
+    /// 只包含 `Trailer` 请求头中声明过的字段；客户端没有发送或全部被过滤时返回 `None`。
+    /// 工作窃取引擎路径和 HTTP/2 直连路径不读取 trailers，同样返回 `None`
+    pub fn trailers(&self) -> Option<HeaderMap> {
+        self.trailers.clone()
+    }
+
+    /// 是否通过 TLS 连接接收
+    pub fn is_tls(&self) -> bool {
+        self.tls_info.is_some()
+    }
+
+    /// 获取指定类型的应用状态（通过 `Router::with_state` 注册）
+    pub fn state<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.state.get::<T>()
+    }
+
+    /// 注入应用状态，优先于路由器注册的同类型状态（常用于测试中替换为模拟对象）
+    pub fn with_state<T: Send + Sync + 'static>(mut self, value: Arc<T>) -> Self {
+        self.state.insert(value);
+        self
+    }
+
+    /// 在最终响应之前发送 103 Early Hints（通常是 `Link: <...>; rel=preload` 头部）
+    ///
+    /// 连接不支持中间响应时，`Link` 头部会合并到最终响应中。
+    /// 返回 `false` 表示当前不在请求处理作用域内，提示被丢弃
+    pub fn early_hints(&self, headers: HeaderMap) -> bool {
+        crate::server::early_hints::send(headers)
+    }
+
     /// 获取请求路径
     pub fn path(&self) -> &str {
         self.uri.path()
@@ -169,6 +391,40 @@ impl HttpRequest {
         self.headers.get(name)?.to_str().ok()
     }
 
+    /// 获取请求主机名（不含端口，小写）
+    ///
+    /// 优先使用 Host 头部，其次使用 URI 中的 authority（HTTP/2 或绝对形式请求）
+    pub fn host(&self) -> Option<String> {
+        let raw = self.header("host")
+            .filter(|h| !h.trim().is_empty())
+            .or_else(|| self.uri.authority().map(|a| a.as_str()))?;
+        Some(Self::normalize_host(raw))
+    }
+
+    /// 规范化主机名：去除端口、首尾空白和结尾的点，并转换为小写
+    pub(crate) fn normalize_host(raw: &str) -> String {
+        let raw = raw.trim();
+        let host = if raw.starts_with('[') {
+            // IPv6 格式 [::1]:8080
+            match raw.find(']') {
+                Some(end) => &raw[..=end],
+                None => raw,
+            }
+        } else {
+            raw.split(':').next().unwrap_or(raw)
+        };
+        host.trim_end_matches('.').to_ascii_lowercase()
+    }
+
+    /// 检查 HTTP/1.1 请求是否缺少 Host 头部
+    ///
+    /// 绝对形式的请求 URI（包含 authority）视为已提供主机信息
+    pub fn is_missing_host(&self) -> bool {
+        self.version == Version::HTTP_11
+            && self.header("host").map(|h| h.trim().is_empty()).unwrap_or(true)
+            && self.uri.authority().is_none()
+    }
+
     /// 检查是否是 gRPC 请求
     pub fn is_grpc(&self) -> bool {
         // 检查 content-type 头部
@@ -221,15 +477,38 @@ impl HttpRequest {
     }
 
     /// 将请求体转换为字符串
+    #[deprecated(note = "请使用 body_text() 替代，错误信息已本地化")]
     pub fn body_as_string(&self) -> Result<String, std::string::FromUtf8Error> {
         String::from_utf8(self.body.to_vec())
     }
 
     /// 将请求体解析为 JSON
+    #[deprecated(note = "请使用 body_json() 替代，错误信息已本地化并包含行列位置")]
     pub fn body_as_json(&self) -> Result<Value, serde_json::Error> {
         serde_json::from_slice(&self.body)
     }
 
+    /// 将请求体转换为字符串
+    ///
+    /// 不是有效的 UTF-8 时返回本地化的 `RatError::ValidationError`
+    pub fn body_text(&self) -> crate::error::RatResult<String> {
+        String::from_utf8(self.body.to_vec()).map_err(|e| {
+            crate::error::RatError::ValidationError(rat_embed_lang::tf("request_body_not_utf8", &[("msg", &e.to_string())]))
+        })
+    }
+
+    /// 将请求体解析为 JSON
+    ///
+    /// 解析失败时返回本地化的 `RatError::DeserializationError`，包含出错的行列位置
+    pub fn body_json(&self) -> crate::error::RatResult<Value> {
+        self.body_as()
+    }
+
+    /// 将请求体反序列化为指定类型
+    pub fn body_as<T: serde::de::DeserializeOwned>(&self) -> crate::error::RatResult<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
     /// 获取 Content-Type
     pub fn content_type(&self) -> Option<&str> {
         self.header("content-type")
@@ -281,6 +560,11 @@ impl HttpRequest {
     /// 
     /// 当 `validate_public_ip` 为 false 时，适用于内网部署场景
     pub fn client_ip_with_validation(&self, validate_public_ip: bool) -> Option<std::net::IpAddr> {
+        // 0. 路由器已按可信头部配置解析过，不再回退到其他头部
+        if let Some(ip) = self.real_ip {
+            return (!validate_public_ip || self.is_valid_public_ip(&ip.to_string())).then_some(ip);
+        }
+
         // 1. Cloudflare 专用头（最高优先级）
         if let Some(cf_ip) = self.header("cf-connecting-ip") {
             if let Ok(ip) = cf_ip.trim().parse::<std::net::IpAddr>() {
@@ -539,4 +823,56 @@ impl HttpRequest {
         self.header("Access-Control-Request-Headers")
             .or_else(|| self.header("access-control-request-headers"))
     }
-}
\ No newline at end of file
+}
+
+/// 只保留 `Trailer` 请求头中声明过、且允许放在 trailers 中的字段
+fn advertised_trailers(
+    headers: &HeaderMap,
+    trailers: HeaderMap,
+) -> Result<Option<HeaderMap>, Box<dyn std::error::Error + Send + Sync>> {
+    let size: usize = trailers.iter()
+        .map(|(name, value)| name.as_str().len() + value.len())
+        .sum();
+    if size > MAX_REQUEST_TRAILERS_SIZE {
+        return Err(Box::new(TrailersTooLarge { size }));
+    }
+
+    let advertised: Vec<String> = headers.get_all(hyper::header::TRAILER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty() && !FORBIDDEN_TRAILERS.contains(&name.as_str()))
+        .collect();
+
+    let mut accepted = HeaderMap::new();
+    for (name, value) in trailers.iter() {
+        if advertised.iter().any(|allowed| allowed == name.as_str()) {
+            accepted.append(name.clone(), value.clone());
+        } else {
+            crate::utils::logger::debug!("🚫 [HttpRequest] 忽略未在 Trailer 头中声明的 trailer: {}", name);
+        }
+    }
+    Ok((!accepted.is_empty()).then_some(accepted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailers_size_limit() {
+        let mut headers = HeaderMap::new();
+        headers.insert(hyper::header::TRAILER, "x-checksum".parse().unwrap());
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", "a".repeat(MAX_REQUEST_TRAILERS_SIZE).parse().unwrap());
+        let error = advertised_trailers(&headers, trailers).unwrap_err();
+        assert!(error.is::<TrailersTooLarge>());
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", "abc".parse().unwrap());
+        let accepted = advertised_trailers(&headers, trailers).unwrap().unwrap();
+        assert_eq!(accepted["x-checksum"], "abc");
+    }
+}
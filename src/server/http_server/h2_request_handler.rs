@@ -37,7 +37,7 @@ pub async fn handle_h2_request(
     debug!("📋 [HTTP专用] 请求头:");
     for (name, value) in request.headers() {
         if let Ok(value_str) = value.to_str() {
-            debug!("   {}: {}", name, value_str);
+            debug!("   {}: {}", name, crate::utils::logger::redact_header_value(name.as_str(), value_str));
         }
     }
 
@@ -45,29 +45,36 @@ pub async fn handle_h2_request(
         request.method(), request.uri().path());
 
     // 读取 RecvStream 数据
-    let (parts, mut recv_stream) = request.into_parts();
-    let mut body_data = Vec::new();
-
-    while let Some(chunk) = recv_stream.data().await {
-        let chunk = chunk.map_err(|e| format!("读取 HTTP/2 请求体失败: {}", e))?;
-        body_data.extend_from_slice(&chunk);
-        recv_stream.flow_control().release_capacity(chunk.len())
-            .map_err(|e| format!("HTTP/2 流量控制失败: {}", e))?;
-    }
+    let (mut parts, mut recv_stream) = request.into_parts();
+    let tls_info = parts.extensions.remove::<crate::server::http_request::TlsInfo>();
+    let Some(body_data) = router.read_h2_body(&parts, &mut recv_stream, &mut respond, remote_addr).await else {
+        return Ok(());
+    };
 
     // 使用通用的 HttpRequest 结构体
-    let http_request = crate::server::http_request::HttpRequest::from_h2_request(
+    let mut http_request = crate::server::http_request::HttpRequest::from_h2_request(
         parts.method,
         parts.uri,
         parts.headers,
         bytes::Bytes::from(body_data),
         Some(remote_addr),
     );
+    http_request.tls_info = tls_info;
 
     debug!("🔄 [HTTP专用] 已转换为通用 HttpRequest，调用 Router::handle_http");
 
-    // 调用 Router 的通用 handle_http 方法
-    match router.handle_http(http_request).await {
+    // 调用 Router 的通用 handle_http 方法；客户端在响应前重置流时放弃处理，
+    // 路由器会通知处理器并计入客户端断开指标
+    let handling = router.handle_http(http_request);
+    tokio::pin!(handling);
+    let result = tokio::select! {
+        result = &mut handling => result,
+        reason = futures_util::future::poll_fn(|cx| respond.poll_reset(cx)) => {
+            debug!("🔌 [HTTP专用] 客户端在响应前重置了流: {:?}", reason);
+            return Ok(());
+        }
+    };
+    match result {
         Ok(response) => {
             debug!("✅ [HTTP专用] Router 处理成功");
 
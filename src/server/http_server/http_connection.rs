@@ -15,13 +15,11 @@ use hyper::body::Incoming;
 use hyper::body::Bytes;
 use http_body_util::{Full, combinators::BoxBody};
 use hyper_util::rt::TokioIo;
-use hyper_util::server::conn::auto::Builder as AutoBuilder;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use futures_util::StreamExt;
 use bytes;
 use crate::utils::logger::{debug, info, warn, error};
-use tokio::io::AsyncReadExt;
 
 pub async fn handle_tls_connection<S>(
     stream: S,
@@ -71,12 +69,13 @@ where
         // 将泛型 stream 转换为 TcpStream（这里需要一些技巧）
         // 简化处理：假设 S 是 TcpStream
         println!("🔍 [服务端] 调用 acceptor.accept()...");
-        let mut tls_stream = acceptor.accept(stream).await
+        let mut tls_stream = crate::server::tls_handshake::accept(&acceptor, stream, router.tls_handshake_timeout(), router.metrics().map(|m| m.as_ref())).await
             .map_err(|e| {
                 error!("❌ [服务端] TLS 握手失败: {}", e);
                 format!("TLS 握手失败: {}", e)
             })?;
         println!("✅ [服务端] TLS accept 成功!");
+        crate::server::handshake_limiter::release_handshake_permit();
 
         info!("✅ [服务端] TLS 握手成功: {}", remote_addr);
 
@@ -89,6 +88,7 @@ where
 
         // TLS 模式强制要求 HTTP/2
         let is_http2 = alpn_protocol.as_ref().map(|p| p == b"h2").unwrap_or(false);
+        let tls_info = crate::server::http_request::TlsInfo::from_connection(conn);
         println!("🔍 [服务端] is_http2 = {}", is_http2);
 
         if !is_http2 {
@@ -120,22 +120,37 @@ where
 
         // 使用 hyper auto builder 处理 HTTP/2，通过 HyperAdapter 使用服务端连接池
         println!("🔍 [服务端] 使用 hyper auto builder 处理 HTTP/2...");
-        use hyper_util::server::conn::auto::Builder as AutoBuilder;
 
         let io = TokioIo::new(tls_stream);
-        let service = hyper::service::service_fn(move |req| {
+        let mut connection_builder = router.http_connection_builder();
+        let connection = router.new_connection_context();
+        let shutdown = router.shutdown_signal().requested();
+        let metrics = router.metrics().cloned();
+        let service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
             let adapter = adapter.clone();
+            req.extensions_mut().insert(connection.clone());
+            // TLS 连接信息随请求扩展传递给处理器
+            req.extensions_mut().insert(tls_info.clone());
             async move {
                 adapter.handle_request(req, Some(remote_addr)).await
             }
         });
 
-        if let Err(e) = AutoBuilder::new(hyper_util::rt::TokioExecutor::new())
+        let serving = connection_builder
             .http2()
             .enable_connect_protocol()
-            .serve_connection_with_upgrades(io, service)
-            .await
-        {
+            .serve_connection_with_upgrades(io, service);
+        tokio::pin!(serving);
+        let result = tokio::select! {
+            result = serving.as_mut() => result,
+            _ = shutdown => {
+                // 引擎关闭：不再接受新请求，当前响应写完后关闭连接（HTTP/2 发送 GOAWAY）
+                serving.as_mut().graceful_shutdown();
+                serving.await
+            }
+        };
+        if let Err(e) = result {
+            crate::server::h2_config::record_serve_error(e.as_ref(), remote_addr, metrics.as_deref());
             // 区分正常的客户端断开连接和真正的服务器错误
             let error_msg = e.to_string();
             if error_msg.contains("connection closed") ||
@@ -169,10 +184,10 @@ pub async fn handle_h2_tls_connection(
     use h2::server;
 
     debug!("🔍 [HTTP专用] 开始处理 HTTP/2 over TLS 连接: {}", remote_addr);
+    let tls_info = crate::server::http_request::TlsInfo::from_connection(tls_stream.get_ref().1);
 
     // 配置 HTTP/2 服务器
-    let mut h2_builder = h2::server::Builder::default();
-    h2_builder.max_frame_size(1024 * 1024);
+    let h2_builder = router.h2_config().server_builder();
 
     // 创建 HTTP/2 服务器连接
     let mut connection = h2_builder.handshake(tls_stream).await
@@ -183,12 +198,35 @@ pub async fn handle_h2_tls_connection(
 
     info!("✅ [HTTP专用] HTTP/2 连接已建立: {}", remote_addr);
 
+    // 连接级 gRPC 并发流限制（同一连接上也可能承载 gRPC 请求）
+    let stream_limit = router.new_grpc_stream_limit();
+
     // 处理 HTTP 请求
-    while let Some(request_result) = connection.accept().await {
+    let shutdown = router.shutdown_signal().requested();
+    tokio::pin!(shutdown);
+    let mut draining = false;
+    loop {
+        let request_result = tokio::select! {
+            request_result = connection.accept() => match request_result {
+                Some(request_result) => request_result,
+                None => break,
+            },
+            _ = &mut shutdown, if !draining => {
+                // 引擎关闭：发送 GOAWAY，不再接受新流，已接受的流继续完成
+                debug!("🛑 [HTTP专用] 引擎关闭，开始优雅关闭连接: {}", remote_addr);
+                connection.graceful_shutdown();
+                draining = true;
+                continue;
+            }
+        };
         match request_result {
-            Ok((request, respond)) => {
+            Ok((mut request, respond)) => {
                 debug!("📥 [HTTP专用] 接收到 HTTP 请求: {} {}",
                     request.method(), request.uri().path());
+                request.extensions_mut().insert(tls_info.clone());
+                if let Some(limit) = &stream_limit {
+                    request.extensions_mut().insert(limit.clone());
+                }
 
                 let router_clone = router.clone();
 
@@ -199,6 +237,7 @@ pub async fn handle_h2_tls_connection(
                 });
             }
             Err(e) => {
+                crate::server::h2_config::record_connection_error(&e, remote_addr, router.metrics().map(|m| m.as_ref()));
                 error!("❌ [HTTP专用] 接受请求失败: {}", e);
                 break;
             }
@@ -217,19 +256,33 @@ pub async fn handle_http1_connection(
     adapter: Arc<HyperAdapter>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let io = TokioIo::new(stream);
-    let service = hyper::service::service_fn(move |req| {
+    let mut connection_builder = adapter.router().http_connection_builder();
+    let connection = adapter.router().new_connection_context();
+    let shutdown = adapter.router().shutdown_signal().requested();
+    let metrics = adapter.router().metrics().cloned();
+    let service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
         let adapter = adapter.clone();
+        req.extensions_mut().insert(connection.clone());
         async move {
             adapter.handle_request(req, Some(remote_addr)).await
         }
     });
 
-    if let Err(e) = AutoBuilder::new(hyper_util::rt::TokioExecutor::new())
+    let serving = connection_builder
         .http2()
         .enable_connect_protocol()
-        .serve_connection_with_upgrades(io, service)
-        .await
-    {
+        .serve_connection_with_upgrades(io, service);
+    tokio::pin!(serving);
+    let result = tokio::select! {
+        result = serving.as_mut() => result,
+        _ = shutdown => {
+            // 引擎关闭：不再接受新请求，当前响应写完后关闭连接（HTTP/2 发送 GOAWAY）
+            serving.as_mut().graceful_shutdown();
+            serving.await
+        }
+    };
+    if let Err(e) = result {
+        crate::server::h2_config::record_serve_error(e.as_ref(), remote_addr, metrics.as_deref());
         // 区分正常的客户端断开连接和真正的服务器错误
         let error_msg = e.to_string();
         if error_msg.contains("connection closed before message completed") ||
@@ -260,19 +313,33 @@ where
     S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
 {
     let io = TokioIo::new(stream);
-    let service = hyper::service::service_fn(move |req| {
+    let mut connection_builder = adapter.router().http_connection_builder();
+    let connection = adapter.router().new_connection_context();
+    let shutdown = adapter.router().shutdown_signal().requested();
+    let metrics = adapter.router().metrics().cloned();
+    let service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
         let adapter = adapter.clone();
+        req.extensions_mut().insert(connection.clone());
         async move {
             adapter.handle_request(req, Some(remote_addr)).await
         }
     });
 
-    if let Err(e) = AutoBuilder::new(hyper_util::rt::TokioExecutor::new())
+    let serving = connection_builder
         .http2()
         .enable_connect_protocol()
-        .serve_connection_with_upgrades(io, service)
-        .await
-    {
+        .serve_connection_with_upgrades(io, service);
+    tokio::pin!(serving);
+    let result = tokio::select! {
+        result = serving.as_mut() => result,
+        _ = shutdown => {
+            // 引擎关闭：不再接受新请求，当前响应写完后关闭连接（HTTP/2 发送 GOAWAY）
+            serving.as_mut().graceful_shutdown();
+            serving.await
+        }
+    };
+    if let Err(e) = result {
+        crate::server::h2_config::record_serve_error(e.as_ref(), remote_addr, metrics.as_deref());
         // 区分正常的客户端断开连接和真正的服务器错误
         let error_msg = e.to_string();
         if error_msg.contains("connection closed before message completed") ||
@@ -315,25 +382,11 @@ pub async fn handle_http_dedicated_connection(
 
     // 读取连接的前几个字节来检测 PROXY protocol 和 TLS
     let mut buffer = [0u8; 1024];
-    let mut total_read = 0;
 
     // 尝试读取数据
     let read_result = tokio::time::timeout(
         std::time::Duration::from_millis(1000),
-        async {
-            while total_read < buffer.len() {
-                match stream.read(&mut buffer[total_read..]).await {
-                    Ok(0) => break,
-                    Ok(n) => total_read += n,
-                    Err(e) => return Err(e),
-                }
-
-                if total_read >= 64 {
-                    break;
-                }
-            }
-            Ok(total_read)
-        }
+        crate::server::protocol_detector::read_detection_prefix(&mut stream, &mut buffer)
     ).await;
 
     let bytes_read = match read_result {
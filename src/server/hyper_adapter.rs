@@ -4,6 +4,7 @@ use hyper::body::Bytes;
 use hyper::service::Service;
 use hyper::{Request, Response};
 use crate::server::router::Router;
+use crate::server::access_log::{AccessLogProtocol, AccessLogRecord, ConnectionContext};
 use std::sync::Arc;
 use std::future::Future;
 use std::pin::Pin;
@@ -20,6 +21,11 @@ impl HyperAdapter {
         HyperAdapter { router }
     }
 
+    /// 获取适配器使用的路由器
+    pub fn router(&self) -> &Arc<Router> {
+        &self.router
+    }
+
     pub async fn handle_request(
         &self,
         req: Request<Incoming>,
@@ -30,32 +36,54 @@ impl HyperAdapter {
         let path = req.uri().path().to_string();
         let start = std::time::Instant::now();
         let client_ip = remote_addr.map(|addr| addr.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+        let protocol = AccessLogProtocol::detect(
+            req.version(),
+            req.headers().get(hyper::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()),
+        );
+        let connection = req.extensions().get::<ConnectionContext>().cloned();
+        let connection_request_seq = connection.as_ref().map(|c| c.begin_request());
         
         crate::utils::logger::debug!("🔍 [HyperAdapter] 收到请求: {} {}", method, path);
-        crate::utils::logger::debug!("🔍 [HyperAdapter] 请求头: {:?}", req.headers());
+        crate::utils::logger::debug!("🔍 [HyperAdapter] 请求头: {:?}", crate::utils::logger::redact_headers(req.headers()));
         
         // 处理请求
         crate::utils::logger::debug!("🔍 [HyperAdapter] 开始路由处理...");
         let router_start = std::time::Instant::now();
-        let response = self.router.handle_hyper_request(req, remote_addr).await;
+        let mut response = self.router.handle_hyper_request(req, remote_addr).await
+            .map(|resp| crate::server::graceful_shutdown::close_event_stream_on_shutdown(resp, self.router.shutdown_signal()));
         let total_duration = start.elapsed();
+
+        // 协议检测调试头部
+        if self.router.expose_detection_debug() {
+            let detected = connection.as_ref().and_then(|c| c.detected_protocol());
+            if let (Ok(resp), Some(detected)) = (&mut response, detected) {
+                if let Ok(value) = hyper::header::HeaderValue::from_str(&detected.header_value()) {
+                    resp.headers_mut().insert("x-detected-protocol", value);
+                }
+            }
+        }
         
         match &response {
             Ok(resp) => {
-                let status_code = resp.status().as_u16();
-                // 统计信息日志（info 级别，生产环境可见）
-                crate::utils::logger::info!(
-                    "📊 {} {} {} {} {}",
-                    client_ip,
-                    method,
-                    path,
-                    status_code,
-                    crate::utils::logger::format_duration(total_duration)
+                let record = AccessLogRecord {
+                    client_ip: remote_addr.map(|addr| addr.ip()),
+                    method: method.clone(),
+                    path: path.clone(),
+                    status: resp.status().as_u16(),
+                    duration: total_duration,
+                    protocol,
+                    connection_request_seq,
+                    connection_age: connection.as_ref().map(|c| c.age()),
+                };
+                // 统计信息日志（默认 info 级别，可按路由调整或关闭）
+                crate::utils::logger::log_at(
+                    self.router.access_log_level(&path),
+                    format_args!("📊 {}", record),
                 );
 
                 crate::utils::logger::debug!("🔍 [HyperAdapter] 路由处理成功，总耗时: {}", crate::utils::logger::format_duration(total_duration));
                 crate::utils::logger::debug!("🔍 [HyperAdapter] 响应状态码: {}", resp.status());
-                crate::utils::logger::debug!("🔍 [HyperAdapter] 响应头: {:?}", resp.headers());
+                crate::utils::logger::debug!("🔍 [HyperAdapter] 响应头: {:?}", crate::utils::logger::redact_headers(resp.headers()));
                 crate::utils::logger::debug!("🔍 [HyperAdapter] 响应体类型: Full<Bytes>");
                 crate::utils::logger::debug!("🔍 [HyperAdapter] 准备返回响应给 Hyper...");
             },
@@ -0,0 +1,174 @@
+//! 处理器返回值到 HTTP 响应的转换
+//!
+//! 通过 `Router::add_fallible_route` 注册的处理器返回 `Result<impl IntoResponse, RatError>`：
+//! `Ok` 中的值转换为响应，`Err` 交给路由器的错误处理器，默认按 [`RatError::status_code`] 选择状态码。
+//!
+//! 内置实现：
+//!
+//! | 返回值 | 状态码 | Content-Type |
+//! |--------|--------|--------------|
+//! | `String`、`&'static str` | 200 | `text/plain; charset=utf-8` |
+//! | `Vec<u8>` | 200 | `application/octet-stream` |
+//! | [`Json<T>`] | 200 | `application/json` |
+//! | `StatusCode` | 指定值 | 无响应体 |
+//! | `(StatusCode, T)` | 指定值 | 由 `T` 决定 |
+//! | `Response<Full<Bytes>>` | 原样返回 | 原样返回 |
+//!
+//! ```ignore
+//! router.add_fallible_route(Method::GET, "/users/<id>", |req| async move {
+//!     let id = req.param_as_u64("id")
+//!         .ok_or_else(|| RatError::InvalidArgument("id 必须是数字".to_string()))?;
+//!     let user = load_user(id).await?;
+//!     Ok((StatusCode::OK, user.name))
+//! });
+//! ```
+
+use std::sync::Arc;
+
+use hyper::{Response, StatusCode};
+use hyper::body::Bytes;
+use http_body_util::Full;
+
+use crate::error::RatError;
+
+/// 可以转换为 HTTP 响应的类型
+pub trait IntoResponse {
+    /// 转换为响应
+    fn into_response(self) -> Response<Full<Bytes>>;
+}
+
+/// JSON 响应体，序列化 `T` 并设置 `Content-Type: application/json`
+///
+/// 序列化失败时返回 500
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+/// 可失败路由的错误处理器：把处理器返回的错误转换为响应
+pub type ErrorHandler = Arc<dyn Fn(&RatError) -> Response<Full<Bytes>> + Send + Sync>;
+
+impl IntoResponse for Response<Full<Bytes>> {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        self
+    }
+}
+
+impl IntoResponse for String {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        text_response(Bytes::from(self), "text/plain; charset=utf-8")
+    }
+}
+
+impl IntoResponse for &'static str {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        text_response(Bytes::from_static(self.as_bytes()), "text/plain; charset=utf-8")
+    }
+}
+
+impl IntoResponse for Vec<u8> {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        text_response(Bytes::from(self), "application/octet-stream")
+    }
+}
+
+/// 只有状态码、没有响应体
+impl IntoResponse for StatusCode {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        let mut response = Response::new(Full::new(Bytes::new()));
+        *response.status_mut() = self;
+        response
+    }
+}
+
+impl<T: serde::Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        match serde_json::to_vec(&self.0) {
+            Ok(body) => text_response(Bytes::from(body), "application/json"),
+            Err(e) => {
+                crate::utils::logger::error!("❌ JSON 响应序列化失败: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// 使用指定状态码，其余部分由 `T` 决定
+impl<T: IntoResponse> IntoResponse for (StatusCode, T) {
+    fn into_response(self) -> Response<Full<Bytes>> {
+        let (status, body) = self;
+        let mut response = body.into_response();
+        *response.status_mut() = status;
+        response
+    }
+}
+
+fn text_response(body: Bytes, content_type: &'static str) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(body));
+    response.headers_mut().insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static(content_type));
+    response
+}
+
+/// 默认的错误处理器
+///
+/// 返回 `{"error": ..., "code": ...}`；5xx 错误只返回状态码的标准描述，避免把内部细节暴露给客户端
+pub fn default_error_handler(error: &RatError) -> Response<Full<Bytes>> {
+    let status = error.status_code();
+    let message = if status.is_server_error() {
+        status.canonical_reason().unwrap_or("Internal Server Error").to_string()
+    } else {
+        error.to_string()
+    };
+    let body = serde_json::json!({
+        "error": message,
+        "code": status.as_u16(),
+    });
+    let mut response = text_response(Bytes::from(body.to_string()), "application/json");
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn test_default_error_handler_hides_server_errors() {
+        let response = default_error_handler(&RatError::InvalidArgument("id 必须是数字".to_string()));
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&body).contains("id 必须是数字"));
+
+        let response = default_error_handler(&RatError::CacheError("redis://secret@10.0.0.1".to_string()));
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(!String::from_utf8_lossy(&body).contains("secret"));
+    }
+
+    #[test]
+    fn test_status_tuple_overrides_status() {
+        let response = (StatusCode::CREATED, "created".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["content-type"], "text/plain; charset=utf-8");
+
+        let response = (StatusCode::ACCEPTED, Json(serde_json::json!({"queued": true}))).into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(response.headers()["content-type"], "application/json");
+    }
+
+    #[tokio::test]
+    async fn test_builtin_conversions() {
+        let response = Json(vec![1, 2, 3]).into_response();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"[1,2,3]");
+
+        let response = vec![0u8, 255].into_response();
+        assert_eq!(response.headers()["content-type"], "application/octet-stream");
+
+        let response = StatusCode::NO_CONTENT.into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(response.headers().get("content-type").is_none());
+
+        let response = "pong".into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,310 @@
+//! 过载拒绝响应
+//!
+//! 连接数上限、工作队列深度、SSE 连接数等限流手段共用同一份拒绝响应配置，
+//! 无论哪个限制器触发，客户端看到的状态码、`Retry-After` 和响应体都一致。
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use hyper::{Response, StatusCode};
+use hyper::body::Bytes;
+use hyper::header::{CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, RETRY_AFTER, HeaderValue};
+use http_body_util::Full;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// 同时进行中的连接级拒绝上限，超过后直接关闭套接字，避免过载时继续堆积任务
+const MAX_PENDING_REJECTIONS: usize = 64;
+/// 拒绝连接时窥探请求行、写出响应和排空输入各自的最长等待时间
+const REJECT_IO_TIMEOUT: Duration = Duration::from_millis(200);
+/// 能识别为明文 HTTP/1 请求行的方法
+const HTTP1_METHODS: &[&[u8]] = &[
+    b"GET ", b"HEAD ", b"POST ", b"PUT ", b"DELETE ", b"OPTIONS ", b"PATCH ", b"TRACE ", b"CONNECT ",
+];
+
+static PENDING_REJECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// 触发拒绝的限制器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadShedReason {
+    /// 连接数达到上限
+    ConnectionLimit,
+    /// 工作队列已满
+    QueueFull,
+    /// SSE 连接数达到上限
+    SseConnectionLimit,
+}
+
+impl LoadShedReason {
+    /// 日志中使用的描述
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoadShedReason::ConnectionLimit => "连接数达到上限",
+            LoadShedReason::QueueFull => "工作队列已满",
+            LoadShedReason::SseConnectionLimit => "SSE 连接数达到上限",
+        }
+    }
+}
+
+/// 过载时返回给客户端的响应
+///
+/// 默认返回 `503 Service Unavailable`、`Retry-After: 1` 和纯文本响应体
+#[derive(Debug, Clone)]
+pub struct LoadShedResponse {
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    content_type: String,
+    body: Bytes,
+}
+
+impl Default for LoadShedResponse {
+    fn default() -> Self {
+        Self {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            retry_after: Some(Duration::from_secs(1)),
+            content_type: "text/plain; charset=utf-8".to_string(),
+            body: Bytes::from_static(b"Service Unavailable"),
+        }
+    }
+}
+
+impl LoadShedResponse {
+    /// 创建默认的 503 拒绝响应
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用 `429 Too Many Requests`，并把响应体改为对应的原因短语
+    pub fn too_many_requests() -> Self {
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            body: Bytes::from_static(b"Too Many Requests"),
+            ..Self::default()
+        }
+    }
+
+    /// 设置状态码，只接受 429 和 5xx，其他值保持原状态码
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            self.status = status;
+        } else {
+            crate::utils::logger::warn!("⚠️ 过载拒绝响应只支持 429 或 5xx 状态码，忽略 {}", status);
+        }
+        self
+    }
+
+    /// 设置 `Retry-After`（按秒向上取整），为 `None` 时不发送该头部
+    pub fn with_retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// 设置响应体和 Content-Type
+    ///
+    /// Content-Type 不是合法的头部值（例如包含 CR/LF）时保留原来的 Content-Type
+    pub fn with_body(mut self, content_type: impl Into<String>, body: impl Into<Bytes>) -> Self {
+        let content_type = content_type.into();
+        if HeaderValue::from_str(&content_type).is_ok() {
+            self.content_type = content_type;
+        } else {
+            crate::utils::logger::warn!("⚠️ 过载拒绝响应的 Content-Type 不是合法的头部值，忽略 {:?}", content_type);
+        }
+        self.body = body.into();
+        self
+    }
+
+    /// 状态码
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// `Retry-After` 时长
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+
+    /// `Retry-After` 头部的取值（秒）
+    pub fn retry_after_header(&self) -> Option<String> {
+        self.retry_after.map(|d| {
+            let secs = d.as_secs() + u64::from(d.subsec_nanos() > 0);
+            secs.to_string()
+        })
+    }
+
+    /// Content-Type
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// 响应体
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// 构建 hyper 响应
+    pub fn to_response(&self) -> Response<Full<Bytes>> {
+        let mut response = Response::new(Full::new(self.body.clone()));
+        *response.status_mut() = self.status;
+        let headers = response.headers_mut();
+        if let Ok(value) = HeaderValue::from_str(&self.content_type) {
+            headers.insert(CONTENT_TYPE, value);
+        }
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(self.body.len()));
+        if let Some(retry_after) = self.retry_after_header() {
+            if let Ok(value) = HeaderValue::from_str(&retry_after) {
+                headers.insert(RETRY_AFTER, value);
+            }
+        }
+        response
+    }
+
+    /// 序列化为完整的 HTTP/1.1 响应报文，并要求关闭连接
+    ///
+    /// 用于还没有进入 hyper 的连接（例如在接受连接阶段或工作队列中被拒绝）
+    pub fn to_http1_bytes(&self) -> Vec<u8> {
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n",
+            self.status.as_u16(),
+            self.status.canonical_reason().unwrap_or(""),
+            self.content_type,
+            self.body.len()
+        );
+        if let Some(retry_after) = self.retry_after_header() {
+            head.push_str(&format!("Retry-After: {}\r\n", retry_after));
+        }
+        head.push_str(&format!("{}: close\r\n\r\n", CONNECTION.as_str()));
+
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+}
+
+/// 在接受连接阶段拒绝连接
+///
+/// 只有客户端发来的首个数据是明文 HTTP/1 请求行时才写回拒绝响应，
+/// TLS、HTTP/2 前言、gRPC 以及不发数据的连接都直接关闭，不会收到无法解析的明文字节。
+/// 进行中的拒绝超过上限时同样直接关闭
+pub(crate) fn reject_connection(stream: TcpStream, response: &LoadShedResponse) {
+    if PENDING_REJECTIONS.fetch_add(1, Ordering::AcqRel) >= MAX_PENDING_REJECTIONS {
+        PENDING_REJECTIONS.fetch_sub(1, Ordering::AcqRel);
+        return;
+    }
+
+    let bytes = response.to_http1_bytes();
+    tokio::spawn(async move {
+        write_rejection(stream, &bytes).await;
+        PENDING_REJECTIONS.fetch_sub(1, Ordering::AcqRel);
+    });
+}
+
+async fn write_rejection(mut stream: TcpStream, bytes: &[u8]) {
+    let mut head = [0u8; 16];
+    let peeked = match tokio::time::timeout(REJECT_IO_TIMEOUT, stream.peek(&mut head)).await {
+        Ok(Ok(n)) => n,
+        _ => return,
+    };
+    if is_http1_request_line(&head[..peeked]) {
+        write_and_close(&mut stream, bytes).await;
+    }
+}
+
+/// 写出一个完整的响应报文后关闭连接
+///
+/// 写出和排空输入各自最多等待 200ms；关闭前读掉客户端已发送的数据，
+/// 避免未读数据触发 RST 导致客户端丢弃响应
+pub(crate) async fn write_and_close(stream: &mut TcpStream, bytes: &[u8]) {
+    if !matches!(tokio::time::timeout(REJECT_IO_TIMEOUT, stream.write_all(bytes)).await, Ok(Ok(()))) {
+        return;
+    }
+    let _ = stream.shutdown().await;
+
+    let mut sink = [0u8; 1024];
+    let _ = tokio::time::timeout(REJECT_IO_TIMEOUT, async {
+        while matches!(stream.read(&mut sink).await, Ok(n) if n > 0) {}
+    }).await;
+}
+
+/// 判断数据是否以明文 HTTP/1 请求行开头
+fn is_http1_request_line(data: &[u8]) -> bool {
+    HTTP1_METHODS.iter().any(|method| data.starts_with(method))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_503_with_retry_after() {
+        let response = LoadShedResponse::default().to_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+
+        let raw = String::from_utf8(LoadShedResponse::default().to_http1_bytes()).unwrap();
+        assert!(raw.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(raw.contains("Retry-After: 1\r\n"));
+        assert!(raw.ends_with("\r\n\r\nService Unavailable"));
+    }
+
+    #[test]
+    fn test_custom_response() {
+        let shed = LoadShedResponse::too_many_requests()
+            .with_retry_after(Some(Duration::from_millis(2500)))
+            .with_body("application/json", r#"{"error":"busy"}"#);
+        let raw = String::from_utf8(shed.to_http1_bytes()).unwrap();
+        assert!(raw.starts_with("HTTP/1.1 429 Too Many Requests\r\n"));
+        assert!(raw.contains("Retry-After: 3\r\n"));
+        assert!(raw.contains("Content-Length: 16\r\n"));
+
+        let without_retry = shed.clone().with_retry_after(None).to_response();
+        assert!(!without_retry.headers().contains_key(RETRY_AFTER));
+
+        // 非过载语义的状态码会被忽略
+        assert_eq!(shed.with_status(StatusCode::OK).status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_content_type_with_crlf_is_ignored() {
+        let shed = LoadShedResponse::default()
+            .with_body("text/plain\r\nX-Injected: 1", "busy");
+        assert_eq!(shed.content_type(), "text/plain; charset=utf-8");
+        let raw = String::from_utf8(shed.to_http1_bytes()).unwrap();
+        assert!(!raw.contains("X-Injected"));
+        assert!(raw.ends_with("\r\n\r\nbusy"));
+    }
+
+    #[test]
+    fn test_only_plaintext_http1_is_answered() {
+        assert!(is_http1_request_line(b"GET / HTTP/1.1\r\n"));
+        assert!(is_http1_request_line(b"OPTIONS * HTTP/1.1"));
+        assert!(!is_http1_request_line(b"PRI * HTTP/2.0\r\n"));
+        assert!(!is_http1_request_line(&[0x16, 0x03, 0x01, 0x02, 0x00]));
+        assert!(!is_http1_request_line(b"GE"));
+        assert!(!is_http1_request_line(b""));
+    }
+
+    #[tokio::test]
+    async fn test_reject_connection_skips_tls_clients() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shed = LoadShedResponse::default();
+
+        // 明文 HTTP/1 客户端收到拒绝响应
+        let mut http = TcpStream::connect(addr).await.unwrap();
+        http.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        reject_connection(stream, &shed);
+        let mut raw = Vec::new();
+        http.read_to_end(&mut raw).await.unwrap();
+        assert!(raw.starts_with(b"HTTP/1.1 503 "));
+
+        // TLS ClientHello 不会收到明文字节
+        let mut tls = TcpStream::connect(addr).await.unwrap();
+        tls.write_all(&[0x16, 0x03, 0x01, 0x00, 0x05, 0x01, 0x00, 0x00, 0x01, 0x00]).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        reject_connection(stream, &shed);
+        let mut raw = Vec::new();
+        let _ = tls.read_to_end(&mut raw).await;
+        assert!(raw.is_empty());
+    }
+}
@@ -129,8 +129,10 @@ pub mod cache_middleware_impl;
 pub mod cache_version_manager;
 pub mod protocol_detection_middleware;
 pub mod protocol_detector;
+pub mod protocol_policy;
 pub mod grpc_types;
 pub mod grpc_codec;
+pub mod grpc_metadata;
 pub mod cert_manager;
 pub mod grpc_handler;
 pub mod grpc_queue_bridge_adapter;
@@ -138,6 +140,21 @@ pub mod grpc_delegated_handler;
 pub mod http_request;
 pub mod global_sse_manager;
 pub mod proxy_protocol;
+pub mod handshake_limiter;
+pub mod access_log;
+pub mod early_hints;
+pub mod body_transform;
+pub mod load_shed;
+pub mod validation;
+pub mod into_response;
+pub mod client_disconnect;
+pub mod tls_handshake;
+pub mod app_state;
+pub mod tcp_write_mode;
+pub mod cache_control;
+pub mod h2_config;
+pub mod throttle;
+pub(crate) mod graceful_shutdown;
 
 // 物理分离：HTTP 和 gRPC 独立服务器
 pub mod http_server;
@@ -151,6 +168,7 @@ pub use http_server::handle_tls_connection;
 pub use http_server::handle_h2_tls_connection;
 pub use grpc_server::handle_grpc_tls_connection;
 pub use grpc_h2c_server::handle_grpc_h2c_over_tls_connection;
+pub use grpc_metadata::{ResponseMetadata, GrpcResponseMetadata};
 
 pub use config::ServerConfig;
 pub use port_config::{PortConfig, PortConfigBuilder, PortMode, PortConfigError, HttpsConfig, CertificateConfig};
@@ -158,7 +176,14 @@ pub use router::Router;
 pub use performance::{PerformanceManager, global_performance_manager, init_performance_optimization, set_thread_affinity, optimize_for_throughput};
 pub use worker_pool::WorkerPool;
 pub use hyper_adapter::HyperAdapter;
-pub use streaming::{StreamingResponse, SseResponse, ChunkedResponse};
+pub use load_shed::{LoadShedResponse, LoadShedReason};
+pub use validation::{Validate, ValidationErrors, FieldError};
+pub use into_response::{IntoResponse, Json};
+pub use client_disconnect::ClientDisconnect;
+pub use cache_control::CacheControl;
+pub use h2_config::H2Config;
+pub use throttle::{BandwidthLimit, with_bandwidth_limit};
+pub use streaming::{StreamingResponse, StreamSender, SseResponse, BinaryStreamResponse, BinaryFrameSender, NdjsonResponse, ChunkedResponse, SseLimits, SseOversizePolicy};
 
 
 /// 使用自定义路由器启动服务器（已弃用 - 请使用 RatEngineBuilder）
@@ -205,6 +230,25 @@ pub async fn run_separated_server(
     config: ServerConfig,
     router: Arc<Router>,
     cert_manager: Option<Arc<std::sync::RwLock<crate::server::cert_manager::CertificateManager>>>,
+) -> crate::error::RatResult<()> {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+        println!("\n🛑 收到 Ctrl+C 信号，正在优雅关闭服务器...");
+    };
+    serve_separated_until(config, router, cert_manager, ctrl_c, None).await
+}
+
+/// 运行分端口服务器，直到 `shutdown` 完成
+///
+/// 每个连接任务持有一份 `drain` 接收端，调用方可据此等待进行中的连接结束
+pub(crate) async fn serve_separated_until(
+    config: ServerConfig,
+    router: Arc<Router>,
+    cert_manager: Option<Arc<std::sync::RwLock<crate::server::cert_manager::CertificateManager>>>,
+    shutdown: impl std::future::Future<Output = ()>,
+    drain: Option<tokio::sync::watch::Receiver<()>>,
 ) -> crate::error::RatResult<()> {
     let adapter = Arc::new(HyperAdapter::new(router.clone()));
 
@@ -257,47 +301,31 @@ pub async fn run_separated_server(
     crate::utils::logger::info!("   🔧 gRPC server: {}://{}", scheme, grpc_addr);
 
     // 显示已注册的路由和 gRPC 方法
-    let routes = router.list_routes();
-    let grpc_methods = router.list_grpc_methods();
-    let has_http_routes = !routes.is_empty();
-    let has_grpc_methods = !grpc_methods.is_empty();
-    
-    if has_http_routes {
-        crate::utils::logger::info!("📋 已注册的 HTTP 路由:");
-        for (method, path) in routes {
-            crate::utils::logger::info!("   {} {}", method, path);
-        }
-    }
-    
-    if has_grpc_methods {
-        crate::utils::logger::info!("🔧 已注册的 gRPC 方法:");
-        for method in grpc_methods {
-            crate::utils::logger::info!("   {}", method);
-        }
-    }
-
-    // 创建信号处理器
-    let ctrl_c = async {
-        signal::ctrl_c()
-            .await
-            .expect("failed to install Ctrl+C handler");
-    };
+    router.log_registered_routes();
 
     // HTTP 服务器循环
     let http_server_loop = {
         let router = router.clone();
         let adapter = adapter.clone();
         let cert_mgr = cert_manager.clone();
+        let drain = drain.clone();
         async move {
             loop {
                 let (stream, remote_addr) = http_listener.accept().await
                     .map_err(|e| crate::error::RatError::IoError(e))?;
 
+                let permit = match router.handshake_limiter() {
+                    Some(limiter) => Some(limiter.acquire().await),
+                    None => None,
+                };
+
                 let router_clone = router.clone();
                 let adapter_clone = adapter.clone();
                 let cert_mgr_clone = cert_mgr.clone();
+                let drain = drain.clone();
 
-                tokio::task::spawn(async move {
+                tokio::task::spawn(handshake_limiter::scope(permit, async move {
+                    let _drain = drain;
                     if let Err(err) = handle_http_connection_with_cert(stream, remote_addr, router_clone, adapter_clone, cert_mgr_clone).await {
                         let err_str = err.to_string();
                         if err_str.contains("IncompleteMessage") || err_str.contains("connection closed") {
@@ -306,7 +334,7 @@ pub async fn run_separated_server(
                             crate::utils::logger::error!("Error serving HTTP connection: {:?}", err);
                         }
                     }
-                });
+                }));
             }
         }
     };
@@ -316,16 +344,24 @@ pub async fn run_separated_server(
         let router = router.clone();
         let adapter = adapter.clone();
         let cert_mgr = cert_manager.clone();
+        let drain = drain.clone();
         async move {
             loop {
                 let (stream, remote_addr) = grpc_listener.accept().await
                     .map_err(|e| crate::error::RatError::IoError(e))?;
 
+                let permit = match router.handshake_limiter() {
+                    Some(limiter) => Some(limiter.acquire().await),
+                    None => None,
+                };
+
                 let router_clone = router.clone();
                 let adapter_clone = adapter.clone();
                 let cert_mgr_clone = cert_mgr.clone();
+                let drain = drain.clone();
 
-                tokio::task::spawn(async move {
+                tokio::task::spawn(handshake_limiter::scope(permit, async move {
+                    let _drain = drain;
                     if let Err(err) = handle_grpc_connection_with_cert(stream, remote_addr, router_clone, adapter_clone, cert_mgr_clone).await {
                         let err_str = err.to_string();
                         if err_str.contains("IncompleteMessage") || err_str.contains("connection closed") {
@@ -334,12 +370,12 @@ pub async fn run_separated_server(
                             crate::utils::logger::error!("Error serving gRPC connection: {:?}", err);
                         }
                     }
-                });
+                }));
             }
         }
     };
 
-    // 等待任一服务器循环或 Ctrl+C 信号
+    // 等待任一服务器循环结束或关闭信号，返回时监听器随之关闭
     tokio::select! {
         result = http_server_loop => {
             result
@@ -347,8 +383,7 @@ pub async fn run_separated_server(
         result = grpc_server_loop => {
             result
         }
-        _ = ctrl_c => {
-            println!("\n🛑 收到 Ctrl+C 信号，正在优雅关闭服务器...");
+        _ = shutdown => {
             Ok(())
         }
     }
@@ -423,7 +458,7 @@ async fn handle_connection(
     match detect_and_handle_protocol(stream, remote_addr, router.clone(), adapter.clone()).await {
         Ok(_) => return Ok(()),
         Err(e) => {
-            rat_logger::warn!("❌ [服务端] 协议检测失败: {}", e);
+            crate::utils::logger::warn!("❌ [服务端] 协议检测失败: {}", e);
             return Err(e);
         }
     }
@@ -451,27 +486,13 @@ pub async fn detect_and_handle_protocol_with_tls(
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     // 读取连接的前几个字节来检测协议
+    // TLS 连接只预读到首字节为止，ClientHello 的其余部分留给握手从连接中读取
     let mut buffer = [0u8; 1024];
-    let mut total_read = 0;
     
     // 尝试读取数据，但设置超时
     let read_result = tokio::time::timeout(
         std::time::Duration::from_millis(1000), // 增加超时时间到1秒，给正常客户端足够时间
-        async {
-            while total_read < buffer.len() {
-                match stream.read(&mut buffer[total_read..]).await {
-                    Ok(0) => break, // 连接关闭
-                    Ok(n) => total_read += n,
-                    Err(e) => return Err(e),
-                }
-                
-                // 如果已经读取到足够的数据来判断协议，就提前退出
-                if total_read >= 64 { // 增加最小读取量到64字节，确保能检测到HTTP/2前言
-                    break;
-                }
-            }
-            Ok(total_read)
-        }
+        crate::server::protocol_detector::read_detection_prefix(&mut stream, &mut buffer)
     ).await;
     
     let bytes_read = match read_result {
@@ -499,11 +520,38 @@ pub async fn detect_and_handle_protocol_with_tls(
     }
     
     // 首先检查是否是 PROXY protocol v2
-    let mut detection_data = &buffer[..bytes_read];
+    // 头部可能被拆分到多个 TCP 分段，需要读取到声明的完整长度
+    let mut data = buffer[..bytes_read].to_vec();
+    let proxy_header_len = if crate::server::proxy_protocol::ProxyProtocolV2Parser::may_be_proxy_v2(&data) {
+        let proxy_config = router.proxy_protocol_config();
+        match crate::server::proxy_protocol::ProxyProtocolV2Parser::read_full_header(&mut stream, &mut data, &proxy_config).await {
+            Ok(len) => len.unwrap_or(0),
+            Err(e) => {
+                crate::utils::logger::warn!("🚫 PROXY protocol 头部读取失败，丢弃连接: {} ({})", remote_addr, e);
+                drop(stream);
+                return Ok(());
+            }
+        }
+    } else {
+        0
+    };
+
+    // 头部之后还没有应用数据时，继续读取用于协议检测
+    if proxy_header_len > 0 && data.len() == proxy_header_len {
+        let mut chunk = [0u8; 1024];
+        match tokio::time::timeout(std::time::Duration::from_millis(1000), stream.read(&mut chunk)).await {
+            Ok(Ok(n)) if n > 0 => data.extend_from_slice(&chunk[..n]),
+            _ => {
+                debug!("🔌 [服务端] PROXY 头部之后没有应用数据，关闭连接: {}", remote_addr);
+                return Ok(());
+            }
+        }
+    }
+
+    let mut detection_data = &data[..];
     let mut actual_remote_addr = remote_addr;
-    let mut proxy_header_len = 0;
 
-    if crate::server::proxy_protocol::ProxyProtocolV2Parser::is_proxy_v2(detection_data) {
+    if proxy_header_len > 0 {
         println!("📡 [服务端] 检测到 PROXY protocol v2: {}", remote_addr);
         println!("🔍 [服务端] 原始代理地址: {}", remote_addr);
         println!("🔍 [服务端] PROXY头部数据: {:?}", &detection_data[..detection_data.len().min(50)]);
@@ -557,8 +605,7 @@ pub async fn detect_and_handle_protocol_with_tls(
             println!("❌ [服务端] PROXY protocol v2 解析失败");
         }
 
-        // 计算并跳过PROXY头部
-        proxy_header_len = 16 + u16::from_be_bytes([detection_data[14], detection_data[15]]) as usize;
+        // 跳过PROXY头部
         detection_data = &detection_data[proxy_header_len..];
 
         println!("🔄 [服务端] 跳过 PROXY protocol v2 头部 ({} 字节)，剩余应用数据: {} 字节",
@@ -578,6 +625,11 @@ pub async fn detect_and_handle_protocol_with_tls(
     let safe_preview: String = data_str.chars().take(100).collect();
     println!("🔍 [服务端] 协议检测数据 (前100字符): {:?}", safe_preview);
 
+    // 明文连接在协议检测完成后即释放握手许可，TLS 连接在握手完成后释放
+    if detection_data.first() != Some(&0x16) {
+        handshake_limiter::release_handshake_permit();
+    }
+
     // 情况1: HTTP 专用模式 - 支持 HTTP 和 HTTPS（自动升级到 TLS）
     if router.is_http_only() {
         // 检测是否为 TLS 连接
@@ -591,6 +643,7 @@ pub async fn detect_and_handle_protocol_with_tls(
         } else {
             // HTTP 专用模式 + 明文连接 → 使用 HTTP
             println!("✅ [服务端] HTTP 专用模式，使用 HTTP 处理器");
+            handshake_limiter::release_handshake_permit();
             route_by_detected_protocol(stream, detection_data, ProtocolType::HTTP1_1, actual_remote_addr, router, adapter, tls_cert_manager.clone()).await;
             return Ok(());
         }
@@ -633,6 +686,11 @@ pub async fn detect_and_handle_protocol_with_tls(
             println!("❌ [服务端] gRPC 请求需要 TLS 证书，但未配置");
             return Err("gRPC 请求需要 TLS 证书".into());
         }
+    } else if crate::server::protocol_detector::is_websocket_upgrade(detection_data) {
+        // WebSocket 升级请求（明文 HTTP/1.1）
+        debug!("✅ [服务端] 检测到 WebSocket 升级请求，路由到 HTTP 处理器");
+        route_by_detected_protocol(stream, detection_data, ProtocolType::WebSocket, actual_remote_addr, router, adapter, tls_cert_manager.clone()).await;
+        return Ok(());
     } else {
         // 默认为 HTTP 请求
         println!("✅ [服务端] 默认路由到 HTTP 处理器");
@@ -642,6 +700,10 @@ pub async fn detect_and_handle_protocol_with_tls(
 }
 
 /// 根据检测到的协议类型路由到相应的处理器
+///
+/// `protocol_type` 是按路由器模式选出的处理器类型；协议策略看到的是对预读数据的实际判定结果，
+/// 引擎没有处理器的协议（SSH、MQTT、Redis 等）即使被策略放行，也会在分派时被拒绝。
+/// 启用协议检测调试头部时，连接在带有分派结果的作用域中处理
 async fn route_by_detected_protocol(
     stream: tokio::net::TcpStream,
     buffer: &[u8],
@@ -650,10 +712,55 @@ async fn route_by_detected_protocol(
     router: Arc<Router>,
     adapter: Arc<HyperAdapter>,
     tls_cert_manager: Option<Arc<std::sync::RwLock<crate::server::cert_manager::CertificateManager>>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let detected = crate::server::protocol_detector::classify(buffer);
+    match router.protocol_decision(&detected.protocol, detected.confidence) {
+        crate::server::protocol_policy::BlockDecision::Allow => {}
+        crate::server::protocol_policy::BlockDecision::Block => {
+            warn!("🚫 [服务端] 协议策略拒绝连接: {} (协议: {})", remote_addr, detected.header_value());
+            return Err("协议被策略拒绝".into());
+        }
+        crate::server::protocol_policy::BlockDecision::BlockWith(response) => {
+            warn!("🚫 [服务端] 协议策略拒绝连接并返回 {}: {} (协议: {})", response.status(), remote_addr, detected.header_value());
+            // TLS 和 HTTP/2 前言无法解析 HTTP/1.1 报文，直接关闭
+            if !matches!(detected.protocol, ProtocolType::TLS | ProtocolType::HTTP2) {
+                let mut stream = stream;
+                crate::server::load_shed::write_and_close(&mut stream, &crate::server::protocol_policy::to_http1_bytes(response)).await;
+            }
+            return Ok(());
+        }
+    }
+
+    // 策略放行但引擎没有对应处理器的协议按判定结果分派，由分派拒绝
+    let protocol_type = if crate::server::protocol_policy::has_handler(&detected.protocol) {
+        protocol_type
+    } else {
+        detected.protocol
+    };
+
+    if router.expose_detection_debug() {
+        // 调试头部报告实际分派的处理器类型，置信度仍为预读数据的判定结果
+        let routed = crate::server::protocol_detector::DetectedProtocol { protocol: protocol_type, ..detected };
+        debug!("🔍 [服务端] 协议判定: {}，分派为 {:?} ({})", detected.header_value(), protocol_type, remote_addr);
+        let routing = route_to_protocol_handler(stream, buffer, protocol_type, remote_addr, router, adapter, tls_cert_manager);
+        return crate::server::protocol_detector::scope_detected(routed, routing).await;
+    }
+    route_to_protocol_handler(stream, buffer, protocol_type, remote_addr, router, adapter, tls_cert_manager).await
+}
+
+/// 按协议类型分派连接
+async fn route_to_protocol_handler(
+    stream: tokio::net::TcpStream,
+    buffer: &[u8],
+    protocol_type: ProtocolType,
+    remote_addr: SocketAddr,
+    router: Arc<Router>,
+    adapter: Arc<HyperAdapter>,
+    tls_cert_manager: Option<Arc<std::sync::RwLock<crate::server::cert_manager::CertificateManager>>>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     match protocol_type {
         ProtocolType::HTTP1_0 | ProtocolType::HTTP1_1 => {
-            rat_logger::debug!("🌐 [服务端] 路由到 HTTP/1.1 处理器: {}", remote_addr);
+            crate::utils::logger::debug!("🌐 [服务端] 路由到 HTTP/1.1 处理器: {}", remote_addr);
             let reconstructed_stream = ReconstructedStream::new(stream, buffer);
             handle_http1_connection_with_stream(reconstructed_stream, remote_addr, adapter).await
         }
@@ -736,11 +843,13 @@ async fn route_by_detected_protocol(
             }
         }
         ProtocolType::WebSocket => {
-            warn!("🚫 [服务端] WebSocket 协议不支持，拒绝连接: {}", remote_addr);
-            Err("WebSocket 协议不支持".into())
+            // WebSocket 握手是 HTTP/1.1 请求，交给支持升级的 HTTP/1.1 处理器
+            crate::utils::logger::debug!("🔌 [服务端] WebSocket 升级请求，路由到 HTTP/1.1 处理器: {}", remote_addr);
+            let reconstructed_stream = ReconstructedStream::new(stream, buffer);
+            handle_http1_connection_with_stream(reconstructed_stream, remote_addr, adapter).await
         }
         ProtocolType::Unknown => {
-            rat_logger::debug!("🤔 [服务端] 未知协议类型，尝试按HTTP/1.1处理: {} (协议: {:?})", remote_addr, protocol_type);
+            crate::utils::logger::debug!("🤔 [服务端] 未知协议类型，尝试按HTTP/1.1处理: {} (协议: {:?})", remote_addr, protocol_type);
             // 对于未知协议，尝试按HTTP/1.1处理，可能是HTTP变种或者检测不准确
             let reconstructed_stream = ReconstructedStream::new(stream, buffer);
             handle_http1_connection_with_stream(reconstructed_stream, remote_addr, adapter).await
@@ -43,7 +43,7 @@ where
 
     // 进行 TLS 握手
     let acceptor = tokio_rustls::TlsAcceptor::from(server_config);
-    let tls_stream = acceptor.accept(stream).await
+    let tls_stream = crate::server::tls_handshake::accept(&acceptor, stream, router.tls_handshake_timeout(), router.metrics().map(|m| m.as_ref())).await
         .map_err(|e| {
             error!("❌ [多协议] TLS 握手失败: {}", e);
             format!("TLS 握手失败: {}", e)
@@ -57,14 +57,14 @@ where
     info!("🔐 [多协议] ALPN 协议: {:?}", alpn_protocol);
 
     let is_http2 = alpn_protocol.as_ref().map(|p| p == b"h2").unwrap_or(false);
+    let tls_info = crate::server::http_request::TlsInfo::from_connection(conn);
     if !is_http2 {
         error!("❌ [多协议] 单端口模式强制要求 HTTP/2，ALPN={:?}", alpn_protocol);
         return Err("单端口多协议模式强制要求 HTTP/2".into());
     }
 
     // 使用 h2 server 处理 HTTP/2
-    let mut h2_builder = h2::server::Builder::default();
-    h2_builder.max_frame_size(1024 * 1024);
+    let h2_builder = router.h2_config().server_builder();
 
     let mut connection = h2_builder.handshake(tls_stream).await
         .map_err(|e| {
@@ -74,10 +74,18 @@ where
 
     info!("✅ [多协议] HTTP/2 连接已建立: {}", remote_addr);
 
+    // 连接级 gRPC 并发流限制
+    let stream_limit = router.new_grpc_stream_limit();
+
     // 处理请求
     while let Some(request_result) = connection.accept().await {
         match request_result {
-            Ok((request, respond)) => {
+            Ok((mut request, respond)) => {
+                if let Some(limit) = &stream_limit {
+                    request.extensions_mut().insert(limit.clone());
+                }
+                // TLS 连接信息随请求扩展传递给处理器
+                request.extensions_mut().insert(tls_info.clone());
                 let path = request.uri().path().to_string();
                 let method = request.method().clone();
                 debug!("📥 [多协议] 接收到请求: {} {}", method, path);
@@ -106,6 +114,7 @@ where
                 });
             }
             Err(e) => {
+                crate::server::h2_config::record_connection_error(&e, remote_addr, router.metrics().map(|m| m.as_ref()));
                 error!("❌ [多协议] 接收请求失败: {}", e);
                 break;
             }
@@ -154,6 +163,11 @@ async fn handle_http_request(
         source: crate::server::http_request::RequestSource::Http2,
         path_params: std::collections::HashMap::new(),
         python_handler_name: None,
+        tls_info: parts.extensions.get::<crate::server::http_request::TlsInfo>().cloned(),
+        state: Default::default(),
+        real_ip: None,
+        client_disconnect: Default::default(),
+        trailers: None,
     };
 
     // 调用 HTTP 处理器
@@ -186,3 +200,62 @@ async fn handle_http_request(
     debug!("✅ [多协议-HTTP] 响应已发送");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::cert_manager::{CertConfig, CertManagerConfig};
+    use rustls::pki_types::{CertificateDer, ServerName};
+
+    #[tokio::test]
+    async fn test_http_request_carries_tls_info() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let dir = tempfile::tempdir().unwrap();
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+        std::fs::write(&cert_path, cert.serialize_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, cert.serialize_private_key_pem()).unwrap();
+        let cert_manager = Arc::new(std::sync::RwLock::new(CertificateManager::from_config(
+            CertManagerConfig::shared(CertConfig::from_paths(&cert_path, &key_path))
+        ).unwrap()));
+
+        // 处理器返回请求上看到的 ALPN
+        let mut router = Router::new();
+        router.add_route(hyper::Method::GET, "/tls", |req| {
+            let alpn = req.tls_info.as_ref().and_then(|info| info.alpn.clone()).unwrap_or_else(|| "none".to_string());
+            Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(alpn)))) })
+        });
+        let router = Arc::new(router);
+        let adapter = Arc::new(HyperAdapter::new(router.clone()));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, remote_addr) = listener.accept().await.unwrap();
+            let _ = handle_multi_protocol_tls_connection(stream, remote_addr, router, adapter, cert_manager).await;
+        });
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(CertificateDer::from(cert.serialize_der().unwrap())).unwrap();
+        let mut config = rustls::ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+        config.alpn_protocols = vec![b"h2".to_vec()];
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let tls = tokio_rustls::TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), stream).await.unwrap();
+
+        let (mut client, connection) = h2::client::handshake(tls).await.unwrap();
+        tokio::spawn(connection);
+        let request = Request::builder().uri("https://localhost/tls").body(()).unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let mut body = response.await.unwrap().into_body();
+        let mut data = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.unwrap();
+            let _ = body.flow_control().release_capacity(chunk.len());
+            data.extend_from_slice(&chunk);
+        }
+        assert_eq!(data, b"h2");
+    }
+}
@@ -39,7 +39,7 @@ impl Default for PortMode {
     }
 }
 
-/// 证书配置（仅限 ECDSA+secp384r1+X.509+PEM）
+/// 证书配置（X.509 + PEM，支持 RSA、ECDSA P-256/P-384 和 Ed25519 密钥）
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CertificateConfig {
     /// 证书文件路径（PEM 格式）
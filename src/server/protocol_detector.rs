@@ -2,6 +2,148 @@
 //!
 //! 提供协议类型检测功能，用于混合模式下的协议自动识别
 
+use std::future::Future;
+
+use tokio::io::AsyncReadExt;
+
+use super::ProtocolType;
+
+/// 明文协议检测需要的最小预读字节数（足以覆盖 HTTP/2 前言）
+const MIN_DETECTION_BYTES: usize = 64;
+
+/// HTTP/1.x 请求行可能使用的方法
+const HTTP_METHODS: [&[u8]; 9] = [
+    b"GET ", b"POST ", b"PUT ", b"DELETE ", b"HEAD ", b"OPTIONS ", b"PATCH ", b"CONNECT ", b"TRACE ",
+];
+
+/// 检测数据是否为 TLS 记录（握手记录类型 0x16）
+pub fn is_tls_record(data: &[u8]) -> bool {
+    data.first() == Some(&0x16)
+}
+
+/// 预读用于协议检测的数据，返回读取的字节数
+///
+/// 首字节为 0x16 时立即返回：TLS 只凭首字节即可判定，完整的 ClientHello
+/// 由握手从预读数据加上原始连接中继续读取，不要求它能装进检测缓冲区。
+/// 明文连接读满 64 字节、请求头已经完整或者数据不可能是 HTTP 时返回，连接关闭时提前返回
+pub async fn read_detection_prefix(stream: &mut tokio::net::TcpStream, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut total_read = 0;
+    while total_read < buffer.len() {
+        match stream.read(&mut buffer[total_read..]).await? {
+            0 => break,
+            n => total_read += n,
+        }
+
+        if detection_complete(&buffer[..total_read]) {
+            break;
+        }
+    }
+    Ok(total_read)
+}
+
+/// 预读的数据是否已足以判定协议
+///
+/// 短于 64 字节的完整请求（例如只有请求行和 Host 的 GET）不必等待更多数据，
+/// SSH、MQTT 等不以 HTTP 方法开头的协议凭首个分段即可判定
+fn detection_complete(data: &[u8]) -> bool {
+    is_tls_record(data)
+        || data.len() >= MIN_DETECTION_BYTES
+        || data.windows(4).any(|w| w == b"\r\n\r\n")
+        || !may_be_http(data)
+}
+
+/// 数据是否可能是 HTTP/1.x 请求或 HTTP/2 前言的开头
+fn may_be_http(data: &[u8]) -> bool {
+    HTTP_METHODS.iter()
+        .chain(std::iter::once(&&b"PRI "[..]))
+        .any(|prefix| prefix.starts_with(data) || data.starts_with(prefix))
+}
+
+/// 基于预读数据的协议判定结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectedProtocol {
+    /// 判定的协议
+    pub protocol: ProtocolType,
+    /// 置信度（0.0 ~ 1.0），由命中的检测规则决定
+    pub confidence: f32,
+}
+
+impl DetectedProtocol {
+    /// `X-Detected-Protocol` 头部值，例如 `HTTP1_1; confidence=0.97`
+    pub fn header_value(&self) -> String {
+        format!("{:?}; confidence={:.2}", self.protocol, self.confidence)
+    }
+}
+
+tokio::task_local! {
+    static DETECTED_PROTOCOL: DetectedProtocol;
+}
+
+/// 在带有协议判定结果的作用域中处理连接
+///
+/// 连接处理函数在同一任务中创建连接上下文时读取该结果，随后传递给该连接上的每个请求
+pub async fn scope_detected<F: Future>(detected: DetectedProtocol, future: F) -> F::Output {
+    DETECTED_PROTOCOL.scope(detected, future).await
+}
+
+/// 当前连接的协议判定结果（不在判定作用域中时返回 None）
+pub fn current_detected() -> Option<DetectedProtocol> {
+    DETECTED_PROTOCOL.try_with(|detected| *detected).ok()
+}
+
+/// 根据预读数据判定协议并给出置信度
+///
+/// 置信度反映命中规则的可靠程度：TLS 记录头和 HTTP/2 前言是确定的，
+/// 完整的 HTTP/1.x 请求行接近确定，只能回退到默认 HTTP/1.1 时最低
+pub fn classify(data: &[u8]) -> DetectedProtocol {
+    let (protocol, confidence) = if is_tls_record(data) {
+        (ProtocolType::TLS, 1.0)
+    } else if data.starts_with(b"PRI * HTTP/2.0") {
+        (ProtocolType::HTTP2, 1.0)
+    } else if data.starts_with(b"SSH-") {
+        (ProtocolType::SSH, 1.0)
+    } else if is_mqtt_connect(data) {
+        (ProtocolType::MQTT, 0.95)
+    } else if is_redis_command(data) {
+        (ProtocolType::Redis, 0.9)
+    } else if is_grpc_request(data) {
+        (ProtocolType::GRPC, 0.9)
+    } else if is_websocket_upgrade(data) {
+        (ProtocolType::WebSocket, 0.95)
+    } else if HTTP_METHODS.iter().any(|m| data.starts_with(m)) {
+        let request_line = data.split(|&b| b == b'\n').next().unwrap_or(data);
+        if request_line.windows(8).any(|w| w == b"HTTP/1.0") {
+            (ProtocolType::HTTP1_0, 0.97)
+        } else if request_line.windows(8).any(|w| w == b"HTTP/1.1") {
+            (ProtocolType::HTTP1_1, 0.97)
+        } else {
+            // 请求行未读完，只凭方法名判定
+            (ProtocolType::HTTP1_1, 0.8)
+        }
+    } else {
+        (ProtocolType::HTTP1_1, 0.3)
+    };
+
+    DetectedProtocol { protocol, confidence }
+}
+
+/// 检测数据是否为 MQTT CONNECT 报文（协议名 `MQTT` 或 3.1 版本的 `MQIsdp`）
+fn is_mqtt_connect(data: &[u8]) -> bool {
+    if data.first() != Some(&0x10) {
+        return false;
+    }
+    // 固定头之后是 1~4 字节的剩余长度，随后是带长度前缀的协议名
+    let head = &data[..data.len().min(16)];
+    head.windows(6).any(|w| w == b"\x00\x04MQTT") || head.windows(8).any(|w| w == b"\x00\x06MQIsdp")
+}
+
+/// 检测数据是否为 RESP 格式的 Redis 命令（`*<参数个数>\r\n$<长度>\r\n...`）
+fn is_redis_command(data: &[u8]) -> bool {
+    data.first() == Some(&b'*')
+        && data.get(1).is_some_and(|b| b.is_ascii_digit())
+        && data.windows(3).any(|w| w == b"\r\n$")
+}
+
 /// 检测数据是否为 gRPC 请求
 ///
 /// 检测方法：
@@ -15,7 +157,7 @@ pub fn is_grpc_request(data: &[u8]) -> bool {
 
     // 方法1: 检查是否为 TLS ClientHello (0x16 = TLS record)
     // 如果是 TLS，需要 TLS 握手后才能判断内容
-    if data[0] == 0x16 {
+    if is_tls_record(data) {
         // TLS 连接，返回 false 让 TLS 处理器接管
         return false;
     }
@@ -42,3 +184,121 @@ pub fn is_grpc_request(data: &[u8]) -> bool {
 
     false
 }
+
+/// 检测数据是否为 WebSocket 升级请求
+///
+/// WebSocket 握手本身就是带 `Upgrade: websocket` 的 HTTP/1.1 GET 请求，
+/// 识别后交给 HTTP/1.1 处理器，由 hyper 的升级机制完成协议切换
+pub fn is_websocket_upgrade(data: &[u8]) -> bool {
+    if !data.starts_with(b"GET ") {
+        return false;
+    }
+
+    let head = String::from_utf8_lossy(data);
+    let head = head.split("\r\n\r\n").next().unwrap_or("");
+    head.lines().skip(1).any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("upgrade") && value.trim().eq_ignore_ascii_case("websocket")
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_upgrade_is_not_grpc() {
+        let upgrade = b"GET /chat HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n";
+        assert!(is_websocket_upgrade(upgrade));
+        assert!(!is_grpc_request(upgrade));
+
+        assert!(!is_websocket_upgrade(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n"));
+        assert!(!is_websocket_upgrade(b"POST /chat HTTP/1.1\r\nUpgrade: websocket\r\n\r\n"));
+        // 请求体中的 Upgrade 字样不算
+        assert!(!is_websocket_upgrade(b"GET / HTTP/1.1\r\nHost: a\r\n\r\nUpgrade: websocket"));
+    }
+
+    #[test]
+    fn test_classify_confidence() {
+        let detected = classify(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert_eq!(detected.protocol, ProtocolType::HTTP1_1);
+        assert_eq!(detected.header_value(), "HTTP1_1; confidence=0.97");
+
+        assert_eq!(classify(&[0x16, 0x03, 0x01]).protocol, ProtocolType::TLS);
+        assert_eq!(classify(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n").protocol, ProtocolType::HTTP2);
+        assert_eq!(classify(b"\x00\x01garbage").header_value(), "HTTP1_1; confidence=0.30");
+    }
+
+    #[test]
+    fn test_detection_stops_at_complete_short_request() {
+        assert!(detection_complete(b"GET / HTTP/1.1\r\nHost: x\r\n\r\n"));
+        assert!(detection_complete(b"SSH-2.0-OpenSSH_9.6\r\n"));
+        assert!(detection_complete(&[0x16, 0x03]));
+        // 请求头未读完时继续等待
+        assert!(!detection_complete(b"GE"));
+        assert!(!detection_complete(b"GET / HTTP/1.1\r\nHost: x\r\n"));
+        assert!(!detection_complete(b"PRI * HTTP/2.0\r\n"));
+    }
+
+    #[test]
+    fn test_classify_non_http_protocols() {
+        assert_eq!(classify(b"GET / HTTP/1.0\r\n\r\n").protocol, ProtocolType::HTTP1_0);
+        assert_eq!(classify(b"SSH-2.0-OpenSSH_9.6\r\n").protocol, ProtocolType::SSH);
+        assert_eq!(classify(b"\x10\x10\x00\x04MQTT\x04\x02\x00\x3c\x00\x04test").protocol, ProtocolType::MQTT);
+        assert_eq!(classify(b"\x10\x12\x00\x06MQIsdp\x03\x02\x00\x3c").protocol, ProtocolType::MQTT);
+        assert_eq!(classify(b"*1\r\n$4\r\nPING\r\n").protocol, ProtocolType::Redis);
+        // 以 * 开头但不是 RESP 的数据不算 Redis
+        assert_ne!(classify(b"*not-redis").protocol, ProtocolType::Redis);
+    }
+
+    #[tokio::test]
+    async fn test_large_client_hello_is_not_truncated() {
+        use std::sync::Arc;
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        // 长 SNI + 大量 ALPN，使 ClientHello 超过 1024 字节的检测缓冲区
+        let host = format!("{}.example.com", vec!["a".repeat(60); 3].join("."));
+        let cert = rcgen::generate_simple_self_signed(vec![host.clone()]).unwrap();
+        let cert_der = CertificateDer::from(cert.serialize_der().unwrap());
+        let key_der = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.serialize_private_key_der()));
+
+        let mut server_config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert_der.clone()], key_der)
+            .unwrap();
+        server_config.alpn_protocols = vec![b"proto-099".to_vec()];
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(cert_der).unwrap();
+        let mut client_config = rustls::ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        client_config.alpn_protocols = (0..100).map(|i| format!("proto-{:03}", i).into_bytes()).collect();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server_name = ServerName::try_from(host.clone()).unwrap();
+        let client = tokio::spawn(async move {
+            let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+            connector.connect(server_name, stream).await.map(|_| ())
+        });
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = [0u8; 1024];
+        let n = read_detection_prefix(&mut stream, &mut buffer).await.unwrap();
+        assert!(is_tls_record(&buffer[..n]));
+        assert!(!is_grpc_request(&buffer[..n]));
+
+        let reconstructed = crate::server::ReconstructedStream::new(stream, &buffer[..n]);
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+        let tls = acceptor.accept(reconstructed).await.unwrap();
+        let (_, connection) = tls.get_ref();
+        assert_eq!(connection.server_name(), Some(host.as_str()));
+        assert_eq!(connection.alpn_protocol(), Some(&b"proto-099"[..]));
+        client.await.unwrap().unwrap();
+    }
+}
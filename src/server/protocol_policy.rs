@@ -0,0 +1,210 @@
+//! 协议拦截策略
+//!
+//! TCP 层完成协议检测后，连接在分派到具体处理器之前先经过协议策略。
+//! 默认策略只放行引擎能够处理的协议（HTTP/1.x、HTTP/2、gRPC、TLS、WebSocket 升级请求，
+//! 以及按 HTTP/1.1 兜底处理的未知协议），其余协议直接关闭连接。
+//!
+//! 通过 `RatEngineBuilder::protocol_policy` 可以放宽或收紧这一策略：
+//!
+//! ```ignore
+//! let policy: ProtocolPolicy = Arc::new(|protocol, confidence| match protocol {
+//!     // 不接受 WebSocket
+//!     ProtocolType::WebSocket => BlockDecision::Block,
+//!     // 检测不可靠的连接返回 400
+//!     _ if confidence < 0.5 => BlockDecision::BlockWith(bad_request()),
+//!     other => default_protocol_policy(other, confidence),
+//! });
+//! ```
+//!
+//! 放行引擎没有处理器的协议（例如 SSH）不会让它们变得可用，连接仍会在分派时被拒绝。
+//!
+//! 策略返回 [`BlockDecision::Block`] 时默认不写任何数据直接关闭连接；
+//! 通过 `RatEngineBuilder::protocol_block_response` 设置 [`ProtocolBlockResponse`] 后，
+//! `Block` 等同于返回该响应的 [`BlockDecision::BlockWith`]：
+//!
+//! ```ignore
+//! let builder = RatEngine::builder()
+//!     .protocol_block_response(Some(
+//!         ProtocolBlockResponse::new(StatusCode::FORBIDDEN)
+//!             .with_header("content-type", "application/json")
+//!             .with_body(r#"{"error":"protocol_blocked"}"#),
+//!     ));
+//! ```
+
+use std::sync::Arc;
+
+use hyper::{Response, StatusCode};
+use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+use hyper::body::Bytes;
+use http_body_util::Full;
+
+use crate::server::ProtocolType;
+
+/// 协议策略的判定结果
+#[derive(Debug)]
+pub enum BlockDecision {
+    /// 放行，交给对应的协议处理器
+    Allow,
+    /// 拦截并直接关闭连接
+    Block,
+    /// 拦截，写回指定的 HTTP/1.1 响应后关闭连接
+    ///
+    /// TLS 连接和 HTTP/2 前言无法解析 HTTP/1.1 报文，这两种情况下等同于 [`BlockDecision::Block`]
+    BlockWith(Response<Full<Bytes>>),
+}
+
+/// 协议策略：参数为检测到的协议和检测置信度（0.0 ~ 1.0）
+pub type ProtocolPolicy = Arc<dyn Fn(&ProtocolType, f32) -> BlockDecision + Send + Sync>;
+
+/// 策略判定为 [`BlockDecision::Block`] 时返回的响应
+#[derive(Debug, Clone)]
+pub struct ProtocolBlockResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl ProtocolBlockResponse {
+    /// 创建只有状态码、没有响应体的拦截响应
+    pub fn new(status: StatusCode) -> Self {
+        Self { status, headers: HeaderMap::new(), body: Bytes::new() }
+    }
+
+    /// 添加响应头，名称或值无效时忽略
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            (Ok(name), Ok(value)) => {
+                self.headers.append(name, value);
+            }
+            _ => crate::utils::logger::warn!("⚠️ 忽略无效的拦截响应头: {}", name),
+        }
+        self
+    }
+
+    /// 设置响应体
+    pub fn with_body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// 构建响应
+    pub fn to_response(&self) -> Response<Full<Bytes>> {
+        let mut response = Response::new(Full::new(self.body.clone()));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        response
+    }
+}
+
+/// 默认协议策略：只放行引擎能够处理的协议
+pub fn default_protocol_policy(protocol: &ProtocolType, _confidence: f32) -> BlockDecision {
+    if has_handler(protocol) {
+        BlockDecision::Allow
+    } else {
+        BlockDecision::Block
+    }
+}
+
+/// 引擎是否有处理该协议的处理器
+pub(crate) fn has_handler(protocol: &ProtocolType) -> bool {
+    matches!(
+        protocol,
+        ProtocolType::HTTP1_0
+            | ProtocolType::HTTP1_1
+            | ProtocolType::HTTP2
+            | ProtocolType::GRPC
+            | ProtocolType::TLS
+            | ProtocolType::WebSocket
+            | ProtocolType::Unknown
+    )
+}
+
+/// 把拦截响应序列化为 HTTP/1.1 报文，并要求关闭连接
+pub(crate) fn to_http1_bytes(response: Response<Full<Bytes>>) -> Vec<u8> {
+    use http_body_util::BodyExt;
+    use futures_util::FutureExt;
+
+    let (parts, body) = response.into_parts();
+    // Full 的数据在内存中，收集不会挂起
+    let body = body.collect().now_or_never()
+        .map(|collected| match collected {
+            Ok(collected) => collected.to_bytes(),
+            Err(never) => match never {},
+        })
+        .unwrap_or_default();
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        parts.status.as_u16(),
+        parts.status.canonical_reason().unwrap_or("")
+    );
+    for (name, value) in &parts.headers {
+        if matches!(name.as_str(), "content-length" | "connection" | "transfer-encoding") {
+            continue;
+        }
+        if let Ok(value) = value.to_str() {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    head.push_str(&format!("content-length: {}\r\nconnection: close\r\n\r\n", body.len()));
+
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(&body);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_matches_supported_protocols() {
+        for protocol in [ProtocolType::HTTP1_1, ProtocolType::HTTP2, ProtocolType::GRPC, ProtocolType::TLS, ProtocolType::WebSocket] {
+            assert!(matches!(default_protocol_policy(&protocol, 1.0), BlockDecision::Allow));
+        }
+        for protocol in [ProtocolType::SSH, ProtocolType::MQTT, ProtocolType::Redis] {
+            assert!(matches!(default_protocol_policy(&protocol, 1.0), BlockDecision::Block));
+        }
+    }
+
+    #[test]
+    fn test_block_response_serialization() {
+        let response = Response::builder()
+            .status(403)
+            .header("content-type", "application/json")
+            .header("connection", "keep-alive")
+            .body(Full::new(Bytes::from(r#"{"error":"blocked"}"#)))
+            .unwrap();
+        let raw = String::from_utf8(to_http1_bytes(response)).unwrap();
+        assert!(raw.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        assert!(raw.contains("content-type: application/json\r\n"));
+        assert!(raw.contains("content-length: 19\r\nconnection: close\r\n\r\n"));
+        assert!(!raw.contains("keep-alive"));
+        assert!(raw.ends_with(r#"{"error":"blocked"}"#));
+    }
+
+    #[test]
+    fn test_block_response_applies_to_block_decisions() {
+        let mut router = crate::server::Router::new();
+        assert!(matches!(router.protocol_decision(&ProtocolType::SSH, 1.0), BlockDecision::Block));
+
+        router.set_protocol_block_response(Some(
+            ProtocolBlockResponse::new(StatusCode::FORBIDDEN)
+                .with_header("x-waf-rule", "protocol")
+                .with_body("blocked"),
+        ));
+        match router.protocol_decision(&ProtocolType::SSH, 1.0) {
+            BlockDecision::BlockWith(response) => {
+                assert_eq!(response.status(), StatusCode::FORBIDDEN);
+                assert_eq!(response.headers()["x-waf-rule"], "protocol");
+            }
+            other => panic!("unexpected decision: {:?}", other),
+        }
+        // 放行的协议不受影响
+        assert!(matches!(router.protocol_decision(&ProtocolType::HTTP1_1, 1.0), BlockDecision::Allow));
+
+        // 恢复为静默关闭
+        router.set_protocol_block_response(None);
+        assert!(matches!(router.protocol_decision(&ProtocolType::SSH, 1.0), BlockDecision::Block));
+    }
+}
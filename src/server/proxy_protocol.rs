@@ -4,12 +4,35 @@
 //! 获取原始客户端连接信息。
 
 use std::net::{SocketAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use crate::error::RatError;
 use crate::utils::logger;
 
 /// PROXY protocol v2 签名
 const PROXY_V2_SIGNATURE: &[u8] = b"\x0D\x0A\x0D\x0A\x00\x0D\x0A\x51\x55\x49\x54\x0A";
 
+/// PROXY protocol v2 固定头部长度（签名 + 版本/命令 + 地址族 + 长度）
+pub const PROXY_V2_FIXED_HEADER_LEN: usize = 16;
+
+/// PROXY 头部读取配置
+#[derive(Debug, Clone, Copy)]
+pub struct ProxyProtocolConfig {
+    /// 允许的最大头部长度（含 16 字节固定头部），超过则断开连接
+    pub max_header_size: usize,
+    /// 读取完整头部的超时时间
+    pub read_timeout: Duration,
+}
+
+impl Default for ProxyProtocolConfig {
+    fn default() -> Self {
+        Self {
+            max_header_size: 4096,
+            read_timeout: Duration::from_secs(3),
+        }
+    }
+}
+
 /// PROXY protocol v2 命令类型
 #[derive(Debug, Clone, Copy)]
 pub enum ProxyCommand {
@@ -110,6 +133,74 @@ impl ProxyProtocolV2Parser {
         data.starts_with(PROXY_V2_SIGNATURE) && (data[12] & 0xF0) == 0x20
     }
 
+    /// 检查数据是否可能是 PROXY protocol v2 头部的开头
+    ///
+    /// 数据不足 16 字节时只比较已到达的部分签名，用于判断是否需要继续读取
+    pub fn may_be_proxy_v2(data: &[u8]) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+        if data.len() >= PROXY_V2_FIXED_HEADER_LEN {
+            return Self::is_proxy_v2(data);
+        }
+        let n = data.len().min(PROXY_V2_SIGNATURE.len());
+        data[..n] == PROXY_V2_SIGNATURE[..n] && (data.len() <= 12 || (data[12] & 0xF0) == 0x20)
+    }
+
+    /// 根据固定头部中的长度字段（第 14-15 字节）计算完整头部长度
+    pub fn header_len(data: &[u8]) -> Option<usize> {
+        if data.len() < PROXY_V2_FIXED_HEADER_LEN {
+            return None;
+        }
+        Some(PROXY_V2_FIXED_HEADER_LEN + u16::from_be_bytes([data[14], data[15]]) as usize)
+    }
+
+    /// 持续读取直到 `buffer` 中包含完整的 PROXY v2 头部
+    ///
+    /// `buffer` 为已读取的数据，可能只包含部分头部；新读取的数据会追加到其中。
+    ///
+    /// # 返回值
+    /// * `Ok(Some(len))` - 完整头部长度，`buffer[len..]` 为之后的应用数据
+    /// * `Ok(None)` - 数据并非 PROXY v2 头部
+    /// * `Err` - 超时、超过最大长度或连接提前关闭
+    pub async fn read_full_header<S>(
+        stream: &mut S,
+        buffer: &mut Vec<u8>,
+        config: &ProxyProtocolConfig,
+    ) -> Result<Option<usize>, RatError>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let read_loop = async {
+            let mut chunk = [0u8; 1024];
+            loop {
+                if !Self::may_be_proxy_v2(buffer) {
+                    return Ok(None);
+                }
+                if let Some(len) = Self::header_len(buffer) {
+                    if len > config.max_header_size {
+                        return Err(RatError::SecurityError(format!(
+                            "PROXY protocol v2 头部过大: {} 字节（上限 {} 字节）", len, config.max_header_size
+                        )));
+                    }
+                    if buffer.len() >= len {
+                        return Ok(Some(len));
+                    }
+                }
+
+                match stream.read(&mut chunk).await {
+                    Ok(0) => return Err(RatError::NetworkError("连接在 PROXY 头部读取完成前关闭".to_string())),
+                    Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                    Err(e) => return Err(RatError::NetworkError(format!("读取 PROXY 头部失败: {}", e))),
+                }
+            }
+        };
+
+        tokio::time::timeout(config.read_timeout, read_loop)
+            .await
+            .map_err(|_| RatError::TimeoutError(format!("读取 PROXY 头部超时（{:?}）", config.read_timeout)))?
+    }
+
     /// 解析 PROXY protocol v2 头部
     pub fn parse(data: &[u8]) -> Result<ProxyProtocolV2Info, RatError> {
         if data.len() < 16 {
@@ -285,6 +376,51 @@ impl ProxyProtocolV2Parser {
 mod tests {
     use super::*;
 
+    fn proxy_v2_header(payload_len: u16) -> Vec<u8> {
+        let mut data = PROXY_V2_SIGNATURE.to_vec();
+        data.push(0x21); // v2 + PROXY
+        data.push(0x11); // INET + STREAM
+        data.extend_from_slice(&payload_len.to_be_bytes());
+        data.resize(data.len() + payload_len as usize, 0);
+        data
+    }
+
+    #[tokio::test]
+    async fn test_read_header_split_across_segments() {
+        let header = proxy_v2_header(12);
+        let mut buffer = header[..10].to_vec();
+        let mut rest: &[u8] = &[&header[10..], b"GET / HTTP/1.1\r\n".as_slice()].concat();
+
+        let len = ProxyProtocolV2Parser::read_full_header(&mut rest, &mut buffer, &ProxyProtocolConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(len, Some(header.len()));
+        assert!(buffer.len() >= header.len());
+    }
+
+    #[tokio::test]
+    async fn test_read_header_rejects_oversized_and_truncated() {
+        let config = ProxyProtocolConfig { max_header_size: 64, ..Default::default() };
+
+        let mut buffer = proxy_v2_header(200)[..16].to_vec();
+        let mut empty: &[u8] = &[];
+        assert!(ProxyProtocolV2Parser::read_full_header(&mut empty, &mut buffer, &config).await.is_err());
+
+        let mut buffer = proxy_v2_header(32)[..20].to_vec();
+        let mut empty: &[u8] = &[];
+        assert!(ProxyProtocolV2Parser::read_full_header(&mut empty, &mut buffer, &config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_header_non_proxy_data() {
+        let mut buffer = b"GET / HTTP/1.1\r\n".to_vec();
+        let mut empty: &[u8] = &[];
+        let result = ProxyProtocolV2Parser::read_full_header(&mut empty, &mut buffer, &ProxyProtocolConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(result, None);
+    }
+
     #[test]
     fn test_proxy_v2_signature() {
         assert!(ProxyProtocolV2Parser::is_proxy_v2(b"\x0D\x0A\x0D\x0A\x00\x0D\x0A\x51\x55\x49\x54\x0A\x20\x00\x00\x00"));
@@ -15,7 +15,7 @@ use hyper::body::Incoming;
 use hyper::http;
 use http_body_util::{Full, combinators::BoxBody, BodyExt};
 use hyper::body::Bytes;
-use crate::server::streaming::{StreamingBody, StreamingResponse, SseResponse, ChunkedResponse};
+use crate::server::streaming::{StreamingBody, StreamingResponse, SseResponse, ChunkedResponse, NdjsonResponse};
 use crate::server::http_request::HttpRequest;
 use crate::server::config::SpaConfig;
 use std::collections::{HashMap, HashSet};
@@ -24,7 +24,7 @@ use std::future::Future;
 use std::pin::Pin;
 use std::net::{SocketAddr, IpAddr};
 use std::str::FromStr;
-use crate::utils::ip_extractor::{IpExtractor, IpInfo};
+use crate::utils::ip_extractor::{IpExtractor, IpInfo, RealIpConfig};
 use crate::server::config::ServerConfig;
 use regex::Regex;
 use crate::common::path_params::compile_pattern;
@@ -34,10 +34,88 @@ use h2::server::{Connection, SendResponse};
 use h2::RecvStream;
 
 // HTTP 处理器类型定义
+/// 路由匹配前的路径重写函数，返回 None 表示保持原路径
+pub type PathRewriteFn = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// 读取请求体之前的检查函数，返回 `Some(响应)` 表示直接拒绝
+pub type BeforeBodyCheck = Arc<dyn Fn(&http::request::Parts) -> Option<Response<Full<Bytes>>> + Send + Sync>;
+
+/// 按状态码执行的响应钩子，见 [`Router::on_status`]
+pub type StatusHook = Arc<dyn Fn(&mut Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>) + Send + Sync>;
+
 pub type HttpAsyncHandler = Arc<dyn Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> + Send + Sync>;
 
 pub type HttpStreamingHandler = Arc<dyn Fn(HttpRequest, HashMap<String, String>) -> Pin<Box<dyn Future<Output = Result<Response<StreamingBody>, hyper::Error>> + Send>> + Send + Sync>;
 
+/// 请求路径默认允许的最大段数
+pub const DEFAULT_MAX_PATH_SEGMENTS: usize = 64;
+
+/// 查询字符串默认允许的最大参数个数
+pub const DEFAULT_MAX_QUERY_PARAMS: usize = 1000;
+
+/// 路由器生成的响应使用的 `server` 头部
+const SERVER_HEADER: &str = concat!("RAT-Engine/", env!("CARGO_PKG_VERSION"));
+
+/// 计算 `Allow` 列表时检查的方法
+const ALLOW_CANDIDATE_METHODS: [Method; 7] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::PATCH,
+    Method::OPTIONS,
+];
+
+/// 自动方法处理配置
+///
+/// 统一控制符合标准的方法处理行为：HEAD 回退到 GET 处理器、OPTIONS 自动响应、
+/// 路径存在但方法不匹配时返回 405，各项默认全部启用
+#[derive(Debug, Clone)]
+pub struct AutoMethodsConfig {
+    /// HEAD 请求自动使用 GET 处理器（丢弃响应体）
+    pub auto_head: bool,
+    /// 自动响应 OPTIONS：配置了 CORS 时按预检处理，其余请求返回 `Allow` 列表
+    pub auto_options: bool,
+    /// 路径存在但方法不匹配时返回 `405 Method Not Allowed` 并附带 `Allow` 头部
+    pub method_not_allowed: bool,
+}
+
+impl Default for AutoMethodsConfig {
+    fn default() -> Self {
+        Self {
+            auto_head: true,
+            auto_options: true,
+            method_not_allowed: true,
+        }
+    }
+}
+
+impl AutoMethodsConfig {
+    /// 创建默认配置（全部启用）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置是否自动处理 HEAD
+    pub fn auto_head(mut self, enabled: bool) -> Self {
+        self.auto_head = enabled;
+        self
+    }
+
+    /// 设置是否自动响应 OPTIONS
+    pub fn auto_options(mut self, enabled: bool) -> Self {
+        self.auto_options = enabled;
+        self
+    }
+
+    /// 设置方法不匹配时是否返回 405
+    pub fn method_not_allowed(mut self, enabled: bool) -> Self {
+        self.method_not_allowed = enabled;
+        self
+    }
+}
+
 
 
 /// 路由参数映射信息
@@ -724,6 +802,12 @@ pub struct Router {
     compressor: Option<Arc<crate::compression::Compressor>>,
     #[cfg(feature = "cache")]
     cache_middleware: Option<Arc<crate::server::cache_middleware_impl::CacheMiddlewareImpl>>,
+    // 按路由覆盖的缓存 TTL（handler_id -> TTL，精确到毫秒）
+    #[cfg(feature = "cache")]
+    route_cache_ttls: HashMap<usize, std::time::Duration>,
+    // 是否在响应中保留 x-cache 等缓存状态头部
+    #[cfg(feature = "cache")]
+    expose_cache_headers: bool,
 
 
     protocol_detection_middleware: Option<Arc<crate::server::protocol_detection_middleware::ProtocolDetectionMiddleware>>,
@@ -746,6 +830,111 @@ pub struct Router {
     // HEAD 请求回退到 GET 的配置
     head_fallback_enabled: bool,
     head_fallback_whitelist: Option<HashSet<String>>,
+
+    // 自动方法处理（OPTIONS 自动响应、405）
+    auto_methods: Option<AutoMethodsConfig>,
+    /// 是否响应 `OPTIONS *`（服务器级能力查询）
+    asterisk_options: bool,
+
+    // Host 头部校验
+    require_host: bool,
+
+    // 虚拟主机：主机模式 -> 独立的子路由器（支持 *.example.com 通配符）
+    virtual_hosts: Vec<(String, Router)>,
+
+    // 性能指标（用于统计活跃请求数）
+    metrics: Option<Arc<crate::engine::metrics::AtomicMetrics>>,
+
+    // 兜底路由：任何未匹配的请求（在 404 之前）
+    fallback_handler: Option<HttpAsyncHandler>,
+
+    // PROXY protocol 头部读取限制
+    proxy_protocol_config: crate::server::proxy_protocol::ProxyProtocolConfig,
+
+    // 构建信息端点路径
+    info_endpoint: Option<String>,
+
+    // 指标端点路径
+    metrics_endpoint: Option<String>,
+
+    // 请求路径允许的最大段数（超过时在路由匹配前返回 400）
+    max_path_segments: usize,
+
+    // 查询字符串允许的最大参数个数（超过时在路由匹配前返回 400）
+    max_query_params: usize,
+
+    // 是否对提取的路径参数做百分号解码
+    decode_path_params: bool,
+
+    // 客户端真实 IP 解析配置（None 时使用 HttpRequest 内置的头部优先级）
+    real_ip_config: Option<Arc<RealIpConfig>>,
+
+    // 响应体转换规则（按添加顺序执行，仅作用于普通路由）
+    body_transforms: Vec<crate::server::body_transform::BodyTransformRule>,
+
+    // 握手并发限制（None 表示不限制）
+    handshake_limiter: Option<Arc<crate::server::handshake_limiter::HandshakeLimiter>>,
+
+    // 路由匹配前的路径重写
+    path_rewrite: Option<PathRewriteFn>,
+
+    // 请求体大小上限（按解码后的字节计算，None 表示不限制）
+    max_request_body_size: Option<usize>,
+    /// 读取请求体的超时
+    request_body_timeout: Option<std::time::Duration>,
+
+    // 读取请求体之前的检查（认证、限流等）
+    before_body_check: Option<BeforeBodyCheck>,
+
+    // 按状态码执行的响应钩子
+    status_hooks: HashMap<StatusCode, Vec<StatusHook>>,
+
+    // 按路径配置的访问日志级别（None 表示不记录）
+    access_log_levels: Vec<(String, Option<crate::utils::logger::LogLevel>)>,
+
+    // 默认响应头（如安全头部），不覆盖处理器已设置的同名头部
+    default_headers: hyper::HeaderMap,
+
+    // Strict-Transport-Security 默认头部，单独保存以免被 default_headers() 替换
+    hsts: Option<hyper::header::HeaderValue>,
+
+    // 处理器 panic 时是否转换为 500 响应（默认开启）
+    catch_handler_panics: bool,
+
+    // 单个请求的处理期限（None 表示不限制）
+    request_timeout: Option<std::time::Duration>,
+
+    // 读取客户端处理期限的请求头（小写，None 表示不读取）
+    timeout_header: Option<String>,
+
+    // HTTP/1.1 keep-alive 连接空闲超时（None 表示不限制）
+    connection_idle_timeout: Option<std::time::Duration>,
+
+    // TLS 握手超时（None 表示不限制）
+    tls_handshake_timeout: Option<std::time::Duration>,
+    /// HTTP/2 连接参数与洪泛防护阈值
+    h2_config: crate::server::h2_config::H2Config,
+    /// 引擎关闭信号，连接据此优雅关闭
+    shutdown_signal: crate::server::graceful_shutdown::ShutdownSignal,
+
+    // 是否在响应中添加协议检测调试头部
+    expose_detection_debug: bool,
+    /// 启动时只输出路由数量摘要
+    quiet_startup: bool,
+    /// 响应体默认写出速率上限
+    response_rate_limit: Option<crate::server::throttle::BandwidthLimit>,
+
+    // 协议拦截策略
+    protocol_policy: crate::server::protocol_policy::ProtocolPolicy,
+
+    // 协议策略判定为 Block 时返回的响应（None 表示静默关闭连接）
+    protocol_block_response: Option<crate::server::protocol_policy::ProtocolBlockResponse>,
+
+    // 可失败路由的错误处理器（路由注册后仍可替换，因此共享给已注册的处理器）
+    error_handler: Arc<std::sync::RwLock<crate::server::into_response::ErrorHandler>>,
+
+    // 应用共享状态（按类型索引）
+    state: crate::server::app_state::AppState,
 }
 
 impl Router {
@@ -765,6 +954,10 @@ impl Router {
             compressor: None,
             #[cfg(feature = "cache")]
             cache_middleware: None,
+            #[cfg(feature = "cache")]
+            route_cache_ttls: HashMap::new(),
+            #[cfg(feature = "cache")]
+            expose_cache_headers: true,
             protocol_detection_middleware: None,
             grpc_registry: grpc_registry.clone(),
             grpc_handler: Some(Arc::new(GrpcRequestHandler::new(grpc_registry))),
@@ -775,6 +968,43 @@ impl Router {
             grpc_only_mode: false,
             head_fallback_enabled: false,
             head_fallback_whitelist: None,
+            auto_methods: None,
+            asterisk_options: true,
+            require_host: true,
+            virtual_hosts: Vec::new(),
+            metrics: None,
+            fallback_handler: None,
+            proxy_protocol_config: crate::server::proxy_protocol::ProxyProtocolConfig::default(),
+            info_endpoint: None,
+            metrics_endpoint: None,
+            max_path_segments: DEFAULT_MAX_PATH_SEGMENTS,
+            max_query_params: DEFAULT_MAX_QUERY_PARAMS,
+            decode_path_params: false,
+            real_ip_config: None,
+            body_transforms: Vec::new(),
+            handshake_limiter: None,
+            path_rewrite: None,
+            max_request_body_size: None,
+            request_body_timeout: None,
+            before_body_check: None,
+            status_hooks: HashMap::new(),
+            access_log_levels: Vec::new(),
+            default_headers: hyper::HeaderMap::new(),
+            hsts: None,
+            catch_handler_panics: true,
+            request_timeout: None,
+            timeout_header: None,
+            connection_idle_timeout: None,
+            tls_handshake_timeout: None,
+            h2_config: Default::default(),
+            shutdown_signal: Default::default(),
+            expose_detection_debug: false,
+            quiet_startup: false,
+            response_rate_limit: None,
+            protocol_policy: Arc::new(crate::server::protocol_policy::default_protocol_policy),
+            protocol_block_response: None,
+            error_handler: Arc::new(std::sync::RwLock::new(Arc::new(crate::server::into_response::default_error_handler))),
+            state: crate::server::app_state::AppState::new(),
         }
     }
 
@@ -816,6 +1046,220 @@ impl Router {
         self
     }
 
+    /// 注册 `GET /favicon.ico`，返回给定的图标内容
+    ///
+    /// 浏览器会自动请求该路径，没有图标时传入空内容即可返回 `204 No Content`，
+    /// 避免日志中出现大量 404
+    pub fn default_favicon(&mut self, icon: impl Into<Bytes>) -> &mut Self {
+        let icon = icon.into();
+        self.add_route(Method::GET, "/favicon.ico", move |_req| {
+            let mut response = Response::new(Full::new(icon.clone()));
+            if icon.is_empty() {
+                *response.status_mut() = StatusCode::NO_CONTENT;
+            } else {
+                let headers = response.headers_mut();
+                headers.insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("image/x-icon"));
+                headers.insert(hyper::header::CACHE_CONTROL, hyper::header::HeaderValue::from_static("public, max-age=86400"));
+            }
+            Box::pin(async move { Ok(response) })
+        })
+    }
+
+    /// 注册 `GET /robots.txt`，返回给定的文本内容
+    pub fn default_robots(&mut self, content: impl Into<String>) -> &mut Self {
+        let content = Bytes::from(content.into());
+        self.add_route(Method::GET, "/robots.txt", move |_req| {
+            let mut response = Response::new(Full::new(content.clone()));
+            let headers = response.headers_mut();
+            headers.insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("text/plain; charset=utf-8"));
+            headers.insert(hyper::header::CACHE_CONTROL, hyper::header::HeaderValue::from_static("public, max-age=86400"));
+            Box::pin(async move { Ok(response) })
+        })
+    }
+
+    /// 设置路由匹配前的路径重写函数
+    ///
+    /// 函数接收原始请求路径，返回 `Some(新路径)` 时按新路径匹配路由（查询字符串保留），
+    /// 返回 `None` 时保持原路径。适用于反向代理剥离挂载前缀、旧地址兼容等场景，
+    /// 无需为每种前缀重复注册路由
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rat_engine::server::Router;
+    ///
+    /// let mut router = Router::new();
+    /// // 应用挂载在 /app 下，路由按去掉前缀后的路径注册
+    /// router.with_path_rewrite(|path| {
+    ///     path.strip_prefix("/app").map(|rest| if rest.is_empty() { "/".to_string() } else { rest.to_string() })
+    /// });
+    /// ```
+    pub fn with_path_rewrite<F>(&mut self, rewrite: F) -> &mut Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.path_rewrite = Some(Arc::new(rewrite));
+        self
+    }
+
+    /// 设置读取请求体之前的检查
+    ///
+    /// 检查只能看到请求行和请求头，返回 `Some(响应)` 时直接返回该响应，不再读取请求体。
+    /// 对携带 `Expect: 100-continue` 的 HTTP/1.1 请求，拒绝发生在 `100 Continue` 之前，
+    /// 客户端不会上传被拒绝的请求体。适合放置认证、限流等不依赖请求体的检查
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rat_engine::server::Router;
+    /// use rat_engine::{Response, Full, Bytes, StatusCode};
+    ///
+    /// let mut router = Router::new();
+    /// router.with_before_body_check(|parts| {
+    ///     if parts.headers.contains_key("authorization") {
+    ///         return None;
+    ///     }
+    ///     let mut response = Response::new(Full::new(Bytes::from("Unauthorized")));
+    ///     *response.status_mut() = StatusCode::UNAUTHORIZED;
+    ///     Some(response)
+    /// });
+    /// ```
+    pub fn with_before_body_check<F>(&mut self, check: F) -> &mut Self
+    where
+        F: Fn(&http::request::Parts) -> Option<Response<Full<Bytes>>> + Send + Sync + 'static,
+    {
+        self.before_body_check = Some(Arc::new(check));
+        self
+    }
+
+    /// 设置默认响应头
+    ///
+    /// 这些头部会附加到所有协议路径（HTTP/1.1、HTTP/2、工作窃取路径）的每个响应上，
+    /// 处理器已设置的同名头部不会被覆盖。常用于统一添加安全头部
+    ///
+    /// 同名响应头的处理规则：
+    /// - 默认响应头：仅在响应中不存在该头部时添加（insert-if-absent）
+    /// - CORS 等显式中间件：替换处理器设置的同名头部，以中间件配置为准
+    /// - 处理器自身：使用 [`crate::response::set_header`] 替换，[`crate::response::append_header`] 追加
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rat_engine::server::Router;
+    /// use rat_engine::HeaderMap;
+    /// use hyper::header::HeaderValue;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("x-content-type-options", HeaderValue::from_static("nosniff"));
+    /// headers.insert("x-frame-options", HeaderValue::from_static("DENY"));
+    ///
+    /// let mut router = Router::new();
+    /// router.default_headers(headers);
+    /// ```
+    pub fn default_headers(&mut self, headers: hyper::HeaderMap) -> &mut Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// 添加 `Strict-Transport-Security` 默认响应头
+    ///
+    /// 与 [`Router::default_headers`] 分开保存、在合并默认响应头时一起应用，调用顺序无关；
+    /// 两者都设置了该头部时以此处为准
+    pub fn with_hsts(&mut self, max_age: std::time::Duration, include_subdomains: bool, preload: bool) -> &mut Self {
+        let mut value = format!("max-age={}", max_age.as_secs());
+        if include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if preload {
+            value.push_str("; preload");
+        }
+        // 值只由数字和固定的 ASCII 指令组成，总是合法的头部值
+        self.hsts = hyper::header::HeaderValue::from_str(&value).ok();
+        self
+    }
+
+    /// 为指定状态码的响应注册钩子
+    ///
+    /// 钩子在处理器和错误映射完成之后、合并默认响应头之前执行，可以修改响应头、响应体甚至状态码，
+    /// 适合为所有 401 添加 `WWW-Authenticate`、统一 5xx 的响应体这类横切需求。
+    /// 同一状态码可注册多个钩子，按注册顺序执行；钩子修改状态码后不会再触发新状态码的钩子。
+    /// 路由器生成的 413、408 等拒绝响应同样会经过钩子
+    ///
+    /// # 示例
+    ///
+    /// ```ignore
+    /// router.on_status(StatusCode::UNAUTHORIZED, |resp| {
+    ///     resp.headers_mut().insert("www-authenticate", HeaderValue::from_static("Bearer realm=\"api\""));
+    /// });
+    /// ```
+    pub fn on_status<F>(&mut self, status: StatusCode, hook: F) -> &mut Self
+    where
+        F: Fn(&mut Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>) + Send + Sync + 'static,
+    {
+        self.status_hooks.entry(status).or_default().push(Arc::new(hook));
+        self
+    }
+
+    /// 执行与响应状态码对应的钩子
+    fn apply_status_hooks(&self, response: &mut Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>) {
+        if let Some(hooks) = self.status_hooks.get(&response.status()) {
+            for hook in hooks {
+                hook(response);
+            }
+        }
+    }
+
+    /// 将默认响应头合并到响应头中（已存在的同名头部保持不变）
+    pub fn apply_default_headers(&self, headers: &mut hyper::HeaderMap) {
+        if let Some(hsts) = &self.hsts {
+            if !headers.contains_key(hyper::header::STRICT_TRANSPORT_SECURITY) {
+                headers.insert(hyper::header::STRICT_TRANSPORT_SECURITY, hsts.clone());
+            }
+        }
+        for name in self.default_headers.keys() {
+            if !headers.contains_key(name) {
+                for value in self.default_headers.get_all(name) {
+                    headers.append(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    /// 注册应用共享状态
+    ///
+    /// 处理器通过 `req.state::<T>()` 获取，每种类型保存一个实例，重复注册会替换旧值。
+    /// 虚拟主机子路由器使用各自注册的状态
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use rat_engine::server::Router;
+    ///
+    /// struct AppConfig { greeting: String }
+    ///
+    /// let mut router = Router::new();
+    /// router.with_state(Arc::new(AppConfig { greeting: "hello".to_string() }));
+    /// ```
+    pub fn with_state<T: Send + Sync + 'static>(&mut self, value: Arc<T>) -> &mut Self {
+        self.state.insert(value);
+        self
+    }
+
+    /// 注册兜底路由
+    ///
+    /// 任何方法、任何路径在没有匹配到具体路由时都会交给该处理器（在返回 404 之前），
+    /// 具体路由始终优先。完整路径可通过 `req.path()` 获取，
+    /// 去掉前导斜杠的路径也会作为路径参数 `path` 传入。
+    pub fn add_fallback<H>(&mut self, handler: H) -> &mut Self
+    where
+        H: Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> + Send + Sync + 'static,
+    {
+        self.fallback_handler = Some(Arc::new(handler));
+        crate::utils::logger::debug!("🔧 [Router] 注册兜底路由");
+        self
+    }
+
     /// 添加支持多个 HTTP 方法的路由
     pub fn add_route_with_methods<H, I>(&mut self, methods: I, path: impl Into<String>, handler: H) -> &mut Self
     where
@@ -835,6 +1279,151 @@ impl Router {
         self
     }
 
+    /// 添加自动设置客户端缓存头部的路由
+    ///
+    /// 成功响应会按 `cache_control` 补上 `Cache-Control` 和 `Expires`，
+    /// 处理器自行设置了 `Cache-Control` 时以处理器为准
+    pub fn add_route_with_cache_control<H>(
+        &mut self,
+        method: Method,
+        path: impl Into<String>,
+        cache_control: crate::server::cache_control::CacheControl,
+        handler: H,
+    ) -> &mut Self
+    where
+        H: Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> + Send + Sync + 'static,
+    {
+        self.add_route(method, path, move |req| -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> {
+            let response = handler(req);
+            Box::pin(async move {
+                let mut response = response.await?;
+                cache_control.apply(&mut response);
+                Ok(response)
+            })
+        })
+    }
+
+    /// 添加限制响应写出速率的路由
+    ///
+    /// 响应体按 `bytes_per_sec` 匀速写出，适用于大文件下载或模拟慢速响应
+    pub fn add_route_with_rate<H>(&mut self, method: Method, path: impl Into<String>, bytes_per_sec: u64, handler: H) -> &mut Self
+    where
+        H: Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> + Send + Sync + 'static,
+    {
+        self.add_route(method, path, move |req| -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> {
+            let response = handler(req);
+            Box::pin(async move {
+                Ok(crate::server::throttle::with_bandwidth_limit(response.await?, bytes_per_sec))
+            })
+        })
+    }
+
+    /// 设置所有 HTTP 响应默认的写出速率上限（None 表示不限制）
+    ///
+    /// 单独标记了速率的响应以标记为准。
+    ///
+    /// 速率按单个响应计算，不是连接级上限：HTTP/2 连接上的每个流各自获得完整速率，
+    /// 并发 N 个流的客户端可以得到 N 倍带宽，见 [`crate::server::throttle`]
+    pub fn set_response_rate_limit(&mut self, bytes_per_sec: Option<u64>) -> &mut Self {
+        self.response_rate_limit = bytes_per_sec.map(crate::server::throttle::BandwidthLimit::new);
+        self
+    }
+
+    /// 添加基于主机名的 HTTP 路由
+    ///
+    /// 等价于 `router.for_host(host).add_route(method, path, handler)`
+    ///
+    /// # 示例
+    /// ```rust
+    /// # use rat_engine::server::Router;
+    /// # use rat_engine::{Method, Response, Full, Bytes};
+    /// # let mut router = Router::new();
+    /// router.add_host_route("api.example.com", Method::GET, "/status", |_req| {
+    ///     Box::pin(async { Ok(Response::new(Full::new(Bytes::from("api")))) })
+    /// });
+    /// ```
+    pub fn add_host_route<H>(&mut self, host: impl Into<String>, method: Method, path: impl Into<String>, handler: H) -> &mut Self
+    where
+        H: Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> + Send + Sync + 'static,
+    {
+        self.for_host(host).add_route(method, path, handler);
+        self
+    }
+
+    /// 获取（或创建）指定主机的虚拟主机子路由器
+    ///
+    /// 子路由器拥有独立的路由表，只有请求的 Host 与 `host` 匹配时才会生效：
+    /// - 精确匹配：`api.example.com`
+    /// - 通配符匹配：`*.example.com` 匹配任意子域名（不包含 `example.com` 本身）
+    ///
+    /// 精确匹配优先于通配符，较长的通配符后缀优先于较短的。
+    /// 未匹配任何虚拟主机、或虚拟主机中没有该路径的路由（且未配置兜底路由）时，
+    /// 请求回退到当前路由器的默认路由。
+    ///
+    /// 子路由器只提供路由表、兜底路由和应用状态；CORS、压缩、默认头部、自动方法、
+    /// SPA 回退等中间件统一使用当前路由器的配置，在子路由器上设置不会生效。
+    /// 缓存键不区分主机，虚拟主机路由不参与响应缓存。
+    ///
+    /// # 示例
+    /// ```rust
+    /// # use rat_engine::server::Router;
+    /// # use rat_engine::{Method, Response, Full, Bytes};
+    /// # let mut router = Router::new();
+    /// router.for_host("api.example.com")
+    ///     .add_route(Method::GET, "/users", |_req| {
+    ///         Box::pin(async { Ok(Response::new(Full::new(Bytes::from("users")))) })
+    ///     });
+    /// router.for_host("*.example.com")
+    ///     .add_route(Method::GET, "/", |_req| {
+    ///         Box::pin(async { Ok(Response::new(Full::new(Bytes::from("tenant")))) })
+    ///     });
+    /// ```
+    pub fn for_host(&mut self, host: impl Into<String>) -> &mut Router {
+        let pattern = HttpRequest::normalize_host(&host.into());
+        let index = match self.virtual_hosts.iter().position(|(p, _)| *p == pattern) {
+            Some(index) => index,
+            None => {
+                crate::utils::logger::debug!("🔧 [Router] 创建虚拟主机: {}", pattern);
+                let mut sub_router = Router::new();
+                // Host 校验由顶层路由器完成
+                sub_router.require_host = false;
+                // 路径段数限制同样由顶层路由器校验
+                sub_router.max_path_segments = usize::MAX;
+                sub_router.max_query_params = usize::MAX;
+                self.virtual_hosts.push((pattern, sub_router));
+                self.virtual_hosts.len() - 1
+            }
+        };
+        &mut self.virtual_hosts[index].1
+    }
+
+    /// 查找与请求主机匹配的虚拟主机子路由器
+    fn match_virtual_host(&self, req: &HttpRequest) -> Option<&Router> {
+        if self.virtual_hosts.is_empty() {
+            return None;
+        }
+        let host = req.host()?;
+
+        // 1. 精确匹配
+        if let Some((_, router)) = self.virtual_hosts.iter().find(|(pattern, _)| *pattern == host) {
+            return Some(router);
+        }
+
+        // 2. 通配符匹配（最长后缀优先）
+        self.virtual_hosts
+            .iter()
+            .filter_map(|(pattern, router)| {
+                let suffix = pattern.strip_prefix("*")?;
+                if host.len() > suffix.len() && host.ends_with(suffix) {
+                    Some((suffix.len(), router))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, router)| router)
+    }
+
     /// 添加流式 HTTP 路由 (🆕 基于 Radix Tree)
     pub fn add_streaming_route<H>(&mut self, method: Method, path: impl Into<String>, handler: H) -> &mut Self
     where
@@ -852,6 +1441,99 @@ impl Router {
         self
     }
 
+    /// 添加带请求体校验的路由
+    ///
+    /// 请求体按 JSON 反序列化为 `T` 并调用 [`Validate::validate`](crate::server::validation::Validate::validate)，
+    /// 失败时直接返回 422（JSON 语法错误返回 400）并列出字段级错误，处理器只会收到合法的 `T`
+    pub fn add_validated_route<T, H>(&mut self, method: Method, path: impl Into<String>, handler: H) -> &mut Self
+    where
+        T: serde::de::DeserializeOwned + crate::server::validation::Validate + Send + 'static,
+        H: Fn(HttpRequest, T) -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> + Send + Sync + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.add_route(method, path, move |req| -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> {
+            match crate::server::validation::parse_and_validate::<T>(&req.body) {
+                Ok(value) => handler(req, value),
+                Err(response) => Box::pin(async move { Ok(response) }),
+            }
+        })
+    }
+
+    /// 添加可失败的路由
+    ///
+    /// 处理器返回 `Result<impl IntoResponse, RatError>`：`Ok` 转换为响应，
+    /// `Err` 交给 [`Router::set_error_handler`] 设置的错误处理器（默认按 [`RatError::status_code`](crate::error::RatError::status_code) 返回 JSON 错误）
+    ///
+    /// # 示例
+    /// ```ignore
+    /// router.add_fallible_route(Method::GET, "/orders/<id>", |req| async move {
+    ///     let id = req.param_as_u64("id").ok_or_else(|| RatError::InvalidArgument("无效的订单号".to_string()))?;
+    ///     Ok((StatusCode::OK, load_order(id).await?.to_string()))
+    /// });
+    /// ```
+    pub fn add_fallible_route<H, Fut, R>(&mut self, method: Method, path: impl Into<String>, handler: H) -> &mut Self
+    where
+        H: Fn(HttpRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, crate::error::RatError>> + Send + 'static,
+        R: crate::server::into_response::IntoResponse + 'static,
+    {
+        let handler = Arc::new(handler);
+        let error_handler = self.error_handler.clone();
+        self.add_route(method, path, move |req| -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> {
+            let result = handler(req);
+            let error_handler = error_handler.clone();
+            Box::pin(async move {
+                match result.await {
+                    Ok(value) => Ok(value.into_response()),
+                    Err(e) => {
+                        // 5xx 的详情不会返回给客户端，只能从日志中排查
+                        if e.status_code().is_server_error() {
+                            crate::utils::logger::error!("❌ [Router] 处理器返回错误: {}", e);
+                        } else {
+                            crate::utils::logger::debug!("⚠️ [Router] 处理器返回错误: {}", e);
+                        }
+                        let handler = error_handler.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+                        Ok(handler(&e))
+                    }
+                }
+            })
+        })
+    }
+
+    /// 设置可失败路由的错误处理器，对已注册和之后注册的路由都生效
+    pub fn set_error_handler<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&crate::error::RatError) -> Response<Full<Bytes>> + Send + Sync + 'static,
+    {
+        *self.error_handler.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(handler);
+        self
+    }
+
+    /// 添加 NDJSON 流式路由
+    ///
+    /// 处理器返回 `Stream<Item = Result<T, E>>`，路由器把每一项序列化为一行 JSON 并逐项写出，
+    /// 响应类型为 `application/x-ndjson`。数据源返回错误时响应体中止
+    ///
+    /// # 示例
+    /// ```ignore
+    /// router.add_ndjson_stream_route(Method::GET, "/rows", |_req, _params| {
+    ///     futures_util::stream::iter((0..3).map(|id| Ok::<_, String>(Row { id })))
+    /// });
+    /// ```
+    pub fn add_ndjson_stream_route<H, S, T, E>(&mut self, method: Method, path: impl Into<String>, handler: H) -> &mut Self
+    where
+        H: Fn(HttpRequest, HashMap<String, String>) -> S + Send + Sync + 'static,
+        S: futures_util::Stream<Item = Result<T, E>> + Send + 'static,
+        T: Serialize + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.add_streaming_route(method, path, move |req, params| {
+            let items = handler(req, params);
+            Box::pin(async move { NdjsonResponse::new(items).build() })
+        })
+    }
+
     /// 🆕 添加带有Python处理器名称的HTTP路由 (基于 Radix Tree)
     ///
     /// 这个方法专门用于Python集成，可以传递python_handler_name来避免Python层的二次路由匹配
@@ -893,28 +1575,308 @@ impl Router {
     }
 
     /// 处理 HTTP 请求的主入口（通用结构体版本）
-    pub async fn handle_http(&self, req: HttpRequest) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+    pub async fn handle_http(&self, mut req: HttpRequest) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+        // 活跃请求计数保持到响应体发送完毕（流式响应在处理器返回后仍在发送）
+        let active_guard = self.metrics.as_ref().map(|m| m.track_active_request());
+
+        // 处理完成前该 future 被丢弃（客户端断开）时通知处理器并计入指标
+        let (disconnect_guard, client_disconnect) = crate::server::client_disconnect::DisconnectGuard::new(self.metrics.clone());
+        req.client_disconnect = client_disconnect;
+
         // HTTP 和 gRPC 已物理分离，不再进行 gRPC 检测
-        self.handle_http_internal(req).await
+        // 无法发送 103 中间响应的连接路径上，Early Hints 合并到最终响应的 Link 头部
+        // 上游传递的期限与路由器默认期限取较小值，超时均返回 504
+        let header_timeout = self.timeout_header.as_deref()
+            .and_then(|name| req.header(name))
+            .and_then(parse_timeout_header);
+        let (limit, upstream_deadline) = match (self.request_timeout, header_timeout) {
+            (Some(default), Some(header)) if header < default => (Some(header), true),
+            (None, Some(header)) => (Some(header), true),
+            (default, _) => (default, false),
+        };
+        let request_line = limit.map(|_| format!("{} {}", req.method, req.path()));
+        let handling = crate::server::early_hints::scope_deferred(self.handle_http_guarded(req));
+        let (result, early_hints) = match limit {
+            Some(limit) => {
+                match tokio::time::timeout(limit, handling).await {
+                    Ok(output) => output,
+                    Err(_) => {
+                        disconnect_guard.complete();
+                        let mut response = if upstream_deadline {
+                            crate::utils::logger::warn!("⏱️ [Router] 超过请求头指定的处理期限（{:?}），返回 504: {}", limit, request_line.unwrap_or_default());
+                            self.create_error_response(StatusCode::GATEWAY_TIMEOUT, "Deadline Exceeded")
+                        } else {
+                            crate::utils::logger::warn!("⏱️ [Router] 请求处理超时（{:?}），返回 504: {}", limit, request_line.unwrap_or_default());
+                            self.create_error_response(StatusCode::GATEWAY_TIMEOUT, "Gateway Timeout")
+                        };
+                        self.apply_status_hooks(&mut response);
+                        self.apply_default_headers(response.headers_mut());
+                        if let Some(guard) = active_guard {
+                            response = guard.hold_until_body_done(response);
+                        }
+                        return Ok(response);
+                    }
+                }
+            }
+            None => handling.await,
+        };
+        disconnect_guard.complete();
+        let mut response = result?;
+        crate::server::early_hints::merge_links(response.headers_mut(), &early_hints);
+        self.apply_status_hooks(&mut response);
+        self.apply_default_headers(response.headers_mut());
+        let rate_limit = response.extensions().get::<crate::server::throttle::BandwidthLimit>().copied()
+            .or(self.response_rate_limit);
+        if let Some(limit) = rate_limit {
+            response = response.map(|body| crate::server::throttle::ThrottledBody::new(body, limit).boxed());
+        }
+        if let Some(guard) = active_guard {
+            response = guard.hold_until_body_done(response);
+        }
+        Ok(response)
     }
 
-    /// 处理 Hyper Request<Incoming> 的兼容性入口（用于向后兼容）
-    pub async fn handle_hyper_request(&self, req: Request<Incoming>, remote_addr: Option<SocketAddr>) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
-        // 转换为 HttpRequest
-        let http_req = match HttpRequest::from_hyper_request(req, remote_addr).await {
-            Ok(req) => req,
-            Err(e) => {
-                crate::utils::logger::error!("转换 HTTP 请求失败: {}", e);
-                return Ok(self.create_error_response(StatusCode::BAD_REQUEST, "Invalid request"));
+    /// 处理请求，按配置将处理器 panic 转换为 500 响应（仅 `panic = "unwind"` 时有效）
+    async fn handle_http_guarded(&self, req: HttpRequest) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+        let response = if self.catch_handler_panics {
+            use futures_util::FutureExt;
+
+            let method = req.method.clone();
+            let path = req.path().to_string();
+            match std::panic::AssertUnwindSafe(self.handle_http_internal(req)).catch_unwind().await {
+                Ok(result) => result?,
+                Err(panic) => {
+                    let reason = panic.downcast_ref::<&str>().map(|s| s.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "未知原因".to_string());
+                    crate::utils::logger::error!("💥 [Router] 处理器构建响应时 panic，返回 500: {} {} - {}", method, path, reason);
+                    self.create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
             }
+        } else {
+            self.handle_http_internal(req).await?
         };
+        Ok(response)
+    }
 
-        // 调用通用入口
-        self.handle_http(http_req).await
+    /// 设置是否将处理器 panic 转换为 500 响应（默认开启）
+    ///
+    /// 开启后，处理器中 `.unwrap()` 等导致的 panic（例如响应构建或序列化失败）
+    /// 会被记录为错误日志并返回 500，而不会中断连接任务。
+    ///
+    /// 仅在最终二进制以 `panic = "unwind"`（Cargo 默认）编译时生效。
+    /// 本仓库 Cargo.toml 的 release 配置使用 `panic = "abort"`，按该配置构建的示例等程序中
+    /// 处理器 panic 会直接终止进程；作为依赖使用时以应用自身的 profile 为准
+    pub fn catch_handler_panics(&mut self, enabled: bool) -> &mut Self {
+        self.catch_handler_panics = enabled;
+        self
+    }
+
+    /// 处理 Hyper Request<Incoming> 的兼容性入口（用于向后兼容）
+    pub async fn handle_hyper_request(&self, req: Request<Incoming>, remote_addr: Option<SocketAddr>) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+        // 读取请求体之前的拒绝：hyper 只在第一次读取请求体时发送 100 Continue，
+        // 因此这里返回的最终状态不会先经过 100 Continue
+        let (parts, body) = req.into_parts();
+        if let Some(mut response) = self.reject_before_body(&parts) {
+            self.apply_status_hooks(&mut response);
+            self.apply_default_headers(response.headers_mut());
+            close_if_body_unread(&parts, &mut response);
+            return Ok(response);
+        }
+        let version = parts.version;
+        let method = parts.method.clone();
+        let path = parts.uri.path().to_string();
+        let started = std::time::Instant::now();
+
+        // 转换为 HttpRequest；请求体按空闲时间计时，持续上传的大请求体不会被截断
+        let body = crate::server::http_request::IdleTimeoutBody::new(body, self.request_body_timeout);
+        let read_result = HttpRequest::from_hyper_request_limited(Request::from_parts(parts, body), remote_addr, self.max_request_body_size).await;
+        let http_req = match read_result {
+            Ok(req) => req,
+            Err(e) if e.is::<crate::server::http_request::BodyIdleTimeout>() => {
+                crate::utils::logger::warn!("⏱️ [Router] {}，返回 408", e);
+                let mut response = self.connection_error_response(StatusCode::REQUEST_TIMEOUT, "Request Timeout");
+                response.headers_mut().insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("close"));
+                return Ok(response);
+            }
+            Err(e) if e.is::<http_body_util::LengthLimitError>() => {
+                crate::utils::logger::warn!("🚫 [Router] 请求体超过限制 {} 字节，返回 413", self.max_request_body_size.unwrap_or_default());
+                let mut response = self.create_error_response(StatusCode::PAYLOAD_TOO_LARGE, "Payload Too Large");
+                self.apply_status_hooks(&mut response);
+                self.apply_default_headers(response.headers_mut());
+                // 剩余的请求体没有读取，连接不能再复用
+                if version <= hyper::Version::HTTP_11 {
+                    response.headers_mut().insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("close"));
+                }
+                return Ok(response);
+            }
+            Err(e) if e.is::<crate::server::http_request::TrailersTooLarge>() => {
+                crate::utils::logger::warn!("🚫 [Router] {}，返回 431", e);
+                let mut response = self.create_error_response(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE, "Request Trailers Too Large");
+                self.apply_status_hooks(&mut response);
+                self.apply_default_headers(response.headers_mut());
+                return Ok(response);
+            }
+            Err(e) => {
+                let incomplete = e.downcast_ref::<hyper::Error>().is_some_and(|e| e.is_incomplete_message());
+                // 请求体读取中途失败（分块编码错误、连接中断等）：处理器不会执行，以访问日志格式记录
+                crate::utils::logger::warn!(
+                    "❌ {} {} {} 400 {} - {}: {}",
+                    remote_addr.map(|addr| addr.ip().to_string()).unwrap_or_else(|| "unknown".to_string()),
+                    method,
+                    path,
+                    crate::utils::logger::format_duration(started.elapsed()),
+                    if incomplete { "请求体短于声明的 Content-Length" } else { "读取请求体失败" },
+                    e
+                );
+                let mut response = self.create_error_response(StatusCode::BAD_REQUEST, "Invalid request");
+                self.apply_status_hooks(&mut response);
+                self.apply_default_headers(response.headers_mut());
+                // 请求体没有完整读取，连接不能再复用
+                if version <= hyper::Version::HTTP_11 {
+                    response.headers_mut().insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("close"));
+                }
+                return Ok(response);
+            }
+        };
+
+        // 调用通用入口
+        self.handle_http(http_req).await
+    }
+
+    /// 请求已完整读入内存的连接路径（工作窃取路径）使用的拒绝检查
+    ///
+    /// 与 hyper 路径使用相同的规则：请求体大小上限和读取请求体之前的检查，返回的响应已经过状态码钩子和默认响应头
+    pub(crate) fn reject_buffered_request(&self, parts: &http::request::Parts) -> Option<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>> {
+        let mut response = self.reject_before_body(parts)?;
+        self.apply_status_hooks(&mut response);
+        self.apply_default_headers(response.headers_mut());
+        Some(response)
+    }
+
+    /// 在 HTTP/2 流上发送连接层错误响应后结束该流
+    ///
+    /// 响应经过状态码钩子和默认响应头；随后以 `RST_STREAM(NO_ERROR)` 告知客户端不必再发送剩余的请求体
+    pub(crate) async fn send_h2_error_response(&self, respond: &mut h2::server::SendResponse<Bytes>, status: StatusCode, message: &str) {
+        let (parts, body) = self.connection_error_response(status, message).into_parts();
+        let body = body.collect().await.map(|collected| collected.to_bytes()).unwrap_or_default();
+        match respond.send_response(Response::from_parts(parts, ()), body.is_empty()) {
+            Ok(mut stream) => {
+                if !body.is_empty() {
+                    let _ = stream.send_data(body, true);
+                }
+                respond.send_reset(h2::Reason::NO_ERROR);
+            }
+            Err(e) => crate::utils::logger::debug!("发送 HTTP/2 错误响应失败: {}", e),
+        }
+    }
+
+    /// 读取 HTTP/2 请求体
+    ///
+    /// 空闲超时对每块数据单独计时，持续上传的大请求体不受总时长限制；超时返回 408，
+    /// 超过大小限制返回 413，读取或流量控制失败时只重置当前流。
+    /// 返回 `None` 表示流已经结束，调用方不应再发送响应
+    pub(crate) async fn read_h2_body(
+        &self,
+        parts: &http::request::Parts,
+        recv_stream: &mut RecvStream,
+        respond: &mut h2::server::SendResponse<Bytes>,
+        remote_addr: SocketAddr,
+    ) -> Option<Vec<u8>> {
+        let started = std::time::Instant::now();
+        let mut body_data = Vec::new();
+        loop {
+            let next = match self.request_body_timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, recv_stream.data()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        // 请求体迟迟没有新数据：返回 408 并结束流
+                        crate::utils::logger::warn!("⏱️ {} {} {} 408 - 读取 HTTP/2 请求体超时",
+                            remote_addr.ip(), parts.method, parts.uri.path());
+                        self.send_h2_error_response(respond, StatusCode::REQUEST_TIMEOUT, "Request Timeout").await;
+                        return None;
+                    }
+                },
+                None => recv_stream.data().await,
+            };
+            let Some(chunk) = next else { return Some(body_data) };
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    // 请求体读取中途失败：只重置当前流，同一连接上的其他流不受影响。
+                    // 实际长度与 Content-Length 不符时 h2 返回 PROTOCOL_ERROR（RFC 9113 §8.1.1）
+                    let reason = e.reason().unwrap_or(h2::Reason::INTERNAL_ERROR);
+                    respond.send_reset(reason);
+                    crate::utils::logger::warn!(
+                        "❌ {} {} {} RST_STREAM({:?}) {} - 读取 HTTP/2 请求体失败: {}",
+                        remote_addr.ip(),
+                        parts.method,
+                        parts.uri.path(),
+                        reason,
+                        crate::utils::logger::format_duration(started.elapsed()),
+                        e
+                    );
+                    return None;
+                }
+            };
+            if let Some(max) = self.max_request_body_size.filter(|max| body_data.len() + chunk.len() > *max) {
+                // 请求体超过限制：立即返回 413 并结束流，不再读取剩余数据
+                crate::utils::logger::warn!("🚫 {} {} {} 413 - 请求体超过限制 {} 字节",
+                    remote_addr.ip(), parts.method, parts.uri.path(), max);
+                self.send_h2_error_response(respond, StatusCode::PAYLOAD_TOO_LARGE, "Payload Too Large").await;
+                return None;
+            }
+            body_data.extend_from_slice(&chunk);
+            if let Err(e) = recv_stream.flow_control().release_capacity(chunk.len()) {
+                respond.send_reset(h2::Reason::FLOW_CONTROL_ERROR);
+                crate::utils::logger::warn!("❌ {} {} {} RST_STREAM(FLOW_CONTROL_ERROR) - HTTP/2 流量控制失败: {}",
+                    remote_addr.ip(), parts.method, parts.uri.path(), e);
+                return None;
+            }
+        }
+    }
+
+    /// 连接层生成的错误响应（读取超时、请求过大等），已经过状态码钩子和默认响应头
+    pub(crate) fn connection_error_response(&self, status: StatusCode, message: &str) -> Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>> {
+        let mut response = self.create_error_response(status, message);
+        self.apply_status_hooks(&mut response);
+        self.apply_default_headers(response.headers_mut());
+        response
+    }
+
+    /// 只根据请求头就能做出的拒绝：声明的 Content-Length 超过上限，或读取请求体之前的检查未通过
+    fn reject_before_body(&self, parts: &http::request::Parts) -> Option<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>> {
+        if let Some(max) = self.max_request_body_size {
+            let declared = parts.headers.get(hyper::header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok());
+            if let Some(declared) = declared.filter(|len| *len > max as u64) {
+                crate::utils::logger::warn!("🚫 [Router] 声明的请求体 {} 字节超过限制 {} 字节，返回 413", declared, max);
+                return Some(self.create_error_response(StatusCode::PAYLOAD_TOO_LARGE, "Payload Too Large"));
+            }
+        }
+
+        let check = self.before_body_check.as_ref()?;
+        let response = check(parts)?;
+        crate::utils::logger::debug!("🚫 [Router] 请求在读取请求体之前被拒绝: {} {} -> {}", parts.method, parts.uri.path(), response.status());
+        Some(response.map(|body| BoxBody::new(body.map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} }))))
     }
 
     /// 内部 HTTP 请求处理逻辑
-    async fn handle_http_internal(&self, req: HttpRequest) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+    async fn handle_http_internal(&self, mut req: HttpRequest) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+        // 路由匹配前重写请求路径（查询字符串保持不变）
+        if let Some(rewrite) = &self.path_rewrite {
+            if let Some(new_path) = rewrite(req.path()) {
+                crate::utils::logger::debug!("🔀 [Router] 路径重写: {} -> {}", req.path(), new_path);
+                req.set_path(&new_path);
+            }
+        }
+
+        // 按可信头部配置解析客户端真实 IP
+        if let Some(config) = &self.real_ip_config {
+            req.real_ip = IpExtractor::resolve_client_ip(&req.headers, req.remote_addr, config);
+        }
+
         let method = &req.method;
         let path = req.path();
 
@@ -929,24 +1891,97 @@ impl Router {
             }
         }
 
+        // HTTP/1.1 要求必须携带 Host 头部
+        if self.require_host && req.is_missing_host() {
+            crate::utils::logger::warn!("🚫 [Router] HTTP/1.1 请求缺少 Host 头部: {} {}", method, path);
+            return Ok(self.create_error_response(StatusCode::BAD_REQUEST, "Missing Host header"));
+        }
+
+        // asterisk-form（`*`）只对 OPTIONS 有意义，查询的是整个服务器而不是某个资源
+        if self.asterisk_options && path == "*" {
+            if method == Method::OPTIONS {
+                return Ok(self.create_server_options_response(&req));
+            }
+            crate::utils::logger::warn!("🚫 [Router] 非 OPTIONS 请求使用了 asterisk-form: {} *", method);
+            return Ok(self.create_error_response(StatusCode::BAD_REQUEST, "Asterisk-form is only allowed for OPTIONS"));
+        }
+
+        // 限制路径段数，避免超长路径放大路由匹配开销
+        if self.max_path_segments != usize::MAX
+            && path.split('/').filter(|s| !s.is_empty()).take(self.max_path_segments + 1).count() > self.max_path_segments
+        {
+            crate::utils::logger::warn!("🚫 [Router] 请求路径段数超过限制 {}: {} {}", self.max_path_segments, method, path);
+            return Ok(self.create_error_response(StatusCode::BAD_REQUEST, "Too many path segments"));
+        }
+
+        // 限制查询参数个数，在处理器构建参数 HashMap 之前拒绝
+        if self.max_query_params != usize::MAX {
+            if let Some(query) = req.query() {
+                if query.split('&').filter(|p| !p.is_empty()).take(self.max_query_params + 1).count() > self.max_query_params {
+                    crate::utils::logger::warn!("🚫 [Router] 查询参数个数超过限制 {}: {} {}", self.max_query_params, method, path);
+                    return Ok(self.create_error_response(StatusCode::BAD_REQUEST, "Too many query parameters"));
+                }
+            }
+        }
+
+        // 内置构建信息端点（优先于虚拟主机和普通路由）
+        if self.info_endpoint.as_deref() == Some(path) && (method == Method::GET || method == Method::HEAD) {
+            return Ok(self.create_info_response());
+        }
+
+        // 内置指标端点
+        if self.metrics_endpoint.as_deref() == Some(path) && (method == Method::GET || method == Method::HEAD) {
+            return Ok(self.create_json_response(self.metrics_snapshot()));
+        }
+
+        // 虚拟主机分发：只替换路由表，中间件仍使用当前路由器的配置
+        let routes = match self.match_virtual_host(&req) {
+            Some(vhost_router) if vhost_router.serves(method, path) => {
+                crate::utils::logger::debug!("🌐 [Router] 请求分发到虚拟主机: {:?}", req.host());
+                vhost_router
+            }
+            Some(_) => {
+                crate::utils::logger::debug!("🌐 [Router] 虚拟主机无匹配路由，回退到默认路由: {:?} {} {}", req.host(), method, path);
+                self
+            }
+            None => self,
+        };
+
         // 协议检测已在 TCP 层完成，这里不需要额外处理
         crate::utils::logger::debug!("ℹ️ [Router] 协议检测已在 TCP 层完成");
 
         // 路由匹配和处理
-        self.route_and_handle(req).await
+        self.route_and_handle(routes, req).await
+    }
+
+    /// 虚拟主机子路由器是否能处理该请求（存在该路径的路由或配置了兜底路由）
+    fn serves(&self, method: &Method, path: &str) -> bool {
+        self.fallback_handler.is_some()
+            || !self.match_routes(method, path).is_empty()
+            || !self.registered_methods(path, None).is_empty()
     }
 
     /// 路由匹配和处理
-    async fn route_and_handle(&self, req: HttpRequest) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
-        self.route_and_handle_internal(req, false).await
+    ///
+    /// `routes` 提供路由表（当前路由器或虚拟主机子路由器），CORS、缓存、压缩等中间件始终来自 `self`
+    async fn route_and_handle(&self, routes: &Router, mut req: HttpRequest) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+        // 注入应用状态（请求上已有的同类型状态优先，虚拟主机状态优先于顶层状态）
+        req.state.fill_from(&routes.state);
+        if !std::ptr::eq(routes, self) {
+            req.state.fill_from(&self.state);
+        }
+        self.route_and_handle_internal(routes, req, false).await
     }
 
-    async fn route_and_handle_internal(&self, req: HttpRequest, is_spa_fallback: bool) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+    async fn route_and_handle_internal(&self, routes: &Router, req: HttpRequest, is_spa_fallback: bool) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
         let method = req.method.clone(); // 克隆 method 避免借用问题
         let path = req.path().to_string(); // 克隆路径字符串
+        // 缓存键不包含主机，虚拟主机路由不参与响应缓存，避免与默认路由的同名路径串用
+        #[cfg(feature = "cache")]
+        let cacheable = std::ptr::eq(routes, self);
 
         crate::utils::logger::debug!("🔍 [Router] 开始 Radix Tree 路由匹配: {} {}", method, path);
-        crate::utils::logger::debug!("🔍 [Router] 注册的HTTP处理器数量: {}", self.http_handlers.len());
+        crate::utils::logger::debug!("🔍 [Router] 注册的HTTP处理器数量: {}", routes.http_handlers.len());
 
         // 检查 CORS 预检请求
         if let Some(cors_config) = &self.cors_config {
@@ -999,19 +2034,19 @@ impl Router {
 
                 // 添加 CORS 头部
                 if let Some(origin) = req.cors_origin() {
-                    response.headers_mut().insert("Access-Control-Allow-Origin", hyper::header::HeaderValue::from_str(origin).unwrap());
+                    if let Ok(value) = hyper::header::HeaderValue::from_str(origin) {
+                        response.headers_mut().insert("Access-Control-Allow-Origin", value);
+                    }
                 }
 
-                response.headers_mut().insert(
-                    "Access-Control-Allow-Methods",
-                    hyper::header::HeaderValue::from_str(
-                        &cors_config.allowed_methods
-                            .iter()
-                            .map(|m| m.as_str())
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    ).unwrap()
-                );
+                let allowed_methods = cors_config.allowed_methods
+                    .iter()
+                    .map(|m| m.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if let Ok(value) = hyper::header::HeaderValue::from_str(&allowed_methods) {
+                    response.headers_mut().insert("Access-Control-Allow-Methods", value);
+                }
 
                 if !cors_config.allowed_headers.is_empty() {
                     let headers = if cors_config.allowed_headers.contains(&"*".to_string()) {
@@ -1019,7 +2054,9 @@ impl Router {
                     } else {
                         &cors_config.allowed_headers.join(", ")
                     };
-                    response.headers_mut().insert("Access-Control-Allow-Headers", hyper::header::HeaderValue::from_str(headers).unwrap());
+                    if let Ok(value) = hyper::header::HeaderValue::from_str(headers) {
+                        response.headers_mut().insert("Access-Control-Allow-Headers", value);
+                    }
                 }
 
                 if cors_config.allow_credentials {
@@ -1027,7 +2064,7 @@ impl Router {
                 }
 
                 if let Some(max_age) = cors_config.max_age {
-                    response.headers_mut().insert("Access-Control-Max-Age", hyper::header::HeaderValue::from_str(&max_age.to_string()).unwrap());
+                    response.headers_mut().insert("Access-Control-Max-Age", hyper::header::HeaderValue::from(max_age));
                 }
 
                 return Ok(response);
@@ -1035,7 +2072,7 @@ impl Router {
         }
 
         // 🆕 使用 Radix Tree 进行智能路由匹配
-        let matches = self.route_tree.find_routes(&method, &path);
+        let matches = routes.match_routes(&method, &path);
 
         if !matches.is_empty() {
             // 选择优先级最高的匹配路由
@@ -1047,8 +2084,8 @@ impl Router {
             // 检查是否是流式路由（通过 route_type 字段判断）
             if best_match.route_info.route_type == RouteType::Streaming {
                 // 流式路由处理
-                if best_match.route_info.handler_id < self.http_streaming_handlers.len() {
-                    let handler = &self.http_streaming_handlers[best_match.route_info.handler_id];
+                if best_match.route_info.handler_id < routes.http_streaming_handlers.len() {
+                    let handler = &routes.http_streaming_handlers[best_match.route_info.handler_id];
                     let req_with_params = Self::set_path_params_and_handler_to_request(req, best_match.params.clone(), best_match.route_info.python_handler_name.clone());
                     let response = handler(req_with_params.clone(), best_match.params.clone()).await?;
                     let (parts, body) = response.into_parts();
@@ -1060,30 +2097,40 @@ impl Router {
                 }
             } else {
                 // 标准HTTP路由
-                if best_match.route_info.handler_id < self.http_handlers.len() {
-                    let handler = &self.http_handlers[best_match.route_info.handler_id];
+                if best_match.route_info.handler_id < routes.http_handlers.len() {
+                    let handler = &routes.http_handlers[best_match.route_info.handler_id];
                     let req_with_params = Self::set_path_params_and_handler_to_request(req, best_match.params.clone(), best_match.route_info.python_handler_name.clone());
 
                     // 对于GET请求，先检查缓存
                     if method == hyper::Method::GET {
                         #[cfg(feature = "cache")]
                         {
-                            if let Some(cached_response) = self.apply_cache(&req_with_params, &path).await {
+                            if !cacheable {
+                                crate::utils::logger::debug!("🔍 [Router] 虚拟主机路由跳过缓存: GET {}", path);
+                            } else if let Some(mut cached_response) = self.apply_cache(&req_with_params, &path).await {
                                 crate::utils::logger::debug!("🎯 [Router] 缓存命中: GET {}", path);
+                                crate::server::cache_middleware_impl::observe_cache_status(
+                                    &mut cached_response, self.metrics.as_deref(), self.expose_cache_headers
+                                );
                                 return Ok(cached_response);
                             }
                         }
 
                         // 缓存未命中或无缓存功能，处理请求
                         let response = handler(req_with_params.clone()).await?;
+                        let response = crate::server::body_transform::apply(&self.body_transforms, response).await;
                         let (parts, body) = response.into_parts();
                         let boxed_body = BoxBody::new(body.map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} }));
                         let mut response = Response::from_parts(parts, boxed_body);
 
                         // 应用缓存中间件（如果启用）
                         #[cfg(feature = "cache")]
-                        {
-                            response = self.apply_cache_middleware(&req_with_params, response).await?;
+                        if cacheable {
+                            let route_ttl = self.route_cache_ttls.get(&best_match.route_info.handler_id).copied();
+                            response = self.apply_cache_middleware(&req_with_params, response, route_ttl).await?;
+                            crate::server::cache_middleware_impl::observe_cache_status(
+                                &mut response, self.metrics.as_deref(), self.expose_cache_headers
+                            );
                         }
 
                         // 应用 CORS 头部
@@ -1094,6 +2141,7 @@ impl Router {
 
                     // 非GET请求直接处理
                     let response = handler(req_with_params.clone()).await?;
+                    let response = crate::server::body_transform::apply(&self.body_transforms, response).await;
                     let (parts, body) = response.into_parts();
                     let boxed_body = BoxBody::new(body.map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} }));
                     let mut response = Response::from_parts(parts, boxed_body);
@@ -1107,6 +2155,26 @@ impl Router {
             crate::utils::logger::debug!("❌ [Router] Radix Tree 未找到匹配路由: {} {}", method, path);
         }
 
+        // 自动方法处理：路径存在但没有当前方法的路由
+        if let Some(auto_methods) = &self.auto_methods {
+            let allowed = routes.registered_methods(&path, Some(auto_methods));
+            if !allowed.is_empty() && !allowed.contains(&method) {
+                if auto_methods.method_not_allowed {
+                    crate::utils::logger::debug!("🚫 [Router] 方法不允许: {} {}", method, path);
+                    let mut response = self.create_error_response(StatusCode::METHOD_NOT_ALLOWED, "Method Not Allowed");
+                    response.headers_mut().insert(hyper::header::ALLOW, Self::allow_header_value(&allowed));
+                    return Ok(self.apply_cors_headers(response, &req));
+                }
+            } else if method == Method::OPTIONS && auto_methods.auto_options && allowed.contains(&Method::OPTIONS) {
+                let empty_body = BoxBody::new(http_body_util::Full::new(Bytes::new())
+                    .map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} }));
+                let mut response = Response::new(empty_body);
+                *response.status_mut() = StatusCode::NO_CONTENT;
+                response.headers_mut().insert(hyper::header::ALLOW, Self::allow_header_value(&allowed));
+                return Ok(self.apply_cors_headers(response, &req));
+            }
+        }
+
         // 检查 SPA 回退（避免无限递归）
         crate::utils::logger::debug!("🔍 [Router] SPA 回退检查: enabled={}, is_spa_fallback={}, path={}",
             self.spa_config.enabled, is_spa_fallback, path);
@@ -1122,7 +2190,7 @@ impl Router {
                 fallback_req.set_path(fallback_path);
 
                 // 递归调用路由处理，标记为 SPA 回退以避免无限递归
-                return Box::pin(self.route_and_handle_internal(fallback_req, true)).await;
+                return Box::pin(self.route_and_handle_internal(routes, fallback_req, true)).await;
             }
         }
 
@@ -1142,7 +2210,7 @@ impl Router {
                 } else {
                     // 尝试查找对应的 GET 路由
                     crate::utils::logger::debug!("🔍 [Router] HEAD 回退: 尝试匹配 GET 路由 {}", path);
-                    let get_matches = self.route_tree.find_routes(&hyper::Method::GET, &path);
+                    let get_matches = routes.match_routes(&hyper::Method::GET, &path);
 
                     if !get_matches.is_empty() {
                         let get_match = &get_matches[0]; // 已按优先级排序
@@ -1153,8 +2221,8 @@ impl Router {
                         get_req.method = hyper::Method::GET;
 
                         // 使用 GET 处理器，但标记为 HEAD 请求以优化性能
-                        if get_match.route_info.handler_id < self.http_handlers.len() {
-                            let handler = &self.http_handlers[get_match.route_info.handler_id];
+                        if get_match.route_info.handler_id < routes.http_handlers.len() {
+                            let handler = &routes.http_handlers[get_match.route_info.handler_id];
                             let req_with_params = Self::set_path_params_and_handler_to_request(
                                 get_req,
                                 get_match.params.clone(),
@@ -1181,7 +2249,7 @@ impl Router {
             } else {
                 // 没有白名单限制，对所有路径尝试回退
                 crate::utils::logger::debug!("🔍 [Router] HEAD 回退: 尝试匹配 GET 路由 {}", path);
-                let get_matches = self.route_tree.find_routes(&hyper::Method::GET, &path);
+                let get_matches = routes.match_routes(&hyper::Method::GET, &path);
 
                 if !get_matches.is_empty() {
                     let get_match = &get_matches[0];
@@ -1190,8 +2258,8 @@ impl Router {
                     let mut get_req = req.clone();
                     get_req.method = hyper::Method::GET;
 
-                    if get_match.route_info.handler_id < self.http_handlers.len() {
-                        let handler = &self.http_handlers[get_match.route_info.handler_id];
+                    if get_match.route_info.handler_id < routes.http_handlers.len() {
+                        let handler = &routes.http_handlers[get_match.route_info.handler_id];
                         let req_with_params = Self::set_path_params_and_handler_to_request(
                             get_req,
                             get_match.params.clone(),
@@ -1213,6 +2281,23 @@ impl Router {
             }
         }
 
+        // 兜底路由（优先级最低，所有具体路由、SPA 回退和 HEAD 回退之后）
+        if let Some(handler) = &routes.fallback_handler {
+            crate::utils::logger::debug!("🔍 [Router] 使用兜底路由处理: {} {}", method, path);
+            let mut params = HashMap::new();
+            params.insert("path".to_string(), path.trim_start_matches('/').to_string());
+            let req_with_params = Self::set_path_params_to_request(req, params);
+
+            let response = handler(req_with_params.clone()).await?;
+            let response = crate::server::body_transform::apply(&self.body_transforms, response).await;
+            let (parts, body) = response.into_parts();
+            let boxed_body = BoxBody::new(body.map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} }));
+            let response = Response::from_parts(parts, boxed_body);
+
+            let response = self.apply_cors_headers(response, &req_with_params);
+            return Ok(self.apply_compression_boxed(response, &path, &req_with_params).await?);
+        }
+
         // 未找到匹配路由，返回404
         crate::utils::logger::warn!("⚠️ [Router] 未找到匹配路由: {} {} -> 返回404", method, path);
 
@@ -1272,7 +2357,9 @@ impl Router {
 
                     // 设置正确的 Content-Encoding 头部
                     if cache_result.encoding != "identity" {
-                        response.headers_mut().insert("content-encoding", cache_result.encoding.parse().unwrap());
+                        if let Ok(value) = cache_result.encoding.parse() {
+                            response.headers_mut().insert("content-encoding", value);
+                        }
                     }
 
                     return Some(response);
@@ -1286,7 +2373,7 @@ impl Router {
 
     /// 应用缓存中间件（用于写入缓存）
     #[cfg(feature = "cache")]
-    async fn apply_cache_middleware(&self, req: &HttpRequest, response: Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
+    async fn apply_cache_middleware(&self, req: &HttpRequest, response: Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, ttl: Option<std::time::Duration>) -> Result<Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>>, hyper::Error> {
         if let Some(cache_middleware) = &self.cache_middleware {
             // 将HttpRequest转换为hyper::Request，并保留原始头部
             let mut hyper_req = hyper::Request::builder()
@@ -1301,7 +2388,7 @@ impl Router {
             let hyper_req = hyper_req.body(()).unwrap();
             
             // 应用缓存中间件
-            cache_middleware.process(&hyper_req, response).await
+            cache_middleware.process_with_ttl(&hyper_req, response, ttl).await
         } else {
             Ok(response)
         }
@@ -1390,7 +2477,9 @@ impl Router {
                 if let Some(origin) = req.cors_origin() {
                     if cors_config.is_origin_allowed(origin) {
                         crate::utils::logger::debug!("🌐 [CORS] 添加头部: {}", origin);
-                        response_parts.headers.insert("Access-Control-Allow-Origin", hyper::header::HeaderValue::from_str(origin).unwrap());
+                        if let Ok(value) = hyper::header::HeaderValue::from_str(origin) {
+                            response_parts.headers.insert("Access-Control-Allow-Origin", value);
+                        }
                     } else {
                         crate::utils::logger::warn!("🌐 [CORS] 拒绝来源: {}", origin);
                     }
@@ -1407,7 +2496,11 @@ impl Router {
                 // 添加暴露的头部
                 if !cors_config.exposed_headers.is_empty() {
                     let exposed = cors_config.exposed_headers.join(", ");
-                    response_parts.headers.insert("Access-Control-Expose-Headers", hyper::header::HeaderValue::from_str(&exposed).unwrap());
+                    if let Ok(value) = hyper::header::HeaderValue::from_str(&exposed) {
+                        response_parts.headers.insert("Access-Control-Expose-Headers", value);
+                    } else {
+                        crate::utils::logger::warn!("🌐 [CORS] 暴露头部配置包含非法字符: {}", exposed);
+                    }
                 }
 
                 return Response::from_parts(response_parts, body);
@@ -1429,7 +2522,9 @@ impl Router {
                 if let Some(origin) = req.cors_origin() {
                     if cors_config.is_origin_allowed(origin) {
                         crate::utils::logger::debug!("🌐 [CORS] 流式响应添加头部: {}", origin);
-                        response_parts.headers.insert("Access-Control-Allow-Origin", hyper::header::HeaderValue::from_str(origin).unwrap());
+                        if let Ok(value) = hyper::header::HeaderValue::from_str(origin) {
+                            response_parts.headers.insert("Access-Control-Allow-Origin", value);
+                        }
                     } else {
                         crate::utils::logger::warn!("🌐 [CORS] 流式响应拒绝来源: {}", origin);
                     }
@@ -1446,7 +2541,11 @@ impl Router {
                 // 添加暴露的头部
                 if !cors_config.exposed_headers.is_empty() {
                     let exposed = cors_config.exposed_headers.join(", ");
-                    response_parts.headers.insert("Access-Control-Expose-Headers", hyper::header::HeaderValue::from_str(&exposed).unwrap());
+                    if let Ok(value) = hyper::header::HeaderValue::from_str(&exposed) {
+                        response_parts.headers.insert("Access-Control-Expose-Headers", value);
+                    } else {
+                        crate::utils::logger::warn!("🌐 [CORS] 暴露头部配置包含非法字符: {}", exposed);
+                    }
                 }
 
                 return Response::from_parts(response_parts, body);
@@ -1477,8 +2576,17 @@ impl Router {
                 return Ok(response);
             }
 
-            // 使用压缩器压缩响应，使用真实的 Accept-Encoding 头部
-            compressor.compress_response(response, accept_encoding, file_ext).await
+            // 使用压缩器压缩响应，使用真实的 Accept-Encoding 头部；
+            // 没有指标收集器时无法获得负载，退回按静态级别压缩
+            match &self.metrics {
+                Some(metrics) => compressor.compress_response_under_load(response, accept_encoding, file_ext, metrics.active_requests()).await,
+                None => {
+                    if compressor.config().adaptive_threshold.is_some() {
+                        crate::utils::logger::debug!("⚠️ [Router] 未设置性能指标收集器，自适应压缩按静态级别处理");
+                    }
+                    compressor.compress_response(response, accept_encoding, file_ext).await
+                }
+            }
         } else {
             Ok(response)
         }
@@ -1510,12 +2618,13 @@ impl Router {
 
         let boxed_body = BoxBody::new(body.map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} }));
 
-        Response::builder()
-            .status(status)
-            .header("Content-Type", content_type)
-            .header("server", format!("RAT-Engine/{}", env!("CARGO_PKG_VERSION")))
-            .body(boxed_body)
-            .unwrap()
+        // 直接设置状态和静态头部，构建过程不会失败
+        let mut response = Response::new(boxed_body);
+        *response.status_mut() = status;
+        let headers = response.headers_mut();
+        headers.insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static(content_type));
+        headers.insert(hyper::header::SERVER, hyper::header::HeaderValue::from_static(SERVER_HEADER));
+        response
     }
 
     /// 生成HTML错误页面
@@ -1660,6 +2769,23 @@ impl Router {
         self
     }
 
+    /// 添加强类型 gRPC 服务端流服务（异步函数返回响应流）
+    pub fn add_grpc_server_stream_fn<Req, Resp, S, F, Fut>(&mut self, method: impl Into<String>, handler: F) -> &mut Self
+    where
+        Req: for<'de> serde::Deserialize<'de> + bincode::Decode<()> + Send + 'static,
+        Resp: Serialize + bincode::Encode + Send + 'static,
+        S: futures_util::Stream<Item = Result<Resp, crate::server::grpc_types::GrpcError>> + Send + 'static,
+        F: Fn(Req, crate::server::grpc_types::GrpcContext) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<S, crate::server::grpc_types::GrpcError>> + Send + 'static,
+    {
+        if let Ok(mut registry) = self.grpc_registry.write() {
+            registry.add_server_stream_typed(method, handler);
+        } else {
+            crate::utils::logger::error!("❌ 无法获取 gRPC 注册表写锁");
+        }
+        self
+    }
+
     /// 添加 gRPC 客户端流服务
     pub fn add_grpc_client_stream<H>(&mut self, method: impl Into<String>, handler: H) -> &mut Self
     where
@@ -1686,17 +2812,77 @@ impl Router {
         self
     }
 
+    /// 设置每个连接允许的最大并发 gRPC 流
+    ///
+    /// 与 HTTP/2 的 max_concurrent_streams 相互独立，超出上限的新 RPC 以 RESOURCE_EXHAUSTED 拒绝，
+    /// 避免单个连接占满昂贵的流式处理器
+    pub fn grpc_max_streams_per_connection(&mut self, limit: usize) -> &mut Self {
+        if let Ok(mut registry) = self.grpc_registry.write() {
+            registry.set_max_streams_per_connection(Some(limit));
+        } else {
+            crate::utils::logger::error!("❌ 无法获取 gRPC 注册表写锁");
+        }
+        self
+    }
+
+    /// 为新连接创建 gRPC 并发流限制（未配置时返回 None）
+    pub(crate) fn new_grpc_stream_limit(&self) -> Option<crate::server::grpc_handler::GrpcStreamLimit> {
+        self.grpc_registry.read().ok()?
+            .max_streams_per_connection()
+            .map(crate::server::grpc_handler::GrpcStreamLimit::new)
+    }
+
     /// 处理 gRPC 请求
     pub async fn handle_grpc_request(
         &self,
         req: http::Request<h2::RecvStream>,
         respond: h2::server::SendResponse<bytes::Bytes>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        if let Some(grpc_handler) = &self.grpc_handler {
-            grpc_handler.handle_request(req, respond).await
-        } else {
-            Err("gRPC 处理器未初始化".into())
+        let _active_guard = self.metrics.as_ref().map(|m| m.track_active_request());
+
+        let Some(grpc_handler) = &self.grpc_handler else {
+            return Err("gRPC 处理器未初始化".into());
+        };
+
+        // gRPC 请求不经过 HyperAdapter，在这里单独记录访问日志
+        let start = std::time::Instant::now();
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+        let client_ip = req.extensions().get::<SocketAddr>().map(|addr| addr.ip());
+        let protocol = crate::server::access_log::AccessLogProtocol::detect(
+            req.version(),
+            req.headers().get(hyper::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()),
+        );
+
+        let result = grpc_handler.handle_request(req, respond).await;
+        let duration = start.elapsed();
+        match &result {
+            Ok(()) => {
+                // gRPC 的 HTTP 状态固定为 200，调用结果由 grpc-status 表示
+                let record = crate::server::access_log::AccessLogRecord {
+                    client_ip,
+                    method,
+                    path: path.clone(),
+                    status: StatusCode::OK.as_u16(),
+                    duration,
+                    protocol,
+                    connection_request_seq: None,
+                    connection_age: None,
+                };
+                crate::utils::logger::log_at(self.access_log_level(&path), format_args!("📊 {}", record));
+            }
+            Err(e) => {
+                crate::utils::logger::error!(
+                    "❌ {} {} {} ERROR {} - {}",
+                    client_ip.map(|ip| ip.to_string()).unwrap_or_else(|| "unknown".to_string()),
+                    method,
+                    path,
+                    crate::utils::logger::format_duration(duration),
+                    e
+                );
+            }
         }
+        result
     }
 
     // ========== 配置方法 ==========
@@ -1720,6 +2906,48 @@ impl Router {
         self.cache_middleware = Some(cache_middleware);
         self
     }
+
+    /// 设置是否在响应中暴露缓存状态头部（默认暴露）
+    ///
+    /// `x-cache` 的取值为 `HIT`、`MISS` 或 `HIT; stale`（命中过期缓存，后台重新验证中）。
+    /// 关闭后移除 `x-cache` 系列头部，但命中/未命中计数仍会记录到 `set_metrics` 设置的指标中
+    #[cfg(feature = "cache")]
+    pub fn expose_cache_headers(&mut self, expose: bool) -> &mut Self {
+        self.expose_cache_headers = expose;
+        self
+    }
+
+    /// 添加带独立缓存 TTL 的路由（覆盖缓存中间件的默认 TTL，精确到毫秒）
+    #[cfg(feature = "cache")]
+    pub fn add_cached_route<H>(&mut self, method: Method, path: impl Into<String>, ttl: std::time::Duration, handler: H) -> &mut Self
+    where
+        H: Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<Response<Full<Bytes>>, hyper::Error>> + Send>> + Send + Sync + 'static,
+    {
+        let handler_id = self.http_handlers.len();
+        self.add_route(method, path, handler);
+        self.route_cache_ttls.insert(handler_id, ttl.max(std::time::Duration::from_millis(1)));
+        self
+    }
+
+    /// 按路径或模式使缓存失效（`/users/1` 精确匹配，`/users/*` 前缀匹配）
+    ///
+    /// 返回删除的缓存条目数量，未启用缓存时返回 0
+    #[cfg(feature = "cache")]
+    pub async fn invalidate_cache(&self, path_or_pattern: &str) -> usize {
+        match &self.cache_middleware {
+            Some(cache_middleware) => cache_middleware.invalidate(path_or_pattern).await,
+            None => 0,
+        }
+    }
+
+    /// 清空全部响应缓存
+    #[cfg(feature = "cache")]
+    pub async fn invalidate_all(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match &self.cache_middleware {
+            Some(cache_middleware) => cache_middleware.invalidate_all().await,
+            None => Ok(()),
+        }
+    }
     
 
   
@@ -1752,6 +2980,381 @@ impl Router {
         self
     }
 
+    /// 启用自动方法处理
+    ///
+    /// 统一开关，替代分别配置 HEAD 回退和 OPTIONS 响应：
+    /// - `auto_head`：HEAD 请求回退到 GET 处理器（等同 `enable_head_fallback(true, None)`）
+    /// - `auto_options`：配置了 CORS 的预检请求按 CORS 规则响应，其余 OPTIONS 请求返回
+    ///   `204 No Content` 和该路径已注册方法的 `Allow` 列表（未配置 CORS 时同样生效）
+    /// - `method_not_allowed`：路径存在但方法不匹配时返回 `405` 并附带 `Allow` 头部
+    ///
+    /// 显式注册的 HEAD/OPTIONS 路由始终优先
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rat_engine::server::Router;
+    /// use rat_engine::server::router::AutoMethodsConfig;
+    ///
+    /// let mut router = Router::new();
+    /// router.enable_auto_methods(AutoMethodsConfig::new());
+    /// ```
+    pub fn enable_auto_methods(&mut self, config: AutoMethodsConfig) -> &mut Self {
+        self.head_fallback_enabled = config.auto_head;
+        self.head_fallback_whitelist = None;
+        self.auto_methods = Some(config);
+        self
+    }
+
+    /// 获取路径已注册的方法（启用自动方法处理时包含自动提供的 HEAD/OPTIONS）
+    ///
+    /// 路径不存在任何路由时返回空列表
+    pub fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        self.registered_methods(path, self.auto_methods.as_ref())
+    }
+
+    /// 按给定的自动方法配置计算路径的方法列表（虚拟主机路由表沿用顶层路由器的配置）
+    fn registered_methods(&self, path: &str, auto_methods: Option<&AutoMethodsConfig>) -> Vec<Method> {
+        let mut allowed: Vec<Method> = ALLOW_CANDIDATE_METHODS.iter()
+            .filter(|method| !self.route_tree.find_routes(method, path).is_empty())
+            .cloned()
+            .collect();
+
+        if allowed.is_empty() {
+            return allowed;
+        }
+
+        if let Some(auto_methods) = auto_methods {
+            if auto_methods.auto_head && allowed.contains(&Method::GET) && !allowed.contains(&Method::HEAD) {
+                allowed.push(Method::HEAD);
+            }
+            if auto_methods.auto_options && !allowed.contains(&Method::OPTIONS) {
+                allowed.push(Method::OPTIONS);
+            }
+        }
+        allowed
+    }
+
+    /// 获取服务器上任一路由支持的方法（用于响应 `OPTIONS *`）
+    ///
+    /// 始终包含 OPTIONS；启用自动 HEAD 时，存在 GET 路由即包含 HEAD
+    pub fn server_methods(&self) -> Vec<Method> {
+        let registered: HashSet<Method> = self.route_tree.collect_all_routes()
+            .into_iter()
+            .map(|route| route.method.clone())
+            .collect();
+        let auto_head = self.auto_methods.as_ref().map(|m| m.auto_head).unwrap_or(self.head_fallback_enabled);
+
+        ALLOW_CANDIDATE_METHODS.iter()
+            .filter(|method| {
+                registered.contains(*method)
+                    || **method == Method::OPTIONS
+                    || (**method == Method::HEAD && auto_head && registered.contains(&Method::GET))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 设置是否响应 `OPTIONS *`（默认启用）
+    ///
+    /// 启用时 `OPTIONS *` 返回 `204` 并在 `Allow` 中列出服务器支持的方法，配置了 CORS 时附带 CORS 能力；
+    /// 其他方法使用 asterisk-form 返回 `400`。关闭后 `*` 按普通路径参与路由匹配
+    pub fn set_asterisk_options(&mut self, enabled: bool) -> &mut Self {
+        self.asterisk_options = enabled;
+        self
+    }
+
+    /// 构建 `OPTIONS *` 响应
+    fn create_server_options_response(&self, req: &HttpRequest) -> Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>> {
+        let empty_body = BoxBody::new(http_body_util::Full::new(Bytes::new())
+            .map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} }));
+        let mut response = Response::new(empty_body);
+        *response.status_mut() = StatusCode::NO_CONTENT;
+        response.headers_mut().insert(hyper::header::ALLOW, Self::allow_header_value(&self.server_methods()));
+
+        if let Some(cors_config) = self.cors_config.as_ref().filter(|c| c.enabled) {
+            let headers = response.headers_mut();
+            headers.insert("Access-Control-Allow-Methods", Self::allow_header_value(&cors_config.allowed_methods));
+            if !cors_config.allowed_headers.is_empty() {
+                if let Ok(value) = hyper::header::HeaderValue::from_str(&cors_config.allowed_headers.join(", ")) {
+                    headers.insert("Access-Control-Allow-Headers", value);
+                }
+            }
+            if let Some(max_age) = cors_config.max_age {
+                headers.insert("Access-Control-Max-Age", hyper::header::HeaderValue::from(max_age));
+            }
+        }
+        self.apply_cors_headers(response, req)
+    }
+
+    fn allow_header_value(methods: &[Method]) -> hyper::header::HeaderValue {
+        let value = methods.iter().map(|m| m.as_str()).collect::<Vec<_>>().join(", ");
+        hyper::header::HeaderValue::from_str(&value).unwrap_or_else(|_| hyper::header::HeaderValue::from_static("GET"))
+    }
+
+    /// 设置是否要求 HTTP/1.1 请求携带 Host 头部（默认启用）
+    ///
+    /// 启用时，缺少 Host 头部的 HTTP/1.1 请求会直接返回 `400 Bad Request`
+    pub fn require_host(&mut self, required: bool) -> &mut Self {
+        self.require_host = required;
+        self
+    }
+
+    /// 设置请求路径允许的最大段数（默认 64）
+    ///
+    /// 超过限制的请求在路由匹配前直接返回 `400 Bad Request`，
+    /// 传入 `usize::MAX` 可关闭该限制
+    pub fn set_max_path_segments(&mut self, max_segments: usize) -> &mut Self {
+        self.max_path_segments = max_segments;
+        self
+    }
+
+    /// 获取请求路径允许的最大段数
+    pub fn max_path_segments(&self) -> usize {
+        self.max_path_segments
+    }
+
+    /// 设置查询字符串允许的最大参数个数（默认 1000）
+    ///
+    /// 超过限制的请求在路由匹配前直接返回 `400 Bad Request`，
+    /// 传入 `usize::MAX` 可关闭该限制
+    pub fn set_max_query_params(&mut self, max_params: usize) -> &mut Self {
+        self.max_query_params = max_params;
+        self
+    }
+
+    /// 获取查询字符串允许的最大参数个数
+    pub fn max_query_params(&self) -> usize {
+        self.max_query_params
+    }
+
+    /// 设置是否对路径参数做百分号解码（默认不解码）
+    ///
+    /// 路由匹配始终基于原始路径按字面 `/` 分段，`%2F` 不会拆分段，
+    /// 因此 `/files/<name>` 的 `name` 可以包含编码后的斜杠。
+    /// 启用后，提取出的参数值在匹配完成后再解码（`a%2Fb` → `a/b`，`%20` → 空格）；
+    /// 解码结果不是合法 UTF-8 时保留原值
+    pub fn decode_path_params(&mut self, enabled: bool) -> &mut Self {
+        self.decode_path_params = enabled;
+        self
+    }
+
+    /// 设置客户端真实 IP 的解析方式
+    ///
+    /// 配置后按给定顺序读取可信头部，只有直连对端在可信代理列表中时才采信；
+    /// 解析结果写入 `HttpRequest::real_ip`，IP 黑名单和 `client_ip()` 都以它为准
+    pub fn real_ip_config(&mut self, config: RealIpConfig) -> &mut Self {
+        self.real_ip_config = Some(Arc::new(config));
+        self
+    }
+
+    /// 添加响应体转换器
+    ///
+    /// 转换在处理器返回之后、缓存和压缩之前执行，只处理 Content-Type 匹配 `content_types`
+    /// 的响应（支持 `text/*`，为空时匹配所有响应）。多个转换器按添加顺序依次执行。
+    /// 流式路由和已带 `Content-Encoding` 的响应不会被转换
+    pub fn add_body_transform<T>(&mut self, content_types: &[&str], transform: T) -> &mut Self
+    where
+        T: crate::server::body_transform::BodyTransform + 'static,
+    {
+        self.body_transforms.push(crate::server::body_transform::BodyTransformRule::new(content_types, transform));
+        self
+    }
+
+    /// 匹配路由，并按配置解码最佳匹配的路径参数
+    ///
+    /// 匹配基于原始（未解码）路径，%2F 不会被当作分隔符
+    fn match_routes(&self, method: &Method, path: &str) -> Vec<RouteMatch> {
+        let mut matches = self.route_tree.find_routes(method, path);
+        if self.decode_path_params {
+            if let Some(best_match) = matches.first_mut() {
+                Self::percent_decode_params(&mut best_match.params);
+            }
+        }
+        matches
+    }
+
+    /// 对路径参数做百分号解码
+    fn percent_decode_params(params: &mut HashMap<String, String>) {
+        for value in params.values_mut() {
+            if value.contains('%') {
+                if let Ok(decoded) = urlencoding::decode(value) {
+                    *value = decoded.into_owned();
+                }
+            }
+        }
+    }
+
+    /// 设置请求体大小上限（字节）
+    ///
+    /// 按解码后的字节计算，分块传输编码（`Transfer-Encoding: chunked`）的请求体同样适用；
+    /// 超出时立即停止读取并返回 `413 Payload Too Large`
+    pub fn set_max_request_body_size(&mut self, max_bytes: usize) -> &mut Self {
+        self.max_request_body_size = Some(max_bytes);
+        self
+    }
+
+    /// 获取请求体大小上限
+    pub fn max_request_body_size(&self) -> Option<usize> {
+        self.max_request_body_size
+    }
+
+    /// 设置读取请求体的空闲超时
+    ///
+    /// 每收到一块数据重新计时，连续超过该时间没有新数据时返回 `408 Request Timeout`，
+    /// 持续上传的大请求体不受总时长限制。请求体短于声明的 Content-Length 且客户端不关闭连接时同样返回 408；
+    /// 客户端提前关闭连接时返回 400。HTTP/2 的长度不符由 h2 按 RFC 9113 以 `RST_STREAM(PROTOCOL_ERROR)` 拒绝
+    pub fn set_request_body_timeout(&mut self, timeout: Option<std::time::Duration>) -> &mut Self {
+        self.request_body_timeout = timeout;
+        self
+    }
+
+    /// 获取读取请求体的空闲超时
+    pub fn request_body_timeout(&self) -> Option<std::time::Duration> {
+        self.request_body_timeout
+    }
+
+    /// 设置指定路径的访问日志级别（默认 info）
+    ///
+    /// `path` 为精确匹配，以 `*` 结尾时按前缀匹配（如 `/static/*`）；
+    /// `level` 为 `None` 时不记录该路径的成功访问日志，错误日志不受影响。
+    /// 多条规则同时匹配时以先添加的为准
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rat_engine::server::Router;
+    /// use rat_engine::utils::logger::LogLevel;
+    ///
+    /// let mut router = Router::new();
+    /// router
+    ///     .set_access_log_level("/health", None)
+    ///     .set_access_log_level("/static/*", Some(LogLevel::Debug));
+    /// ```
+    pub fn set_access_log_level(&mut self, path: impl Into<String>, level: Option<crate::utils::logger::LogLevel>) -> &mut Self {
+        let path = path.into();
+        match self.access_log_levels.iter_mut().find(|(pattern, _)| *pattern == path) {
+            Some(rule) => rule.1 = level,
+            None => self.access_log_levels.push((path, level)),
+        }
+        self
+    }
+
+    /// 获取请求路径对应的访问日志级别（`None` 表示不记录）
+    pub fn access_log_level(&self, path: &str) -> Option<crate::utils::logger::LogLevel> {
+        self.access_log_levels.iter()
+            .find(|(pattern, _)| match pattern.strip_suffix('*') {
+                Some(prefix) => path.starts_with(prefix),
+                None => pattern == path,
+            })
+            .map(|(_, level)| *level)
+            .unwrap_or(Some(crate::utils::logger::LogLevel::Info))
+    }
+
+    /// 限制同时进行中的 TCP/TLS 握手（含协议检测）数量
+    ///
+    /// 达到上限时接受循环暂停接受新连接，避免握手洪泛派生无限多的任务
+    pub fn set_max_concurrent_handshakes(&mut self, max_handshakes: usize) -> &mut Self {
+        self.handshake_limiter = Some(Arc::new(crate::server::handshake_limiter::HandshakeLimiter::new(max_handshakes)));
+        self
+    }
+
+    /// 获取握手并发限制器
+    pub fn handshake_limiter(&self) -> Option<&Arc<crate::server::handshake_limiter::HandshakeLimiter>> {
+        self.handshake_limiter.as_ref()
+    }
+
+    /// 设置 PROXY protocol v2 头部的最大长度和读取超时
+    ///
+    /// 头部被拆分到多个 TCP 分段时会持续读取直到完整；超过最大长度或超时的连接直接断开
+    pub fn set_proxy_protocol_limits(&mut self, max_header_size: usize, read_timeout: std::time::Duration) -> &mut Self {
+        self.proxy_protocol_config = crate::server::proxy_protocol::ProxyProtocolConfig {
+            max_header_size: max_header_size.max(crate::server::proxy_protocol::PROXY_V2_FIXED_HEADER_LEN),
+            read_timeout,
+        };
+        self
+    }
+
+    /// 获取 PROXY protocol 头部读取配置
+    pub fn proxy_protocol_config(&self) -> crate::server::proxy_protocol::ProxyProtocolConfig {
+        self.proxy_protocol_config
+    }
+
+    /// 启用内置的构建信息端点
+    ///
+    /// `GET <path>` 返回 JSON：版本号、编译启用的特性、构建时间和当前配置的协议，
+    /// 用于确认线上实际部署的构建
+    pub fn enable_info_endpoint(&mut self, path: impl Into<String>) -> &mut Self {
+        self.info_endpoint = Some(path.into());
+        self
+    }
+
+    /// 获取构建信息
+    pub fn build_info(&self) -> serde_json::Value {
+        let features: Vec<&str> = [
+            ("tls", cfg!(feature = "tls")),
+            ("compression", cfg!(feature = "compression")),
+            ("compression-br", cfg!(feature = "compression-br")),
+            ("compression-zstd", cfg!(feature = "compression-zstd")),
+            ("cache", cfg!(feature = "cache")),
+            ("cache-full", cfg!(feature = "cache-full")),
+            ("acme", cfg!(feature = "acme")),
+            ("client", cfg!(feature = "client")),
+            ("http-client", cfg!(feature = "http-client")),
+            ("grpc-client", cfg!(feature = "grpc-client")),
+            ("python", cfg!(feature = "python")),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name)
+        .collect();
+
+        let mut protocols = Vec::new();
+        if !self.grpc_only_mode {
+            protocols.push("http/1.1");
+            if self.h2_enabled {
+                protocols.push("h2");
+            }
+            if self.h2c_enabled {
+                protocols.push("h2c");
+            }
+        }
+        if !self.http_only_mode {
+            protocols.push("grpc");
+        }
+
+        serde_json::json!({
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "build_time": env!("BUILD_TIME"),
+            "git_hash": option_env!("GIT_HASH").unwrap_or("unknown"),
+            "profile": env!("BUILD_PROFILE"),
+            "target": env!("BUILD_TARGET"),
+            "features": features,
+            "protocols": protocols,
+            "tls": self.cert_manager.is_some(),
+            "mode": if self.http_only_mode { "http" } else if self.grpc_only_mode { "grpc" } else { "mixed" },
+        })
+    }
+
+    /// 创建构建信息响应
+    fn create_info_response(&self) -> Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>> {
+        self.create_json_response(self.build_info())
+    }
+
+    /// 创建内置端点使用的 JSON 响应（不缓存）
+    fn create_json_response(&self, value: serde_json::Value) -> Response<BoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>> {
+        let body = Full::new(Bytes::from(value.to_string()));
+        let boxed_body = BoxBody::new(body.map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} }));
+
+        let mut response = Response::new(boxed_body);
+        let headers = response.headers_mut();
+        headers.insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("application/json"));
+        headers.insert(hyper::header::CACHE_CONTROL, hyper::header::HeaderValue::from_static("no-store"));
+        headers.insert(hyper::header::SERVER, hyper::header::HeaderValue::from_static(SERVER_HEADER));
+        response
+    }
+
     /// 启用 HTTP/2
     pub fn enable_h2(&mut self) -> &mut Self {
         self.h2_enabled = true;
@@ -1842,6 +3445,188 @@ impl Router {
         self
     }
 
+    /// 设置性能指标收集器
+    ///
+    /// 设置后所有协议路径（HTTP/1.1、HTTP/2、gRPC）都会统计活跃请求数
+    pub fn set_metrics(&mut self, metrics: Arc<crate::engine::metrics::AtomicMetrics>) -> &mut Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// 获取性能指标收集器
+    pub fn metrics(&self) -> Option<&Arc<crate::engine::metrics::AtomicMetrics>> {
+        self.metrics.as_ref()
+    }
+
+    /// 设置单个请求的处理期限
+    ///
+    /// 处理器在该时间内未返回响应时返回 `504 Gateway Timeout`；流式响应只限制到响应头返回为止
+    pub fn set_request_timeout(&mut self, timeout: Option<std::time::Duration>) -> &mut Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// 获取单个请求的处理期限
+    pub fn request_timeout(&self) -> Option<std::time::Duration> {
+        self.request_timeout
+    }
+
+    /// 从指定请求头读取上游传递的处理期限（例如 `X-Request-Timeout` 或 `Deadline`）
+    ///
+    /// 头部取值支持三种格式：`grpc-timeout` 格式（如 `500m`、`2S`）、十进制秒数（如 `1.5`）
+    /// 以及表示截止时刻的 HTTP 日期。实际期限取请求头与 [`Router::set_request_timeout`] 中的较小值，
+    /// 由请求头决定的期限超时后返回 `504 Gateway Timeout`；无法解析的取值会被忽略
+    pub fn honor_timeout_header(&mut self, name: impl Into<String>) -> &mut Self {
+        self.timeout_header = Some(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// 设置 HTTP/1.1 keep-alive 连接空闲超时（等待下一个请求头的最长时间）
+    pub fn set_connection_idle_timeout(&mut self, timeout: Option<std::time::Duration>) -> &mut Self {
+        self.connection_idle_timeout = timeout;
+        self
+    }
+
+    /// 获取连接空闲超时
+    pub fn connection_idle_timeout(&self) -> Option<std::time::Duration> {
+        self.connection_idle_timeout
+    }
+
+    /// 关联引擎的关闭信号，由 `RatEngineBuilder::build` 调用
+    pub(crate) fn set_shutdown_signal(&mut self, receiver: tokio::sync::watch::Receiver<bool>) -> &mut Self {
+        self.shutdown_signal = crate::server::graceful_shutdown::ShutdownSignal::new(receiver);
+        self
+    }
+
+    /// 引擎关闭信号
+    pub(crate) fn shutdown_signal(&self) -> &crate::server::graceful_shutdown::ShutdownSignal {
+        &self.shutdown_signal
+    }
+
+    /// 设置 TLS 握手超时
+    pub fn set_tls_handshake_timeout(&mut self, timeout: Option<std::time::Duration>) -> &mut Self {
+        self.tls_handshake_timeout = timeout;
+        self
+    }
+
+    /// 获取 TLS 握手超时
+    pub fn tls_handshake_timeout(&self) -> Option<std::time::Duration> {
+        self.tls_handshake_timeout
+    }
+
+    /// 设置 HTTP/2 连接参数与洪泛防护阈值
+    pub fn set_h2_config(&mut self, config: crate::server::h2_config::H2Config) -> &mut Self {
+        self.h2_config = config;
+        self
+    }
+
+    /// 获取 HTTP/2 连接参数
+    pub fn h2_config(&self) -> &crate::server::h2_config::H2Config {
+        &self.h2_config
+    }
+
+    /// 设置是否在响应中添加 X-Detected-Protocol 调试头部
+    pub fn set_expose_detection_debug(&mut self, enabled: bool) -> &mut Self {
+        self.expose_detection_debug = enabled;
+        self
+    }
+
+    /// 是否在响应中添加 X-Detected-Protocol 调试头部
+    pub fn expose_detection_debug(&self) -> bool {
+        self.expose_detection_debug
+    }
+
+    /// 设置协议拦截策略（默认只放行引擎能够处理的协议）
+    pub fn set_protocol_policy(&mut self, policy: crate::server::protocol_policy::ProtocolPolicy) -> &mut Self {
+        self.protocol_policy = policy;
+        self
+    }
+
+    /// 设置协议策略判定为 `Block` 时返回的响应，`None` 表示不写任何数据直接关闭连接
+    pub fn set_protocol_block_response(&mut self, response: Option<crate::server::protocol_policy::ProtocolBlockResponse>) -> &mut Self {
+        self.protocol_block_response = response;
+        self
+    }
+
+    /// 按协议策略判定是否放行连接
+    ///
+    /// 设置了拦截响应时，`Block` 转换为返回该响应的 `BlockWith`
+    pub fn protocol_decision(&self, protocol: &crate::server::ProtocolType, confidence: f32) -> crate::server::protocol_policy::BlockDecision {
+        use crate::server::protocol_policy::BlockDecision;
+        match ((self.protocol_policy)(protocol, confidence), &self.protocol_block_response) {
+            (BlockDecision::Block, Some(response)) => BlockDecision::BlockWith(response.to_response()),
+            (decision, _) => decision,
+        }
+    }
+
+    /// 创建按路由器配置的 hyper 连接构建器
+    ///
+    /// 配置了连接空闲超时时，HTTP/1.1 连接等待下一个请求头超过该时间即关闭
+    pub fn http_connection_builder(&self) -> hyper_util::server::conn::auto::Builder<hyper_util::rt::TokioExecutor> {
+        let mut builder = hyper_util::server::conn::auto::Builder::new(hyper_util::rt::TokioExecutor::new());
+        if let Some(idle_timeout) = self.connection_idle_timeout {
+            builder.http1()
+                .timer(hyper_util::rt::TokioTimer::new())
+                .header_read_timeout(idle_timeout);
+        }
+        builder.http2()
+            .max_frame_size(self.h2_config.max_frame_size)
+            .max_pending_accept_reset_streams(self.h2_config.max_pending_accept_reset_streams);
+        builder
+    }
+
+    /// 启用内置的指标端点
+    ///
+    /// `GET <path>` 返回 JSON：正在处理的请求数，以及按 gRPC 方法统计的调用次数、
+    /// 耗时、收发字节数和最终状态码分布
+    pub fn enable_metrics_endpoint(&mut self, path: impl Into<String>) -> &mut Self {
+        self.metrics_endpoint = Some(path.into());
+        self
+    }
+
+    /// 获取按方法统计的 gRPC 调用指标
+    pub fn grpc_metrics(&self) -> Option<Arc<crate::server::grpc_handler::GrpcMethodMetrics>> {
+        self.grpc_handler.as_ref().map(|handler| handler.metrics().clone())
+    }
+
+    /// 获取指标快照
+    pub fn metrics_snapshot(&self) -> serde_json::Value {
+        let grpc = self.grpc_metrics().map(|metrics| metrics.snapshot()).unwrap_or_default();
+        let connections = self.metrics.as_ref().map(|metrics| {
+            let reuse = metrics.connection_reuse_stats();
+            let histogram: serde_json::Map<String, serde_json::Value> = reuse.requests_per_connection.iter()
+                .map(|(upper, count)| (crate::engine::metrics::ConnectionReuseStats::bucket_label(*upper), (*count).into()))
+                .collect();
+            serde_json::json!({
+                "new_per_second": reuse.new_connections_per_second,
+                "closed": reuse.closed_connections,
+                "reuse_ratio": reuse.reuse_ratio,
+                "requests_per_connection": histogram,
+            })
+        }).unwrap_or_default();
+        serde_json::json!({
+            "active_requests": self.active_requests(),
+            "client_disconnects": self.metrics.as_ref().map(|m| m.client_disconnects()).unwrap_or(0),
+            "tls": {
+                "handshake_timeouts": self.metrics.as_ref().map(|m| m.tls_handshake_timeouts()).unwrap_or(0),
+                "handshake_failures": self.metrics.as_ref().map(|m| m.tls_handshake_failures()).unwrap_or(0),
+            },
+            "h2_flood_closures": self.metrics.as_ref().map(|m| m.h2_flood_closures()).unwrap_or(0),
+            "connections": connections,
+            "grpc": grpc,
+        })
+    }
+
+    /// 为新连接创建连接上下文，连接关闭时请求数会汇总到连接复用指标
+    pub(crate) fn new_connection_context(&self) -> crate::server::access_log::ConnectionContext {
+        crate::server::access_log::ConnectionContext::with_metrics(self.metrics.clone())
+    }
+
+    /// 获取正在处理的请求数（未设置指标收集器时返回 0）
+    pub fn active_requests(&self) -> usize {
+        self.metrics.as_ref().map(|m| m.active_requests()).unwrap_or(0)
+    }
+
     /// 获取证书管理器
     pub fn get_cert_manager(&self) -> Option<Arc<RwLock<CertificateManager>>> {
         self.cert_manager.clone()
@@ -1899,6 +3684,13 @@ impl Router {
 
             routes.push((method_str, display_pattern));
         }
+
+        // 虚拟主机路由
+        for (host, sub_router) in &self.virtual_hosts {
+            for (method_str, display_pattern) in sub_router.list_routes() {
+                routes.push((method_str, format!("{}{}", host, display_pattern)));
+            }
+        }
         
 
         
@@ -1923,6 +3715,39 @@ impl Router {
         self
     }
 
+    /// 设置启动时是否只输出路由数量摘要（完整列表降为 debug 级别）
+    pub fn set_quiet_startup(&mut self, quiet: bool) -> &mut Self {
+        self.quiet_startup = quiet;
+        self
+    }
+
+    /// 输出已注册的 HTTP 路由和 gRPC 方法
+    pub(crate) fn log_registered_routes(&self) {
+        use crate::utils::logger::{log_at, LogLevel};
+
+        let routes = self.list_routes();
+        let grpc_methods = self.list_grpc_methods();
+        let listing_level = if self.quiet_startup {
+            crate::utils::logger::info!("📋 已注册 {} 个 HTTP 路由，{} 个 gRPC 方法", routes.len(), grpc_methods.len());
+            LogLevel::Debug
+        } else {
+            LogLevel::Info
+        };
+
+        if !routes.is_empty() {
+            log_at(Some(listing_level), format_args!("📋 已注册的 HTTP 路由:"));
+            for (method, path) in routes {
+                log_at(Some(listing_level), format_args!("   {} {}", method, path));
+            }
+        }
+        if !grpc_methods.is_empty() {
+            log_at(Some(listing_level), format_args!("📝 已注册的 gRPC 方法:"));
+            for method in grpc_methods {
+                log_at(Some(listing_level), format_args!("   {}", method));
+            }
+        }
+    }
+
     /// 列出所有已注册的 gRPC 方法
     pub fn list_grpc_methods(&self) -> Vec<String> {
         if let Ok(registry) = self.grpc_registry.read() {
@@ -1932,4 +3757,28 @@ impl Router {
             Vec::new()
         }
     }
-}
\ No newline at end of file
+}
+
+/// 请求体未被读取就返回响应时，HTTP/1.x 连接上还可能有客户端稍后发送的请求体，
+/// 要求关闭连接，避免把请求体当成下一个请求解析
+fn close_if_body_unread<B>(parts: &http::request::Parts, response: &mut Response<B>) {
+    let has_body = parts.headers.contains_key(hyper::header::TRANSFER_ENCODING)
+        || parts.headers.get(hyper::header::CONTENT_LENGTH).is_some_and(|v| v.as_bytes() != b"0");
+    if has_body && parts.version <= hyper::Version::HTTP_11 {
+        response.headers_mut().insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("close"));
+    }
+}
+
+/// 解析请求头中的处理期限：`grpc-timeout` 格式、十进制秒数或 HTTP 日期（截止时刻）
+fn parse_timeout_header(value: &str) -> Option<std::time::Duration> {
+    let value = value.trim();
+    if let Some(timeout) = crate::server::grpc_types::GrpcContext::parse_grpc_timeout(value) {
+        return Some(timeout);
+    }
+    if let Ok(seconds) = value.parse::<f64>() {
+        return std::time::Duration::try_from_secs_f64(seconds).ok();
+    }
+    httpdate::parse_http_date(value).ok().map(|deadline| {
+        deadline.duration_since(std::time::SystemTime::now()).unwrap_or_default()
+    })
+}
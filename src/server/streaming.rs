@@ -2,6 +2,7 @@
 //! 
 //! 提供高性能的流式数据传输能力：
 //! - Server-Sent Events (SSE) 支持（含连接池管理）
+//! - 长连接二进制帧推送（MJPEG、protobuf 事件等）
 //! - 分块传输编码 (Chunked Transfer Encoding)
 //! - 自定义流式响应处理器
 //! - PyO3 绑定支持
@@ -12,6 +13,7 @@ use hyper::body::{Bytes, Frame, Incoming};
 use http_body_util::{StreamBody, BodyExt};
 use tokio_stream::{Stream, StreamExt};
 use std::pin::Pin;
+use std::future::Future;
 use std::task::{Context, Poll};
 use std::collections::HashMap;
 use futures_util::stream;
@@ -77,6 +79,44 @@ impl StreamingResponse {
         self
     }
 
+    /// 使用有界通道作为数据源，返回推送数据的发送器
+    ///
+    /// 通道最多缓冲 `buffer` 个数据块（至少为 1）。缓冲区满时 [`StreamSender::send`] 会等待客户端读走数据，
+    /// 生产者因此跟随客户端的读取速度，慢客户端不会让未发送的数据在内存中无限堆积
+    ///
+    /// # 示例
+    /// ```ignore
+    /// let (response, sender) = StreamingResponse::new()
+    ///     .with_header("Content-Type", "text/csv")
+    ///     .channel(DEFAULT_STREAM_BUFFER);
+    /// tokio::spawn(async move {
+    ///     while let Some(row) = rows.next().await {
+    ///         if sender.send(row).await.is_err() {
+    ///             break; // 客户端已断开
+    ///         }
+    ///     }
+    /// });
+    /// response.build()
+    /// ```
+    pub fn channel(mut self, buffer: usize) -> (Self, StreamSender) {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        self.stream = Some(Box::pin(tokio_stream::wrappers::ReceiverStream::new(receiver)));
+        (self, StreamSender { sender })
+    }
+
+    /// 设置延迟计算的响应体
+    ///
+    /// `producer` 在响应体首次被轮询时才会执行，响应头先行发送；
+    /// 客户端在此之前断开时连接被丢弃，计算不会被触发，计算过程中断开则直接取消
+    pub fn deferred<F, Fut>(mut self, producer: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> + Send + 'static,
+    {
+        self.stream = Some(Box::pin(DeferredStream::new(producer)));
+        self
+    }
+
     /// 构建 hyper Response
     pub fn build(self) -> Result<Response<StreamingBody>, hyper::Error> {
         let mut response = Response::builder().status(self.status);
@@ -114,6 +154,175 @@ impl StreamingResponse {
     }
 }
 
+/// 延迟计算响应体的 future 类型
+type DeferredBodyFuture = Pin<Box<dyn Future<Output = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> + Send>>;
+
+/// 延迟计算响应体的执行状态
+enum DeferredState {
+    /// 尚未被轮询，保存生成函数
+    Pending(Box<dyn FnOnce() -> DeferredBodyFuture + Send>),
+    /// 正在计算
+    Running(DeferredBodyFuture),
+    /// 已产出数据
+    Done,
+}
+
+/// 延迟计算的响应体数据源
+///
+/// 状态放在 Mutex 中只是为了满足 `StreamingBody` 的 Sync 约束，
+/// 轮询时通过 `get_mut` 访问，不会产生锁竞争
+struct DeferredStream {
+    state: std::sync::Mutex<DeferredState>,
+}
+
+impl DeferredStream {
+    fn new<F, Fut>(producer: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> + Send + 'static,
+    {
+        let producer: Box<dyn FnOnce() -> DeferredBodyFuture + Send> = Box::new(move || Box::pin(producer()));
+        Self {
+            state: std::sync::Mutex::new(DeferredState::Pending(producer)),
+        }
+    }
+}
+
+impl Stream for DeferredStream {
+    type Item = Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let state = self.get_mut().state.get_mut().unwrap_or_else(|e| e.into_inner());
+        loop {
+            match state {
+                DeferredState::Pending(_) => {
+                    if let DeferredState::Pending(producer) = std::mem::replace(state, DeferredState::Done) {
+                        trace!("⏳ 开始计算延迟响应体");
+                        *state = DeferredState::Running(producer());
+                    }
+                }
+                DeferredState::Running(future) => {
+                    let result = std::task::ready!(future.as_mut().poll(cx));
+                    *state = DeferredState::Done;
+                    return Poll::Ready(Some(result.map(Frame::data)));
+                }
+                DeferredState::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// 超过 SSE 单行上限或消息绝对上限时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SseOversizePolicy {
+    /// 拒绝发送，返回错误
+    Reject,
+    /// 截断到上限后发送
+    Truncate,
+}
+
+/// SSE 消息大小限制
+///
+/// 按 SSE 规范，多个连续的 `data:` 行会被客户端用换行拼接，
+/// 因此超长的行不能拆分为多行发送（会改变数据内容），只能拒绝或截断
+#[derive(Debug, Clone, Copy)]
+pub struct SseLimits {
+    /// 单个 `data:` 行的最大字节数（None 表示不限制）
+    pub max_line_size: Option<usize>,
+    /// 单条消息数据的绝对上限（字节，None 表示不限制）
+    pub max_message_size: Option<usize>,
+    /// 超过单行上限或绝对上限时的处理方式
+    pub oversize_policy: SseOversizePolicy,
+}
+
+impl Default for SseLimits {
+    fn default() -> Self {
+        Self {
+            max_line_size: None,
+            max_message_size: None,
+            oversize_policy: SseOversizePolicy::Reject,
+        }
+    }
+}
+
+impl SseLimits {
+    /// 设置单行最大字节数，超限时按 `oversize_policy` 拒绝或截断该行
+    pub fn with_max_line_size(mut self, size: usize) -> Self {
+        self.max_line_size = Some(size.max(1));
+        self
+    }
+
+    /// 设置消息绝对上限及超限处理方式
+    pub fn with_max_message_size(mut self, size: usize, policy: SseOversizePolicy) -> Self {
+        self.max_message_size = Some(size);
+        self.oversize_policy = policy;
+        self
+    }
+
+    /// 格式化 SSE 消息（不含结尾空行）
+    ///
+    /// 数据中的换行（`\n`、`\r\n` 或单独的 `\r`）会拆分为多个 `data:` 行；
+    /// 超过单行上限的行按超限策略拒绝或按 UTF-8 字符边界截断。
+    /// 事件名包含 CR/LF 时拒绝，避免注入额外字段
+    pub fn format_message(&self, event: Option<&str>, data: &str) -> Result<String, String> {
+        if event.is_some_and(|event| event.contains(['\r', '\n'])) {
+            return Err("SSE 事件名不能包含换行符".to_string());
+        }
+
+        let data = match self.max_message_size {
+            Some(max) if data.len() > max => match self.oversize_policy {
+                SseOversizePolicy::Reject => {
+                    return Err(format!("SSE 消息过大: {} 字节，上限 {} 字节", data.len(), max));
+                }
+                SseOversizePolicy::Truncate => &data[..floor_char_boundary(data, max)],
+            },
+            _ => data,
+        };
+
+        let mut formatted = String::with_capacity(data.len() + 32);
+        if let Some(event) = event {
+            formatted.push_str("event: ");
+            formatted.push_str(event);
+            formatted.push('\n');
+        }
+
+        let normalized;
+        let data = if data.contains("\r\n") {
+            normalized = data.replace("\r\n", "\n");
+            normalized.as_str()
+        } else {
+            data
+        };
+        for line in data.split(['\n', '\r']) {
+            let line = match self.max_line_size {
+                Some(max) if line.len() > max => match self.oversize_policy {
+                    SseOversizePolicy::Reject => {
+                        return Err(format!("SSE 数据行过长: {} 字节，上限 {} 字节", line.len(), max));
+                    }
+                    SseOversizePolicy::Truncate => &line[..floor_char_boundary(line, max)],
+                },
+                _ => line,
+            };
+            formatted.push_str("data: ");
+            formatted.push_str(line);
+            formatted.push('\n');
+        }
+
+        Ok(formatted)
+    }
+}
+
+/// 向下取整到 UTF-8 字符边界
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    let mut i = index;
+    while !s.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
 
 /// Server-Sent Events (SSE) 流式响应
 ///
@@ -121,35 +330,43 @@ impl StreamingResponse {
 pub struct SseResponse {
     pub sender: mpsc::UnboundedSender<Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>,
     pub receiver: mpsc::UnboundedReceiver<Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>,
+    /// 消息大小限制
+    pub limits: SseLimits,
 }
 
 impl SseResponse {
     /// 创建新的 SSE 响应
     pub fn new() -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
-        Self { sender, receiver }
+        Self { sender, receiver, limits: SseLimits::default() }
+    }
+
+    /// 设置消息大小限制
+    pub fn with_limits(mut self, limits: SseLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     /// 发送 SSE 事件
     pub fn send_event(&self, event: &str, data: &str) -> Result<(), String> {
-        rat_logger::debug!("Sending SSE event: type={}, data={}", event, data);
-        let formatted = format!("event: {}\ndata: {}\n\n\n", event, data);
+        crate::utils::logger::debug!("Sending SSE event: type={}, data={}", event, data);
+        let formatted = format!("{}\n\n", self.limits.format_message(Some(event), data)?);
         self.sender
             .send(Ok(Frame::data(Bytes::from(formatted))))
             .map_err(|e| {
-                rat_logger::debug!("Failed to send SSE event: {:?}", e);
+                crate::utils::logger::debug!("Failed to send SSE event: {:?}", e);
                 "Failed to send SSE event".to_string()
             })
     }
 
     /// 发送简单数据
     pub fn send_data(&self, data: &str) -> Result<(), String> {
-        rat_logger::debug!("Sending SSE data: {}", data);
-        let formatted = format!("data: {}\n\n\n", data);
+        crate::utils::logger::debug!("Sending SSE data: {}", data);
+        let formatted = format!("{}\n\n", self.limits.format_message(None, data)?);
         self.sender
             .send(Ok(Frame::data(Bytes::from(formatted))))
             .map_err(|e| {
-                rat_logger::debug!("Failed to send SSE data: {:?}", e);
+                crate::utils::logger::debug!("Failed to send SSE data: {:?}", e);
                 "Failed to send SSE data".to_string()
             })
     }
@@ -190,6 +407,221 @@ impl SseResponse {
     }
 }
 
+/// multipart 二进制推送使用的分隔符
+const BINARY_STREAM_BOUNDARY: &str = "rat-frame";
+
+/// 推送通道默认缓冲的数据块个数
+pub const DEFAULT_STREAM_BUFFER: usize = 16;
+
+/// 有界推送通道的发送器，由 [`StreamingResponse::channel`] 创建
+///
+/// 可以克隆后交给多个任务；所有克隆共享同一个缓冲区
+#[derive(Clone)]
+pub struct StreamSender {
+    sender: mpsc::Sender<Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>,
+}
+
+impl StreamSender {
+    /// 推送一个数据块，缓冲区满时等待客户端读走数据
+    ///
+    /// 客户端断开（响应体被丢弃）后返回错误
+    pub async fn send<T: Into<Bytes>>(&self, chunk: T) -> Result<(), String> {
+        self.sender
+            .send(Ok(Frame::data(chunk.into())))
+            .await
+            .map_err(|_| "客户端已断开".to_string())
+    }
+
+    /// 尝试推送一个数据块，缓冲区满或客户端断开时立即返回错误而不等待
+    pub fn try_send<T: Into<Bytes>>(&self, chunk: T) -> Result<(), String> {
+        self.sender
+            .try_send(Ok(Frame::data(chunk.into())))
+            .map_err(|e| match e {
+                mpsc::error::TrySendError::Full(_) => "缓冲区已满".to_string(),
+                mpsc::error::TrySendError::Closed(_) => "客户端已断开".to_string(),
+            })
+    }
+
+    /// 以错误中止响应体，客户端会看到不完整的响应
+    pub async fn abort(&self, error: impl Into<Box<dyn std::error::Error + Send + Sync>>) {
+        let _ = self.sender.send(Err(error.into())).await;
+    }
+
+    /// 客户端是否已断开（响应体已被丢弃）
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// 缓冲区当前的剩余容量
+    pub fn capacity(&self) -> usize {
+        self.sender.capacity()
+    }
+}
+
+/// 二进制帧发送器
+///
+/// 可以克隆后交给其他任务推送数据；multipart 模式下每帧自动加上分隔符和分段头部
+#[derive(Clone)]
+pub struct BinaryFrameSender {
+    sender: mpsc::UnboundedSender<Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>,
+    /// multipart 模式下每个分段的 Content-Type
+    part_content_type: Option<Arc<str>>,
+}
+
+impl BinaryFrameSender {
+    /// 推送一帧数据
+    pub fn send_frame<T: Into<Bytes>>(&self, frame: T) -> Result<(), String> {
+        let frame = frame.into();
+        let frame = match &self.part_content_type {
+            Some(part_content_type) => {
+                let header = format!(
+                    "--{}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+                    BINARY_STREAM_BOUNDARY, part_content_type, frame.len()
+                );
+                let mut part = Vec::with_capacity(header.len() + frame.len() + 2);
+                part.extend_from_slice(header.as_bytes());
+                part.extend_from_slice(&frame);
+                part.extend_from_slice(b"\r\n");
+                Bytes::from(part)
+            }
+            None => frame,
+        };
+
+        trace!("📦 [二进制流] 发送帧: {} 字节", frame.len());
+        self.sender
+            .send(Ok(Frame::data(frame)))
+            .map_err(|_| "Failed to send binary frame".to_string())
+    }
+
+    /// 客户端是否已断开（响应体已被丢弃）
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
+
+/// 长连接二进制推送响应
+///
+/// 与 SSE 一样由处理器持有发送器持续推送，但不做文本分帧，可以传输任意 `Bytes`，
+/// 适用于摄像头 MJPEG、遥测 protobuf 事件等场景
+pub struct BinaryStreamResponse {
+    frames: BinaryFrameSender,
+    receiver: mpsc::UnboundedReceiver<Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>>,
+    content_type: String,
+}
+
+impl BinaryStreamResponse {
+    /// 创建原样推送数据的二进制流，`content_type` 为整个响应的类型
+    pub fn new(content_type: impl Into<String>) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            frames: BinaryFrameSender { sender, part_content_type: None },
+            receiver,
+            content_type: content_type.into(),
+        }
+    }
+
+    /// 创建 `multipart/x-mixed-replace` 流，每帧作为一个分段替换上一帧
+    ///
+    /// `part_content_type` 为每个分段的类型，例如 MJPEG 使用 `image/jpeg`
+    pub fn multipart(part_content_type: impl Into<String>) -> Self {
+        let mut response = Self::new(format!("multipart/x-mixed-replace; boundary={}", BINARY_STREAM_BOUNDARY));
+        response.frames.part_content_type = Some(Arc::from(part_content_type.into()));
+        response
+    }
+
+    /// 创建 MJPEG 流
+    pub fn mjpeg() -> Self {
+        Self::multipart("image/jpeg")
+    }
+
+    /// 推送一帧数据
+    pub fn send_frame<T: Into<Bytes>>(&self, frame: T) -> Result<(), String> {
+        self.frames.send_frame(frame)
+    }
+
+    /// 获取发送器的克隆
+    pub fn get_sender(&self) -> BinaryFrameSender {
+        self.frames.clone()
+    }
+
+    /// 构建二进制推送响应
+    pub fn build(self) -> Result<Response<StreamingBody>, hyper::Error> {
+        let stream = tokio_stream::wrappers::UnboundedReceiverStream::new(self.receiver);
+
+        StreamingResponse::new()
+            .status(StatusCode::OK)
+            .with_header("Content-Type", self.content_type.as_str())
+            .with_header("Cache-Control", "no-cache")
+            .with_header("Connection", "keep-alive")
+            .stream(stream)
+            .build()
+    }
+}
+
+/// NDJSON（JSON Lines）流式响应
+///
+/// 把 `Stream<Item = Result<T, E>>` 中的每一项序列化为一行 JSON，逐项作为独立的数据帧写出，
+/// 适合流式返回大型查询结果。数据源返回错误或序列化失败时中止响应体，客户端会看到不完整的响应
+pub struct NdjsonResponse {
+    stream: Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>> + Send + Sync>>,
+}
+
+impl NdjsonResponse {
+    /// 从数据流创建 NDJSON 响应
+    pub fn new<S, T, E>(items: S) -> Self
+    where
+        S: Stream<Item = Result<T, E>> + Send + 'static,
+        T: serde::Serialize,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self { stream: Box::pin(NdjsonStream { items: std::sync::Mutex::new(Box::pin(items)) }) }
+    }
+
+    /// 构建 NDJSON 响应
+    pub fn build(self) -> Result<Response<StreamingBody>, hyper::Error> {
+        StreamingResponse::new()
+            .status(StatusCode::OK)
+            .with_header("Content-Type", "application/x-ndjson")
+            .with_header("Cache-Control", "no-cache")
+            .stream(self.stream)
+            .build()
+    }
+}
+
+/// 把数据项逐行序列化为 JSON 的数据源
+///
+/// 与 `DeferredStream` 相同，Mutex 只用于满足 `StreamingBody` 的 Sync 约束
+struct NdjsonStream<S> {
+    items: std::sync::Mutex<Pin<Box<S>>>,
+}
+
+impl<S, T, E> Stream for NdjsonStream<S>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: serde::Serialize,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Item = Result<Frame<Bytes>, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let items = self.get_mut().items.get_mut().unwrap_or_else(|e| e.into_inner());
+        let line = match std::task::ready!(items.as_mut().poll_next(cx)) {
+            Some(Ok(item)) => serde_json::to_vec(&item)
+                .map(|mut line| {
+                    line.push(b'\n');
+                    Frame::data(Bytes::from(line))
+                })
+                .map_err(|e| e.into()),
+            Some(Err(e)) => Err(e.into()),
+            None => return Poll::Ready(None),
+        };
+        if let Err(e) = &line {
+            error!("❌ [NDJSON] 数据流中止: {}", e);
+        }
+        Poll::Ready(Some(line))
+    }
+}
+
 /// 分块流式响应
 #[derive(Clone)]
 pub struct ChunkedResponse {
@@ -218,6 +650,18 @@ impl ChunkedResponse {
         self
     }
 
+    /// 创建由生产者持续推送数据块的分块响应
+    ///
+    /// 最多缓冲 `buffer` 个数据块，缓冲区满时 [`StreamSender::send`] 等待客户端读取（背压），
+    /// 所有发送器被丢弃后响应结束
+    pub fn channel(buffer: usize) -> (StreamingResponse, StreamSender) {
+        StreamingResponse::new()
+            .status(StatusCode::OK)
+            .with_header("Transfer-Encoding", "chunked")
+            .with_header("Content-Type", "text/plain; charset=utf-8")
+            .channel(buffer)
+    }
+
     /// 构建分块响应
     pub fn build(self) -> Result<Response<StreamingBody>, hyper::Error> {
         let chunks = self.chunks;
@@ -0,0 +1,107 @@
+//! 按响应控制 TCP 写出策略
+//!
+//! 全局的 `tcp_nodelay` 对所有响应一视同仁。处理器可以在响应扩展中放入 [`TcpWriteMode`]，
+//! 让大文件传输合并写出（TCP_CORK / TCP_NOPUSH），小的 JSON 响应保持低延迟（TCP_NODELAY）。
+//!
+//! 需要直接操作底层 socket，目前只在引擎直接处理的明文 HTTP/1.1 路径上生效；
+//! TLS、HTTP/2 以及由 hyper 接管的连接会忽略该标记
+
+use std::io;
+use tokio::net::TcpStream;
+
+/// 单个响应的 TCP 写出策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpWriteMode {
+    /// 低延迟：写出期间开启 TCP_NODELAY，小数据立即发送
+    LowLatency,
+    /// 高吞吐：写出期间关闭 TCP_NODELAY 并开启 TCP_CORK，合并为尽量满的报文段
+    Throughput,
+}
+
+/// 写出前应用策略，返回写出后用于恢复的原 TCP_NODELAY 设置
+pub(crate) fn begin(stream: &TcpStream, mode: TcpWriteMode) -> io::Result<bool> {
+    let previous_nodelay = stream.nodelay()?;
+    match mode {
+        TcpWriteMode::LowLatency => stream.set_nodelay(true)?,
+        TcpWriteMode::Throughput => {
+            stream.set_nodelay(false)?;
+            set_cork(stream, true)?;
+        }
+    }
+    Ok(previous_nodelay)
+}
+
+/// 写出后恢复连接原来的设置（关闭 TCP_CORK 时内核会立即发送剩余数据）
+pub(crate) fn end(stream: &TcpStream, mode: TcpWriteMode, previous_nodelay: bool) -> io::Result<()> {
+    if mode == TcpWriteMode::Throughput {
+        set_cork(stream, false)?;
+    }
+    stream.set_nodelay(previous_nodelay)
+}
+
+/// 设置 TCP_CORK（Linux）或 TCP_NOPUSH（BSD/macOS），其他平台不做任何事
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_cork(stream: &TcpStream, enabled: bool) -> io::Result<()> {
+    set_tcp_option(stream, libc::TCP_CORK, enabled)
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+fn set_cork(stream: &TcpStream, enabled: bool) -> io::Result<()> {
+    set_tcp_option(stream, libc::TCP_NOPUSH, enabled)
+}
+
+#[cfg(not(any(
+    target_os = "linux", target_os = "android",
+    target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"
+)))]
+fn set_cork(_stream: &TcpStream, _enabled: bool) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+#[allow(dead_code)]
+fn set_tcp_option(stream: &TcpStream, option: libc::c_int, enabled: bool) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let optval: libc::c_int = enabled as libc::c_int;
+    let ret = unsafe {
+        libc::setsockopt(
+            stream.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            option,
+            &optval as *const _ as *const libc::c_void,
+            std::mem::size_of_val(&optval) as libc::socklen_t,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mode_is_restored_after_write() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (client, _server) = tokio::join!(TcpStream::connect(addr), listener.accept());
+        let client = client.unwrap();
+        client.set_nodelay(true).unwrap();
+
+        let previous = begin(&client, TcpWriteMode::Throughput).unwrap();
+        assert!(previous);
+        assert!(!client.nodelay().unwrap());
+        end(&client, TcpWriteMode::Throughput, previous).unwrap();
+        assert!(client.nodelay().unwrap());
+
+        client.set_nodelay(false).unwrap();
+        let previous = begin(&client, TcpWriteMode::LowLatency).unwrap();
+        assert!(client.nodelay().unwrap());
+        end(&client, TcpWriteMode::LowLatency, previous).unwrap();
+        assert!(!client.nodelay().unwrap());
+    }
+}
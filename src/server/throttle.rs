@@ -0,0 +1,163 @@
+//! 响应带宽限制
+//!
+//! 按字节速率匀速写出响应体，用于大文件下载时避免占满上行带宽，或在测试中模拟慢速响应。
+//! 响应体被切成每 100ms 左右一块，每块写出后等待到按速率应当到达的时刻再继续，
+//! 响应头和 `Content-Length` 不受影响。
+//!
+//! 限速有两种设置方式：
+//! - 单个路由：`Router::add_route_with_rate`，或处理器调用 [`with_bandwidth_limit`] 标记响应
+//! - 整个路由器：`Router::set_response_rate_limit`，作为所有未单独标记的响应的默认值
+//!
+//! 速率按单个响应计算，没有连接级的共享额度：HTTP/1.1 连接上的响应依次发送，等同于按连接限速；
+//! HTTP/2 连接上的多个流各自独立限速，客户端同时打开 N 个流即可获得 N 倍速率。
+//! 需要严格按连接限制带宽时，应只启用 HTTP/1.1，或在前置代理上按连接限速。
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use hyper::Response;
+use hyper::body::{Body, Bytes, Frame, SizeHint};
+use tokio::time::{Instant, Sleep};
+
+/// 单块的最大字节数，避免高速率下一次写出过多数据
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+/// 响应体的写出速率上限（字节/秒）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BandwidthLimit {
+    bytes_per_sec: u64,
+}
+
+impl BandwidthLimit {
+    /// 创建速率上限，0 按 1 字节/秒处理
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self { bytes_per_sec: bytes_per_sec.max(1) }
+    }
+
+    /// 速率上限（字节/秒）
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+
+    fn chunk_size(&self) -> usize {
+        ((self.bytes_per_sec / 10) as usize).clamp(1, MAX_CHUNK_SIZE)
+    }
+}
+
+/// 为响应标记写出速率上限，优先于路由器的默认限速
+pub fn with_bandwidth_limit<B>(mut response: Response<B>, bytes_per_sec: u64) -> Response<B> {
+    response.extensions_mut().insert(BandwidthLimit::new(bytes_per_sec));
+    response
+}
+
+/// 按速率上限写出的响应体
+pub struct ThrottledBody<B> {
+    inner: B,
+    limit: BandwidthLimit,
+    /// 当前数据帧中尚未写出的部分
+    pending: Option<Bytes>,
+    /// 写出第一块的时刻
+    started: Option<Instant>,
+    /// 已写出的字节数
+    sent: u64,
+    /// 等待下一块可以写出
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<B> ThrottledBody<B> {
+    /// 包装响应体
+    pub fn new(inner: B, limit: BandwidthLimit) -> Self {
+        Self { inner, limit, pending: None, started: None, sent: 0, sleep: None }
+    }
+}
+
+impl<B> Body for ThrottledBody<B>
+where
+    B: Body<Data = Bytes> + Unpin,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Bytes>, B::Error>>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(sleep) = this.sleep.as_mut() {
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.sleep = None;
+            }
+
+            if let Some(mut data) = this.pending.take() {
+                let chunk = data.split_to(this.limit.chunk_size().min(data.len()));
+                if !data.is_empty() {
+                    this.pending = Some(data);
+                }
+
+                let started = *this.started.get_or_insert_with(Instant::now);
+                this.sent += chunk.len() as u64;
+                let due = started + Duration::from_secs_f64(this.sent as f64 / this.limit.bytes_per_sec as f64);
+                if due > Instant::now() {
+                    this.sleep = Some(Box::pin(tokio::time::sleep_until(due)));
+                }
+                return Poll::Ready(Some(Ok(Frame::data(chunk))));
+            }
+
+            match Pin::new(&mut this.inner).poll_frame(cx) {
+                Poll::Ready(Some(Ok(frame))) => match frame.into_data() {
+                    Ok(data) => {
+                        if !data.is_empty() {
+                            this.pending = Some(data);
+                        }
+                    }
+                    // trailers 等非数据帧直接透传
+                    Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                },
+                other => return other,
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.pending.is_none() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let pending = self.pending.as_ref().map(|data| data.len() as u64).unwrap_or(0);
+        let inner = self.inner.size_hint();
+        let mut hint = SizeHint::new();
+        hint.set_lower(inner.lower() + pending);
+        if let Some(upper) = inner.upper() {
+            hint.set_upper(upper + pending);
+        }
+        hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::{BodyExt, Full};
+
+    #[tokio::test]
+    async fn test_body_is_paced_and_length_preserved() {
+        let body = ThrottledBody::new(Full::new(Bytes::from(vec![7u8; 600])), BandwidthLimit::new(2000));
+        assert_eq!(body.size_hint().exact(), Some(600));
+
+        let started = std::time::Instant::now();
+        let collected = body.collect().await.unwrap().to_bytes();
+        let elapsed = started.elapsed();
+        assert_eq!(collected.len(), 600);
+        // 600 字节按 2000 字节/秒约需 300ms
+        assert!(elapsed >= Duration::from_millis(250), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(2), "{:?}", elapsed);
+    }
+
+    #[test]
+    fn test_marker_extension() {
+        let response = with_bandwidth_limit(Response::new(()), 0);
+        assert_eq!(response.extensions().get::<BandwidthLimit>().map(|l| l.bytes_per_sec()), Some(1));
+    }
+}
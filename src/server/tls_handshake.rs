@@ -0,0 +1,94 @@
+//! TLS 握手
+//!
+//! 所有 TLS 接入路径统一通过这里完成握手，以便施加握手超时：
+//! 客户端建立 TCP 连接后迟迟不发送或不完成 ClientHello 时，
+//! 超时后关闭连接，避免半开连接长期占用握手许可和内存。
+//!
+//! 握手超时与握手失败分别计入 `tls_handshake_timeouts` 和 `tls_handshake_failures` 指标，
+//! 便于区分慢速握手攻击和证书、协议不匹配等问题。
+
+use std::io;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::server::TlsStream;
+
+use crate::engine::metrics::AtomicMetrics;
+
+/// 执行 TLS 握手，`timeout` 为 None 时不限制握手时间
+///
+/// 超时返回 `io::ErrorKind::TimedOut` 错误，未完成的握手随之丢弃，连接被关闭
+pub async fn accept<S>(
+    acceptor: &TlsAcceptor,
+    stream: S,
+    timeout: Option<Duration>,
+    metrics: Option<&AtomicMetrics>,
+) -> io::Result<TlsStream<S>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let handshake = acceptor.accept(stream);
+    let result = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, handshake).await {
+            Ok(result) => result,
+            Err(_) => {
+                crate::utils::logger::warn!("⏱️ TLS 握手超时（{:?}），关闭连接", timeout);
+                if let Some(metrics) = metrics {
+                    metrics.record_tls_handshake_timeout();
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("TLS 握手超时（{:?}）", timeout),
+                ));
+            }
+        },
+        None => handshake.await,
+    };
+
+    if result.is_err() {
+        if let Some(metrics) = metrics {
+            metrics.record_tls_handshake_failure();
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+    use tokio::io::AsyncWriteExt;
+
+    fn test_acceptor() -> TlsAcceptor {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.serialize_private_key_der()));
+        let config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![CertificateDer::from(cert.serialize_der().unwrap())], key)
+            .unwrap();
+        TlsAcceptor::from(Arc::new(config))
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_counted_separately_from_failures() {
+        let acceptor = test_acceptor();
+        let metrics = AtomicMetrics::new();
+
+        // 客户端建立连接后一直不发送 ClientHello
+        let (_client, server) = tokio::io::duplex(1024);
+        let error = accept(&acceptor, server, Some(Duration::from_millis(20)), Some(&metrics)).await.err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(metrics.tls_handshake_timeouts(), 1);
+        assert_eq!(metrics.tls_handshake_failures(), 0);
+
+        // 发送非 TLS 数据属于握手失败
+        let (mut client, server) = tokio::io::duplex(1024);
+        client.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        assert!(accept(&acceptor, server, Some(Duration::from_secs(5)), Some(&metrics)).await.is_err());
+        assert_eq!(metrics.tls_handshake_timeouts(), 1);
+        assert_eq!(metrics.tls_handshake_failures(), 1);
+    }
+}
@@ -190,15 +190,38 @@ impl HttpRequest {
     }
 
     /// 将请求体转换为字符串
+    #[deprecated(note = "请使用 body_text() 替代，错误信息已本地化")]
     pub fn body_as_string(&self) -> Result<String, std::string::FromUtf8Error> {
         String::from_utf8(self.body.to_vec())
     }
 
     /// 将请求体解析为 JSON
+    #[deprecated(note = "请使用 body_json() 替代，错误信息已本地化并包含行列位置")]
     pub fn body_as_json(&self) -> Result<Value, serde_json::Error> {
         serde_json::from_slice(&self.body)
     }
 
+    /// 将请求体转换为字符串
+    ///
+    /// 不是有效的 UTF-8 时返回本地化的 `RatError::ValidationError`
+    pub fn body_text(&self) -> crate::error::RatResult<String> {
+        String::from_utf8(self.body.to_vec()).map_err(|e| {
+            crate::error::RatError::ValidationError(rat_embed_lang::tf("request_body_not_utf8", &[("msg", &e.to_string())]))
+        })
+    }
+
+    /// 将请求体解析为 JSON
+    ///
+    /// 解析失败时返回本地化的 `RatError::DeserializationError`，包含出错的行列位置
+    pub fn body_json(&self) -> crate::error::RatResult<Value> {
+        self.body_as()
+    }
+
+    /// 将请求体反序列化为指定类型
+    pub fn body_as<T: serde::de::DeserializeOwned>(&self) -> crate::error::RatResult<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
     /// 获取 Content-Type
     pub fn content_type(&self) -> Option<&str> {
         self.header("content-type")
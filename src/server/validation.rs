@@ -0,0 +1,247 @@
+//! 声明式请求校验
+//!
+//! 请求体先反序列化为目标类型，再调用 [`Validate::validate`] 检查取值，
+//! 失败时由路由器统一返回带字段级错误的 `422 Unprocessable Entity`，处理器只会拿到合法的值。
+//!
+//! ```ignore
+//! impl Validate for SendMessage {
+//!     fn validate(&self) -> Result<(), ValidationErrors> {
+//!         let mut errors = ValidationErrors::new();
+//!         errors.check_length("message", &self.message, 1, 500);
+//!         errors.check_range("priority", self.priority, 0, 9);
+//!         errors.into_result()
+//!     }
+//! }
+//!
+//! router.add_validated_route::<SendMessage, _>(Method::POST, "/messages", |req, body| {
+//!     Box::pin(async move { ... })
+//! });
+//! ```
+
+use hyper::{Response, StatusCode};
+use hyper::body::Bytes;
+use http_body_util::Full;
+use serde::Serialize;
+
+/// 单个字段的校验错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// 字段名，嵌套字段用 `.` 连接；无法定位到字段时为空
+    pub field: String,
+    /// 机器可读的错误代码，例如 `required`、`length`、`range`
+    pub code: String,
+    /// 面向调用方的错误描述
+    pub message: String,
+}
+
+/// 校验错误集合
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationErrors {
+    errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    /// 创建空的错误集合
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个字段错误
+    pub fn add(&mut self, field: impl Into<String>, code: impl Into<String>, message: impl Into<String>) -> &mut Self {
+        self.errors.push(FieldError {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+        });
+        self
+    }
+
+    /// 检查字符串长度（按字符计数）是否在 `[min, max]` 内
+    pub fn check_length(&mut self, field: &str, value: &str, min: usize, max: usize) -> &mut Self {
+        let len = value.chars().count();
+        if len < min || len > max {
+            self.add(field, "length", format!("长度必须在 {} 到 {} 之间，当前为 {}", min, max, len));
+        }
+        self
+    }
+
+    /// 检查数值是否在 `[min, max]` 内
+    pub fn check_range<N>(&mut self, field: &str, value: N, min: N, max: N) -> &mut Self
+    where
+        N: PartialOrd + std::fmt::Display,
+    {
+        if value < min || value > max {
+            self.add(field, "range", format!("取值必须在 {} 到 {} 之间，当前为 {}", min, max, value));
+        }
+        self
+    }
+
+    /// 检查可选字段是否存在
+    pub fn check_required<V>(&mut self, field: &str, value: &Option<V>) -> &mut Self {
+        if value.is_none() {
+            self.add(field, "required", "缺少必填字段");
+        }
+        self
+    }
+
+    /// 合并嵌套对象的错误，字段名加上 `prefix.` 前缀
+    pub fn merge_nested(&mut self, prefix: &str, nested: ValidationErrors) -> &mut Self {
+        for mut error in nested.errors {
+            error.field = if error.field.is_empty() {
+                prefix.to_string()
+            } else {
+                format!("{}.{}", prefix, error.field)
+            };
+            self.errors.push(error);
+        }
+        self
+    }
+
+    /// 是否没有错误
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 所有字段错误
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// 没有错误时返回 `Ok(())`
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+
+    /// 构建 `422 Unprocessable Entity` 响应
+    pub fn to_response(&self) -> Response<Full<Bytes>> {
+        error_response(StatusCode::UNPROCESSABLE_ENTITY, "validation_failed", &self.errors)
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<String> = self.errors.iter()
+            .map(|e| if e.field.is_empty() { e.message.clone() } else { format!("{}: {}", e.field, e.message) })
+            .collect();
+        write!(f, "请求校验失败: {}", fields.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// 可校验的请求类型
+pub trait Validate {
+    /// 检查取值是否合法，返回全部字段错误而不是遇到第一个就停止
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// 把请求体解析为 `T` 并校验
+///
+/// - JSON 语法错误或请求体不完整时返回 400
+/// - 缺少字段、类型不匹配以及 [`Validate`] 失败时返回 422，并列出字段级错误
+pub(crate) fn parse_and_validate<T>(body: &[u8]) -> Result<T, Response<Full<Bytes>>>
+where
+    T: serde::de::DeserializeOwned + Validate,
+{
+    let value: T = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(e) if e.is_data() => {
+            let mut errors = ValidationErrors::new();
+            let (field, code) = match missing_field_name(&e) {
+                Some(field) => (field, "required"),
+                None => (String::new(), "type"),
+            };
+            errors.add(field, code, e.to_string());
+            return Err(errors.to_response());
+        }
+        Err(e) => {
+            let error = FieldError { field: String::new(), code: "syntax".to_string(), message: e.to_string() };
+            return Err(error_response(StatusCode::BAD_REQUEST, "invalid_json", std::slice::from_ref(&error)));
+        }
+    };
+
+    match value.validate() {
+        Ok(()) => Ok(value),
+        Err(errors) => {
+            crate::utils::logger::debug!("🚫 [Validation] {}", errors);
+            Err(errors.to_response())
+        }
+    }
+}
+
+/// 从 serde 的 "missing field `xxx`" 错误中提取字段名
+fn missing_field_name(error: &serde_json::Error) -> Option<String> {
+    let message = error.to_string();
+    let rest = message.strip_prefix("missing field `")?;
+    rest.split('`').next().map(|field| field.to_string())
+}
+
+fn error_response(status: StatusCode, error: &str, fields: &[FieldError]) -> Response<Full<Bytes>> {
+    let body = serde_json::json!({
+        "error": error,
+        "code": status.as_u16(),
+        "fields": fields,
+    });
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .unwrap_or_else(|_| Response::new(Full::new(Bytes::new())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[derive(serde::Deserialize)]
+    struct Signup {
+        username: String,
+        age: u32,
+    }
+
+    impl Validate for Signup {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::new();
+            errors.check_length("username", &self.username, 2, 20);
+            errors.check_range("age", self.age, 13, 150);
+            errors.into_result()
+        }
+    }
+
+    async fn body_json(response: Response<Full<Bytes>>) -> serde_json::Value {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_parse_and_validate() {
+        let ok = parse_and_validate::<Signup>(br#"{"username":"alice","age":30}"#).ok().unwrap();
+        assert_eq!(ok.username, "alice");
+
+        // 所有字段错误一起返回
+        let response = parse_and_validate::<Signup>(br#"{"username":"a","age":5}"#).err().unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        let fields: Vec<&str> = body["fields"].as_array().unwrap().iter().map(|f| f["field"].as_str().unwrap()).collect();
+        assert_eq!(fields, vec!["username", "age"]);
+
+        let response = parse_and_validate::<Signup>(br#"{"age":30}"#).err().unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["fields"][0]["field"], "username");
+        assert_eq!(body["fields"][0]["code"], "required");
+
+        let response = parse_and_validate::<Signup>(b"{not json").err().unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_merge_nested() {
+        let mut nested = ValidationErrors::new();
+        nested.add("city", "required", "缺少必填字段");
+        let mut errors = ValidationErrors::new();
+        errors.merge_nested("address", nested);
+        assert_eq!(errors.errors()[0].field, "address.city");
+    }
+}
@@ -35,6 +35,78 @@ impl IpInfo {
     }
 }
 
+/// 真实 IP 解析配置
+///
+/// 按顺序检查可信头部；只有直连对端在可信代理列表中时才读取这些头部，
+/// 否则直接使用连接地址，避免客户端伪造代理头部。未配置可信代理时不信任任何对端
+#[derive(Debug, Clone)]
+pub struct RealIpConfig {
+    /// 按优先级排列的头部名（小写）
+    headers: Vec<String>,
+    /// 可信代理地址（为空时不信任任何对端）
+    trusted_proxies: Vec<IpAddr>,
+    /// X-Forwarded-For 中从右往左取第几个地址（0 表示不读取 X-Forwarded-For）
+    forwarded_for_hops: usize,
+}
+
+impl Default for RealIpConfig {
+    fn default() -> Self {
+        Self {
+            headers: vec![
+                "cf-connecting-ip".to_string(),
+                "x-forwarded-for".to_string(),
+                "x-real-ip".to_string(),
+                "true-client-ip".to_string(),
+            ],
+            trusted_proxies: Vec::new(),
+            forwarded_for_hops: 1,
+        }
+    }
+}
+
+impl RealIpConfig {
+    /// 创建默认配置（CF-Connecting-IP、X-Forwarded-For、X-Real-IP、True-Client-IP）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置按优先级排列的头部列表（替换默认列表）
+    pub fn headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.headers = headers.into_iter().map(|h| h.as_ref().to_ascii_lowercase()).collect();
+        self
+    }
+
+    /// 设置可信代理地址，来自其他对端的代理头部会被忽略
+    ///
+    /// 列表为空（默认）时不信任任何对端，始终使用连接地址
+    pub fn trusted_proxies<I>(mut self, proxies: I) -> Self
+    where
+        I: IntoIterator<Item = IpAddr>,
+    {
+        self.trusted_proxies = proxies.into_iter().collect();
+        self
+    }
+
+    /// 设置可信代理层数，X-Forwarded-For 取从右往左第 N 个地址（默认 1）
+    ///
+    /// 每层代理都会在末尾追加它看到的对端地址，因此 N 层可信代理时，
+    /// 从右数第 N 个才是可信代理记录的客户端地址，更左侧的内容可能被客户端伪造。
+    /// 条目数少于 N 时改用连接地址；为 0 时不读取 X-Forwarded-For
+    pub fn forwarded_for_hops(mut self, hops: usize) -> Self {
+        self.forwarded_for_hops = hops;
+        self
+    }
+
+    /// 对端是否为可信代理
+    pub fn is_trusted_peer(&self, peer: Option<IpAddr>) -> bool {
+        peer.is_some_and(|ip| self.trusted_proxies.contains(&ip))
+    }
+}
+
 /// IP 提取器
 /// 
 /// 负责从各种代理头部和协议中提取真实的客户端 IP 地址
@@ -323,6 +395,57 @@ impl IpExtractor {
         }
     }
     
+    /// 按配置解析客户端真实 IP
+    ///
+    /// 对端不可信或所有配置的头部都无法解析时，返回连接地址
+    pub fn resolve_client_ip(
+        headers: &hyper::HeaderMap,
+        remote_addr: Option<SocketAddr>,
+        config: &RealIpConfig,
+    ) -> Option<IpAddr> {
+        let peer = remote_addr.map(|addr| addr.ip());
+        if !config.is_trusted_peer(peer) {
+            return peer;
+        }
+
+        for header_name in &config.headers {
+            // 同名头部可能出现多次，按顺序合并
+            let values: Vec<&str> = headers.get_all(header_name.as_str())
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .collect();
+            if values.is_empty() {
+                continue;
+            }
+
+            let ip = match header_name.as_str() {
+                "x-forwarded-for" if config.forwarded_for_hops == 0 => continue,
+                "x-forwarded-for" => match Self::select_forwarded_for(&values.join(","), config.forwarded_for_hops) {
+                    Some(ip) => Some(ip),
+                    // 条目不足说明链路与配置的代理层数不符，不采信其他头部
+                    None => return peer,
+                },
+                "forwarded" => Self::parse_forwarded_header(values[0]).and_then(|ip| ip.parse().ok()),
+                _ => values[0].trim().parse().ok(),
+            };
+            if let Some(ip) = ip {
+                return Some(ip);
+            }
+        }
+
+        peer
+    }
+
+    /// 从 X-Forwarded-For 中选取从右往左第 `hops` 个地址，条目不足时返回 `None`
+    fn select_forwarded_for(value: &str, hops: usize) -> Option<IpAddr> {
+        let entries: Vec<&str> = value.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect();
+        let index = entries.len().checked_sub(hops)?;
+        entries[index].parse().ok()
+    }
+
     /// 解析 HAProxy Proxy Protocol v2
     /// 
     /// # 参数
@@ -629,6 +752,53 @@ mod tests {
         assert!(invalid_ip.is_private());
     }
     
+    #[test]
+    fn test_resolve_client_ip_with_config() {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.1.1.1, 203.0.113.1, 10.0.0.2".parse().unwrap());
+        headers.insert("x-real-ip", "198.51.100.7".parse().unwrap());
+        let proxy: SocketAddr = "10.0.0.1:443".parse().unwrap();
+
+        let trusted = || RealIpConfig::new().trusted_proxies([proxy.ip()]);
+
+        // 未配置可信代理：不信任任何对端
+        let config = RealIpConfig::new();
+        assert_eq!(IpExtractor::resolve_client_ip(&headers, Some(proxy), &config), Some(proxy.ip()));
+
+        // 默认一层可信代理：取最右侧
+        let config = trusted();
+        assert_eq!(IpExtractor::resolve_client_ip(&headers, Some(proxy), &config), Some("10.0.0.2".parse().unwrap()));
+
+        // 两层可信代理：从右往左第 2 个
+        let config = trusted().forwarded_for_hops(2);
+        assert_eq!(IpExtractor::resolve_client_ip(&headers, Some(proxy), &config), Some("203.0.113.1".parse().unwrap()));
+
+        // 层数超过条目数时使用连接地址
+        let config = trusted().forwarded_for_hops(5);
+        assert_eq!(IpExtractor::resolve_client_ip(&headers, Some(proxy), &config), Some(proxy.ip()));
+
+        // 为 0 时不读取 X-Forwarded-For
+        let config = trusted().forwarded_for_hops(0);
+        assert_eq!(IpExtractor::resolve_client_ip(&headers, Some(proxy), &config), Some("198.51.100.7".parse().unwrap()));
+
+        // 自定义优先级
+        let config = trusted().headers(["X-Real-IP", "X-Forwarded-For"]);
+        assert_eq!(IpExtractor::resolve_client_ip(&headers, Some(proxy), &config), Some("198.51.100.7".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_resolve_client_ip_ignores_untrusted_peer() {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.1.1.1".parse().unwrap());
+        let config = RealIpConfig::new().trusted_proxies(["10.0.0.1".parse().unwrap()]);
+
+        let trusted: SocketAddr = "10.0.0.1:443".parse().unwrap();
+        assert_eq!(IpExtractor::resolve_client_ip(&headers, Some(trusted), &config), Some("1.1.1.1".parse().unwrap()));
+
+        let untrusted: SocketAddr = "203.0.113.9:443".parse().unwrap();
+        assert_eq!(IpExtractor::resolve_client_ip(&headers, Some(untrusted), &config), Some(untrusted.ip()));
+    }
+
     #[test]
     fn test_extract_real_ip_info() {
         let mut headers = HashMap::new();
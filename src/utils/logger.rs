@@ -11,7 +11,81 @@ use std::sync::Arc;
 use chrono::Local;
 
 // 重新导出 rat_logger 的日志宏
-pub use rat_logger::{error, warn, info, debug, trace, emergency, startup_log, flush_logs};
+pub use rat_logger::{emergency, startup_log, flush_logs};
+// 级别日志宏在日志器未初始化时静默跳过，见 [`logger_ready`]
+pub use crate::{
+    __rat_engine_error as error,
+    __rat_engine_warn as warn,
+    __rat_engine_info as info,
+    __rat_engine_debug as debug,
+    __rat_engine_trace as trace,
+};
+
+#[doc(hidden)]
+pub use rat_logger as __rat_logger;
+
+/// 日志器是否已就绪（初始化后不会再变回未初始化）
+static LOGGER_READY: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// 日志器是否已初始化
+///
+/// 日志宏在输出前检查此函数：只读取一个原子标志，未初始化时直接跳过，不会触及 rat_logger 的全局锁，
+/// 因此嵌入引擎的程序即使从未初始化日志也不会在第一次输出日志时出错。
+/// 标志由 [`Logger::init`] 和引擎构建过程设置；绕过两者直接初始化 rat_logger 时需调用 [`mark_logger_ready`]
+pub fn logger_ready() -> bool {
+    LOGGER_READY.load(std::sync::atomic::Ordering::Acquire)
+}
+
+/// 标记日志器已就绪，之后引擎的日志宏开始输出
+///
+/// 仅在 rat_logger 的全局日志器确实已初始化时生效，返回是否已就绪
+pub fn mark_logger_ready() -> bool {
+    let ready = is_logger_initialized();
+    if ready {
+        LOGGER_READY.store(true, std::sync::atomic::Ordering::Release);
+    }
+    ready
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __rat_engine_log {
+    ($level:ident, $($arg:tt)+) => {
+        if $crate::utils::logger::logger_ready() {
+            $crate::utils::logger::__rat_logger::$level!($($arg)+);
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __rat_engine_error {
+    ($($arg:tt)+) => { $crate::__rat_engine_log!(error, $($arg)+) };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __rat_engine_warn {
+    ($($arg:tt)+) => { $crate::__rat_engine_log!(warn, $($arg)+) };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __rat_engine_info {
+    ($($arg:tt)+) => { $crate::__rat_engine_log!(info, $($arg)+) };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __rat_engine_debug {
+    ($($arg:tt)+) => { $crate::__rat_engine_log!(debug, $($arg)+) };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __rat_engine_trace {
+    ($($arg:tt)+) => { $crate::__rat_engine_log!(trace, $($arg)+) };
+}
 
 /// 日志级别映射
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -102,6 +176,21 @@ impl LogConfig {
         }
     }
     
+    /// 创建最小化的终端日志配置
+    ///
+    /// 只输出警告和错误，不使用颜色和 emoji；引擎在调用方没有初始化日志时用它兜底
+    pub fn minimal() -> Self {
+        LogConfig {
+            enabled: true,
+            level: LogLevel::Warn,
+            output: LogOutput::Terminal,
+            use_colors: false,
+            use_emoji: false,
+            show_timestamp: true,
+            show_module: false,
+        }
+    }
+    
     /// 创建文件日志配置
     pub fn file<P: Into<PathBuf>>(log_dir: P) -> Self {
         LogConfig {
@@ -286,7 +375,10 @@ impl Logger {
             }
         }
 
-        match builder.init() {
+        let result = builder.init();
+        // 无论本次是否成功，全局日志器已存在时日志宏即可输出
+        mark_logger_ready();
+        match result {
             Ok(_) => Ok(()),
             Err(e) => {
                 // 如果已经初始化过了，这是正常的
@@ -304,7 +396,11 @@ impl Logger {
 
 /// 检查日志器是否已初始化（内部使用）
 pub(crate) fn is_logger_initialized() -> bool {
-    rat_logger::core::LOGGER.lock().unwrap().is_some()
+    // 其他线程在持锁时 panic 不影响判断
+    match rat_logger::core::LOGGER.lock() {
+        Ok(logger) => logger.is_some(),
+        Err(poisoned) => poisoned.into_inner().is_some(),
+    }
 }
 
 /// 时间格式化工具方法
@@ -321,6 +417,97 @@ pub fn format_duration(duration: std::time::Duration) -> String {
     }
 }
 
+/// 按指定级别输出一条日志，`None` 表示不输出
+///
+/// 用于级别可配置的日志（如按路由设置的访问日志）
+pub fn log_at(level: Option<LogLevel>, message: std::fmt::Arguments<'_>) {
+    match level {
+        Some(LogLevel::Error) => error!("{}", message),
+        Some(LogLevel::Warn) => warn!("{}", message),
+        Some(LogLevel::Info) => info!("{}", message),
+        Some(LogLevel::Debug) => debug!("{}", message),
+        Some(LogLevel::Trace) => trace!("{}", message),
+        None => {}
+    }
+}
+
+/// 默认需要脱敏的请求/响应头
+pub const DEFAULT_REDACTED_HEADERS: &[&str] = &["authorization", "cookie", "set-cookie", "proxy-authorization"];
+
+/// 脱敏后的占位值
+pub const REDACTED_VALUE: &str = "***";
+
+/// 全局脱敏头部列表（小写）
+static REDACTED_HEADERS: std::sync::OnceLock<std::sync::RwLock<Vec<String>>> = std::sync::OnceLock::new();
+
+fn redacted_header_list() -> &'static std::sync::RwLock<Vec<String>> {
+    REDACTED_HEADERS.get_or_init(|| {
+        std::sync::RwLock::new(DEFAULT_REDACTED_HEADERS.iter().map(|h| h.to_string()).collect())
+    })
+}
+
+/// 设置日志中需要脱敏的头部列表（替换默认列表，大小写不敏感）
+pub fn set_redacted_headers<I, S>(headers: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let list: Vec<String> = headers.into_iter()
+        .map(|h| h.as_ref().trim().to_ascii_lowercase())
+        .filter(|h| !h.is_empty())
+        .collect();
+    if let Ok(mut guard) = redacted_header_list().write() {
+        *guard = list;
+    }
+}
+
+/// 获取当前的脱敏头部列表
+pub fn redacted_headers() -> Vec<String> {
+    redacted_header_list().read().map(|guard| guard.clone()).unwrap_or_default()
+}
+
+/// 检查头部是否需要脱敏
+pub fn is_redacted_header(name: &str) -> bool {
+    redacted_header_list().read()
+        .map(|guard| guard.iter().any(|h| h.eq_ignore_ascii_case(name)))
+        .unwrap_or(true)
+}
+
+/// 返回用于日志输出的头部值（敏感头部替换为 `***`）
+pub fn redact_header_value<'a>(name: &str, value: &'a str) -> &'a str {
+    if is_redacted_header(name) {
+        REDACTED_VALUE
+    } else {
+        value
+    }
+}
+
+/// 用于日志输出的脱敏头部包装
+///
+/// ```ignore
+/// debug!("请求头: {:?}", redact_headers(req.headers()));
+/// ```
+pub struct RedactedHeaders<'a>(&'a hyper::HeaderMap);
+
+/// 包装 HeaderMap，使其在日志中输出时自动脱敏
+pub fn redact_headers(headers: &hyper::HeaderMap) -> RedactedHeaders<'_> {
+    RedactedHeaders(headers)
+}
+
+impl std::fmt::Debug for RedactedHeaders<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        for (name, value) in self.0.iter() {
+            if is_redacted_header(name.as_str()) {
+                map.entry(&name.as_str(), &REDACTED_VALUE);
+            } else {
+                map.entry(&name.as_str(), value);
+            }
+        }
+        map.finish()
+    }
+}
+
 /// Python 模块专用的日志初始化函数（内部使用）
 /// 确保使用正确的 RAT Engine 格式和配置
 /// 注意：调用者必须显式调用此函数才能启用Python模块的日志
@@ -352,6 +539,32 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_header_redaction() {
+        assert!(is_redacted_header("Authorization"));
+        assert_eq!(redact_header_value("set-cookie", "sid=1"), REDACTED_VALUE);
+        assert_eq!(redact_header_value("content-type", "text/plain"), "text/plain");
+
+        set_redacted_headers(["X-Api-Key"]);
+        assert!(is_redacted_header("x-api-key"));
+        assert!(!is_redacted_header("authorization"));
+
+        set_redacted_headers(DEFAULT_REDACTED_HEADERS.iter().copied());
+    }
+    
+    #[test]
+    fn test_macros_safe_without_logger() {
+        // 其他测试可能已经初始化过日志：无论哪种情况宏都不能 panic
+        info!("日志器可能尚未初始化: {}", 1);
+        if logger_ready() {
+            assert!(is_logger_initialized());
+        }
+
+        let minimal = LogConfig::minimal();
+        assert_eq!(minimal.level, LogLevel::Warn);
+        assert!(!minimal.use_colors && !minimal.use_emoji);
+    }
+    
     #[test]
     fn test_log_levels() {
         let _ = Logger::init_default();
@@ -61,4 +61,1585 @@ fn test_constants() {
     assert_eq!(rat_engine::MAX_WORKERS, 1024);
     
     println!("✅ Constants validated");
-}
\ No newline at end of file
+}
+/// 构造测试用的 HttpRequest
+fn make_http_request(method: rat_engine::Method, uri: &str, headers: &[(&str, &str)]) -> rat_engine::server::http_request::HttpRequest {
+    let mut header_map = rat_engine::HeaderMap::new();
+    for (name, value) in headers {
+        header_map.insert(
+            hyper::header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+    }
+
+    rat_engine::server::http_request::HttpRequest {
+        method,
+        uri: uri.parse().unwrap(),
+        version: rat_engine::Version::HTTP_11,
+        headers: header_map,
+        body: rat_engine::Bytes::new(),
+        remote_addr: Some("127.0.0.1:12345".parse().unwrap()),
+        source: rat_engine::server::http_request::RequestSource::Http1,
+        path_params: std::collections::HashMap::new(),
+        python_handler_name: None,
+        tls_info: None,
+        state: Default::default(),
+        real_ip: None,
+        client_disconnect: Default::default(),
+        trailers: None,
+    }
+}
+
+#[tokio::test]
+async fn test_missing_host_and_host_routes() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, BodyExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("default")))) })
+    });
+    router.add_host_route("api.example.com", Method::GET, "/", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("api")))) })
+    });
+
+    // 缺少 Host 头部的 HTTP/1.1 请求返回 400
+    let resp = router.handle_http(make_http_request(Method::GET, "/", &[])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // 主机路由按 Host 匹配（忽略端口和大小写）
+    let resp = router.handle_http(make_http_request(Method::GET, "/", &[("host", "API.example.com:8080")])).await.unwrap();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"api");
+
+    // 其他主机回退到普通路由
+    let resp = router.handle_http(make_http_request(Method::GET, "/", &[("host", "www.example.com")])).await.unwrap();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"default");
+
+    // 关闭校验后允许缺少 Host
+    router.require_host(false);
+    let resp = router.handle_http(make_http_request(Method::GET, "/", &[])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_virtual_host_routing() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, BodyExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("default")))) })
+    });
+    router.for_host("*.example.com").add_route(Method::GET, "/", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("wildcard")))) })
+    });
+    router.for_host("api.example.com").add_route(Method::GET, "/users", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("users")))) })
+    });
+
+    // 通配符匹配子域名
+    let resp = router.handle_http(make_http_request(Method::GET, "/", &[("host", "tenant.example.com")])).await.unwrap();
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"wildcard");
+
+    // 精确匹配优先于通配符
+    let resp = router.handle_http(make_http_request(Method::GET, "/users", &[("host", "api.example.com")])).await.unwrap();
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"users");
+
+    // 虚拟主机中没有该路径的路由时回退到默认路由
+    let resp = router.handle_http(make_http_request(Method::GET, "/", &[("host", "api.example.com")])).await.unwrap();
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"default");
+    let resp = router.handle_http(make_http_request(Method::GET, "/missing", &[("host", "api.example.com")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // 通配符不匹配顶级域名本身，回退到默认路由
+    let resp = router.handle_http(make_http_request(Method::GET, "/", &[("host", "example.com")])).await.unwrap();
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"default");
+}
+
+#[tokio::test]
+async fn test_virtual_host_shares_parent_middleware() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+    use rat_engine::server::cors::CorsConfig;
+
+    let mut router = Router::new();
+    router.for_host("api.example.com").add_route(Method::GET, "/users", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("users")))) })
+    });
+    router.enable_cors(CorsConfig::new().enable().allowed_origins(vec!["https://app.example.com"]));
+
+    // 顶层路由器的 CORS 配置同样作用于虚拟主机路由
+    let resp = router.handle_http(make_http_request(
+        Method::GET,
+        "/users",
+        &[("host", "api.example.com"), ("origin", "https://app.example.com")],
+    )).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "https://app.example.com");
+
+    // 预检请求由顶层路由器处理
+    let resp = router.handle_http(make_http_request(
+        Method::OPTIONS,
+        "/users",
+        &[
+            ("host", "api.example.com"),
+            ("origin", "https://app.example.com"),
+            ("access-control-request-method", "GET"),
+        ],
+    )).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "https://app.example.com");
+}
+
+#[tokio::test]
+async fn test_fallback_route() {
+    use rat_engine::{Method, Response, Full, Bytes, BodyExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/api/users", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("users")))) })
+    });
+    router.add_fallback(|req| {
+        Box::pin(async move {
+            let body = format!("fallback:{}", req.param("path").unwrap_or_default());
+            Ok(Response::new(Full::new(Bytes::from(body))))
+        })
+    });
+
+    // 具体路由优先
+    let resp = router.handle_http(make_http_request(Method::GET, "/api/users", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"users");
+
+    // 任何未匹配的路径和方法都进入兜底路由
+    let resp = router.handle_http(make_http_request(Method::POST, "/some/deep/path", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"fallback:some/deep/path");
+}
+
+#[tokio::test]
+async fn test_info_endpoint() {
+    use rat_engine::{Method, StatusCode, BodyExt};
+
+    let mut router = Router::new();
+    router.enable_info_endpoint("/_info");
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/_info", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    assert!(info["features"].is_array());
+    assert!(info["protocols"].is_array());
+
+    // 非 GET/HEAD 请求不会命中信息端点
+    let resp = router.handle_http(make_http_request(Method::POST, "/_info", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint() {
+    use rat_engine::{Method, StatusCode, BodyExt};
+
+    let mut router = Router::new();
+    router.enable_metrics_endpoint("/_metrics");
+    router.grpc_metrics().unwrap().record(
+        "/svc.Echo/Say",
+        std::time::Duration::from_millis(2),
+        10,
+        20,
+        rat_engine::server::grpc_types::GrpcStatusCode::Ok,
+    );
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/_metrics", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let metrics: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let say = &metrics["grpc"]["/svc.Echo/Say"];
+    assert_eq!(say["calls"], 1);
+    assert_eq!(say["bytes_received"], 10);
+    assert_eq!(say["bytes_sent"], 20);
+    assert_eq!(say["status_codes"]["Ok"], 1);
+}
+
+#[tokio::test]
+async fn test_max_path_segments() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/files/<path:file>", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("file")))) })
+    });
+    router.set_max_path_segments(4);
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/files/a/b/c", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // 超过段数限制的请求在路由匹配前被拒绝
+    let resp = router.handle_http(make_http_request(Method::GET, "/files/a/b/c/d", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let deep_path = format!("/files{}", "/x".repeat(10_000));
+    router.set_max_path_segments(rat_engine::server::router::DEFAULT_MAX_PATH_SEGMENTS);
+    let resp = router.handle_http(make_http_request(Method::GET, &deep_path, &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_max_query_params() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/search", |req| {
+        Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(req.query_params().len().to_string())))) })
+    });
+    router.set_max_query_params(3);
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/search?a=1&b=2&c=3", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/search?a=1&b=2&c=3&d=4", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let flood = format!("/search?{}", (0..50_000).map(|i| format!("k{}=v", i)).collect::<Vec<_>>().join("&"));
+    router.set_max_query_params(rat_engine::server::router::DEFAULT_MAX_QUERY_PARAMS);
+    let resp = router.handle_http(make_http_request(Method::GET, &flood, &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_encoded_slash_in_path_params() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, BodyExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/files/<name>", |req| {
+        let name = req.param("name").unwrap_or_default().to_string();
+        Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(name)))) })
+    });
+
+    // 默认：%2F 不拆分段，参数保持原样
+    let resp = router.handle_http(make_http_request(Method::GET, "/files/a%2Fb", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.into_body().collect().await.unwrap().to_bytes(), Bytes::from("a%2Fb"));
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/files/a/b", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // 启用解码后参数值被解码，但匹配仍基于原始路径
+    router.decode_path_params(true);
+    let resp = router.handle_http(make_http_request(Method::GET, "/files/a%2Fb", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.into_body().collect().await.unwrap().to_bytes(), Bytes::from("a/b"));
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/files/my%20doc%3Fv%3D1", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.into_body().collect().await.unwrap().to_bytes(), Bytes::from("my doc?v=1"));
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/files/a/b", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_body_transform_by_content_type() {
+    use rat_engine::{Method, Response, Full, Bytes, BodyExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/page", |_req| {
+        Box::pin(async move {
+            Ok(Response::builder()
+                .header("content-type", "text/html; charset=utf-8")
+                .body(Full::new(Bytes::from("<body></body>")))
+                .unwrap())
+        })
+    });
+    router.add_route(Method::GET, "/text", |_req| {
+        Box::pin(async move {
+            Ok(Response::builder()
+                .header("content-type", "text/plain")
+                .body(Full::new(Bytes::from("<body></body>")))
+                .unwrap())
+        })
+    });
+    router.add_body_transform(&["text/html"], |_: &str, body: &[u8]| {
+        String::from_utf8_lossy(body).replace("</body>", "<script src=\"/a.js\"></script></body>").into_bytes()
+    });
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/page", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(
+        resp.into_body().collect().await.unwrap().to_bytes(),
+        Bytes::from("<body><script src=\"/a.js\"></script></body>")
+    );
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/text", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.into_body().collect().await.unwrap().to_bytes(), Bytes::from("<body></body>"));
+}
+
+#[tokio::test]
+async fn test_real_ip_from_trusted_headers() {
+    use rat_engine::{Method, Response, Full, Bytes, BodyExt};
+    use rat_engine::utils::ip_extractor::RealIpConfig;
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/ip", |req| {
+        let ip = req.client_ip_with_validation(false).map(|ip| ip.to_string()).unwrap_or_default();
+        Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(ip)))) })
+    });
+
+    let headers = [("host", "localhost"), ("x-forwarded-for", "6.6.6.6, 203.0.113.5")];
+
+    // 直连对端是可信代理：取 X-Forwarded-For 从右数第 1 个
+    router.real_ip_config(RealIpConfig::new()
+        .trusted_proxies(["127.0.0.1".parse().unwrap()])
+        .forwarded_for_hops(1));
+    let resp = router.handle_http(make_http_request(Method::GET, "/ip", &headers)).await.unwrap();
+    assert_eq!(resp.into_body().collect().await.unwrap().to_bytes(), Bytes::from("203.0.113.5"));
+
+    // 对端不在可信列表中：忽略代理头部，使用连接地址
+    router.real_ip_config(RealIpConfig::new().trusted_proxies(["10.0.0.1".parse().unwrap()]));
+    let resp = router.handle_http(make_http_request(Method::GET, "/ip", &headers)).await.unwrap();
+    assert_eq!(resp.into_body().collect().await.unwrap().to_bytes(), Bytes::from("127.0.0.1"));
+}
+
+#[tokio::test]
+async fn test_engine_graceful_shutdown() {
+    use rat_engine::{Method, Response, Full, Bytes, RatEngine};
+    use std::sync::Arc;
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("ok")))) })
+    });
+
+    let engine = Arc::new(RatEngine::builder()
+        .worker_threads(1)
+        .shutdown_timeout(Duration::from_secs(1))
+        .router(router)
+        .build()
+        .unwrap());
+
+    let server = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.start("127.0.0.1".to_string(), 0).await }
+    });
+
+    let addr = loop {
+        if let Some(addr) = engine.local_addr() {
+            break addr;
+        }
+        sleep(Duration::from_millis(10)).await;
+    };
+
+    // shutdown 返回时接受循环已退出，start 随之返回
+    engine.shutdown().await.unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(5), server).await
+        .expect("start() 未在关闭后返回")
+        .unwrap();
+    assert!(result.is_ok());
+    assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+}
+
+#[test]
+fn test_access_log_level_rules() {
+    use rat_engine::utils::logger::LogLevel;
+
+    let mut router = Router::new();
+    router
+        .set_access_log_level("/health", None)
+        .set_access_log_level("/static/*", Some(LogLevel::Debug));
+
+    assert_eq!(router.access_log_level("/health"), None);
+    assert_eq!(router.access_log_level("/static/app.js"), Some(LogLevel::Debug));
+    // 精确匹配不覆盖子路径，未配置的路径保持 info
+    assert_eq!(router.access_log_level("/health/db"), Some(LogLevel::Info));
+    assert_eq!(router.access_log_level("/api/users"), Some(LogLevel::Info));
+
+    // 重复设置同一路径会更新原有规则
+    router.set_access_log_level("/health", Some(LogLevel::Debug));
+    assert_eq!(router.access_log_level("/health"), Some(LogLevel::Debug));
+}
+
+#[tokio::test]
+async fn test_chunked_body_over_limit_rejected() {
+    use rat_engine::{Method, Response, Full, Bytes};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::POST, "/upload", |req| {
+        Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(req.body.len().to_string())))) })
+    });
+    router.set_max_request_body_size(16);
+    let adapter = Arc::new(rat_engine::server::HyperAdapter::new(Arc::new(router)));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, remote_addr)) = listener.accept().await {
+            let adapter = adapter.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |req| {
+                    let adapter = adapter.clone();
+                    async move { adapter.handle_request(req, Some(remote_addr)).await }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+
+    async fn post_chunked(addr: SocketAddr, chunks: &[&str]) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut request = String::from(
+            "POST /upload HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+        );
+        for chunk in chunks {
+            request.push_str(&format!("{:x}\r\n{}\r\n", chunk.len(), chunk));
+        }
+        request.push_str("0\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        // 只读取状态行：服务器拒绝后可能不再读取剩余请求体
+        let mut response = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !response.windows(2).any(|w| w == b"\r\n") {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => response.extend_from_slice(&buf[..n]),
+            }
+        }
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    // 按解码后的字节计数：10 字节的请求体未超限（含分块帧开销时超过 16 字节）
+    let response = post_chunked(addr, &["hello", "world"]).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+    // 超过限制的分块请求体在读取过程中被拒绝
+    let response = post_chunked(addr, &["aaaaaaaa", "bbbbbbbb", "cccccccc", "dddddddd"]).await;
+    assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+}
+
+#[tokio::test]
+async fn test_chunked_request_trailers() {
+    use rat_engine::{Method, Response, Full, Bytes};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::POST, "/upload", |req| {
+        Box::pin(async move {
+            let trailers = req.trailers().unwrap_or_default();
+            let checksum = trailers.get("x-checksum").and_then(|v| v.to_str().ok()).unwrap_or("-");
+            let body = format!("{}:{}:{}", req.body.len(), checksum, trailers.len());
+            Ok(Response::new(Full::new(Bytes::from(body))))
+        })
+    });
+    let adapter = Arc::new(rat_engine::server::HyperAdapter::new(Arc::new(router)));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, remote_addr)) = listener.accept().await {
+            let adapter = adapter.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |req| {
+                    let adapter = adapter.clone();
+                    async move { adapter.handle_request(req, Some(remote_addr)).await }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+
+    async fn post_with_trailers(addr: SocketAddr, advertised: Option<&str>, trailers: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut request = String::from(
+            "POST /upload HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n",
+        );
+        if let Some(advertised) = advertised {
+            request.push_str(&format!("Trailer: {}\r\n", advertised));
+        }
+        request.push_str("\r\n5\r\nhello\r\n0\r\n");
+        request.push_str(trailers);
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = Vec::new();
+        let _ = stream.read_to_end(&mut response).await;
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    // 只保留 Trailer 头中声明过的字段
+    let response = post_with_trailers(addr, Some("X-Checksum"), "X-Checksum: abc123\r\nX-Other: 1\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.ends_with("5:abc123:1"), "{}", response);
+
+    // 未声明 Trailer 头时忽略全部 trailers
+    let response = post_with_trailers(addr, None, "X-Checksum: abc123\r\n").await;
+    assert!(response.ends_with("5:-:0"), "{}", response);
+
+    // 影响分帧的字段即使声明了也不会被采纳
+    let response = post_with_trailers(addr, Some("Content-Length"), "Content-Length: 99\r\n").await;
+    assert!(response.ends_with("5:-:0"), "{}", response);
+}
+
+#[tokio::test]
+async fn test_expect_continue_rejected_before_body() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::POST, "/upload", |req| {
+        Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(req.body.len().to_string())))) })
+    });
+    router.set_max_request_body_size(16);
+    router.with_before_body_check(|parts| {
+        if parts.headers.contains_key("authorization") {
+            return None;
+        }
+        let mut response = Response::new(Full::new(Bytes::from("Unauthorized")));
+        *response.status_mut() = StatusCode::UNAUTHORIZED;
+        Some(response)
+    });
+    let adapter = Arc::new(rat_engine::server::HyperAdapter::new(Arc::new(router)));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, remote_addr)) = listener.accept().await {
+            let adapter = adapter.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |req| {
+                    let adapter = adapter.clone();
+                    async move { adapter.handle_request(req, Some(remote_addr)).await }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+
+    // 只发送请求头并等待服务器回应，客户端不上传请求体
+    async fn send_head(addr: SocketAddr, extra: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 100\r\nExpect: 100-continue\r\n{}\r\n",
+            extra
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        // 服务器返回最终状态后关闭连接
+        let mut response = Vec::new();
+        let read = tokio::time::timeout(std::time::Duration::from_secs(5), stream.read_to_end(&mut response)).await;
+        assert!(read.is_ok(), "服务器仍在等待请求体");
+        String::from_utf8_lossy(&response).to_ascii_lowercase()
+    }
+
+    // 声明的请求体超过上限：直接 413，不发送 100 Continue
+    let response = send_head(addr, "Authorization: token\r\n").await;
+    assert!(response.starts_with("http/1.1 413"), "{}", response);
+    assert!(!response.contains("100 continue"), "{}", response);
+    assert!(response.contains("connection: close"), "{}", response);
+
+    // 读取请求体之前的认证检查同样先于 100 Continue
+    let response = send_head(addr, "").await;
+    assert!(response.starts_with("http/1.1 401"), "{}", response);
+    assert!(!response.contains("100 continue"), "{}", response);
+}
+
+#[tokio::test]
+async fn test_path_rewrite_before_routing() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, BodyExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/users/<id>", |req| {
+        Box::pin(async move {
+            let body = format!("{}?{}", req.path(), req.query().unwrap_or(""));
+            Ok(Response::new(Full::new(Bytes::from(body))))
+        })
+    });
+    router.with_path_rewrite(|path| path.strip_prefix("/app").map(str::to_string));
+
+    // 去掉挂载前缀后匹配路由，查询字符串保留
+    let resp = router.handle_http(make_http_request(Method::GET, "/app/users/7?full=1", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"/users/7?full=1");
+
+    // 返回 None 时保持原路径
+    let resp = router.handle_http(make_http_request(Method::GET, "/users/7", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_auto_methods() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+    use rat_engine::server::router::AutoMethodsConfig;
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/items/<id>", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("item")))) })
+    });
+    router.add_route(Method::DELETE, "/items/<id>", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("deleted")))) })
+    });
+    router.enable_auto_methods(AutoMethodsConfig::new());
+
+    // 未配置 CORS 时 OPTIONS 也返回 Allow 列表
+    let resp = router.handle_http(make_http_request(Method::OPTIONS, "/items/1", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(resp.headers()["allow"], "GET, DELETE, HEAD, OPTIONS");
+
+    // HEAD 自动使用 GET 处理器
+    let resp = router.handle_http(make_http_request(Method::HEAD, "/items/1", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // 路径存在但方法不匹配时返回 405
+    let resp = router.handle_http(make_http_request(Method::POST, "/items/1", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(resp.headers()["allow"], "GET, DELETE, HEAD, OPTIONS");
+
+    // 不存在的路径仍返回 404
+    let resp = router.handle_http(make_http_request(Method::POST, "/missing", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_default_security_headers() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, HeaderMap};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/framed", |_req| {
+        Box::pin(async {
+            let mut resp = Response::new(Full::new(Bytes::from("ok")));
+            resp.headers_mut().insert("x-frame-options", "SAMEORIGIN".parse().unwrap());
+            Ok(resp)
+        })
+    });
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-content-type-options", "nosniff".parse().unwrap());
+    headers.insert("x-frame-options", "DENY".parse().unwrap());
+    // with_hsts 先于 default_headers 调用也不会被替换
+    router.with_hsts(Duration::from_secs(31536000), true, false);
+    router.default_headers(headers);
+
+    // 处理器设置的头部不被覆盖
+    let resp = router.handle_http(make_http_request(Method::GET, "/framed", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.headers()["x-frame-options"], "SAMEORIGIN");
+    assert_eq!(resp.headers()["x-content-type-options"], "nosniff");
+    assert_eq!(resp.headers()["strict-transport-security"], "max-age=31536000; includeSubDomains");
+
+    // 框架生成的错误响应同样携带默认头部
+    let resp = router.handle_http(make_http_request(Method::GET, "/missing", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()["x-frame-options"], "DENY");
+}
+
+#[tokio::test]
+async fn test_duplicate_header_precedence() {
+    use rat_engine::{Method, Response, Full, Bytes, HeaderMap};
+    use rat_engine::server::cors::CorsConfig;
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/doc", |_req| {
+        Box::pin(async {
+            let resp = Response::new(Full::new(Bytes::from("<doc/>")));
+            let resp = rat_engine::response::set_header(resp, "content-type", "application/xml").unwrap();
+            let resp = rat_engine::response::set_header(resp, "access-control-allow-origin", "*").unwrap();
+            Ok(rat_engine::response::append_header(resp, "link", "</a.css>; rel=preload").unwrap())
+        })
+    });
+
+    let mut headers = HeaderMap::new();
+    headers.insert("content-type", "text/plain".parse().unwrap());
+    headers.insert("link", "</default.css>; rel=preload".parse().unwrap());
+    headers.insert("x-content-type-options", "nosniff".parse().unwrap());
+    router.default_headers(headers);
+    router.enable_cors(CorsConfig::new().enable().allowed_origins(vec!["https://app.example.com"]));
+
+    let resp = router.handle_http(make_http_request(
+        Method::GET,
+        "/doc",
+        &[("host", "localhost"), ("origin", "https://app.example.com")],
+    )).await.unwrap();
+
+    // 默认头部不覆盖处理器设置的头部，也不追加到同名头部上
+    assert_eq!(resp.headers()["content-type"], "application/xml");
+    assert_eq!(resp.headers().get_all("link").iter().count(), 1);
+    assert_eq!(resp.headers()["x-content-type-options"], "nosniff");
+    // CORS 中间件替换处理器设置的值
+    let origins: Vec<_> = resp.headers().get_all("access-control-allow-origin").iter().collect();
+    assert_eq!(origins, vec!["https://app.example.com"]);
+}
+
+#[tokio::test]
+async fn test_handler_panic_becomes_500() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/broken", |_req| {
+        Box::pin(async {
+            let resp = Response::builder()
+                .header("x-bad", "line\nbreak")
+                .body(Full::new(Bytes::new()))
+                .unwrap();
+            Ok(resp)
+        })
+    });
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/broken", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn test_early_hints_merged_into_final_response() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, HeaderMap};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/page", |req| {
+        Box::pin(async move {
+            let mut hints = HeaderMap::new();
+            hints.insert("link", "</app.css>; rel=preload; as=style".parse().unwrap());
+            assert!(req.early_hints(hints));
+            Ok(Response::new(Full::new(Bytes::from("<html></html>"))))
+        })
+    });
+
+    // 通过 Router 直接处理时无法发送 103，Link 头部合并到最终响应
+    let resp = router.handle_http(make_http_request(Method::GET, "/page", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["link"], "</app.css>; rel=preload; as=style");
+}
+
+#[tokio::test]
+async fn test_early_hints_only_for_http11_on_work_stealing_path() {
+    use rat_engine::{Method, Response, Full, Bytes, HeaderMap, RatEngine};
+    use rat_engine::engine::HttpPath;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/page", |req| {
+        Box::pin(async move {
+            let mut hints = HeaderMap::new();
+            hints.insert("link", "</app.css>; rel=preload; as=style".parse().unwrap());
+            req.early_hints(hints);
+            Ok(Response::new(Full::new(Bytes::from("page"))))
+        })
+    });
+
+    let engine = Arc::new(RatEngine::builder()
+        .worker_threads(1)
+        .http_path(HttpPath::WorkStealing)
+        .router(router)
+        .build()
+        .unwrap());
+    let server = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.start("127.0.0.1".to_string(), 0).await }
+    });
+    let addr = loop {
+        if let Some(addr) = engine.local_addr() {
+            break addr;
+        }
+        sleep(Duration::from_millis(10)).await;
+    };
+
+    async fn exchange(addr: std::net::SocketAddr, data: &[u8]) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream.write_all(data).await.unwrap();
+        let mut raw = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut raw)).await.unwrap().unwrap();
+        String::from_utf8_lossy(&raw).to_string()
+    }
+
+    let http11 = exchange(addr, b"GET /page HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n").await;
+    assert!(http11.starts_with("HTTP/1.1 103 Early Hints"), "{}", http11);
+
+    // HTTP/1.0 不发送 103，提示合并到最终响应
+    let http10 = exchange(addr, b"GET /page HTTP/1.0\r\nHost: x\r\n\r\n").await;
+    assert!(http10.starts_with("HTTP/1.0 200"), "{}", http10);
+    assert!(!http10.contains(" 103 "));
+    assert!(http10.contains("link: </app.css>; rel=preload; as=style"), "{}", http10);
+
+    engine.shutdown().await.unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_request_timeout_returns_504() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/slow", |_req| {
+        Box::pin(async {
+            sleep(Duration::from_millis(500)).await;
+            Ok(Response::new(Full::new(Bytes::from("late"))))
+        })
+    });
+    router.add_route(Method::GET, "/fast", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("ok")))) })
+    });
+    router.set_request_timeout(Some(Duration::from_millis(50)));
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/slow", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/fast", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_timeout_header_returns_504() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/slow", |_req| {
+        Box::pin(async {
+            sleep(Duration::from_millis(300)).await;
+            Ok(Response::new(Full::new(Bytes::from("late"))))
+        })
+    });
+    router.set_request_timeout(Some(Duration::from_secs(5)));
+    router.honor_timeout_header("X-Request-Timeout");
+
+    // 请求头期限更短时生效，超时返回 504
+    let resp = router.handle_http(make_http_request(Method::GET, "/slow", &[("host", "localhost"), ("x-request-timeout", "50m")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/slow", &[("host", "localhost"), ("x-request-timeout", "0.05")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
+    // 路由器默认期限更短时同样返回 504
+    router.set_request_timeout(Some(Duration::from_millis(50)));
+    let resp = router.handle_http(make_http_request(Method::GET, "/slow", &[("host", "localhost"), ("x-request-timeout", "10")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
+    // 无法解析的取值被忽略
+    router.set_request_timeout(None);
+    let resp = router.handle_http(make_http_request(Method::GET, "/slow", &[("host", "localhost"), ("x-request-timeout", "soon")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_client_disconnect_signal() {
+    use rat_engine::{Method, Response, Full, Bytes};
+    use rat_engine::engine::metrics::AtomicMetrics;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let aborted = Arc::new(AtomicBool::new(false));
+    let metrics = Arc::new(AtomicMetrics::new());
+    let mut router = Router::new();
+    router.set_metrics(metrics.clone());
+    {
+        let aborted = aborted.clone();
+        router.add_route(Method::GET, "/report", move |req| {
+            let aborted = aborted.clone();
+            Box::pin(async move {
+                // 耗时工作放在独立任务中，客户端断开后由信号终止
+                let signal = req.client_disconnect.clone();
+                tokio::spawn(async move {
+                    tokio::select! {
+                        _ = sleep(Duration::from_secs(5)) => {}
+                        _ = signal.disconnected() => aborted.store(true, Ordering::SeqCst),
+                    }
+                });
+                sleep(Duration::from_secs(5)).await;
+                Ok(Response::new(Full::new(Bytes::from("report"))))
+            })
+        });
+    }
+    router.add_route(Method::GET, "/fast", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("ok")))) })
+    });
+    let router = Arc::new(router);
+
+    let _ = router.handle_http(make_http_request(Method::GET, "/fast", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(metrics.client_disconnects(), 0);
+
+    // 连接关闭时处理请求的 future 被丢弃
+    let task = tokio::spawn({
+        let router = router.clone();
+        async move { router.handle_http(make_http_request(Method::GET, "/report", &[("host", "localhost")])).await }
+    });
+    sleep(Duration::from_millis(50)).await;
+    task.abort();
+    let _ = task.await;
+    sleep(Duration::from_millis(50)).await;
+
+    assert!(aborted.load(Ordering::SeqCst));
+    assert_eq!(metrics.client_disconnects(), 1);
+}
+
+#[tokio::test]
+async fn test_app_state_injection() {
+    use std::sync::Arc;
+    use rat_engine::{Method, Response, Full, Bytes, BodyExt};
+
+    struct Greeting(&'static str);
+
+    let mut router = Router::new();
+    router.with_state(Arc::new(Greeting("hello")));
+    router.add_route(Method::GET, "/greet", |req| {
+        Box::pin(async move {
+            let greeting = req.state::<Greeting>().map(|g| g.0).unwrap_or("missing");
+            Ok(Response::new(Full::new(Bytes::from(greeting))))
+        })
+    });
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/greet", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"hello");
+
+    // 请求上注入的状态优先于路由器注册的状态
+    let req = make_http_request(Method::GET, "/greet", &[("host", "localhost")]).with_state(Arc::new(Greeting("mock")));
+    let resp = router.handle_http(req).await.unwrap();
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"mock");
+}
+
+#[tokio::test]
+async fn test_ndjson_stream_route() {
+    use rat_engine::{Method, BodyExt};
+
+    #[derive(serde::Serialize)]
+    struct Row {
+        id: u32,
+    }
+
+    let mut router = Router::new();
+    router.add_ndjson_stream_route(Method::GET, "/rows", |_req, _params| {
+        futures_util::stream::iter((1..=3).map(|id| Ok::<_, String>(Row { id })))
+    });
+    router.add_ndjson_stream_route(Method::GET, "/broken", |_req, _params| {
+        futures_util::stream::iter(vec![Ok(Row { id: 1 }), Err("查询失败".to_string())])
+    });
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/rows", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.headers()["content-type"], "application/x-ndjson");
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n");
+
+    // 数据源出错时响应体中止
+    let resp = router.handle_http(make_http_request(Method::GET, "/broken", &[("host", "localhost")])).await.unwrap();
+    assert!(resp.into_body().collect().await.is_err());
+}
+
+#[tokio::test]
+async fn test_validated_route() {
+    use rat_engine::{Method, Response, Full, Bytes, BodyExt, StatusCode};
+    use rat_engine::server::{Validate, ValidationErrors};
+
+    #[derive(serde::Deserialize)]
+    struct ChatMessage {
+        message: String,
+    }
+
+    impl Validate for ChatMessage {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::new();
+            errors.check_length("message", &self.message, 1, 500);
+            errors.into_result()
+        }
+    }
+
+    let mut router = Router::new();
+    router.add_validated_route::<ChatMessage, _>(Method::POST, "/messages", |_req, body| {
+        Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(body.message)))) })
+    });
+
+    let send = |body: &'static str| {
+        let mut req = make_http_request(Method::POST, "/messages", &[("host", "localhost"), ("content-type", "application/json")]);
+        req.body = Bytes::from(body);
+        req
+    };
+
+    let resp = router.handle_http(send(r#"{"message":"hi"}"#)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(&resp.into_body().collect().await.unwrap().to_bytes()[..], b"hi");
+
+    let resp = router.handle_http(send(r#"{"message":""}"#)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = serde_json::from_slice(&resp.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(body["fields"][0]["field"], "message");
+    assert_eq!(body["fields"][0]["code"], "length");
+}
+
+#[tokio::test]
+async fn test_options_asterisk() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/a", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("a")))) })
+    });
+    router.add_route(Method::POST, "/b", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("b")))) })
+    });
+
+    let resp = router.handle_http(make_http_request(Method::OPTIONS, "*", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let allow = resp.headers()["allow"].to_str().unwrap().to_string();
+    assert!(allow.contains("GET") && allow.contains("POST") && allow.contains("OPTIONS"));
+    assert!(!allow.contains("DELETE"));
+
+    // 其他方法不允许使用 asterisk-form
+    let resp = router.handle_http(make_http_request(Method::GET, "*", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    router.set_asterisk_options(false);
+    let resp = router.handle_http(make_http_request(Method::OPTIONS, "*", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_fallible_route_error_mapping() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, BodyExt, RatError};
+
+    let mut router = Router::new();
+    router.add_fallible_route(Method::GET, "/orders/<id>", |req| async move {
+        let id = req.param_as_u64("id")
+            .ok_or_else(|| RatError::InvalidArgument("无效的订单号".to_string()))?;
+        if id == 0 {
+            return Err(RatError::RouteError("订单不存在".to_string()));
+        }
+        Ok((StatusCode::CREATED, format!("order-{}", id)))
+    });
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/orders/7", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"order-7");
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/orders/abc", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+
+    // 错误处理器在路由注册之后替换仍然生效
+    router.set_error_handler(|error: &RatError| {
+        let mut response = Response::new(Full::new(Bytes::from("custom")));
+        *response.status_mut() = error.status_code();
+        response
+    });
+    let resp = router.handle_http(make_http_request(Method::GET, "/orders/0", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"custom");
+}
+
+#[tokio::test]
+async fn test_default_favicon_and_robots() {
+    use rat_engine::{Method, StatusCode, BodyExt};
+
+    let mut router = Router::new();
+    router
+        .default_favicon(Vec::new())
+        .default_robots("User-agent: *\nDisallow: /admin\n");
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/favicon.ico", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/robots.txt", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/plain; charset=utf-8");
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"User-agent: *\nDisallow: /admin\n");
+
+    // 提供图标内容时直接返回
+    let mut router = Router::new();
+    router.default_favicon(&b"\x00\x00\x01\x00"[..]);
+    let resp = router.handle_http(make_http_request(Method::GET, "/favicon.ico", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "image/x-icon");
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"\x00\x00\x01\x00");
+}
+
+#[tokio::test]
+async fn test_route_cache_control_headers() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, CacheControl};
+
+    let mut router = Router::new();
+    router.add_route_with_cache_control(Method::GET, "/assets/app.3f9a.js", CacheControl::immutable_asset(), |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("console.log(1)")))) })
+    });
+    router.add_route_with_cache_control(Method::GET, "/missing", CacheControl::immutable_asset(), |_req| {
+        Box::pin(async {
+            let mut response = Response::new(Full::new(Bytes::new()));
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            Ok(response)
+        })
+    });
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/assets/app.3f9a.js", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.headers()["cache-control"], "public, max-age=31536000, immutable");
+    assert!(resp.headers().contains_key("expires"));
+
+    // 错误响应不会被客户端长期缓存
+    let resp = router.handle_http(make_http_request(Method::GET, "/missing", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert!(!resp.headers().contains_key("cache-control"));
+}
+
+#[tokio::test]
+async fn test_route_bandwidth_limit() {
+    use rat_engine::{Method, Response, Full, Bytes, BodyExt};
+
+    let mut router = Router::new();
+    router.add_route_with_rate(Method::GET, "/download", 4000, |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from(vec![7u8; 2000])))) })
+    });
+    router.add_route(Method::GET, "/fast", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from(vec![7u8; 2000])))) })
+    });
+
+    // 2000 字节按 4000 B/s 写出约需 0.5 秒
+    let started = std::time::Instant::now();
+    let resp = router.handle_http(make_http_request(Method::GET, "/download", &[("host", "localhost")])).await.unwrap();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body.len(), 2000);
+    assert!(started.elapsed() >= Duration::from_millis(300), "响应未被限速: {:?}", started.elapsed());
+
+    // 其他路由不受影响
+    let started = std::time::Instant::now();
+    let resp = router.handle_http(make_http_request(Method::GET, "/fast", &[("host", "localhost")])).await.unwrap();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body.len(), 2000);
+    assert!(started.elapsed() < Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn test_status_hooks() {
+    use rat_engine::{Method, Response, Full, Bytes, StatusCode, BodyExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/private", |_req| {
+        Box::pin(async {
+            let mut response = Response::new(Full::new(Bytes::new()));
+            *response.status_mut() = StatusCode::UNAUTHORIZED;
+            Ok(response)
+        })
+    });
+    router.add_route(Method::GET, "/ok", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("ok")))) })
+    });
+    router.on_status(StatusCode::UNAUTHORIZED, |resp| {
+        resp.headers_mut().insert("www-authenticate", "Bearer realm=\"api\"".parse().unwrap());
+    });
+    router.on_status(StatusCode::NOT_FOUND, |resp| {
+        *resp.body_mut() = Full::new(Bytes::from("nothing here"))
+            .map_err(|never| -> Box<dyn std::error::Error + Send + Sync> { match never {} })
+            .boxed();
+    });
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/private", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(resp.headers()["www-authenticate"], "Bearer realm=\"api\"");
+
+    // 路由器生成的 404 同样经过钩子
+    let resp = router.handle_http(make_http_request(Method::GET, "/missing", &[("host", "localhost")])).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "nothing here");
+
+    let resp = router.handle_http(make_http_request(Method::GET, "/ok", &[("host", "localhost")])).await.unwrap();
+    assert!(!resp.headers().contains_key("www-authenticate"));
+}
+
+#[tokio::test]
+async fn test_content_length_mismatch() {
+    use rat_engine::{Method, Response, Full, Bytes};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::POST, "/upload", |req| {
+        Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(req.body.len().to_string())))) })
+    });
+    router.set_request_body_timeout(Some(Duration::from_millis(200)));
+    let adapter = Arc::new(rat_engine::server::HyperAdapter::new(Arc::new(router)));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, remote_addr)) = listener.accept().await {
+            let adapter = adapter.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |req| {
+                    let adapter = adapter.clone();
+                    async move { adapter.handle_request(req, Some(remote_addr)).await }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+
+    async fn exchange(addr: SocketAddr, raw: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream.write_all(raw.as_bytes()).await.unwrap();
+        let mut response = Vec::new();
+        let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response)).await;
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    // 声明 10 字节只发送 3 字节：超时后返回 408 并关闭连接
+    let response = exchange(addr, "POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 10\r\n\r\nabc").await;
+    assert!(response.starts_with("HTTP/1.1 408"), "{}", response);
+    assert!(response.to_ascii_lowercase().contains("connection: close"), "{}", response);
+
+    // 声明 3 字节却发送更多：只有声明的 3 字节属于请求体，多出的数据按下一个请求解析失败，
+    // 不会作为第二个请求被处理
+    let response = exchange(addr, "POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 3\r\n\r\nabcXYZ\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.contains("\r\n\r\n3"), "{}", response);
+    assert_eq!(response.matches("HTTP/1.1 200").count(), 1, "{}", response);
+
+    // 超时按空闲时间计算：总耗时超过超时、但一直有数据到达的上传正常完成
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\nConnection: close\r\n\r\n").await.unwrap();
+    for byte in b"abcde" {
+        sleep(Duration::from_millis(100)).await;
+        stream.write_all(&[*byte]).await.unwrap();
+    }
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response)).await.unwrap().unwrap();
+    let response = String::from_utf8_lossy(&response);
+    assert!(response.starts_with("HTTP/1.1 200") && response.ends_with("\r\n\r\n5"), "{}", response);
+
+    // 分块请求体读到一半出错：返回 400 并关闭连接，处理器不会执行
+    let mut other = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let response = exchange(addr, "POST /upload HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\nzz\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+    assert!(response.to_ascii_lowercase().contains("connection: close"), "{}", response);
+
+    // 其他连接不受影响
+    other.write_all(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 4\r\n\r\nabcd").await.unwrap();
+    let mut response = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !String::from_utf8_lossy(&response).ends_with("\r\n\r\n4") {
+        let n = tokio::time::timeout(Duration::from_secs(5), other.read(&mut buf)).await.unwrap().unwrap();
+        assert!(n > 0, "{}", String::from_utf8_lossy(&response));
+        response.extend_from_slice(&buf[..n]);
+    }
+    assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 200"));
+}
+
+#[tokio::test]
+async fn test_work_stealing_path_end_to_end() {
+    use rat_engine::{Method, Response, Full, Bytes, RatEngine};
+    use rat_engine::engine::HttpPath;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 读取一个以 Content-Length 分帧的完整响应
+    async fn read_response(stream: &mut tokio::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let text = String::from_utf8_lossy(&data).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end].lines()
+                    .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+                if data.len() >= end + 4 + length {
+                    return text;
+                }
+            }
+            let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await.unwrap().unwrap();
+            assert!(n > 0, "连接在响应完整前关闭: {:?}", String::from_utf8_lossy(&data));
+            data.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/hello", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("hello")))) })
+    });
+    router.add_route(Method::POST, "/upload", |req| {
+        Box::pin(async move { Ok(Response::new(Full::new(Bytes::from(req.body.len().to_string())))) })
+    });
+    router.set_max_request_body_size(8);
+
+    let engine = Arc::new(RatEngine::builder()
+        .worker_threads(1)
+        .http_path(HttpPath::WorkStealing)
+        .connection_idle_timeout(Duration::from_millis(300))
+        .shutdown_timeout(Duration::from_secs(2))
+        .router(router)
+        .build()
+        .unwrap());
+    let server = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.start("127.0.0.1".to_string(), 0).await }
+    });
+    let addr = loop {
+        if let Some(addr) = engine.local_addr() {
+            break addr;
+        }
+        sleep(Duration::from_millis(10)).await;
+    };
+
+    // 空闲连接和只发送一半的请求都不会占住唯一的工作线程
+    let mut idle = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let mut partial = tokio::net::TcpStream::connect(addr).await.unwrap();
+    partial.write_all(b"GET /hello HTTP/1.1\r\nHost: x\r\n").await.unwrap();
+
+    // keep-alive：同一连接上间隔发送的两个请求都得到响应
+    let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+    client.write_all(b"GET /hello HTTP/1.1\r\nHost: x\r\n\r\n").await.unwrap();
+    let first = read_response(&mut client).await;
+    assert!(first.starts_with("HTTP/1.1 200") && first.ends_with("hello"), "{}", first);
+    sleep(Duration::from_millis(100)).await;
+    client.write_all(b"GET /hello HTTP/1.1\r\nHost: x\r\n\r\n").await.unwrap();
+    assert!(read_response(&mut client).await.ends_with("hello"));
+
+    // HTTP/1.0：状态行使用请求的版本，默认处理完后关闭连接
+    let mut http10 = tokio::net::TcpStream::connect(addr).await.unwrap();
+    http10.write_all(b"GET /hello HTTP/1.0\r\nHost: x\r\n\r\n").await.unwrap();
+    let mut raw = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), http10.read_to_end(&mut raw)).await.unwrap().unwrap();
+    let raw = String::from_utf8_lossy(&raw).to_string();
+    assert!(raw.starts_with("HTTP/1.0 200") && raw.to_lowercase().contains("connection: close"), "{}", raw);
+
+    // HTTP/1.0 显式要求保持连接
+    let mut http10 = tokio::net::TcpStream::connect(addr).await.unwrap();
+    http10.write_all(b"GET /hello HTTP/1.0\r\nConnection: keep-alive\r\n\r\n").await.unwrap();
+    let kept = read_response(&mut http10).await;
+    assert!(kept.starts_with("HTTP/1.0 200") && kept.to_lowercase().contains("connection: keep-alive"), "{}", kept);
+    http10.write_all(b"GET /hello HTTP/1.0\r\nConnection: keep-alive\r\n\r\n").await.unwrap();
+    assert!(read_response(&mut http10).await.ends_with("hello"));
+
+    // 路由器的请求体上限同样生效
+    client.write_all(b"POST /upload HTTP/1.1\r\nHost: x\r\nContent-Length: 16\r\n\r\n0123456789abcdef").await.unwrap();
+    assert!(read_response(&mut client).await.starts_with("HTTP/1.1 413"));
+
+    // 声明的长度未超限时，按实际读取的请求体再检查一次
+    let mut smuggled = tokio::net::TcpStream::connect(addr).await.unwrap();
+    smuggled.write_all(b"POST /upload HTTP/1.1\r\nHost: x\r\nContent-Length: 16\r\nContent-Length: 1\r\n\r\n0123456789abcdef").await.unwrap();
+    assert!(read_response(&mut smuggled).await.starts_with("HTTP/1.1 413"));
+
+    // 超过空闲超时：空闲连接被关闭，不完整的请求收到 408
+    let mut rest = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), idle.read_to_end(&mut rest)).await.unwrap().unwrap();
+    assert!(rest.is_empty());
+    let timed_out = read_response(&mut partial).await;
+    assert!(timed_out.starts_with("HTTP/1.1 408"), "{}", timed_out);
+    assert!(timed_out.to_lowercase().contains("connection: close"));
+
+    // 关闭时保持中的连接被及时关闭，不等待 shutdown_timeout
+    let started = std::time::Instant::now();
+    engine.shutdown().await.unwrap();
+    assert!(started.elapsed() < Duration::from_secs(1), "关闭耗时 {:?}", started.elapsed());
+    server.await.unwrap().unwrap();
+    let mut rest = Vec::new();
+    tokio::time::timeout(Duration::from_secs(1), client.read_to_end(&mut rest)).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_engine_keeps_router_request_timeout() {
+    use rat_engine::{Method, Response, Full, Bytes, RatEngine};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/slow", |_req| {
+        Box::pin(async {
+            sleep(Duration::from_millis(500)).await;
+            Ok(Response::new(Full::new(Bytes::from("late"))))
+        })
+    });
+    router.set_request_timeout(Some(Duration::from_millis(50)));
+
+    // 构建器没有显式设置 request_timeout，路由器上的期限保持不变
+    let engine = Arc::new(RatEngine::builder().router(router).build().unwrap());
+    let server = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.start("127.0.0.1".to_string(), 0).await }
+    });
+    let addr = loop {
+        if let Some(addr) = engine.local_addr() {
+            break addr;
+        }
+        sleep(Duration::from_millis(10)).await;
+    };
+
+    let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+    client.write_all(b"GET /slow HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n").await.unwrap();
+    let mut raw = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), client.read_to_end(&mut raw)).await.unwrap().unwrap();
+    let raw = String::from_utf8_lossy(&raw);
+    assert!(raw.starts_with("HTTP/1.1 504"), "{}", raw);
+
+    engine.shutdown().await.unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_keep_alive_and_sse_connections() {
+    use rat_engine::{Method, Response, Full, Bytes, RatEngine};
+    use rat_engine::server::streaming::SseResponse;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/hello", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("hello")))) })
+    });
+    router.add_streaming_route(Method::GET, "/events", |_req, _params| {
+        Box::pin(async {
+            let sse = SseResponse::new();
+            sse.send_data("ready").unwrap();
+            // 发送端一直存活，流本身不会结束
+            let sender = sse.get_sender();
+            tokio::spawn(async move {
+                sleep(Duration::from_secs(60)).await;
+                drop(sender);
+            });
+            sse.build()
+        })
+    });
+
+    let engine = Arc::new(RatEngine::builder()
+        .shutdown_timeout(Duration::from_secs(10))
+        .router(router)
+        .build()
+        .unwrap());
+    let server = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.start("127.0.0.1".to_string(), 0).await }
+    });
+    let addr = loop {
+        if let Some(addr) = engine.local_addr() {
+            break addr;
+        }
+        sleep(Duration::from_millis(10)).await;
+    };
+
+    // 完成一个请求后保持空闲的 keep-alive 连接
+    let mut keep_alive = tokio::net::TcpStream::connect(addr).await.unwrap();
+    keep_alive.write_all(b"GET /hello HTTP/1.1\r\nHost: x\r\n\r\n").await.unwrap();
+    let mut buf = [0u8; 1024];
+    let n = keep_alive.read(&mut buf).await.unwrap();
+    assert!(String::from_utf8_lossy(&buf[..n]).ends_with("hello"));
+
+    // 正在推送的 SSE 连接
+    let mut events = tokio::net::TcpStream::connect(addr).await.unwrap();
+    events.write_all(b"GET /events HTTP/1.1\r\nHost: x\r\n\r\n").await.unwrap();
+    let n = events.read(&mut buf).await.unwrap();
+    assert!(String::from_utf8_lossy(&buf[..n]).contains("text/event-stream"));
+
+    let started = std::time::Instant::now();
+    engine.shutdown().await.unwrap();
+    assert!(started.elapsed() < Duration::from_secs(2), "关闭耗时 {:?}", started.elapsed());
+    server.await.unwrap().unwrap();
+
+    // 两个连接都被服务端关闭
+    let mut rest = Vec::new();
+    tokio::time::timeout(Duration::from_secs(1), keep_alive.read_to_end(&mut rest)).await.unwrap().unwrap();
+    tokio::time::timeout(Duration::from_secs(1), events.read_to_end(&mut rest)).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_protocol_policy_sees_classified_protocol() {
+    use rat_engine::{Method, Response, Full, Bytes, RatEngine, StatusCode};
+    use rat_engine::server::ProtocolType;
+    use rat_engine::server::protocol_policy::{BlockDecision, ProtocolBlockResponse, ProtocolPolicy, default_protocol_policy};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn exchange(addr: std::net::SocketAddr, data: &[u8]) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream.write_all(data).await.unwrap();
+        let mut raw = Vec::new();
+        let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut raw)).await.unwrap();
+        String::from_utf8_lossy(&raw).to_string()
+    }
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("ok")))) })
+    });
+
+    let policy: ProtocolPolicy = Arc::new(|protocol, confidence| match protocol {
+        ProtocolType::HTTP1_0 => BlockDecision::Block,
+        // 放行没有处理器的协议，连接仍在分派时被拒绝
+        ProtocolType::Redis => BlockDecision::Allow,
+        other => default_protocol_policy(other, confidence),
+    });
+    let engine = Arc::new(RatEngine::builder()
+        .protocol_policy(policy)
+        .protocol_block_response(Some(ProtocolBlockResponse::new(StatusCode::FORBIDDEN).with_body("blocked")))
+        .router(router)
+        .build()
+        .unwrap());
+    let server = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.start("127.0.0.1".to_string(), 0).await }
+    });
+    let addr = loop {
+        if let Some(addr) = engine.local_addr() {
+            break addr;
+        }
+        sleep(Duration::from_millis(10)).await;
+    };
+
+    let ok = exchange(addr, b"GET / HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n").await;
+    assert!(ok.starts_with("HTTP/1.1 200"), "{}", ok);
+
+    let http10 = exchange(addr, b"GET / HTTP/1.0\r\nHost: x\r\n\r\n").await;
+    assert!(http10.starts_with("HTTP/1.1 403") && http10.ends_with("blocked"), "{}", http10);
+
+    let ssh = exchange(addr, b"SSH-2.0-OpenSSH_9.6\r\n").await;
+    assert!(ssh.starts_with("HTTP/1.1 403"), "{}", ssh);
+
+    let mqtt = exchange(addr, b"\x10\x10\x00\x04MQTT\x04\x02\x00\x3c\x00\x04test").await;
+    assert!(mqtt.starts_with("HTTP/1.1 403"), "{}", mqtt);
+
+    let redis = exchange(addr, b"*1\r\n$4\r\nPING\r\n").await;
+    assert!(redis.is_empty(), "{}", redis);
+
+    engine.shutdown().await.unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_detection_header_reports_routed_protocol() {
+    use rat_engine::{Method, Response, Full, Bytes, RatEngine};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut router = Router::new();
+    router.add_route(Method::GET, "/", |_req| {
+        Box::pin(async { Ok(Response::new(Full::new(Bytes::from("ok")))) })
+    });
+
+    let engine = Arc::new(RatEngine::builder()
+        .expose_detection_debug(true)
+        .router(router)
+        .build()
+        .unwrap());
+    let server = tokio::spawn({
+        let engine = engine.clone();
+        async move { engine.start("127.0.0.1".to_string(), 0).await }
+    });
+    let addr = loop {
+        if let Some(addr) = engine.local_addr() {
+            break addr;
+        }
+        sleep(Duration::from_millis(10)).await;
+    };
+
+    // 判定为 HTTP/1.0，但由 HTTP/1.1 处理器处理
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"GET / HTTP/1.0\r\nHost: x\r\n\r\n").await.unwrap();
+    let mut raw = Vec::new();
+    let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut raw)).await.unwrap();
+    let raw = String::from_utf8_lossy(&raw).to_ascii_lowercase();
+    assert!(raw.contains("x-detected-protocol: http1_1; confidence="), "{}", raw);
+
+    engine.shutdown().await.unwrap();
+    server.await.unwrap().unwrap();
+}
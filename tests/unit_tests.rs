@@ -56,6 +56,74 @@ mod router_tests {
     }
 }
 
+#[cfg(test)]
+mod builder_error_tests {
+    use rat_engine::{BuilderError, RatEngine};
+
+    #[test]
+    fn test_build_without_router_is_typed() {
+        let err = RatEngine::builder().build().err().unwrap();
+        assert!(matches!(err, BuilderError::MissingRouter));
+        assert!(std::error::Error::source(&err).is_none());
+        assert!(!err.to_string().is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_alpn_is_rejected() {
+        let err = RatEngine::builder()
+            .router(rat_engine::server::Router::new())
+            .alpn_protocols(vec!["h3".to_string()])
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, BuilderError::InvalidAlpn(ref msg) if msg.contains("h3")));
+    }
+
+    #[test]
+    fn test_work_stealing_path_requires_plain_http() {
+        use rat_engine::engine::HttpPath;
+
+        let engine = RatEngine::builder()
+            .router(rat_engine::server::Router::new())
+            .http_path(HttpPath::WorkStealing)
+            .build();
+        assert!(engine.is_ok());
+
+        let err = RatEngine::builder()
+            .router(rat_engine::server::Router::new())
+            .http_path(HttpPath::WorkStealing)
+            .grpc_port(50051)
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, BuilderError::UnsupportedHttpPath(_)));
+    }
+
+    #[test]
+    fn test_separated_grpc_port_is_validated() {
+        use rat_engine::server::PortConfigError;
+
+        // 默认 HTTP 端口为 8080，gRPC 不能使用同一端口
+        let err = RatEngine::builder()
+            .router(rat_engine::server::Router::new())
+            .grpc_port(8080)
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, BuilderError::InvalidPortConfig(PortConfigError::PortConflict(8080))));
+
+        // 分端口模式下 gRPC 必须配置证书
+        let err = RatEngine::builder()
+            .router(rat_engine::server::Router::new())
+            .grpc_port(50051)
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, BuilderError::InvalidPortConfig(PortConfigError::IncompatibleConfig(_))));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
+
 #[cfg(test)]
 mod worker_pool_tests {
     use super::*;
@@ -86,4 +154,617 @@ mod worker_pool_tests {
         
         assert_eq!(counter.load(Ordering::SeqCst), 5);
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod sse_limits_tests {
+    use rat_engine::server::streaming::{SseLimits, SseOversizePolicy};
+
+    #[test]
+    fn test_long_data_lines_are_not_split() {
+        // 拆成多个 data 行会在客户端拼出额外的换行，超长行只能拒绝或截断
+        let reject = SseLimits::default().with_max_line_size(4);
+        assert!(reject.format_message(Some("update"), "abcdefghij").is_err());
+        assert_eq!(reject.format_message(Some("update"), "abcd\nefgh").unwrap(), "event: update\ndata: abcd\ndata: efgh\n");
+
+        let truncate = SseLimits::default()
+            .with_max_message_size(usize::MAX, SseOversizePolicy::Truncate)
+            .with_max_line_size(4);
+        assert_eq!(truncate.format_message(None, "abcdefghij\nok").unwrap(), "data: abcd\ndata: ok\n");
+    }
+
+    #[test]
+    fn test_multiline_data_and_utf8_boundary() {
+        let limits = SseLimits::default()
+            .with_max_message_size(usize::MAX, SseOversizePolicy::Truncate)
+            .with_max_line_size(4);
+        let formatted = limits.format_message(None, "你好\r\nok\rx").unwrap();
+        assert_eq!(formatted, "data: 你\ndata: ok\ndata: x\n");
+    }
+
+    #[test]
+    fn test_event_name_with_newline_rejected() {
+        let limits = SseLimits::default();
+        assert!(limits.format_message(Some("update\ndata: injected"), "x").is_err());
+        assert!(limits.format_message(Some("update\r"), "x").is_err());
+    }
+
+    #[test]
+    fn test_absolute_cap() {
+        let reject = SseLimits::default().with_max_message_size(3, SseOversizePolicy::Reject);
+        assert!(reject.format_message(None, "abcd").is_err());
+
+        let truncate = SseLimits::default().with_max_message_size(3, SseOversizePolicy::Truncate);
+        assert_eq!(truncate.format_message(None, "abcd").unwrap(), "data: abc\n");
+    }
+}
+
+#[cfg(test)]
+mod deferred_body_tests {
+    use rat_engine::server::streaming::StreamingResponse;
+    use rat_engine::{Bytes, BodyExt};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn test_body_computed_on_first_poll() {
+        let computed = Arc::new(AtomicBool::new(false));
+        let flag = computed.clone();
+        let response = StreamingResponse::new()
+            .deferred(move || async move {
+                flag.store(true, Ordering::SeqCst);
+                Ok(Bytes::from("expensive"))
+            })
+            .build()
+            .unwrap();
+        assert!(!computed.load(Ordering::SeqCst));
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(computed.load(Ordering::SeqCst));
+        assert_eq!(&body[..], b"expensive");
+    }
+
+    #[test]
+    fn test_dropped_body_never_computes() {
+        let computed = Arc::new(AtomicBool::new(false));
+        let flag = computed.clone();
+        let response = StreamingResponse::new()
+            .deferred(move || async move {
+                flag.store(true, Ordering::SeqCst);
+                Ok(Bytes::new())
+            })
+            .build()
+            .unwrap();
+        drop(response);
+        assert!(!computed.load(Ordering::SeqCst));
+    }
+}
+
+#[cfg(test)]
+mod stream_channel_tests {
+    use rat_engine::server::streaming::ChunkedResponse;
+    use rat_engine::BodyExt;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_send_waits_when_buffer_is_full() {
+        let (response, sender) = ChunkedResponse::channel(2);
+        let response = response.build().unwrap();
+
+        sender.send("a").await.unwrap();
+        sender.send("b").await.unwrap();
+        assert_eq!(sender.capacity(), 0);
+        assert!(sender.try_send("c").is_err());
+
+        // 缓冲区满时发送会等待
+        let blocked = tokio::time::timeout(Duration::from_millis(50), sender.send("c")).await;
+        assert!(blocked.is_err());
+
+        // 客户端读取后生产者继续
+        let producer = tokio::spawn({
+            let sender = sender.clone();
+            async move {
+                for chunk in ["c", "d", "e"] {
+                    sender.send(chunk).await.unwrap();
+                }
+            }
+        });
+        drop(sender);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        producer.await.unwrap();
+        assert_eq!(&body[..], b"abcde");
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_after_client_disconnects() {
+        let (response, sender) = ChunkedResponse::channel(1);
+        drop(response);
+        assert!(sender.is_closed());
+        assert!(sender.send("late").await.is_err());
+    }
+}
+
+#[cfg(test)]
+mod binary_stream_tests {
+    use rat_engine::server::streaming::BinaryStreamResponse;
+    use rat_engine::BodyExt;
+
+    #[tokio::test]
+    async fn test_raw_frames_are_sent_unchanged() {
+        let response = BinaryStreamResponse::new("application/x-protobuf");
+        let sender = response.get_sender();
+        let response = response.build().unwrap();
+        assert_eq!(response.headers()["content-type"], "application/x-protobuf");
+
+        sender.send_frame(vec![0u8, 1, 2]).unwrap();
+        sender.send_frame(&b"\xff\n"[..]).unwrap();
+        drop(sender);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], &[0u8, 1, 2, 0xff, b'\n']);
+    }
+
+    #[tokio::test]
+    async fn test_mjpeg_frames_use_multipart_parts() {
+        let response = BinaryStreamResponse::mjpeg();
+        let sender = response.get_sender();
+        let response = response.build().unwrap();
+        assert_eq!(
+            response.headers()["content-type"],
+            "multipart/x-mixed-replace; boundary=rat-frame"
+        );
+
+        sender.send_frame(&b"JPEG"[..]).unwrap();
+        drop(sender);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            &body[..],
+            &b"--rat-frame\r\nContent-Type: image/jpeg\r\nContent-Length: 4\r\n\r\nJPEG\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn test_sender_reports_closed_after_body_dropped() {
+        let response = BinaryStreamResponse::new("application/octet-stream");
+        let sender = response.get_sender();
+        drop(response);
+        assert!(sender.is_closed());
+        assert!(sender.send_frame(vec![1u8]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod body_parse_error_tests {
+    use rat_engine::server::http_request::{HttpRequest, RequestSource};
+    use rat_engine::{Bytes, Method, RatError};
+
+    fn request_with_body(body: &'static [u8]) -> HttpRequest {
+        HttpRequest {
+            method: Method::POST,
+            uri: "/".parse().unwrap(),
+            version: rat_engine::Version::HTTP_11,
+            headers: rat_engine::HeaderMap::new(),
+            body: Bytes::from_static(body),
+            remote_addr: None,
+            source: RequestSource::Http1,
+            path_params: std::collections::HashMap::new(),
+            python_handler_name: None,
+            tls_info: None,
+            state: Default::default(),
+            real_ip: None,
+            client_disconnect: Default::default(),
+            trailers: None,
+        }
+    }
+
+    #[test]
+    fn test_json_error_is_localized_with_position() {
+        rat_engine::error_i18n::init_error_translations();
+        rat_embed_lang::set_language("en-US");
+
+        let err = request_with_body(b"{\n  \"name\": }").body_json().unwrap_err();
+        match err {
+            RatError::DeserializationError(msg) => {
+                assert!(msg.starts_with("JSON parse failed at line 2 column"), "{}", msg);
+                assert!(msg.ends_with(": expected value"), "{}", msg);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_typed_body_and_utf8_errors() {
+        #[derive(serde::Deserialize)]
+        struct Payload {
+            name: String,
+        }
+
+        let payload: Payload = request_with_body(br#"{"name":"rat"}"#).body_as().unwrap();
+        assert_eq!(payload.name, "rat");
+        assert!(matches!(request_with_body(b"\xff").body_text(), Err(RatError::ValidationError(_))));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_accessors_keep_original_errors() {
+        let _: serde_json::Error = request_with_body(b"{").body_as_json().unwrap_err();
+        let _: std::string::FromUtf8Error = request_with_body(b"\xff").body_as_string().unwrap_err();
+        assert_eq!(request_with_body(b"rat").body_as_string().unwrap(), "rat");
+    }
+}
+
+#[cfg(test)]
+mod sse_reaper_tests {
+    use rat_engine::server::global_sse_manager::GlobalSseManager;
+    use std::time::Duration;
+
+    #[test]
+    fn test_register_with_custom_status_and_headers() {
+        use rat_engine::server::global_sse_manager::SseRegisterOptions;
+
+        let manager = GlobalSseManager::new();
+        let options = SseRegisterOptions::default()
+            .with_status(rat_engine::StatusCode::ACCEPTED)
+            .disable_proxy_buffering()
+            .with_header("Cache-Control", "no-cache, no-transform");
+        let response = manager.register_connection_with_options("custom".to_string(), options).unwrap();
+
+        assert_eq!(response.status(), rat_engine::StatusCode::ACCEPTED);
+        assert_eq!(response.headers()["x-accel-buffering"], "no");
+        assert_eq!(response.headers()["cache-control"], "no-cache, no-transform");
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+    }
+
+    #[test]
+    fn test_reap_idle_connections() {
+        let manager = GlobalSseManager::new();
+        let _response = manager.register_connection("idle".to_string()).unwrap();
+        assert!(manager.idle_duration("idle").is_some());
+
+        assert_eq!(manager.reap_idle_connections(Duration::from_secs(60)), 0);
+        assert!(manager.has_connection("idle"));
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(manager.reap_idle_connections(Duration::from_millis(5)), 1);
+        assert!(!manager.has_connection("idle"));
+        assert!(manager.idle_duration("idle").is_none());
+    }
+
+    #[test]
+    fn test_connection_limits() {
+        use rat_engine::server::global_sse_manager::{SseConnectionLimits, SseRegisterError, SseRegisterOptions};
+
+        let manager = GlobalSseManager::new();
+        manager.set_connection_limits(SseConnectionLimits::default().with_max_connections(3).with_max_connections_per_ip(2));
+        let ip: std::net::IpAddr = "10.0.0.1".parse().unwrap();
+        let from_ip = || SseRegisterOptions::default().with_client_ip(ip);
+
+        // 持有响应体，接收端被丢弃的连接会在名额不足时被回收
+        let _a = manager.register_connection_with_options("a".to_string(), from_ip()).unwrap();
+        let _b = manager.register_connection_with_options("b".to_string(), from_ip()).unwrap();
+        assert_eq!(manager.connection_count_for_ip(&ip), 2);
+
+        // 单 IP 超限
+        let err = manager.try_register_connection_with_options("c".to_string(), from_ip()).unwrap_err();
+        assert!(matches!(err, SseRegisterError::TooManyConnectionsFromIp { limit: 2, .. }));
+        let response = manager.register_connection_with_options("c".to_string(), from_ip()).unwrap();
+        assert_eq!(response.status(), rat_engine::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()["retry-after"], "5");
+        assert!(!manager.has_connection("c"));
+
+        // 全局超限
+        let d = manager.register_connection("d".to_string()).unwrap();
+        let err = manager.try_register_connection_with_options("e".to_string(), SseRegisterOptions::default()).unwrap_err();
+        assert!(matches!(err, SseRegisterError::TooManyConnections { limit: 3 }));
+
+        // 使用统一配置的过载拒绝响应
+        manager.set_load_shed_response(rat_engine::server::LoadShedResponse::too_many_requests().with_retry_after(None));
+        let response = manager.register_connection("e".to_string()).unwrap();
+        assert_eq!(response.status(), rat_engine::StatusCode::TOO_MANY_REQUESTS);
+        assert!(!response.headers().contains_key("retry-after"));
+
+        // 断开后释放名额
+        assert!(manager.disconnect_connection("a"));
+        assert_eq!(manager.connection_count_for_ip(&ip), 1);
+        let _c = manager.register_connection_with_options("c".to_string(), from_ip()).unwrap();
+        assert!(manager.has_connection("c"));
+
+        // 接收端已被丢弃的连接不再占用名额
+        drop(d);
+        let _e = manager.try_register_connection_with_options("e".to_string(), SseRegisterOptions::default()).unwrap();
+        assert!(!manager.has_connection("d"));
+        assert!(manager.has_connection("e"));
+    }
+}
+
+#[cfg(test)]
+mod grpc_context_tests {
+    use rat_engine::server::grpc_types::{GrpcContext, GrpcMethodDescriptor, GrpcMethodType};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(GrpcContext::parse_grpc_timeout("5S"), Some(Duration::from_secs(5)));
+        assert_eq!(GrpcContext::parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(GrpcContext::parse_grpc_timeout("2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(GrpcContext::parse_grpc_timeout("S"), None);
+        assert_eq!(GrpcContext::parse_grpc_timeout("123456789S"), None);
+        assert_eq!(GrpcContext::parse_grpc_timeout("10x"), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_headers_derived_from_metadata() {
+        let mut metadata = hyper::HeaderMap::new();
+        metadata.insert("authorization", "Bearer t".parse().unwrap());
+        metadata.append("x-tag", "a".parse().unwrap());
+        metadata.append("x-tag", "b".parse().unwrap());
+        let context = GrpcContext {
+            remote_addr: None,
+            method: GrpcMethodDescriptor::new("test.Auth", "Check", GrpcMethodType::Unary),
+            metadata,
+            timeout: None,
+            deadline: None,
+            response_metadata: Default::default(),
+        };
+
+        assert_eq!(context.metadata().get_all("x-tag").iter().count(), 2);
+        assert_eq!(context.metadata_value("authorization"), Some("Bearer t"));
+        let headers = context.headers();
+        assert_eq!(headers.get("authorization").map(String::as_str), Some("Bearer t"));
+        assert_eq!(headers.get("x-tag").map(String::as_str), Some("b"));
+    }
+}
+
+#[cfg(test)]
+mod grpc_typed_server_stream_tests {
+    use std::collections::HashMap;
+    use futures_util::StreamExt;
+    use rat_engine::server::grpc_codec::GrpcCodec;
+    use rat_engine::server::grpc_handler::GrpcServiceRegistry;
+    use rat_engine::server::grpc_types::*;
+
+    #[tokio::test]
+    async fn test_add_server_stream_typed() {
+        let mut registry = GrpcServiceRegistry::new();
+        registry.add_server_stream_typed("/test.Counter/Count", |count: u32, _ctx: GrpcContext| async move {
+            if count == 0 {
+                return Err(GrpcError::InvalidArgument("count 不能为 0".to_string()));
+            }
+            Ok(futures_util::stream::iter((1..=count).map(Ok::<u32, GrpcError>)))
+        });
+
+        let handler = registry.get_server_stream_handler("/test.Counter/Count").unwrap();
+        let context = GrpcContext {
+            remote_addr: None,
+            method: GrpcMethodDescriptor::new("test.Counter", "Count", GrpcMethodType::ServerStreaming),
+            metadata: hyper::HeaderMap::new(),
+            timeout: None,
+            deadline: None,
+            response_metadata: Default::default(),
+        };
+        let request = GrpcRequest {
+            id: 7,
+            method: "/test.Counter/Count".to_string(),
+            data: GrpcCodec::encode(&3u32).unwrap(),
+            metadata: HashMap::new(),
+        };
+
+        let messages: Vec<_> = handler.handle(request.clone(), context.clone()).await.unwrap().collect().await;
+        let values: Vec<u32> = messages.iter()
+            .map(|m| GrpcCodec::decode(&m.as_ref().unwrap().data).unwrap())
+            .collect();
+        assert_eq!(values, vec![1, 2, 3]);
+        assert_eq!(messages[2].as_ref().unwrap().sequence, 2);
+
+        let zero = GrpcRequest { data: GrpcCodec::encode(&0u32).unwrap(), ..request };
+        assert!(matches!(handler.handle(zero, context).await, Err(GrpcError::InvalidArgument(_))));
+    }
+}
+
+#[cfg(test)]
+mod adaptive_compression_tests {
+    use rat_engine::compression::CompressionConfig;
+
+    #[test]
+    fn test_level_for_load() {
+        let config = CompressionConfig::new().level(6);
+        assert_eq!(config.level_for_load(10_000), Some(6));
+
+        let config = config.adaptive(100);
+        assert_eq!(config.level_for_load(99), Some(6));
+        assert_eq!(config.level_for_load(100), Some(1));
+        assert_eq!(config.level_for_load(199), Some(1));
+        assert_eq!(config.level_for_load(200), None);
+    }
+
+    #[test]
+    fn test_brotli_settings_per_content_class() {
+        use rat_engine::compression::BrotliSettings;
+        use rat_engine::compression::config::DEFAULT_BROTLI_WINDOW;
+
+        let config = CompressionConfig::new().level(5);
+        assert_eq!(config.brotli_settings(true, 5), BrotliSettings::new(5, DEFAULT_BROTLI_WINDOW));
+
+        let config = config.brotli_static(11, 24).brotli_dynamic(4, 18).adaptive(100);
+        assert_eq!(config.brotli_settings(true, 5), BrotliSettings::new(11, 24));
+        assert_eq!(config.brotli_settings(false, 5), BrotliSettings::new(4, 18));
+        // 自适应降级时质量跟随降级后的级别
+        assert_eq!(config.brotli_settings(true, 1), BrotliSettings::new(1, 24));
+        // 超出范围的参数被截断
+        assert_eq!(BrotliSettings::new(20, 30), BrotliSettings::new(11, 24));
+    }
+
+    #[test]
+    fn test_cacheable_response_detection() {
+        use rat_engine::HeaderMap;
+
+        let cacheable = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("cache-control", value.parse().unwrap());
+            CompressionConfig::is_cacheable_response(&headers)
+        };
+        assert!(cacheable("public, max-age=3600"));
+        assert!(cacheable("max-age=31536000, immutable"));
+        assert!(!cacheable("max-age=0"));
+        assert!(!cacheable("public, no-store"));
+        assert!(!cacheable("private, max-age=60"));
+        assert!(!CompressionConfig::is_cacheable_response(&HeaderMap::new()));
+    }
+}
+
+#[cfg(all(test, feature = "cache"))]
+mod cache_invalidation_tests {
+    use rat_engine::server::cache_middleware::CacheMiddleware;
+
+    #[test]
+    fn test_cache_key_matches() {
+        assert!(CacheMiddleware::key_matches("GET/users/1", "/users/1"));
+        assert!(CacheMiddleware::key_matches("GET/users/1?page=2", "/users/1"));
+        assert!(CacheMiddleware::key_matches("GET/users/1:gzip", "/users/1"));
+        assert!(CacheMiddleware::key_matches("GET/users/2", "/users/*"));
+        assert!(!CacheMiddleware::key_matches("GET/users/10", "/users/1"));
+        assert!(!CacheMiddleware::key_matches("GET/posts/1", "/users/*"));
+    }
+}
+
+#[cfg(all(test, feature = "cache"))]
+mod cache_status_tests {
+    use rat_engine::server::cache_middleware_impl::CacheStatus;
+    use rat_engine::engine::metrics::AtomicMetrics;
+
+    #[test]
+    fn test_cache_status_header_round_trip() {
+        for status in [CacheStatus::Hit, CacheStatus::StaleHit, CacheStatus::Miss] {
+            let mut headers = hyper::HeaderMap::new();
+            headers.insert("x-cache", status.header_value().parse().unwrap());
+            assert_eq!(CacheStatus::from_headers(&headers), Some(status));
+        }
+        assert_eq!(CacheStatus::StaleHit.header_value(), "HIT; stale");
+        assert_eq!(CacheStatus::from_headers(&hyper::HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_cache_counters() {
+        let metrics = AtomicMetrics::new();
+        metrics.record_cache_hit(false);
+        metrics.record_cache_hit(true);
+        metrics.record_cache_miss();
+        metrics.record_cache_miss();
+        assert_eq!(metrics.cache_stats(), (1, 1, 2));
+        assert_eq!(metrics.get_all()["cache_misses"], 2);
+    }
+}
+
+#[cfg(all(test, feature = "http-client"))]
+mod http_client_tests {
+    use rat_engine::client::{HttpStatusCode, RatHttpClientBuilder, RatHttpResponse};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_response_helpers() {
+        let mut headers = HashMap::new();
+        headers.insert("content-type".to_string(), "application/json".to_string());
+        let response = RatHttpResponse {
+            status: HttpStatusCode::OK,
+            headers,
+            body: r#"{"ok":true}"#.into(),
+        };
+
+        assert!(response.is_success());
+        assert_eq!(response.header("Content-Type"), Some("application/json"));
+        let value: serde_json::Value = response.json().unwrap();
+        assert_eq!(value["ok"], true);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unsupported_scheme() {
+        let client = RatHttpClientBuilder::new().build().unwrap();
+        assert!(client.get("ftp://example.com/file").await.is_err());
+    }
+
+    /// 启动一个按 Accept-Encoding 返回 gzip 响应的 keep-alive 服务器，返回地址和已接受的连接数
+    #[cfg(feature = "compression")]
+    async fn spawn_gzip_server(payload: &'static str) -> (std::net::SocketAddr, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use rat_engine::compression::{CompressionType, Compressor};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut data = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    loop {
+                        let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") else {
+                            match stream.read(&mut buf).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => data.extend_from_slice(&buf[..n]),
+                            }
+                            continue;
+                        };
+                        let head = String::from_utf8_lossy(&data[..end]).to_lowercase();
+                        data.drain(..end + 4);
+
+                        let (encoding, body) = if head.contains("accept-encoding:") && head.contains("gzip") {
+                            ("content-encoding: gzip\r\n", Compressor::default().compress(payload.as_bytes(), CompressionType::Gzip).unwrap())
+                        } else {
+                            ("", payload.as_bytes().to_vec())
+                        };
+                        let header = format!("HTTP/1.1 200 OK\r\n{}content-length: {}\r\n\r\n", encoding, body.len());
+                        if stream.write_all(header.as_bytes()).await.is_err() || stream.write_all(&body).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        (addr, accepted)
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_compressed_round_trip_shares_pool() {
+        use std::sync::atomic::Ordering;
+
+        let payload = "compressed payload ".repeat(64);
+        let payload: &'static str = Box::leak(payload.into_boxed_str());
+        let (addr, accepted) = spawn_gzip_server(payload).await;
+        let url = format!("http://{}/data", addr);
+
+        let client = RatHttpClientBuilder::new().build().unwrap();
+        let compressed = client.with_compression(true);
+
+        // 压缩响应被解压，编码相关头部被移除
+        let response = compressed.get(&url).await.unwrap();
+        assert_eq!(response.text().unwrap(), payload);
+        assert!(response.header("content-encoding").is_none());
+
+        // 未启用压缩的客户端收到原始响应，并复用同一条连接
+        let response = client.get(&url).await.unwrap();
+        assert_eq!(response.text().unwrap(), payload);
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_decompressed_size_is_bounded() {
+        let payload = "a".repeat(4096);
+        let payload: &'static str = Box::leak(payload.into_boxed_str());
+        let (addr, _) = spawn_gzip_server(payload).await;
+
+        // 压缩后的响应体远小于限制，解压后超过限制
+        let client = RatHttpClientBuilder::new()
+            .max_response_size(1024)
+            .enable_compression()
+            .build()
+            .unwrap();
+        assert!(client.get(&format!("http://{}/data", addr)).await.is_err());
+    }
+}